@@ -0,0 +1,206 @@
+use std::fmt::Write as _;
+
+use proc_macro2::Literal;
+
+use crate::{Error, Grammar, TokenDef};
+
+impl Grammar {
+    /// The `synkit::parser_kit! { ... }` invocation for this grammar.
+    ///
+    /// Fails with [`Error::Invalid`] naming the field if a path, type,
+    /// identifier or callback doesn't parse as Rust, or a token doesn't have
+    /// exactly one of `token` and `regex`.
+    pub fn to_parser_kit(&self) -> Result<String, Error> {
+        if self.error.is_none() {
+            return Err(Error::invalid("error", "missing the error type"));
+        }
+        let out = format!("synkit::parser_kit! {{\n{}}}\n", self.to_fields()?);
+        syn::parse_file(&out).map_err(|err| Error::invalid("grammar", err))?;
+        Ok(out)
+    }
+
+    /// The fields of the `parser_kit!` invocation, one per line, for
+    /// splicing into an invocation that declares the rest.
+    pub fn to_fields(&self) -> Result<String, Error> {
+        let mut out = String::new();
+        for (i, arg) in self.logos.iter().enumerate() {
+            check::<proc_macro2::TokenStream>(&format!("logos[{i}]"), arg)?;
+            let _ = writeln!(out, "    #[logos({arg})]");
+        }
+        if let Some(error) = &self.error {
+            check::<syn::Path>("error", error)?;
+            let _ = writeln!(out, "    error: {error},");
+        }
+
+        if !self.skip_tokens.is_empty() {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            list(&mut out, "skip_tokens", &self.skip_tokens, check_ident)?;
+        }
+
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str("    tokens: {\n");
+        for (i, token) in self.tokens.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            write_token(&mut out, token)?;
+        }
+        out.push_str("    },\n");
+
+        out.push_str("\n    delimiters: {\n");
+        for delim in &self.delimiters {
+            let field = format!("delimiters.{}", delim.name);
+            check_ident(&field, &delim.name)?;
+            check_ident(&format!("{field}.open"), &delim.open)?;
+            check_ident(&format!("{field}.close"), &delim.close)?;
+            let _ = writeln!(
+                out,
+                "        {} => ({}, {}),",
+                delim.name, delim.open, delim.close
+            );
+        }
+        out.push_str("    },\n");
+
+        let derives = [
+            ("span_derives", &self.span_derives),
+            ("token_derives", &self.token_derives),
+            ("custom_derives", &self.custom_derives),
+        ];
+        if derives.iter().any(|(_, paths)| !paths.is_empty()) {
+            out.push('\n');
+        }
+        for (name, paths) in derives {
+            if !paths.is_empty() {
+                list(&mut out, name, paths, check::<syn::Path>)?;
+            }
+        }
+
+        let mut options = String::new();
+        if let Some(ty) = &self.string_type {
+            check::<syn::Type>("string_type", ty)?;
+            let _ = writeln!(options, "    string_type: {ty},");
+        }
+        for (name, value) in [
+            ("line_index", self.line_index),
+            ("normalize_newlines", self.normalize_newlines),
+            ("file_ids", self.file_ids),
+            ("packed_spans", self.packed_spans),
+            ("provenance", self.provenance),
+            ("container_impls", self.container_impls),
+            ("borrowed", self.borrowed),
+            ("delimiter_sugar", self.delimiter_sugar),
+            ("token_conversions", self.token_conversions),
+            ("token_tests", self.token_tests),
+            ("assertions", self.assertions),
+            ("peek_cache", self.peek_cache),
+        ] {
+            if let Some(value) = value {
+                let _ = writeln!(options, "    {name}: {value},");
+            }
+        }
+        if let Some(bytes) = self.bytes_per_token {
+            let _ = writeln!(options, "    bytes_per_token: {bytes},");
+        }
+        if !options.is_empty() {
+            out.push('\n');
+            out.push_str(&options);
+        }
+        Ok(out)
+    }
+}
+
+fn write_token(out: &mut String, token: &TokenDef) -> Result<(), Error> {
+    let field = format!("tokens.{}", token.name);
+    check_ident(&field, &token.name)?;
+
+    if let Some(doc) = &token.doc {
+        for line in doc.lines() {
+            let _ = writeln!(
+                out,
+                "        ///{}{line}",
+                if line.is_empty() { "" } else { " " }
+            );
+        }
+    }
+
+    let (kind, pattern) = match (&token.token, &token.regex) {
+        (Some(literal), None) => ("token", literal),
+        (None, Some(regex)) => ("regex", regex),
+        _ => {
+            return Err(Error::invalid(
+                field,
+                "needs exactly one of `token` and `regex`",
+            ));
+        }
+    };
+    let mut args = Literal::string(pattern).to_string();
+    if let Some(callback) = &token.callback {
+        check::<syn::Expr>(&format!("{field}.callback"), callback)?;
+        let _ = write!(args, ", {callback}");
+    }
+    if let Some(priority) = token.priority {
+        let _ = write!(args, ", priority = {priority}");
+    }
+    if let Some(until) = &token.until {
+        let _ = write!(args, ", until = {}", Literal::string(until));
+    }
+    if token.longest == Some(true) {
+        args.push_str(", longest");
+    }
+    if token.nested == Some(true) {
+        args.push_str(", nested");
+    }
+    let _ = writeln!(out, "        #[{kind}({args})]");
+
+    if let Some(fmt) = &token.fmt {
+        let _ = writeln!(out, "        #[fmt({})]", Literal::string(fmt));
+    }
+    if !token.derive.is_empty() {
+        for (i, path) in token.derive.iter().enumerate() {
+            check::<syn::Path>(&format!("{field}.derive[{i}]"), path)?;
+        }
+        let _ = writeln!(out, "        #[derive({})]", token.derive.join(", "));
+    }
+    for sample in &token.samples {
+        let _ = writeln!(out, "        #[sample({})]", Literal::string(sample));
+    }
+
+    match &token.value {
+        Some(ty) => {
+            check::<syn::Type>(&format!("{field}.value"), ty)?;
+            let _ = writeln!(out, "        {}({ty}),", token.name);
+        }
+        None => {
+            let _ = writeln!(out, "        {},", token.name);
+        }
+    }
+    Ok(())
+}
+
+/// Write `name: [a, b, c],`, checking each entry.
+fn list(
+    out: &mut String,
+    name: &str,
+    items: &[String],
+    check_item: fn(&str, &str) -> Result<(), Error>,
+) -> Result<(), Error> {
+    for (i, item) in items.iter().enumerate() {
+        check_item(&format!("{name}[{i}]"), item)?;
+    }
+    let _ = writeln!(out, "    {name}: [{}],", items.join(", "));
+    Ok(())
+}
+
+fn check<T: syn::parse::Parse>(field: &str, source: &str) -> Result<(), Error> {
+    syn::parse_str::<T>(source)
+        .map(drop)
+        .map_err(|err| Error::invalid(field, format_args!("{err} in `{source}`")))
+}
+
+fn check_ident(field: &str, source: &str) -> Result<(), Error> {
+    check::<syn::Ident>(field, source)
+}
@@ -0,0 +1,77 @@
+use std::fmt;
+use std::path::PathBuf;
+
+/// Why a grammar file couldn't be compiled.
+#[derive(Debug)]
+pub enum Error {
+    /// `OUT_DIR` is unset; [`compile`](crate::compile) only works in a
+    /// build script.
+    MissingOutDir,
+
+    /// Reading the grammar or writing the output failed.
+    Io {
+        /// The file being read or written.
+        path: PathBuf,
+        /// The underlying error.
+        source: std::io::Error,
+    },
+
+    /// The grammar file isn't valid TOML, or doesn't match the schema.
+    Toml(toml::de::Error),
+
+    /// The grammar file isn't valid RON, or doesn't match the schema.
+    #[cfg(feature = "ron")]
+    Ron(ron::error::SpannedError),
+
+    /// The grammar file is RON, but the `ron` feature is disabled.
+    RonDisabled,
+
+    /// A field holds something that isn't valid Rust where Rust is
+    /// expected, or the grammar is inconsistent.
+    Invalid {
+        /// Where in the grammar, e.g. `tokens.Ident.value`.
+        field: String,
+        /// What is wrong with it.
+        message: String,
+    },
+}
+
+impl Error {
+    pub(crate) fn invalid(field: impl Into<String>, message: impl fmt::Display) -> Self {
+        Error::Invalid {
+            field: field.into(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::MissingOutDir => write!(f, "OUT_DIR is not set; call from a build script"),
+            Error::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            Error::Toml(err) => write!(f, "invalid grammar: {}", err),
+            #[cfg(feature = "ron")]
+            Error::Ron(err) => write!(f, "invalid grammar: {}", err),
+            Error::RonDisabled => {
+                write!(
+                    f,
+                    "RON grammar files need the `ron` feature of synkit-build"
+                )
+            }
+            Error::Invalid { field, message } => write!(f, "`{}`: {}", field, message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Toml(err) => Some(err),
+            #[cfg(feature = "ron")]
+            Error::Ron(err) => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::Error;
+
+/// A `parser_kit!` invocation as data.
+///
+/// Fields holding Rust (paths, types, callbacks) are strings, checked when
+/// the invocation is emitted. Options left unset keep the macro's defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Grammar {
+    /// The error type, e.g. `crate::LexError`. Required unless the
+    /// invocation declares it, as with `from_file`.
+    pub error: Option<String>,
+    /// Tokens the stream skips, e.g. whitespace.
+    #[serde(default)]
+    pub skip_tokens: Vec<String>,
+    /// Arguments of `#[logos(...)]` attributes on the token enum, e.g.
+    /// `subpattern ident = r"[a-z]+"`.
+    #[serde(default)]
+    pub logos: Vec<String>,
+    /// Tokens, in priority order for equal-length matches.
+    #[serde(default)]
+    pub tokens: Vec<TokenDef>,
+    /// Delimiter pairs.
+    #[serde(default)]
+    pub delimiters: Vec<DelimiterDef>,
+    /// Derives for the generated span types.
+    #[serde(default)]
+    pub span_derives: Vec<String>,
+    /// Derives for the token enum and token structs.
+    #[serde(default)]
+    pub token_derives: Vec<String>,
+    /// Derives added to every generated type.
+    #[serde(default)]
+    pub custom_derives: Vec<String>,
+    /// Payload type replacing `String` in string tokens.
+    pub string_type: Option<String>,
+    /// The `parser_kit!` options of the same names.
+    pub line_index: Option<bool>,
+    pub normalize_newlines: Option<bool>,
+    pub file_ids: Option<bool>,
+    pub packed_spans: Option<bool>,
+    pub provenance: Option<bool>,
+    pub container_impls: Option<bool>,
+    pub borrowed: Option<bool>,
+    pub delimiter_sugar: Option<bool>,
+    pub token_conversions: Option<bool>,
+    pub token_tests: Option<bool>,
+    pub assertions: Option<bool>,
+    pub peek_cache: Option<bool>,
+    pub bytes_per_token: Option<u32>,
+}
+
+/// One token: exactly one of `token` (a literal) or `regex`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenDef {
+    /// Variant name, e.g. `Ident`; the token struct is `IdentToken`.
+    pub name: String,
+    /// Literal text, for `#[token("...")]`.
+    pub token: Option<String>,
+    /// Pattern, for `#[regex("...")]`.
+    pub regex: Option<String>,
+    /// Logos callback producing the payload, e.g. `|lex| lex.slice().to_string()`.
+    pub callback: Option<String>,
+    pub priority: Option<u32>,
+    /// Extend the match through the first occurrence of this text, e.g. `*/`.
+    pub until: Option<String>,
+    /// With `until`, extend through the last occurrence instead.
+    pub longest: Option<bool>,
+    /// With `until` and `token`, extend through the occurrence balancing
+    /// the literal, counting nested pairs.
+    pub nested: Option<bool>,
+    /// Payload type, e.g. `String`.
+    pub value: Option<String>,
+    /// Name used in error messages, e.g. `identifier`.
+    pub fmt: Option<String>,
+    /// Doc comment, one `///` line per line.
+    pub doc: Option<String>,
+    /// Extra derives for the token struct.
+    #[serde(default)]
+    pub derive: Vec<String>,
+    /// Example source text for `Generate`.
+    #[serde(default)]
+    pub samples: Vec<String>,
+}
+
+/// A delimiter pair, e.g. `Brace => (LBrace, RBrace)`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DelimiterDef {
+    /// Delimiter name, e.g. `Brace`.
+    pub name: String,
+    /// Name of the opening token.
+    pub open: String,
+    /// Name of the closing token.
+    pub close: String,
+}
+
+/// Grammar file syntax.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    Toml,
+    /// Requires the `ron` feature.
+    Ron,
+}
+
+impl Format {
+    /// `.ron` files are RON, `.synkit` files are RON if they start with
+    /// `(`, and everything else is TOML.
+    pub fn detect(path: &Path, source: &str) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("ron") => Format::Ron,
+            Some("synkit") if source.trim_start().starts_with('(') => Format::Ron,
+            _ => Format::Toml,
+        }
+    }
+}
+
+impl Grammar {
+    /// Read and parse the grammar at `path`, detecting its [`Format`].
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&source, Format::detect(path, &source))
+    }
+
+    /// Parse a grammar written in `format`.
+    pub fn parse(source: &str, format: Format) -> Result<Self, Error> {
+        match format {
+            Format::Toml => Self::from_toml(source),
+            Format::Ron => Self::from_ron(source),
+        }
+    }
+
+    /// Parse a TOML grammar.
+    pub fn from_toml(source: &str) -> Result<Self, Error> {
+        toml::from_str(source).map_err(Error::Toml)
+    }
+
+    /// Parse a RON grammar, with optional fields written without `Some`.
+    /// Fails with [`Error::RonDisabled`] without the `ron` feature.
+    pub fn from_ron(source: &str) -> Result<Self, Error> {
+        #[cfg(feature = "ron")]
+        {
+            ron::Options::default()
+                .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+                .from_str(source)
+                .map_err(Error::Ron)
+        }
+        #[cfg(not(feature = "ron"))]
+        {
+            let _ = source;
+            Err(Error::RonDisabled)
+        }
+    }
+}
@@ -0,0 +1,93 @@
+//! Generate synkit parsers from grammar files in `build.rs`.
+//!
+//! A grammar file describes the tokens, delimiters and options of a
+//! `parser_kit!` invocation as data, so grammar changes show up as small,
+//! reviewable diffs. [`compile`] turns it into the invocation and writes it
+//! to `OUT_DIR`, where the crate includes it. `parser_kit!` reads the same
+//! files with its `from_file` field.
+//!
+//! Files are TOML, or RON with the `ron` feature: `.ron` files, and `.synkit`
+//! files starting with `(`, are read as RON.
+//!
+//! # Example
+//!
+//! ```toml
+//! # grammar.synkit
+//! error = "crate::LexError"
+//! skip_tokens = ["Space"]
+//! span_derives = ["Debug", "Clone", "PartialEq", "Eq", "Hash", "Copy"]
+//! token_derives = ["Clone", "PartialEq", "Debug"]
+//!
+//! [[tokens]]
+//! name = "Space"
+//! regex = "[ \t\n]+"
+//!
+//! [[tokens]]
+//! name = "Ident"
+//! regex = "[a-z]+"
+//! callback = "|lex| lex.slice().to_string()"
+//! value = "String"
+//! fmt = "identifier"
+//! ```
+//!
+//! ```ignore
+//! // build.rs
+//! fn main() {
+//!     synkit_build::compile("grammar.synkit").expect("invalid grammar");
+//! }
+//!
+//! // src/lib.rs
+//! include!(concat!(env!("OUT_DIR"), "/grammar.rs"));
+//! ```
+
+#![deny(
+    unsafe_code,
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::todo,
+    clippy::unimplemented,
+    clippy::dbg_macro
+)]
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used, clippy::panic))]
+
+mod emit;
+mod error;
+mod grammar;
+
+use std::path::{Path, PathBuf};
+
+pub use error::Error;
+pub use grammar::{DelimiterDef, Format, Grammar, TokenDef};
+
+/// Read the grammar at `path`, write its `parser_kit!` invocation to
+/// `$OUT_DIR/<file stem>.rs`, and tell Cargo to rerun the build script
+/// when the grammar changes.
+///
+/// Returns the path written.
+pub fn compile(path: impl AsRef<Path>) -> Result<PathBuf, Error> {
+    let out_dir = std::env::var_os("OUT_DIR").ok_or(Error::MissingOutDir)?;
+    compile_to(path, out_dir)
+}
+
+/// Like [`compile`], writing to `out_dir` instead of `$OUT_DIR`.
+pub fn compile_to(path: impl AsRef<Path>, out_dir: impl AsRef<Path>) -> Result<PathBuf, Error> {
+    let path = path.as_ref();
+    println!("cargo:rerun-if-changed={}", path.display());
+
+    let grammar = Grammar::from_path(path)?;
+    let stem = path
+        .file_stem()
+        .map_or_else(|| "grammar".into(), |stem| stem.to_string_lossy());
+    let out = out_dir.as_ref().join(format!("{stem}.rs"));
+    let source = grammar.to_parser_kit()?;
+    let header = format!(
+        "// Generated by synkit-build from `{}`; do not edit.\n\n",
+        path.display()
+    );
+    std::fs::write(&out, header + &source).map_err(|source| Error::Io {
+        path: out.clone(),
+        source,
+    })?;
+    Ok(out)
+}
@@ -0,0 +1,109 @@
+//! Tests for turning grammar files into `parser_kit!` invocations.
+
+use std::path::Path;
+
+use synkit_build::{Error, Format, Grammar};
+
+const CALC: &str = "tests/fixtures/calc.synkit";
+
+#[test]
+fn test_snapshot() {
+    let grammar = Grammar::from_path(CALC).expect("grammar parses");
+    let source = grammar.to_parser_kit().expect("valid grammar");
+    insta::assert_snapshot!(source);
+}
+
+#[test]
+fn test_compile_to() {
+    let out_dir = std::env::temp_dir().join(format!("synkit-build-{}", std::process::id()));
+    std::fs::create_dir_all(&out_dir).expect("create out dir");
+    let out = synkit_build::compile_to(CALC, &out_dir).expect("compiles");
+    assert_eq!(out, out_dir.join("calc.rs"));
+    let written = std::fs::read_to_string(&out).expect("output written");
+    assert!(written.starts_with("// Generated by synkit-build from `tests/fixtures/calc.synkit`"));
+    assert!(written.contains("synkit::parser_kit! {"));
+    std::fs::remove_dir_all(&out_dir).expect("clean up");
+}
+
+#[test]
+fn test_invalid_rust_names_the_field() {
+    let grammar = Grammar::from_toml(
+        r#"
+        error = "crate::LexError"
+
+        [[tokens]]
+        name = "Number"
+        regex = "[0-9]+"
+        value = "Vec<"
+        "#,
+    )
+    .expect("grammar parses");
+    let err = grammar.to_parser_kit().unwrap_err();
+    assert!(
+        matches!(&err, Error::Invalid { field, .. } if field == "tokens.Number.value"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_token_needs_one_pattern() {
+    let grammar = Grammar::from_toml(
+        r#"
+        error = "LexError"
+
+        [[tokens]]
+        name = "Both"
+        token = "a"
+        regex = "a"
+        "#,
+    )
+    .expect("grammar parses");
+    assert!(matches!(
+        grammar.to_parser_kit(),
+        Err(Error::Invalid { field, .. }) if field == "tokens.Both"
+    ));
+}
+
+#[test]
+fn test_unknown_fields_are_rejected() {
+    let err = Grammar::from_toml("error = \"E\"\nskip = [\"Space\"]\n").unwrap_err();
+    assert!(matches!(err, Error::Toml(_)));
+}
+
+#[test]
+fn test_format_detection() {
+    assert_eq!(
+        Format::detect(Path::new("g.synkit"), "error = \"E\""),
+        Format::Toml
+    );
+    assert_eq!(
+        Format::detect(Path::new("g.synkit"), "\n(error: \"E\")"),
+        Format::Ron
+    );
+    assert_eq!(Format::detect(Path::new("g.ron"), ""), Format::Ron);
+    assert_eq!(Format::detect(Path::new("g.toml"), "("), Format::Toml);
+}
+
+#[cfg(feature = "ron")]
+#[test]
+fn test_ron() {
+    let grammar = Grammar::from_ron(
+        r#"(
+            error: "LexError",
+            tokens: [(name: "Semi", token: ";")],
+            delimiters: [],
+        )"#,
+    )
+    .expect("grammar parses");
+    assert_eq!(grammar.tokens[0].token.as_deref(), Some(";"));
+    assert!(grammar.to_parser_kit().is_ok());
+}
+
+#[cfg(not(feature = "ron"))]
+#[test]
+fn test_ron_disabled() {
+    assert!(matches!(
+        Grammar::parse("(error: \"E\")", Format::Ron),
+        Err(Error::RonDisabled)
+    ));
+}
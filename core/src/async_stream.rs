@@ -53,10 +53,17 @@
 //! ```
 
 use core::fmt;
+use core::fmt::Write as _;
 use core::future::Future;
+use core::marker::PhantomData;
+use core::ops::Range;
 use core::pin::Pin;
 use core::task::{Context, Poll};
 
+use crate::{Parse, TokenStream};
+#[cfg(any(feature = "tokio", feature = "futures"))]
+use crate::{SpanLike as _, SpannedLike};
+
 /// State of an incremental parse operation.
 ///
 /// Represents the three possible outcomes when parsing from an incremental
@@ -222,6 +229,23 @@ impl fmt::Display for StreamError {
     }
 }
 
+impl StreamError {
+    /// Whether restarting the failed task could help.
+    ///
+    /// Lex and parse errors and timeouts concern the input seen so far;
+    /// closed channels, overflowing buffers and exhausted limits do not go
+    /// away on a restart.
+    pub fn is_recoverable(&self) -> bool {
+        matches!(
+            self,
+            StreamError::LexError(_)
+                | StreamError::ParseError(_)
+                | StreamError::Timeout
+                | StreamError::ChunkTooLarge { .. }
+        )
+    }
+}
+
 #[cfg(feature = "std")]
 impl std::error::Error for StreamError {}
 
@@ -281,6 +305,35 @@ impl LexerCapacityHint {
             tokens_per_chunk: chunk_size / 4,
         }
     }
+
+    /// Measure hints by lexing `sample`, a representative chunk of
+    /// production input such as one network read or one record.
+    ///
+    /// The buffer is sized for the sample and the token count is what `L`
+    /// actually produced, instead of the 4-bytes-per-token guess of
+    /// [`from_chunk_size`](Self::from_chunk_size).
+    ///
+    /// # Example
+    /// ```ignore
+    /// let hint = LexerCapacityHint::measure::<MyLexer>(include_str!("typical.jsonl"))?;
+    /// let lexer = MyLexer::with_capacity_hint(hint);
+    /// ```
+    pub fn measure<L: IncrementalLexer>(sample: &str) -> Result<Self, L::Error> {
+        let mut lexer = L::new();
+        let mut tokens = lexer.feed(sample)?.len();
+        tokens += lexer.finish()?.len();
+        Ok(Self {
+            buffer_capacity: sample.len(),
+            tokens_per_chunk: tokens,
+        })
+    }
+
+    /// Source bytes per token these hints assume, or `None` if they expect
+    /// no tokens.
+    pub fn bytes_per_token(&self) -> Option<f64> {
+        (self.tokens_per_chunk > 0)
+            .then(|| self.buffer_capacity as f64 / self.tokens_per_chunk as f64)
+    }
 }
 
 /// Trait for types that can be incrementally lexed.
@@ -409,15 +462,35 @@ pub trait ChunkBoundary {
         false
     }
 
+    /// Returns true if this token opens or closes a quoted section.
+    ///
+    /// Between an opening and closing quote, boundaries, depth changes and
+    /// field separators are ignored, e.g. newlines inside a quoted CSV
+    /// field. An escaped quote lexed as two quote tokens (`""`) leaves the
+    /// quote state unchanged.
+    #[inline]
+    fn is_quote(token: &Self::Token) -> bool {
+        let _ = token;
+        false
+    }
+
     /// Find the next chunk boundary in the token slice.
     ///
     /// Returns `Some(end_pos)` where `end_pos` is the index AFTER the boundary token,
     /// or `None` if no complete chunk is available.
     fn find_boundary<S: AsRef<Self::Token>>(tokens: &[S], start: usize) -> Option<usize> {
         let mut depth: i32 = 0;
+        let mut quoted = false;
 
         for (i, tok) in tokens.iter().enumerate().skip(start) {
             let token = tok.as_ref();
+            if Self::is_quote(token) {
+                quoted = !quoted;
+                continue;
+            }
+            if quoted {
+                continue;
+            }
             depth += Self::depth_delta(token);
 
             if depth == 0 && Self::is_boundary_token(token) {
@@ -435,6 +508,296 @@ pub trait ChunkBoundary {
     }
 }
 
+/// A [`ChunkBoundary`] whose records are split further into fields, as in
+/// CSV or TSV.
+///
+/// Records end at boundary tokens and fields at separators, both only at
+/// depth 0 outside quotes (see [`ChunkBoundary::is_quote`]).
+///
+/// # Example
+/// ```ignore
+/// impl FieldBoundary for Csv {
+///     fn is_field_separator(token: &Token) -> bool {
+///         matches!(token, Token::Comma)
+///     }
+/// }
+///
+/// while let Some(end) = Csv::find_boundary(buffer.remaining(), 0) {
+///     let record = &buffer.remaining()[..end];
+///     let row: Vec<_> = Csv::split_fields(record)
+///         .into_iter()
+///         .map(|field| parse_cell(&record[field]))
+///         .collect();
+///     buffer.consume(end);
+/// }
+/// ```
+pub trait FieldBoundary: ChunkBoundary {
+    /// Returns true if this token separates two fields of a record.
+    fn is_field_separator(token: &Self::Token) -> bool;
+
+    /// Token index ranges of the fields in `record`, one record with or
+    /// without its boundary token.
+    ///
+    /// Separators and the boundary token are left out of the ranges. An
+    /// empty record has a single empty field.
+    fn split_fields<S: AsRef<Self::Token>>(record: &[S]) -> Vec<Range<usize>> {
+        let mut fields = Vec::new();
+        let mut depth: i32 = 0;
+        let mut quoted = false;
+        let mut start = 0;
+        let mut end = record.len();
+
+        for (i, tok) in record.iter().enumerate() {
+            let token = tok.as_ref();
+            if Self::is_quote(token) {
+                quoted = !quoted;
+                continue;
+            }
+            if quoted {
+                continue;
+            }
+            depth += Self::depth_delta(token);
+            if depth != 0 {
+                continue;
+            }
+            if Self::is_boundary_token(token) {
+                end = i;
+                break;
+            }
+            if Self::is_field_separator(token) {
+                fields.push(start..i);
+                start = i + 1;
+            }
+        }
+
+        fields.push(start..end);
+        fields
+    }
+}
+
+/// Builds the token stream [`BoundaryParsed`] parses a chunk from.
+///
+/// # Example
+/// ```ignore
+/// impl ChunkStream for Lines {
+///     type Stream = TokenStream;
+///
+///     fn chunk_stream<S: AsRef<Token>>(tokens: &[S]) -> TokenStream {
+///         let tokens = tokens.iter().map(|tok| Spanned {
+///             span: Span::call_site(),
+///             value: tok.as_ref().clone(),
+///         });
+///         TokenStream::from_tokens(Arc::from(""), Arc::new(tokens.collect()))
+///     }
+/// }
+/// ```
+pub trait ChunkStream: ChunkBoundary {
+    /// The stream chunks are parsed from, e.g. the generated `TokenStream`.
+    type Stream: TokenStream<Token = Self::Token>;
+
+    /// A stream over `tokens`, one chunk without its boundary token.
+    fn chunk_stream<S: AsRef<Self::Token>>(tokens: &[S]) -> Self::Stream;
+}
+
+/// A `T` parsed incrementally one chunk at a time, with chunks split by
+/// the [`ChunkBoundary`] `B`.
+///
+/// Implements [`IncrementalParse`] for any blocking [`Parse`] type: each
+/// chunk up to a boundary token at depth 0 is handed, without that token,
+/// to a stream from [`ChunkStream::chunk_stream`] and parsed as a `T`.
+/// Chunks holding only ignorable tokens are skipped. The last record needs
+/// its boundary token too, as a chunk is only complete once one is seen.
+///
+/// # Example
+/// ```ignore
+/// let lines = IterParseStream::<_, BoundaryParsed<JsonValue, Lines>>::new(tokens);
+/// for line in lines {
+///     process(line?.into_inner());
+/// }
+/// ```
+pub struct BoundaryParsed<T, B> {
+    /// The parsed chunk.
+    pub value: T,
+    boundary: PhantomData<fn() -> B>,
+}
+
+impl<T, B> BoundaryParsed<T, B> {
+    /// Wrap an already parsed value.
+    #[inline]
+    pub const fn new(value: T) -> Self {
+        Self {
+            value,
+            boundary: PhantomData,
+        }
+    }
+
+    /// The parsed value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+// Not derived, which would bound the marker `B` too.
+impl<T: fmt::Debug, B> fmt::Debug for BoundaryParsed<T, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BoundaryParsed").field(&self.value).finish()
+    }
+}
+
+impl<T: Clone, B> Clone for BoundaryParsed<T, B> {
+    fn clone(&self) -> Self {
+        Self::new(self.value.clone())
+    }
+}
+
+impl<T: PartialEq, B> PartialEq for BoundaryParsed<T, B> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T, B> IncrementalParse for BoundaryParsed<T, B>
+where
+    T: Parse,
+    T::Error: fmt::Display,
+    B: ChunkStream<Token = T::Token>,
+{
+    type Token = T::Token;
+    type Error = T::Error;
+
+    fn parse_incremental<S>(
+        tokens: &[S],
+        checkpoint: &ParseCheckpoint,
+    ) -> Result<(Option<Self>, ParseCheckpoint), Self::Error>
+    where
+        S: AsRef<Self::Token>,
+    {
+        let mut next = *checkpoint;
+        while let Some(rest) = tokens.get(next.cursor..) {
+            let Some(len) = B::find_boundary(rest, 0) else {
+                break;
+            };
+            // `find_boundary` ends the chunk just past its boundary token
+            let chunk = rest.get(..len - 1).unwrap_or_default();
+            next.cursor += len;
+            next.tokens_consumed += len;
+            if chunk.iter().all(|tok| B::is_ignorable(tok.as_ref())) {
+                continue;
+            }
+            let mut stream = B::chunk_stream(chunk);
+            let value = T::parse(&mut stream)?;
+            return Ok((Some(Self::new(value)), next));
+        }
+        // Skipped chunks are consumed even without a node
+        Ok((None, next))
+    }
+
+    fn can_parse<S>(tokens: &[S], checkpoint: &ParseCheckpoint) -> bool
+    where
+        S: AsRef<Self::Token>,
+    {
+        tokens
+            .get(checkpoint.cursor..)
+            .is_some_and(|rest| B::has_complete_chunk(rest, 0))
+    }
+}
+
+/// A record type recognized by its first token, so [`Either`] can tell it
+/// apart from other record types in the same stream.
+pub trait RecordStart: IncrementalParse {
+    /// Whether a record of this type starts with `token`.
+    fn is_start(token: &Self::Token) -> bool;
+}
+
+/// A record that is an `A` or a `B`, for streams interleaving record types
+/// such as header and data lines.
+///
+/// Each record goes to `A` if [`RecordStart::is_start`] accepts its first
+/// token, and to `B` otherwise. Nest for more types: `Either<A, Either<B,
+/// C>>` tries `A`, then `B`, then falls back to `C`.
+///
+/// # Example
+/// ```ignore
+/// impl RecordStart for Header {
+///     fn is_start(token: &Token) -> bool {
+///         matches!(token, Token::Hash)
+///     }
+/// }
+///
+/// let mut parser = AstStream::<Either<Header, Entry>, _>::new(token_rx, ast_tx);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Either<A, B> {
+    /// A record starting with a token `A` accepts.
+    Left(A),
+    /// Any other record.
+    Right(B),
+}
+
+impl<A, B> Either<A, B> {
+    /// Whether `tokens[checkpoint.cursor]` starts an `A`, or `None` if
+    /// there are no tokens left.
+    fn is_left<S>(tokens: &[S], checkpoint: &ParseCheckpoint) -> Option<bool>
+    where
+        A: RecordStart,
+        S: AsRef<A::Token>,
+    {
+        let first = tokens.get(checkpoint.cursor)?;
+        Some(A::is_start(first.as_ref()))
+    }
+}
+
+impl<A, B> IncrementalParse for Either<A, B>
+where
+    A: RecordStart,
+    B: IncrementalParse<Token = A::Token, Error = A::Error>,
+{
+    type Token = A::Token;
+    type Error = A::Error;
+
+    fn parse_incremental<S>(
+        tokens: &[S],
+        checkpoint: &ParseCheckpoint,
+    ) -> Result<(Option<Self>, ParseCheckpoint), Self::Error>
+    where
+        S: AsRef<Self::Token>,
+    {
+        match Self::is_left(tokens, checkpoint) {
+            Some(true) => {
+                let (node, next) = A::parse_incremental(tokens, checkpoint)?;
+                Ok((node.map(Either::Left), next))
+            }
+            Some(false) => {
+                let (node, next) = B::parse_incremental(tokens, checkpoint)?;
+                Ok((node.map(Either::Right), next))
+            }
+            None => Ok((None, *checkpoint)),
+        }
+    }
+
+    fn can_parse<S>(tokens: &[S], checkpoint: &ParseCheckpoint) -> bool
+    where
+        S: AsRef<Self::Token>,
+    {
+        match Self::is_left(tokens, checkpoint) {
+            Some(true) => A::can_parse(tokens, checkpoint),
+            Some(false) => B::can_parse(tokens, checkpoint),
+            None => false,
+        }
+    }
+}
+
+impl<A, B> RecordStart for Either<A, B>
+where
+    A: RecordStart,
+    B: RecordStart<Token = A::Token, Error = A::Error>,
+{
+    fn is_start(token: &Self::Token) -> bool {
+        A::is_start(token) || B::is_start(token)
+    }
+}
+
 /// A reusable buffer for incremental token processing.
 ///
 /// This buffer manages tokens efficiently by:
@@ -629,7 +992,8 @@ pub trait IncrementalParse: Sized {
     ///
     /// Returns:
     /// - `Ok((Some(node), new_checkpoint))` if a complete node was parsed
-    /// - `Ok((None, checkpoint))` if more tokens are needed
+    /// - `Ok((None, checkpoint))` if more tokens are needed; the checkpoint
+    ///   may move past tokens that hold no node, like blank lines
     /// - `Err(error)` if an unrecoverable error occurred
     fn parse_incremental<S>(
         tokens: &[S],
@@ -712,6 +1076,355 @@ impl<'a, T> Future for TokenFuture<'a, T> {
     }
 }
 
+/// A diagnostic for one AST node of a stream, sent on the side channel
+/// given to `AstStream::with_diagnostics`.
+///
+/// `node` is the node's position in the stream's output, counting from 0,
+/// so consumers can match warnings to the records they belong to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeDiagnostic<S> {
+    /// Index of the node among those the stream emitted.
+    pub node: usize,
+    /// How serious it is.
+    pub severity: crate::Severity,
+    /// Where it is, if known.
+    pub span: Option<S>,
+    /// What is wrong.
+    pub message: String,
+}
+
+impl<S> NodeDiagnostic<S> {
+    /// A warning at `span`; the stream fills in `node`.
+    pub fn warning(span: Option<S>, message: impl Into<String>) -> Self {
+        Self {
+            node: 0,
+            severity: crate::Severity::Warning,
+            span,
+            message: message.into(),
+        }
+    }
+
+    /// An error at `span`; the stream fills in `node`.
+    pub fn error(span: Option<S>, message: impl Into<String>) -> Self {
+        Self {
+            node: 0,
+            severity: crate::Severity::Error,
+            span,
+            message: message.into(),
+        }
+    }
+
+    /// The diagnostic as one line of JSON:
+    /// `{"node", "severity", "start"?, "end"?, "message"}`.
+    ///
+    /// `start` and `end` are byte offsets, left out for call-site spans.
+    pub fn to_json(&self) -> String
+    where
+        S: crate::SpanLike,
+    {
+        let mut out = format!(
+            "{{\"node\":{},\"severity\":\"{}\"",
+            self.node, self.severity
+        );
+        if let Some(span) = self.span.as_ref().filter(|span| !span.is_call_site()) {
+            let _ = write!(out, ",\"start\":{},\"end\":{}", span.start(), span.end());
+        }
+        out.push_str(",\"message\":");
+        crate::attempts::write_json_str(&self.message, &mut out);
+        out.push('}');
+        out
+    }
+}
+
+impl<S> fmt::Display for NodeDiagnostic<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "node {}: {}: {}", self.node, self.severity, self.message)
+    }
+}
+
+/// AST nodes that carry diagnostics from their own parse, such as
+/// deprecated syntax accepted with a warning.
+///
+/// Record them on the node in `IncrementalParse::parse_incremental` and
+/// return them here; `AstStream::with_diagnostics` forwards them with the
+/// node's index instead of the parser task logging them.
+pub trait NodeDiagnostics {
+    /// The span type of the diagnostics.
+    type Span;
+
+    /// Diagnostics found while parsing this node. Their `node` is ignored.
+    fn diagnostics(&self) -> Vec<NodeDiagnostic<Self::Span>>;
+}
+
+/// A node from a streaming parser, with where it came from.
+///
+/// Produced by `AstStream::emitted` and `ParseStream::emitted`, so records
+/// can be traced back to offsets in the overall input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Emitted<T> {
+    /// The parsed node.
+    pub value: T,
+    /// Byte range of the node's tokens in the whole stream, from the start
+    /// of its first token to the end of its last; empty at 0 if it consumed
+    /// none.
+    pub range: Range<usize>,
+    /// Position among the nodes the stream emitted, from 0.
+    pub index: usize,
+}
+
+/// Byte range covered by `tokens`.
+///
+/// Incremental lexers give tokens spans relative to the whole stream, so
+/// this is the absolute range of a node parsed from them.
+#[cfg(any(feature = "tokio", feature = "futures"))]
+fn byte_range<S, Tok>(tokens: &[S]) -> Range<usize>
+where
+    S: SpannedLike<Tok>,
+{
+    match (tokens.first(), tokens.last()) {
+        (Some(first), Some(last)) => first.span().start()..last.span().end(),
+        _ => 0..0,
+    }
+}
+
+/// Byte range of a node's tokens; `byte_range`, captured where the token
+/// type is known to be spanned.
+#[cfg(feature = "tokio")]
+type TokenRange<S> = fn(&[S]) -> Range<usize>;
+
+/// Re-lex and re-parse the one record at `range` of `source`, e.g. the
+/// [`Emitted::range`] of a record that failed validation.
+///
+/// Lexes with a fresh `L`, so token spans start at 0 at `range.start`.
+/// Fails with [`StreamError::LexError`] if `range` isn't a valid range of
+/// `source`, [`StreamError::IncompleteInput`] if it ends mid-record, and
+/// [`StreamError::ParseError`] if the record fails to parse or tokens are
+/// left after it.
+///
+/// # Example
+/// ```ignore
+/// let record = replay::<JsonIncrementalLexer, JsonLine>(&source, emitted.range)?;
+/// ```
+pub fn replay<L, T>(source: &str, range: Range<usize>) -> Result<T, StreamError>
+where
+    L: IncrementalLexer,
+    L::Spanned: AsRef<T::Token>,
+    T: IncrementalParse,
+{
+    let text = source.get(range.clone()).ok_or_else(|| {
+        StreamError::LexError(format!(
+            "byte range {range:?} is not within the {}-byte source",
+            source.len()
+        ))
+    })?;
+    let mut lexer = L::new();
+    let mut tokens = lexer
+        .feed(text)
+        .map_err(|e| StreamError::LexError(e.to_string()))?;
+    lexer
+        .finish_into(&mut tokens)
+        .map_err(|e| StreamError::LexError(e.to_string()))?;
+
+    match T::parse_incremental(&tokens, &ParseCheckpoint::default()) {
+        Ok((Some(node), checkpoint)) if checkpoint.cursor >= tokens.len() => Ok(node),
+        Ok((Some(_), checkpoint)) => Err(StreamError::ParseError(format!(
+            "{} tokens left after the record",
+            tokens.len() - checkpoint.cursor
+        ))),
+        Ok((None, _)) => Err(StreamError::IncompleteInput),
+        Err(e) => Err(StreamError::ParseError(e.to_string())),
+    }
+}
+
+/// How [`Framer`] splits a byte stream into frames.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Framing {
+    /// Frames end at this byte sequence, which is left out of the frame.
+    /// An empty delimiter never matches.
+    Delimiter(Vec<u8>),
+    /// Each frame is preceded by its length as a big-endian `u32`.
+    LengthU32,
+    /// Each frame is preceded by its length as an unsigned LEB128 varint,
+    /// as in protobuf.
+    Varint,
+}
+
+/// One frame's payload, from [`Framer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// Byte offset of the payload in the whole framed stream.
+    pub offset: usize,
+    /// The payload, without its prefix or delimiter.
+    pub data: Vec<u8>,
+}
+
+impl Frame {
+    /// The payload as text, for an [`IncrementalLexer`].
+    ///
+    /// Fails with [`StreamError::LexError`] if it isn't UTF-8.
+    pub fn as_str(&self) -> Result<&str, StreamError> {
+        core::str::from_utf8(&self.data)
+            .map_err(|e| StreamError::LexError(format!("frame at byte {}: {e}", self.offset)))
+    }
+}
+
+/// Splits bytes from a wire protocol into [`Frame`]s as they arrive, so
+/// each frame's payload can be lexed as its own source.
+///
+/// # Example
+/// ```ignore
+/// let mut framer = Framer::new(Framing::LengthU32).with_max_frame_size(1 << 20);
+/// while let Some(bytes) = socket.next().await {
+///     framer.push(&bytes?);
+///     while let Some(frame) = framer.next_frame()? {
+///         tokens.feed_frame(&frame).await?;
+///     }
+/// }
+/// if let Some(frame) = framer.finish()? {
+///     tokens.feed_frame(&frame).await?;
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Framer {
+    framing: Framing,
+    buffer: Vec<u8>,
+    /// Offset of `buffer[0]` in the whole stream.
+    offset: usize,
+    max_frame_size: usize,
+}
+
+impl Framer {
+    /// A framer with no limit on frame size.
+    pub fn new(framing: Framing) -> Self {
+        Self {
+            framing,
+            buffer: Vec::new(),
+            offset: 0,
+            max_frame_size: usize::MAX,
+        }
+    }
+
+    /// Fail with [`StreamError::ChunkTooLarge`] on frames over `max` bytes,
+    /// instead of buffering them.
+    pub fn with_max_frame_size(mut self, max: usize) -> Self {
+        self.max_frame_size = max;
+        self
+    }
+
+    /// Buffer more bytes of the stream.
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Bytes buffered but not yet returned in a frame.
+    #[inline]
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// The next complete frame, or `None` until more bytes are pushed.
+    pub fn next_frame(&mut self) -> Result<Option<Frame>, StreamError> {
+        let (start, len, skip) = match &self.framing {
+            Framing::Delimiter(delimiter) => {
+                let Some(len) = find_subslice(&self.buffer, delimiter) else {
+                    return self.check_size(self.buffer.len()).map(|()| None);
+                };
+                (0, len, delimiter.len())
+            }
+            Framing::LengthU32 => {
+                let Some(prefix) = self.buffer.first_chunk::<4>() else {
+                    return Ok(None);
+                };
+                let len = u32::from_be_bytes(*prefix) as usize;
+                (4, len, 0)
+            }
+            Framing::Varint => match read_varint(&self.buffer, self.offset)? {
+                Some((len, prefix)) => (prefix, len, 0),
+                None => return Ok(None),
+            },
+        };
+        self.check_size(len)?;
+        let end = start.saturating_add(len);
+        let Some(data) = self.buffer.get(start..end) else {
+            return Ok(None);
+        };
+        let frame = Frame {
+            offset: self.offset + start,
+            data: data.to_vec(),
+        };
+        // The delimiter was found, so `end + skip` is in the buffer
+        self.buffer.drain(..end + skip);
+        self.offset += end + skip;
+        Ok(Some(frame))
+    }
+
+    /// Signal the end of the stream, returning what remains as a last frame.
+    ///
+    /// With [`Framing::Delimiter`], bytes after the last delimiter make a
+    /// final frame. With a length prefix, leftover bytes are an incomplete
+    /// frame and fail with [`StreamError::IncompleteInput`].
+    pub fn finish(&mut self) -> Result<Option<Frame>, StreamError> {
+        if let Some(frame) = self.next_frame()? {
+            return Ok(Some(frame));
+        }
+        if self.buffer.is_empty() {
+            return Ok(None);
+        }
+        match self.framing {
+            Framing::Delimiter(_) => {
+                let frame = Frame {
+                    offset: self.offset,
+                    data: core::mem::take(&mut self.buffer),
+                };
+                self.offset += frame.data.len();
+                Ok(Some(frame))
+            }
+            Framing::LengthU32 | Framing::Varint => Err(StreamError::IncompleteInput),
+        }
+    }
+
+    fn check_size(&self, size: usize) -> Result<(), StreamError> {
+        if size > self.max_frame_size {
+            return Err(StreamError::ChunkTooLarge {
+                size,
+                max: self.max_frame_size,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Index of the first `needle` in `haystack`; never found if empty.
+fn find_subslice(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() {
+        return None;
+    }
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// A LEB128 varint at the start of `bytes` and its length in bytes, or
+/// `None` if it continues past the end.
+fn read_varint(bytes: &[u8], offset: usize) -> Result<Option<(usize, usize)>, StreamError> {
+    let mut value: u64 = 0;
+    for (i, byte) in bytes.iter().enumerate() {
+        let shift = 7 * i as u32;
+        let bits = u64::from(byte & 0x7f);
+        if shift >= u64::BITS || (bits << shift) >> shift != bits {
+            return Err(StreamError::LexError(format!(
+                "varint length prefix at byte {offset} overflows"
+            )));
+        }
+        value |= bits << shift;
+        if byte & 0x80 == 0 {
+            let len = usize::try_from(value).unwrap_or(usize::MAX);
+            return Ok(Some((len, i + 1)));
+        }
+    }
+    Ok(None)
+}
+
 /// Configuration for async stream processing.
 ///
 /// Controls buffer sizes, capacity hints, and resource limits for the
@@ -746,6 +1459,7 @@ impl<'a, T> Future for TokenFuture<'a, T> {
 ///     ast_buffer_size: 128,
 ///     max_chunk_size: 128 * 1024,
 ///     lexer_hint: LexerCapacityHint::large(),
+///     yield_every: 4096,
 /// };
 /// ```
 #[derive(Debug, Clone)]
@@ -770,6 +1484,12 @@ pub struct StreamConfig {
     /// Passed to [`IncrementalLexer::with_capacity_hint`] to pre-allocate
     /// internal buffers. Default: [`LexerCapacityHint::medium()`].
     pub lexer_hint: LexerCapacityHint,
+    /// Tokens handled between yields to the async executor; 0 never yields.
+    ///
+    /// Lexing and parsing between awaits runs without giving other tasks a
+    /// turn, so a single huge record could hold a worker thread for as long
+    /// as it takes. Default: 4096.
+    pub yield_every: usize,
 }
 
 impl Default for StreamConfig {
@@ -779,6 +1499,7 @@ impl Default for StreamConfig {
             ast_buffer_size: 64,
             max_chunk_size: 64 * 1024, // 64KB
             lexer_hint: LexerCapacityHint::medium(),
+            yield_every: 4096,
         }
     }
 }
@@ -791,6 +1512,7 @@ impl StreamConfig {
             ast_buffer_size: 16,
             max_chunk_size: 4 * 1024,
             lexer_hint: LexerCapacityHint::small(),
+            yield_every: 1024,
         }
     }
 
@@ -801,6 +1523,7 @@ impl StreamConfig {
             ast_buffer_size: 64,
             max_chunk_size: 64 * 1024,
             lexer_hint: LexerCapacityHint::medium(),
+            yield_every: 4096,
         }
     }
 
@@ -811,17 +1534,43 @@ impl StreamConfig {
             ast_buffer_size: 512,
             max_chunk_size: 256 * 1024,
             lexer_hint: LexerCapacityHint::large(),
+            yield_every: 16 * 1024,
         }
     }
 
     /// Create configuration from expected chunk size.
     pub const fn from_chunk_size(chunk_size: usize) -> Self {
-        let tokens_estimate = chunk_size / 4;
+        Self::from_hint(LexerCapacityHint::from_chunk_size(chunk_size))
+    }
+
+    /// Create configuration by lexing `sample`, a representative chunk of
+    /// production input, with `L`.
+    ///
+    /// Sizes buffers like [`from_chunk_size`](Self::from_chunk_size) with
+    /// the sample's length, but from the token count
+    /// [`LexerCapacityHint::measure`] observed rather than a guess.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let config = StreamConfig::tuned_from_sample::<MyLexer>(&sample_record)?;
+    /// let stream = AsyncTokenStream::<MyLexer>::with_config(tx, config);
+    /// ```
+    pub fn tuned_from_sample<L: IncrementalLexer>(sample: &str) -> Result<Self, L::Error> {
+        let mut config = Self::from_hint(LexerCapacityHint::measure::<L>(sample)?);
+        // Channels need room for at least one item
+        config.token_buffer_size = config.token_buffer_size.max(1);
+        config.ast_buffer_size = config.ast_buffer_size.max(1);
+        Ok(config)
+    }
+
+    /// Buffers sized for chunks described by `hint`.
+    const fn from_hint(hint: LexerCapacityHint) -> Self {
         Self {
-            token_buffer_size: tokens_estimate,
-            ast_buffer_size: tokens_estimate / 16,
-            max_chunk_size: chunk_size * 2,
-            lexer_hint: LexerCapacityHint::from_chunk_size(chunk_size),
+            token_buffer_size: hint.tokens_per_chunk,
+            ast_buffer_size: hint.tokens_per_chunk / 16,
+            max_chunk_size: hint.buffer_capacity * 2,
+            lexer_hint: hint,
+            yield_every: hint.tokens_per_chunk,
         }
     }
 }
@@ -833,11 +1582,60 @@ pub mod tokio_impl {
     use super::*;
     use ::tokio::sync::mpsc;
 
+    /// Counts tokens handled by an async task and yields to the executor
+    /// every so many, so a huge record doesn't starve other tasks.
+    ///
+    /// [`AsyncTokenStream`] and [`AstStream`] keep one, set from
+    /// [`StreamConfig::yield_every`]; hand-written parse loops can too.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut budget = YieldBudget::new(4096);
+    /// for token in tokens {
+    ///     handle(token);
+    ///     budget.tick(1).await;
+    /// }
+    /// ```
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct YieldBudget {
+        every: usize,
+        since: usize,
+    }
+
+    impl YieldBudget {
+        /// Yield once per `every` tokens; 0 never yields.
+        #[inline]
+        pub const fn new(every: usize) -> Self {
+            Self { every, since: 0 }
+        }
+
+        /// Count `tokens` more, yielding if that reaches the limit.
+        pub async fn tick(&mut self, tokens: usize) {
+            if self.every == 0 {
+                return;
+            }
+            self.since = self.since.saturating_add(tokens);
+            if self.since >= self.every {
+                self.since = 0;
+                ::tokio::task::yield_now().await;
+            }
+        }
+    }
+
     /// Async token stream that receives source chunks and emits tokens.
     pub struct AsyncTokenStream<L: IncrementalLexer> {
         lexer: L,
         token_tx: mpsc::Sender<L::Spanned>,
         config: StreamConfig,
+        yields: YieldBudget,
+        bytes: usize,
+        tokens: usize,
+        progress: Option<(crate::ProgressTracker, ProgressCallback)>,
+    }
+
+    enum ProgressCallback {
+        Sync(crate::OnProgress),
+        Async(crate::OnProgressAsync),
     }
 
     impl<L: IncrementalLexer> AsyncTokenStream<L> {
@@ -853,7 +1651,82 @@ pub mod tokio_impl {
             Self {
                 lexer: L::with_capacity_hint(config.lexer_hint),
                 token_tx,
+                yields: YieldBudget::new(config.yield_every),
                 config,
+                bytes: 0,
+                tokens: 0,
+                progress: None,
+            }
+        }
+
+        /// Call `f(bytes_consumed, tokens_consumed)` as chunks are lexed,
+        /// once per `granularity` step and again from [`finish`](Self::finish).
+        ///
+        /// # Example
+        ///
+        /// ```ignore
+        /// let mut lexer = AsyncTokenStream::new(token_tx).on_progress(
+        ///     ProgressGranularity::every_bytes(16 << 20),
+        ///     move |bytes, _| bar.set_position(bytes as u64),
+        /// );
+        /// ```
+        pub fn on_progress(
+            mut self,
+            granularity: crate::ProgressGranularity,
+            f: impl FnMut(usize, usize) + Send + 'static,
+        ) -> Self {
+            self.progress = Some((
+                crate::ProgressTracker::new(granularity),
+                ProgressCallback::Sync(Box::new(f)),
+            ));
+            self
+        }
+
+        /// Like [`on_progress`](Self::on_progress), but awaits the future `f`
+        /// returns before lexing more, e.g. to send on a bounded channel.
+        pub fn on_progress_async<F>(
+            mut self,
+            granularity: crate::ProgressGranularity,
+            mut f: impl FnMut(usize, usize) -> F + Send + 'static,
+        ) -> Self
+        where
+            F: Future<Output = ()> + Send + 'static,
+        {
+            self.progress = Some((
+                crate::ProgressTracker::new(granularity),
+                ProgressCallback::Async(Box::new(move |bytes, tokens| Box::pin(f(bytes, tokens)))),
+            ));
+            self
+        }
+
+        /// Bytes fed so far.
+        #[inline]
+        pub fn bytes_consumed(&self) -> usize {
+            self.bytes
+        }
+
+        /// Tokens sent so far.
+        #[inline]
+        pub fn tokens_consumed(&self) -> usize {
+            self.tokens
+        }
+
+        async fn report(
+            progress: &mut Option<(crate::ProgressTracker, ProgressCallback)>,
+            bytes: usize,
+            tokens: usize,
+            finished: bool,
+        ) {
+            let Some((tracker, callback)) = progress else {
+                return;
+            };
+            let due = tracker.update(bytes, tokens);
+            if !(due || finished && tracker.finish()) {
+                return;
+            }
+            match callback {
+                ProgressCallback::Sync(f) => f(bytes, tokens),
+                ProgressCallback::Async(f) => f(bytes, tokens).await,
             }
         }
 
@@ -874,51 +1747,181 @@ pub mod tokio_impl {
                 .map_err(|e| StreamError::LexError(e.to_string()))?;
 
             // Send tokens to the parser
-            for token in tokens {
-                self.token_tx
-                    .send(token)
-                    .await
-                    .map_err(|_| StreamError::ChannelClosed)?;
-            }
+            self.send(tokens).await?;
 
+            self.bytes += chunk.len();
+            Self::report(&mut self.progress, self.bytes, self.tokens, false).await;
             Ok(())
         }
 
+        /// Lex one frame from a [`Framer`] as a source of its own.
+        ///
+        /// The frame is fed, then the lexer is finished and replaced by a
+        /// fresh one, so a token can't span two frames. Token spans are
+        /// relative to the start of the frame; add [`Frame::offset`] for
+        /// positions in the framed stream.
+        pub async fn feed_frame(&mut self, frame: &Frame) -> Result<(), StreamError> {
+            self.feed(frame.as_str()?).await?;
+            let lexer = core::mem::replace(
+                &mut self.lexer,
+                L::with_capacity_hint(self.config.lexer_hint),
+            );
+            let tokens = lexer
+                .finish()
+                .map_err(|e| StreamError::LexError(e.to_string()))?;
+            self.send(tokens).await
+        }
+
         /// Signal that no more input will arrive.
-        pub async fn finish(self) -> Result<(), StreamError> {
+        pub async fn finish(mut self) -> Result<(), StreamError> {
             let tokens = self
                 .lexer
                 .finish()
                 .map_err(|e| StreamError::LexError(e.to_string()))?;
 
+            self.tokens += tokens.len();
             for token in tokens {
                 self.token_tx
                     .send(token)
                     .await
                     .map_err(|_| StreamError::ChannelClosed)?;
+                self.yields.tick(1).await;
+            }
+
+            Self::report(&mut self.progress, self.bytes, self.tokens, true).await;
+            Ok(())
+        }
+
+        async fn send(&mut self, tokens: Vec<L::Spanned>) -> Result<(), StreamError> {
+            self.tokens += tokens.len();
+            for token in tokens {
+                self.token_tx
+                    .send(token)
+                    .await
+                    .map_err(|_| StreamError::ChannelClosed)?;
+                self.yields.tick(1).await;
+            }
+            Ok(())
+        }
+    }
+
+    /// Byte budget for parsed nodes in flight between an [`AstStream`] and
+    /// its consumer.
+    ///
+    /// Channel capacity only bounds the number of nodes, which is a poor
+    /// proxy for memory when node sizes vary widely. Clones share the same
+    /// budget.
+    ///
+    /// A node larger than the whole budget is admitted once everything else
+    /// has been released, so a single oversized node can't stall the stream.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let budget = MemoryBudget::new(16 * 1024 * 1024);
+    /// let mut parser = AstStream::<JsonLine, _>::new(token_rx, ast_tx)
+    ///     .with_memory_budget(budget.clone());
+    ///
+    /// while let Some(line) = ast_rx.recv().await {
+    ///     process(&line);
+    ///     budget.release_node(&line);
+    /// }
+    /// ```
+    #[derive(Debug, Clone)]
+    pub struct MemoryBudget {
+        permits: std::sync::Arc<::tokio::sync::Semaphore>,
+        max_bytes: usize,
+    }
+
+    impl MemoryBudget {
+        /// Create a budget of `max_bytes`, capped at `u32::MAX`.
+        pub fn new(max_bytes: usize) -> Self {
+            let max_bytes = max_bytes.clamp(1, u32::MAX as usize);
+            Self {
+                permits: std::sync::Arc::new(::tokio::sync::Semaphore::new(max_bytes)),
+                max_bytes,
             }
+        }
+
+        /// The total budget in bytes.
+        #[inline]
+        pub fn max_bytes(&self) -> usize {
+            self.max_bytes
+        }
 
+        /// Bytes currently acquired and not yet released.
+        #[inline]
+        pub fn in_use(&self) -> usize {
+            self.max_bytes
+                .saturating_sub(self.permits.available_permits())
+        }
+
+        /// Wait until `bytes` fit in the budget, then reserve them.
+        pub async fn acquire(&self, bytes: usize) -> Result<(), StreamError> {
+            let bytes = self.clamp(bytes);
+            self.permits
+                .acquire_many(bytes)
+                .await
+                .map_err(|_| StreamError::ChannelClosed)?
+                .forget();
             Ok(())
         }
+
+        /// Return `bytes` previously passed to [`acquire`](Self::acquire).
+        pub fn release(&self, bytes: usize) {
+            self.permits.add_permits(self.clamp(bytes) as usize);
+        }
+
+        /// Return the bytes reserved for `node` by [`AstStream`].
+        #[inline]
+        pub fn release_node<T: crate::EstimateSize>(&self, node: &T) {
+            self.release(node.estimated_size());
+        }
+
+        fn clamp(&self, bytes: usize) -> u32 {
+            // `max_bytes` fits in a `u32` by construction.
+            bytes.min(self.max_bytes) as u32
+        }
+    }
+
+    /// Captured `EstimateSize::estimated_size`, so only `with_memory_budget`
+    /// needs the bound.
+    type NodeSize<T> = fn(&T) -> usize;
+
+    /// Forwards a node's diagnostics, given its index; captured by
+    /// `with_diagnostics` so `AstStream` needn't name the span type.
+    type DiagnosticSink<T> = Box<dyn FnMut(usize, &T) + Send>;
+
+    /// Where an `AstStream` sends its nodes.
+    enum NodeSink<T, Tok> {
+        Plain(mpsc::Sender<T>),
+        Emitted(mpsc::Sender<Emitted<T>>, TokenRange<Tok>),
     }
 
     /// Async AST stream that receives tokens and emits parsed nodes.
+    ///
+    /// `Tok` is whatever the channel carries, typically `Spanned<Token>`;
+    /// it only needs to implement `AsRef<T::Token>`.
     pub struct AstStream<T, Tok>
     where
-        T: IncrementalParse<Token = Tok>,
-        Tok: Clone,
+        T: IncrementalParse,
     {
         token_rx: mpsc::Receiver<Tok>,
-        ast_tx: mpsc::Sender<T>,
+        ast_tx: NodeSink<T, Tok>,
         token_buffer: Vec<Tok>,
         checkpoint: ParseCheckpoint,
         config: StreamConfig,
+        yields: YieldBudget,
+        memory: Option<(MemoryBudget, NodeSize<T>)>,
+        diagnostics: Option<DiagnosticSink<T>>,
+        /// Nodes sent so far, i.e. the index of the next one.
+        nodes: usize,
     }
 
     impl<T, Tok> AstStream<T, Tok>
     where
-        T: IncrementalParse<Token = Tok>,
-        Tok: Clone + AsRef<Tok>,
+        T: IncrementalParse,
+        Tok: AsRef<T::Token>,
     {
         /// Create a new AST stream.
         pub fn new(token_rx: mpsc::Receiver<Tok>, ast_tx: mpsc::Sender<T>) -> Self {
@@ -930,16 +1933,109 @@ pub mod tokio_impl {
             token_rx: mpsc::Receiver<Tok>,
             ast_tx: mpsc::Sender<T>,
             config: StreamConfig,
+        ) -> Self {
+            Self::with_sink(token_rx, NodeSink::Plain(ast_tx), config)
+        }
+
+        /// Create an AST stream that sends each node as an [`Emitted`],
+        /// with its index and byte range in the whole input.
+        ///
+        /// # Example
+        /// ```ignore
+        /// let mut parser = AstStream::<JsonLine, _>::emitted(token_rx, ast_tx);
+        /// tokio::spawn(async move { parser.run().await });
+        ///
+        /// while let Some(line) = ast_rx.recv().await {
+        ///     audit.record(line.index, line.range.clone());
+        /// }
+        /// ```
+        pub fn emitted(token_rx: mpsc::Receiver<Tok>, ast_tx: mpsc::Sender<Emitted<T>>) -> Self
+        where
+            Tok: SpannedLike<T::Token>,
+        {
+            Self::emitted_with_config(token_rx, ast_tx, StreamConfig::default())
+        }
+
+        /// [`emitted`](Self::emitted) with custom configuration.
+        pub fn emitted_with_config(
+            token_rx: mpsc::Receiver<Tok>,
+            ast_tx: mpsc::Sender<Emitted<T>>,
+            config: StreamConfig,
+        ) -> Self
+        where
+            Tok: SpannedLike<T::Token>,
+        {
+            let sink = NodeSink::Emitted(ast_tx, byte_range::<Tok, T::Token>);
+            Self::with_sink(token_rx, sink, config)
+        }
+
+        fn with_sink(
+            token_rx: mpsc::Receiver<Tok>,
+            ast_tx: NodeSink<T, Tok>,
+            config: StreamConfig,
         ) -> Self {
             Self {
                 token_rx,
                 ast_tx,
                 token_buffer: Vec::with_capacity(config.token_buffer_size),
                 checkpoint: ParseCheckpoint::default(),
+                yields: YieldBudget::new(config.yield_every),
                 config,
+                memory: None,
+                diagnostics: None,
+                nodes: 0,
             }
         }
 
+        /// Bound the nodes sent but not yet released by their estimated size.
+        ///
+        /// Before sending a node, the stream waits until `budget` has room for
+        /// its [`EstimateSize::estimated_size`]; the consumer gives the bytes
+        /// back with [`MemoryBudget::release_node`] once it is done with it.
+        pub fn with_memory_budget(mut self, budget: MemoryBudget) -> Self
+        where
+            T: crate::EstimateSize,
+        {
+            self.memory = Some((budget, T::estimated_size));
+            self
+        }
+
+        /// Send each node's [`NodeDiagnostics`] on `diagnostics_tx`, tagged
+        /// with the node's index, before sending the node itself.
+        ///
+        /// The channel is unbounded so diagnostics never hold up parsing.
+        /// If its receiver is dropped, diagnostics are discarded and
+        /// parsing carries on.
+        ///
+        /// # Example
+        /// ```ignore
+        /// let (diag_tx, mut diag_rx) = mpsc::unbounded_channel();
+        /// let mut parser = AstStream::<Record, _>::new(token_rx, ast_tx)
+        ///     .with_diagnostics(diag_tx);
+        ///
+        /// while let Some(diag) = diag_rx.recv().await {
+        ///     println!("{}", diag.to_json());
+        /// }
+        /// ```
+        pub fn with_diagnostics(
+            mut self,
+            diagnostics_tx: mpsc::UnboundedSender<NodeDiagnostic<T::Span>>,
+        ) -> Self
+        where
+            T: NodeDiagnostics,
+            T::Span: Send + 'static,
+        {
+            self.diagnostics = Some(Box::new(move |node, value: &T| {
+                for mut diagnostic in value.diagnostics() {
+                    diagnostic.node = node;
+                    if diagnostics_tx.send(diagnostic).is_err() {
+                        break;
+                    }
+                }
+            }));
+            self
+        }
+
         /// Run the parser until the token stream is exhausted.
         pub async fn run(&mut self) -> Result<(), StreamError> {
             loop {
@@ -960,6 +2056,7 @@ pub mod tokio_impl {
                         if T::can_parse(&self.token_buffer, &self.checkpoint) {
                             self.try_parse().await?;
                         }
+                        self.yields.tick(1).await;
                     }
                     None => {
                         // Channel closed - try final parse
@@ -982,14 +2079,36 @@ pub mod tokio_impl {
             loop {
                 match T::parse_incremental(&self.token_buffer, &self.checkpoint) {
                     Ok((Some(node), new_checkpoint)) => {
+                        let consumed = self.checkpoint.cursor..new_checkpoint.cursor;
                         self.checkpoint = new_checkpoint;
-                        self.ast_tx
-                            .send(node)
-                            .await
-                            .map_err(|_| StreamError::ChannelClosed)?;
+                        if let Some((budget, size_of)) = &self.memory {
+                            budget.acquire(size_of(&node)).await?;
+                        }
+                        if let Some(sink) = &mut self.diagnostics {
+                            sink(self.nodes, &node);
+                        }
+                        let sent = match &self.ast_tx {
+                            NodeSink::Plain(tx) => tx.send(node).await.is_ok(),
+                            NodeSink::Emitted(tx, range_of) => {
+                                let tokens = self.token_buffer.get(consumed).unwrap_or_default();
+                                let emitted = Emitted {
+                                    value: node,
+                                    range: range_of(tokens),
+                                    index: self.nodes,
+                                };
+                                tx.send(emitted).await.is_ok()
+                            }
+                        };
+                        if !sent {
+                            return Err(StreamError::ChannelClosed);
+                        }
+                        self.nodes += 1;
                     }
-                    Ok((None, _)) => {
-                        // Need more tokens
+                    Ok((None, checkpoint)) => {
+                        // Need more tokens; keep any skipped without a node
+                        if checkpoint.cursor > self.checkpoint.cursor {
+                            self.checkpoint = checkpoint;
+                        }
                         break;
                     }
                     Err(e) => {
@@ -1017,6 +2136,258 @@ pub mod tokio_impl {
     }
 }
 
+#[cfg(feature = "tokio")]
+pub mod supervisor {
+    //! Supervised lexer and parser tasks.
+    //!
+    //! A [`Supervisor`] spawns each task from a factory, restarts it after
+    //! recoverable errors according to a [`RestartPolicy`], and reports
+    //! every start, restart and exit on one [`SupervisorStatus`] channel.
+    //!
+    //! ```ignore
+    //! let mut supervisor = Supervisor::new(RestartPolicy::on_error(3));
+    //! let mut status = supervisor.status();
+    //! supervisor.spawn_lexer(move |_attempt| lex_into(token_tx.clone()));
+    //! supervisor.spawn_parser(move |_attempt| parse_from(shared_rx.clone()));
+    //!
+    //! while let Some(event) = status.recv().await {
+    //!     log::info!("{event}");
+    //! }
+    //! supervisor.join().await?;
+    //! ```
+
+    use super::*;
+    use ::tokio::sync::mpsc;
+    use ::tokio::task::JoinHandle;
+    use std::time::Duration;
+
+    /// Which pipeline stage a supervised task runs.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum TaskKind {
+        Lexer,
+        Parser,
+    }
+
+    impl fmt::Display for TaskKind {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                TaskKind::Lexer => write!(f, "lexer"),
+                TaskKind::Parser => write!(f, "parser"),
+            }
+        }
+    }
+
+    /// When and how often a failed task is restarted.
+    #[derive(Debug, Clone, Copy)]
+    pub struct RestartPolicy {
+        /// Restarts allowed per task before it is reported as failed.
+        pub max_restarts: usize,
+        /// Delay before each restart.
+        pub backoff: Duration,
+        /// Which errors are worth a restart. Default:
+        /// [`StreamError::is_recoverable`].
+        pub restart_on: fn(&StreamError) -> bool,
+    }
+
+    impl RestartPolicy {
+        /// Never restart; the first error fails the task.
+        pub const fn never() -> Self {
+            Self::on_error(0)
+        }
+
+        /// Restart after recoverable errors, up to `max_restarts` times.
+        pub const fn on_error(max_restarts: usize) -> Self {
+            Self {
+                max_restarts,
+                backoff: Duration::ZERO,
+                restart_on: StreamError::is_recoverable,
+            }
+        }
+
+        /// Wait `backoff` before each restart.
+        pub const fn with_backoff(mut self, backoff: Duration) -> Self {
+            self.backoff = backoff;
+            self
+        }
+
+        /// Decide which errors are restarted.
+        pub const fn with_restart_on(mut self, restart_on: fn(&StreamError) -> bool) -> Self {
+            self.restart_on = restart_on;
+            self
+        }
+
+        fn should_restart(&self, error: &StreamError, restarts: usize) -> bool {
+            restarts < self.max_restarts && (self.restart_on)(error)
+        }
+    }
+
+    impl Default for RestartPolicy {
+        fn default() -> Self {
+            Self::never()
+        }
+    }
+
+    /// A lifecycle event of a supervised task.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum SupervisorStatus {
+        /// The task started; `attempt` is 0 for the first run.
+        Started { task: TaskKind, attempt: usize },
+        /// The task failed with `error` and will be started again.
+        Restarting {
+            task: TaskKind,
+            attempt: usize,
+            error: StreamError,
+        },
+        /// The task completed successfully.
+        Finished { task: TaskKind },
+        /// The task failed with `error` and will not be restarted.
+        Failed { task: TaskKind, error: StreamError },
+    }
+
+    impl fmt::Display for SupervisorStatus {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                SupervisorStatus::Started { task, attempt: 0 } => write!(f, "{task} started"),
+                SupervisorStatus::Started { task, attempt } => {
+                    write!(f, "{task} started (restart {attempt})")
+                }
+                SupervisorStatus::Restarting { task, error, .. } => {
+                    write!(f, "{task} restarting after: {error}")
+                }
+                SupervisorStatus::Finished { task } => write!(f, "{task} finished"),
+                SupervisorStatus::Failed { task, error } => write!(f, "{task} failed: {error}"),
+            }
+        }
+    }
+
+    /// Runs pipeline tasks, restarting them per a [`RestartPolicy`].
+    ///
+    /// Tasks are built by factories that receive the attempt number, so a
+    /// restart starts from fresh state. Anything that must survive a
+    /// restart, such as a channel receiver, has to be shared with the
+    /// factory (e.g. behind an `Arc<tokio::sync::Mutex<_>>`).
+    pub struct Supervisor {
+        policy: RestartPolicy,
+        status_tx: mpsc::UnboundedSender<SupervisorStatus>,
+        status_rx: Option<mpsc::UnboundedReceiver<SupervisorStatus>>,
+        tasks: Vec<(TaskKind, JoinHandle<Result<(), StreamError>>)>,
+    }
+
+    impl Supervisor {
+        pub fn new(policy: RestartPolicy) -> Self {
+            let (status_tx, status_rx) = mpsc::unbounded_channel();
+            Self {
+                policy,
+                status_tx,
+                status_rx: Some(status_rx),
+                tasks: Vec::new(),
+            }
+        }
+
+        /// The status channel for all tasks.
+        ///
+        /// It closes once the supervisor and every task are done. Taking it
+        /// a second time returns a closed receiver.
+        pub fn status(&mut self) -> mpsc::UnboundedReceiver<SupervisorStatus> {
+            self.status_rx
+                .take()
+                .unwrap_or_else(|| mpsc::unbounded_channel().1)
+        }
+
+        /// Spawn a supervised lexer task.
+        pub fn spawn_lexer<F, Fut>(&mut self, factory: F)
+        where
+            F: FnMut(usize) -> Fut + Send + 'static,
+            Fut: Future<Output = Result<(), StreamError>> + Send + 'static,
+        {
+            self.spawn(TaskKind::Lexer, factory);
+        }
+
+        /// Spawn a supervised parser task.
+        pub fn spawn_parser<F, Fut>(&mut self, factory: F)
+        where
+            F: FnMut(usize) -> Fut + Send + 'static,
+            Fut: Future<Output = Result<(), StreamError>> + Send + 'static,
+        {
+            self.spawn(TaskKind::Parser, factory);
+        }
+
+        /// Spawn a supervised task built by `factory(attempt)`.
+        pub fn spawn<F, Fut>(&mut self, task: TaskKind, mut factory: F)
+        where
+            F: FnMut(usize) -> Fut + Send + 'static,
+            Fut: Future<Output = Result<(), StreamError>> + Send + 'static,
+        {
+            let policy = self.policy;
+            let status = self.status_tx.clone();
+            let handle = ::tokio::spawn(async move {
+                let mut attempt = 0;
+                loop {
+                    // The receiver may be gone; the task runs regardless
+                    let _ = status.send(SupervisorStatus::Started { task, attempt });
+                    match factory(attempt).await {
+                        Ok(()) => {
+                            let _ = status.send(SupervisorStatus::Finished { task });
+                            return Ok(());
+                        }
+                        Err(error) if policy.should_restart(&error, attempt) => {
+                            attempt += 1;
+                            let _ = status.send(SupervisorStatus::Restarting {
+                                task,
+                                attempt,
+                                error,
+                            });
+                            if !policy.backoff.is_zero() {
+                                ::tokio::time::sleep(policy.backoff).await;
+                            }
+                        }
+                        Err(error) => {
+                            let _ = status.send(SupervisorStatus::Failed {
+                                task,
+                                error: error.clone(),
+                            });
+                            return Err(error);
+                        }
+                    }
+                }
+            });
+            self.tasks.push((task, handle));
+        }
+
+        /// Wait for every task, returning the first failure in spawn order.
+        ///
+        /// A task that panics counts as failed with a lex or parse error
+        /// naming the panic.
+        pub async fn join(self) -> Result<(), StreamError> {
+            let Self {
+                status_tx, tasks, ..
+            } = self;
+            let mut first_error = None;
+            for (task, handle) in tasks {
+                let result = match handle.await {
+                    Ok(result) => result,
+                    Err(join_error) => {
+                        let message = format!("{task} task panicked: {join_error}");
+                        let error = match task {
+                            TaskKind::Lexer => StreamError::LexError(message),
+                            TaskKind::Parser => StreamError::ParseError(message),
+                        };
+                        let _ = status_tx.send(SupervisorStatus::Failed {
+                            task,
+                            error: error.clone(),
+                        });
+                        Err(error)
+                    }
+                };
+                if let Err(error) = result {
+                    first_error.get_or_insert(error);
+                }
+            }
+            first_error.map_or(Ok(()), Err)
+        }
+    }
+}
+
 #[cfg(feature = "futures")]
 pub mod futures_impl {
     //! Futures-based async stream implementation (runtime-agnostic).
@@ -1026,24 +2397,27 @@ pub mod futures_impl {
     use futures_core::Stream;
 
     /// A stream adapter that yields parsed AST nodes.
+    ///
+    /// `Tok` is the stream's item type, typically `Spanned<Token>`; it only
+    /// needs to implement `AsRef<T::Token>`.
     pub struct ParseStream<S, T, Tok>
     where
         S: Stream<Item = Tok>,
-        T: IncrementalParse<Token = Tok>,
-        Tok: Clone,
+        T: IncrementalParse,
     {
         inner: S,
         token_buffer: Vec<Tok>,
         checkpoint: ParseCheckpoint,
         pending_node: Option<T>,
+        max_tokens_per_poll: usize,
         _marker: core::marker::PhantomData<T>,
     }
 
     impl<S, T, Tok> ParseStream<S, T, Tok>
     where
         S: Stream<Item = Tok>,
-        T: IncrementalParse<Token = Tok>,
-        Tok: Clone,
+        T: IncrementalParse,
+        Tok: AsRef<T::Token>,
     {
         /// Create a new parse stream wrapping a token stream.
         pub fn new(inner: S) -> Self {
@@ -1059,41 +2433,82 @@ pub mod futures_impl {
                 token_buffer: Vec::with_capacity(token_buffer_capacity),
                 checkpoint: ParseCheckpoint::default(),
                 pending_node: None,
+                max_tokens_per_poll: usize::MAX,
                 _marker: core::marker::PhantomData,
             }
         }
+
+        /// Take at most `max` tokens from the inner stream per `poll_next`.
+        ///
+        /// Once a poll has taken `max` tokens without completing a node, the
+        /// stream wakes itself and returns `Pending`, so a large record is
+        /// parsed across several polls and other tasks on the executor get
+        /// a turn in between. Unlimited by default; 0 is treated as 1.
+        pub fn with_max_tokens_per_poll(mut self, max: usize) -> Self {
+            self.max_tokens_per_poll = max.max(1);
+            self
+        }
     }
 
-    impl<S, T, Tok> Stream for ParseStream<S, T, Tok>
+    /// A node and the indices of the buffered tokens it was parsed from.
+    type Consumed<T> = (T, Range<usize>);
+
+    impl<S, T, Tok> ParseStream<S, T, Tok>
     where
         S: Stream<Item = Tok> + Unpin,
-        T: IncrementalParse<Token = Tok> + Unpin,
-        Tok: Clone + AsRef<Tok> + Unpin,
+        T: IncrementalParse + Unpin,
+        Tok: AsRef<T::Token> + Unpin,
     {
-        type Item = Result<T, StreamError>;
+        /// Yield each node as an [`Emitted`], with its index and byte range
+        /// in the whole input.
+        pub fn emitted(self) -> EmittedParseStream<S, T, Tok>
+        where
+            Tok: SpannedLike<T::Token>,
+        {
+            EmittedParseStream {
+                inner: self,
+                index: 0,
+            }
+        }
 
-        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        /// Poll for the next node and the indices of the buffered tokens it
+        /// was parsed from.
+        fn poll_node(
+            &mut self,
+            cx: &mut Context<'_>,
+        ) -> Poll<Option<Result<Consumed<T>, StreamError>>> {
             // Return pending node if we have one
             if let Some(node) = self.pending_node.take() {
-                return Poll::Ready(Some(Ok(node)));
+                let at = self.checkpoint.cursor;
+                return Poll::Ready(Some(Ok((node, at..at))));
             }
 
             // Try to get more tokens
-            let this = self.get_mut();
+            let mut taken = 0;
             loop {
-                match Pin::new(&mut this.inner).poll_next(cx) {
+                if taken == self.max_tokens_per_poll {
+                    // Out of budget for this poll; resume on the next one
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
+                match Pin::new(&mut self.inner).poll_next(cx) {
                     Poll::Ready(Some(token)) => {
-                        this.token_buffer.push(token);
+                        taken += 1;
+                        self.token_buffer.push(token);
 
                         // Try to parse
-                        if T::can_parse(&this.token_buffer, &this.checkpoint) {
-                            match T::parse_incremental(&this.token_buffer, &this.checkpoint) {
+                        if T::can_parse(&self.token_buffer, &self.checkpoint) {
+                            match T::parse_incremental(&self.token_buffer, &self.checkpoint) {
                                 Ok((Some(node), new_checkpoint)) => {
-                                    this.checkpoint = new_checkpoint;
-                                    return Poll::Ready(Some(Ok(node)));
+                                    return Poll::Ready(Some(Ok(
+                                        self.advance(node, new_checkpoint)
+                                    )));
                                 }
-                                Ok((None, _)) => {
-                                    // Need more tokens
+                                Ok((None, checkpoint)) => {
+                                    // Need more tokens; keep any skipped without a node
+                                    if checkpoint.cursor > self.checkpoint.cursor {
+                                        self.checkpoint = checkpoint;
+                                    }
                                     continue;
                                 }
                                 Err(e) => {
@@ -1106,13 +2521,19 @@ pub mod futures_impl {
                     }
                     Poll::Ready(None) => {
                         // Stream ended - try final parse
-                        if this.checkpoint.cursor < this.token_buffer.len() {
-                            match T::parse_incremental(&this.token_buffer, &this.checkpoint) {
+                        while self.checkpoint.cursor < self.token_buffer.len() {
+                            match T::parse_incremental(&self.token_buffer, &self.checkpoint) {
                                 Ok((Some(node), new_checkpoint)) => {
-                                    this.checkpoint = new_checkpoint;
-                                    return Poll::Ready(Some(Ok(node)));
+                                    return Poll::Ready(Some(Ok(
+                                        self.advance(node, new_checkpoint)
+                                    )));
+                                }
+                                Ok((None, checkpoint))
+                                    if checkpoint.cursor > self.checkpoint.cursor =>
+                                {
+                                    self.checkpoint = checkpoint;
                                 }
-                                Ok((None, _)) if this.token_buffer.is_empty() => {
+                                Ok((None, _)) if self.token_buffer.is_empty() => {
                                     return Poll::Ready(None);
                                 }
                                 Ok((None, _)) => {
@@ -1133,5 +2554,65 @@ pub mod futures_impl {
                 }
             }
         }
+
+        fn advance(&mut self, node: T, checkpoint: ParseCheckpoint) -> Consumed<T> {
+            let consumed = self.checkpoint.cursor..checkpoint.cursor;
+            self.checkpoint = checkpoint;
+            (node, consumed)
+        }
+    }
+
+    impl<S, T, Tok> Stream for ParseStream<S, T, Tok>
+    where
+        S: Stream<Item = Tok> + Unpin,
+        T: IncrementalParse + Unpin,
+        Tok: AsRef<T::Token> + Unpin,
+    {
+        type Item = Result<T, StreamError>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            self.get_mut()
+                .poll_node(cx)
+                .map(|item| item.map(|result| result.map(|(node, _)| node)))
+        }
+    }
+
+    /// A [`ParseStream`] yielding [`Emitted`] nodes, from
+    /// [`ParseStream::emitted`].
+    pub struct EmittedParseStream<S, T, Tok>
+    where
+        S: Stream<Item = Tok>,
+        T: IncrementalParse,
+    {
+        inner: ParseStream<S, T, Tok>,
+        index: usize,
+    }
+
+    impl<S, T, Tok> Stream for EmittedParseStream<S, T, Tok>
+    where
+        S: Stream<Item = Tok> + Unpin,
+        T: IncrementalParse + Unpin,
+        Tok: AsRef<T::Token> + SpannedLike<T::Token> + Unpin,
+    {
+        type Item = Result<Emitted<T>, StreamError>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = self.get_mut();
+            let item = match this.inner.poll_node(cx) {
+                Poll::Ready(Some(Ok(item))) => item,
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            };
+            let (value, consumed) = item;
+            let tokens = this.inner.token_buffer.get(consumed).unwrap_or_default();
+            let emitted = Emitted {
+                value,
+                range: byte_range::<Tok, T::Token>(tokens),
+                index: this.index,
+            };
+            this.index += 1;
+            Poll::Ready(Some(Ok(emitted)))
+        }
     }
 }
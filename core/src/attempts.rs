@@ -0,0 +1,489 @@
+use std::fmt::{self, Write as _};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Records speculative parse attempts as a tree, to show why a grammar
+/// chose (or rejected) an alternative.
+///
+/// Generated streams record into a tree attached with
+/// `with_attempt_tree()`: every `stream.attempt(label, ..)` adds a node with
+/// where it started, whether it was accepted and how many tokens it
+/// consumed. Attempts started inside another attempt become its children.
+/// The tree is shared by a stream and its forks.
+///
+/// Dump it with [`to_json`](Self::to_json), as a collapsible HTML report
+/// with [`to_html`](Self::to_html), or as indented text with `Display`.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut stream = TokenStream::lex(source)?.with_attempt_tree();
+/// let result = stream.parse::<Document>();
+/// if let Some(tree) = stream.attempt_tree() {
+///     std::fs::write("attempts.html", tree.to_html())?;
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct AttemptTree {
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    attempts: Vec<Attempt>,
+    roots: Vec<AttemptId>,
+    /// Attempts entered but not yet finished, innermost last.
+    open: Vec<AttemptId>,
+}
+
+/// Index of an [`Attempt`] in its [`AttemptTree`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AttemptId(pub usize);
+
+/// One speculative branch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attempt {
+    /// What was attempted, e.g. the alternative's name.
+    pub label: String,
+    /// Byte offset of the first token the attempt saw.
+    pub offset: usize,
+    /// How the attempt ended.
+    pub outcome: AttemptOutcome,
+    /// Attempts made while this one was running.
+    pub children: Vec<AttemptId>,
+}
+
+/// How an [`Attempt`] ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttemptOutcome {
+    /// Still running, or unwound by a panic.
+    Pending,
+    /// Succeeded; the stream advanced past `tokens` significant tokens.
+    Accepted {
+        /// Significant tokens consumed.
+        tokens: usize,
+    },
+    /// Failed; the stream was left where the attempt started.
+    Rejected {
+        /// Significant tokens read, including the one that failed.
+        tokens: usize,
+        /// The parse error.
+        error: String,
+    },
+}
+
+/// An alternative that read more than one token before it was rejected,
+/// from [`AttemptTree::conflicts`].
+///
+/// One token of lookahead can't rule it out, so choosing between it and its
+/// siblings needs `attempt`/backtracking, or a grammar change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// Label of the rejected attempt.
+    pub label: String,
+    /// Byte offset it started at.
+    pub offset: usize,
+    /// Significant tokens it read before failing: the lookahead needed to
+    /// reject it.
+    pub lookahead: usize,
+    /// Label of the attempt accepted at the same offset instead, if any.
+    pub chosen: Option<String>,
+}
+
+/// ``args @ 4 needs 2 tokens of lookahead (chose `ident`)``
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} @ {} needs {} tokens of lookahead",
+            self.label, self.offset, self.lookahead
+        )?;
+        if let Some(chosen) = &self.chosen {
+            write!(f, " (chose `{chosen}`)")?;
+        }
+        Ok(())
+    }
+}
+
+impl AttemptOutcome {
+    fn describe(&self) -> String {
+        match self {
+            Self::Pending => "pending".to_string(),
+            Self::Accepted { tokens } => format!("accepted after {tokens} tokens"),
+            Self::Rejected { tokens, error } => {
+                format!("rejected after {tokens} tokens: {error}")
+            }
+        }
+    }
+}
+
+impl AttemptTree {
+    /// Creates an empty tree.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Start an attempt at byte `offset`, as a child of the innermost
+    /// unfinished attempt. Pair with [`finish`](Self::finish).
+    pub fn enter(&self, label: impl Into<String>, offset: usize) -> AttemptId {
+        let mut state = self.state();
+        let id = AttemptId(state.attempts.len());
+        state.attempts.push(Attempt {
+            label: label.into(),
+            offset,
+            outcome: AttemptOutcome::Pending,
+            children: Vec::new(),
+        });
+        match state.open.last().copied() {
+            Some(parent) => state.attempts[parent.0].children.push(id),
+            None => state.roots.push(id),
+        }
+        state.open.push(id);
+        id
+    }
+
+    /// Record how attempt `id` ended. Attempts entered after it and left
+    /// unfinished are closed too, staying [`Pending`](AttemptOutcome::Pending).
+    pub fn finish(&self, id: AttemptId, outcome: AttemptOutcome) {
+        let mut state = self.state();
+        if let Some(pos) = state.open.iter().rposition(|&open| open == id) {
+            state.open.truncate(pos);
+        }
+        if let Some(attempt) = state.attempts.get_mut(id.0) {
+            attempt.outcome = outcome;
+        }
+    }
+
+    /// A copy of every attempt, indexed by [`AttemptId`].
+    pub fn attempts(&self) -> Vec<Attempt> {
+        self.state().attempts.clone()
+    }
+
+    /// Attempts made outside any other attempt, in order.
+    pub fn roots(&self) -> Vec<AttemptId> {
+        self.state().roots.clone()
+    }
+
+    /// Number of attempts recorded.
+    pub fn len(&self) -> usize {
+        self.state().attempts.len()
+    }
+
+    /// Returns `true` if nothing was attempted.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Rejected attempts that read more than one token, in the order they
+    /// were made: the places a single-token `peek` can't decide and an
+    /// `attempt` is needed.
+    ///
+    /// Attempts rejected after one token could be a `peek` instead. Run a
+    /// corpus through a stream with an attempt tree to collect them.
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        let state = self.state();
+        let groups = std::iter::once(&state.roots)
+            .chain(state.attempts.iter().map(|attempt| &attempt.children));
+        let mut conflicts: Vec<(AttemptId, Conflict)> = Vec::new();
+        for siblings in groups {
+            let attempts: Vec<_> = siblings
+                .iter()
+                .filter_map(|&id| Some((id, state.attempts.get(id.0)?)))
+                .collect();
+            for &(id, attempt) in &attempts {
+                let AttemptOutcome::Rejected { tokens, .. } = attempt.outcome else {
+                    continue;
+                };
+                if tokens <= 1 {
+                    continue;
+                }
+                let chosen = attempts.iter().find(|(_, other)| {
+                    other.offset == attempt.offset
+                        && matches!(other.outcome, AttemptOutcome::Accepted { .. })
+                });
+                conflicts.push((
+                    id,
+                    Conflict {
+                        label: attempt.label.clone(),
+                        offset: attempt.offset,
+                        lookahead: tokens,
+                        chosen: chosen.map(|(_, other)| other.label.clone()),
+                    },
+                ));
+            }
+        }
+        conflicts.sort_by_key(|(id, _)| id.0);
+        conflicts
+            .into_iter()
+            .map(|(_, conflict)| conflict)
+            .collect()
+    }
+
+    /// Discard every recorded attempt.
+    pub fn clear(&self) {
+        *self.state() = State::default();
+    }
+
+    /// The tree as JSON: an array of root attempts, each
+    /// `{"label", "offset", "outcome", "tokens", "error"?, "children"}`.
+    pub fn to_json(&self) -> String {
+        let state = self.state();
+        let mut out = String::new();
+        write_json_list(&state.attempts, &state.roots, &mut out);
+        out
+    }
+
+    /// The tree as a standalone HTML page of nested, collapsible
+    /// `<details>` elements; rejected attempts start collapsed.
+    pub fn to_html(&self) -> String {
+        let state = self.state();
+        let mut out = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Parse attempts</title>\n<style>\n\
+             body { font-family: monospace; }\n\
+             details { margin-left: 1.5em; }\n\
+             .accepted > summary { color: #1a7f37; }\n\
+             .rejected > summary { color: #cf222e; }\n\
+             .pending > summary { color: #9a6700; }\n\
+             </style>\n</head>\n<body>\n",
+        );
+        for &id in &state.roots {
+            write_html(&state.attempts, id, &mut out);
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+/// Indented text, one attempt per line.
+impl fmt::Display for AttemptTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn write_node(
+            attempts: &[Attempt],
+            id: AttemptId,
+            depth: usize,
+            f: &mut fmt::Formatter<'_>,
+        ) -> fmt::Result {
+            let Some(attempt) = attempts.get(id.0) else {
+                return Ok(());
+            };
+            writeln!(
+                f,
+                "{:indent$}{} @ {}: {}",
+                "",
+                attempt.label,
+                attempt.offset,
+                attempt.outcome.describe(),
+                indent = depth * 2
+            )?;
+            for &child in &attempt.children {
+                write_node(attempts, child, depth + 1, f)?;
+            }
+            Ok(())
+        }
+
+        let state = self.state();
+        for &id in &state.roots {
+            write_node(&state.attempts, id, 0, f)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_json_list(attempts: &[Attempt], ids: &[AttemptId], out: &mut String) {
+    out.push('[');
+    for (i, &id) in ids.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let Some(attempt) = attempts.get(id.0) else {
+            out.push_str("null");
+            continue;
+        };
+        out.push_str("{\"label\":");
+        write_json_str(&attempt.label, out);
+        let _ = write!(out, ",\"offset\":{}", attempt.offset);
+        match &attempt.outcome {
+            AttemptOutcome::Pending => out.push_str(",\"outcome\":\"pending\",\"tokens\":0"),
+            AttemptOutcome::Accepted { tokens } => {
+                let _ = write!(out, ",\"outcome\":\"accepted\",\"tokens\":{tokens}");
+            }
+            AttemptOutcome::Rejected { tokens, error } => {
+                let _ = write!(out, ",\"outcome\":\"rejected\",\"tokens\":{tokens}");
+                out.push_str(",\"error\":");
+                write_json_str(error, out);
+            }
+        }
+        out.push_str(",\"children\":");
+        write_json_list(attempts, &attempt.children, out);
+        out.push('}');
+    }
+    out.push(']');
+}
+
+pub(crate) fn write_json_str(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn write_html(attempts: &[Attempt], id: AttemptId, out: &mut String) {
+    let Some(attempt) = attempts.get(id.0) else {
+        return;
+    };
+    let (class, open) = match attempt.outcome {
+        AttemptOutcome::Pending => ("pending", " open"),
+        AttemptOutcome::Accepted { .. } => ("accepted", " open"),
+        AttemptOutcome::Rejected { .. } => ("rejected", ""),
+    };
+    let _ = write!(out, "<details class=\"{class}\"{open}><summary>");
+    write_html_escaped(&attempt.label, out);
+    let _ = write!(out, " @ {}: ", attempt.offset);
+    write_html_escaped(&attempt.outcome.describe(), out);
+    out.push_str("</summary>\n");
+    for &child in &attempt.children {
+        write_html(attempts, child, out);
+    }
+    out.push_str("</details>\n");
+}
+
+fn write_html_escaped(s: &str, out: &mut String) {
+    for c in s.chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> AttemptTree {
+        let tree = AttemptTree::new();
+        let call = tree.enter("call", 0);
+        let args = tree.enter("args", 4);
+        tree.finish(
+            args,
+            AttemptOutcome::Rejected {
+                tokens: 2,
+                error: "expected `)`".into(),
+            },
+        );
+        tree.finish(
+            call,
+            AttemptOutcome::Rejected {
+                tokens: 1,
+                error: "expected `)`".into(),
+            },
+        );
+        let ident = tree.enter("ident", 0);
+        tree.finish(ident, AttemptOutcome::Accepted { tokens: 1 });
+        tree
+    }
+
+    #[test]
+    fn test_nesting() {
+        let tree = sample();
+        assert_eq!(tree.roots(), [AttemptId(0), AttemptId(2)]);
+        let attempts = tree.attempts();
+        assert_eq!(attempts[0].children, [AttemptId(1)]);
+        assert_eq!(attempts[2].outcome, AttemptOutcome::Accepted { tokens: 1 });
+        assert_eq!(
+            tree.to_string(),
+            "call @ 0: rejected after 1 tokens: expected `)`\n  \
+             args @ 4: rejected after 2 tokens: expected `)`\n\
+             ident @ 0: accepted after 1 tokens\n"
+        );
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let tree = sample();
+        let cast = tree.enter("cast", 0);
+        tree.finish(
+            cast,
+            AttemptOutcome::Rejected {
+                tokens: 3,
+                error: "expected type".into(),
+            },
+        );
+        let conflicts = tree.conflicts();
+        assert_eq!(
+            conflicts,
+            [
+                Conflict {
+                    label: "args".into(),
+                    offset: 4,
+                    lookahead: 2,
+                    chosen: None,
+                },
+                Conflict {
+                    label: "cast".into(),
+                    offset: 0,
+                    lookahead: 3,
+                    chosen: Some("ident".into()),
+                },
+            ]
+        );
+        assert_eq!(
+            conflicts[1].to_string(),
+            "cast @ 0 needs 3 tokens of lookahead (chose `ident`)"
+        );
+    }
+
+    #[test]
+    fn test_unfinished_children_are_closed() {
+        let tree = AttemptTree::new();
+        let outer = tree.enter("outer", 0);
+        tree.enter("abandoned", 1);
+        tree.finish(outer, AttemptOutcome::Accepted { tokens: 3 });
+        let next = tree.enter("next", 5);
+        assert_eq!(tree.roots(), [outer, next]);
+        assert_eq!(tree.attempts()[1].outcome, AttemptOutcome::Pending);
+    }
+
+    #[test]
+    fn test_json() {
+        assert_eq!(
+            sample().to_json(),
+            r#"[{"label":"call","offset":0,"outcome":"rejected","tokens":1,"error":"expected `)`","children":[{"label":"args","offset":4,"outcome":"rejected","tokens":2,"error":"expected `)`","children":[]}]},{"label":"ident","offset":0,"outcome":"accepted","tokens":1,"children":[]}]"#
+        );
+
+        let tree = AttemptTree::new();
+        tree.enter("quote \"\n", 0);
+        assert!(tree.to_json().contains(r#""quote \"\n""#));
+    }
+
+    #[test]
+    fn test_html() {
+        let html = sample().to_html();
+        assert!(html.contains(
+            "<details class=\"rejected\"><summary>call @ 0: rejected after 1 tokens: expected `)`</summary>"
+        ));
+        assert!(html.contains("<details class=\"accepted\" open><summary>ident"));
+
+        let tree = AttemptTree::new();
+        tree.enter("<T>", 0);
+        assert!(tree.to_html().contains("&lt;T&gt;"));
+    }
+}
@@ -0,0 +1,218 @@
+//! Compact binary encoding of ASTs, for caching parse results on disk.
+//!
+//! [`to_bytes`] and [`from_bytes`] use [postcard](https://docs.rs/postcard):
+//! integers are varints and structs are written without field names, so an
+//! encoded AST is a fraction of the size of its JSON. The same `Serialize`
+//! impls work with bincode and other non-self-describing formats.
+//!
+//! Spans dominate the size of an encoded tree. With `packed_spans: true`,
+//! `parser_kit!` serializes each span as a [`PackedSpan`]: a start offset and
+//! a length, both varints, so most spans take 3-5 bytes.
+//!
+//! ```ignore
+//! synkit::parser_kit! {
+//!     // ...
+//!     packed_spans: true,
+//!     token_derives: [Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize],
+//! }
+//!
+//! let bytes = synkit::binary::to_bytes(&doc)?;
+//! let doc: Document = synkit::binary::from_bytes(&bytes)?;
+//! ```
+
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+
+use crate::SpanLike;
+
+/// Why an AST couldn't be encoded or decoded.
+#[derive(Debug)]
+pub enum Error {
+    /// The data is malformed, truncated, or doesn't match the type.
+    Postcard(postcard::Error),
+    /// The value was decoded, but this many bytes followed it.
+    TrailingBytes(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Postcard(err) => write!(f, "binary AST: {}", err),
+            Error::TrailingBytes(n) => write!(f, "binary AST: {} trailing bytes", n),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Postcard(err) => Some(err),
+            Error::TrailingBytes(_) => None,
+        }
+    }
+}
+
+impl From<postcard::Error> for Error {
+    fn from(err: postcard::Error) -> Self {
+        Error::Postcard(err)
+    }
+}
+
+/// Encode `value`.
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Error> {
+    postcard::to_stdvec(value).map_err(Error::Postcard)
+}
+
+/// Decode a `T` from all of `bytes`.
+pub fn from_bytes<'a, T: Deserialize<'a>>(bytes: &'a [u8]) -> Result<T, Error> {
+    let (value, rest) = postcard::take_from_bytes(bytes)?;
+    if !rest.is_empty() {
+        return Err(Error::TrailingBytes(rest.len()));
+    }
+    Ok(value)
+}
+
+/// A span as its start offset and length, or `None` for a call-site span.
+///
+/// Lengths are smaller than end offsets, so they take fewer varint bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct PackedSpan(pub Option<(usize, usize)>);
+
+impl PackedSpan {
+    /// Pack `span`.
+    #[inline]
+    pub fn pack<S: SpanLike>(span: &S) -> Self {
+        if span.is_call_site() {
+            Self(None)
+        } else {
+            Self(Some((span.start(), span.len())))
+        }
+    }
+
+    /// The span this was packed from.
+    #[inline]
+    pub fn unpack<S: SpanLike>(self) -> S {
+        match self.0 {
+            Some((start, len)) => S::new(start, start + len),
+            None => S::call_site(),
+        }
+    }
+}
+
+impl Serialize for PackedSpan {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PackedSpan {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let packed = Option::<(usize, usize)>::deserialize(deserializer)?;
+        if let Some((start, len)) = packed.filter(|&(start, len)| start.checked_add(len).is_none())
+        {
+            return Err(de::Error::custom(format_args!(
+                "span {}+{} overflows",
+                start, len
+            )));
+        }
+        Ok(Self(packed))
+    }
+}
+
+/// Serializes any [`SpanLike`] as a [`PackedSpan`], for
+/// `#[serde(with = "synkit::binary::span")]` on span fields of
+/// hand-written types.
+pub mod span {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::PackedSpan;
+    use crate::SpanLike;
+
+    /// Serialize `span` packed.
+    pub fn serialize<T: SpanLike, S: Serializer>(
+        span: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        PackedSpan::pack(span).serialize(serializer)
+    }
+
+    /// Deserialize a packed span.
+    pub fn deserialize<'de, T: SpanLike, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<T, D::Error> {
+        PackedSpan::deserialize(deserializer).map(PackedSpan::unpack)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum TestSpan {
+        CallSite,
+        Known(usize, usize),
+    }
+
+    impl SpanLike for TestSpan {
+        fn start(&self) -> usize {
+            match self {
+                TestSpan::Known(start, _) => *start,
+                TestSpan::CallSite => 0,
+            }
+        }
+
+        fn end(&self) -> usize {
+            match self {
+                TestSpan::Known(_, end) => *end,
+                TestSpan::CallSite => 0,
+            }
+        }
+
+        fn new(start: usize, end: usize) -> Self {
+            TestSpan::Known(start, end)
+        }
+
+        fn call_site() -> Self {
+            TestSpan::CallSite
+        }
+
+        fn is_call_site(&self) -> bool {
+            matches!(self, TestSpan::CallSite)
+        }
+    }
+
+    #[test]
+    fn test_packed_span_round_trip() {
+        for span in [TestSpan::Known(300, 305), TestSpan::CallSite] {
+            let bytes = to_bytes(&PackedSpan::pack(&span)).unwrap();
+            let packed: PackedSpan = from_bytes(&bytes).unwrap();
+            assert_eq!(packed.unpack::<TestSpan>(), span);
+        }
+    }
+
+    #[test]
+    fn test_packed_span_size() {
+        // Tag, 2-byte start, 1-byte length
+        assert_eq!(to_bytes(&PackedSpan(Some((300, 5)))).unwrap().len(), 4);
+        assert_eq!(to_bytes(&PackedSpan(None)).unwrap(), [0]);
+    }
+
+    #[test]
+    fn test_packed_span_overflow() {
+        let bytes = to_bytes(&Some((usize::MAX, 1usize))).unwrap();
+        assert!(from_bytes::<PackedSpan>(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_trailing_bytes() {
+        let mut bytes = to_bytes(&7u32).unwrap();
+        bytes.push(0);
+        assert!(matches!(
+            from_bytes::<u32>(&bytes),
+            Err(Error::TrailingBytes(1))
+        ));
+    }
+}
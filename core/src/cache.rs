@@ -0,0 +1,614 @@
+//! Content-hash keyed memoization of lex and parse results.
+//!
+//! Build tools and query systems (e.g. salsa) re-request parses of files
+//! that have not changed. [`ParsedCache`] stores results keyed by a
+//! [`CacheKey`] — a stable hash of the source text plus the
+//! [`ParseConfig`] used — so unchanged inputs skip lexing and parsing.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use synkit::cache::ParsedCache;
+//! use synkit::ParseConfig;
+//!
+//! let mut cache: ParsedCache<Document> = ParsedCache::new();
+//! let config = ParseConfig::default();
+//!
+//! let doc = cache.get_or_parse(source, &config, |src, _| Document::parse_str(src))?;
+//! let again = cache.get_or_parse(source, &config, |src, _| Document::parse_str(src))?;
+//! assert!(Arc::ptr_eq(&doc, &again));
+//! assert_eq!(cache.stats().hits, 1);
+//! ```
+//!
+//! [`SharedParsedCache`] is the thread-safe variant for servers that parse
+//! the same documents across requests: it takes `&self`, shards its entries
+//! behind read-write locks, and evicts by age and size.
+//!
+//! ```ignore
+//! use synkit::cache::SharedParsedCache;
+//!
+//! static CACHE: LazyLock<SharedParsedCache<Document>> = LazyLock::new(|| {
+//!     SharedParsedCache::new()
+//!         .with_ttl(Duration::from_secs(300))
+//!         .with_max_entries(10_000)
+//! });
+//!
+//! // In a request handler; `source: Arc<str>` is shared, not copied
+//! let doc = CACHE.get_or_parse(&source, &config, |src, _| Document::parse_str(src))?;
+//! ```
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::ParseConfig;
+
+/// Stable 64-bit hash of source text (FNV-1a).
+///
+/// Unlike `std`'s `DefaultHasher`, the value is stable across Rust versions
+/// and processes, so it can be persisted or used as a query-system key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ContentHash(u64);
+
+impl ContentHash {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    /// Hash `source`.
+    pub fn of(source: &str) -> Self {
+        let hash = source.bytes().fold(Self::OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(Self::PRIME)
+        });
+        Self(hash)
+    }
+
+    /// The raw hash value.
+    #[inline]
+    pub const fn value(self) -> u64 {
+        self.0
+    }
+}
+
+/// Cache key: content hash, source length and parse configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    /// Hash of the source text.
+    pub hash: ContentHash,
+    /// Length of the source text in bytes.
+    pub len: usize,
+    /// Configuration the source was parsed with.
+    pub config: ParseConfig,
+}
+
+impl CacheKey {
+    /// Compute the key for `source` parsed with `config`.
+    pub fn new(source: &str, config: &ParseConfig) -> Self {
+        Self {
+            hash: ContentHash::of(source),
+            len: source.len(),
+            config: *config,
+        }
+    }
+}
+
+/// Hit/miss counters for a [`ParsedCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Lookups answered from the cache.
+    pub hits: u64,
+    /// Lookups that ran the parser.
+    pub misses: u64,
+}
+
+#[derive(Debug)]
+struct CacheEntry<T> {
+    source: Arc<str>,
+    value: Arc<T>,
+}
+
+/// Memoizes parse results by source content and configuration.
+///
+/// Entries keep a copy of their source so that hash collisions are detected
+/// and treated as misses rather than returning the wrong result. Results are
+/// shared as `Arc<T>`.
+#[derive(Debug)]
+pub struct ParsedCache<T> {
+    entries: HashMap<CacheKey, CacheEntry<T>>,
+    stats: CacheStats,
+}
+
+impl<T> Default for ParsedCache<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ParsedCache<T> {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Creates an empty cache with room for `capacity` entries.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::with_capacity(capacity),
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Look up a cached result without parsing or touching the stats.
+    pub fn get(&self, source: &str, config: &ParseConfig) -> Option<Arc<T>> {
+        self.entries
+            .get(&CacheKey::new(source, config))
+            .filter(|entry| &*entry.source == source)
+            .map(|entry| Arc::clone(&entry.value))
+    }
+
+    /// Return the cached result for `source`, or run `parse` and cache it.
+    ///
+    /// Errors are returned as-is and are not cached.
+    pub fn get_or_parse<E, F>(
+        &mut self,
+        source: &str,
+        config: &ParseConfig,
+        parse: F,
+    ) -> Result<Arc<T>, E>
+    where
+        F: FnOnce(&str, &ParseConfig) -> Result<T, E>,
+    {
+        if let Some(value) = self.get(source, config) {
+            self.stats.hits += 1;
+            return Ok(value);
+        }
+        self.stats.misses += 1;
+        let value = parse(source, config)?;
+        Ok(self.insert(source, config, value))
+    }
+
+    /// Insert a result, replacing any previous entry for the same key.
+    pub fn insert(&mut self, source: &str, config: &ParseConfig, value: T) -> Arc<T> {
+        let value = Arc::new(value);
+        self.entries.insert(
+            CacheKey::new(source, config),
+            CacheEntry {
+                source: Arc::from(source),
+                value: Arc::clone(&value),
+            },
+        );
+        value
+    }
+
+    /// Remove the entry for `source` parsed with `config`.
+    pub fn remove(&mut self, source: &str, config: &ParseConfig) -> Option<Arc<T>> {
+        let key = CacheKey::new(source, config);
+        match self.entries.get(&key) {
+            Some(entry) if &*entry.source == source => {
+                self.entries.remove(&key).map(|entry| entry.value)
+            }
+            _ => None,
+        }
+    }
+
+    /// Keep only the entries for which `f` returns `true`.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&CacheKey, &T) -> bool,
+    {
+        self.entries.retain(|key, entry| f(key, &entry.value));
+    }
+
+    /// Remove all entries. Stats are kept.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Number of cached entries.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if nothing is cached.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Hit/miss counters since creation.
+    #[inline]
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+}
+
+#[derive(Debug)]
+struct SharedEntry<T> {
+    source: Arc<str>,
+    value: Arc<T>,
+    inserted: Instant,
+    /// Cache clock reading at the last access, for LRU eviction.
+    last_access: AtomicU64,
+}
+
+type Shard<T> = RwLock<HashMap<CacheKey, SharedEntry<T>>>;
+
+/// Thread-safe [`ParsedCache`] with time-to-live and size eviction.
+///
+/// Entries are spread over independently locked shards, so lookups of
+/// different documents rarely contend. Sources are stored as the caller's
+/// `Arc<str>` rather than copied.
+///
+/// When two threads miss on the same source at once, both parse it but the
+/// first result inserted is returned to both, so callers always share one
+/// `Arc<T>` per entry.
+#[derive(Debug)]
+pub struct SharedParsedCache<T> {
+    shards: Box<[Shard<T>]>,
+    ttl: Option<Duration>,
+    max_entries: Option<usize>,
+    clock: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<T> Default for SharedParsedCache<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SharedParsedCache<T> {
+    /// Default number of shards.
+    pub const DEFAULT_SHARDS: usize = 16;
+
+    /// Creates an empty cache with no TTL or size limit.
+    pub fn new() -> Self {
+        Self::with_shards(Self::DEFAULT_SHARDS)
+    }
+
+    /// Creates an empty cache with `shards` independently locked shards
+    /// (at least one).
+    pub fn with_shards(shards: usize) -> Self {
+        Self {
+            shards: (0..shards.max(1))
+                .map(|_| RwLock::new(HashMap::new()))
+                .collect(),
+            ttl: None,
+            max_entries: None,
+            clock: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Expire entries `ttl` after they were inserted.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Keep at most about `max_entries` entries, evicting the least recently
+    /// used. The limit is split evenly across shards.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+
+    /// Look up a cached, unexpired result without parsing or touching the
+    /// stats.
+    pub fn get(&self, source: &str, config: &ParseConfig) -> Option<Arc<T>> {
+        let key = CacheKey::new(source, config);
+        let shard = self
+            .shard(&key)
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        let entry = shard
+            .get(&key)
+            .filter(|entry| &*entry.source == source && !self.is_expired(entry))?;
+        entry.last_access.store(self.tick(), Ordering::Relaxed);
+        Some(Arc::clone(&entry.value))
+    }
+
+    /// Return the cached result for `source`, or run `parse` and cache it.
+    ///
+    /// The lock is not held while parsing. Errors are returned as-is and are
+    /// not cached.
+    pub fn get_or_parse<E, F>(
+        &self,
+        source: &Arc<str>,
+        config: &ParseConfig,
+        parse: F,
+    ) -> Result<Arc<T>, E>
+    where
+        F: FnOnce(&str, &ParseConfig) -> Result<T, E>,
+    {
+        if let Some(value) = self.get(source, config) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(value);
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let value = parse(source, config)?;
+        Ok(self.insert_if_absent(source, config, value))
+    }
+
+    /// Insert a result, replacing any previous entry for the same key.
+    pub fn insert(&self, source: &Arc<str>, config: &ParseConfig, value: T) -> Arc<T> {
+        let key = CacheKey::new(source, config);
+        let value = Arc::new(value);
+        let mut shard = self
+            .shard(&key)
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        shard.remove(&key);
+        self.make_room(&mut shard);
+        shard.insert(key, self.entry(source, Arc::clone(&value)));
+        value
+    }
+
+    /// Remove the entry for `source` parsed with `config`.
+    pub fn remove(&self, source: &str, config: &ParseConfig) -> Option<Arc<T>> {
+        let key = CacheKey::new(source, config);
+        let mut shard = self
+            .shard(&key)
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        match shard.get(&key) {
+            Some(entry) if &*entry.source == source => shard.remove(&key).map(|entry| entry.value),
+            _ => None,
+        }
+    }
+
+    /// Drop every expired entry and return how many were removed.
+    ///
+    /// Expired entries are never returned, but they are only freed by this
+    /// method or when their shard needs room.
+    pub fn purge_expired(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| {
+                let mut shard = shard.write().unwrap_or_else(PoisonError::into_inner);
+                let before = shard.len();
+                shard.retain(|_, entry| !self.is_expired(entry));
+                before - shard.len()
+            })
+            .sum()
+    }
+
+    /// Remove all entries. Stats are kept.
+    pub fn clear(&self) {
+        for shard in self.shards.iter() {
+            shard
+                .write()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clear();
+        }
+    }
+
+    /// Number of cached entries, including expired ones not yet purged.
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.read().unwrap_or_else(PoisonError::into_inner).len())
+            .sum()
+    }
+
+    /// Returns `true` if nothing is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Hit/miss counters of [`get_or_parse`](Self::get_or_parse) since
+    /// creation.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    fn insert_if_absent(&self, source: &Arc<str>, config: &ParseConfig, value: T) -> Arc<T> {
+        let key = CacheKey::new(source, config);
+        let mut shard = self
+            .shard(&key)
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(entry) = shard
+            .get(&key)
+            .filter(|entry| entry.source == *source && !self.is_expired(entry))
+        {
+            return Arc::clone(&entry.value);
+        }
+        let value = Arc::new(value);
+        shard.remove(&key);
+        self.make_room(&mut shard);
+        shard.insert(key, self.entry(source, Arc::clone(&value)));
+        value
+    }
+
+    fn entry(&self, source: &Arc<str>, value: Arc<T>) -> SharedEntry<T> {
+        SharedEntry {
+            source: Arc::clone(source),
+            value,
+            inserted: Instant::now(),
+            last_access: AtomicU64::new(self.tick()),
+        }
+    }
+
+    /// Evict expired entries, then least recently used ones, until the
+    /// shard has room for one more entry.
+    fn make_room(&self, shard: &mut HashMap<CacheKey, SharedEntry<T>>) {
+        let Some(max_entries) = self.max_entries else {
+            return;
+        };
+        let capacity = max_entries.div_ceil(self.shards.len());
+        if shard.len() < capacity {
+            return;
+        }
+        shard.retain(|_, entry| !self.is_expired(entry));
+        while !shard.is_empty() && shard.len() >= capacity {
+            let oldest = shard
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_access.load(Ordering::Relaxed))
+                .map(|(key, _)| *key);
+            match oldest {
+                Some(key) => shard.remove(&key),
+                None => break,
+            };
+        }
+    }
+
+    fn shard(&self, key: &CacheKey) -> &Shard<T> {
+        // `shards` is never empty and the modulus keeps the index in range
+        let index = (key.hash.value() % self.shards.len() as u64) as usize;
+        &self.shards[index]
+    }
+
+    fn is_expired(&self, entry: &SharedEntry<T>) -> bool {
+        self.ttl.is_some_and(|ttl| entry.inserted.elapsed() > ttl)
+    }
+
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_len(source: &str, _: &ParseConfig) -> Result<usize, ()> {
+        Ok(source.len())
+    }
+
+    #[test]
+    fn test_content_hash_is_stable() {
+        // FNV-1a reference values
+        assert_eq!(ContentHash::of("").value(), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(ContentHash::of("a").value(), 0xaf63_dc4c_8601_ec8c);
+        assert_ne!(ContentHash::of("ab"), ContentHash::of("ba"));
+    }
+
+    #[test]
+    fn test_get_or_parse_memoizes() {
+        let mut cache = ParsedCache::new();
+        let config = ParseConfig::default();
+
+        let a = cache.get_or_parse("abc", &config, parse_len).unwrap();
+        let b = cache.get_or_parse("abc", &config, parse_len).unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(*a, 3);
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 1 });
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_config_is_part_of_key() {
+        let mut cache = ParsedCache::new();
+        let default = ParseConfig::default();
+        let strict = ParseConfig::new().with_max_recursion_depth(4);
+
+        cache.get_or_parse("abc", &default, parse_len).unwrap();
+        assert!(cache.get("abc", &strict).is_none());
+        cache.get_or_parse("abc", &strict, parse_len).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.stats().misses, 2);
+    }
+
+    #[test]
+    fn test_errors_are_not_cached() {
+        let mut cache: ParsedCache<usize> = ParsedCache::new();
+        let config = ParseConfig::default();
+
+        let result = cache.get_or_parse("bad", &config, |_, _| Err("nope"));
+        assert_eq!(result.unwrap_err(), "nope");
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_shared_get_or_parse_memoizes() {
+        let cache = SharedParsedCache::new();
+        let config = ParseConfig::default();
+        let source: Arc<str> = Arc::from("abc");
+
+        let a = cache.get_or_parse(&source, &config, parse_len).unwrap();
+        let b = cache.get_or_parse(&source, &config, parse_len).unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(cache.stats(), CacheStats { hits: 1, misses: 1 });
+        assert_eq!(cache.remove("abc", &config).as_deref(), Some(&3));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_shared_across_threads() {
+        let cache = Arc::new(SharedParsedCache::with_shards(4));
+        let config = ParseConfig::default();
+        let sources: Vec<Arc<str>> = (0..8).map(|i| Arc::from("x".repeat(i))).collect();
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let cache = Arc::clone(&cache);
+                let sources = sources.clone();
+                std::thread::spawn(move || {
+                    for source in &sources {
+                        let len = cache.get_or_parse(source, &config, parse_len).unwrap();
+                        assert_eq!(*len, source.len());
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(cache.len(), 8);
+        let stats = cache.stats();
+        assert_eq!(stats.hits + stats.misses, 32);
+    }
+
+    #[test]
+    fn test_shared_ttl() {
+        let cache = SharedParsedCache::new().with_ttl(Duration::ZERO);
+        let config = ParseConfig::default();
+        cache.insert(&Arc::from("a"), &config, 1);
+        std::thread::sleep(Duration::from_millis(1));
+
+        assert!(cache.get("a", &config).is_none());
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.purge_expired(), 1);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_shared_evicts_least_recently_used() {
+        let cache = SharedParsedCache::with_shards(1).with_max_entries(2);
+        let config = ParseConfig::default();
+        cache.insert(&Arc::from("a"), &config, 1);
+        cache.insert(&Arc::from("b"), &config, 2);
+        cache.get("a", &config);
+        cache.insert(&Arc::from("c"), &config, 3);
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get("b", &config).is_none());
+        assert!(cache.get("a", &config).is_some());
+        assert!(cache.get("c", &config).is_some());
+    }
+
+    #[test]
+    fn test_remove_and_retain() {
+        let mut cache = ParsedCache::new();
+        let config = ParseConfig::default();
+        cache.insert("a", &config, 1);
+        cache.insert("bb", &config, 2);
+
+        assert_eq!(cache.remove("a", &config).as_deref(), Some(&1));
+        assert!(cache.remove("a", &config).is_none());
+
+        cache.retain(|key, _| key.len > 2);
+        assert!(cache.is_empty());
+    }
+}
@@ -27,7 +27,13 @@
 //!     .with_max_recursion_depth(usize::MAX);
 //! ```
 
+use core::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::sync::{Mutex, PoisonError};
+
 use crate::Error;
+use crate::error::BudgetLimit;
 
 /// Configuration for parser behavior and resource limits.
 ///
@@ -40,6 +46,9 @@ use crate::Error;
 /// |---------|---------|-----------|
 /// | `max_recursion_depth` | 128 | Matches serde_json default |
 /// | `max_tokens` | `usize::MAX` | No limit by default |
+/// | `max_fuel` | `usize::MAX` | No limit by default |
+/// | `timeout` | `None` | No limit by default |
+/// | `max_repeats` | 1024 | Stops `while peek` loops that never advance |
 ///
 /// # Security Considerations
 ///
@@ -61,7 +70,7 @@ use crate::Error;
 ///     Ok(Nested { inner })
 /// }
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct ParseConfig {
     /// Maximum allowed recursion depth.
     ///
@@ -79,6 +88,35 @@ pub struct ParseConfig {
     ///
     /// Default: `usize::MAX` (no limit)
     pub max_tokens: usize,
+
+    /// Maximum token reads per parse, enforced by [`ParseBudget`].
+    ///
+    /// Unlike `max_tokens`, tokens re-read after backtracking count again,
+    /// so exponential backtracking exhausts the fuel.
+    ///
+    /// Default: `usize::MAX` (no limit)
+    pub max_fuel: usize,
+
+    /// Maximum wall-clock time per parse, enforced by [`ParseBudget`] at
+    /// every token read.
+    ///
+    /// Reading the clock requires the `std` feature: without it this limit
+    /// is ignored, and `with_timeout` is not available to set it.
+    ///
+    /// Default: `None` (no limit)
+    pub timeout: Option<Duration>,
+
+    /// Maximum times one type may be parsed at the same cursor, enforced by
+    /// [`ParseBudget`].
+    ///
+    /// A loop whose body parses nothing would otherwise spin forever; past
+    /// this limit the parse stops with [`Error::NoProgress`].
+    ///
+    /// Counting parses requires the `std` feature: without it this limit is
+    /// ignored, and `with_max_repeats` is not available to set it.
+    ///
+    /// Default: 1024
+    pub max_repeats: usize,
 }
 
 impl Default for ParseConfig {
@@ -86,6 +124,9 @@ impl Default for ParseConfig {
     ///
     /// - `max_recursion_depth`: 128
     /// - `max_tokens`: `usize::MAX`
+    /// - `max_fuel`: `usize::MAX`
+    /// - `timeout`: `None`
+    /// - `max_repeats`: 1024
     #[inline]
     fn default() -> Self {
         Self::DEFAULT
@@ -99,6 +140,9 @@ impl ParseConfig {
     pub const DEFAULT: Self = Self {
         max_recursion_depth: 128,
         max_tokens: usize::MAX,
+        max_fuel: usize::MAX,
+        timeout: None,
+        max_repeats: 1024,
     };
 
     /// Creates a new configuration with default values.
@@ -136,6 +180,50 @@ impl ParseConfig {
         self
     }
 
+    /// Sets the maximum token reads per parse, including re-reads after
+    /// backtracking.
+    ///
+    /// # Arguments
+    ///
+    /// * `fuel` - Maximum token reads. Use `usize::MAX` to disable.
+    #[inline]
+    pub const fn with_max_fuel(mut self, fuel: usize) -> Self {
+        self.max_fuel = fuel;
+        self
+    }
+
+    /// Sets the maximum wall-clock time per parse. Requires the `std`
+    /// feature.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let config = ParseConfig::new()
+    ///     .with_timeout(Duration::from_millis(50));
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    pub const fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets how many times one type may be parsed at the same cursor before
+    /// the parse is stopped as making no progress.
+    ///
+    /// # Arguments
+    ///
+    /// * `repeats` - Maximum parses per type and cursor. Use `usize::MAX` to
+    ///   disable.
+    ///
+    /// Requires the `std` feature.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub const fn with_max_repeats(mut self, repeats: usize) -> Self {
+        self.max_repeats = repeats;
+        self
+    }
+
     /// Disables the recursion limit.
     ///
     /// # Warning
@@ -210,6 +298,15 @@ impl RecursionGuard {
         }
     }
 
+    /// A guard one level deeper than `self`, for a nested parser that
+    /// carries its own guard (e.g. the stream over a delimited group).
+    #[inline]
+    pub const fn nested(self) -> Self {
+        Self {
+            depth: self.depth.saturating_add(1),
+        }
+    }
+
     /// Exit a nested context, decrementing depth.
     ///
     /// Uses saturating subtraction so extra `exit()` calls don't underflow.
@@ -227,6 +324,208 @@ impl RecursionGuard {
     }
 }
 
+/// Fuel, deadline and no-progress guard for a single parse.
+///
+/// Generated streams charge their budget once per token read, so a parse
+/// that backtracks pathologically or runs too long stops at the next token.
+/// They also [note](Self::note_parse) every `stream.parse::<T>()`, so a loop
+/// that keeps parsing the same type without advancing stops too.
+/// The budget is shared by a stream and its forks; once it runs out it stays
+/// exhausted and [`check`](Self::check) reports which limit was hit.
+///
+/// # Example
+///
+/// ```ignore
+/// let config = ParseConfig::new()
+///     .with_max_fuel(1_000_000)
+///     .with_timeout(Duration::from_millis(50));
+/// let mut stream = TokenStream::lex(source)?.with_budget(&config);
+/// // Fails with `MyError::from(Error::BudgetExceeded { .. })` once it runs out
+/// let doc = stream.parse::<Document>()?;
+/// ```
+#[derive(Debug)]
+pub struct ParseBudget {
+    max_fuel: usize,
+    timeout: Option<Duration>,
+    #[cfg(feature = "std")]
+    started: std::time::Instant,
+    consumed: AtomicUsize,
+    /// 0 while within budget, else which limit was hit.
+    exceeded: AtomicU8,
+    #[cfg(feature = "std")]
+    max_repeats: usize,
+    #[cfg(feature = "std")]
+    repeats: Mutex<Repeats>,
+}
+
+/// Parses started at `cursor`, per type name.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+struct Repeats {
+    cursor: usize,
+    counts: Vec<(&'static str, usize)>,
+    /// The `Error::NoProgress` once the guard has tripped.
+    stuck: Option<Error>,
+}
+
+const WITHIN_BUDGET: u8 = 0;
+const FUEL_EXCEEDED: u8 = 1;
+const TIMEOUT_EXCEEDED: u8 = 2;
+const NO_PROGRESS: u8 = 3;
+
+impl ParseBudget {
+    /// Creates a budget from `config`'s `max_fuel`, `timeout` and
+    /// `max_repeats`, starting the clock now.
+    pub fn new(config: &ParseConfig) -> Self {
+        Self {
+            max_fuel: config.max_fuel,
+            timeout: config.timeout,
+            #[cfg(feature = "std")]
+            started: std::time::Instant::now(),
+            consumed: AtomicUsize::new(0),
+            exceeded: AtomicU8::new(WITHIN_BUDGET),
+            #[cfg(feature = "std")]
+            max_repeats: config.max_repeats,
+            #[cfg(feature = "std")]
+            repeats: Mutex::new(Repeats {
+                cursor: usize::MAX,
+                counts: Vec::new(),
+                stuck: None,
+            }),
+        }
+    }
+
+    /// Charge one token read.
+    ///
+    /// Returns `Err(Error::BudgetExceeded)` if the fuel or time is used up;
+    /// every later call fails too.
+    pub fn charge(&self) -> Result<(), Error> {
+        if self.exceeded.load(Ordering::Relaxed) != WITHIN_BUDGET {
+            return self.check();
+        }
+        let consumed = self
+            .consumed
+            .fetch_add(1, Ordering::Relaxed)
+            .saturating_add(1);
+        if consumed > self.max_fuel {
+            self.exceeded.store(FUEL_EXCEEDED, Ordering::Relaxed);
+        } else if self.timed_out() {
+            self.exceeded.store(TIMEOUT_EXCEEDED, Ordering::Relaxed);
+        }
+        self.check()
+    }
+
+    /// Note a parse of `type_name` starting at token index `cursor`, whose
+    /// first token spans `start..end`.
+    ///
+    /// Returns `Err(Error::NoProgress)` once the same type has been parsed
+    /// at the same cursor more than `max_repeats` times in a row, i.e. with
+    /// no parse starting anywhere else in between; every later call (and
+    /// [`charge`](Self::charge)) fails too. Without the `std` feature
+    /// parses aren't counted, so this only reports other limits.
+    pub fn note_parse(
+        &self,
+        cursor: usize,
+        type_name: &'static str,
+        start: usize,
+        end: usize,
+    ) -> Result<(), Error> {
+        if self.is_exhausted() {
+            return self.check();
+        }
+        #[cfg(feature = "std")]
+        self.count_parse(cursor, type_name, start, end);
+        #[cfg(not(feature = "std"))]
+        let _ = (cursor, type_name, start, end);
+        self.check()
+    }
+
+    /// Count a parse for [`note_parse`](Self::note_parse), tripping the
+    /// guard past `max_repeats`.
+    #[cfg(feature = "std")]
+    fn count_parse(&self, cursor: usize, type_name: &'static str, start: usize, end: usize) {
+        let mut repeats = self.repeats.lock().unwrap_or_else(PoisonError::into_inner);
+        if repeats.cursor != cursor {
+            repeats.cursor = cursor;
+            repeats.counts.clear();
+        }
+        let count = match repeats
+            .counts
+            .iter_mut()
+            .find(|(name, _)| *name == type_name)
+        {
+            Some((_, count)) => {
+                *count = count.saturating_add(1);
+                *count
+            }
+            None => {
+                repeats.counts.push((type_name, 1));
+                1
+            }
+        };
+        if count > self.max_repeats {
+            repeats.stuck = Some(Error::NoProgress {
+                type_name,
+                start,
+                end,
+                count,
+            });
+            self.exceeded.store(NO_PROGRESS, Ordering::Relaxed);
+        }
+    }
+
+    /// `Err(Error::BudgetExceeded)` if a previous [`charge`](Self::charge)
+    /// ran out of budget, or `Err(Error::NoProgress)` if
+    /// [`note_parse`](Self::note_parse) found the parse stuck.
+    pub fn check(&self) -> Result<(), Error> {
+        let limit = match self.exceeded.load(Ordering::Relaxed) {
+            FUEL_EXCEEDED => BudgetLimit::Fuel(self.max_fuel),
+            TIMEOUT_EXCEEDED => BudgetLimit::Timeout(self.timeout.unwrap_or_default()),
+            NO_PROGRESS => return self.stuck(),
+            _ => return Ok(()),
+        };
+        Err(Error::BudgetExceeded {
+            consumed: self.consumed().min(self.max_fuel),
+            limit,
+        })
+    }
+
+    /// Returns `true` once the budget has run out.
+    #[inline]
+    pub fn is_exhausted(&self) -> bool {
+        self.exceeded.load(Ordering::Relaxed) != WITHIN_BUDGET
+    }
+
+    /// Tokens charged so far.
+    #[inline]
+    pub fn consumed(&self) -> usize {
+        self.consumed.load(Ordering::Relaxed)
+    }
+
+    /// The `Error::NoProgress` recorded when the guard tripped.
+    #[cfg(feature = "std")]
+    fn stuck(&self) -> Result<(), Error> {
+        let repeats = self.repeats.lock().unwrap_or_else(PoisonError::into_inner);
+        repeats.stuck.map_or(Ok(()), Err)
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn stuck(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    fn timed_out(&self) -> bool {
+        self.timeout
+            .is_some_and(|timeout| self.started.elapsed() > timeout)
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn timed_out(&self) -> bool {
+        false
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -300,6 +599,80 @@ mod tests {
         assert_eq!(guard.depth(), 0);
     }
 
+    #[test]
+    fn test_budget_fuel() {
+        let budget = ParseBudget::new(&ParseConfig::new().with_max_fuel(2));
+        budget.charge().unwrap();
+        budget.charge().unwrap();
+        assert!(!budget.is_exhausted());
+
+        let err = Error::BudgetExceeded {
+            consumed: 2,
+            limit: BudgetLimit::Fuel(2),
+        };
+        assert_eq!(budget.charge(), Err(err));
+        assert_eq!(budget.charge(), Err(err));
+        assert_eq!(budget.check(), Err(err));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_budget_timeout() {
+        let budget = ParseBudget::new(&ParseConfig::new().with_timeout(Duration::ZERO));
+        std::thread::sleep(Duration::from_millis(1));
+        assert!(matches!(
+            budget.charge(),
+            Err(Error::BudgetExceeded {
+                consumed: 1,
+                limit: BudgetLimit::Timeout(Duration::ZERO),
+            })
+        ));
+        assert!(budget.is_exhausted());
+    }
+
+    #[test]
+    fn test_budget_unlimited_by_default() {
+        let budget = ParseBudget::new(&ParseConfig::default());
+        for _ in 0..1000 {
+            budget.charge().unwrap();
+        }
+        assert_eq!(budget.consumed(), 1000);
+        assert_eq!(budget.check(), Ok(()));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_budget_no_progress() {
+        let budget = ParseBudget::new(&ParseConfig::new().with_max_repeats(2));
+        budget.note_parse(0, "Item", 0, 1).unwrap();
+        budget.note_parse(0, "Other", 0, 1).unwrap();
+        budget.note_parse(0, "Item", 0, 1).unwrap();
+        // A parse elsewhere resets the counts
+        budget.note_parse(1, "Item", 2, 3).unwrap();
+        budget.note_parse(1, "Item", 2, 3).unwrap();
+        assert!(!budget.is_exhausted());
+
+        let err = Error::NoProgress {
+            type_name: "Item",
+            start: 2,
+            end: 3,
+            count: 3,
+        };
+        assert_eq!(budget.note_parse(1, "Item", 2, 3), Err(err));
+        assert_eq!(budget.note_parse(5, "Other", 9, 9), Err(err));
+        assert_eq!(budget.charge(), Err(err));
+        assert_eq!(budget.check(), Err(err));
+    }
+
+    #[test]
+    fn test_recursion_guard_nested() {
+        let mut guard = RecursionGuard::new();
+        guard.enter(128).unwrap();
+        let inner = guard.nested();
+        assert_eq!(inner.depth(), 2);
+        assert_eq!(guard.depth(), 1);
+    }
+
     #[test]
     fn test_recursion_guard_reset() {
         let mut guard = RecursionGuard::new();
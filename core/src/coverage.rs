@@ -0,0 +1,149 @@
+use std::fmt;
+use std::ops::Range;
+
+use crate::SpanError;
+
+/// The first place token spans fail to tile their source, from the
+/// generated `TokenStream::verify_coverage`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CoverageError {
+    /// A token has a call-site span, so it can't be placed in the source.
+    CallSite {
+        /// Index of the token.
+        index: usize,
+    },
+    /// A token's span doesn't fit the source.
+    Span {
+        /// Index of the token.
+        index: usize,
+        /// What is wrong with it.
+        error: SpanError,
+    },
+    /// A token starts before the previous one ends.
+    Overlap {
+        /// Index of the token.
+        index: usize,
+        /// Where it starts.
+        start: usize,
+        /// Where the previous token ends.
+        previous_end: usize,
+    },
+    /// Source bytes between tokens (or before the first or after the last)
+    /// that the lexer wouldn't skip.
+    Gap {
+        /// Start byte offset.
+        start: usize,
+        /// End byte offset.
+        end: usize,
+    },
+}
+
+impl CoverageError {
+    /// Check that `spans`, in order, tile `source`: each fits it, none
+    /// overlap, and the text of every gap satisfies `is_skipped`. A `None`
+    /// span is a call-site span.
+    pub fn check<I, F>(source: &str, spans: I, mut is_skipped: F) -> Result<(), CoverageError>
+    where
+        I: IntoIterator<Item = Option<Range<usize>>>,
+        F: FnMut(&str) -> bool,
+    {
+        let mut gap = |start: usize, end: usize| {
+            let text = source.get(start..end).unwrap_or_default();
+            if start < end && !is_skipped(text) {
+                return Err(CoverageError::Gap { start, end });
+            }
+            Ok(())
+        };
+
+        let mut covered = 0;
+        for (index, span) in spans.into_iter().enumerate() {
+            let span = span.ok_or(CoverageError::CallSite { index })?;
+            let span = SpanError::check(span.start, span.end, source)
+                .map_err(|error| CoverageError::Span { index, error })?;
+            if span.start < covered {
+                return Err(CoverageError::Overlap {
+                    index,
+                    start: span.start,
+                    previous_end: covered,
+                });
+            }
+            gap(covered, span.start)?;
+            covered = span.end;
+        }
+        gap(covered, source.len())
+    }
+}
+
+impl fmt::Display for CoverageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoverageError::CallSite { index } => {
+                write!(f, "token {} has a call-site span", index)
+            }
+            CoverageError::Span { index, error } => write!(f, "token {}: {}", index, error),
+            CoverageError::Overlap {
+                index,
+                start,
+                previous_end,
+            } => write!(
+                f,
+                "token {} starts at {}, before the previous token ends at {}",
+                index, start, previous_end
+            ),
+            CoverageError::Gap { start, end } => write!(
+                f,
+                "bytes {}..{} are not covered by a token or skipped by the lexer",
+                start, end
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CoverageError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(source: &str, spans: &[(usize, usize)]) -> Result<(), CoverageError> {
+        CoverageError::check(source, spans.iter().map(|&(s, e)| Some(s..e)), |gap| {
+            gap.trim().is_empty()
+        })
+    }
+
+    #[test]
+    fn test_tiled() {
+        assert_eq!(check("ab cd", &[(0, 2), (3, 5)]), Ok(()));
+        assert_eq!(check(" ab ", &[(1, 3)]), Ok(()));
+        assert_eq!(check("", &[]), Ok(()));
+        assert_eq!(check("a", &[(0, 0), (0, 1), (1, 1)]), Ok(()));
+    }
+
+    #[test]
+    fn test_first_anomaly() {
+        assert_eq!(
+            check("abcd", &[(0, 2), (3, 4)]),
+            Err(CoverageError::Gap { start: 2, end: 3 })
+        );
+        assert_eq!(
+            check("abcd", &[(0, 2)]),
+            Err(CoverageError::Gap { start: 2, end: 4 })
+        );
+        assert_eq!(
+            check("abcd", &[(0, 3), (2, 4)]),
+            Err(CoverageError::Overlap {
+                index: 1,
+                start: 2,
+                previous_end: 3
+            })
+        );
+        assert_eq!(
+            check("abcd", &[(0, 9)]).map_err(|e| e.to_string()),
+            Err("token 0: span 0..9 is out of bounds for source of length 4".to_string())
+        );
+        assert_eq!(
+            CoverageError::check("ab", [Some(0..1), None], |_| true),
+            Err(CoverageError::CallSite { index: 1 })
+        );
+    }
+}
@@ -0,0 +1,353 @@
+//! Decompressing sources for incremental lexing.
+//!
+//! Log archives and data dumps are usually compressed. [`DecompressedChunks`]
+//! reads a gzip or zstd file as bounded UTF-8 chunks for
+//! [`IncrementalLexer::feed`](crate::async_stream::IncrementalLexer::feed),
+//! and [`Decompressor`] does the same for compressed bytes pushed from an
+//! async source. Both carry multi-byte characters split across reads over to
+//! the next chunk, so chunk edges never land inside a character.
+//!
+//! Gzip needs the `gzip` feature and zstd the `zstd` feature.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use synkit::decompress::DecompressedChunks;
+//!
+//! let mut lexer = MyLexer::new();
+//! for chunk in DecompressedChunks::open("access.log.gz")? {
+//!     tokens.extend(lexer.feed(&chunk?)?);
+//! }
+//! tokens.extend(lexer.finish()?);
+//! ```
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use crate::async_stream::StreamError;
+
+/// Default [`DecompressedChunks`] read size.
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// How a source is compressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// Not compressed; bytes pass through unchanged.
+    Plain,
+    /// Gzip, including concatenated members as written by `cat a.gz b.gz`.
+    #[cfg(feature = "gzip")]
+    Gzip,
+    /// Zstandard, including concatenated frames.
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Compression {
+    /// `.gz` files are gzip, `.zst` files are zstd, and everything else is
+    /// plain.
+    ///
+    /// Fails with [`StreamError::LexError`] for a compressed file whose
+    /// feature is disabled, rather than lexing the compressed bytes.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, StreamError> {
+        let path = path.as_ref();
+        match path.extension().and_then(|ext| ext.to_str()) {
+            #[cfg(feature = "gzip")]
+            Some("gz") => Ok(Compression::Gzip),
+            #[cfg(feature = "zstd")]
+            Some("zst") => Ok(Compression::Zstd),
+            #[cfg(not(feature = "gzip"))]
+            Some("gz") => Err(disabled(path, "gzip")),
+            #[cfg(not(feature = "zstd"))]
+            Some("zst") => Err(disabled(path, "zstd")),
+            _ => Ok(Compression::Plain),
+        }
+    }
+}
+
+#[cfg(not(all(feature = "gzip", feature = "zstd")))]
+fn disabled(path: &Path, feature: &str) -> StreamError {
+    StreamError::LexError(format!(
+        "`{}` needs the `{feature}` feature",
+        path.display()
+    ))
+}
+
+/// A blocking iterator of decompressed text, at most `chunk_size` bytes
+/// at a time.
+///
+/// The iterator is fused: after an error, or once the input is exhausted, it
+/// only returns `None`. Truncated input and text that isn't UTF-8 fail with
+/// [`StreamError::LexError`].
+pub struct DecompressedChunks<R: Read> {
+    decoder: ReadDecoder<R>,
+    text: Utf8Buffer,
+    buffer: Vec<u8>,
+    done: bool,
+}
+
+impl DecompressedChunks<File> {
+    /// Open the file at `path`, detecting its [`Compression`] with
+    /// [`Compression::from_path`].
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StreamError> {
+        let path = path.as_ref();
+        let compression = Compression::from_path(path)?;
+        let file = File::open(path).map_err(|e| io_error("opening", e))?;
+        Self::new(file, compression)
+    }
+}
+
+impl<R: Read> DecompressedChunks<R> {
+    /// Decompress `reader`, reading [`DEFAULT_CHUNK_SIZE`] bytes at a time.
+    pub fn new(reader: R, compression: Compression) -> Result<Self, StreamError> {
+        let decoder = match compression {
+            Compression::Plain => ReadDecoder::Plain(reader),
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => ReadDecoder::Gzip(flate2::read::MultiGzDecoder::new(reader)),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => {
+                ReadDecoder::Zstd(zstd::Decoder::new(reader).map_err(|e| io_error("starting", e))?)
+            }
+        };
+        Ok(Self {
+            decoder,
+            text: Utf8Buffer::default(),
+            buffer: vec![0; DEFAULT_CHUNK_SIZE],
+            done: false,
+        })
+    }
+
+    /// Read at most `size` decompressed bytes per chunk. Zero is treated as
+    /// one.
+    pub fn with_chunk_size(mut self, size: usize) -> Self {
+        self.buffer = vec![0; size.max(1)];
+        self
+    }
+
+    /// Decompressed bytes returned so far.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.text.offset
+    }
+}
+
+impl<R: Read> Iterator for DecompressedChunks<R> {
+    type Item = Result<String, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let read = match self.decoder.read(&mut self.buffer) {
+                Ok(read) => read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(io_error("decompressing", e)));
+                }
+            };
+            if read == 0 {
+                self.done = true;
+                return self.text.finish().err().map(Err);
+            }
+            let bytes = self.buffer.get(..read).unwrap_or_default();
+            match self.text.decode(bytes) {
+                // Only part of a character so far
+                Ok(text) if text.is_empty() => {}
+                Ok(text) => return Some(Ok(text)),
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<R: Read> core::iter::FusedIterator for DecompressedChunks<R> {}
+
+/// Decompresses bytes as they are pushed, for async sources.
+///
+/// # Example
+/// ```ignore
+/// let mut inflate = Decompressor::new(Compression::Gzip)?;
+/// while let Some(bytes) = body.next().await {
+///     tokens.feed(&inflate.push(&bytes?)?).await?;
+/// }
+/// tokens.feed(&inflate.finish()?).await?;
+/// tokens.finish().await?;
+/// ```
+pub struct Decompressor {
+    decoder: WriteDecoder,
+    text: Utf8Buffer,
+}
+
+impl Decompressor {
+    /// A decompressor for `compression`.
+    pub fn new(compression: Compression) -> Result<Self, StreamError> {
+        let decoder = match compression {
+            Compression::Plain => WriteDecoder::Plain(Vec::new()),
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => WriteDecoder::Gzip(flate2::write::MultiGzDecoder::new(Vec::new())),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => WriteDecoder::Zstd(zstd::stream::zio::Writer::new(
+                Vec::new(),
+                zstd::stream::raw::Decoder::new().map_err(|e| io_error("starting", e))?,
+            )),
+        };
+        Ok(Self {
+            decoder,
+            text: Utf8Buffer::default(),
+        })
+    }
+
+    /// Decompress `bytes`, returning the text completed so far. Returns an
+    /// empty string while a compressed block or character is incomplete.
+    pub fn push(&mut self, bytes: &[u8]) -> Result<String, StreamError> {
+        self.decoder
+            .write_all(bytes)
+            .and_then(|()| self.decoder.flush())
+            .map_err(|e| io_error("decompressing", e))?;
+        let output = core::mem::take(self.decoder.output());
+        self.text.decode(&output)
+    }
+
+    /// Signal the end of the input, returning the remaining text.
+    ///
+    /// Fails with [`StreamError::LexError`] if the input or its last
+    /// character was cut off.
+    pub fn finish(mut self) -> Result<String, StreamError> {
+        let output = self
+            .decoder
+            .finish()
+            .map_err(|e| io_error("decompressing", e))?;
+        let text = self.text.decode(&output)?;
+        self.text.finish()?;
+        Ok(text)
+    }
+
+    /// Decompressed bytes returned so far.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.text.offset
+    }
+}
+
+enum ReadDecoder<R: Read> {
+    Plain(R),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::read::MultiGzDecoder<R>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::Decoder<'static, io::BufReader<R>>),
+}
+
+impl<R: Read> Read for ReadDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            ReadDecoder::Plain(reader) => reader.read(buf),
+            #[cfg(feature = "gzip")]
+            ReadDecoder::Gzip(decoder) => decoder.read(buf),
+            #[cfg(feature = "zstd")]
+            ReadDecoder::Zstd(decoder) => decoder.read(buf),
+        }
+    }
+}
+
+enum WriteDecoder {
+    Plain(Vec<u8>),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::MultiGzDecoder<Vec<u8>>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::zio::Writer<Vec<u8>, zstd::stream::raw::Decoder<'static>>),
+}
+
+impl WriteDecoder {
+    /// Decompressed bytes written so far.
+    fn output(&mut self) -> &mut Vec<u8> {
+        match self {
+            WriteDecoder::Plain(output) => output,
+            #[cfg(feature = "gzip")]
+            WriteDecoder::Gzip(decoder) => decoder.get_mut(),
+            #[cfg(feature = "zstd")]
+            WriteDecoder::Zstd(decoder) => decoder.writer_mut(),
+        }
+    }
+
+    fn finish(self) -> io::Result<Vec<u8>> {
+        match self {
+            WriteDecoder::Plain(output) => Ok(output),
+            #[cfg(feature = "gzip")]
+            WriteDecoder::Gzip(decoder) => decoder.finish(),
+            #[cfg(feature = "zstd")]
+            // Fails on a cut-off frame, unlike `flush`
+            WriteDecoder::Zstd(mut decoder) => {
+                decoder.finish()?;
+                Ok(decoder.into_inner().0)
+            }
+        }
+    }
+}
+
+impl Write for WriteDecoder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            WriteDecoder::Plain(output) => output.write(buf),
+            #[cfg(feature = "gzip")]
+            WriteDecoder::Gzip(decoder) => decoder.write(buf),
+            #[cfg(feature = "zstd")]
+            WriteDecoder::Zstd(decoder) => decoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            WriteDecoder::Plain(_) => Ok(()),
+            #[cfg(feature = "gzip")]
+            WriteDecoder::Gzip(decoder) => decoder.flush(),
+            #[cfg(feature = "zstd")]
+            WriteDecoder::Zstd(decoder) => decoder.flush(),
+        }
+    }
+}
+
+/// Holds back the bytes of a character split across chunks.
+#[derive(Debug, Default)]
+struct Utf8Buffer {
+    pending: Vec<u8>,
+    /// Decoded bytes returned so far.
+    offset: usize,
+}
+
+impl Utf8Buffer {
+    fn decode(&mut self, bytes: &[u8]) -> Result<String, StreamError> {
+        self.pending.extend_from_slice(bytes);
+        let valid = match core::str::from_utf8(&self.pending) {
+            Ok(text) => text.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => return Err(self.invalid(e.valid_up_to())),
+        };
+        let rest = self.pending.split_off(valid);
+        let text = core::mem::replace(&mut self.pending, rest);
+        self.offset += text.len();
+        // `text` was checked above
+        String::from_utf8(text).map_err(|e| self.invalid(e.utf8_error().valid_up_to()))
+    }
+
+    fn finish(&self) -> Result<(), StreamError> {
+        if self.pending.is_empty() {
+            Ok(())
+        } else {
+            Err(self.invalid(0))
+        }
+    }
+
+    fn invalid(&self, at: usize) -> StreamError {
+        StreamError::LexError(format!(
+            "decompressed input is not UTF-8 at byte {}",
+            self.offset + at
+        ))
+    }
+}
+
+fn io_error(doing: &str, e: io::Error) -> StreamError {
+    StreamError::LexError(format!("{doing} compressed input: {e}"))
+}
@@ -76,6 +76,53 @@ pub enum Error {
         /// Maximum allowed token count.
         limit: usize,
     },
+
+    /// A parse ran out of fuel or time.
+    ///
+    /// Returned when a stream with a [`ParseBudget`](crate::config::ParseBudget)
+    /// reads more tokens than `ParseConfig::max_fuel`, counting re-reads after
+    /// backtracking, or runs past `ParseConfig::timeout`.
+    BudgetExceeded {
+        /// Tokens read when the budget ran out.
+        consumed: usize,
+        /// Which limit was hit.
+        limit: BudgetLimit,
+    },
+
+    /// A parse kept parsing the same type at the same position.
+    ///
+    /// Returned when a stream with a [`ParseBudget`](crate::config::ParseBudget)
+    /// starts parsing one type at the same cursor more than
+    /// `ParseConfig::max_repeats` times, e.g. from a `while peek` loop whose
+    /// body consumes nothing.
+    NoProgress {
+        /// Name of the type being parsed, from `std::any::type_name`.
+        type_name: &'static str,
+        /// Start byte offset of the token the parse was stuck at.
+        start: usize,
+        /// End byte offset of that token; equal to `start` at end of input.
+        end: usize,
+        /// Times the type was parsed there.
+        count: usize,
+    },
+}
+
+/// The limit behind an [`Error::BudgetExceeded`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetLimit {
+    /// Token reads allowed per parse.
+    Fuel(usize),
+    /// Wall-clock time allowed per parse.
+    Timeout(core::time::Duration),
+}
+
+impl fmt::Display for BudgetLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BudgetLimit::Fuel(fuel) => write!(f, "fuel of {} tokens", fuel),
+            BudgetLimit::Timeout(timeout) => write!(f, "timeout of {:?}", timeout),
+        }
+    }
 }
 
 impl fmt::Display for Error {
@@ -102,6 +149,25 @@ impl fmt::Display for Error {
                     consumed, limit
                 )
             }
+            Error::BudgetExceeded { consumed, limit } => {
+                write!(
+                    f,
+                    "parse budget exceeded: {} after {} tokens",
+                    limit, consumed
+                )
+            }
+            Error::NoProgress {
+                type_name,
+                start,
+                end,
+                count,
+            } => {
+                write!(
+                    f,
+                    "no progress: `{}` parsed {} times at {}..{}",
+                    type_name, count, start, end
+                )
+            }
         }
     }
 }
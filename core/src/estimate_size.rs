@@ -0,0 +1,307 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::{
+    Delimited, Punctuated, PunctuatedInner, Repeated, RepeatedItem, Separated, Terminated,
+};
+
+/// Approximate memory footprint of an AST node, in bytes.
+///
+/// Streaming consumers use this to bound buffered nodes by size rather than
+/// count, since a single node can be a thousand times larger than its
+/// neighbours. Estimates count allocated capacity, not just length, and
+/// ignore allocator overhead. Shared pointees (`Rc`, `Arc`) are counted in
+/// full by every owner.
+///
+/// Use `#[derive(EstimateSize)]` on AST nodes; spans and `Spanned<T>`
+/// wrappers generated by `parser_kit!` already implement it.
+///
+/// # Example
+///
+/// ```ignore
+/// use synkit::EstimateSize;
+///
+/// #[derive(EstimateSize)]
+/// enum Value {
+///     Number(Spanned<NumberToken>),
+///     List(Vec<Spanned<Value>>),
+/// }
+///
+/// let value = Value::parse_str("[1, 2, 3]")?;
+/// assert!(value.estimated_size() >= std::mem::size_of::<Value>());
+/// ```
+pub trait EstimateSize {
+    /// Bytes owned by `self` on the heap, excluding `self` itself.
+    fn heap_size(&self) -> usize;
+
+    /// Total estimated bytes: the inline size of `self` plus
+    /// [`heap_size`](Self::heap_size).
+    #[inline]
+    fn estimated_size(&self) -> usize {
+        std::mem::size_of_val(self) + self.heap_size()
+    }
+}
+
+/// Implement `EstimateSize` for types that own no heap memory.
+macro_rules! impl_estimate_size_inline {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl EstimateSize for $ty {
+                #[inline]
+                fn heap_size(&self) -> usize {
+                    0
+                }
+            }
+        )*
+    };
+}
+
+impl_estimate_size_inline!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    str,
+);
+
+impl EstimateSize for String {
+    #[inline]
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+/// Implement `EstimateSize` for owning pointers: the pointee is on the heap.
+macro_rules! impl_estimate_size_pointer {
+    ($($ptr:ident),*) => {
+        $(
+            impl<T: EstimateSize + ?Sized> EstimateSize for $ptr<T> {
+                #[inline]
+                fn heap_size(&self) -> usize {
+                    (**self).estimated_size()
+                }
+            }
+        )*
+    };
+}
+
+impl_estimate_size_pointer!(Box, Rc, Arc);
+
+/// References don't own their pointee.
+impl<T: ?Sized> EstimateSize for &T {
+    #[inline]
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+/// Borrowed data is not owned; owned data is counted like `B::Owned`.
+impl<B> EstimateSize for Cow<'_, B>
+where
+    B: ToOwned + ?Sized,
+    B::Owned: EstimateSize,
+{
+    #[inline]
+    fn heap_size(&self) -> usize {
+        match self {
+            Cow::Borrowed(_) => 0,
+            Cow::Owned(owned) => owned.heap_size(),
+        }
+    }
+}
+
+#[cfg(feature = "compact_str")]
+impl EstimateSize for compact_str::CompactString {
+    #[inline]
+    fn heap_size(&self) -> usize {
+        if self.is_heap_allocated() {
+            self.capacity()
+        } else {
+            0
+        }
+    }
+}
+
+impl<T: EstimateSize> EstimateSize for Option<T> {
+    #[inline]
+    fn heap_size(&self) -> usize {
+        self.as_ref().map_or(0, T::heap_size)
+    }
+}
+
+impl<T: EstimateSize> EstimateSize for [T] {
+    fn heap_size(&self) -> usize {
+        self.iter().map(T::heap_size).sum()
+    }
+}
+
+impl<T: EstimateSize, const N: usize> EstimateSize for [T; N] {
+    #[inline]
+    fn heap_size(&self) -> usize {
+        self.as_slice().heap_size()
+    }
+}
+
+impl<T: EstimateSize> EstimateSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * std::mem::size_of::<T>() + self.as_slice().heap_size()
+    }
+}
+
+impl<T: EstimateSize> EstimateSize for VecDeque<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * std::mem::size_of::<T>() + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<K: EstimateSize, V: EstimateSize, S> EstimateSize for HashMap<K, V, S> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * std::mem::size_of::<(K, V)>()
+            + self
+                .iter()
+                .map(|(k, v)| k.heap_size() + v.heap_size())
+                .sum::<usize>()
+    }
+}
+
+impl<K: EstimateSize, V: EstimateSize> EstimateSize for BTreeMap<K, V> {
+    fn heap_size(&self) -> usize {
+        self.iter()
+            .map(|(k, v)| std::mem::size_of::<(K, V)>() + k.heap_size() + v.heap_size())
+            .sum()
+    }
+}
+
+/// Implement `EstimateSize` for tuples element-wise.
+macro_rules! impl_estimate_size_tuple {
+    ($(($($name:ident $idx:tt),+)),* $(,)?) => {
+        $(
+            impl<$($name: EstimateSize),+> EstimateSize for ($($name,)+) {
+                #[inline]
+                fn heap_size(&self) -> usize {
+                    0 $(+ self.$idx.heap_size())+
+                }
+            }
+        )*
+    };
+}
+
+impl_estimate_size_tuple!(
+    (A 0),
+    (A 0, B 1),
+    (A 0, B 1, C 2),
+    (A 0, B 1, C 2, D 3),
+    (A 0, B 1, C 2, D 3, E 4),
+    (A 0, B 1, C 2, D 3, E 4, F 5),
+);
+
+impl<T: EstimateSize, P: EstimateSize> EstimateSize for PunctuatedInner<T, P> {
+    #[inline]
+    fn heap_size(&self) -> usize {
+        self.inner.heap_size()
+    }
+}
+
+/// Implement `EstimateSize` for the punctuated wrappers via their inner storage.
+macro_rules! impl_estimate_size_punctuated {
+    ($($name:ident),*) => {
+        $(
+            impl<T: EstimateSize, P: EstimateSize> EstimateSize for $name<T, P> {
+                #[inline]
+                fn heap_size(&self) -> usize {
+                    self.as_ref().heap_size()
+                }
+            }
+        )*
+    };
+}
+
+impl_estimate_size_punctuated!(Punctuated, Terminated, Separated);
+
+impl<T: EstimateSize, Span> EstimateSize for Delimited<T, Span> {
+    #[inline]
+    fn heap_size(&self) -> usize {
+        self.inner.heap_size()
+    }
+}
+
+impl<T, Sep, S: EstimateSize> EstimateSize for RepeatedItem<T, Sep, S> {
+    #[inline]
+    fn heap_size(&self) -> usize {
+        self.value.heap_size() + self.sep.heap_size()
+    }
+}
+
+impl<T, Sep, S: EstimateSize> EstimateSize for Repeated<T, Sep, S> {
+    #[inline]
+    fn heap_size(&self) -> usize {
+        self.values.heap_size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem::size_of;
+
+    #[test]
+    fn test_inline_types_have_no_heap() {
+        assert_eq!(42u64.heap_size(), 0);
+        assert_eq!(42u64.estimated_size(), 8);
+        assert_eq!(("a", 1u8).heap_size(), 0);
+    }
+
+    #[test]
+    fn test_counts_capacity() {
+        let s = String::with_capacity(100);
+        assert_eq!(s.heap_size(), 100);
+
+        let v: Vec<u32> = Vec::with_capacity(10);
+        assert_eq!(v.heap_size(), 40);
+        assert_eq!(v.estimated_size(), size_of::<Vec<u32>>() + 40);
+    }
+
+    #[test]
+    fn test_nested_heap() {
+        let v = vec![String::with_capacity(8), String::with_capacity(16)];
+        assert_eq!(v.heap_size(), 2 * size_of::<String>() + 24);
+
+        let boxed = Box::new(String::with_capacity(4));
+        assert_eq!(boxed.heap_size(), size_of::<String>() + 4);
+        assert_eq!(Some(boxed).heap_size(), size_of::<String>() + 4);
+    }
+
+    #[test]
+    fn test_cow() {
+        let borrowed: Cow<'static, str> = Cow::Borrowed("static");
+        assert_eq!(borrowed.heap_size(), 0);
+
+        let owned: Cow<'static, str> = Cow::Owned(String::with_capacity(12));
+        assert_eq!(owned.heap_size(), 12);
+    }
+
+    #[test]
+    fn test_punctuated() {
+        let mut p: Punctuated<String, u8> = Punctuated::new();
+        p.push_value(String::with_capacity(5));
+        p.push_punct(b',');
+        let inner: &PunctuatedInner<String, u8> = p.as_ref();
+        let expected = inner.inner.capacity() * size_of::<(String, Option<u8>)>() + 5;
+        assert_eq!(p.heap_size(), expected);
+    }
+}
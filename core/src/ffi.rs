@@ -0,0 +1,353 @@
+//! Helpers for exposing parsers over a C ABI.
+//!
+//! Parsers are exported as `extern "C"` functions taking UTF-8 input as a
+//! pointer/length pair and returning a [`SynkitResult`]: either the AST
+//! serialized as JSON, or a [`SynkitError`] with a status code, message and
+//! optional byte span. All types are `#[repr(C)]` and free of generics, so
+//! `cbindgen` can produce a header directly.
+//!
+//! ```ignore
+//! synkit::export_parser!(toml_parse => Document::parse_str);
+//! ```
+//!
+//! ```c
+//! SynkitResult r = toml_parse((const uint8_t *)src, strlen(src));
+//! if (r.status == SYNKIT_STATUS_OK) {
+//!     puts(r.json);
+//! } else {
+//!     fprintf(stderr, "%zu: %s\n", r.error.start, r.error.message);
+//! }
+//! synkit_result_free(&r);
+//! ```
+#![allow(unsafe_code)]
+
+use std::ffi::{CString, c_char};
+use std::fmt::Display;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use serde::Serialize;
+
+use crate::{SpanLike, SpannedError};
+
+/// Outcome of an exported parse call.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SynkitStatus {
+    /// Parsing succeeded; `json` holds the AST.
+    Ok = 0,
+    /// The input did not parse.
+    ParseError = 1,
+    /// The input was not valid UTF-8.
+    InvalidUtf8 = 2,
+    /// The input pointer was null with a non-zero length.
+    NullInput = 3,
+    /// The AST could not be serialized.
+    SerializeError = 4,
+    /// The parser panicked; `error.message` holds the panic message.
+    Panic = 5,
+}
+
+/// Error details. All fields are zero/null on success.
+#[repr(C)]
+#[derive(Debug)]
+pub struct SynkitError {
+    /// NUL-terminated message, owned by the result.
+    pub message: *mut c_char,
+    /// Whether `start`/`end` hold a span.
+    pub has_span: bool,
+    /// Start byte offset of the error.
+    pub start: usize,
+    /// End byte offset of the error (exclusive).
+    pub end: usize,
+}
+
+impl SynkitError {
+    const fn none() -> Self {
+        Self {
+            message: ptr::null_mut(),
+            has_span: false,
+            start: 0,
+            end: 0,
+        }
+    }
+}
+
+/// Result of an exported parse call. Release with [`synkit_result_free`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct SynkitResult {
+    /// Outcome of the call.
+    pub status: SynkitStatus,
+    /// NUL-terminated JSON AST on success, null otherwise.
+    pub json: *mut c_char,
+    /// Error details when `status` is not `Ok`.
+    pub error: SynkitError,
+}
+
+impl SynkitResult {
+    fn ok(json: String) -> Self {
+        Self {
+            status: SynkitStatus::Ok,
+            json: into_c_string(json),
+            error: SynkitError::none(),
+        }
+    }
+
+    fn err(status: SynkitStatus, message: String, span: Option<(usize, usize)>) -> Self {
+        let (start, end) = span.unwrap_or((0, 0));
+        Self {
+            status,
+            json: ptr::null_mut(),
+            error: SynkitError {
+                message: into_c_string(message),
+                has_span: span.is_some(),
+                start,
+                end,
+            },
+        }
+    }
+}
+
+/// Run `parse` on a UTF-8 buffer and package the outcome for C callers.
+///
+/// Panics in `parse` or in serialization are caught and reported as
+/// [`SynkitStatus::Panic`] rather than unwinding into the caller.
+///
+/// # Safety
+///
+/// `input` must be null (with `len == 0`) or point to `len` readable bytes
+/// that stay valid for the duration of the call.
+pub unsafe fn parse_to_c<T, E, F>(input: *const u8, len: usize, parse: F) -> SynkitResult
+where
+    T: Serialize,
+    E: Display + SpannedError,
+    F: FnOnce(&str) -> Result<T, E>,
+{
+    let bytes: &[u8] = if len == 0 {
+        &[]
+    } else if input.is_null() {
+        return SynkitResult::err(
+            SynkitStatus::NullInput,
+            "input pointer is null".to_string(),
+            None,
+        );
+    } else {
+        // SAFETY: caller guarantees `input` points to `len` readable bytes.
+        unsafe { std::slice::from_raw_parts(input, len) }
+    };
+
+    let source = match std::str::from_utf8(bytes) {
+        Ok(source) => source,
+        Err(e) => {
+            let start = e.valid_up_to();
+            let end = start + e.error_len().unwrap_or(1);
+            return SynkitResult::err(SynkitStatus::InvalidUtf8, e.to_string(), Some((start, end)));
+        }
+    };
+
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| match parse(source) {
+        Ok(value) => match serde_json::to_string(&value) {
+            Ok(json) => SynkitResult::ok(json),
+            Err(e) => SynkitResult::err(SynkitStatus::SerializeError, e.to_string(), None),
+        },
+        Err(error) => {
+            let span = error.span().map(|s| (s.start(), s.end()));
+            SynkitResult::err(SynkitStatus::ParseError, error.to_string(), span)
+        }
+    }));
+    outcome.unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| (*s).to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "parser panicked".to_string());
+        SynkitResult::err(SynkitStatus::Panic, message, None)
+    })
+}
+
+/// Free the strings owned by a [`SynkitResult`] and null them out.
+///
+/// # Safety
+///
+/// `result` must be null or point to a result returned by an exported parse
+/// function. Calling this twice on the same result is a no-op.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn synkit_result_free(result: *mut SynkitResult) {
+    // SAFETY: caller guarantees `result` is null or valid.
+    let Some(result) = (unsafe { result.as_mut() }) else {
+        return;
+    };
+    // SAFETY: both pointers were produced by `into_c_string` or are null.
+    unsafe {
+        free_c_string(result.json);
+        free_c_string(result.error.message);
+    }
+    result.json = ptr::null_mut();
+    result.error.message = ptr::null_mut();
+}
+
+/// Export `fn(&str) -> Result<T, E>` as an `extern "C"` parse function.
+///
+/// The generated function has the signature
+/// `SynkitResult name(const uint8_t *input, size_t len)`.
+#[macro_export]
+macro_rules! export_parser {
+    ($(#[$meta:meta])* $name:ident => $parse:expr) => {
+        $(#[$meta])*
+        ///
+        /// # Safety
+        ///
+        /// `input` must be null (with `len == 0`) or point to `len` readable bytes.
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn $name(input: *const u8, len: usize) -> $crate::ffi::SynkitResult {
+            let parse = $parse;
+            // SAFETY: forwarded from the caller.
+            unsafe { $crate::ffi::parse_to_c(input, len, parse) }
+        }
+    };
+}
+
+fn into_c_string(s: String) -> *mut c_char {
+    // Interior NULs would truncate the string on the C side; drop them.
+    let s = CString::new(s).unwrap_or_else(|e| {
+        let mut bytes = e.into_vec();
+        bytes.retain(|&b| b != 0);
+        CString::new(bytes).unwrap_or_default()
+    });
+    s.into_raw()
+}
+
+unsafe fn free_c_string(s: *mut c_char) {
+    if !s.is_null() {
+        // SAFETY: `s` came from `CString::into_raw`.
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    struct Span(usize, usize);
+
+    impl SpanLike for Span {
+        fn start(&self) -> usize {
+            self.0
+        }
+
+        fn end(&self) -> usize {
+            self.1
+        }
+
+        fn new(start: usize, end: usize) -> Self {
+            Self(start, end)
+        }
+
+        fn call_site() -> Self {
+            Self(0, 0)
+        }
+    }
+
+    #[derive(Debug)]
+    struct Error(Option<Span>);
+
+    impl Display for Error {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("expected number")
+        }
+    }
+
+    impl SpannedError for Error {
+        type Span = Span;
+
+        fn with_span(self, span: Span) -> Self {
+            Self(Some(span))
+        }
+
+        fn span(&self) -> Option<&Span> {
+            self.0.as_ref()
+        }
+    }
+
+    fn parse_number(input: &str) -> Result<i64, Error> {
+        input
+            .parse()
+            .map_err(|_| Error(None).with_span(Span(0, input.len())))
+    }
+
+    fn call(input: &[u8]) -> SynkitResult {
+        unsafe { parse_to_c(input.as_ptr(), input.len(), parse_number) }
+    }
+
+    fn c_str(s: *mut c_char) -> String {
+        unsafe { CStr::from_ptr(s) }.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_ok_json() {
+        let mut result = call(b"42");
+        assert_eq!(result.status, SynkitStatus::Ok);
+        assert_eq!(c_str(result.json), "42");
+        assert!(result.error.message.is_null());
+        unsafe { synkit_result_free(&mut result) };
+        assert!(result.json.is_null());
+    }
+
+    #[test]
+    fn test_parse_error_span() {
+        let mut result = call(b"abc");
+        assert_eq!(result.status, SynkitStatus::ParseError);
+        assert!(result.json.is_null());
+        assert_eq!(c_str(result.error.message), "expected number");
+        assert!(result.error.has_span);
+        assert_eq!((result.error.start, result.error.end), (0, 3));
+        unsafe {
+            synkit_result_free(&mut result);
+            synkit_result_free(&mut result);
+        }
+    }
+
+    crate::export_parser!(synkit_test_parse_number => parse_number);
+
+    #[test]
+    fn test_export_parser() {
+        let input = b"7";
+        let mut result = unsafe { synkit_test_parse_number(input.as_ptr(), input.len()) };
+        assert_eq!(result.status, SynkitStatus::Ok);
+        assert_eq!(c_str(result.json), "7");
+        unsafe { synkit_result_free(&mut result) };
+    }
+
+    #[test]
+    fn test_panic_is_caught() {
+        let input = b"1";
+        let mut result = unsafe {
+            parse_to_c(
+                input.as_ptr(),
+                input.len(),
+                |s: &str| -> Result<i64, Error> { panic!("unreachable token at {}", s.len()) },
+            )
+        };
+        assert_eq!(result.status, SynkitStatus::Panic);
+        assert!(result.json.is_null());
+        assert_eq!(c_str(result.error.message), "unreachable token at 1");
+        assert!(!result.error.has_span);
+        unsafe { synkit_result_free(&mut result) };
+    }
+
+    #[test]
+    fn test_invalid_utf8_and_null() {
+        let mut result = call(b"1\xff2");
+        assert_eq!(result.status, SynkitStatus::InvalidUtf8);
+        assert_eq!((result.error.start, result.error.end), (1, 2));
+        unsafe { synkit_result_free(&mut result) };
+
+        let mut result = unsafe { parse_to_c(ptr::null(), 3, parse_number) };
+        assert_eq!(result.status, SynkitStatus::NullInput);
+        unsafe { synkit_result_free(&mut result) };
+    }
+}
@@ -0,0 +1,313 @@
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::{Punctuated, Separated, Terminated};
+
+/// Seeded, size-bounded source of random AST values.
+///
+/// The same seed always produces the same values, so a generated corpus is
+/// reproducible from `(seed, max_size, max_depth)` alone. Generation stops
+/// growing once either limit is reached: `Option`s become `None`, sequences
+/// stop and derived enums pick their first variant. The limits are soft:
+/// fields a node requires are still generated after the budget runs out.
+///
+/// # Example
+///
+/// ```ignore
+/// use synkit::{Generate, Generator};
+///
+/// let mut g = Generator::new(42).with_max_size(200);
+/// let doc = Document::generate(&mut g);
+/// let source = doc.to_string_formatted();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Generator {
+    state: u64,
+    depth: usize,
+    max_depth: usize,
+    remaining: usize,
+}
+
+impl Generator {
+    /// Create a generator with at most 256 tokens and nesting depth 16.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: seed,
+            depth: 0,
+            max_depth: 16,
+            remaining: 256,
+        }
+    }
+
+    /// Stop growing after `tokens` tokens have been generated.
+    pub fn with_max_size(mut self, tokens: usize) -> Self {
+        self.remaining = tokens;
+        self
+    }
+
+    /// Stop growing below `depth` levels of nesting.
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Next pseudo-random value (SplitMix64).
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A value in `0..n`, or 0 if `n` is 0.
+    pub fn below(&mut self, n: usize) -> usize {
+        if n == 0 {
+            0
+        } else {
+            (self.next_u64() % n as u64) as usize
+        }
+    }
+
+    /// True with probability `numerator / denominator`.
+    pub fn ratio(&mut self, numerator: u32, denominator: u32) -> bool {
+        denominator > 0 && self.below(denominator as usize) < numerator as usize
+    }
+
+    /// A random element of `items`.
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        items.get(self.below(items.len()))
+    }
+
+    /// Current nesting depth.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Tokens left before the size limit.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// Whether the size or depth limit has been reached.
+    #[inline]
+    pub fn exhausted(&self) -> bool {
+        self.remaining == 0 || self.depth >= self.max_depth
+    }
+
+    /// Count one generated token against the size limit.
+    #[inline]
+    pub fn take_token(&mut self) {
+        self.remaining = self.remaining.saturating_sub(1);
+    }
+
+    /// Run `f` one nesting level deeper.
+    pub fn nested<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.depth += 1;
+        let out = f(self);
+        self.depth -= 1;
+        out
+    }
+
+    /// Generate a token payload from one of `samples`, starting at a random
+    /// one and returning the first that `lex` accepts.
+    ///
+    /// Generated token structs call this with their `#[sample]`, `#[token]`
+    /// or regex-derived samples; `lex` returns the payload when the sample
+    /// lexes as exactly that token.
+    ///
+    /// # Panics
+    ///
+    /// If no sample is accepted.
+    #[track_caller]
+    #[allow(clippy::panic)]
+    pub fn sample_token<T>(
+        &mut self,
+        token: &str,
+        samples: &[&str],
+        mut lex: impl FnMut(&str) -> Option<T>,
+    ) -> T {
+        self.take_token();
+        let start = self.below(samples.len());
+        let rotated = samples[start..].iter().chain(&samples[..start]);
+        for sample in rotated {
+            if let Some(value) = lex(sample) {
+                return value;
+            }
+        }
+        panic!("no sample lexes as `{token}`; add one with `#[sample(\"...\")]`")
+    }
+
+    /// A sequence length in `0..=max`, or 0 once exhausted.
+    pub fn repeat_len(&mut self, max: usize) -> usize {
+        if self.exhausted() {
+            0
+        } else {
+            self.below(max + 1)
+        }
+    }
+}
+
+/// AST nodes that can be generated at random.
+///
+/// Printing a generated value with `ToTokens` gives syntactically valid
+/// input, for differential testing, fuzz corpora and benchmarks. Token
+/// structs and `Spanned<T>` from `parser_kit!` implement it; use
+/// `#[derive(Generate)]` on AST nodes and implement it by hand where the
+/// grammar has constraints the types don't express.
+pub trait Generate: Sized {
+    /// Generate a value, drawing randomness and limits from `g`.
+    fn generate(g: &mut Generator) -> Self;
+}
+
+impl<T: Generate> Generate for Option<T> {
+    fn generate(g: &mut Generator) -> Self {
+        (!g.exhausted() && g.ratio(1, 2)).then(|| g.nested(T::generate))
+    }
+}
+
+/// Implement `Generate` for owning pointers, one level deeper.
+macro_rules! impl_generate_pointer {
+    ($($ptr:ident),*) => {
+        $(
+            impl<T: Generate> Generate for $ptr<T> {
+                fn generate(g: &mut Generator) -> Self {
+                    $ptr::new(g.nested(T::generate))
+                }
+            }
+        )*
+    };
+}
+
+impl_generate_pointer!(Box, Rc, Arc);
+
+impl<T: Generate> Generate for Vec<T> {
+    fn generate(g: &mut Generator) -> Self {
+        let len = g.repeat_len(4);
+        g.nested(|g| {
+            (0..len)
+                .map_while(|_| (g.remaining() > 0).then(|| T::generate(g)))
+                .collect()
+        })
+    }
+}
+
+/// Implement `Generate` for tuples element-wise.
+macro_rules! impl_generate_tuple {
+    ($(($($name:ident),+)),* $(,)?) => {
+        $(
+            impl<$($name: Generate),+> Generate for ($($name,)+) {
+                fn generate(g: &mut Generator) -> Self {
+                    ($($name::generate(g),)+)
+                }
+            }
+        )*
+    };
+}
+
+impl_generate_tuple!(
+    (A),
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F),
+);
+
+/// Implement `Generate` for the punctuated wrappers; `$trailing` decides
+/// whether a non-empty sequence ends with a separator.
+macro_rules! impl_generate_punctuated {
+    ($($name:ident => $trailing:expr),* $(,)?) => {
+        $(
+            impl<T: Generate, P: Generate> Generate for $name<T, P> {
+                fn generate(g: &mut Generator) -> Self {
+                    let len = g.repeat_len(4);
+                    let mut items = Self::new();
+                    g.nested(|g| {
+                        for i in 0..len {
+                            if g.remaining() == 0 {
+                                break;
+                            }
+                            items.push_value(T::generate(g));
+                            let trailing: fn(&mut Generator) -> bool = $trailing;
+                            if i + 1 < len || trailing(g) {
+                                items.push_punct(P::generate(g));
+                            }
+                        }
+                    });
+                    items
+                }
+            }
+        )*
+    };
+}
+
+impl_generate_punctuated!(
+    Punctuated => |g| g.ratio(1, 4),
+    Terminated => |_| true,
+    Separated => |_| false,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl Generate for u8 {
+        fn generate(g: &mut Generator) -> Self {
+            g.take_token();
+            g.below(256) as u8
+        }
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let a: Vec<Vec<u8>> = Generate::generate(&mut Generator::new(7));
+        let b: Vec<Vec<u8>> = Generate::generate(&mut Generator::new(7));
+        assert_eq!(a, b);
+
+        let seeds: Vec<Vec<Vec<u8>>> = (0..8)
+            .map(|seed| Generate::generate(&mut Generator::new(seed)))
+            .collect();
+        assert!(seeds.iter().any(|v| v != &seeds[0]));
+    }
+
+    #[test]
+    fn test_size_bound() {
+        for seed in 0..32 {
+            let mut g = Generator::new(seed).with_max_size(10);
+            let v: Vec<Vec<Vec<u8>>> = Generate::generate(&mut g);
+            let tokens: usize = v.iter().flatten().map(Vec::len).sum();
+            assert!(tokens <= 10);
+        }
+
+        let mut g = Generator::new(1).with_max_depth(0);
+        assert!(g.exhausted());
+        assert_eq!(Option::<u8>::generate(&mut g), None);
+    }
+
+    #[test]
+    fn test_sample_token() {
+        let mut g = Generator::new(3);
+        let n: u32 = g.sample_token("Number", &["x", "12", "y"], |s| s.parse().ok());
+        assert_eq!(n, 12);
+        assert_eq!(g.remaining(), 255);
+
+        let result = std::panic::catch_unwind(|| {
+            Generator::new(0).sample_token("Number", &["x"], |s| s.parse::<u32>().ok())
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_punctuated_policies() {
+        for seed in 0..32 {
+            let mut g = Generator::new(seed);
+            let t: Terminated<u8, u8> = Generate::generate(&mut g);
+            assert!(t.is_empty() || t.trailing_punct());
+            let s: Separated<u8, u8> = Generate::generate(&mut g);
+            assert!(!s.trailing_punct());
+        }
+    }
+}
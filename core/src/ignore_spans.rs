@@ -0,0 +1,444 @@
+use std::borrow::Cow;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::{
+    Delimited, Punctuated, PunctuatedInner, Repeated, RepeatedItem, Separated, Terminated,
+};
+
+/// Structural equality and hashing that ignores source spans.
+///
+/// Implemented by span types (always equal), `Spanned<T>` wrappers (compare
+/// only the value) and everything built from them. Use
+/// `#[derive(SpanInsensitive)]` on AST nodes and wrap values in
+/// [`IgnoreSpans`] to compare or hash them structurally.
+///
+/// # Example
+///
+/// ```ignore
+/// use synkit::{IgnoreSpans, SpanInsensitive};
+///
+/// #[derive(SpanInsensitive)]
+/// struct KeyValue {
+///     key: Spanned<Key>,
+///     value: Spanned<Value>,
+/// }
+///
+/// let a = KeyValue::parse_str("a = 1")?;
+/// let b = KeyValue::parse_str("a    =    1")?;
+/// assert!(a.eq_ignore_spans(&b));
+/// assert_eq!(IgnoreSpans(a), IgnoreSpans(b));
+/// ```
+pub trait SpanInsensitive {
+    /// Compare `self` and `other`, ignoring spans.
+    fn eq_ignore_spans(&self, other: &Self) -> bool;
+
+    /// Hash `self`, ignoring spans.
+    ///
+    /// Must be consistent with [`eq_ignore_spans`](Self::eq_ignore_spans).
+    fn hash_ignore_spans<H: Hasher>(&self, state: &mut H);
+}
+
+/// Wrapper whose `PartialEq`, `Eq` and `Hash` ignore spans.
+///
+/// Useful for test assertions and as a cache/map key for ASTs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IgnoreSpans<T>(pub T);
+
+impl<T> IgnoreSpans<T> {
+    /// Unwrap the inner value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: SpanInsensitive> PartialEq for IgnoreSpans<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eq_ignore_spans(&other.0)
+    }
+}
+
+impl<T: SpanInsensitive> Eq for IgnoreSpans<T> {}
+
+impl<T: SpanInsensitive> Hash for IgnoreSpans<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash_ignore_spans(state);
+    }
+}
+
+impl<T> std::ops::Deref for IgnoreSpans<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> std::ops::DerefMut for IgnoreSpans<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// Implement `SpanInsensitive` via `PartialEq` + `Hash` for span-free types.
+macro_rules! impl_span_insensitive_eq {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl SpanInsensitive for $ty {
+                #[inline]
+                fn eq_ignore_spans(&self, other: &Self) -> bool {
+                    self == other
+                }
+
+                #[inline]
+                fn hash_ignore_spans<H: Hasher>(&self, state: &mut H) {
+                    self.hash(state);
+                }
+            }
+        )*
+    };
+}
+
+impl_span_insensitive_eq!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    str,
+    String,
+);
+
+/// Floats compare bitwise so that equality stays consistent with hashing.
+macro_rules! impl_span_insensitive_float {
+    ($($ty:ty),*) => {
+        $(
+            impl SpanInsensitive for $ty {
+                #[inline]
+                fn eq_ignore_spans(&self, other: &Self) -> bool {
+                    self.to_bits() == other.to_bits()
+                }
+
+                #[inline]
+                fn hash_ignore_spans<H: Hasher>(&self, state: &mut H) {
+                    self.to_bits().hash(state);
+                }
+            }
+        )*
+    };
+}
+
+impl_span_insensitive_float!(f32, f64);
+
+/// Implement `SpanInsensitive` for smart pointers by delegating to the pointee.
+macro_rules! impl_span_insensitive_deref {
+    ($($ptr:ident),*) => {
+        $(
+            impl<T: SpanInsensitive + ?Sized> SpanInsensitive for $ptr<T> {
+                #[inline]
+                fn eq_ignore_spans(&self, other: &Self) -> bool {
+                    (**self).eq_ignore_spans(&**other)
+                }
+
+                #[inline]
+                fn hash_ignore_spans<H: Hasher>(&self, state: &mut H) {
+                    (**self).hash_ignore_spans(state);
+                }
+            }
+        )*
+    };
+}
+
+impl_span_insensitive_deref!(Box, Rc, Arc);
+
+impl<B: SpanInsensitive + ToOwned + ?Sized> SpanInsensitive for Cow<'_, B> {
+    #[inline]
+    fn eq_ignore_spans(&self, other: &Self) -> bool {
+        (**self).eq_ignore_spans(&**other)
+    }
+
+    #[inline]
+    fn hash_ignore_spans<H: Hasher>(&self, state: &mut H) {
+        (**self).hash_ignore_spans(state);
+    }
+}
+
+#[cfg(feature = "compact_str")]
+impl_span_insensitive_eq!(compact_str::CompactString);
+
+impl<T: SpanInsensitive + ?Sized> SpanInsensitive for &T {
+    #[inline]
+    fn eq_ignore_spans(&self, other: &Self) -> bool {
+        (**self).eq_ignore_spans(&**other)
+    }
+
+    #[inline]
+    fn hash_ignore_spans<H: Hasher>(&self, state: &mut H) {
+        (**self).hash_ignore_spans(state);
+    }
+}
+
+impl<T: SpanInsensitive> SpanInsensitive for Option<T> {
+    fn eq_ignore_spans(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Some(a), Some(b)) => a.eq_ignore_spans(b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    fn hash_ignore_spans<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Some(v) => {
+                state.write_u8(1);
+                v.hash_ignore_spans(state);
+            }
+            None => state.write_u8(0),
+        }
+    }
+}
+
+impl<T: SpanInsensitive> SpanInsensitive for [T] {
+    fn eq_ignore_spans(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .zip(other.iter())
+                .all(|(a, b)| a.eq_ignore_spans(b))
+    }
+
+    fn hash_ignore_spans<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for item in self {
+            item.hash_ignore_spans(state);
+        }
+    }
+}
+
+impl<T: SpanInsensitive, const N: usize> SpanInsensitive for [T; N] {
+    #[inline]
+    fn eq_ignore_spans(&self, other: &Self) -> bool {
+        self.as_slice().eq_ignore_spans(other.as_slice())
+    }
+
+    #[inline]
+    fn hash_ignore_spans<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash_ignore_spans(state);
+    }
+}
+
+impl<T: SpanInsensitive> SpanInsensitive for Vec<T> {
+    #[inline]
+    fn eq_ignore_spans(&self, other: &Self) -> bool {
+        self.as_slice().eq_ignore_spans(other.as_slice())
+    }
+
+    #[inline]
+    fn hash_ignore_spans<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash_ignore_spans(state);
+    }
+}
+
+/// Implement `SpanInsensitive` for tuples element-wise.
+macro_rules! impl_span_insensitive_tuple {
+    ($(($($name:ident $idx:tt),+)),* $(,)?) => {
+        $(
+            impl<$($name: SpanInsensitive),+> SpanInsensitive for ($($name,)+) {
+                fn eq_ignore_spans(&self, other: &Self) -> bool {
+                    $(self.$idx.eq_ignore_spans(&other.$idx))&&+
+                }
+
+                fn hash_ignore_spans<H: Hasher>(&self, state: &mut H) {
+                    $(self.$idx.hash_ignore_spans(state);)+
+                }
+            }
+        )*
+    };
+}
+
+impl_span_insensitive_tuple!(
+    (A 0),
+    (A 0, B 1),
+    (A 0, B 1, C 2),
+    (A 0, B 1, C 2, D 3),
+    (A 0, B 1, C 2, D 3, E 4),
+    (A 0, B 1, C 2, D 3, E 4, F 5),
+);
+
+impl<T: SpanInsensitive, P: SpanInsensitive> SpanInsensitive for PunctuatedInner<T, P> {
+    #[inline]
+    fn eq_ignore_spans(&self, other: &Self) -> bool {
+        self.inner.eq_ignore_spans(&other.inner)
+    }
+
+    #[inline]
+    fn hash_ignore_spans<H: Hasher>(&self, state: &mut H) {
+        self.inner.hash_ignore_spans(state);
+    }
+}
+
+/// Implement `SpanInsensitive` for the punctuated wrappers via their inner storage.
+macro_rules! impl_span_insensitive_punctuated {
+    ($($name:ident),*) => {
+        $(
+            impl<T: SpanInsensitive, P: SpanInsensitive> SpanInsensitive for $name<T, P> {
+                #[inline]
+                fn eq_ignore_spans(&self, other: &Self) -> bool {
+                    self.as_ref().eq_ignore_spans(other.as_ref())
+                }
+
+                #[inline]
+                fn hash_ignore_spans<H: Hasher>(&self, state: &mut H) {
+                    self.as_ref().hash_ignore_spans(state);
+                }
+            }
+        )*
+    };
+}
+
+impl_span_insensitive_punctuated!(Punctuated, Terminated, Separated);
+
+impl<T: SpanInsensitive, Span> SpanInsensitive for Delimited<T, Span> {
+    #[inline]
+    fn eq_ignore_spans(&self, other: &Self) -> bool {
+        self.inner.eq_ignore_spans(&other.inner)
+    }
+
+    #[inline]
+    fn hash_ignore_spans<H: Hasher>(&self, state: &mut H) {
+        self.inner.hash_ignore_spans(state);
+    }
+}
+
+impl<T, Sep, S: SpanInsensitive> SpanInsensitive for RepeatedItem<T, Sep, S> {
+    fn eq_ignore_spans(&self, other: &Self) -> bool {
+        self.value.eq_ignore_spans(&other.value) && self.sep.eq_ignore_spans(&other.sep)
+    }
+
+    fn hash_ignore_spans<H: Hasher>(&self, state: &mut H) {
+        self.value.hash_ignore_spans(state);
+        self.sep.hash_ignore_spans(state);
+    }
+}
+
+impl<T, Sep, S: SpanInsensitive> SpanInsensitive for Repeated<T, Sep, S> {
+    #[inline]
+    fn eq_ignore_spans(&self, other: &Self) -> bool {
+        self.values.eq_ignore_spans(&other.values)
+    }
+
+    #[inline]
+    fn hash_ignore_spans<H: Hasher>(&self, state: &mut H) {
+        self.values.hash_ignore_spans(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+
+    /// Minimal spanned wrapper for exercising span-insensitive comparisons.
+    #[derive(Debug)]
+    struct Sp<T> {
+        start: usize,
+        value: T,
+    }
+
+    impl<T: SpanInsensitive> SpanInsensitive for Sp<T> {
+        fn eq_ignore_spans(&self, other: &Self) -> bool {
+            self.value.eq_ignore_spans(&other.value)
+        }
+
+        fn hash_ignore_spans<H: Hasher>(&self, state: &mut H) {
+            self.value.hash_ignore_spans(state);
+        }
+    }
+
+    fn hash_of<T: SpanInsensitive>(value: T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        IgnoreSpans(value).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_spans_ignored_in_containers() {
+        let a = vec![
+            Some(Sp {
+                start: 0,
+                value: 1u8,
+            }),
+            None,
+        ];
+        let b = vec![
+            Some(Sp {
+                start: 9,
+                value: 1u8,
+            }),
+            None,
+        ];
+        assert_ne!(
+            a[0].as_ref().map(|s| s.start),
+            b[0].as_ref().map(|s| s.start)
+        );
+        assert!(a.eq_ignore_spans(&b));
+
+        let c = vec![
+            Some(Sp {
+                start: 0,
+                value: 2u8,
+            }),
+            None,
+        ];
+        assert!(!a.eq_ignore_spans(&c));
+        assert_eq!(hash_of(&a), hash_of(&b));
+    }
+
+    #[test]
+    fn test_punctuated_compares_values_and_punct() {
+        let mut a: Punctuated<Sp<&str>, Sp<char>> = Punctuated::new();
+        a.push_value(Sp {
+            start: 0,
+            value: "x",
+        });
+        a.push_punct(Sp {
+            start: 1,
+            value: ',',
+        });
+
+        let mut b: Punctuated<Sp<&str>, Sp<char>> = Punctuated::new();
+        b.push_value(Sp {
+            start: 5,
+            value: "x",
+        });
+        assert!(!a.eq_ignore_spans(&b));
+
+        b.push_punct(Sp {
+            start: 7,
+            value: ',',
+        });
+        assert!(a.eq_ignore_spans(&b));
+        assert_eq!(IgnoreSpans(a), IgnoreSpans(b));
+    }
+
+    #[test]
+    fn test_float_bitwise() {
+        assert!(f64::NAN.eq_ignore_spans(&f64::NAN));
+        assert!(!0.0f64.eq_ignore_spans(&-0.0));
+        assert_eq!(hash_of(f64::NAN), hash_of(f64::NAN));
+    }
+}
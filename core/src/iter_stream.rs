@@ -0,0 +1,165 @@
+//! Synchronous incremental parsing over token iterators.
+//!
+//! [`IterParseStream`] is the blocking counterpart of the async
+//! `ParseStream`: it pulls tokens from any [`Iterator`] and yields AST nodes
+//! through [`IncrementalParse`] as soon as they are complete. Pipelines that
+//! already read input line by line or chunk by chunk can use it without a
+//! runtime or the `futures` feature.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use synkit::iter_stream::IterParseStream;
+//!
+//! let tokens = TokenStream::lex(source)?.into_vec();
+//! for line in IterParseStream::<_, JsonLine>::new(tokens) {
+//!     process(line?);
+//! }
+//! ```
+
+use core::marker::PhantomData;
+
+use crate::async_stream::{IncrementalParse, ParseCheckpoint, StreamError};
+
+/// An iterator adapter that yields parsed AST nodes from a token iterator.
+///
+/// Items only need to implement `AsRef<T::Token>`, so an iterator of
+/// `Spanned<Token>` can feed a parser written against bare tokens.
+///
+/// Tokens are buffered until [`IncrementalParse::can_parse`] reports that a
+/// node may be available, then [`IncrementalParse::parse_incremental`] is
+/// run from the last checkpoint. Consumed tokens are periodically dropped
+/// from the front of the buffer, so memory stays proportional to the largest
+/// node rather than the whole input.
+///
+/// The iterator is fused: after a parse error, or once the input is
+/// exhausted, it only returns `None`.
+pub struct IterParseStream<I, T>
+where
+    I: Iterator,
+{
+    inner: I,
+    token_buffer: Vec<I::Item>,
+    checkpoint: ParseCheckpoint,
+    exhausted: bool,
+    done: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<I, T> IterParseStream<I, T>
+where
+    I: Iterator,
+    T: IncrementalParse,
+    I::Item: AsRef<T::Token>,
+{
+    /// Create a new parse stream over a token iterator.
+    pub fn new(tokens: impl IntoIterator<IntoIter = I>) -> Self {
+        Self::with_capacity(tokens, 256)
+    }
+
+    /// Create a new parse stream with pre-allocated token buffer.
+    ///
+    /// Use this when you have an estimate of how many tokens make up a node.
+    pub fn with_capacity(tokens: impl IntoIterator<IntoIter = I>, capacity: usize) -> Self {
+        Self {
+            inner: tokens.into_iter(),
+            token_buffer: Vec::with_capacity(capacity),
+            checkpoint: ParseCheckpoint::default(),
+            exhausted: false,
+            done: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of buffered tokens not yet consumed by a node.
+    #[inline]
+    pub fn buffered(&self) -> usize {
+        self.token_buffer.len() - self.checkpoint.cursor.min(self.token_buffer.len())
+    }
+
+    /// Returns the current parse checkpoint.
+    #[inline]
+    pub fn checkpoint(&self) -> &ParseCheckpoint {
+        &self.checkpoint
+    }
+
+    /// Consume the stream, returning the underlying token iterator.
+    ///
+    /// Tokens already pulled into the buffer are lost.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+
+    /// Run the parser from the checkpoint, adopting checkpoints that advance
+    /// without producing a node (e.g. skipped blank lines).
+    fn try_parse(&mut self) -> Option<Result<T, StreamError>> {
+        loop {
+            match T::parse_incremental(&self.token_buffer, &self.checkpoint) {
+                Ok((Some(node), checkpoint)) => {
+                    self.checkpoint = checkpoint;
+                    self.compact();
+                    return Some(Ok(node));
+                }
+                Ok((None, checkpoint)) if checkpoint.cursor > self.checkpoint.cursor => {
+                    self.checkpoint = checkpoint;
+                }
+                Ok((None, _)) => return None,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(StreamError::ParseError(e.to_string())));
+                }
+            }
+        }
+    }
+
+    /// Drop consumed tokens once they make up at least half the buffer.
+    fn compact(&mut self) {
+        let cursor = self.checkpoint.cursor.min(self.token_buffer.len());
+        if cursor > 0 && cursor * 2 >= self.token_buffer.len() {
+            self.token_buffer.drain(..cursor);
+            self.checkpoint.cursor = 0;
+        }
+    }
+}
+
+impl<I, T> Iterator for IterParseStream<I, T>
+where
+    I: Iterator,
+    T: IncrementalParse,
+    I::Item: AsRef<T::Token>,
+{
+    type Item = Result<T, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        loop {
+            if self.exhausted || T::can_parse(&self.token_buffer, &self.checkpoint) {
+                if let Some(result) = self.try_parse() {
+                    return Some(result);
+                }
+            }
+
+            if self.exhausted {
+                self.done = true;
+                return (self.checkpoint.cursor < self.token_buffer.len())
+                    .then_some(Err(StreamError::IncompleteInput));
+            }
+
+            match self.inner.next() {
+                Some(token) => self.token_buffer.push(token),
+                None => self.exhausted = true,
+            }
+        }
+    }
+}
+
+impl<I, T> core::iter::FusedIterator for IterParseStream<I, T>
+where
+    I: Iterator,
+    T: IncrementalParse,
+    I::Item: AsRef<T::Token>,
+{
+}
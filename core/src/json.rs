@@ -0,0 +1,347 @@
+use std::borrow::Cow;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use serde_json::{Map, Value};
+
+use crate::traits::SpanLike;
+use crate::{
+    Delimited, Punctuated, PunctuatedInner, Repeated, RepeatedItem, Separated, Terminated,
+};
+
+/// Conversion of a parse tree to a [`serde_json::Value`], for quick
+/// inspection, golden tests and REPL tooling.
+///
+/// Generated spans, `Spanned<T>` wrappers and tokens implement it with the
+/// `json` feature; use `#[derive(ToJsonValue)]` on AST nodes. Derived
+/// structs become objects (or their field, for newtypes) and enum variants
+/// are tagged like serde's default: `"Unit"`, `{"Variant": ...}`. Tokens
+/// without a payload become their `fmt()` text, such as `"="`, and token
+/// structs with one become the payload, which must implement `ToJsonValue`
+/// too. Punctuation in `Punctuated` lists is left out.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(ToJsonValue)]
+/// struct KeyValue {
+///     key: Spanned<IdentToken>,
+///     value: Spanned<NumberToken>,
+/// }
+///
+/// let kv = KeyValue::parse_str("a = 1")?;
+/// assert_eq!(kv.to_json_value(), json!({"key": "a", "value": 1}));
+/// assert_eq!(
+///     kv.to_json_value_with_spans(),
+///     json!({"key": {"span": [0, 1], "value": "a"}, "value": {"span": [4, 5], "value": 1}}),
+/// );
+/// ```
+pub trait ToJsonValue {
+    /// `self` as JSON. With `spans`, spanned values become
+    /// `{"span": [start, end], "value": ...}`; without, just their value.
+    fn to_json(&self, spans: bool) -> Value;
+
+    /// `self` as JSON without spans.
+    #[inline]
+    fn to_json_value(&self) -> Value {
+        self.to_json(false)
+    }
+
+    /// `self` as JSON with spans.
+    #[inline]
+    fn to_json_value_with_spans(&self) -> Value {
+        self.to_json(true)
+    }
+}
+
+/// `[start, end]`, or `null` for a call-site span.
+pub fn span_to_json<S: SpanLike>(span: &S) -> Value {
+    if span.is_call_site() {
+        Value::Null
+    } else {
+        Value::from(vec![span.start(), span.end()])
+    }
+}
+
+/// `value` with its span as `{"span": ..., "value": ...}` if `spans` is
+/// set, otherwise just `value`. For `Spanned<T>` impls.
+pub fn spanned_to_json<S: SpanLike, T: ToJsonValue + ?Sized>(
+    span: &S,
+    value: &T,
+    spans: bool,
+) -> Value {
+    let value = value.to_json(spans);
+    if !spans {
+        return value;
+    }
+    let mut object = Map::new();
+    object.insert("span".to_string(), span_to_json(span));
+    object.insert("value".to_string(), value);
+    Value::Object(object)
+}
+
+/// Implement `ToJsonValue` via `Value::from`.
+macro_rules! impl_to_json_from {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl ToJsonValue for $ty {
+                #[inline]
+                fn to_json(&self, _spans: bool) -> Value {
+                    Value::from(self.clone())
+                }
+            }
+        )*
+    };
+}
+
+impl_to_json_from!(
+    bool, u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, String,
+);
+
+/// Non-finite floats become `null`, as in `serde_json`.
+macro_rules! impl_to_json_float {
+    ($($ty:ty),*) => {
+        $(
+            impl ToJsonValue for $ty {
+                #[inline]
+                fn to_json(&self, _spans: bool) -> Value {
+                    serde_json::Number::from_f64(f64::from(*self)).map_or(Value::Null, Value::Number)
+                }
+            }
+        )*
+    };
+}
+
+impl_to_json_float!(f32, f64);
+
+/// 128-bit integers out of `u64`/`i64` range become strings.
+macro_rules! impl_to_json_wide {
+    ($($ty:ty => $narrow:ty),*) => {
+        $(
+            impl ToJsonValue for $ty {
+                #[inline]
+                fn to_json(&self, _spans: bool) -> Value {
+                    <$narrow>::try_from(*self)
+                        .map_or_else(|_| Value::String(self.to_string()), Value::from)
+                }
+            }
+        )*
+    };
+}
+
+impl_to_json_wide!(u128 => u64, i128 => i64);
+
+impl ToJsonValue for () {
+    #[inline]
+    fn to_json(&self, _spans: bool) -> Value {
+        Value::Null
+    }
+}
+
+impl ToJsonValue for char {
+    #[inline]
+    fn to_json(&self, _spans: bool) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+impl ToJsonValue for str {
+    #[inline]
+    fn to_json(&self, _spans: bool) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+#[cfg(feature = "compact_str")]
+impl ToJsonValue for compact_str::CompactString {
+    #[inline]
+    fn to_json(&self, _spans: bool) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+/// Implement `ToJsonValue` for smart pointers by delegating to the pointee.
+macro_rules! impl_to_json_deref {
+    ($($ptr:ident),*) => {
+        $(
+            impl<T: ToJsonValue + ?Sized> ToJsonValue for $ptr<T> {
+                #[inline]
+                fn to_json(&self, spans: bool) -> Value {
+                    (**self).to_json(spans)
+                }
+            }
+        )*
+    };
+}
+
+impl_to_json_deref!(Box, Rc, Arc);
+
+impl<B: ToJsonValue + ToOwned + ?Sized> ToJsonValue for Cow<'_, B> {
+    #[inline]
+    fn to_json(&self, spans: bool) -> Value {
+        (**self).to_json(spans)
+    }
+}
+
+impl<T: ToJsonValue + ?Sized> ToJsonValue for &T {
+    #[inline]
+    fn to_json(&self, spans: bool) -> Value {
+        (**self).to_json(spans)
+    }
+}
+
+impl<T: ToJsonValue> ToJsonValue for Option<T> {
+    #[inline]
+    fn to_json(&self, spans: bool) -> Value {
+        self.as_ref()
+            .map_or(Value::Null, |value| value.to_json(spans))
+    }
+}
+
+impl<T: ToJsonValue> ToJsonValue for [T] {
+    fn to_json(&self, spans: bool) -> Value {
+        Value::Array(self.iter().map(|item| item.to_json(spans)).collect())
+    }
+}
+
+impl<T: ToJsonValue, const N: usize> ToJsonValue for [T; N] {
+    #[inline]
+    fn to_json(&self, spans: bool) -> Value {
+        self.as_slice().to_json(spans)
+    }
+}
+
+impl<T: ToJsonValue> ToJsonValue for Vec<T> {
+    #[inline]
+    fn to_json(&self, spans: bool) -> Value {
+        self.as_slice().to_json(spans)
+    }
+}
+
+/// Implement `ToJsonValue` for tuples as arrays.
+macro_rules! impl_to_json_tuple {
+    ($(($($name:ident $idx:tt),+)),* $(,)?) => {
+        $(
+            impl<$($name: ToJsonValue),+> ToJsonValue for ($($name,)+) {
+                fn to_json(&self, spans: bool) -> Value {
+                    Value::Array(vec![$(self.$idx.to_json(spans)),+])
+                }
+            }
+        )*
+    };
+}
+
+impl_to_json_tuple!(
+    (A 0),
+    (A 0, B 1),
+    (A 0, B 1, C 2),
+    (A 0, B 1, C 2, D 3),
+    (A 0, B 1, C 2, D 3, E 4),
+    (A 0, B 1, C 2, D 3, E 4, F 5),
+);
+
+impl<T: ToJsonValue, P> ToJsonValue for PunctuatedInner<T, P> {
+    fn to_json(&self, spans: bool) -> Value {
+        Value::Array(self.iter().map(|item| item.to_json(spans)).collect())
+    }
+}
+
+/// Implement `ToJsonValue` for the punctuated wrappers via their inner storage.
+macro_rules! impl_to_json_punctuated {
+    ($($name:ident),*) => {
+        $(
+            impl<T: ToJsonValue, P> ToJsonValue for $name<T, P> {
+                #[inline]
+                fn to_json(&self, spans: bool) -> Value {
+                    self.as_ref().to_json(spans)
+                }
+            }
+        )*
+    };
+}
+
+impl_to_json_punctuated!(Punctuated, Terminated, Separated);
+
+impl<T: ToJsonValue, Span: SpanLike> ToJsonValue for Delimited<T, Span> {
+    #[inline]
+    fn to_json(&self, spans: bool) -> Value {
+        spanned_to_json(&self.span, &self.inner, spans)
+    }
+}
+
+impl<T, Sep, S: ToJsonValue> ToJsonValue for RepeatedItem<T, Sep, S> {
+    #[inline]
+    fn to_json(&self, spans: bool) -> Value {
+        self.value.to_json(spans)
+    }
+}
+
+impl<T, Sep, S: ToJsonValue> ToJsonValue for Repeated<T, Sep, S> {
+    #[inline]
+    fn to_json(&self, spans: bool) -> Value {
+        self.values.to_json(spans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct Sp(usize, usize);
+
+    impl SpanLike for Sp {
+        fn start(&self) -> usize {
+            self.0
+        }
+
+        fn end(&self) -> usize {
+            self.1
+        }
+
+        fn new(start: usize, end: usize) -> Self {
+            Sp(start, end)
+        }
+
+        fn call_site() -> Self {
+            Sp(usize::MAX, usize::MAX)
+        }
+
+        fn is_call_site(&self) -> bool {
+            self.0 == usize::MAX
+        }
+    }
+
+    #[test]
+    fn test_std_values() {
+        assert_eq!(
+            (1u8, "a", 'b', Some(true), None::<u8>, ()).to_json_value(),
+            json!([1, "a", "b", true, null, null])
+        );
+        assert_eq!(vec![1.5f64, f64::NAN].to_json_value(), json!([1.5, null]));
+        assert_eq!(u128::MAX.to_json_value(), json!(u128::MAX.to_string()));
+        assert_eq!((-5i128).to_json_value(), json!(-5));
+    }
+
+    #[test]
+    fn test_spans_only_when_asked() {
+        let delimited = Delimited::new(Sp(2, 9), vec!["x"]);
+        assert_eq!(delimited.to_json_value(), json!(["x"]));
+        assert_eq!(
+            delimited.to_json_value_with_spans(),
+            json!({"span": [2, 9], "value": ["x"]})
+        );
+        assert_eq!(span_to_json(&Sp::call_site()), Value::Null);
+    }
+
+    #[test]
+    fn test_punctuated_drops_punctuation() {
+        let mut list: Punctuated<u8, char> = Punctuated::new();
+        list.push_value(1);
+        list.push_punct(',');
+        list.push_value(2);
+        assert_eq!(list.to_json_value(), json!([1, 2]));
+    }
+}
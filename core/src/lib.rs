@@ -8,21 +8,101 @@
     clippy::dbg_macro
 )]
 
+mod attempts;
+#[cfg(feature = "binary")]
+pub mod binary;
 pub mod config;
+mod coverage;
 mod delimited;
 mod error;
+mod estimate_size;
+mod generate;
+mod ignore_spans;
+#[cfg(feature = "json")]
+mod json;
+mod line_index;
+mod lower;
+mod newlines;
+mod pool;
+mod progress;
+mod provenance;
 mod punctuated;
+mod query;
+pub mod recovery;
 mod repeated;
+mod rewriter;
+pub mod scan;
+pub mod scopes;
+mod source_files;
+mod source_map;
+mod span_error;
+mod string_payload;
+pub mod testing;
+mod token_dump;
 pub mod traits;
 
-#[cfg(any(feature = "tokio", feature = "futures"))]
 pub mod async_stream;
+pub mod iter_stream;
 
-pub use config::{ParseConfig, RecursionGuard};
+#[cfg(feature = "cache")]
+pub mod cache;
+
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub mod decompress;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "pyo3")]
+pub mod python;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(feature = "serde")]
+pub mod versioned;
+
+#[cfg(feature = "proc-macro2")]
+mod macro_input;
+
+pub use attempts::{Attempt, AttemptId, AttemptOutcome, AttemptTree, Conflict};
+#[cfg(feature = "compact_str")]
+pub use compact_str;
+pub use config::{ParseBudget, ParseConfig, RecursionGuard};
+pub use coverage::CoverageError;
 pub use delimited::Delimited;
-pub use error::Error;
+pub use error::{BudgetLimit, Error};
+pub use estimate_size::EstimateSize;
+pub use generate::{Generate, Generator};
+pub use ignore_spans::{IgnoreSpans, SpanInsensitive};
+#[cfg(feature = "json")]
+pub use json::{ToJsonValue, span_to_json, spanned_to_json};
+pub use line_index::{LineCol, LineIndex};
+pub use lower::{Lower, LowerCx, LowerDiagnostic, Lowered, NodeId, Severity};
+#[cfg(feature = "proc-macro2")]
+pub use macro_input::MacroInput;
+pub use newlines::NewlineMap;
+pub use pool::{NodePool, PoolStats, Recycle};
+#[cfg(feature = "proc-macro2")]
+pub use proc_macro2;
+pub use progress::{
+    OnProgress, OnProgressAsync, ProgressGranularity, ProgressReporter, ProgressTracker,
+};
+pub use provenance::{Provenance, ToolId};
 pub use punctuated::{Punctuated, PunctuatedInner, Separated, Terminated, TrailingPolicy};
+pub use query::{Match, Query, QueryError, QueryNode};
 pub use repeated::{Repeated, RepeatedItem};
+pub use rewriter::{Edit, RewriteError, Rewriter};
+#[cfg(feature = "serde")]
+pub use serde;
+#[cfg(feature = "json")]
+pub use serde_json;
+pub use source_files::{FileId, SourceFile, SourceFiles};
+pub use source_map::{MappingEntry, SourceMapping};
+pub use span_error::SpanError;
+pub use string_payload::IntoStringPayload;
+pub use token_dump::{DumpError, DumpedToken, TokenDump};
 pub use traits::{
-    Diagnostic, Parse, Peek, Printer, SpanLike, SpannedError, SpannedLike, ToTokens, TokenStream,
+    CommentPlacement, CommentPolicy, Diagnostic, Parse, Peek, Printer, SpanLike, SpannedError,
+    SpannedLike, ToTokens, TokenStream,
 };
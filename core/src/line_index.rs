@@ -0,0 +1,152 @@
+/// 1-based line and column of a byte offset.
+///
+/// Columns count characters, not bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LineCol {
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// Column number, starting at 1.
+    pub column: usize,
+}
+
+impl LineCol {
+    /// Locate `offset` in `source`.
+    ///
+    /// Offsets past the end are clamped to the end; offsets inside a
+    /// multi-byte character resolve to that character.
+    pub fn of(source: &str, offset: usize) -> Self {
+        let offset = floor_char_boundary(source, offset);
+        let prefix = &source[..offset];
+        let line_start = last_newline(prefix).map_or(0, |i| i + 1);
+        Self {
+            line: newlines(prefix).count() + 1,
+            column: prefix[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+/// Byte offsets of the start of each line, for repeated line/column lookups.
+///
+/// [`LineCol::of`] scans the source on every call; a `LineIndex` is built
+/// once per source and answers lines by binary search. Generated streams
+/// build one at lex time with `line_index: true`.
+///
+/// # Example
+///
+/// ```ignore
+/// let index = LineIndex::new(source);
+/// let pos = index.line_col(source, err_span.start());
+/// eprintln!("{}:{}: {}", pos.line, pos.column, err);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineIndex {
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    /// Index the lines of `source`.
+    pub fn new(source: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(newlines(source).map(|i| i + 1))
+            .collect();
+        Self { line_starts }
+    }
+
+    /// Number of lines; a trailing newline starts an empty last line.
+    #[inline]
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Byte offset where 1-based `line` starts.
+    #[inline]
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        self.line_starts.get(line.checked_sub(1)?).copied()
+    }
+
+    /// 1-based line containing `offset`.
+    ///
+    /// Offsets past the end resolve to the last line.
+    #[inline]
+    pub fn line(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= offset)
+    }
+
+    /// Line and column of `offset` in `source`, which must be the text this
+    /// index was built from.
+    ///
+    /// Clamps like [`LineCol::of`].
+    pub fn line_col(&self, source: &str, offset: usize) -> LineCol {
+        let offset = floor_char_boundary(source, offset);
+        let line = self.line(offset);
+        let line_start = self.line_start(line).unwrap_or(0).min(offset);
+        LineCol {
+            line,
+            column: source[line_start..offset].chars().count() + 1,
+        }
+    }
+}
+
+/// Byte offsets of every `\n` in `source`. With the `simd` feature this
+/// is a vectorized `memchr` scan, which dominates building a [`LineIndex`]
+/// for large inputs.
+#[cfg(feature = "simd")]
+fn newlines(source: &str) -> impl Iterator<Item = usize> + '_ {
+    memchr::memchr_iter(b'\n', source.as_bytes())
+}
+
+#[cfg(not(feature = "simd"))]
+fn newlines(source: &str) -> impl Iterator<Item = usize> + '_ {
+    source.match_indices('\n').map(|(i, _)| i)
+}
+
+#[cfg(feature = "simd")]
+fn last_newline(source: &str) -> Option<usize> {
+    memchr::memrchr(b'\n', source.as_bytes())
+}
+
+#[cfg(not(feature = "simd"))]
+fn last_newline(source: &str) -> Option<usize> {
+    source.rfind('\n')
+}
+
+fn floor_char_boundary(source: &str, offset: usize) -> usize {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_col() {
+        let source = "ab\ncd\n\u{e9}f";
+        assert_eq!(LineCol::of(source, 0), LineCol { line: 1, column: 1 });
+        assert_eq!(LineCol::of(source, 4), LineCol { line: 2, column: 2 });
+        assert_eq!(LineCol::of(source, 6), LineCol { line: 3, column: 1 });
+        // Offset 7 is inside the two-byte 'é'
+        assert_eq!(LineCol::of(source, 7), LineCol { line: 3, column: 1 });
+        assert_eq!(LineCol::of(source, 8), LineCol { line: 3, column: 2 });
+        assert_eq!(LineCol::of(source, 100), LineCol { line: 3, column: 3 });
+    }
+
+    #[test]
+    fn test_line_index_matches_line_col() {
+        let source = "ab\ncd\n\u{e9}f\n";
+        let index = LineIndex::new(source);
+        assert_eq!(index.line_count(), 4);
+        assert_eq!(index.line_start(2), Some(3));
+        assert_eq!(index.line_start(0), None);
+        for offset in 0..=source.len() + 1 {
+            assert_eq!(
+                index.line_col(source, offset),
+                LineCol::of(source, offset),
+                "offset {offset}"
+            );
+        }
+    }
+}
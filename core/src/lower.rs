@@ -0,0 +1,519 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::{Punctuated, PunctuatedInner, Repeated, Separated, Terminated};
+
+/// Converts an AST node into an IR node.
+///
+/// `S` is the grammar's span type. Lowering a `Spanned<T>` allocates a
+/// [`NodeId`] for it in the [`LowerCx`], records its span and returns the
+/// lowered value as a [`Lowered`] carrying both, so IR nodes keep pointing
+/// at their source without threading spans by hand. Implementations report
+/// problems through the context instead of failing, so one pass collects
+/// every diagnostic.
+///
+/// Use `#[derive(Lower)]` for AST nodes whose IR counterpart has the same
+/// shape.
+///
+/// # Example
+///
+/// ```ignore
+/// use synkit::{Lower, LowerCx};
+///
+/// #[derive(Lower)]
+/// #[lower(into = ir::Assign)]
+/// struct Assign {
+///     name: Spanned<IdentToken>,
+///     #[lower(skip)]
+///     eq: Spanned<EqToken>,
+///     value: Spanned<Expr>,
+/// }
+///
+/// let mut cx = LowerCx::new();
+/// let ir = ast.lower(&mut cx);
+/// for diag in cx.diagnostics() {
+///     report(diag.span.as_ref(), &diag.message);
+/// }
+/// ```
+pub trait Lower<S> {
+    /// The IR node.
+    type Output;
+
+    /// Lower `self`, reporting diagnostics to `cx`.
+    fn lower(&self, cx: &mut LowerCx<S>) -> Self::Output;
+}
+
+/// Identifies a node allocated during lowering.
+///
+/// Ids are dense indices in allocation order, which is the pre-order of the
+/// lowered tree, so they can index side tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(u32);
+
+impl NodeId {
+    /// The id as an index.
+    #[inline]
+    pub const fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// An IR node with the id and span of the AST node it was lowered from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Lowered<T, S> {
+    /// The node's id in the [`LowerCx`] that lowered it.
+    pub id: NodeId,
+    /// The span of the AST node.
+    pub span: S,
+    /// The lowered value.
+    pub node: T,
+}
+
+impl<T, S> Lowered<T, S> {
+    /// Transform the node, keeping its id and span.
+    #[inline]
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Lowered<U, S> {
+        Lowered {
+            id: self.id,
+            span: self.span,
+            node: f(self.node),
+        }
+    }
+}
+
+impl<T, S> std::ops::Deref for Lowered<T, S> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.node
+    }
+}
+
+/// How serious a [`LowerDiagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Severity {
+    /// The output is usable, but the input is suspicious.
+    Warning,
+    /// The output is not valid.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Warning => "warning",
+            Self::Error => "error",
+        })
+    }
+}
+
+/// A problem reported while lowering.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LowerDiagnostic<S> {
+    /// How serious it is.
+    pub severity: Severity,
+    /// The node being lowered when it was reported, if any.
+    pub node: Option<NodeId>,
+    /// Where it is, usually that node's span.
+    pub span: Option<S>,
+    /// What is wrong.
+    pub message: String,
+}
+
+impl<S> fmt::Display for LowerDiagnostic<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// State shared by one lowering pass: the span of every node allocated so
+/// far, the nodes currently being lowered, and the diagnostics reported.
+#[derive(Debug, Clone)]
+pub struct LowerCx<S> {
+    spans: Vec<S>,
+    stack: Vec<NodeId>,
+    diagnostics: Vec<LowerDiagnostic<S>>,
+}
+
+impl<S> Default for LowerCx<S> {
+    fn default() -> Self {
+        Self {
+            spans: Vec::new(),
+            stack: Vec::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+}
+
+impl<S> LowerCx<S> {
+    /// An empty context.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allocate a node spanning `span` and lower its contents with `f`,
+    /// during which it is the [`current`](Self::current) node.
+    ///
+    /// This is what lowering `Spanned<T>` does; call it directly for IR
+    /// nodes that don't come from a `Spanned` value.
+    pub fn node<R>(&mut self, span: S, f: impl FnOnce(&mut Self) -> R) -> (NodeId, R) {
+        let id = NodeId(u32::try_from(self.spans.len()).unwrap_or(u32::MAX));
+        self.spans.push(span);
+        self.stack.push(id);
+        let value = f(self);
+        self.stack.pop();
+        (id, value)
+    }
+
+    /// The span recorded for `id`.
+    #[inline]
+    pub fn span(&self, id: NodeId) -> Option<&S> {
+        self.spans.get(id.index())
+    }
+
+    /// The innermost node being lowered.
+    #[inline]
+    pub fn current(&self) -> Option<NodeId> {
+        self.stack.last().copied()
+    }
+
+    /// The span of the innermost node being lowered.
+    #[inline]
+    pub fn current_span(&self) -> Option<&S> {
+        self.current().and_then(|id| self.span(id))
+    }
+
+    /// The number of nodes allocated so far.
+    #[inline]
+    pub fn node_count(&self) -> usize {
+        self.spans.len()
+    }
+
+    /// Everything reported so far, in order.
+    #[inline]
+    pub fn diagnostics(&self) -> &[LowerDiagnostic<S>] {
+        &self.diagnostics
+    }
+
+    /// Whether an [`Error`](Severity::Error) was reported.
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|d| d.severity == Severity::Error)
+    }
+
+    /// The span table, indexed by [`NodeId::index`], and the diagnostics.
+    pub fn into_parts(self) -> (Vec<S>, Vec<LowerDiagnostic<S>>) {
+        (self.spans, self.diagnostics)
+    }
+}
+
+impl<S: Clone> LowerCx<S> {
+    /// Report `message` at `severity` against the current node.
+    pub fn report(&mut self, severity: Severity, message: impl Into<String>) {
+        let node = self.current();
+        let span = self.current_span().cloned();
+        self.diagnostics.push(LowerDiagnostic {
+            severity,
+            node,
+            span,
+            message: message.into(),
+        });
+    }
+
+    /// Report an error against the current node.
+    #[inline]
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.report(Severity::Error, message);
+    }
+
+    /// Report a warning against the current node.
+    #[inline]
+    pub fn warning(&mut self, message: impl Into<String>) {
+        self.report(Severity::Warning, message);
+    }
+
+    /// Report an error at `span`, e.g. a token inside the current node.
+    pub fn error_at(&mut self, span: S, message: impl Into<String>) {
+        let node = self.current();
+        self.diagnostics.push(LowerDiagnostic {
+            severity: Severity::Error,
+            node,
+            span: Some(span),
+            message: message.into(),
+        });
+    }
+}
+
+/// Implement `Lower` for values that lower to a copy of themselves.
+macro_rules! impl_lower_clone {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl<S> Lower<S> for $ty {
+                type Output = Self;
+
+                #[inline]
+                fn lower(&self, _cx: &mut LowerCx<S>) -> Self::Output {
+                    self.clone()
+                }
+            }
+        )*
+    };
+}
+
+impl_lower_clone!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    String,
+);
+
+impl<S> Lower<S> for str {
+    type Output = String;
+
+    #[inline]
+    fn lower(&self, _cx: &mut LowerCx<S>) -> Self::Output {
+        self.to_owned()
+    }
+}
+
+impl<S> Lower<S> for Cow<'_, str> {
+    type Output = String;
+
+    #[inline]
+    fn lower(&self, _cx: &mut LowerCx<S>) -> Self::Output {
+        self.to_string()
+    }
+}
+
+/// Implement `Lower` for owning pointers: the pointee is lowered into a new
+/// pointer of the same kind.
+macro_rules! impl_lower_pointer {
+    ($($ptr:ident),*) => {
+        $(
+            impl<S, T: Lower<S> + ?Sized> Lower<S> for $ptr<T> {
+                type Output = $ptr<T::Output>;
+
+                #[inline]
+                fn lower(&self, cx: &mut LowerCx<S>) -> Self::Output {
+                    $ptr::new((**self).lower(cx))
+                }
+            }
+        )*
+    };
+}
+
+impl_lower_pointer!(Box, Rc, Arc);
+
+impl<S, T: Lower<S> + ?Sized> Lower<S> for &T {
+    type Output = T::Output;
+
+    #[inline]
+    fn lower(&self, cx: &mut LowerCx<S>) -> Self::Output {
+        (**self).lower(cx)
+    }
+}
+
+impl<S, T: Lower<S>> Lower<S> for Option<T> {
+    type Output = Option<T::Output>;
+
+    #[inline]
+    fn lower(&self, cx: &mut LowerCx<S>) -> Self::Output {
+        self.as_ref().map(|v| v.lower(cx))
+    }
+}
+
+impl<S, T: Lower<S>> Lower<S> for [T] {
+    type Output = Vec<T::Output>;
+
+    fn lower(&self, cx: &mut LowerCx<S>) -> Self::Output {
+        self.iter().map(|v| v.lower(cx)).collect()
+    }
+}
+
+impl<S, T: Lower<S>> Lower<S> for Vec<T> {
+    type Output = Vec<T::Output>;
+
+    #[inline]
+    fn lower(&self, cx: &mut LowerCx<S>) -> Self::Output {
+        self.as_slice().lower(cx)
+    }
+}
+
+/// Implement `Lower` for tuples element-wise.
+macro_rules! impl_lower_tuple {
+    ($(($($name:ident $idx:tt),+)),* $(,)?) => {
+        $(
+            impl<S, $($name: Lower<S>),+> Lower<S> for ($($name,)+) {
+                type Output = ($($name::Output,)+);
+
+                #[inline]
+                fn lower(&self, cx: &mut LowerCx<S>) -> Self::Output {
+                    ($(self.$idx.lower(cx),)+)
+                }
+            }
+        )*
+    };
+}
+
+impl_lower_tuple!(
+    (A 0),
+    (A 0, B 1),
+    (A 0, B 1, C 2),
+    (A 0, B 1, C 2, D 3),
+    (A 0, B 1, C 2, D 3, E 4),
+    (A 0, B 1, C 2, D 3, E 4, F 5),
+);
+
+/// Separators carry no meaning after parsing, so punctuated lists lower to
+/// their items.
+impl<S, T: Lower<S>, P> Lower<S> for PunctuatedInner<T, P> {
+    type Output = Vec<T::Output>;
+
+    fn lower(&self, cx: &mut LowerCx<S>) -> Self::Output {
+        self.iter().map(|v| v.lower(cx)).collect()
+    }
+}
+
+/// Implement `Lower` for the punctuated wrappers via their inner storage.
+macro_rules! impl_lower_punctuated {
+    ($($name:ident),*) => {
+        $(
+            impl<S, T: Lower<S>, P> Lower<S> for $name<T, P> {
+                type Output = Vec<T::Output>;
+
+                #[inline]
+                fn lower(&self, cx: &mut LowerCx<S>) -> Self::Output {
+                    self.as_ref().lower(cx)
+                }
+            }
+        )*
+    };
+}
+
+impl_lower_punctuated!(Punctuated, Terminated, Separated);
+
+impl<S, T, Sep, Sp: Lower<S>> Lower<S> for Repeated<T, Sep, Sp> {
+    type Output = Vec<Sp::Output>;
+
+    fn lower(&self, cx: &mut LowerCx<S>) -> Self::Output {
+        self.values
+            .iter()
+            .map(|item| item.value.lower(cx))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Span = std::ops::Range<usize>;
+
+    struct Spanned<T> {
+        span: Span,
+        value: T,
+    }
+
+    impl<T: Lower<Span>> Lower<Span> for Spanned<T> {
+        type Output = Lowered<T::Output, Span>;
+
+        fn lower(&self, cx: &mut LowerCx<Span>) -> Self::Output {
+            let (id, node) = cx.node(self.span.clone(), |cx| self.value.lower(cx));
+            Lowered {
+                id,
+                span: self.span.clone(),
+                node,
+            }
+        }
+    }
+
+    struct Positive(i64);
+
+    impl Lower<Span> for Positive {
+        type Output = u64;
+
+        fn lower(&self, cx: &mut LowerCx<Span>) -> u64 {
+            u64::try_from(self.0).unwrap_or_else(|_| {
+                cx.error("expected a positive number");
+                0
+            })
+        }
+    }
+
+    #[test]
+    fn test_spans_and_ids() {
+        let ast = Spanned {
+            span: 0..9,
+            value: vec![
+                Spanned {
+                    span: 1..2,
+                    value: Positive(1),
+                },
+                Spanned {
+                    span: 4..6,
+                    value: Positive(-2),
+                },
+            ],
+        };
+
+        let mut cx = LowerCx::new();
+        let ir = ast.lower(&mut cx);
+        assert_eq!(ir.id.index(), 0);
+        assert_eq!(ir.span, 0..9);
+        assert_eq!(ir.node[1].id.index(), 2);
+        assert_eq!(ir.node[1].span, 4..6);
+        assert_eq!(ir.node[1].node, 0);
+        assert_eq!(cx.span(ir.node[0].id), Some(&(1..2)));
+        assert_eq!(cx.node_count(), 3);
+        assert_eq!(cx.current(), None);
+
+        assert!(cx.has_errors());
+        let (spans, diagnostics) = cx.into_parts();
+        assert_eq!(spans, vec![0..9, 1..2, 4..6]);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].node, Some(ir.node[1].id));
+        assert_eq!(diagnostics[0].span, Some(4..6));
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "error: expected a positive number"
+        );
+    }
+
+    #[test]
+    fn test_containers() {
+        let mut cx = LowerCx::<Span>::new();
+        let value = (Some(Box::new(1u8)), "a", vec![true]);
+        assert_eq!(
+            value.lower(&mut cx),
+            (Some(Box::new(1u8)), "a".to_string(), vec![true])
+        );
+        cx.warning("outside any node");
+        assert_eq!(cx.diagnostics()[0].span, None);
+        assert!(!cx.has_errors());
+    }
+}
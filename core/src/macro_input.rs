@@ -0,0 +1,208 @@
+//! Interop with `proc-macro2` for DSLs embedded in Rust macros.
+//!
+//! A synkit lexer works on text, while macro input arrives as a
+//! `proc_macro2::TokenStream`. [`MacroInput`] renders the token stream to
+//! text and remembers which byte range each token tree occupies, so spans
+//! produced by the parser can be mapped back to `proc_macro2::Span`s for
+//! precise error reporting.
+//!
+//! ```ignore
+//! #[proc_macro_attribute]
+//! pub fn query(attr: proc_macro::TokenStream, item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+//!     let input = synkit::MacroInput::new(attr.into());
+//!     match Query::parse_str(input.text()) {
+//!         Ok(query) => expand(query, item),
+//!         Err(e) => input.error(e.span().unwrap_or(&Span::CallSite), e).into(),
+//!     }
+//! }
+//! ```
+//!
+//! Token trees are separated by a single space unless a punctuation token
+//! is `Spacing::Joint`, so `a.b` renders as `a . b` and `->` as `->`.
+
+use std::ops::Range;
+
+use proc_macro2::{Delimiter, Spacing, Span, TokenStream, TokenTree};
+
+use crate::SpanLike;
+
+/// Text rendering of a `proc_macro2::TokenStream` with per-token spans.
+#[derive(Debug, Clone)]
+pub struct MacroInput {
+    text: String,
+    spans: Vec<(Range<usize>, Span)>,
+}
+
+impl MacroInput {
+    /// Render `tokens` to text, recording the span of each token.
+    pub fn new(tokens: TokenStream) -> Self {
+        let mut input = Self {
+            text: String::new(),
+            spans: Vec::new(),
+        };
+        input.render(tokens);
+        input
+    }
+
+    /// The rendered source text to lex and parse.
+    #[inline]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Byte ranges of each rendered token with its `proc_macro2::Span`,
+    /// in source order.
+    #[inline]
+    pub fn token_spans(&self) -> &[(Range<usize>, Span)] {
+        &self.spans
+    }
+
+    /// The `proc_macro2::Span` covering the byte range of `span`.
+    ///
+    /// Joins the spans of the first and last overlapping tokens where the
+    /// compiler supports it, falling back to the first token's span. Empty
+    /// spans resolve to the token that starts at or after their offset;
+    /// spans outside the input resolve to `Span::call_site()`.
+    pub fn span_of<S: SpanLike>(&self, span: &S) -> Span {
+        let (start, end) = (span.start(), span.end());
+        let mut overlapping = self
+            .spans
+            .iter()
+            .filter(|(range, _)| range.start < end.max(start + 1) && range.end > start);
+        let Some((_, first)) = overlapping.next() else {
+            return self
+                .spans
+                .iter()
+                .find(|(range, _)| range.start >= start)
+                .map_or_else(Span::call_site, |(_, s)| *s);
+        };
+        match overlapping.next_back() {
+            Some((_, last)) => first.join(*last).unwrap_or(*first),
+            None => *first,
+        }
+    }
+
+    /// The byte range in [`text`](Self::text) of the token at `index`.
+    pub fn byte_range(&self, index: usize) -> Option<Range<usize>> {
+        self.spans.get(index).map(|(range, _)| range.clone())
+    }
+
+    /// A `compile_error!` invocation pointing at `span`.
+    pub fn error<S: SpanLike>(&self, span: &S, message: impl std::fmt::Display) -> TokenStream {
+        let span = self.span_of(span);
+        let message = proc_macro2::Literal::string(&message.to_string());
+        let mut literal = TokenTree::Literal(message);
+        literal.set_span(span);
+        let mut tokens: Vec<TokenTree> = vec![
+            proc_macro2::Ident::new("compile_error", span).into(),
+            proc_macro2::Punct::new('!', Spacing::Alone).into(),
+        ];
+        let mut group = proc_macro2::Group::new(Delimiter::Parenthesis, literal.into());
+        group.set_span(span);
+        tokens.push(group.into());
+        tokens.into_iter().collect()
+    }
+
+    fn render(&mut self, tokens: TokenStream) {
+        let mut joint = true;
+        for tree in tokens {
+            if !joint {
+                self.text.push(' ');
+            }
+            joint = false;
+            match tree {
+                TokenTree::Group(group) => {
+                    let (open, close) = match group.delimiter() {
+                        Delimiter::Parenthesis => ("(", ")"),
+                        Delimiter::Brace => ("{", "}"),
+                        Delimiter::Bracket => ("[", "]"),
+                        Delimiter::None => ("", ""),
+                    };
+                    if !open.is_empty() {
+                        self.push(open, group.span_open());
+                    }
+                    self.render(group.stream());
+                    if !close.is_empty() {
+                        self.push(close, group.span_close());
+                    }
+                }
+                TokenTree::Ident(ident) => self.push(&ident.to_string(), ident.span()),
+                TokenTree::Literal(lit) => self.push(&lit.to_string(), lit.span()),
+                TokenTree::Punct(punct) => {
+                    let mut buf = [0; 4];
+                    self.push(punct.as_char().encode_utf8(&mut buf), punct.span());
+                    joint = punct.spacing() == Spacing::Joint;
+                }
+            }
+        }
+    }
+
+    fn push(&mut self, text: &str, span: Span) {
+        let start = self.text.len();
+        self.text.push_str(text);
+        self.spans.push((start..self.text.len(), span));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy)]
+    struct ByteSpan(usize, usize);
+
+    impl SpanLike for ByteSpan {
+        fn start(&self) -> usize {
+            self.0
+        }
+
+        fn end(&self) -> usize {
+            self.1
+        }
+
+        fn new(start: usize, end: usize) -> Self {
+            Self(start, end)
+        }
+
+        fn call_site() -> Self {
+            Self(0, 0)
+        }
+    }
+
+    fn input(src: &str) -> MacroInput {
+        MacroInput::new(src.parse().unwrap())
+    }
+
+    #[test]
+    fn test_render_text() {
+        assert_eq!(input("a -> (b, [1])").text(), "a -> (b , [1])");
+        assert_eq!(input("x.y = \"s\"").text(), "x . y = \"s\"");
+    }
+
+    #[test]
+    fn test_token_ranges() {
+        let input = input("key = { v }");
+        let text = input.text();
+        let tokens: Vec<_> = input
+            .token_spans()
+            .iter()
+            .map(|(range, _)| &text[range.clone()])
+            .collect();
+        assert_eq!(tokens, ["key", "=", "{", "v", "}"]);
+        assert_eq!(input.byte_range(1), Some(4..5));
+        assert_eq!(input.byte_range(9), None);
+    }
+
+    #[test]
+    fn test_span_of_and_error() {
+        let input = input("a = 1");
+        // Spans inside the input resolve without panicking
+        let _ = input.span_of(&ByteSpan(4, 5));
+        let _ = input.span_of(&ByteSpan(0, 5));
+        let _ = input.span_of(&ByteSpan(2, 2));
+        let _ = input.span_of(&ByteSpan(100, 200));
+
+        let err = input.error(&ByteSpan(4, 5), "bad value").to_string();
+        assert_eq!(err, "compile_error ! (\"bad value\")");
+    }
+}
@@ -0,0 +1,122 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// Where `\r\n` line endings were folded to `\n`, for mapping offsets in the
+/// normalized text back to the original.
+///
+/// Printers and boundary checks only have to handle `\n` once the source is
+/// normalized, while diagnostics can still point into the file as written.
+/// Generated streams normalize at lex time with `normalize_newlines: true`.
+/// A lone `\r` is left alone.
+///
+/// # Example
+///
+/// ```ignore
+/// let (text, map) = NewlineMap::normalize("a\r\nb");
+/// assert_eq!(text, "a\nb");
+/// // `b` is at 2 in the normalized text and 3 in the original
+/// assert_eq!(map.original_offset(2), 3);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NewlineMap {
+    /// Normalized offset of each `\n` that lost its `\r`, ascending.
+    folded: Vec<usize>,
+}
+
+impl NewlineMap {
+    /// Fold every `\r\n` in `source` to `\n`, borrowing `source` if it has
+    /// none.
+    pub fn normalize(source: &str) -> (Cow<'_, str>, Self) {
+        if !source.contains("\r\n") {
+            return (Cow::Borrowed(source), Self::default());
+        }
+        let mut text = String::with_capacity(source.len());
+        let mut folded = Vec::new();
+        for (i, line) in source.split("\r\n").enumerate() {
+            if i > 0 {
+                folded.push(text.len());
+                text.push('\n');
+            }
+            text.push_str(line);
+        }
+        (Cow::Owned(text), Self { folded })
+    }
+
+    /// Number of `\r\n` pairs folded.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.folded.len()
+    }
+
+    /// Returns `true` if the source had no `\r\n`.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.folded.is_empty()
+    }
+
+    /// Offset in the original source of normalized `offset`.
+    ///
+    /// A folded `\n` maps to its `\r`, so a span covering the `\n` covers
+    /// the whole `\r\n` once mapped.
+    #[inline]
+    pub fn original_offset(&self, offset: usize) -> usize {
+        offset + self.folded.partition_point(|&at| at < offset)
+    }
+
+    /// Range in the original source of normalized `range`.
+    #[inline]
+    pub fn original_range(&self, range: Range<usize>) -> Range<usize> {
+        self.original_offset(range.start)..self.original_offset(range.end)
+    }
+
+    /// Offset in the normalized text of `original`. An offset between a
+    /// folded `\r` and its `\n` maps to the `\n`.
+    pub fn normalized_offset(&self, original: usize) -> usize {
+        // `folded[i] + i` is where the i-th `\r` was, which also ascends
+        let (mut lo, mut hi) = (0, self.folded.len());
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if self.folded.get(mid).is_some_and(|&at| at + mid < original) {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        original - lo
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        let (text, map) = NewlineMap::normalize("a\r\nbc\n\rd\r\n");
+        assert_eq!(text, "a\nbc\n\rd\n");
+        assert_eq!(map.len(), 2);
+
+        let (text, map) = NewlineMap::normalize("a\nb\r");
+        assert!(matches!(text, Cow::Borrowed("a\nb\r")));
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_offsets_round_trip() {
+        let source = "ab\r\n\r\ncd\ne\r\n";
+        let (text, map) = NewlineMap::normalize(source);
+        assert_eq!(text, "ab\n\ncd\ne\n");
+        // The first `\n` token covers the first `\r\n`
+        assert_eq!(map.original_range(2..3), 2..4);
+        assert_eq!(map.original_range(3..4), 4..6);
+        assert_eq!(map.original_range(4..6), 6..8);
+        assert_eq!(map.original_offset(text.len()), source.len());
+
+        for (offset, _) in text.char_indices() {
+            assert_eq!(map.normalized_offset(map.original_offset(offset)), offset);
+        }
+        // Between a `\r` and its `\n`
+        assert_eq!(map.normalized_offset(3), 2);
+        assert_eq!(map.normalized_offset(source.len()), text.len());
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Values that can be emptied for reuse without giving up their allocation.
+///
+/// Implemented for `String`, `Vec`, `VecDeque` and `HashMap`; implement it
+/// for AST nodes that own buffers worth keeping.
+pub trait Recycle {
+    /// Reset to an empty state, keeping allocated capacity.
+    fn recycle(&mut self);
+}
+
+impl Recycle for String {
+    #[inline]
+    fn recycle(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Recycle for Vec<T> {
+    #[inline]
+    fn recycle(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Recycle for VecDeque<T> {
+    #[inline]
+    fn recycle(&mut self) {
+        self.clear();
+    }
+}
+
+impl<K, V, S> Recycle for HashMap<K, V, S> {
+    #[inline]
+    fn recycle(&mut self) {
+        self.clear();
+    }
+}
+
+/// Counters for a [`NodePool`], for tuning `max_idle`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// `get` calls served from the pool.
+    pub hits: usize,
+    /// `get` calls that had to allocate.
+    pub misses: usize,
+    /// Values dropped by `put` because the pool was full.
+    pub discarded: usize,
+}
+
+/// A thread-safe pool of recycled values.
+///
+/// Streaming consumers hand finished values back with [`put`](Self::put),
+/// and the parser takes them with [`get`](Self::get) instead of allocating,
+/// so buffers like per-record `String`s keep their capacity across records.
+/// At most `max_idle` values are kept; extras are dropped.
+///
+/// `new` is `const`, so a pool can live in a `static` that parse functions
+/// reach directly.
+///
+/// # Example
+///
+/// ```ignore
+/// use synkit::NodePool;
+///
+/// static STRINGS: NodePool<String> = NodePool::new(4096);
+///
+/// // Parser side
+/// let key = STRINGS.string_from(lex.slice());
+///
+/// // Consumer side, once done with a node
+/// for (key, _) in object.entries.drain(..) {
+///     STRINGS.put(key);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct NodePool<T> {
+    idle: Mutex<Vec<T>>,
+    max_idle: usize,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+    discarded: AtomicUsize,
+}
+
+impl<T> NodePool<T> {
+    /// Create a pool that keeps at most `max_idle` values.
+    pub const fn new(max_idle: usize) -> Self {
+        Self {
+            idle: Mutex::new(Vec::new()),
+            max_idle,
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+            discarded: AtomicUsize::new(0),
+        }
+    }
+
+    /// The maximum number of idle values kept.
+    #[inline]
+    pub fn max_idle(&self) -> usize {
+        self.max_idle
+    }
+
+    /// Number of idle values currently in the pool.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns true if the pool holds no idle values.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Take a recycled value, or create one with `make` if the pool is empty.
+    pub fn get_or(&self, make: impl FnOnce() -> T) -> T {
+        let recycled = self.lock().pop();
+        match recycled {
+            Some(value) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                value
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                make()
+            }
+        }
+    }
+
+    /// Take a recycled value, or `T::default()` if the pool is empty.
+    #[inline]
+    pub fn get(&self) -> T
+    where
+        T: Default,
+    {
+        self.get_or(T::default)
+    }
+
+    /// Recycle `value` and return it to the pool, unless the pool is full.
+    pub fn put(&self, mut value: T)
+    where
+        T: Recycle,
+    {
+        let mut idle = self.lock();
+        if idle.len() < self.max_idle {
+            value.recycle();
+            idle.push(value);
+        } else {
+            drop(idle);
+            self.discarded.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Drop all idle values, releasing their memory.
+    pub fn clear(&self) {
+        let idle = std::mem::take(&mut *self.lock());
+        drop(idle);
+    }
+
+    /// Snapshot of the hit, miss and discard counters.
+    pub fn stats(&self) -> PoolStats {
+        PoolStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            discarded: self.discarded.load(Ordering::Relaxed),
+        }
+    }
+
+    /// The pool holds no invariants a panicking holder could break.
+    fn lock(&self) -> MutexGuard<'_, Vec<T>> {
+        self.idle.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl NodePool<String> {
+    /// Take a recycled `String` and fill it with `s`.
+    pub fn string_from(&self, s: &str) -> String {
+        let mut string = self.get();
+        string.push_str(s);
+        string
+    }
+}
+
+impl<T> Default for NodePool<T> {
+    /// A pool keeping up to 1024 idle values.
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reuses_capacity() {
+        let pool = NodePool::<String>::new(4);
+        let mut s = pool.get();
+        s.reserve(64);
+        s.push_str("hello");
+        let capacity = s.capacity();
+        pool.put(s);
+
+        let s = pool.string_from("hi");
+        assert_eq!(s, "hi");
+        assert_eq!(s.capacity(), capacity);
+        assert_eq!(
+            pool.stats(),
+            PoolStats {
+                hits: 1,
+                misses: 1,
+                discarded: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_respects_max_idle() {
+        let pool = NodePool::<Vec<u8>>::new(2);
+        for _ in 0..3 {
+            pool.put(vec![1, 2, 3]);
+        }
+        assert_eq!(pool.len(), 2);
+        assert_eq!(pool.stats().discarded, 1);
+        assert!(pool.get().is_empty());
+
+        pool.clear();
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    fn test_static_pool_across_threads() {
+        static POOL: NodePool<String> = NodePool::new(16);
+
+        std::thread::spawn(|| POOL.put(String::with_capacity(32)))
+            .join()
+            .expect("thread panicked");
+        assert!(POOL.get().capacity() >= 32);
+    }
+}
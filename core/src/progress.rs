@@ -0,0 +1,211 @@
+use core::fmt;
+use core::future::Future;
+use core::pin::Pin;
+use std::sync::{Mutex, PoisonError};
+
+/// How often progress callbacks fire.
+///
+/// A report is due once either count has advanced by its step since the
+/// last report. Use `usize::MAX` to ignore one of the counts.
+///
+/// # Example
+///
+/// ```ignore
+/// // Every 4 MiB, regardless of token count
+/// let every = ProgressGranularity::every_bytes(4 << 20);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProgressGranularity {
+    /// Bytes between reports.
+    pub bytes: usize,
+    /// Tokens between reports.
+    pub tokens: usize,
+}
+
+impl Default for ProgressGranularity {
+    /// Every 1 MiB or 65536 tokens, whichever comes first.
+    fn default() -> Self {
+        Self {
+            bytes: 1 << 20,
+            tokens: 1 << 16,
+        }
+    }
+}
+
+impl ProgressGranularity {
+    /// Report every `bytes` bytes.
+    pub const fn every_bytes(bytes: usize) -> Self {
+        Self {
+            bytes,
+            tokens: usize::MAX,
+        }
+    }
+
+    /// Report every `tokens` tokens.
+    pub const fn every_tokens(tokens: usize) -> Self {
+        Self {
+            bytes: usize::MAX,
+            tokens,
+        }
+    }
+}
+
+/// Decides when progress is worth reporting.
+///
+/// Counts only move forward: a stream that backtracks reports its furthest
+/// position, so progress bars never run backwards.
+#[derive(Debug, Clone, Default)]
+pub struct ProgressTracker {
+    granularity: ProgressGranularity,
+    bytes: usize,
+    tokens: usize,
+    reported: Option<(usize, usize)>,
+}
+
+impl ProgressTracker {
+    /// Creates a tracker that has reported nothing yet.
+    pub fn new(granularity: ProgressGranularity) -> Self {
+        Self {
+            granularity,
+            ..Self::default()
+        }
+    }
+
+    /// Bytes consumed so far.
+    #[inline]
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Tokens consumed so far.
+    #[inline]
+    pub fn tokens(&self) -> usize {
+        self.tokens
+    }
+
+    /// Move to `bytes` and `tokens` consumed in total. Returns `true` if a
+    /// report is due, and counts it as made.
+    pub fn update(&mut self, bytes: usize, tokens: usize) -> bool {
+        self.bytes = self.bytes.max(bytes);
+        self.tokens = self.tokens.max(tokens);
+        let (last_bytes, last_tokens) = self.reported.unwrap_or_default();
+        let due = self.bytes - last_bytes >= self.granularity.bytes.max(1)
+            || self.tokens - last_tokens >= self.granularity.tokens.max(1);
+        if due {
+            self.reported = Some((self.bytes, self.tokens));
+        }
+        due
+    }
+
+    /// Returns `true` if anything changed since the last report, for a final
+    /// report at the end of input, and counts it as made.
+    pub fn finish(&mut self) -> bool {
+        let current = (self.bytes, self.tokens);
+        let due = self.reported != Some(current);
+        self.reported = Some(current);
+        due
+    }
+}
+
+/// A progress callback, called with `(bytes_consumed, tokens_consumed)`.
+pub type OnProgress = Box<dyn FnMut(usize, usize) + Send>;
+
+/// An async progress callback; the stream awaits the returned future before
+/// continuing, so a slow consumer slows the parse down.
+pub type OnProgressAsync =
+    Box<dyn FnMut(usize, usize) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send>;
+
+/// A [`ProgressTracker`] with its callback, shareable between a stream and
+/// its forks.
+///
+/// Generated streams call [`update`](Self::update) at every token read
+/// once `on_progress` attaches a reporter.
+pub struct ProgressReporter {
+    state: Mutex<(ProgressTracker, OnProgress)>,
+}
+
+impl ProgressReporter {
+    /// Creates a reporter calling `f` at `granularity`.
+    pub fn new(
+        granularity: ProgressGranularity,
+        f: impl FnMut(usize, usize) + Send + 'static,
+    ) -> Self {
+        Self {
+            state: Mutex::new((ProgressTracker::new(granularity), Box::new(f))),
+        }
+    }
+
+    /// Move to `bytes` and `tokens` consumed, calling the callback if a
+    /// report is due.
+    pub fn update(&self, bytes: usize, tokens: usize) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let (tracker, f) = &mut *state;
+        if tracker.update(bytes, tokens) {
+            f(tracker.bytes(), tracker.tokens());
+        }
+    }
+
+    /// Call the callback with the final counts, unless they were just
+    /// reported.
+    pub fn finish(&self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let (tracker, f) = &mut *state;
+        if tracker.finish() {
+            f(tracker.bytes(), tracker.tokens());
+        }
+    }
+}
+
+impl fmt::Debug for ProgressReporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        f.debug_struct("ProgressReporter")
+            .field("tracker", &state.0)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_granularity() {
+        let mut tracker = ProgressTracker::new(ProgressGranularity {
+            bytes: 10,
+            tokens: 3,
+        });
+        assert!(!tracker.update(4, 1));
+        assert!(tracker.update(10, 2));
+        assert!(!tracker.update(12, 4));
+        assert!(tracker.update(13, 5));
+        assert!(!tracker.finish());
+        assert!(!tracker.update(14, 5));
+        assert!(tracker.finish());
+        assert!(!tracker.finish());
+    }
+
+    #[test]
+    fn test_never_runs_backwards() {
+        let mut tracker = ProgressTracker::new(ProgressGranularity::every_tokens(1));
+        assert!(tracker.update(8, 4));
+        assert!(!tracker.update(2, 1));
+        assert_eq!((tracker.bytes(), tracker.tokens()), (8, 4));
+    }
+
+    #[test]
+    fn test_reporter() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let reporter = ProgressReporter::new(ProgressGranularity::every_bytes(5), move |b, t| {
+            sink.lock().unwrap().push((b, t));
+        });
+        for i in 1..=12 {
+            reporter.update(i, i);
+        }
+        reporter.finish();
+        reporter.finish();
+        assert_eq!(*seen.lock().unwrap(), [(5, 5), (10, 10), (12, 12)]);
+    }
+}
@@ -0,0 +1,104 @@
+use std::fmt;
+use std::num::NonZeroU32;
+
+/// Identifies a tool that synthesizes code, such as a macro expander or a
+/// desugaring pass.
+///
+/// Ids are chosen by the tools themselves; synkit only compares them.
+/// Stored as a non-zero `u32`, so [`Provenance`] is 4 bytes.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ToolId(NonZeroU32);
+
+impl ToolId {
+    /// The tool with id `id`.
+    #[inline]
+    pub const fn new(id: NonZeroU32) -> Self {
+        Self(id)
+    }
+
+    /// The numeric id.
+    #[inline]
+    pub const fn get(self) -> u32 {
+        self.0.get()
+    }
+}
+
+impl TryFrom<u32> for ToolId {
+    type Error = std::num::TryFromIntError;
+
+    #[inline]
+    fn try_from(id: u32) -> Result<Self, Self::Error> {
+        NonZeroU32::try_from(id).map(Self)
+    }
+}
+
+impl fmt::Display for ToolId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "tool #{}", self.0)
+    }
+}
+
+/// Where the code under a span came from.
+///
+/// Lexed spans are [`UserSource`](Self::UserSource). Passes that synthesize
+/// nodes at a real location, e.g. a macro expansion pointing at its
+/// invocation, mark them [`Generated`](Self::Generated) so diagnostics can
+/// tell them apart from what the user wrote.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum Provenance {
+    /// Parsed from the input.
+    #[default]
+    UserSource,
+    /// Produced by a tool.
+    Generated(ToolId),
+}
+
+impl Provenance {
+    /// Returns `true` for [`Generated`](Self::Generated).
+    #[inline]
+    pub const fn is_generated(self) -> bool {
+        matches!(self, Self::Generated(_))
+    }
+
+    /// The tool that generated the code, if any.
+    #[inline]
+    pub const fn tool(self) -> Option<ToolId> {
+        match self {
+            Self::Generated(tool) => Some(tool),
+            Self::UserSource => None,
+        }
+    }
+
+    /// Provenance of a span covering both: user source only if both are,
+    /// otherwise the first generated one.
+    #[inline]
+    pub const fn join(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::UserSource, other) => other,
+            (generated, _) => generated,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join() {
+        let a = Provenance::Generated(ToolId::try_from(1).unwrap());
+        let b = Provenance::Generated(ToolId::try_from(2).unwrap());
+        assert_eq!(
+            Provenance::UserSource.join(Provenance::UserSource),
+            Provenance::UserSource
+        );
+        assert_eq!(Provenance::UserSource.join(b), b);
+        assert_eq!(a.join(Provenance::UserSource), a);
+        assert_eq!(a.join(b), a);
+        assert_eq!(b.tool().map(ToolId::get), Some(2));
+        assert!(ToolId::try_from(0).is_err());
+        assert_eq!(size_of::<Provenance>(), 4);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::traits::{SpanLike, SpannedLike};
+
 /// Policy for trailing punctuation in punctuated sequences.
 ///
 /// Controls whether a trailing separator (e.g., comma) is allowed after the last element.
@@ -118,6 +120,26 @@ impl<T, P> PunctuatedInner<T, P> {
     }
 }
 
+impl<T, P> PunctuatedInner<T, P> {
+    /// Span from the first value to the last value or trailing punctuation.
+    ///
+    /// Returns `None` for an empty sequence. Lets a list node report its
+    /// extent without wrapping it in a spanned struct.
+    pub fn span<V, Q, S>(&self) -> Option<S>
+    where
+        T: SpannedLike<V, Span = S>,
+        P: SpannedLike<Q, Span = S>,
+        S: SpanLike + Copy,
+    {
+        let (first, _) = self.inner.first()?;
+        let end = match self.inner.last()? {
+            (_, Some(punct)) => *punct.span(),
+            (value, None) => *value.span(),
+        };
+        Some(first.span().join(&end))
+    }
+}
+
 impl<T, P> Default for PunctuatedInner<T, P> {
     fn default() -> Self {
         Self::new()
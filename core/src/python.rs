@@ -0,0 +1,234 @@
+//! Helpers for exposing parsers to Python via [`pyo3`].
+//!
+//! ASTs are converted to plain Python values (dicts, lists, strings,
+//! numbers) through their `serde::Serialize` impl, and parse failures are
+//! raised as [`ParseError`] carrying a diagnostic with line/column info.
+//!
+//! ```ignore
+//! synkit::python_module! {
+//!     /// TOML-like parser.
+//!     toml_parser {
+//!         parse_document => Document::parse_str,
+//!     }
+//! }
+//! ```
+//!
+//! From Python:
+//!
+//! ```text
+//! >>> import toml_parser
+//! >>> toml_parser.parse_document("key = 1")
+//! {'items': [...]}
+//! >>> try:
+//! ...     toml_parser.parse_document("= 1")
+//! ... except toml_parser.ParseError as e:
+//! ...     message, diagnostic = e.args
+//! >>> diagnostic
+//! {'message': 'expected key, found =', 'line': 1, 'column': 1, 'start': 0, 'end': 1}
+//! ```
+
+use std::ffi::CStr;
+use std::fmt::Display;
+
+pub use pyo3;
+
+use pyo3::IntoPyObjectExt;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyCFunction, PyDict, PyList};
+use serde::Serialize;
+use serde_json::Value;
+
+pub use crate::LineCol;
+
+use crate::{SpanLike, SpannedError};
+
+pyo3::create_exception!(
+    synkit,
+    ParseError,
+    PyValueError,
+    "Raised when input fails to parse. `args` is `(message, diagnostic)`."
+);
+
+/// Convert a serializable value into plain Python objects.
+pub fn to_python<'py, T: Serialize + ?Sized>(
+    py: Python<'py>,
+    value: &T,
+) -> PyResult<Bound<'py, PyAny>> {
+    let value = serde_json::to_value(value).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    json_to_python(py, &value)
+}
+
+fn json_to_python<'py>(py: Python<'py>, value: &Value) -> PyResult<Bound<'py, PyAny>> {
+    match value {
+        Value::Null => Ok(py.None().into_bound(py)),
+        Value::Bool(b) => b.into_bound_py_any(py),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                i.into_bound_py_any(py)
+            } else if let Some(u) = n.as_u64() {
+                u.into_bound_py_any(py)
+            } else {
+                n.as_f64().unwrap_or(f64::NAN).into_bound_py_any(py)
+            }
+        }
+        Value::String(s) => s.into_bound_py_any(py),
+        Value::Array(items) => {
+            let list = PyList::empty(py);
+            for item in items {
+                list.append(json_to_python(py, item)?)?;
+            }
+            Ok(list.into_any())
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (key, item) in map {
+                dict.set_item(key, json_to_python(py, item)?)?;
+            }
+            Ok(dict.into_any())
+        }
+    }
+}
+
+/// Build a diagnostic dict for `error`:
+/// `{"message", "line", "column", "start", "end"}`.
+///
+/// Location fields are `None` when the error carries no span.
+pub fn diagnostic<'py, E>(py: Python<'py>, source: &str, error: &E) -> PyResult<Bound<'py, PyDict>>
+where
+    E: Display + SpannedError,
+{
+    let dict = PyDict::new(py);
+    dict.set_item("message", error.to_string())?;
+    let span = error.span().map(|s| (s.start(), s.end()));
+    let pos = span.map(|(start, _)| LineCol::of(source, start));
+    dict.set_item("line", pos.map(|p| p.line))?;
+    dict.set_item("column", pos.map(|p| p.column))?;
+    dict.set_item("start", span.map(|(start, _)| start))?;
+    dict.set_item("end", span.map(|(_, end)| end))?;
+    Ok(dict)
+}
+
+/// Convert `error` into a [`ParseError`] with a `line:column` prefixed
+/// message and a [`diagnostic`] dict as its second argument.
+pub fn parse_error<E>(py: Python<'_>, source: &str, error: &E) -> PyErr
+where
+    E: Display + SpannedError,
+{
+    let message = match error.span() {
+        Some(span) => {
+            let pos = LineCol::of(source, span.start());
+            format!("{}:{}: {}", pos.line, pos.column, error)
+        }
+        None => error.to_string(),
+    };
+    match diagnostic(py, source, error) {
+        Ok(diagnostic) => ParseError::new_err((message, diagnostic.unbind())),
+        Err(err) => err,
+    }
+}
+
+/// Register a Python function `name(input: str)` on `module` that runs
+/// `parse` and returns the AST as Python objects, raising [`ParseError`]
+/// on failure.
+pub fn add_parser<T, E>(
+    module: &Bound<'_, PyModule>,
+    name: &'static CStr,
+    parse: fn(&str) -> Result<T, E>,
+) -> PyResult<()>
+where
+    T: Serialize + 'static,
+    E: Display + SpannedError + 'static,
+{
+    let function = PyCFunction::new_closure(
+        module.py(),
+        Some(name),
+        None,
+        move |args, _kwargs| -> PyResult<Py<PyAny>> {
+            let py = args.py();
+            let (input,): (String,) = args.extract()?;
+            match parse(&input) {
+                Ok(value) => to_python(py, &value).map(Bound::unbind),
+                Err(error) => Err(parse_error(py, &input, &error)),
+            }
+        },
+    )?;
+    module.add_function(function)
+}
+
+/// Register [`ParseError`] on `module`.
+pub fn add_exceptions(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add("ParseError", module.py().get_type::<ParseError>())
+}
+
+/// Define a `#[pymodule]` exposing one or more parse functions.
+///
+/// Each entry maps a Python function name to a
+/// `fn(&str) -> Result<T, E>` where `T: Serialize` and
+/// `E: Display + SpannedError`. The module also exports `ParseError`.
+///
+/// ```ignore
+/// synkit::python_module! {
+///     jsonl {
+///         parse_line => JsonValue::parse_str,
+///         parse_lines => JsonLines::parse_str,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! python_module {
+    (
+        $(#[$meta:meta])*
+        $module:ident {
+            $($name:ident => $parse:expr),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[$crate::python::pyo3::pymodule]
+        #[pyo3(crate = "synkit::python::pyo3")]
+        pub fn $module(
+            module: &$crate::python::pyo3::Bound<'_, $crate::python::pyo3::types::PyModule>,
+        ) -> $crate::python::pyo3::PyResult<()> {
+            $crate::python::add_exceptions(module)?;
+            $(
+                $crate::python::add_parser(
+                    module,
+                    $crate::python::pyo3::ffi::c_str!(stringify!($name)),
+                    $parse,
+                )?;
+            )*
+            Ok(())
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_python() {
+        #[derive(Serialize)]
+        struct Entry {
+            key: &'static str,
+            values: Vec<i64>,
+            flag: Option<bool>,
+        }
+
+        Python::initialize();
+        Python::attach(|py| {
+            let entry = Entry {
+                key: "a",
+                values: vec![1, 2],
+                flag: None,
+            };
+            let obj = to_python(py, &entry).unwrap();
+            let dict = obj.cast::<PyDict>().unwrap();
+            let key: String = dict.get_item("key").unwrap().unwrap().extract().unwrap();
+            let values: Vec<i64> = dict.get_item("values").unwrap().unwrap().extract().unwrap();
+            assert_eq!(key, "a");
+            assert_eq!(values, vec![1, 2]);
+            assert!(dict.get_item("flag").unwrap().unwrap().is_none());
+        });
+    }
+}
@@ -0,0 +1,565 @@
+use std::any::Any;
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Range;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::Arc;
+
+pub use self::syntax::QueryError;
+use self::syntax::{Combinator, Predicate, Step};
+use crate::{
+    Delimited, Punctuated, PunctuatedInner, Repeated, RepeatedItem, Separated, Terminated,
+};
+
+mod syntax;
+
+/// Generic traversal of a parse tree, for running [`Query`] selectors.
+///
+/// A node with a [`kind`](Self::kind) is what selectors name; everything
+/// else is transparent and only passes its children through. Use
+/// `#[derive(QueryNode)]` on AST nodes: structs are nodes named after the
+/// type, with their fields as children and as attributes for predicates,
+/// while enums are transparent and pass their active variant's fields
+/// through.
+/// Generated `Spanned<T>` wrappers stand for `T` and supply its span, and
+/// token structs are nodes named after the struct (`IdentToken`) whose
+/// text is their display form.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(QueryNode)]
+/// struct KeyValue {
+///     key: Spanned<IdentToken>,
+///     value: Spanned<NumberToken>,
+/// }
+///
+/// let doc = Document::parse_str("a = 1\nport = 80")?;
+/// let found = query!("KeyValue[key='port'] > NumberToken").find_all(&doc);
+/// assert_eq!(found[0].text().as_deref(), Some("80"));
+/// ```
+pub trait QueryNode {
+    /// Name matched by selectors, or `None` for transparent wrappers.
+    fn kind(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Source range of the node, if known.
+    fn span(&self) -> Option<Range<usize>> {
+        None
+    }
+
+    /// Text compared by `[.='...']`, and by `[field='...']` on the parent.
+    fn text(&self) -> Option<Cow<'_, str>> {
+        None
+    }
+
+    /// Named field (or tuple index) for attribute predicates.
+    fn field(&self, name: &str) -> Option<&dyn QueryNode> {
+        let _ = name;
+        None
+    }
+
+    /// Call `visit` with each direct child, in source order.
+    fn children<'a>(&'a self, visit: &mut dyn FnMut(&'a dyn QueryNode));
+
+    /// The node as `Any`, for [`Match::downcast_ref`].
+    fn as_any(&self) -> Option<&dyn Any> {
+        None
+    }
+}
+
+/// Implement `QueryNode` for leaf values: text from `Display`, no children.
+macro_rules! impl_query_node_display {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl QueryNode for $ty {
+                #[inline]
+                fn text(&self) -> Option<Cow<'_, str>> {
+                    Some(Cow::Owned(self.to_string()))
+                }
+
+                #[inline]
+                fn children<'a>(&'a self, _visit: &mut dyn FnMut(&'a dyn QueryNode)) {}
+            }
+        )*
+    };
+}
+
+impl_query_node_display!(
+    bool, char, u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64,
+);
+
+impl QueryNode for () {
+    #[inline]
+    fn children<'a>(&'a self, _visit: &mut dyn FnMut(&'a dyn QueryNode)) {}
+}
+
+impl QueryNode for str {
+    #[inline]
+    fn text(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed(self))
+    }
+
+    #[inline]
+    fn children<'a>(&'a self, _visit: &mut dyn FnMut(&'a dyn QueryNode)) {}
+}
+
+impl QueryNode for String {
+    #[inline]
+    fn text(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed(self))
+    }
+
+    #[inline]
+    fn children<'a>(&'a self, _visit: &mut dyn FnMut(&'a dyn QueryNode)) {}
+}
+
+#[cfg(feature = "compact_str")]
+impl QueryNode for compact_str::CompactString {
+    #[inline]
+    fn text(&self) -> Option<Cow<'_, str>> {
+        Some(Cow::Borrowed(self.as_str()))
+    }
+
+    #[inline]
+    fn children<'a>(&'a self, _visit: &mut dyn FnMut(&'a dyn QueryNode)) {}
+}
+
+/// Implement `QueryNode` for smart pointers by delegating to the pointee.
+macro_rules! impl_query_node_deref {
+    ($($ptr:ident),*) => {
+        $(
+            impl<T: QueryNode + ?Sized> QueryNode for $ptr<T> {
+                #[inline]
+                fn kind(&self) -> Option<&'static str> {
+                    (**self).kind()
+                }
+
+                #[inline]
+                fn span(&self) -> Option<Range<usize>> {
+                    (**self).span()
+                }
+
+                #[inline]
+                fn text(&self) -> Option<Cow<'_, str>> {
+                    (**self).text()
+                }
+
+                #[inline]
+                fn field(&self, name: &str) -> Option<&dyn QueryNode> {
+                    (**self).field(name)
+                }
+
+                #[inline]
+                fn children<'a>(&'a self, visit: &mut dyn FnMut(&'a dyn QueryNode)) {
+                    (**self).children(visit);
+                }
+
+                #[inline]
+                fn as_any(&self) -> Option<&dyn Any> {
+                    (**self).as_any()
+                }
+            }
+        )*
+    };
+}
+
+impl_query_node_deref!(Box, Rc, Arc);
+
+/// A present `Option` stands for its value; an absent one has no children.
+impl<T: QueryNode> QueryNode for Option<T> {
+    #[inline]
+    fn kind(&self) -> Option<&'static str> {
+        self.as_ref().and_then(T::kind)
+    }
+
+    #[inline]
+    fn span(&self) -> Option<Range<usize>> {
+        self.as_ref().and_then(T::span)
+    }
+
+    #[inline]
+    fn text(&self) -> Option<Cow<'_, str>> {
+        self.as_ref().and_then(T::text)
+    }
+
+    #[inline]
+    fn field(&self, name: &str) -> Option<&dyn QueryNode> {
+        self.as_ref().and_then(|value| value.field(name))
+    }
+
+    #[inline]
+    fn children<'a>(&'a self, visit: &mut dyn FnMut(&'a dyn QueryNode)) {
+        if let Some(value) = self {
+            value.children(visit);
+        }
+    }
+
+    #[inline]
+    fn as_any(&self) -> Option<&dyn Any> {
+        self.as_ref().and_then(T::as_any)
+    }
+}
+
+impl<T: QueryNode> QueryNode for [T] {
+    fn children<'a>(&'a self, visit: &mut dyn FnMut(&'a dyn QueryNode)) {
+        for item in self {
+            visit(item);
+        }
+    }
+}
+
+impl<T: QueryNode, const N: usize> QueryNode for [T; N] {
+    #[inline]
+    fn children<'a>(&'a self, visit: &mut dyn FnMut(&'a dyn QueryNode)) {
+        self.as_slice().children(visit);
+    }
+}
+
+impl<T: QueryNode> QueryNode for Vec<T> {
+    #[inline]
+    fn children<'a>(&'a self, visit: &mut dyn FnMut(&'a dyn QueryNode)) {
+        self.as_slice().children(visit);
+    }
+}
+
+/// Implement `QueryNode` for tuples, visiting each element.
+macro_rules! impl_query_node_tuple {
+    ($(($($name:ident $idx:tt),+)),* $(,)?) => {
+        $(
+            impl<$($name: QueryNode),+> QueryNode for ($($name,)+) {
+                fn children<'a>(&'a self, visit: &mut dyn FnMut(&'a dyn QueryNode)) {
+                    $(visit(&self.$idx);)+
+                }
+            }
+        )*
+    };
+}
+
+impl_query_node_tuple!(
+    (A 0),
+    (A 0, B 1),
+    (A 0, B 1, C 2),
+    (A 0, B 1, C 2, D 3),
+    (A 0, B 1, C 2, D 3, E 4),
+    (A 0, B 1, C 2, D 3, E 4, F 5),
+);
+
+impl<T: QueryNode, P: QueryNode> QueryNode for PunctuatedInner<T, P> {
+    fn children<'a>(&'a self, visit: &mut dyn FnMut(&'a dyn QueryNode)) {
+        for (value, punct) in self.pairs() {
+            visit(value);
+            if let Some(punct) = punct {
+                visit(punct);
+            }
+        }
+    }
+}
+
+/// Implement `QueryNode` for the punctuated wrappers via their inner storage.
+macro_rules! impl_query_node_punctuated {
+    ($($name:ident),*) => {
+        $(
+            impl<T: QueryNode, P: QueryNode> QueryNode for $name<T, P> {
+                #[inline]
+                fn children<'a>(&'a self, visit: &mut dyn FnMut(&'a dyn QueryNode)) {
+                    self.as_ref().children(visit);
+                }
+            }
+        )*
+    };
+}
+
+impl_query_node_punctuated!(Punctuated, Terminated, Separated);
+
+impl<T: QueryNode, Span> QueryNode for Delimited<T, Span> {
+    #[inline]
+    fn children<'a>(&'a self, visit: &mut dyn FnMut(&'a dyn QueryNode)) {
+        visit(&self.inner);
+    }
+}
+
+impl<T, Sep, S: QueryNode> QueryNode for RepeatedItem<T, Sep, S> {
+    fn children<'a>(&'a self, visit: &mut dyn FnMut(&'a dyn QueryNode)) {
+        visit(&self.value);
+        if let Some(sep) = &self.sep {
+            visit(sep);
+        }
+    }
+}
+
+impl<T, Sep, S: QueryNode> QueryNode for Repeated<T, Sep, S> {
+    #[inline]
+    fn children<'a>(&'a self, visit: &mut dyn FnMut(&'a dyn QueryNode)) {
+        self.values.children(visit);
+    }
+}
+
+/// A node found by [`Query::find_all`].
+#[derive(Clone, Copy)]
+pub struct Match<'a> {
+    /// The matched node.
+    pub node: &'a dyn QueryNode,
+}
+
+impl<'a> Match<'a> {
+    /// The node's kind.
+    #[inline]
+    pub fn kind(&self) -> &'static str {
+        self.node.kind().unwrap_or_default()
+    }
+
+    /// The node's source range, if it was reached through a `Spanned<T>`.
+    #[inline]
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.node.span()
+    }
+
+    /// The node's text, e.g. a token's display form.
+    #[inline]
+    pub fn text(&self) -> Option<Cow<'a, str>> {
+        self.node.text()
+    }
+
+    /// The node as `T`, which for a `Spanned<T>` is the value inside.
+    #[inline]
+    pub fn downcast_ref<T: Any>(&self) -> Option<&'a T> {
+        self.node.as_any()?.downcast_ref()
+    }
+}
+
+impl fmt::Debug for Match<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Match")
+            .field("kind", &self.kind())
+            .field("span", &self.span())
+            .finish()
+    }
+}
+
+impl Predicate {
+    fn holds(&self, node: &dyn QueryNode) -> bool {
+        let target = match &self.field {
+            Some(name) => node.field(name),
+            None => Some(node),
+        };
+        let equal = target
+            .and_then(|target| target.text())
+            .is_some_and(|text| text == self.value);
+        equal != self.negated
+    }
+}
+
+impl Step {
+    fn matches(&self, node: &dyn QueryNode) -> bool {
+        self.kind
+            .as_deref()
+            .is_none_or(|kind| node.kind() == Some(kind))
+            && self.predicates.iter().all(|p| p.holds(node))
+    }
+}
+
+/// A selector over [`QueryNode`] trees, in a small CSS/XPath-like syntax.
+///
+/// - `KeyValue` matches every `KeyValue` node; `*` matches any node.
+/// - `Table KeyValue` matches a `KeyValue` anywhere inside a `Table`.
+/// - `Table > KeyValue` matches a `KeyValue` directly inside a `Table`,
+///   with only transparent wrappers (enums, `Vec`, `Spanned<T>`, ...)
+///   between them.
+/// - `KeyValue[key='port']` also requires the `key` field's text to be
+///   `port`; `!=` negates, `[.='x']` tests the node's own text and tuple
+///   fields are named by index, `[0='x']`. Predicates can be chained.
+///
+/// Strings are quoted with `'` or `"`; `\` escapes the next character.
+/// Use the `query!` macro to check a selector at compile time.
+///
+/// # Example
+///
+/// ```ignore
+/// let query = Query::parse("Table > KeyValue[key='port']")?;
+/// for found in query.find_all(&document) {
+///     println!("{:?} at {:?}", found.text(), found.span());
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    steps: Vec<Step>,
+}
+
+impl Query {
+    /// Parse a selector.
+    pub fn parse(source: &str) -> Result<Self, QueryError> {
+        syntax::parse(source).map(|steps| Self { steps })
+    }
+
+    /// Every node under (and including) `root` that matches, in document order.
+    pub fn find_all<'a>(&self, root: &'a dyn QueryNode) -> Vec<Match<'a>> {
+        let mut found = Vec::new();
+        let mut path = Vec::new();
+        self.walk(root, &mut path, &mut found);
+        found
+    }
+
+    /// The first node that matches, in document order.
+    pub fn find<'a>(&self, root: &'a dyn QueryNode) -> Option<Match<'a>> {
+        self.find_all(root).into_iter().next()
+    }
+
+    fn walk<'a>(
+        &self,
+        node: &'a dyn QueryNode,
+        path: &mut Vec<&'a dyn QueryNode>,
+        found: &mut Vec<Match<'a>>,
+    ) {
+        let is_node = node.kind().is_some();
+        if is_node {
+            path.push(node);
+            if self.matches_at(self.steps.len().saturating_sub(1), path.len() - 1, path) {
+                found.push(Match { node });
+            }
+        }
+        node.children(&mut |child| self.walk(child, path, found));
+        if is_node {
+            path.pop();
+        }
+    }
+
+    /// Whether steps `..=step` match with `step` on `path[at]`.
+    fn matches_at(&self, step: usize, at: usize, path: &[&dyn QueryNode]) -> bool {
+        let (Some(current), Some(node)) = (self.steps.get(step), path.get(at)) else {
+            return false;
+        };
+        if !current.matches(*node) {
+            return false;
+        }
+        if step == 0 {
+            return true;
+        }
+        match current.combinator {
+            Combinator::Child => at > 0 && self.matches_at(step - 1, at - 1, path),
+            Combinator::Descendant => (0..at).rev().any(|p| self.matches_at(step - 1, p, path)),
+        }
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A kinded test node with a name and children.
+    struct Node {
+        kind: &'static str,
+        name: String,
+        children: Vec<Node>,
+    }
+
+    impl QueryNode for Node {
+        fn kind(&self) -> Option<&'static str> {
+            Some(self.kind)
+        }
+
+        fn field(&self, name: &str) -> Option<&dyn QueryNode> {
+            match name {
+                "name" => Some(&self.name),
+                _ => None,
+            }
+        }
+
+        fn children<'a>(&'a self, visit: &mut dyn FnMut(&'a dyn QueryNode)) {
+            // Through the transparent `Vec`
+            visit(&self.children);
+        }
+
+        fn as_any(&self) -> Option<&dyn Any> {
+            Some(self)
+        }
+    }
+
+    fn node(kind: &'static str, name: &str, children: Vec<Node>) -> Node {
+        Node {
+            kind,
+            name: name.to_string(),
+            children,
+        }
+    }
+
+    fn names(query: &str, root: &Node) -> Vec<String> {
+        Query::parse(query)
+            .unwrap()
+            .find_all(root)
+            .iter()
+            .map(|found| found.downcast_ref::<Node>().unwrap().name.clone())
+            .collect()
+    }
+
+    fn tree() -> Node {
+        node(
+            "Doc",
+            "root",
+            vec![
+                node(
+                    "Table",
+                    "a",
+                    vec![node("Pair", "x", vec![]), node("Pair", "y", vec![])],
+                ),
+                node(
+                    "Table",
+                    "b",
+                    vec![node("Group", "g", vec![node("Pair", "x", vec![])])],
+                ),
+                node("Pair", "x", vec![]),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_combinators() {
+        let tree = tree();
+        assert_eq!(names("Pair", &tree), ["x", "y", "x", "x"]);
+        assert_eq!(names("Table Pair", &tree), ["x", "y", "x"]);
+        assert_eq!(names("Table > Pair", &tree), ["x", "y"]);
+        assert_eq!(names("Doc > *", &tree), ["a", "b", "x"]);
+        assert_eq!(names("Table > * > Pair", &tree), ["x"]);
+        assert!(names("Pair Pair", &tree).is_empty());
+    }
+
+    #[test]
+    fn test_predicates() {
+        let tree = tree();
+        assert_eq!(names("Table[name='b'] Pair", &tree), ["x"]);
+        assert_eq!(names("Pair[name!='x']", &tree), ["y"]);
+        assert_eq!(names("Table[name = \"a\"] > Pair[name='x']", &tree), ["x"]);
+        assert!(names("Pair[missing='x']", &tree).is_empty());
+        assert_eq!(names("Pair[missing!='x']", &tree).len(), 4);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |query: &str| Query::parse(query).unwrap_err();
+        assert_eq!(error("").offset, 0);
+        assert_eq!(error("A >").message, "expected a node kind or `*`");
+        assert_eq!(error("A[b='c'").offset, 7);
+        assert_eq!(error("A[b='c]").message, "unterminated string");
+        assert_eq!(error("A[b c]").message, "expected `=` or `!=`");
+        assert_eq!(error("A,B").message, "expected `>` or a space, found `,`");
+        assert_eq!(
+            error("A[b=c]").to_string(),
+            "invalid query at 4: expected a quoted string"
+        );
+        assert_eq!(
+            "A[.='it\\'s']".parse::<Query>().map(|q| q.steps.len()),
+            Ok(1)
+        );
+    }
+}
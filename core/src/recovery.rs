@@ -0,0 +1,267 @@
+//! Ready-made error recovery strategies for `TokenStream::parse_or_recover`.
+//!
+//! When a parse fails, the generated stream rewinds and skips tokens until a
+//! [`Recover`] strategy says to stop, then returns the skipped tokens as an
+//! error node. The strategies here cover the common cases:
+//!
+//! - [`SkipToSeparator`]: the next separator at the same delimiter depth,
+//!   e.g. the `,` after a broken argument, without stopping inside `f(a, b)`.
+//! - [`SkipToClose`]: the closing delimiter of the enclosing group.
+//! - [`SkipToLineStart`]: the first token on a later line.
+//! - [`SkipUntil`]: the first token a predicate accepts.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use synkit::recovery::SkipToSeparator;
+//!
+//! let (mut args, _) = stream.extract_inner::<LParenToken, RParenToken>()?;
+//! while !args.is_empty() {
+//!     items.push(args.parse_or_recover::<Arg>(SkipToSeparator::<CommaToken>::new()));
+//!     let _ = args.parse::<CommaToken>();
+//! }
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::Peek;
+
+/// How a token changes delimiter nesting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Nesting {
+    /// Opens a delimiter group, e.g. `(`.
+    Open,
+    /// Closes a delimiter group, e.g. `)`.
+    Close,
+    /// Neither.
+    Other,
+}
+
+/// A token recovery is about to skip.
+#[derive(Debug, Clone, Copy)]
+pub struct RecoveryToken<'a, T> {
+    /// The token.
+    pub token: &'a T,
+    /// Whether it opens or closes one of the kit's delimiters.
+    pub nesting: Nesting,
+    /// Whether it is the first token on its line.
+    pub starts_line: bool,
+}
+
+/// Decides where error recovery stops skipping.
+///
+/// [`stop`](Self::stop) sees every token from where recovery starts, so
+/// strategies can track nesting. The first token is skipped whatever it
+/// returns, so recovery always makes progress; the token it stops at is
+/// left in the stream.
+pub trait Recover<T> {
+    /// Returns `true` to stop before `token`.
+    fn stop(&mut self, token: RecoveryToken<'_, T>) -> bool;
+}
+
+/// Stops at the first token `predicate` accepts.
+#[derive(Clone, Copy)]
+pub struct SkipUntil<F>(pub F);
+
+impl<T, F: FnMut(&T) -> bool> Recover<T> for SkipUntil<F> {
+    #[inline]
+    fn stop(&mut self, token: RecoveryToken<'_, T>) -> bool {
+        (self.0)(token.token)
+    }
+}
+
+impl<F> fmt::Debug for SkipUntil<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SkipUntil").finish_non_exhaustive()
+    }
+}
+
+/// Stops at a `Sep` at the depth recovery started at, or before the
+/// closing delimiter of the enclosing group.
+///
+/// Groups opened while skipping are skipped whole, so a broken `f(a, b)`
+/// argument doesn't stop at its inner comma.
+pub struct SkipToSeparator<Sep> {
+    depth: usize,
+    _sep: PhantomData<fn() -> Sep>,
+}
+
+impl<Sep> SkipToSeparator<Sep> {
+    /// A strategy stopping at `Sep`.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            depth: 0,
+            _sep: PhantomData,
+        }
+    }
+}
+
+impl<Sep> Default for SkipToSeparator<Sep> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Sep> Clone for SkipToSeparator<Sep> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            depth: self.depth,
+            _sep: PhantomData,
+        }
+    }
+}
+
+impl<Sep> fmt::Debug for SkipToSeparator<Sep> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SkipToSeparator")
+            .field("separator", &std::any::type_name::<Sep>())
+            .field("depth", &self.depth)
+            .finish()
+    }
+}
+
+impl<Sep: Peek> Recover<Sep::Token> for SkipToSeparator<Sep> {
+    fn stop(&mut self, token: RecoveryToken<'_, Sep::Token>) -> bool {
+        match token.nesting {
+            Nesting::Open => {
+                self.depth += 1;
+                false
+            }
+            Nesting::Close if self.depth == 0 => true,
+            Nesting::Close => {
+                self.depth -= 1;
+                false
+            }
+            Nesting::Other => self.depth == 0 && Sep::is(token.token),
+        }
+    }
+}
+
+/// Stops before the closing delimiter of the enclosing group, skipping
+/// groups opened on the way.
+///
+/// In a stream from `extract_inner` the closing delimiter is out of range,
+/// so this skips the rest of the group.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SkipToClose {
+    depth: usize,
+}
+
+impl SkipToClose {
+    /// A strategy stopping at the enclosing closing delimiter.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T> Recover<T> for SkipToClose {
+    fn stop(&mut self, token: RecoveryToken<'_, T>) -> bool {
+        match token.nesting {
+            Nesting::Open => self.depth += 1,
+            Nesting::Close if self.depth == 0 => return true,
+            Nesting::Close => self.depth -= 1,
+            Nesting::Other => {}
+        }
+        false
+    }
+}
+
+/// Stops at the first token that starts a line, for line-oriented formats.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SkipToLineStart;
+
+impl<T> Recover<T> for SkipToLineStart {
+    #[inline]
+    fn stop(&mut self, token: RecoveryToken<'_, T>) -> bool {
+        token.starts_line
+    }
+}
+
+/// Whether the token at byte `offset` of `source` is the first on its line:
+/// only spaces, tabs or `\r` separate it from the previous newline or the
+/// start of the source.
+///
+/// Offsets past the end of `source` don't start a line.
+pub fn starts_line(source: &str, offset: usize) -> bool {
+    let Some(before) = source.as_bytes().get(..offset) else {
+        return false;
+    };
+    before
+        .iter()
+        .rev()
+        .find(|&&b| !matches!(b, b' ' | b'\t' | b'\r'))
+        .is_none_or(|&b| b == b'\n')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Comma;
+
+    impl Peek for Comma {
+        type Token = char;
+
+        fn is(token: &char) -> bool {
+            *token == ','
+        }
+    }
+
+    fn nesting(c: char) -> Nesting {
+        match c {
+            '(' => Nesting::Open,
+            ')' => Nesting::Close,
+            _ => Nesting::Other,
+        }
+    }
+
+    /// Index of the first token `strategy` stops at, after the first.
+    fn stop_at(tokens: &str, mut strategy: impl Recover<char>) -> Option<usize> {
+        let tokens: Vec<char> = tokens.chars().collect();
+        tokens.iter().enumerate().position(|(i, token)| {
+            let stop = strategy.stop(RecoveryToken {
+                token,
+                nesting: nesting(*token),
+                starts_line: false,
+            });
+            stop && i > 0
+        })
+    }
+
+    #[test]
+    fn test_skip_to_separator() {
+        assert_eq!(
+            stop_at("a(b,c),d", SkipToSeparator::<Comma>::new()),
+            Some(6)
+        );
+        assert_eq!(stop_at("ab)c,d", SkipToSeparator::<Comma>::new()), Some(2));
+        assert_eq!(stop_at("(a,b", SkipToSeparator::<Comma>::new()), None);
+    }
+
+    #[test]
+    fn test_skip_to_close() {
+        assert_eq!(stop_at("a(b)c)d", SkipToClose::new()), Some(5));
+        assert_eq!(stop_at("a,b", SkipToClose::new()), None);
+    }
+
+    #[test]
+    fn test_skip_until() {
+        assert_eq!(stop_at("ab(c", SkipUntil(|c: &char| *c == '(')), Some(2));
+    }
+
+    #[test]
+    fn test_starts_line() {
+        let source = "a b\n  c /* */ d\r\ne";
+        assert!(starts_line(source, 0));
+        assert!(!starts_line(source, 2));
+        assert!(starts_line(source, 6));
+        assert!(!starts_line(source, 14));
+        assert!(starts_line(source, 17));
+        assert!(!starts_line(source, 100));
+    }
+}
@@ -1,5 +1,7 @@
 use std::marker::PhantomData;
 
+use crate::traits::{SpanLike, SpannedLike};
+
 /// A single item in a [`Repeated`] sequence, holding a value and optional separator.
 ///
 /// # Type Parameters
@@ -147,6 +149,22 @@ impl<T, Sep, Spanned> Repeated<T, Sep, Spanned> {
     }
 }
 
+impl<T, Sep, Spanned> Repeated<T, Sep, Spanned> {
+    /// Span from the first value to the last value or trailing separator.
+    ///
+    /// Returns `None` for an empty sequence.
+    pub fn span<V, S>(&self) -> Option<S>
+    where
+        Spanned: SpannedLike<V, Span = S>,
+        S: SpanLike + Copy,
+    {
+        let first = self.values.first()?;
+        let last = self.values.last()?;
+        let end = last.sep.as_ref().unwrap_or(&last.value);
+        Some(first.value.span().join(end.span()))
+    }
+}
+
 impl<T, Sep, Spanned> Default for Repeated<T, Sep, Spanned> {
     fn default() -> Self {
         Self::empty()
@@ -0,0 +1,627 @@
+use std::fmt::{self, Write as _};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::LineIndex;
+use crate::traits::SpanLike;
+
+/// A replacement of `range` in the original source.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Edit {
+    /// Byte range replaced; empty for an insertion.
+    pub range: Range<usize>,
+    /// Text put in its place.
+    pub replacement: String,
+}
+
+/// Collects span-based edits against a source and applies them at once.
+///
+/// Edits are given in terms of the original source, so spans from a parse
+/// stay valid however many edits come before them. Edits that overlap an
+/// earlier one are rejected when added. Several insertions at the same
+/// offset are applied in the order they were added, and an insertion at the
+/// start of a replaced range goes before the replacement.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut rewriter = Rewriter::new(source);
+/// for found in query!("KeyValue > IdentToken[.='port']").find_all(&doc) {
+///     rewriter.replace(&found.span().unwrap(), "listen_port")?;
+/// }
+/// let (output, doc) = rewriter.apply_verified(|text| Document::parse_str(text))?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rewriter<'s> {
+    source: &'s str,
+    /// Named in diff headers.
+    path: Option<PathBuf>,
+    /// Sorted by range and pairwise non-overlapping.
+    edits: Vec<Edit>,
+}
+
+impl<'s> Rewriter<'s> {
+    /// A rewriter with no edits.
+    #[inline]
+    pub fn new(source: &'s str) -> Self {
+        Self {
+            source,
+            path: None,
+            edits: Vec::new(),
+        }
+    }
+
+    /// Name the source file in [`unified_diff`](Self::unified_diff)
+    /// headers, typically with a token stream's `source_path()`.
+    #[inline]
+    pub fn with_path(mut self, path: Option<impl AsRef<Path>>) -> Self {
+        self.path = path.map(|p| p.as_ref().to_path_buf());
+        self
+    }
+
+    /// The source file's path, if set.
+    #[inline]
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// The original source.
+    #[inline]
+    pub fn source(&self) -> &'s str {
+        self.source
+    }
+
+    /// The edits added so far, in source order.
+    #[inline]
+    pub fn edits(&self) -> &[Edit] {
+        &self.edits
+    }
+
+    /// Number of edits.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.edits.len()
+    }
+
+    /// Returns `true` if no edits were added.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// Replace the text at `span`.
+    pub fn replace<S: SpanLike>(
+        &mut self,
+        span: &S,
+        replacement: impl Into<String>,
+    ) -> Result<&mut Self, RewriteError> {
+        let range = span_range(span)?;
+        self.replace_range(range, replacement)
+    }
+
+    /// Remove the text at `span`.
+    pub fn delete<S: SpanLike>(&mut self, span: &S) -> Result<&mut Self, RewriteError> {
+        self.replace(span, String::new())
+    }
+
+    /// Insert `text` just before `span`.
+    pub fn insert_before<S: SpanLike>(
+        &mut self,
+        span: &S,
+        text: impl Into<String>,
+    ) -> Result<&mut Self, RewriteError> {
+        let at = span_range(span)?.start;
+        self.replace_range(at..at, text)
+    }
+
+    /// Insert `text` just after `span`.
+    pub fn insert_after<S: SpanLike>(
+        &mut self,
+        span: &S,
+        text: impl Into<String>,
+    ) -> Result<&mut Self, RewriteError> {
+        let at = span_range(span)?.end;
+        self.replace_range(at..at, text)
+    }
+
+    /// Replace the byte `range` of the source.
+    pub fn replace_range(
+        &mut self,
+        range: Range<usize>,
+        replacement: impl Into<String>,
+    ) -> Result<&mut Self, RewriteError> {
+        if range.start > range.end || range.end > self.source.len() {
+            return Err(RewriteError::OutOfBounds {
+                range,
+                len: self.source.len(),
+            });
+        }
+        for offset in [range.start, range.end] {
+            if !self.source.is_char_boundary(offset) {
+                return Err(RewriteError::NotCharBoundary { offset });
+            }
+        }
+
+        let key = (range.start, range.end);
+        let at = self
+            .edits
+            .partition_point(|edit| (edit.range.start, edit.range.end) <= key);
+        // Kept edits are ordered by start and end alike, so only the
+        // neighbours can overlap
+        let neighbours = [at.checked_sub(1), Some(at)];
+        for edit in neighbours
+            .into_iter()
+            .flatten()
+            .filter_map(|i| self.edits.get(i))
+        {
+            if overlaps(&edit.range, &range) {
+                return Err(RewriteError::Overlap {
+                    range,
+                    existing: edit.range.clone(),
+                });
+            }
+        }
+
+        self.edits.insert(
+            at,
+            Edit {
+                range,
+                replacement: replacement.into(),
+            },
+        );
+        Ok(self)
+    }
+
+    /// The source with every edit applied.
+    pub fn apply(&self) -> String {
+        let added: usize = self.edits.iter().map(|edit| edit.replacement.len()).sum();
+        let mut output = String::with_capacity(self.source.len() + added);
+        let mut copied = 0;
+        for edit in &self.edits {
+            output.push_str(
+                self.source
+                    .get(copied..edit.range.start)
+                    .unwrap_or_default(),
+            );
+            output.push_str(&edit.replacement);
+            copied = edit.range.end;
+        }
+        output.push_str(self.source.get(copied..).unwrap_or_default());
+        output
+    }
+
+    /// Apply the edits and check the result with `parse`, returning the new
+    /// source and what it parsed to.
+    pub fn apply_verified<T, E>(
+        &self,
+        parse: impl FnOnce(&str) -> Result<T, E>,
+    ) -> Result<(String, T), E> {
+        let output = self.apply();
+        let parsed = parse(&output)?;
+        Ok((output, parsed))
+    }
+
+    /// Where original `offset` ends up once the edits are applied. An offset
+    /// inside a replaced range maps to the end of its replacement, and one
+    /// at an insertion to just after it.
+    pub fn map_offset(&self, offset: usize) -> usize {
+        let (mut added, mut removed) = (0, 0);
+        for edit in &self.edits {
+            if edit.range.start > offset || (edit.range.start == offset && !edit.range.is_empty()) {
+                break;
+            }
+            if offset < edit.range.end {
+                return edit.range.start + added + edit.replacement.len() - removed;
+            }
+            added += edit.replacement.len();
+            removed += edit.range.len();
+        }
+        offset + added - removed
+    }
+
+    /// The edits as a unified diff with three lines of context, or an empty
+    /// string if they change nothing.
+    ///
+    /// Hunks come from the edits themselves rather than from diffing the
+    /// old and new text, so each changed line is one an edit touched.
+    #[inline]
+    pub fn unified_diff(&self) -> String {
+        self.unified_diff_with_context(3)
+    }
+
+    /// [`unified_diff`](Self::unified_diff) with `context` lines around
+    /// each change.
+    pub fn unified_diff_with_context(&self, context: usize) -> String {
+        let lines = Lines::new(self.source);
+        let changes = self.line_changes(&lines);
+        if changes.is_empty() {
+            return String::new();
+        }
+
+        let mut diff = String::new();
+        match &self.path {
+            Some(path) => {
+                let _ = writeln!(diff, "--- a/{}", path.display());
+                let _ = writeln!(diff, "+++ b/{}", path.display());
+            }
+            None => diff.push_str("--- original\n+++ modified\n"),
+        }
+
+        // Lines added and removed by earlier hunks, to offset new line numbers
+        let (mut added, mut removed) = (0, 0);
+        let mut rest = changes.as_slice();
+        while let Some(first) = rest.first() {
+            // Changes whose context would touch share a hunk
+            let mut len = 1;
+            let mut end = first.lines.end;
+            for change in rest.iter().skip(1) {
+                if change.lines.start > end + 2 * context {
+                    break;
+                }
+                end = change.lines.end;
+                len += 1;
+            }
+            let (hunk, next) = rest.split_at(len);
+            rest = next;
+
+            let start = first.lines.start.saturating_sub(context);
+            let end = (end + context).min(lines.count);
+            let old_len = end - start;
+            let (hunk_added, hunk_removed) = hunk.iter().fold((0, 0), |(a, r), change| {
+                (a + change.new_lines(), r + change.lines.len())
+            });
+            let new_len = old_len + hunk_added - hunk_removed;
+            let _ = writeln!(
+                diff,
+                "@@ -{} +{} @@",
+                hunk_range(start, old_len),
+                hunk_range(start + added - removed, new_len)
+            );
+
+            let mut cursor = start;
+            for change in hunk {
+                push_lines(
+                    &mut diff,
+                    ' ',
+                    lines.text(self.source, cursor..change.lines.start),
+                );
+                push_lines(&mut diff, '-', change.old);
+                push_lines(&mut diff, '+', &change.new);
+                cursor = change.lines.end;
+            }
+            push_lines(&mut diff, ' ', lines.text(self.source, cursor..end));
+
+            added += hunk_added;
+            removed += hunk_removed;
+        }
+        diff
+    }
+
+    /// The lines each group of edits touches, before and after, skipping
+    /// groups that leave their lines unchanged.
+    fn line_changes(&self, lines: &Lines) -> Vec<LineChange<'s>> {
+        let mut groups: Vec<(Range<usize>, Range<usize>)> = Vec::new();
+        for (i, edit) in self.edits.iter().enumerate() {
+            let lo = lines.line_of(edit.range.start);
+            let hi = if edit.range.is_empty() {
+                (lo + 1).min(lines.count)
+            } else {
+                lines.line_of(edit.range.end - 1) + 1
+            };
+            match groups.last_mut() {
+                // Edits sharing a line (or both at the end) are one change
+                Some((group, edits)) if lo < group.end.max(group.start + 1) => {
+                    group.end = group.end.max(hi);
+                    edits.end = i + 1;
+                }
+                _ => groups.push((lo..hi, i..i + 1)),
+            }
+        }
+
+        groups
+            .into_iter()
+            .filter_map(|(group, edits)| {
+                let base = lines.start(group.start);
+                let old = lines.text(self.source, group.clone());
+                let mut new = String::with_capacity(old.len());
+                let mut copied = base;
+                for edit in self.edits.get(edits).unwrap_or_default() {
+                    new.push_str(
+                        self.source
+                            .get(copied..edit.range.start)
+                            .unwrap_or_default(),
+                    );
+                    new.push_str(&edit.replacement);
+                    copied = edit.range.end;
+                }
+                new.push_str(
+                    self.source
+                        .get(copied..base + old.len())
+                        .unwrap_or_default(),
+                );
+                (new != old).then_some(LineChange {
+                    lines: group,
+                    old,
+                    new,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Whole lines replaced in a unified diff.
+struct LineChange<'s> {
+    /// Old line indices, 0-based.
+    lines: Range<usize>,
+    old: &'s str,
+    new: String,
+}
+
+impl LineChange<'_> {
+    fn new_lines(&self) -> usize {
+        self.new.split_inclusive('\n').count()
+    }
+}
+
+/// Line lookups for diffing, where a trailing newline ends the last line
+/// rather than starting an empty one.
+struct Lines {
+    index: LineIndex,
+    count: usize,
+    len: usize,
+}
+
+impl Lines {
+    fn new(source: &str) -> Self {
+        let index = LineIndex::new(source);
+        let last_start = index.line_start(index.line_count()).unwrap_or(0);
+        let count = if last_start == source.len() {
+            index.line_count() - 1
+        } else {
+            index.line_count()
+        };
+        Self {
+            index,
+            count,
+            len: source.len(),
+        }
+    }
+
+    /// 0-based line containing `offset`; `count` for the end of a source
+    /// ending in a newline.
+    fn line_of(&self, offset: usize) -> usize {
+        self.index.line(offset) - 1
+    }
+
+    /// Offset where 0-based `line` starts, or the end of the source.
+    fn start(&self, line: usize) -> usize {
+        self.index.line_start(line + 1).unwrap_or(self.len)
+    }
+
+    fn text<'s>(&self, source: &'s str, lines: Range<usize>) -> &'s str {
+        source
+            .get(self.start(lines.start)..self.start(lines.end))
+            .unwrap_or_default()
+    }
+}
+
+/// `start,len` for a hunk header, with `start` 1-based; an empty range
+/// names the line before it.
+fn hunk_range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, len),
+    }
+}
+
+/// Append each line of `text` with `prefix`, marking a missing final newline.
+fn push_lines(diff: &mut String, prefix: char, text: &str) {
+    for line in text.split_inclusive('\n') {
+        diff.push(prefix);
+        diff.push_str(line);
+        if !line.ends_with('\n') {
+            diff.push_str("\n\\ No newline at end of file\n");
+        }
+    }
+}
+
+/// Whether two ranges share text, or an insertion falls strictly inside a
+/// replaced range.
+fn overlaps(a: &Range<usize>, b: &Range<usize>) -> bool {
+    a.start < b.end && b.start < a.end
+}
+
+fn span_range<S: SpanLike>(span: &S) -> Result<Range<usize>, RewriteError> {
+    if span.is_call_site() {
+        return Err(RewriteError::CallSite);
+    }
+    Ok(span.start()..span.end())
+}
+
+/// An edit that a [`Rewriter`] can't accept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RewriteError {
+    /// The span doesn't point into the source.
+    CallSite,
+    /// The range is inverted or runs past the end of the source.
+    OutOfBounds {
+        /// The rejected range.
+        range: Range<usize>,
+        /// Length of the source.
+        len: usize,
+    },
+    /// The range splits a character.
+    NotCharBoundary {
+        /// The offending offset.
+        offset: usize,
+    },
+    /// The range overlaps an edit added earlier.
+    Overlap {
+        /// The rejected range.
+        range: Range<usize>,
+        /// The edit it collides with.
+        existing: Range<usize>,
+    },
+}
+
+impl fmt::Display for RewriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RewriteError::CallSite => write!(f, "cannot edit at a call-site span"),
+            RewriteError::OutOfBounds { range, len } => {
+                write!(
+                    f,
+                    "edit {:?} is out of bounds for source of length {}",
+                    range, len
+                )
+            }
+            RewriteError::NotCharBoundary { offset } => {
+                write!(f, "edit offset {} is not on a character boundary", offset)
+            }
+            RewriteError::Overlap { range, existing } => {
+                write!(f, "edit {:?} overlaps earlier edit {:?}", range, existing)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RewriteError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_in_source_order() {
+        let mut rewriter = Rewriter::new("let a = b + c;");
+        rewriter
+            .replace_range(12..13, "d")
+            .unwrap()
+            .replace_range(4..5, "x")
+            .unwrap()
+            .replace_range(8..12, "")
+            .unwrap()
+            .replace_range(4..4, "mut ")
+            .unwrap()
+            .replace_range(4..4, "/* */ ")
+            .unwrap();
+        assert_eq!(rewriter.apply(), "let mut /* */ x = d;");
+        assert_eq!(rewriter.len(), 5);
+        assert_eq!(Rewriter::new("same").apply(), "same");
+    }
+
+    #[test]
+    fn test_rejects_overlaps() {
+        let mut rewriter = Rewriter::new("abcdefgh");
+        rewriter.replace_range(2..5, "X").unwrap();
+        rewriter.replace_range(5..5, "|").unwrap();
+        rewriter.replace_range(2..2, "|").unwrap();
+        assert_eq!(
+            rewriter.replace_range(4..6, "?").err(),
+            Some(RewriteError::Overlap {
+                range: 4..6,
+                existing: 2..5
+            })
+        );
+        for range in [0..3, 3..3, 1..7, 2..5] {
+            assert!(matches!(
+                rewriter.replace_range(range.clone(), "?"),
+                Err(RewriteError::Overlap { range: rejected, .. }) if rejected == range
+            ));
+        }
+        // Touching is fine
+        rewriter.replace_range(0..2, "").unwrap();
+        assert_eq!(rewriter.apply(), "|X|fgh");
+    }
+
+    #[test]
+    fn test_rejects_bad_ranges() {
+        let mut rewriter = Rewriter::new("né");
+        assert_eq!(
+            rewriter.replace_range(1..4, "").err(),
+            Some(RewriteError::OutOfBounds {
+                range: 1..4,
+                len: 3
+            })
+        );
+        assert_eq!(
+            rewriter.replace_range(2..3, "").err(),
+            Some(RewriteError::NotCharBoundary { offset: 2 })
+        );
+        assert!(rewriter.is_empty());
+    }
+
+    #[test]
+    fn test_map_offset() {
+        let mut rewriter = Rewriter::new("a bb c");
+        rewriter
+            .replace_range(2..4, "dddd")
+            .unwrap()
+            .replace_range(0..0, ">")
+            .unwrap();
+        assert_eq!(rewriter.apply(), ">a dddd c");
+        assert_eq!(rewriter.map_offset(0), 1);
+        assert_eq!(rewriter.map_offset(2), 3);
+        assert_eq!(rewriter.map_offset(3), 7);
+        assert_eq!(rewriter.map_offset(4), 7);
+        assert_eq!(rewriter.map_offset(5), 8);
+    }
+
+    fn numbered(lines: usize) -> String {
+        (1..=lines).map(|i| format!("line {i}\n")).collect()
+    }
+
+    /// Offset of the start of 1-based `line` in [`numbered`] text.
+    fn at(source: &str, line: usize) -> usize {
+        LineIndex::new(source).line_start(line).unwrap()
+    }
+
+    #[test]
+    fn test_unified_diff_hunks() {
+        let source = numbered(20);
+        let mut rewriter = Rewriter::new(&source).with_path(Some("src/config.txt"));
+        let l5 = at(&source, 5);
+        rewriter.replace_range(l5 + 5..l5 + 6, "five").unwrap();
+        // Close enough to share the hunk's context
+        let l10 = at(&source, 10);
+        rewriter.replace_range(l10..at(&source, 11), "").unwrap();
+        // Far away
+        let l18 = at(&source, 18);
+        rewriter.replace_range(l18..l18, "new\n").unwrap();
+
+        assert_eq!(
+            rewriter.unified_diff_with_context(2),
+            "--- a/src/config.txt\n+++ b/src/config.txt\n\
+             @@ -3,10 +3,9 @@\n \
+             line 3\n line 4\n-line 5\n+line five\n line 6\n line 7\n line 8\n line 9\n\
+             -line 10\n line 11\n line 12\n\
+             @@ -16,5 +15,6 @@\n \
+             line 16\n line 17\n-line 18\n+new\n+line 18\n line 19\n line 20\n"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_edges() {
+        let mut rewriter = Rewriter::new("a\nb");
+        rewriter.replace_range(3..3, "c").unwrap();
+        rewriter.replace_range(0..0, "// top\n").unwrap();
+        assert_eq!(
+            rewriter.unified_diff(),
+            "--- original\n+++ modified\n@@ -1,2 +1,3 @@\n\
+             -a\n+// top\n+a\n-b\n\\ No newline at end of file\n+bc\n\\ No newline at end of file\n"
+        );
+
+        let mut rewriter = Rewriter::new("a\n");
+        rewriter.replace_range(2..2, "b\n").unwrap();
+        assert_eq!(
+            rewriter.unified_diff_with_context(0),
+            "--- original\n+++ modified\n@@ -1,0 +2 @@\n+b\n"
+        );
+
+        // Edits that change nothing don't show up
+        let mut rewriter = Rewriter::new("same\n");
+        rewriter.replace_range(0..4, "same").unwrap();
+        assert_eq!(rewriter.unified_diff(), "");
+    }
+}
@@ -0,0 +1,98 @@
+//! Scanning helpers for logos callbacks that extend a match past what a
+//! regex can express.
+//!
+//! Logos always takes the longest match and ignores lazy repetition, so
+//! `/\*(?s:.)*?\*/` runs to the *last* `*/` in the input, and nested
+//! comments can't be matched by any regex. Match the opener instead and
+//! bump over the rest from a callback:
+//!
+//! ```ignore
+//! #[regex(r"/\*", |lex| {
+//!     let len = synkit::scan::nested(lex.remainder(), "/*", "*/");
+//!     lex.bump(len.unwrap_or(lex.remainder().len()));
+//!     len.is_some()
+//! })]
+//! BlockComment,
+//! ```
+//!
+//! `parser_kit!` generates this callback for
+//! `#[block_comment(open = "/*", close = "*/", nested)]`, and the [`until`]
+//! form for `#[regex(..., until = "*/")]` and `#[token(..., until = "*/")]`.
+
+/// Length of `rest` up to and including the first `close`, or the last one
+/// with `longest`. `None` if `rest` doesn't contain `close`.
+///
+/// ```ignore
+/// use synkit::scan::until;
+///
+/// assert_eq!(until(" a */ b */", "*/", false), Some(5));
+/// assert_eq!(until(" a */ b */", "*/", true), Some(10));
+/// assert_eq!(until(" a ", "*/", false), None);
+/// ```
+pub fn until(rest: &str, close: &str, longest: bool) -> Option<usize> {
+    let found = if longest {
+        rest.rfind(close)
+    } else {
+        rest.find(close)
+    };
+    found.map(|start| start + close.len())
+}
+
+/// Length of `rest` up to and including the `close` that balances an
+/// `open` already consumed, counting nested `open`/`close` pairs. `None` if
+/// `rest` ends first.
+///
+/// ```ignore
+/// use synkit::scan::nested;
+///
+/// // After the first `/*` of `/* a /* b */ c */ d`
+/// assert_eq!(nested(" a /* b */ c */ d", "/*", "*/"), Some(15));
+/// assert_eq!(nested(" a /* b */", "/*", "*/"), None);
+/// ```
+pub fn nested(rest: &str, open: &str, close: &str) -> Option<usize> {
+    let mut depth = 1usize;
+    let mut pos = 0;
+    while depth > 0 {
+        let tail = rest.get(pos..)?;
+        let next_close = tail.find(close)?;
+        match tail
+            .find(open)
+            .filter(|&at| at < next_close && !open.is_empty())
+        {
+            Some(at) => {
+                depth += 1;
+                pos += at + open.len();
+            }
+            None => {
+                depth -= 1;
+                pos += next_close + close.len();
+            }
+        }
+    }
+    Some(pos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_until() {
+        assert_eq!(until("*/", "*/", false), Some(2));
+        assert_eq!(until("a*/b*/", "*/", true), Some(6));
+        assert_eq!(until("é\n", "\n", false), Some(3));
+        assert_eq!(until("", "*/", true), None);
+    }
+
+    #[test]
+    fn test_nested() {
+        assert_eq!(nested("*/", "/*", "*/"), Some(2));
+        assert_eq!(nested("/**/*/", "/*", "*/"), Some(6));
+        assert_eq!(nested(" /* /* */ */ */x", "/*", "*/"), Some(15));
+        // `/*/` opens before the `*/` inside it can close
+        assert_eq!(nested("/*/ */", "/*", "*/"), None);
+        assert_eq!(nested("", "/*", "*/"), None);
+        // Identical delimiters don't nest
+        assert_eq!(nested("a\"b\"", "\"", "\""), Some(2));
+    }
+}
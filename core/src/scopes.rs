@@ -0,0 +1,346 @@
+//! Lexical scopes for name resolution after parsing.
+//!
+//! [`Scopes`] is a stack of scopes mapping interned names to the span and
+//! value they were declared with. Inner scopes shadow outer ones; declaring
+//! a name twice in one scope returns a [`Redefinition`] with both spans.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use synkit::scopes::Scopes;
+//!
+//! let mut scopes = Scopes::new();
+//! for param in &func.params {
+//!     if let Err(err) = scopes.declare(&param.name.value, param.name.span, Kind::Param) {
+//!         report(err.second, &err.to_string());
+//!         report(err.first, "first defined here");
+//!     }
+//! }
+//! scopes.push();
+//! // ...
+//! let unused = scopes.pop();
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// An interned name.
+///
+/// Symbols from the same [`Interner`] are equal exactly when their names
+/// are, so they are cheap to compare, hash and copy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// The symbol as an index, in interning order.
+    #[inline]
+    pub const fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Maps names to [`Symbol`]s and back.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    symbols: HashMap<Box<str>, Symbol>,
+    names: Vec<Box<str>>,
+}
+
+impl Interner {
+    /// An empty interner.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The symbol for `name`, interning it if it is new.
+    pub fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(&symbol) = self.symbols.get(name) {
+            return symbol;
+        }
+        let symbol = Symbol(u32::try_from(self.names.len()).unwrap_or(u32::MAX));
+        self.names.push(name.into());
+        self.symbols.insert(name.into(), symbol);
+        symbol
+    }
+
+    /// The symbol for `name`, if it has been interned.
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<Symbol> {
+        self.symbols.get(name).copied()
+    }
+
+    /// The name of `symbol`.
+    ///
+    /// Symbols from another interner resolve to an arbitrary name or `""`.
+    #[inline]
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        self.names.get(symbol.index()).map_or("", |name| name)
+    }
+
+    /// The number of distinct names.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Whether nothing has been interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+/// A declared name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Binding<S, V> {
+    /// The name.
+    pub name: Symbol,
+    /// Where it was declared.
+    pub span: S,
+    /// What the name refers to.
+    pub value: V,
+}
+
+/// A name declared twice in the same scope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redefinition<S> {
+    /// The name.
+    pub name: String,
+    /// The existing declaration, which is kept.
+    pub first: S,
+    /// The rejected declaration.
+    pub second: S,
+}
+
+impl<S> fmt::Display for Redefinition<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` is defined more than once in this scope", self.name)
+    }
+}
+
+impl<S: fmt::Debug> std::error::Error for Redefinition<S> {}
+
+/// One scope: its bindings in declaration order, indexed by name.
+#[derive(Debug, Clone)]
+struct Scope<S, V> {
+    index: HashMap<Symbol, usize>,
+    bindings: Vec<Binding<S, V>>,
+}
+
+impl<S, V> Default for Scope<S, V> {
+    fn default() -> Self {
+        Self {
+            index: HashMap::new(),
+            bindings: Vec::new(),
+        }
+    }
+}
+
+impl<S, V> Scope<S, V> {
+    #[inline]
+    fn get(&self, name: Symbol) -> Option<&Binding<S, V>> {
+        self.index.get(&name).and_then(|&i| self.bindings.get(i))
+    }
+}
+
+/// A stack of lexical scopes, starting with one global scope.
+///
+/// `S` is the span type and `V` whatever a name resolves to, e.g. a
+/// declaration kind or a `NodeId`.
+#[derive(Debug, Clone)]
+pub struct Scopes<S, V> {
+    interner: Interner,
+    stack: Vec<Scope<S, V>>,
+}
+
+impl<S, V> Default for Scopes<S, V> {
+    fn default() -> Self {
+        Self::with_interner(Interner::new())
+    }
+}
+
+impl<S, V> Scopes<S, V> {
+    /// A stack holding only the global scope.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Like [`new`](Self::new), sharing symbols with an existing interner.
+    pub fn with_interner(interner: Interner) -> Self {
+        Self {
+            interner,
+            stack: vec![Scope::default()],
+        }
+    }
+
+    /// The interner names are resolved with.
+    #[inline]
+    pub fn interner(&self) -> &Interner {
+        &self.interner
+    }
+
+    /// Mutable access to the interner, e.g. to intern names up front.
+    #[inline]
+    pub fn interner_mut(&mut self) -> &mut Interner {
+        &mut self.interner
+    }
+
+    /// The number of open scopes, counting the global scope.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Open a scope.
+    #[inline]
+    pub fn push(&mut self) {
+        self.stack.push(Scope::default());
+    }
+
+    /// Close the innermost scope and return its bindings in declaration
+    /// order, e.g. to report unused ones.
+    ///
+    /// The global scope is never closed; returns `None` if it is the only
+    /// one left.
+    pub fn pop(&mut self) -> Option<Vec<Binding<S, V>>> {
+        if self.stack.len() == 1 {
+            return None;
+        }
+        self.stack.pop().map(|scope| scope.bindings)
+    }
+
+    /// Run `f` in a new scope, closing it afterwards.
+    pub fn scoped<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        let depth = self.stack.len();
+        self.push();
+        let result = f(self);
+        self.stack.truncate(depth);
+        result
+    }
+
+    /// Declare `name` in the innermost scope, shadowing any outer binding.
+    ///
+    /// If the innermost scope already has `name`, the first declaration is
+    /// kept and a [`Redefinition`] naming both spans is returned.
+    pub fn declare(&mut self, name: &str, span: S, value: V) -> Result<Symbol, Redefinition<S>>
+    where
+        S: Clone,
+    {
+        let symbol = self.interner.intern(name);
+        self.declare_symbol(symbol, span, value)
+    }
+
+    /// [`declare`](Self::declare) with an interned name.
+    pub fn declare_symbol(
+        &mut self,
+        name: Symbol,
+        span: S,
+        value: V,
+    ) -> Result<Symbol, Redefinition<S>>
+    where
+        S: Clone,
+    {
+        let Some(scope) = self.stack.last_mut() else {
+            return Ok(name);
+        };
+        if let Some(existing) = scope.get(name) {
+            return Err(Redefinition {
+                name: self.interner.resolve(name).to_string(),
+                first: existing.span.clone(),
+                second: span,
+            });
+        }
+        scope.index.insert(name, scope.bindings.len());
+        scope.bindings.push(Binding { name, span, value });
+        Ok(name)
+    }
+
+    /// The innermost binding of `name` in any open scope.
+    pub fn lookup(&self, name: &str) -> Option<&Binding<S, V>> {
+        self.interner
+            .get(name)
+            .and_then(|symbol| self.lookup_symbol(symbol))
+    }
+
+    /// [`lookup`](Self::lookup) with an interned name.
+    pub fn lookup_symbol(&self, name: Symbol) -> Option<&Binding<S, V>> {
+        self.stack.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    /// The binding of `name` in the innermost scope only.
+    pub fn lookup_local(&self, name: &str) -> Option<&Binding<S, V>> {
+        let symbol = self.interner.get(name)?;
+        self.stack.last().and_then(|scope| scope.get(symbol))
+    }
+
+    /// The name of `symbol`.
+    #[inline]
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        self.interner.resolve(symbol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Span = std::ops::Range<usize>;
+
+    #[test]
+    fn test_interner() {
+        let mut interner = Interner::new();
+        let a = interner.intern("a");
+        let b = interner.intern("b");
+        assert_eq!(interner.intern("a"), a);
+        assert_ne!(a, b);
+        assert_eq!(interner.resolve(b), "b");
+        assert_eq!(interner.get("c"), None);
+        assert_eq!(interner.len(), 2);
+    }
+
+    #[test]
+    fn test_shadowing() {
+        let mut scopes: Scopes<Span, &str> = Scopes::new();
+        scopes.declare("x", 0..1, "global").unwrap();
+        scopes.push();
+        scopes.declare("x", 5..6, "local").unwrap();
+        assert_eq!(scopes.lookup("x").unwrap().value, "local");
+        assert_eq!(scopes.lookup_local("x").unwrap().span, 5..6);
+
+        let closed = scopes.pop().unwrap();
+        assert_eq!(closed.len(), 1);
+        assert_eq!(scopes.lookup("x").unwrap().value, "global");
+        assert_eq!(scopes.lookup("y"), None);
+        assert_eq!(scopes.pop(), None);
+        assert_eq!(scopes.depth(), 1);
+    }
+
+    #[test]
+    fn test_redefinition() {
+        let mut scopes: Scopes<Span, u32> = Scopes::new();
+        scopes.declare("f", 0..1, 1).unwrap();
+        let err = scopes.declare("f", 8..9, 2).unwrap_err();
+        assert_eq!(err.first, 0..1);
+        assert_eq!(err.second, 8..9);
+        assert_eq!(
+            err.to_string(),
+            "`f` is defined more than once in this scope"
+        );
+        assert_eq!(scopes.lookup("f").unwrap().value, 1);
+    }
+
+    #[test]
+    fn test_scoped() {
+        let mut scopes: Scopes<Span, ()> = Scopes::new();
+        let inner = scopes.scoped(|scopes| {
+            scopes.declare("a", 0..1, ()).unwrap();
+            scopes.push();
+            scopes.lookup("a").is_some()
+        });
+        assert!(inner);
+        assert_eq!(scopes.depth(), 1);
+        assert!(scopes.lookup("a").is_none());
+    }
+}
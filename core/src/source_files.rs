@@ -0,0 +1,202 @@
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::traits::SpanLike;
+
+/// Identifies a file registered in a [`SourceFiles`].
+///
+/// Stored as a non-zero `u32`, so `Option<FileId>` is 4 bytes.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FileId(NonZeroU32);
+
+impl FileId {
+    /// The id of the file at `index` in registration order.
+    ///
+    /// Returns `None` if `index` does not fit in a `u32`.
+    #[inline]
+    pub fn from_index(index: usize) -> Option<Self> {
+        u32::try_from(index)
+            .ok()
+            .and_then(|i| i.checked_add(1))
+            .and_then(NonZeroU32::new)
+            .map(Self)
+    }
+
+    /// Zero-based registration index.
+    #[inline]
+    pub const fn index(self) -> usize {
+        (self.0.get() - 1) as usize
+    }
+}
+
+/// A registered file: its path and source text.
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    path: Arc<Path>,
+    source: Arc<str>,
+}
+
+impl SourceFile {
+    /// Path the file was registered under.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Source text.
+    #[inline]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+/// Maps [`FileId`]s to file paths and source text.
+///
+/// When ASTs parsed from many files are merged (a module graph, a config
+/// directory), byte offsets alone no longer identify a location. Kits
+/// generated with `file_ids: true` carry a [`FileId`] in every span, handed
+/// out by this registry.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut files = SourceFiles::new();
+/// let a: Document = TokenStream::lex_in(&mut files, "a.toml", &source_a)?.parse()?.value;
+/// let b: Document = TokenStream::lex_in(&mut files, "b.toml", &source_b)?.parse()?.value;
+///
+/// let span = b.tables[0].span;
+/// let file = span.file().unwrap();
+/// println!("{}: {:?}", files.path(file).unwrap().display(), files.slice(file, &span));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SourceFiles {
+    files: Vec<SourceFile>,
+}
+
+impl SourceFiles {
+    /// Creates an empty registry.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `source` under `path` and returns its id.
+    ///
+    /// Adding a path that is already registered replaces its source and
+    /// returns the existing id, so re-lexing an edited file keeps its spans
+    /// comparable.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than `u32::MAX - 1` files are registered.
+    #[allow(clippy::expect_used)]
+    pub fn add(&mut self, path: impl AsRef<Path>, source: impl Into<Arc<str>>) -> FileId {
+        let path = path.as_ref();
+        let source = source.into();
+        if let Some(id) = self.find(path) {
+            self.files[id.index()].source = source;
+            return id;
+        }
+        let id = FileId::from_index(self.files.len()).expect("too many source files");
+        self.files.push(SourceFile {
+            path: Arc::from(path),
+            source,
+        });
+        id
+    }
+
+    /// The file registered as `id`.
+    #[inline]
+    pub fn get(&self, id: FileId) -> Option<&SourceFile> {
+        self.files.get(id.index())
+    }
+
+    /// Path of the file registered as `id`.
+    #[inline]
+    pub fn path(&self, id: FileId) -> Option<&Path> {
+        self.get(id).map(SourceFile::path)
+    }
+
+    /// Source text of the file registered as `id`.
+    #[inline]
+    pub fn source(&self, id: FileId) -> Option<&str> {
+        self.get(id).map(SourceFile::source)
+    }
+
+    /// Text covered by `span` in the file registered as `id`.
+    ///
+    /// Returns `None` for unknown ids and out-of-bounds or non-boundary spans.
+    pub fn slice<S: SpanLike>(&self, id: FileId, span: &S) -> Option<&str> {
+        self.source(id)?.get(span.start()..span.end())
+    }
+
+    /// Id of the file registered under `path`.
+    pub fn find(&self, path: impl AsRef<Path>) -> Option<FileId> {
+        let path = path.as_ref();
+        self.files
+            .iter()
+            .position(|file| &*file.path == path)
+            .and_then(FileId::from_index)
+    }
+
+    /// Registered files in registration order.
+    pub fn iter(&self) -> impl Iterator<Item = (FileId, &SourceFile)> {
+        self.files
+            .iter()
+            .enumerate()
+            .filter_map(|(i, file)| Some((FileId::from_index(i)?, file)))
+    }
+
+    /// Number of registered files.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Returns `true` if no files are registered.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_id_index_round_trip() {
+        let id = FileId::from_index(3).unwrap();
+        assert_eq!(id.index(), 3);
+        assert_eq!(size_of::<Option<FileId>>(), 4);
+        assert!(FileId::from_index(u32::MAX as usize).is_none());
+    }
+
+    #[test]
+    fn test_add_and_lookup() {
+        let mut files = SourceFiles::new();
+        let a = files.add("a.txt", "alpha");
+        let b = files.add("b.txt", "beta");
+
+        assert_ne!(a, b);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files.path(b), Some(Path::new("b.txt")));
+        assert_eq!(files.source(a), Some("alpha"));
+        assert_eq!(files.find("b.txt"), Some(b));
+        assert_eq!(
+            files.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            vec![a, b]
+        );
+    }
+
+    #[test]
+    fn test_re_adding_path_keeps_id() {
+        let mut files = SourceFiles::new();
+        let a = files.add("a.txt", "old");
+        assert_eq!(files.add("a.txt", "new"), a);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files.source(a), Some("new"));
+    }
+}
@@ -0,0 +1,207 @@
+use std::ops::Range;
+
+use crate::traits::SpanLike;
+
+/// A single output→source mapping recorded by a [`Printer`](crate::Printer).
+///
+/// `output` is the byte range in the printed buffer, `source` is the byte
+/// range of the node in the original input that produced it.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MappingEntry {
+    /// Byte range in the printed output.
+    pub output: Range<usize>,
+    /// Byte range in the original source.
+    pub source: Range<usize>,
+}
+
+/// Output→source mappings collected while printing.
+///
+/// Entries are recorded in the order nodes *finish* printing, so nested
+/// nodes appear before their parents. Lookups return the innermost
+/// (narrowest) entry covering the requested offset.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut printer = Printer::new().with_source_map();
+/// doc.write(&mut printer);
+/// let (output, map) = printer.finish_with_source_map();
+///
+/// if let Some(entry) = map.source_for_output(12) {
+///     println!("output byte 12 came from {:?}", entry.source);
+/// }
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceMapping {
+    entries: Vec<MappingEntry>,
+}
+
+impl SourceMapping {
+    /// Creates an empty mapping.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that `output` was produced by the node at `span`.
+    ///
+    /// Empty output ranges are ignored since they cannot be looked up.
+    pub fn push<S: SpanLike>(&mut self, output: Range<usize>, span: &S) {
+        if output.is_empty() {
+            return;
+        }
+        self.entries.push(MappingEntry {
+            output,
+            source: span.start()..span.end(),
+        });
+    }
+
+    /// Returns all recorded entries.
+    #[inline]
+    pub fn entries(&self) -> &[MappingEntry] {
+        &self.entries
+    }
+
+    /// Returns the number of recorded entries.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if nothing has been recorded.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adjusts output ranges for `len` bytes inserted into the output at `at`.
+    ///
+    /// Entries after `at` move; entries spanning `at` grow. Used when a
+    /// printer inserts text before output it has already mapped.
+    pub fn insert_output(&mut self, at: usize, len: usize) {
+        for entry in &mut self.entries {
+            if entry.output.start >= at {
+                entry.output.start += len;
+            }
+            if entry.output.end > at {
+                entry.output.end += len;
+            }
+        }
+    }
+
+    /// Clamps output ranges to an output truncated to `len` bytes, dropping
+    /// entries that no longer cover any output.
+    pub fn truncate_output(&mut self, len: usize) {
+        for entry in &mut self.entries {
+            entry.output.end = entry.output.end.min(len);
+        }
+        self.entries
+            .retain(|entry| entry.output.start < entry.output.end);
+    }
+
+    /// Finds the innermost entry whose output range contains `offset`.
+    pub fn source_for_output(&self, offset: usize) -> Option<&MappingEntry> {
+        self.entries
+            .iter()
+            .filter(|e| e.output.contains(&offset))
+            .min_by_key(|e| e.output.len())
+    }
+
+    /// Finds the innermost entry whose source range contains `offset`.
+    pub fn output_for_source(&self, offset: usize) -> Option<&MappingEntry> {
+        self.entries
+            .iter()
+            .filter(|e| e.source.contains(&offset))
+            .min_by_key(|e| e.source.len())
+    }
+}
+
+impl IntoIterator for SourceMapping {
+    type Item = MappingEntry;
+    type IntoIter = std::vec::IntoIter<MappingEntry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy)]
+    struct TestSpan(usize, usize);
+
+    impl SpanLike for TestSpan {
+        fn start(&self) -> usize {
+            self.0
+        }
+
+        fn end(&self) -> usize {
+            self.1
+        }
+
+        fn new(start: usize, end: usize) -> Self {
+            Self(start, end)
+        }
+
+        fn call_site() -> Self {
+            Self(0, 0)
+        }
+    }
+
+    #[test]
+    fn test_insert_output_shifts_and_grows() {
+        let mut map = SourceMapping::new();
+        map.push(0..2, &TestSpan(0, 2));
+        map.push(2..6, &TestSpan(2, 6));
+        map.push(4..6, &TestSpan(4, 6));
+        map.insert_output(4, 3);
+        let outputs: Vec<_> = map.entries().iter().map(|e| e.output.clone()).collect();
+        assert_eq!(outputs, [0..2, 2..9, 7..9]);
+    }
+
+    #[test]
+    fn test_truncate_output() {
+        let mut map = SourceMapping::new();
+        map.push(0..4, &TestSpan(0, 4));
+        map.push(3..4, &TestSpan(3, 4));
+        map.truncate_output(3);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.entries()[0].output, 0..3);
+    }
+
+    #[test]
+    fn test_push_ignores_empty_output() {
+        let mut map = SourceMapping::new();
+        map.push(3..3, &TestSpan(0, 5));
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_innermost_entry_wins() {
+        let mut map = SourceMapping::new();
+        map.push(0..3, &TestSpan(10, 13));
+        map.push(6..9, &TestSpan(20, 23));
+        map.push(0..9, &TestSpan(10, 23));
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.source_for_output(1).unwrap().source, 10..13);
+        assert_eq!(map.source_for_output(4).unwrap().source, 10..23);
+        assert_eq!(map.source_for_output(7).unwrap().source, 20..23);
+        assert!(map.source_for_output(9).is_none());
+    }
+
+    #[test]
+    fn test_output_for_source() {
+        let mut map = SourceMapping::new();
+        map.push(0..3, &TestSpan(10, 13));
+        map.push(0..9, &TestSpan(10, 23));
+
+        assert_eq!(map.output_for_source(11).unwrap().output, 0..3);
+        assert_eq!(map.output_for_source(15).unwrap().output, 0..9);
+        assert!(map.output_for_source(5).is_none());
+    }
+}
@@ -0,0 +1,98 @@
+use std::fmt;
+use std::ops::Range;
+
+/// A span that doesn't fit the source it's used with.
+///
+/// Returned by the checked span APIs: `Span::try_new`,
+/// [`SpanLike::try_slice`](crate::SpanLike::try_slice) and the generated
+/// `TokenStream::try_slice`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpanError {
+    /// `start` is after `end`.
+    Inverted {
+        /// Start byte offset.
+        start: usize,
+        /// End byte offset.
+        end: usize,
+    },
+    /// The span runs past the end of the source.
+    OutOfBounds {
+        /// Start byte offset.
+        start: usize,
+        /// End byte offset.
+        end: usize,
+        /// Length of the source.
+        len: usize,
+    },
+    /// An offset splits a character.
+    NotCharBoundary {
+        /// The offending offset.
+        offset: usize,
+    },
+}
+
+impl SpanError {
+    /// Check that `start..end` is a byte range of `source` on character
+    /// boundaries.
+    pub fn check(start: usize, end: usize, source: &str) -> Result<Range<usize>, SpanError> {
+        if start > end {
+            return Err(SpanError::Inverted { start, end });
+        }
+        if end > source.len() {
+            return Err(SpanError::OutOfBounds {
+                start,
+                end,
+                len: source.len(),
+            });
+        }
+        for offset in [start, end] {
+            if !source.is_char_boundary(offset) {
+                return Err(SpanError::NotCharBoundary { offset });
+            }
+        }
+        Ok(start..end)
+    }
+}
+
+impl fmt::Display for SpanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpanError::Inverted { start, end } => {
+                write!(f, "span {}..{} is inverted", start, end)
+            }
+            SpanError::OutOfBounds { start, end, len } => write!(
+                f,
+                "span {}..{} is out of bounds for source of length {}",
+                start, end, len
+            ),
+            SpanError::NotCharBoundary { offset } => {
+                write!(f, "span offset {} is not on a character boundary", offset)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SpanError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        assert_eq!(SpanError::check(1, 3, "abcd"), Ok(1..3));
+        assert_eq!(SpanError::check(4, 4, "abcd"), Ok(4..4));
+        assert_eq!(
+            SpanError::check(3, 1, "abcd"),
+            Err(SpanError::Inverted { start: 3, end: 1 })
+        );
+        assert_eq!(
+            SpanError::check(2, 9, "abcd").map_err(|e| e.to_string()),
+            Err("span 2..9 is out of bounds for source of length 4".to_string())
+        );
+        assert_eq!(
+            SpanError::check(0, 1, "é"),
+            Err(SpanError::NotCharBoundary { offset: 1 })
+        );
+    }
+}
@@ -0,0 +1,82 @@
+/// Converts a lexer callback's `String` result into a token payload type.
+///
+/// `parser_kit!`'s `string_type:` setting stores `(String)` token payloads
+/// as another type, such as `Cow<'static, str>`, `Box<str>` or (with the
+/// `compact_str` feature) `CompactString`. Callbacks keep returning
+/// `String`, `Option<String>` or `Result<String, E>`; the generated lexer
+/// passes their result through this trait.
+///
+/// Any `S: From<String>` works as a payload, as long as it also implements
+/// the traits the token structs derive (`Clone`, `Default`, `Display`,
+/// [`SpanInsensitive`](crate::SpanInsensitive),
+/// [`EstimateSize`](crate::EstimateSize), ...).
+///
+/// # Example
+///
+/// ```ignore
+/// use std::borrow::Cow;
+/// use synkit::IntoStringPayload;
+///
+/// let payload: Cow<'static, str> = String::from("key").into_payload();
+/// let parsed: Option<Cow<'static, str>> = Some(String::from("key")).into_payload();
+/// ```
+pub trait IntoStringPayload<S> {
+    /// The callback result with `String` replaced by `S`.
+    type Output;
+
+    /// Convert the `String` inside `self` to `S`.
+    fn into_payload(self) -> Self::Output;
+}
+
+impl<S: From<String>> IntoStringPayload<S> for String {
+    type Output = S;
+
+    #[inline]
+    fn into_payload(self) -> S {
+        S::from(self)
+    }
+}
+
+impl<S: From<String>> IntoStringPayload<S> for Option<String> {
+    type Output = Option<S>;
+
+    #[inline]
+    fn into_payload(self) -> Option<S> {
+        self.map(S::from)
+    }
+}
+
+impl<S: From<String>, E> IntoStringPayload<S> for Result<String, E> {
+    type Output = Result<S, E>;
+
+    #[inline]
+    fn into_payload(self) -> Result<S, E> {
+        self.map(S::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+
+    #[test]
+    fn test_converts_callback_results() {
+        let s: Cow<'static, str> = String::from("a").into_payload();
+        assert!(matches!(s, Cow::Owned(ref v) if v == "a"));
+
+        let s: Option<Box<str>> = Some(String::from("b")).into_payload();
+        assert_eq!(s.as_deref(), Some("b"));
+
+        let s: Result<Box<str>, ()> = Err::<String, ()>(()).into_payload();
+        assert_eq!(s, Err(()));
+    }
+
+    #[cfg(feature = "compact_str")]
+    #[test]
+    fn test_compact_str() {
+        let s: compact_str::CompactString = String::from("short").into_payload();
+        assert_eq!(s, "short");
+        assert!(!s.is_heap_allocated());
+    }
+}
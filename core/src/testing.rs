@@ -0,0 +1,793 @@
+//! Test helpers for formatters built on `ToTokens`.
+//!
+//! A formatter is idempotent when formatting its own output changes nothing.
+//! A printer is faithful when its output lexes to the same significant
+//! tokens as the input it was parsed from. Kits generated by `parser_kit!`
+//! wrap these helpers as `testing::assert_format_idempotent::<T>(input)` and
+//! `testing::assert_relex::<T>(input)`.
+//!
+//! ```ignore
+//! #[test]
+//! fn format_is_idempotent() {
+//!     testing::assert_format_idempotent::<Document>("a=1\n[t]\nb = [1,2]");
+//!     testing::assert_relex::<Document>("a=1\n[t]\nb = [1,2]");
+//! }
+//! ```
+//!
+//! [`lex_print_lex`] checks the tokens themselves: each one, printed on its
+//! own, must lex back to the same token. Kits wrap it as
+//! `testing::lex_print_lex(corpus)`.
+//!
+//! [`shrink`] reduces a failing input to a small one that still fails, for
+//! turning large bug reports into test cases.
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::{LineCol, SourceMapping};
+
+/// Why a formatter failed the idempotence check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdempotenceError {
+    /// The input didn't parse.
+    Input {
+        /// Parse error message.
+        error: String,
+    },
+    /// The formatted output didn't parse.
+    Reparse {
+        /// Output of the first print.
+        output: String,
+        /// Parse error message.
+        error: String,
+    },
+    /// Formatting the output again changed it.
+    Diverged {
+        /// Output of the first print.
+        first: String,
+        /// Output of printing the reparsed first output.
+        second: String,
+        /// Byte offset of the first difference.
+        offset: usize,
+        /// Range in `first` of the innermost node that printed the
+        /// divergent text, if the printer recorded one.
+        span: Option<Range<usize>>,
+    },
+}
+
+impl fmt::Display for IdempotenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Input { error } => write!(f, "input failed to parse: {error}"),
+            Self::Reparse { output, error } => {
+                write!(f, "formatted output failed to parse: {error}\n{output}")
+            }
+            Self::Diverged {
+                first,
+                second,
+                offset,
+                span,
+            } => {
+                let pos = LineCol::of(first, *offset);
+                writeln!(
+                    f,
+                    "formatting is not idempotent at {}:{} (byte {offset})",
+                    pos.line, pos.column
+                )?;
+                writeln!(f, "  first:  {:?}", line_at(first, *offset))?;
+                write!(f, "  second: {:?}", line_at(second, *offset))?;
+                if let Some(range) = span {
+                    if let Some(text) = first.get(range.clone()) {
+                        write!(f, "\n  node:   {range:?} {text:?}")?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for IdempotenceError {}
+
+/// A lexed token: its display text and byte range.
+pub type LexedToken = (String, Range<usize>);
+
+/// Why printed output failed to re-lex to the input's tokens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelexError {
+    /// The input didn't lex or parse.
+    Input {
+        /// Lex or parse error message.
+        error: String,
+    },
+    /// The printed output didn't lex.
+    Lex {
+        /// Printed output.
+        output: String,
+        /// Lex error message.
+        error: String,
+    },
+    /// The printed output lexed to a different token sequence.
+    Mismatch(Box<TokenMismatch>),
+}
+
+/// The first token where printed output and input disagree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenMismatch {
+    /// Printed output.
+    pub output: String,
+    /// Index of the first differing significant token.
+    pub index: usize,
+    /// Token from the input, with its range in the input; `None` if the
+    /// output has extra tokens.
+    pub expected: Option<LexedToken>,
+    /// Token from the output, with its range in the output; `None` if the
+    /// output ended early.
+    pub found: Option<LexedToken>,
+    /// Source range of the innermost node that printed `found`, if the
+    /// printer recorded one.
+    pub node: Option<Range<usize>>,
+}
+
+impl fmt::Display for RelexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Input { error } => write!(f, "input failed to parse: {error}"),
+            Self::Lex { output, error } => {
+                write!(f, "printed output failed to lex: {error}\n{output}")
+            }
+            Self::Mismatch(mismatch) => {
+                let TokenMismatch {
+                    output,
+                    index,
+                    expected,
+                    found,
+                    node,
+                } = &**mismatch;
+                writeln!(f, "printed output re-lexes differently at token {index}")?;
+                match expected {
+                    Some((text, range)) => {
+                        writeln!(f, "  expected: {text:?} at {range:?} in input")?;
+                    }
+                    None => writeln!(f, "  expected: end of input")?,
+                }
+                match found {
+                    Some((text, range)) => {
+                        let pos = LineCol::of(output, range.start);
+                        writeln!(
+                            f,
+                            "  found:    {text:?} at {}:{} in output",
+                            pos.line, pos.column
+                        )?;
+                        write!(f, "  line:     {:?}", line_at(output, range.start))?;
+                    }
+                    None => write!(f, "  found:    end of output")?,
+                }
+                if let Some(range) = node {
+                    write!(f, "\n  node:     {range:?} in input")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for RelexError {}
+
+/// Why a token failed the lex-print-lex check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LexPrintLexError {
+    /// A corpus entry didn't lex.
+    Input {
+        /// Index of the entry in the corpus.
+        entry: usize,
+        /// Lex error message.
+        error: String,
+    },
+    /// A token's printed form didn't lex back to the same token.
+    Mismatch(Box<PrintedTokenMismatch>),
+}
+
+/// A token whose printed form lexes to something else.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrintedTokenMismatch {
+    /// Index of the corpus entry the token came from.
+    pub entry: usize,
+    /// Byte range of the token in the entry.
+    pub range: Range<usize>,
+    /// Token variant name, e.g. `Newline`.
+    pub kind: String,
+    /// What the token printed as.
+    pub printed: String,
+    /// What the printed text lexed as, e.g. `` `Ident` ``, several tokens
+    /// or a lex error.
+    pub found: String,
+}
+
+impl fmt::Display for LexPrintLexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Input { entry, error } => {
+                write!(f, "corpus entry {entry} failed to lex: {error}")
+            }
+            Self::Mismatch(mismatch) => {
+                let PrintedTokenMismatch {
+                    entry,
+                    range,
+                    kind,
+                    printed,
+                    found,
+                } = &**mismatch;
+                write!(
+                    f,
+                    "token `{kind}` at {range:?} in corpus entry {entry} prints as {printed:?}, \
+                     which lexes as {found}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for LexPrintLexError {}
+
+/// Lex each corpus entry, print every token on its own, and lex the printed
+/// text again; it must be exactly one token of the same kind and text.
+/// Reports the first token that doesn't round-trip.
+///
+/// `lex` returns all tokens of a source, skip tokens included, with their
+/// byte ranges. Tokens are compared by `kind` and their `Display` text.
+/// Tokens that print nothing (`#[no_to_tokens]`) are skipped.
+pub fn lex_print_lex<'a, T, E, I, L, K, P>(
+    corpus: I,
+    lex: L,
+    kind: K,
+    print: P,
+) -> Result<(), LexPrintLexError>
+where
+    T: fmt::Display,
+    E: fmt::Display,
+    I: IntoIterator<Item = &'a str>,
+    L: Fn(&str) -> Result<Vec<(T, Range<usize>)>, E>,
+    K: Fn(&T) -> &'static str,
+    P: Fn(&T) -> String,
+{
+    for (entry, source) in corpus.into_iter().enumerate() {
+        let tokens = lex(source).map_err(|e| LexPrintLexError::Input {
+            entry,
+            error: e.to_string(),
+        })?;
+        for (token, range) in tokens {
+            let printed = print(&token);
+            if printed.is_empty() {
+                continue;
+            }
+            let found = match lex(&printed) {
+                Ok(relexed) => match relexed.as_slice() {
+                    [(again, whole)]
+                        if *whole == (0..printed.len())
+                            && kind(again) == kind(&token)
+                            && again.to_string() == token.to_string() =>
+                    {
+                        continue;
+                    }
+                    [(again, whole)] if *whole == (0..printed.len()) => {
+                        if kind(again) == kind(&token) {
+                            format!("`{}` with text {:?}", kind(again), again.to_string())
+                        } else {
+                            format!("`{}`", kind(again))
+                        }
+                    }
+                    [] => "no tokens".to_string(),
+                    relexed => {
+                        let kinds: Vec<_> = relexed
+                            .iter()
+                            .map(|(again, _)| format!("`{}`", kind(again)))
+                            .collect();
+                        format!("{} tokens: {}", relexed.len(), kinds.join(", "))
+                    }
+                },
+                Err(e) => format!("a lex error: {e}"),
+            };
+            return Err(LexPrintLexError::Mismatch(Box::new(PrintedTokenMismatch {
+                entry,
+                range,
+                kind: kind(&token).to_string(),
+                printed,
+                found,
+            })));
+        }
+    }
+    Ok(())
+}
+
+/// Like [`lex_print_lex`], but panics with the report on failure.
+#[track_caller]
+#[allow(clippy::panic)]
+pub fn assert_lex_print_lex_with<'a, T, E, I, L, K, P>(corpus: I, lex: L, kind: K, print: P)
+where
+    T: fmt::Display,
+    E: fmt::Display,
+    I: IntoIterator<Item = &'a str>,
+    L: Fn(&str) -> Result<Vec<(T, Range<usize>)>, E>,
+    K: Fn(&T) -> &'static str,
+    P: Fn(&T) -> String,
+{
+    if let Err(err) = lex_print_lex(corpus, lex, kind, print) {
+        panic!("{err}");
+    }
+}
+
+/// Parse and print `input`, then lex both the input and the output and
+/// compare their significant tokens. Returns the printed output if the
+/// token sequences match.
+///
+/// `lex` returns the significant (non-skip) tokens of a source; tokens are
+/// compared by their text. `print` returns the output with its source
+/// mapping, which locates the node that printed the first wrong token.
+pub fn check_relex<T, E, L, P, F>(
+    input: &str,
+    lex: L,
+    parse: P,
+    print: F,
+) -> Result<String, RelexError>
+where
+    E: fmt::Display,
+    L: Fn(&str) -> Result<Vec<LexedToken>, E>,
+    P: Fn(&str) -> Result<T, E>,
+    F: Fn(&T) -> (String, SourceMapping),
+{
+    let input_error = |e: E| RelexError::Input {
+        error: e.to_string(),
+    };
+    let value = parse(input).map_err(input_error)?;
+    let expected = lex(input).map_err(input_error)?;
+    let (output, map) = print(&value);
+    let found = match lex(&output) {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            return Err(RelexError::Lex {
+                output,
+                error: e.to_string(),
+            });
+        }
+    };
+
+    let Some(index) = (0..expected.len().max(found.len()))
+        .find(|&i| token_text(&expected, i) != token_text(&found, i))
+    else {
+        return Ok(output);
+    };
+    let found = found.get(index).cloned();
+    let node = found
+        .as_ref()
+        .and_then(|(_, range)| map.source_for_output(range.start))
+        .map(|entry| entry.source.clone());
+    Err(RelexError::Mismatch(Box::new(TokenMismatch {
+        output,
+        index,
+        expected: expected.get(index).cloned(),
+        found,
+        node,
+    })))
+}
+
+/// Like [`check_relex`], but panics with the report on failure.
+#[track_caller]
+#[allow(clippy::panic)]
+pub fn assert_relex_with<T, E, L, P, F>(input: &str, lex: L, parse: P, print: F) -> String
+where
+    E: fmt::Display,
+    L: Fn(&str) -> Result<Vec<LexedToken>, E>,
+    P: Fn(&str) -> Result<T, E>,
+    F: Fn(&T) -> (String, SourceMapping),
+{
+    match check_relex(input, lex, parse, print) {
+        Ok(output) => output,
+        Err(err) => panic!("{err}"),
+    }
+}
+
+/// Parse `input`, print it, reparse and reprint the output, and compare the
+/// two printed outputs. Returns the formatted output if they are identical.
+///
+/// `print` returns the output with its source mapping; the mapping of the
+/// second print locates the node whose output diverged.
+pub fn check_format_idempotent<T, E, P, F>(
+    input: &str,
+    parse: P,
+    print: F,
+) -> Result<String, IdempotenceError>
+where
+    E: fmt::Display,
+    P: Fn(&str) -> Result<T, E>,
+    F: Fn(&T) -> (String, SourceMapping),
+{
+    let first = parse(input).map_err(|e| IdempotenceError::Input {
+        error: e.to_string(),
+    })?;
+    let (first, _) = print(&first);
+    let second = match parse(&first) {
+        Ok(value) => value,
+        Err(e) => {
+            return Err(IdempotenceError::Reparse {
+                output: first,
+                error: e.to_string(),
+            });
+        }
+    };
+    let (second, map) = print(&second);
+
+    let Some(offset) = first_difference(&first, &second) else {
+        return Ok(first);
+    };
+    let span = map
+        .source_for_output(offset.min(second.len().saturating_sub(1)))
+        .map(|entry| entry.source.clone());
+    Err(IdempotenceError::Diverged {
+        first,
+        second,
+        offset,
+        span,
+    })
+}
+
+/// Like [`check_format_idempotent`], but panics with the report on failure.
+#[track_caller]
+#[allow(clippy::panic)]
+pub fn assert_format_idempotent_with<T, E, P, F>(input: &str, parse: P, print: F) -> String
+where
+    E: fmt::Display,
+    P: Fn(&str) -> Result<T, E>,
+    F: Fn(&T) -> (String, SourceMapping),
+{
+    match check_format_idempotent(input, parse, print) {
+        Ok(output) => output,
+        Err(err) => panic!("{err}"),
+    }
+}
+
+/// Shrink `input` to a smaller input for which `fails` still returns true.
+///
+/// Regions are removed along token boundaries: each region is one
+/// significant token from `lex` plus the trivia after it, so the remaining
+/// tokens stay separated as in the input. Removal follows delta debugging,
+/// trying large chunks first and halving down to single tokens; the result
+/// is 1-minimal (removing any single remaining token makes it pass). If
+/// `input` doesn't lex, lines are removed instead.
+///
+/// Returns `input` unchanged if it doesn't fail to begin with. Predicates
+/// can check for a parse error, a divergence (e.g. `check_relex(..).is_err()`)
+/// or a panic via [`panics`].
+///
+/// ```ignore
+/// let small = synkit::testing::shrink(&big, lex, |s| {
+///     Document::parse_str(s).is_ok_and(|doc| check(&doc).is_err())
+/// });
+/// ```
+pub fn shrink<E, L, F>(input: &str, lex: L, mut fails: F) -> String
+where
+    L: Fn(&str) -> Result<Vec<LexedToken>, E>,
+    F: FnMut(&str) -> bool,
+{
+    if !fails(input) {
+        return input.to_string();
+    }
+    let starts: Vec<usize> = match lex(input) {
+        Ok(tokens) => tokens.iter().map(|(_, range)| range.start).collect(),
+        Err(_) => std::iter::once(0)
+            .chain(input.match_indices('\n').map(|(i, _)| i + 1))
+            .filter(|&start| start < input.len())
+            .collect(),
+    };
+
+    // Leading trivia is kept; each region carries its trailing trivia
+    let first = starts.first().copied().unwrap_or(input.len());
+    let prefix = &input[..first];
+    let mut regions: Vec<&str> = starts
+        .iter()
+        .enumerate()
+        .filter_map(|(i, &start)| {
+            let end = starts.get(i + 1).copied().unwrap_or(input.len());
+            input.get(start..end)
+        })
+        .collect();
+    let join = |regions: &[&str]| {
+        let mut out = String::from(prefix);
+        out.extend(regions.iter().copied());
+        out
+    };
+
+    let mut chunks = 2;
+    while !regions.is_empty() {
+        let size = regions.len().div_ceil(chunks);
+        let reduced = (0..regions.len()).step_by(size).find_map(|start| {
+            let end = (start + size).min(regions.len());
+            let candidate: Vec<&str> = regions[..start]
+                .iter()
+                .chain(&regions[end..])
+                .copied()
+                .collect();
+            fails(&join(&candidate)).then_some(candidate)
+        });
+        match reduced {
+            Some(candidate) => {
+                regions = candidate;
+                chunks = (chunks - 1).max(2);
+            }
+            None if size == 1 => break,
+            None => chunks = (chunks * 2).min(regions.len()),
+        }
+    }
+    join(&regions)
+}
+
+/// Whether `f` panics, for [`shrink`] predicates. The panic message is
+/// still printed by the panic hook.
+pub fn panics(f: impl FnOnce()) -> bool {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).is_err()
+}
+
+/// Text of the token at `index`, if any.
+fn token_text(tokens: &[LexedToken], index: usize) -> Option<&str> {
+    tokens.get(index).map(|(text, _)| text.as_str())
+}
+
+/// Byte offset of the first character where `a` and `b` differ.
+fn first_difference(a: &str, b: &str) -> Option<usize> {
+    let mut chars = a.char_indices().zip(b.chars());
+    match chars.find(|((_, x), y)| x != y) {
+        Some(((offset, _), _)) => Some(offset),
+        None if a.len() != b.len() => Some(a.len().min(b.len())),
+        None => None,
+    }
+}
+
+/// The line of `text` containing `offset`.
+fn line_at(text: &str, offset: usize) -> &str {
+    let offset = (0..=offset.min(text.len()))
+        .rev()
+        .find(|&i| text.is_char_boundary(i))
+        .unwrap_or(0);
+    let start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let end = text[offset..].find('\n').map_or(text.len(), |i| offset + i);
+    &text[start..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "Parses" whitespace-separated words and prints them joined by `sep`.
+    fn check(input: &str, sep: &'static str) -> Result<String, IdempotenceError> {
+        check_format_idempotent(
+            input,
+            |s: &str| {
+                if s.contains('!') {
+                    Err("unexpected `!`")
+                } else {
+                    Ok(s.split_whitespace().map(str::to_string).collect::<Vec<_>>())
+                }
+            },
+            |words: &Vec<String>| (words.join(sep), SourceMapping::new()),
+        )
+    }
+
+    #[test]
+    fn test_idempotent_format() {
+        assert_eq!(check("a   b\n c", " ").as_deref(), Ok("a b c"));
+    }
+
+    #[test]
+    fn test_divergence_reports_offset() {
+        // Appends `y` on every print, so the second print differs at byte 2
+        let err = check_format_idempotent(
+            "x",
+            |s: &str| Ok::<_, String>(s.to_string()),
+            |s: &String| (format!("{s}y"), SourceMapping::new()),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            IdempotenceError::Diverged {
+                first: "xy".into(),
+                second: "xyy".into(),
+                offset: 2,
+                span: None,
+            }
+        );
+        assert!(
+            err.to_string()
+                .starts_with("formatting is not idempotent at 1:3")
+        );
+    }
+
+    #[test]
+    fn test_parse_failures() {
+        assert!(matches!(
+            check("a!", " "),
+            Err(IdempotenceError::Input { .. })
+        ));
+        assert!(matches!(
+            check("a b c", "!"),
+            Err(IdempotenceError::Reparse { .. })
+        ));
+    }
+
+    fn words(s: &str) -> Result<Vec<LexedToken>, &'static str> {
+        let mut tokens = Vec::new();
+        let mut offset = 0;
+        for word in s.split(' ') {
+            if !word.is_empty() {
+                tokens.push((word.to_string(), offset..offset + word.len()));
+            }
+            offset += word.len() + 1;
+        }
+        Ok(tokens)
+    }
+
+    #[test]
+    fn test_relex_match() {
+        let output = check_relex(
+            "a  b",
+            words,
+            |s: &str| words(s),
+            |tokens: &Vec<LexedToken>| {
+                let texts: Vec<_> = tokens.iter().map(|(t, _)| t.as_str()).collect();
+                (texts.join(" "), SourceMapping::new())
+            },
+        );
+        assert_eq!(output.as_deref(), Ok("a b"));
+    }
+
+    #[test]
+    fn test_relex_mismatch() {
+        // Joins words without separators, so `b` and `c` merge
+        let err = check_relex(
+            "a b c",
+            words,
+            |s: &str| words(s),
+            |tokens: &Vec<LexedToken>| {
+                let texts: Vec<_> = tokens.iter().map(|(t, _)| t.as_str()).collect();
+                (
+                    format!("{} {}", texts[0], texts[1..].concat()),
+                    SourceMapping::new(),
+                )
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            RelexError::Mismatch(Box::new(TokenMismatch {
+                output: "a bc".into(),
+                index: 1,
+                expected: Some(("b".into(), 2..3)),
+                found: Some(("bc".into(), 2..4)),
+                node: None,
+            }))
+        );
+        assert!(err.to_string().contains("\"bc\" at 1:3 in output"));
+    }
+
+    // Lexes words and runs of spaces; words are kinds, digits become "num"
+    fn word_tokens(s: &str) -> Result<Vec<(String, Range<usize>)>, &'static str> {
+        let mut tokens: Vec<(String, Range<usize>)> = Vec::new();
+        for (offset, c) in s.char_indices() {
+            if c == '!' {
+                return Err("unexpected `!`");
+            }
+            match tokens.last_mut() {
+                Some((text, range))
+                    if range.end == offset && text.starts_with(' ') == (c == ' ') =>
+                {
+                    text.push(c);
+                    range.end = offset + c.len_utf8();
+                }
+                _ => tokens.push((c.to_string(), offset..offset + c.len_utf8())),
+            }
+        }
+        Ok(tokens)
+    }
+
+    // `&String` because the tokens are `String`s
+    #[allow(clippy::ptr_arg)]
+    fn word_kind(text: &String) -> &'static str {
+        if text.starts_with(' ') {
+            "Space"
+        } else {
+            "Word"
+        }
+    }
+
+    #[test]
+    fn test_lex_print_lex_round_trips() {
+        let ok = lex_print_lex(["ab  cd", "e"], word_tokens, word_kind, String::clone);
+        assert_eq!(ok, Ok(()));
+    }
+
+    #[test]
+    fn test_lex_print_lex_reports_first_token() {
+        // Spaces print as a placeholder word, like `#[fmt("space")]`
+        let print = |t: &String| {
+            if t.starts_with(' ') {
+                "space".to_string()
+            } else {
+                t.clone()
+            }
+        };
+        let err = lex_print_lex(["ab", "a  b"], word_tokens, word_kind, print).unwrap_err();
+        assert_eq!(
+            err,
+            LexPrintLexError::Mismatch(Box::new(PrintedTokenMismatch {
+                entry: 1,
+                range: 1..3,
+                kind: "Space".into(),
+                printed: "space".into(),
+                found: "`Word`".into(),
+            }))
+        );
+        assert_eq!(
+            err.to_string(),
+            "token `Space` at 1..3 in corpus entry 1 prints as \"space\", which lexes as `Word`"
+        );
+
+        let err = lex_print_lex(["a b"], word_tokens, word_kind, |t| format!("{t}!")).unwrap_err();
+        assert!(
+            err.to_string()
+                .ends_with("which lexes as a lex error: unexpected `!`")
+        );
+        assert_eq!(
+            lex_print_lex(["a!"], word_tokens, word_kind, String::clone),
+            Err(LexPrintLexError::Input {
+                entry: 0,
+                error: "unexpected `!`".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_first_difference() {
+        assert_eq!(first_difference("abc", "abc"), None);
+        assert_eq!(first_difference("abc", "abd"), Some(2));
+        assert_eq!(first_difference("ab", "abc"), Some(2));
+        assert_eq!(first_difference("é1", "é2"), Some(2));
+    }
+
+    #[test]
+    fn test_shrink_removes_tokens() {
+        // Fails while `b` still comes somewhere after `a`
+        let fails = |s: &str| s.find('a').is_some_and(|a| s[a..].contains('b'));
+        let small = shrink("x y a z z  y b x", words, fails);
+        assert_eq!(small, "a b ");
+
+        let small = shrink("x y a z z  y b", words, fails);
+        assert_eq!(small, "a b");
+    }
+
+    #[test]
+    fn test_shrink_passing_input_is_unchanged() {
+        assert_eq!(shrink("a b c", words, |_| false), "a b c");
+    }
+
+    #[test]
+    fn test_shrink_falls_back_to_lines() {
+        let small = shrink(
+            "one\ntwo\nbad\nthree\n",
+            |_: &str| Err::<Vec<LexedToken>, _>("no lexer"),
+            |s| s.contains("bad"),
+        );
+        assert_eq!(small, "bad\n");
+    }
+
+    #[test]
+    fn test_shrink_panics() {
+        let small = shrink("1 2 0 3", words, |s| {
+            panics(|| {
+                for n in s.split_whitespace() {
+                    let n: u32 = n.parse().unwrap();
+                    assert!(n > 0);
+                }
+            })
+        });
+        assert_eq!(small, "0 ");
+    }
+}
@@ -0,0 +1,379 @@
+use std::fmt::{self, Write as _};
+
+/// A token stream saved without its parser: each token's byte range, kind
+/// and source text.
+///
+/// Produced by `TokenStream::dump` and turned back into a stream by
+/// `TokenStream::load`, so parse stages can be tested from token fixtures
+/// without the lexer or the original file. The text form has one token per
+/// line and is meant to be checked in and edited by hand:
+///
+/// ```text
+/// # synkit tokens
+/// 0..3 KwLet "let"
+/// 3..4 Space " "
+/// 4..5 Ident "x"
+/// ```
+///
+/// Blank lines and lines starting with `#` are ignored. The binary form,
+/// from [`to_bytes`](Self::to_bytes), is smaller and stores each kind name
+/// once.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TokenDump {
+    /// Tokens in stream order, skipped tokens included.
+    pub tokens: Vec<DumpedToken>,
+}
+
+/// One token of a [`TokenDump`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DumpedToken {
+    /// Start byte offset in the original source.
+    pub start: usize,
+    /// End byte offset in the original source (exclusive).
+    pub end: usize,
+    /// Token variant name, e.g. `Ident`.
+    pub kind: String,
+    /// Source text of the token; `end - start` bytes long.
+    pub text: String,
+}
+
+/// Why a [`TokenDump`] couldn't be read or loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DumpError {
+    /// A line of the text form is malformed.
+    Syntax {
+        /// 1-based line number.
+        line: usize,
+        /// What is wrong with it.
+        message: String,
+    },
+    /// The binary form is truncated or corrupt.
+    Binary,
+    /// A token's text isn't as long as its range, or it starts before the
+    /// previous token ends.
+    Range {
+        /// Index of the token.
+        index: usize,
+    },
+    /// A token's text doesn't lex as a single token of its kind.
+    Relex {
+        /// Index of the token.
+        index: usize,
+        /// The token's kind in the dump.
+        expected: String,
+        /// What its text lexed as, e.g. `Ident`, `2 tokens` or an error.
+        found: String,
+    },
+}
+
+impl fmt::Display for DumpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DumpError::Syntax { line, message } => {
+                write!(f, "token dump line {}: {}", line, message)
+            }
+            DumpError::Binary => write!(f, "token dump is truncated or corrupt"),
+            DumpError::Range { index } => {
+                write!(f, "token {}: text doesn't match its range", index)
+            }
+            DumpError::Relex {
+                index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "token {}: expected one `{}` token, found {}",
+                index, expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DumpError {}
+
+/// Leads the binary form; text dumps can't start with a NUL.
+const MAGIC: &[u8] = b"\0synkit-tokens\x01";
+const HEADER: &str = "# synkit tokens";
+
+impl TokenDump {
+    /// An empty dump.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a token.
+    pub fn push(
+        &mut self,
+        start: usize,
+        end: usize,
+        kind: impl Into<String>,
+        text: impl Into<String>,
+    ) {
+        self.tokens.push(DumpedToken {
+            start,
+            end,
+            kind: kind.into(),
+            text: text.into(),
+        });
+    }
+
+    /// The text form: a header line, then `start..end Kind "text"` per token.
+    pub fn to_text(&self) -> String {
+        let mut out = String::from(HEADER);
+        out.push('\n');
+        for token in &self.tokens {
+            let _ = writeln!(
+                out,
+                "{}..{} {} {:?}",
+                token.start, token.end, token.kind, token.text
+            );
+        }
+        out
+    }
+
+    /// Read the text form.
+    pub fn from_text(text: &str) -> Result<Self, DumpError> {
+        let mut dump = Self::new();
+        for (i, line) in text.lines().enumerate() {
+            let line_no = i + 1;
+            let line = line.trim_start();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let syntax = |message: &str| DumpError::Syntax {
+                line: line_no,
+                message: message.to_string(),
+            };
+            let (range, rest) = line
+                .split_once(' ')
+                .ok_or_else(|| syntax("expected `start..end Kind \"text\"`"))?;
+            let (start, end) = range
+                .split_once("..")
+                .and_then(|(start, end)| Some((start.parse().ok()?, end.parse().ok()?)))
+                .ok_or_else(|| syntax("expected a range like `0..3`"))?;
+            let (kind, quoted) = rest
+                .trim_start()
+                .split_once(' ')
+                .ok_or_else(|| syntax("expected a kind and quoted text"))?;
+            let text = unquote(quoted.trim()).ok_or_else(|| syntax("malformed quoted text"))?;
+            dump.push(start, end, kind, text);
+        }
+        Ok(dump)
+    }
+
+    /// The binary form.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut kinds: Vec<&str> = Vec::new();
+        let mut indices = Vec::with_capacity(self.tokens.len());
+        for token in &self.tokens {
+            let index = match kinds.iter().position(|kind| *kind == token.kind) {
+                Some(index) => index,
+                None => {
+                    kinds.push(&token.kind);
+                    kinds.len() - 1
+                }
+            };
+            indices.push(index);
+        }
+
+        let mut out = MAGIC.to_vec();
+        write_varint(&mut out, kinds.len());
+        for kind in &kinds {
+            write_str(&mut out, kind);
+        }
+        write_varint(&mut out, self.tokens.len());
+        for (token, kind) in self.tokens.iter().zip(indices) {
+            write_varint(&mut out, token.start);
+            write_varint(&mut out, kind);
+            write_str(&mut out, &token.text);
+        }
+        out
+    }
+
+    /// Read the binary form.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DumpError> {
+        let mut input = bytes.strip_prefix(MAGIC).ok_or(DumpError::Binary)?;
+        let input = &mut input;
+
+        let kind_count = read_varint(input)?;
+        let mut kinds = Vec::new();
+        for _ in 0..kind_count {
+            kinds.push(read_str(input)?);
+        }
+        let count = read_varint(input)?;
+        let mut dump = Self::new();
+        for _ in 0..count {
+            let start = read_varint(input)?;
+            let kind = kinds.get(read_varint(input)?).ok_or(DumpError::Binary)?;
+            let text = read_str(input)?;
+            let end = start.checked_add(text.len()).ok_or(DumpError::Binary)?;
+            dump.push(start, end, kind.as_str(), text);
+        }
+        if !input.is_empty() {
+            return Err(DumpError::Binary);
+        }
+        Ok(dump)
+    }
+
+    /// Read either form.
+    pub fn parse(bytes: &[u8]) -> Result<Self, DumpError> {
+        if bytes.starts_with(MAGIC) {
+            return Self::from_bytes(bytes);
+        }
+        let text = std::str::from_utf8(bytes).map_err(|_| DumpError::Syntax {
+            line: 1,
+            message: "not UTF-8 text or a binary token dump".to_string(),
+        })?;
+        Self::from_text(text)
+    }
+
+    /// The source the tokens were lexed from, as far as the dump records it:
+    /// each token's text at its offset, with spaces in between.
+    ///
+    /// Fails with [`DumpError::Range`] if a token's text doesn't fit its
+    /// range or tokens overlap.
+    pub fn source(&self) -> Result<String, DumpError> {
+        let mut source = String::new();
+        for (index, token) in self.tokens.iter().enumerate() {
+            if token.start < source.len()
+                || token.end.checked_sub(token.start) != Some(token.text.len())
+            {
+                return Err(DumpError::Range { index });
+            }
+            source.extend(std::iter::repeat_n(' ', token.start - source.len()));
+            source.push_str(&token.text);
+        }
+        Ok(source)
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    write_varint(out, s.len());
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn read_varint(input: &mut &[u8]) -> Result<usize, DumpError> {
+    let mut value: usize = 0;
+    for shift in (0..usize::BITS).step_by(7) {
+        let (&byte, rest) = input.split_first().ok_or(DumpError::Binary)?;
+        *input = rest;
+        value |= usize::from(byte & 0x7f)
+            .checked_shl(shift)
+            .ok_or(DumpError::Binary)?;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(DumpError::Binary)
+}
+
+fn read_str(input: &mut &[u8]) -> Result<String, DumpError> {
+    let len = read_varint(input)?;
+    if input.len() < len {
+        return Err(DumpError::Binary);
+    }
+    let (bytes, rest) = input.split_at(len);
+    *input = rest;
+    String::from_utf8(bytes.to_vec()).map_err(|_| DumpError::Binary)
+}
+
+/// Undo `{:?}` quoting of a string.
+fn unquote(quoted: &str) -> Option<String> {
+    let inner = quoted.strip_prefix('"')?.strip_suffix('"')?;
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == '"' {
+            return None;
+        }
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        out.push(match chars.next()? {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '0' => '\0',
+            '\\' => '\\',
+            '"' => '"',
+            '\'' => '\'',
+            'u' => {
+                let rest = chars.as_str().strip_prefix('{')?;
+                let (hex, rest) = rest.split_once('}')?;
+                chars = rest.chars();
+                char::from_u32(u32::from_str_radix(hex, 16).ok()?)?
+            }
+            _ => return None,
+        });
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> TokenDump {
+        let mut dump = TokenDump::new();
+        dump.push(0, 3, "KwLet", "let");
+        dump.push(3, 5, "Space", " \n");
+        dump.push(5, 10, "Str", "\"é\\\"");
+        dump.push(11, 12, "Ctrl", "\u{7}");
+        dump
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let dump = sample();
+        let text = dump.to_text();
+        assert!(text.starts_with("# synkit tokens\n0..3 KwLet \"let\"\n3..5 Space \" \\n\"\n"));
+        assert_eq!(TokenDump::from_text(&text).unwrap(), dump);
+        assert_eq!(TokenDump::parse(text.as_bytes()).unwrap(), dump);
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let dump = sample();
+        let bytes = dump.to_bytes();
+        assert_eq!(TokenDump::from_bytes(&bytes).unwrap(), dump);
+        assert_eq!(TokenDump::parse(&bytes).unwrap(), dump);
+        assert_eq!(
+            TokenDump::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(DumpError::Binary)
+        );
+    }
+
+    #[test]
+    fn test_text_errors() {
+        let err = TokenDump::from_text("# header\n\n0..3 Ident abc").unwrap_err();
+        assert_eq!(
+            err,
+            DumpError::Syntax {
+                line: 3,
+                message: "malformed quoted text".to_string()
+            }
+        );
+        assert!(TokenDump::from_text("0-3 Ident \"abc\"").is_err());
+        assert!(TokenDump::from_text("0..3 \"abc\"").is_err());
+    }
+
+    #[test]
+    fn test_source() {
+        let mut dump = TokenDump::new();
+        dump.push(2, 3, "A", "a");
+        dump.push(5, 7, "B", "bb");
+        assert_eq!(dump.source().unwrap(), "  a  bb");
+
+        dump.push(6, 7, "C", "c");
+        assert_eq!(dump.source(), Err(DumpError::Range { index: 2 }));
+    }
+}
@@ -74,6 +74,6 @@ pub use diagnostic::Diagnostic;
 pub use error::SpannedError;
 pub use parse::Parse;
 pub use peek::Peek;
-pub use printer::Printer;
+pub use printer::{CommentPlacement, CommentPolicy, Printer};
 pub use stream::{SpanLike, SpannedLike, TokenStream};
 pub use to_tokens::ToTokens;
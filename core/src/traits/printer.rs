@@ -1,4 +1,60 @@
+use super::stream::SpanLike;
 use super::to_tokens::ToTokens;
+use crate::{SourceMapping, TrailingPolicy};
+
+/// Where [`Printer::comment`] puts a comment that follows code on the same line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CommentPlacement {
+    /// Keep the comment at the end of the line, after a space.
+    #[default]
+    SameLine,
+    /// Move the comment onto its own line above the code.
+    Above,
+}
+
+/// How [`Printer::comment`] lays out comments.
+///
+/// # Example
+///
+/// ```ignore
+/// let policy = CommentPolicy::new()
+///     .with_placement(CommentPlacement::Above)
+///     .with_wrap_width(80);
+/// let mut printer = Printer::new().with_comment_policy(policy);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CommentPolicy {
+    /// Placement of comments that follow code.
+    pub placement: CommentPlacement,
+    /// Line width, in characters, that comments are wrapped to.
+    /// `None` leaves comment text on one line.
+    pub wrap_width: Option<usize>,
+}
+
+impl CommentPolicy {
+    /// Same-line comments, no wrapping.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            placement: CommentPlacement::SameLine,
+            wrap_width: None,
+        }
+    }
+
+    /// Set where comments following code are placed.
+    #[inline]
+    pub const fn with_placement(mut self, placement: CommentPlacement) -> Self {
+        self.placement = placement;
+        self
+    }
+
+    /// Wrap comments so lines stay within `width` characters.
+    #[inline]
+    pub const fn with_wrap_width(mut self, width: usize) -> Self {
+        self.wrap_width = Some(width);
+        self
+    }
+}
 
 /// Trait for building formatted text output.
 ///
@@ -26,6 +82,12 @@ use super::to_tokens::ToTokens;
 /// - `space()`, `spaces(n)`, `tab()`, `tabs(n)`: Whitespace
 /// - `newline()`: Newline with auto-indent
 ///
+/// Whitespace normalization (based on what was last written):
+/// - `last_char()`: Last character in the buffer
+/// - `ensure_space()`: Space unless at the start of a line or after whitespace
+/// - `no_space()`: Remove trailing spaces/tabs after code
+/// - `blank_lines(n)`: Exactly `n` blank lines before the next output
+///
 /// Indentation:
 /// - `indent()`, `dedent()`: Change indent level
 /// - `open_block(token)`, `close_block(token)`: Block delimiters
@@ -34,6 +96,16 @@ use super::to_tokens::ToTokens;
 /// - `write(value)`: Write a `ToTokens` value
 /// - `write_separated(items, sep, ...)`: Write items with separators
 ///
+/// Comments:
+/// - `comment(marker, text)`: Write a line comment per `comment_policy()`
+///
+/// Sequences:
+/// - `trailing_policy()`: Trailing separators when printing `Punctuated`
+///
+/// Source maps:
+/// - `source_mapping_mut()`: Access the mapping being recorded, if enabled
+/// - `mapped(span, f)`: Record the output written by `f` as coming from `span`
+///
 /// # Example
 ///
 /// ```ignore
@@ -120,6 +192,58 @@ pub trait Printer: Sized {
         self.add_indent();
     }
 
+    /// The last character written, if any.
+    fn last_char(&self) -> Option<char> {
+        self.buf().chars().next_back()
+    }
+
+    /// Append a space unless the buffer is empty or already ends with
+    /// whitespace (including indentation).
+    ///
+    /// Use between words that must stay separate, without risking double
+    /// spaces when the previous node already wrote one.
+    fn ensure_space(&mut self) {
+        if self.last_char().is_some_and(|c| !c.is_whitespace()) {
+            self.space();
+        }
+    }
+
+    /// Remove spaces and tabs written after the last non-whitespace
+    /// character of the current line.
+    ///
+    /// Indentation on an otherwise empty line is kept.
+    fn no_space(&mut self) {
+        let buf = self.buf();
+        let trimmed = buf.trim_end_matches([' ', '\t']).len();
+        let at_line_start = buf[..trimmed].is_empty() || buf[..trimmed].ends_with('\n');
+        if !at_line_start {
+            self.truncate(trimmed);
+        }
+    }
+
+    /// Shorten the buffer to `len` bytes, clamping recorded source mappings.
+    fn truncate(&mut self, len: usize) {
+        self.buf_mut().truncate(len);
+        if let Some(map) = self.source_mapping_mut() {
+            map.truncate_output(len);
+        }
+    }
+
+    /// End the current line and leave exactly `n` blank lines before the
+    /// next output, which starts at the current indentation.
+    ///
+    /// Trailing whitespace and blank lines already written are replaced.
+    /// At the start of the buffer nothing is written.
+    fn blank_lines(&mut self, n: usize) {
+        let trimmed = self.buf().trim_end().len();
+        self.truncate(trimmed);
+        if trimmed == 0 {
+            return;
+        }
+        self.buf_mut().extend(std::iter::repeat_n('\n', n + 1));
+        self.add_indent();
+    }
+
     /// Add indentation at the current level.
     fn add_indent(&mut self) {
         if self.use_tabs() {
@@ -177,6 +301,111 @@ pub trait Printer: Sized {
         self.token(close);
     }
 
+    /// Layout policy used by [`comment`](Self::comment).
+    ///
+    /// Default: same line, no wrapping.
+    fn comment_policy(&self) -> CommentPolicy {
+        CommentPolicy::default()
+    }
+
+    /// Trailing separators for printed `Punctuated` sequences:
+    /// [`Required`](TrailingPolicy::Required) adds one after the last item,
+    /// [`Forbidden`](TrailingPolicy::Forbidden) drops it and
+    /// [`Optional`](TrailingPolicy::Optional) prints it as parsed.
+    ///
+    /// `Terminated` and `Separated` keep their own policies.
+    ///
+    /// Default: `Optional`.
+    fn trailing_policy(&self) -> TrailingPolicy {
+        TrailingPolicy::Optional
+    }
+
+    /// Write the line comment `marker text`, e.g. `comment("#", "note")`,
+    /// laid out according to [`comment_policy`](Self::comment_policy).
+    ///
+    /// On an otherwise empty line the comment is written in place. After
+    /// code, [`CommentPlacement::SameLine`] appends it after a space and
+    /// [`CommentPlacement::Above`] inserts it on its own line above the code,
+    /// at the code's indentation. A same-line comment that would exceed the
+    /// wrap width is moved above as well.
+    ///
+    /// With a wrap width, the words of `text` are re-flowed over as many
+    /// `marker` lines as needed; words longer than the width aren't split.
+    /// The comment doesn't end the line: write a newline after it as usual.
+    fn comment(&mut self, marker: &str, text: &str) {
+        let policy = self.comment_policy();
+        let text = text.trim();
+        let tab_width = self.indent_width();
+
+        let buf = self.buf();
+        let line_start = buf.rfind('\n').map_or(0, |i| i + 1);
+        let line = &buf[line_start..];
+        let code_start = line_start + (line.len() - line.trim_start_matches([' ', '\t']).len());
+        let indent = buf[line_start..code_start].to_string();
+        let line_width = text_width(line, tab_width);
+        let has_code = code_start < buf.len();
+
+        if has_code && policy.placement == CommentPlacement::SameLine {
+            let single = comment_lines(marker, text, None);
+            let fits = policy.wrap_width.is_none_or(|width| {
+                single
+                    .first()
+                    .is_some_and(|c| line_width + 1 + c.chars().count() <= width)
+            });
+            if fits {
+                self.space();
+                single.iter().for_each(|c| self.word(c));
+                return;
+            }
+        }
+
+        let width = policy
+            .wrap_width
+            .map(|width| width.saturating_sub(text_width(&indent, tab_width)));
+        let lines = comment_lines(marker, text, width);
+        if has_code {
+            let block: String = lines
+                .iter()
+                .flat_map(|c| [c.as_str(), "\n", indent.as_str()])
+                .collect();
+            self.buf_mut().insert_str(code_start, &block);
+            if let Some(map) = self.source_mapping_mut() {
+                map.insert_output(code_start, block.len());
+            }
+        } else {
+            for (idx, c) in lines.iter().enumerate() {
+                if idx > 0 {
+                    self.newline();
+                }
+                self.word(c);
+            }
+        }
+    }
+
+    /// Get the source mapping being recorded, if any.
+    ///
+    /// Default: `None` (source mapping disabled). Printers that support
+    /// source maps override this to return their [`SourceMapping`].
+    fn source_mapping_mut(&mut self) -> Option<&mut SourceMapping> {
+        None
+    }
+
+    /// Run `f` and record the output it produced as originating from `span`.
+    ///
+    /// This is a no-op wrapper around `f` when source mapping is disabled.
+    fn mapped<S, F>(&mut self, span: &S, f: F)
+    where
+        S: SpanLike,
+        F: FnOnce(&mut Self),
+    {
+        let start = self.buf().len();
+        f(self);
+        let end = self.buf().len();
+        if let Some(map) = self.source_mapping_mut() {
+            map.push(start..end, span);
+        }
+    }
+
     /// Write a value implementing `ToTokens`.
     fn write<T: ToTokens<Printer = Self>>(&mut self, value: &T) {
         value.write(self);
@@ -233,3 +462,34 @@ pub trait Printer: Sized {
         }
     }
 }
+
+/// Display width of `s` in characters, counting tabs as `tab_width`.
+fn text_width(s: &str, tab_width: usize) -> usize {
+    s.chars()
+        .map(|c| if c == '\t' { tab_width } else { 1 })
+        .sum()
+}
+
+/// Lines of the comment `marker text`, re-flowed to `width` if given.
+fn comment_lines(marker: &str, text: &str, width: Option<usize>) -> Vec<String> {
+    let Some(width) = width else {
+        return vec![if text.is_empty() {
+            marker.to_string()
+        } else {
+            format!("{marker} {text}")
+        }];
+    };
+    let mut lines = Vec::new();
+    let mut current = marker.to_string();
+    for word in text.split_whitespace() {
+        if current.len() > marker.len()
+            && current.chars().count() + 1 + word.chars().count() > width
+        {
+            lines.push(std::mem::replace(&mut current, marker.to_string()));
+        }
+        current.push(' ');
+        current.push_str(word);
+    }
+    lines.push(current);
+    lines
+}
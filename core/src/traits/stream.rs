@@ -1,6 +1,6 @@
 use super::parse::Parse;
 use super::peek::Peek;
-use crate::Error;
+use crate::{Error, Provenance, SpanError};
 
 /// A span representing a source location range.
 ///
@@ -19,6 +19,43 @@ pub trait SpanLike: Clone {
     /// Returns a synthetic span for generated code.
     fn call_site() -> Self;
 
+    /// Creates a span over `start..end` in the same source as `self`.
+    ///
+    /// Span types that carry more than offsets, such as a file id, override
+    /// this to keep it. The default is [`new`](Self::new).
+    #[inline]
+    fn with_range(&self, start: usize, end: usize) -> Self {
+        Self::new(start, end)
+    }
+
+    /// Returns `true` for synthetic spans created by [`call_site`](Self::call_site).
+    ///
+    /// Generated printers don't record call-site spans in source maps, and
+    /// span shifting leaves them alone. The default assumes the span type has
+    /// no separate synthetic representation.
+    #[inline]
+    fn is_call_site(&self) -> bool {
+        false
+    }
+
+    /// Where the code under this span came from; `None` for call-site spans,
+    /// which have no location.
+    ///
+    /// The default reports every other span as user source. Span types that
+    /// record a [`Provenance`] override this.
+    #[inline]
+    fn provenance(&self) -> Option<Provenance> {
+        (!self.is_call_site()).then_some(Provenance::UserSource)
+    }
+
+    /// Returns `true` for call-site spans and spans marked
+    /// [`Provenance::Generated`]: code the user didn't write, which
+    /// diagnostics shouldn't report as a user error.
+    #[inline]
+    fn is_generated(&self) -> bool {
+        self.provenance() != Some(Provenance::UserSource)
+    }
+
     /// Returns the length of this span.
     ///
     /// # Clamping Behavior
@@ -43,10 +80,19 @@ pub trait SpanLike: Clone {
     /// Uses `min()` for start and `max()` for end positions. No overflow checking
     /// is performed since `min`/`max` operations cannot overflow. The result
     /// spans from the earliest start to the latest end, regardless of whether
-    /// the input spans are inverted or disjoint.
+    /// the input spans are inverted or disjoint. The result is built with
+    /// [`with_range`](Self::with_range) on `self`.
     #[inline]
     fn join(&self, other: &Self) -> Self {
-        Self::new(self.start().min(other.start()), self.end().max(other.end()))
+        self.with_range(self.start().min(other.start()), self.end().max(other.end()))
+    }
+
+    /// The text under this span in `source`, or why the span doesn't fit
+    /// it, instead of panicking like indexing would.
+    #[inline]
+    fn try_slice<'s>(&self, source: &'s str) -> Result<&'s str, SpanError> {
+        let range = SpanError::check(self.start(), self.end(), source)?;
+        Ok(source.get(range).unwrap_or_default())
     }
 }
 
@@ -0,0 +1,522 @@
+//! Schema-versioned AST serialization.
+//!
+//! ASTs persisted with plain serde break silently when the grammar changes:
+//! old data either fails with an unrelated error or, worse, deserializes
+//! into the wrong shape. [`Tagged`] writes a schema version next to the AST
+//! and checks it when reading, and [`Migrate`] upgrades data written by
+//! older versions.
+//!
+//! ```ignore
+//! use synkit::versioned::{self, Migrate, Tagged, Versioned};
+//!
+//! impl Versioned for Document {
+//!     const VERSION: u32 = 2;
+//! }
+//!
+//! impl Migrate for Document {
+//!     fn migrate<'de, D: Deserializer<'de>>(version: u32, de: D) -> Result<Self, D::Error> {
+//!         // `DocumentV1: Versioned` with `VERSION = 1`; chains further back
+//!         // through its own `Migrate` impl.
+//!         versioned::read::<DocumentV1, D>(version, de).map(Document::from)
+//!     }
+//! }
+//!
+//! let json = serde_json::to_string(&Tagged(&doc))?; // {"version":2,"ast":...}
+//! let Tagged(doc) = serde_json::from_str::<Tagged<Document>>(&json)?;
+//! ```
+//!
+//! [`WithoutSpans`] serializes an AST with its spans removed, for snapshots
+//! and structural diffs that shouldn't change when whitespace does.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{
+    self, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+    SerializeTupleStruct, SerializeTupleVariant, Serializer,
+};
+use serde::{Deserialize, Serialize};
+
+/// A type with a schema version, bumped whenever its serialized shape
+/// changes.
+pub trait Versioned {
+    /// Current schema version.
+    const VERSION: u32;
+}
+
+impl<T: Versioned + ?Sized> Versioned for &T {
+    const VERSION: u32 = T::VERSION;
+}
+
+impl<T: Versioned> Versioned for WithoutSpans<T> {
+    const VERSION: u32 = T::VERSION;
+}
+
+/// Reads data written by older schema versions.
+pub trait Migrate: Versioned + Sized {
+    /// Deserialize data written at `version`, which is older than
+    /// [`VERSION`](Versioned::VERSION), and upgrade it.
+    ///
+    /// The default rejects every older version.
+    fn migrate<'de, D: Deserializer<'de>>(version: u32, deserializer: D) -> Result<Self, D::Error> {
+        let _ = deserializer;
+        Err(de::Error::custom(format_args!(
+            "no migration from schema version {} to {}",
+            version,
+            Self::VERSION
+        )))
+    }
+}
+
+/// Deserialize a `T` written at `version`, migrating it if it's older than
+/// `T::VERSION`.
+///
+/// Call this from [`Migrate::migrate`] with the previous version's type to
+/// chain migrations.
+pub fn read<'de, T, D>(version: u32, deserializer: D) -> Result<T, D::Error>
+where
+    T: Migrate + Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    match version.cmp(&T::VERSION) {
+        std::cmp::Ordering::Equal => T::deserialize(deserializer),
+        std::cmp::Ordering::Less => T::migrate(version, deserializer),
+        std::cmp::Ordering::Greater => Err(de::Error::custom(format_args!(
+            "schema version {} is newer than {}",
+            version,
+            T::VERSION
+        ))),
+    }
+}
+
+/// An AST with its schema version, serialized as `{version, ast}`.
+///
+/// Serialize `Tagged(&ast)` to avoid moving the AST; deserialize
+/// `Tagged<T>` to check the version and run [`Migrate`] on older data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Tagged<T>(pub T);
+
+impl<T> Tagged<T> {
+    /// Unwrap the inner value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+const FIELDS: &[&str] = &["version", "ast"];
+
+impl<T: Versioned + Serialize> Serialize for Tagged<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Tagged", 2)?;
+        state.serialize_field("version", &T::VERSION)?;
+        state.serialize_field("ast", &self.0)?;
+        state.end()
+    }
+}
+
+impl<'de, T: Migrate + Deserialize<'de>> Deserialize<'de> for Tagged<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_struct("Tagged", FIELDS, TaggedVisitor(PhantomData))
+            .map(Tagged)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum Field {
+    Version,
+    Ast,
+}
+
+struct TaggedVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Migrate + Deserialize<'de>> Visitor<'de> for TaggedVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a versioned AST")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<T, A::Error> {
+        let version: u32 = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        seq.next_element_seed(AtVersion(version, PhantomData))?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<T, A::Error> {
+        let mut version = None;
+        let mut ast = None;
+        while let Some(key) = map.next_key()? {
+            match key {
+                Field::Version if version.is_some() => {
+                    return Err(de::Error::duplicate_field("version"));
+                }
+                Field::Version => version = Some(map.next_value()?),
+                Field::Ast if ast.is_some() => return Err(de::Error::duplicate_field("ast")),
+                Field::Ast => {
+                    // The version picks the type to read `ast` as, so it
+                    // has to come first.
+                    let version =
+                        version.ok_or_else(|| de::Error::custom("`version` must precede `ast`"))?;
+                    ast = Some(map.next_value_seed(AtVersion(version, PhantomData))?);
+                }
+            }
+        }
+        match (version, ast) {
+            (_, Some(ast)) => Ok(ast),
+            (None, None) => Err(de::Error::missing_field("version")),
+            (Some(_), None) => Err(de::Error::missing_field("ast")),
+        }
+    }
+}
+
+struct AtVersion<T>(u32, PhantomData<T>);
+
+impl<'de, T: Migrate + Deserialize<'de>> DeserializeSeed<'de> for AtVersion<T> {
+    type Value = T;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<T, D::Error> {
+        read(self.0, deserializer)
+    }
+}
+
+/// Serializes the wrapped value with its spans removed.
+///
+/// `Spanned` values serialize as just their `value`, and struct fields
+/// named `span` (as on [`Delimited`](crate::Delimited)) are left out. The
+/// output is for snapshots and comparison: it generally can't be read back
+/// into the original types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct WithoutSpans<T>(pub T);
+
+impl<T: Serialize> Serialize for WithoutSpans<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(StripSpans(serializer))
+    }
+}
+
+/// A value serialized through [`StripSpans`].
+struct Strip<'a, T: ?Sized>(&'a T);
+
+impl<T: Serialize + ?Sized> Serialize for Strip<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(StripSpans(serializer))
+    }
+}
+
+/// Serializer adapter behind [`WithoutSpans`].
+struct StripSpans<S>(S);
+
+/// `serialize_struct` for `Spanned`: forwards only `value`, unwrapped.
+enum StructState<S: Serializer> {
+    Fields(S::SerializeStruct),
+    Unwrap(Option<S>, Option<S::Ok>),
+}
+
+/// Compound serializer forwarding elements through [`Strip`].
+struct Compound<C>(C);
+
+impl<S: Serializer> Serializer for StripSpans<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = Compound<S::SerializeSeq>;
+    type SerializeTuple = Compound<S::SerializeTuple>;
+    type SerializeTupleStruct = Compound<S::SerializeTupleStruct>;
+    type SerializeTupleVariant = Compound<S::SerializeTupleVariant>;
+    type SerializeMap = Compound<S::SerializeMap>;
+    type SerializeStruct = StructState<S>;
+    type SerializeStructVariant = Compound<S::SerializeStructVariant>;
+
+    fn serialize_bool(self, v: bool) -> Result<S::Ok, S::Error> {
+        self.0.serialize_bool(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<S::Ok, S::Error> {
+        self.0.serialize_i8(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<S::Ok, S::Error> {
+        self.0.serialize_i16(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<S::Ok, S::Error> {
+        self.0.serialize_i32(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<S::Ok, S::Error> {
+        self.0.serialize_i64(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<S::Ok, S::Error> {
+        self.0.serialize_i128(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<S::Ok, S::Error> {
+        self.0.serialize_u8(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<S::Ok, S::Error> {
+        self.0.serialize_u16(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<S::Ok, S::Error> {
+        self.0.serialize_u32(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<S::Ok, S::Error> {
+        self.0.serialize_u64(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<S::Ok, S::Error> {
+        self.0.serialize_u128(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<S::Ok, S::Error> {
+        self.0.serialize_f32(v)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<S::Ok, S::Error> {
+        self.0.serialize_f64(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<S::Ok, S::Error> {
+        self.0.serialize_char(v)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<S::Ok, S::Error> {
+        self.0.serialize_str(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<S::Ok, S::Error> {
+        self.0.serialize_bytes(v)
+    }
+
+    fn serialize_none(self) -> Result<S::Ok, S::Error> {
+        self.0.serialize_none()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<S::Ok, S::Error> {
+        self.0.serialize_some(&Strip(value))
+    }
+
+    fn serialize_unit(self) -> Result<S::Ok, S::Error> {
+        self.0.serialize_unit()
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<S::Ok, S::Error> {
+        self.0.serialize_unit_struct(name)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<S::Ok, S::Error> {
+        self.0.serialize_unit_variant(name, variant_index, variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        self.0.serialize_newtype_struct(name, &Strip(value))
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error> {
+        self.0
+            .serialize_newtype_variant(name, variant_index, variant, &Strip(value))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, S::Error> {
+        self.0.serialize_seq(len).map(Compound)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, S::Error> {
+        self.0.serialize_tuple(len).map(Compound)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, S::Error> {
+        self.0.serialize_tuple_struct(name, len).map(Compound)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, S::Error> {
+        self.0
+            .serialize_tuple_variant(name, variant_index, variant, len)
+            .map(Compound)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, S::Error> {
+        self.0.serialize_map(len).map(Compound)
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, S::Error> {
+        if name == "Spanned" {
+            return Ok(StructState::Unwrap(Some(self.0), None));
+        }
+        self.0.serialize_struct(name, len).map(StructState::Fields)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, S::Error> {
+        self.0
+            .serialize_struct_variant(name, variant_index, variant, len)
+            .map(Compound)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.0.is_human_readable()
+    }
+}
+
+impl<S: Serializer> SerializeStruct for StructState<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), S::Error> {
+        match self {
+            StructState::Fields(_) if key == "span" => Ok(()),
+            StructState::Fields(state) => state.serialize_field(key, &Strip(value)),
+            StructState::Unwrap(serializer, ok) => {
+                if key == "value" {
+                    if let Some(serializer) = serializer.take() {
+                        *ok = Some(value.serialize(StripSpans(serializer))?);
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        match self {
+            StructState::Fields(state) => state.end(),
+            StructState::Unwrap(_, Some(ok)) => Ok(ok),
+            StructState::Unwrap(_, None) => Err(ser::Error::custom("`Spanned` without a `value`")),
+        }
+    }
+}
+
+impl<C: SerializeSeq> SerializeSeq for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.0.serialize_element(&Strip(value))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.0.end()
+    }
+}
+
+impl<C: SerializeTuple> SerializeTuple for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.0.serialize_element(&Strip(value))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.0.end()
+    }
+}
+
+impl<C: SerializeTupleStruct> SerializeTupleStruct for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.0.serialize_field(&Strip(value))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.0.end()
+    }
+}
+
+impl<C: SerializeTupleVariant> SerializeTupleVariant for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.0.serialize_field(&Strip(value))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.0.end()
+    }
+}
+
+impl<C: SerializeMap> SerializeMap for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), C::Error> {
+        self.0.serialize_key(&Strip(key))
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), C::Error> {
+        self.0.serialize_value(&Strip(value))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.0.end()
+    }
+}
+
+impl<C: SerializeStructVariant> SerializeStructVariant for Compound<C> {
+    type Ok = C::Ok;
+    type Error = C::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), C::Error> {
+        if key == "span" {
+            return Ok(());
+        }
+        self.0.serialize_field(key, &Strip(value))
+    }
+
+    fn end(self) -> Result<C::Ok, C::Error> {
+        self.0.end()
+    }
+}
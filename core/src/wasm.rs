@@ -0,0 +1,144 @@
+//! Parse-to-JSON helpers for WebAssembly playgrounds.
+//!
+//! JavaScript hosts usually want a single string back from a parser call.
+//! These helpers run a parser and encode either the serialized AST or the
+//! error as JSON, so a `wasm-bindgen` export is a one-liner:
+//!
+//! ```ignore
+//! #[wasm_bindgen]
+//! pub fn parse(input: &str) -> String {
+//!     synkit::wasm::parse_to_json(input, Document::parse_str)
+//! }
+//! ```
+//!
+//! Successful parses produce `{"ok":true,"value":...}`; failures produce
+//! `{"error":{"message":"...","span":{"end":3,"start":0}},"ok":false}`,
+//! where `span` is `null` when the error carries no location. Keys come out
+//! sorted, as `serde_json` orders object keys.
+
+use std::fmt::Display;
+
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::{SpanLike, SpannedError};
+
+/// Run `parse` on `input` and encode the result as JSON.
+///
+/// Errors are reported by their `Display` message with a `null` span.
+pub fn parse_to_json<T, E, F>(input: &str, parse: F) -> String
+where
+    T: Serialize,
+    E: Display,
+    F: FnOnce(&str) -> Result<T, E>,
+{
+    encode(parse(input).map_err(|e| error_value(&e, None)))
+}
+
+/// Like [`parse_to_json`], but includes the error's span when present.
+pub fn parse_to_json_spanned<T, E, F>(input: &str, parse: F) -> String
+where
+    T: Serialize,
+    E: Display + SpannedError,
+    F: FnOnce(&str) -> Result<T, E>,
+{
+    encode(parse(input).map_err(|e| {
+        let span = e.span().map(|s| (s.start(), s.end()));
+        error_value(&e, span)
+    }))
+}
+
+fn error_value(error: &impl Display, span: Option<(usize, usize)>) -> Value {
+    let span = match span {
+        Some((start, end)) => json!({ "start": start, "end": end }),
+        None => Value::Null,
+    };
+    json!({ "message": error.to_string(), "span": span })
+}
+
+fn encode<T: Serialize>(result: Result<T, Value>) -> String {
+    let value = match result.map(|v| serde_json::to_value(v)) {
+        Ok(Ok(value)) => json!({ "ok": true, "value": value }),
+        Ok(Err(e)) => json!({ "ok": false, "error": error_value(&e, None) }),
+        Err(error) => json!({ "ok": false, "error": error }),
+    };
+    value.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Span(usize, usize);
+
+    impl SpanLike for Span {
+        fn start(&self) -> usize {
+            self.0
+        }
+
+        fn end(&self) -> usize {
+            self.1
+        }
+
+        fn new(start: usize, end: usize) -> Self {
+            Self(start, end)
+        }
+
+        fn call_site() -> Self {
+            Self(0, 0)
+        }
+    }
+
+    #[derive(Debug)]
+    struct Error(Option<Span>);
+
+    impl Display for Error {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("expected number")
+        }
+    }
+
+    impl SpannedError for Error {
+        type Span = Span;
+
+        fn with_span(self, span: Span) -> Self {
+            Self(Some(span))
+        }
+
+        fn span(&self) -> Option<&Span> {
+            self.0.as_ref()
+        }
+    }
+
+    fn parse_number(input: &str) -> Result<i64, Error> {
+        input
+            .trim()
+            .parse()
+            .map_err(|_| Error(None).with_span(Span(0, input.len())))
+    }
+
+    #[test]
+    fn test_ok_value() {
+        let out = parse_to_json("42", parse_number);
+        assert_eq!(out, r#"{"ok":true,"value":42}"#);
+    }
+
+    #[test]
+    fn test_error_message() {
+        let out = parse_to_json("abc", parse_number);
+        assert_eq!(
+            out,
+            r#"{"error":{"message":"expected number","span":null},"ok":false}"#
+        );
+    }
+
+    #[test]
+    fn test_error_span() {
+        let out = parse_to_json_spanned("abc", parse_number);
+        assert_eq!(
+            out,
+            r#"{"error":{"message":"expected number","span":{"end":3,"start":0}},"ok":false}"#
+        );
+    }
+}
@@ -4,17 +4,18 @@
 
 use crate::Span;
 use std::collections::HashMap;
+use synkit::EstimateSize;
 
 // ANCHOR: ast_types
 /// A JSON value with its span information
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, EstimateSize)]
 pub struct JsonValue {
     pub kind: JsonValueKind,
     pub span: Span,
 }
 
 /// The kind of JSON value
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, EstimateSize)]
 pub enum JsonValueKind {
     /// `null`
     Null,
@@ -31,21 +32,21 @@ pub enum JsonValueKind {
 }
 
 /// A JSON object with ordered keys
-#[derive(Debug, Clone, PartialEq, Default)]
+#[derive(Debug, Clone, PartialEq, Default, EstimateSize)]
 pub struct JsonObject {
     /// Key-value pairs in insertion order
     pub entries: Vec<(String, JsonValue)>,
 }
 
 /// A single line in JSONL format
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, EstimateSize)]
 pub struct JsonLine {
     pub value: JsonValue,
     pub span: Span,
 }
 
 /// A JSONL document (sequence of JSON values)
-#[derive(Debug, Clone, PartialEq, Default)]
+#[derive(Debug, Clone, PartialEq, Default, EstimateSize)]
 pub struct JsonLines {
     pub lines: Vec<JsonLine>,
 }
@@ -164,30 +165,3 @@ impl IntoIterator for JsonLines {
         self.lines.into_iter()
     }
 }
-
-// Memory size estimation for monitoring
-impl JsonValue {
-    /// Estimate the memory size of this value in bytes (rough approximation)
-    pub fn estimated_size(&self) -> usize {
-        let base = std::mem::size_of::<Self>();
-        let content = match &self.kind {
-            JsonValueKind::Null => 0,
-            JsonValueKind::Bool(_) => 0,
-            JsonValueKind::Number(s) => s.capacity(),
-            JsonValueKind::String(s) => s.capacity(),
-            JsonValueKind::Array(arr) => {
-                arr.capacity() * std::mem::size_of::<JsonValue>()
-                    + arr.iter().map(|v| v.estimated_size()).sum::<usize>()
-            }
-            JsonValueKind::Object(obj) => {
-                obj.entries.capacity() * std::mem::size_of::<(String, JsonValue)>()
-                    + obj
-                        .entries
-                        .iter()
-                        .map(|(k, v)| k.capacity() + v.estimated_size())
-                        .sum::<usize>()
-            }
-        };
-        base + content
-    }
-}
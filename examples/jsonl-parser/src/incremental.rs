@@ -442,13 +442,6 @@ pub fn parse_buffered_lines(
     Ok(results)
 }
 
-impl AsRef<Token> for Spanned<Token> {
-    #[inline]
-    fn as_ref(&self) -> &Token {
-        &self.value
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -690,4 +683,40 @@ mod tests {
         let lines2 = parse_buffered_lines(&mut buffer).unwrap();
         assert_eq!(lines2.len(), 1);
     }
+
+    #[test]
+    fn test_iter_parse_spanned_tokens() {
+        let mut lexer = JsonIncrementalLexer::new();
+        let mut tokens = lexer.feed("{\"a\": 1}\n\n[2]\n").unwrap();
+        tokens.extend(lexer.finish().unwrap());
+
+        let lines: Vec<JsonLine> = synkit::iter_stream::IterParseStream::new(tokens)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(lines.len(), 2);
+        assert!(matches!(lines[1].value.kind, JsonValueKind::Array(_)));
+    }
+
+    #[tokio::test]
+    async fn test_ast_stream_spanned_tokens() {
+        let (token_tx, token_rx) = tokio::sync::mpsc::channel(32);
+        let (ast_tx, mut ast_rx) = tokio::sync::mpsc::channel(8);
+
+        let mut lexer = JsonIncrementalLexer::new();
+        for token in lexer.feed("{\"a\": 1}\n[2]\n").unwrap() {
+            token_tx.send(token).await.unwrap();
+        }
+        drop(token_tx);
+
+        crate::stream::AstStream::<JsonLine>::new(token_rx, ast_tx)
+            .run()
+            .await
+            .unwrap();
+
+        let mut count = 0;
+        while ast_rx.recv().await.is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 2);
+    }
 }
@@ -74,7 +74,7 @@ synkit::parser_kit! {
         #[token("\t", priority = 0)]
         Tab,
 
-        // Newline is significant for JSONL - it's our record delimiter
+        /// Record delimiter; newlines are significant in JSONL.
         #[regex(r"\r?\n")]
         #[fmt("newline")]
         #[no_to_tokens]
@@ -109,7 +109,8 @@ synkit::parser_kit! {
         #[token("false")]
         False,
 
-        // Strings (with escape handling)
+        /// String literal, without the surrounding quotes. Escapes are kept
+        /// as written.
         #[regex(r#""([^"\\]|\\.)*""#, |lex| {
             let s = lex.slice();
             // Remove surrounding quotes, keep escapes as-is for now
@@ -119,7 +120,7 @@ synkit::parser_kit! {
         #[no_to_tokens]
         String(String),
 
-        // Numbers (integers and floats)
+        /// Integer or float literal, kept as source text.
         #[regex(r"-?(?:0|[1-9]\d*)(?:\.\d+)?(?:[eE][+-]?\d+)?", |lex| lex.slice().to_string())]
         #[fmt("number")]
         Number(String),
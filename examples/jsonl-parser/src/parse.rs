@@ -104,8 +104,9 @@ fn parse_array(stream: &mut TokenStream) -> Result<Vec<JsonValue>, JsonError> {
     let mut values = Vec::new();
 
     // Check for empty array
-    if let Some(tok) = stream.peek_token()
-        && matches!(tok.value, Token::RBracket)
+    if stream
+        .peek_token()
+        .is_some_and(|tok| matches!(tok.value, Token::RBracket))
     {
         stream.next();
         return Ok(values);
@@ -159,8 +160,9 @@ fn parse_object(stream: &mut TokenStream) -> Result<JsonObject, JsonError> {
     let mut obj = JsonObject::new();
 
     // Check for empty object
-    if let Some(tok) = stream.peek_token()
-        && matches!(tok.value, Token::RBrace)
+    if stream
+        .peek_token()
+        .is_some_and(|tok| matches!(tok.value, Token::RBrace))
     {
         stream.next();
         return Ok(obj);
@@ -250,9 +252,7 @@ impl Parse for JsonLine {
         let span = stream.span_range(start..end);
 
         // Consume trailing newline if present
-        if let Some(tok) = peek_raw(stream)
-            && matches!(tok, Token::Newline)
-        {
+        if matches!(peek_raw(stream), Some(Token::Newline)) {
             stream.next_raw();
         }
 
@@ -14,14 +14,13 @@ use jsonl_parser::{
     tokens::Token,
 };
 use std::time::Instant;
+use synkit::EstimateSize;
 use synkit::async_stream::{IncrementalLexer, IncrementalParse, ParseCheckpoint};
 
 /// Configuration for stress tests
 struct StressConfig {
     /// Number of events to process
     event_count: usize,
-    /// Chunk size for incremental feeding
-    chunk_size: usize,
     /// How often to check memory (every N events)
     memory_check_interval: usize,
     /// Maximum allowed memory growth ratio
@@ -32,7 +31,6 @@ impl Default for StressConfig {
     fn default() -> Self {
         Self {
             event_count: 1_000_000,
-            chunk_size: 4096,
             memory_check_interval: 100_000,
             max_memory_growth: 2.0, // Allow up to 2x memory growth
         }
@@ -161,7 +159,7 @@ fn test_million_events_no_memory_leak() {
 #[test]
 fn test_varied_objects_stress() {
     // Test with varied JSON structures
-    let objects = vec![
+    let objects = [
         r#"{"type": "simple", "value": 1}"#,
         r#"{"type": "nested", "data": {"inner": true}}"#,
         r#"{"type": "array", "items": [1, 2, 3, 4, 5]}"#,
@@ -257,7 +255,7 @@ fn test_chunk_boundary_stress() {
 #[test]
 fn test_empty_and_whitespace_lines() {
     // Test handling of empty lines and whitespace
-    let inputs = vec!["{}\n", "{}\n\n", "{}\n  \n", "\n{}\n", "{}\n\n{}\n\n{}\n"];
+    let inputs = ["{}\n", "{}\n\n", "{}\n  \n", "\n{}\n", "{}\n\n{}\n\n{}\n"];
 
     for (idx, input) in inputs.iter().enumerate() {
         let mut lexer = JsonIncrementalLexer::new();
@@ -305,7 +303,7 @@ fn test_deeply_nested_objects() {
         for _ in 0..depth {
             s.push_str(r#"{"inner": "#);
         }
-        s.push_str("1");
+        s.push('1');
         for _ in 0..depth {
             s.push('}');
         }
@@ -222,13 +222,6 @@ pub enum IncrementalDocumentItem {
     },
 }
 
-impl AsRef<Token> for Spanned<Token> {
-    #[inline]
-    fn as_ref(&self) -> &Token {
-        &self.value
-    }
-}
-
 // ANCHOR: incremental_parse
 /// Implements `IncrementalParse` for `IncrementalDocumentItem`.
 ///
@@ -0,0 +1,54 @@
+//! Tests for `assertions: false`: kits whose tokens aren't `Send`.
+
+use std::rc::Rc;
+
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    // `Rc` tokens would fail the generated `Send` assertions
+    assertions: false,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[regex(r"[a-z]+", |lex| std::rc::Rc::<str>::from(lex.slice()))]
+        #[fmt("identifier")]
+        Ident(std::rc::Rc<str>),
+    },
+
+    delimiters: {},
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+#[test]
+fn test_non_send_tokens() {
+    let mut stream = TokenStream::lex("a bc").expect("lex failed");
+    let idents: Vec<_> = stream
+        .by_ref()
+        .map(|tok| match tok.value {
+            Token::Ident(name) => name,
+            Token::Space => unreachable!("skip token"),
+        })
+        .collect();
+    assert_eq!(idents, [Rc::from("a"), Rc::from("bc")]);
+}
@@ -4,8 +4,11 @@
 //! works correctly with both tokio and futures runtimes.
 
 use synkit::async_stream::{
-    IncrementalLexer, IncrementalParse, ParseCheckpoint, ParseState, StreamConfig, StreamError,
+    Either, Frame, Framer, Framing, IncrementalLexer, IncrementalParse, LexerCapacityHint,
+    NodeDiagnostic, NodeDiagnostics, ParseCheckpoint, ParseState, RecordStart, StreamConfig,
+    StreamError, replay,
 };
+use synkit::{Severity, SpanLike, SpannedLike};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum MockToken {
@@ -94,7 +97,7 @@ impl IncrementalLexer for MockLexer {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, synkit::EstimateSize)]
 pub struct Expr {
     pub value: i64,
 }
@@ -113,7 +116,7 @@ impl IncrementalParse for Expr {
         let mut cursor = checkpoint.cursor;
 
         if cursor >= tokens.len() {
-            return Ok((None, checkpoint.clone()));
+            return Ok((None, *checkpoint));
         }
 
         // Simple parser: expect a single number
@@ -131,7 +134,7 @@ impl IncrementalParse for Expr {
                 // Skip operators, look for next number
                 cursor += 1;
                 if cursor >= tokens.len() {
-                    return Ok((None, checkpoint.clone()));
+                    return Ok((None, *checkpoint));
                 }
                 match tokens[cursor].as_ref() {
                     MockToken::Number(n) => {
@@ -158,6 +161,92 @@ impl IncrementalParse for Expr {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ByteSpan(usize, usize);
+
+impl SpanLike for ByteSpan {
+    fn start(&self) -> usize {
+        self.0
+    }
+
+    fn end(&self) -> usize {
+        self.1
+    }
+
+    fn new(start: usize, end: usize) -> Self {
+        Self(start, end)
+    }
+
+    fn call_site() -> Self {
+        Self(usize::MAX, usize::MAX)
+    }
+
+    fn is_call_site(&self) -> bool {
+        self.0 == usize::MAX
+    }
+}
+
+/// A [`MockToken`] with its byte span in the input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedMock {
+    span: ByteSpan,
+    token: MockToken,
+}
+
+impl AsRef<MockToken> for SpannedMock {
+    fn as_ref(&self) -> &MockToken {
+        &self.token
+    }
+}
+
+impl SpannedLike<MockToken> for SpannedMock {
+    type Span = ByteSpan;
+
+    fn span(&self) -> &ByteSpan {
+        &self.span
+    }
+
+    fn value_ref(&self) -> &MockToken {
+        &self.token
+    }
+
+    fn value(self) -> MockToken {
+        self.token
+    }
+
+    fn new(start: usize, end: usize, token: MockToken) -> Self {
+        Self {
+            span: ByteSpan(start, end),
+            token,
+        }
+    }
+}
+
+/// `10 + 20`, spanned.
+fn spanned_sum() -> Vec<SpannedMock> {
+    vec![
+        SpannedMock::new(0, 2, MockToken::Number(10)),
+        SpannedMock::new(3, 4, MockToken::Plus),
+        SpannedMock::new(5, 7, MockToken::Number(20)),
+    ]
+}
+
+/// Warns about numbers over 100.
+impl NodeDiagnostics for Expr {
+    type Span = ByteSpan;
+
+    fn diagnostics(&self) -> Vec<NodeDiagnostic<ByteSpan>> {
+        if self.value > 100 {
+            vec![NodeDiagnostic::warning(
+                None,
+                format!("{} is large", self.value),
+            )]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
 #[test]
 fn test_parse_state_enum() {
     assert_eq!(ParseState::NeedMore, ParseState::NeedMore);
@@ -195,6 +284,48 @@ fn test_stream_config_default() {
     assert_eq!(config.token_buffer_size, 1024);
     assert_eq!(config.ast_buffer_size, 64);
     assert_eq!(config.max_chunk_size, 64 * 1024);
+    assert_eq!(config.yield_every, 4096);
+}
+
+#[test]
+fn test_capacity_hint_measure() {
+    // 5 tokens in 16 bytes: the padding makes the guess of 4 bytes per token wrong
+    let sample = "10  +  200  -  3";
+    let hint = LexerCapacityHint::measure::<MockLexer>(sample).unwrap();
+    assert_eq!(hint.buffer_capacity, 16);
+    assert_eq!(hint.tokens_per_chunk, 5);
+    assert_eq!(hint.bytes_per_token(), Some(3.2));
+
+    let config = StreamConfig::tuned_from_sample::<MockLexer>(sample).unwrap();
+    assert_eq!(config.token_buffer_size, 5);
+    assert_eq!(config.ast_buffer_size, 1);
+    assert_eq!(config.max_chunk_size, 32);
+
+    assert!(LexerCapacityHint::measure::<MockLexer>("1 @").is_err());
+    assert_eq!(
+        LexerCapacityHint::measure::<MockLexer>("")
+            .unwrap()
+            .bytes_per_token(),
+        None
+    );
+}
+
+#[test]
+fn test_node_diagnostic_json() {
+    let mut diag = NodeDiagnostic::error(Some(ByteSpan(3, 7)), "bad \"key\"");
+    diag.node = 2;
+    assert_eq!(
+        diag.to_json(),
+        r#"{"node":2,"severity":"error","start":3,"end":7,"message":"bad \"key\""}"#
+    );
+    assert_eq!(diag.to_string(), "node 2: error: bad \"key\"");
+
+    let diag = NodeDiagnostic::warning(Some(ByteSpan::call_site()), "generated");
+    assert_eq!(diag.severity, Severity::Warning);
+    assert_eq!(
+        diag.to_json(),
+        r#"{"node":0,"severity":"warning","message":"generated"}"#
+    );
 }
 
 #[test]
@@ -288,10 +419,282 @@ fn test_can_parse() {
     assert!(!Expr::can_parse(&tokens, &consumed_cp));
 }
 
+#[test]
+fn test_framer_delimiter() {
+    let mut framer = Framer::new(Framing::Delimiter(b"\r\n".to_vec()));
+    framer.push(b"1 + 2\r");
+    assert_eq!(framer.next_frame(), Ok(None));
+    framer.push(b"\n3\r\n\r\n4");
+    let frame = framer.next_frame().unwrap().unwrap();
+    assert_eq!((frame.offset, frame.as_str()), (0, Ok("1 + 2")));
+    let frame = framer.next_frame().unwrap().unwrap();
+    assert_eq!((frame.offset, frame.as_str()), (7, Ok("3")));
+    let frame = framer.next_frame().unwrap().unwrap();
+    assert_eq!((frame.offset, frame.data.len()), (10, 0));
+    assert_eq!(framer.next_frame(), Ok(None));
+    let last = framer.finish().unwrap().unwrap();
+    assert_eq!((last.offset, last.as_str()), (12, Ok("4")));
+    assert_eq!(framer.finish(), Ok(None));
+}
+
+#[test]
+fn test_framer_length_prefixed() {
+    let mut framer = Framer::new(Framing::LengthU32);
+    framer.push(&[0, 0, 0, 2, b'4', b'2', 0, 0]);
+    let frame = framer.next_frame().unwrap().unwrap();
+    assert_eq!((frame.offset, frame.as_str()), (4, Ok("42")));
+    assert_eq!(framer.next_frame(), Ok(None));
+    assert_eq!(framer.finish(), Err(StreamError::IncompleteInput));
+
+    // 300 as a varint is 0xac 0x02
+    let mut framer = Framer::new(Framing::Varint).with_max_frame_size(299);
+    framer.push(&[0xac]);
+    assert_eq!(framer.next_frame(), Ok(None));
+    framer.push(&[0x02]);
+    assert_eq!(
+        framer.next_frame(),
+        Err(StreamError::ChunkTooLarge {
+            size: 300,
+            max: 299
+        })
+    );
+
+    let mut framer = Framer::new(Framing::Varint);
+    framer.push(&[0x01, 0xff, 0x00, b'x']);
+    let frame = framer.next_frame().unwrap().unwrap();
+    assert_eq!(
+        frame,
+        Frame {
+            offset: 1,
+            data: vec![0xff]
+        }
+    );
+    assert!(matches!(frame.as_str(), Err(StreamError::LexError(_))));
+    let frame = framer.next_frame().unwrap().unwrap();
+    assert_eq!((frame.offset, frame.data.len()), (3, 0));
+    assert_eq!(framer.buffered(), 1);
+
+    let mut framer = Framer::new(Framing::Varint);
+    framer.push(&[0xff; 11]);
+    assert!(matches!(framer.next_frame(), Err(StreamError::LexError(_))));
+}
+
+#[test]
+fn test_replay() {
+    let source = "10 + 20\n- x";
+
+    let expr = replay::<MockLexer, Expr>(source, 3..7).unwrap();
+    assert_eq!(expr, Expr { value: 20 });
+    assert_eq!(
+        replay::<MockLexer, Expr>(source, 0..7),
+        Err(StreamError::ParseError(
+            "2 tokens left after the record".to_string()
+        ))
+    );
+    assert_eq!(
+        replay::<MockLexer, Expr>(source, 3..5),
+        Err(StreamError::IncompleteInput)
+    );
+    assert_eq!(
+        replay::<MockLexer, Expr>(source, 8..11),
+        Err(StreamError::LexError("unexpected character: x".to_string()))
+    );
+    assert_eq!(
+        replay::<MockLexer, Expr>(source, 8..20),
+        Err(StreamError::LexError(
+            "byte range 8..20 is not within the 11-byte source".to_string()
+        ))
+    );
+}
+
+mod field_boundary_tests {
+    use synkit::async_stream::{ChunkBoundary, FieldBoundary};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Csv {
+        Text,
+        Comma,
+        Quote,
+        Newline,
+    }
+
+    impl AsRef<Csv> for Csv {
+        fn as_ref(&self) -> &Csv {
+            self
+        }
+    }
+
+    impl ChunkBoundary for Csv {
+        type Token = Csv;
+
+        fn is_boundary_token(token: &Csv) -> bool {
+            *token == Csv::Newline
+        }
+
+        fn is_quote(token: &Csv) -> bool {
+            *token == Csv::Quote
+        }
+    }
+
+    impl FieldBoundary for Csv {
+        fn is_field_separator(token: &Csv) -> bool {
+            *token == Csv::Comma
+        }
+    }
+
+    /// `a,"b,<newline>""c""",` then a newline and `d`.
+    fn quoted() -> Vec<Csv> {
+        use Csv::*;
+        vec![
+            Text, Comma, Quote, Text, Comma, Newline, Quote, Quote, Text, Quote, Quote, Quote,
+            Comma, Newline, Text,
+        ]
+    }
+
+    #[test]
+    fn test_quoted_boundary_is_not_a_record_end() {
+        let tokens = quoted();
+        assert_eq!(Csv::find_boundary(&tokens, 0), Some(14));
+        assert_eq!(Csv::find_boundary(&tokens, 14), None);
+    }
+
+    #[test]
+    fn test_split_fields() {
+        let tokens = quoted();
+        assert_eq!(Csv::split_fields(&tokens[..14]), [0..1, 2..12, 13..13]);
+        assert_eq!(Csv::split_fields(&tokens), [0..1, 2..12, 13..13]);
+        // One field, even if empty
+        assert_eq!(Csv::split_fields(&tokens[14..]).pop(), Some(0..1));
+        assert_eq!(Csv::split_fields::<Csv>(&[]).pop(), Some(0..0));
+        assert_eq!(Csv::split_fields(&[Csv::Newline]).pop(), Some(0..0));
+    }
+}
+
+mod iter_stream_tests {
+    use super::*;
+    use synkit::iter_stream::IterParseStream;
+
+    fn lex(source: &str) -> Vec<MockToken> {
+        let mut lexer = MockLexer::new();
+        lexer.feed(source).expect("lex failed")
+    }
+
+    #[test]
+    fn test_iter_parse_stream_yields_nodes() {
+        let values: Vec<i64> = IterParseStream::<_, Expr>::new(lex("1 +2 -3 4"))
+            .map(|r| r.expect("parse failed").value)
+            .collect();
+        assert_eq!(values, [1, 2, 3, 4]);
+    }
+
+    /// `-` and a number, told apart from `Expr` by the `-`.
+    #[derive(Debug, Clone, PartialEq)]
+    struct Negated(i64);
+
+    impl IncrementalParse for Negated {
+        type Token = MockToken;
+        type Error = String;
+
+        fn parse_incremental<S>(
+            tokens: &[S],
+            checkpoint: &ParseCheckpoint,
+        ) -> Result<(Option<Self>, ParseCheckpoint), String>
+        where
+            S: AsRef<MockToken>,
+        {
+            let cursor = checkpoint.cursor;
+            match tokens.get(cursor + 1).map(AsRef::as_ref) {
+                Some(MockToken::Number(n)) => Ok((
+                    Some(Negated(-n)),
+                    ParseCheckpoint {
+                        cursor: cursor + 2,
+                        tokens_consumed: checkpoint.tokens_consumed + 2,
+                        state: 0,
+                    },
+                )),
+                Some(other) => Err(format!("expected number after `-`, found {other:?}")),
+                None => Ok((None, *checkpoint)),
+            }
+        }
+
+        fn can_parse<S>(tokens: &[S], checkpoint: &ParseCheckpoint) -> bool
+        where
+            S: AsRef<MockToken>,
+        {
+            tokens.len() > checkpoint.cursor + 1
+        }
+    }
+
+    impl RecordStart for Negated {
+        fn is_start(token: &MockToken) -> bool {
+            matches!(token, MockToken::Minus)
+        }
+    }
+
+    #[test]
+    fn test_either_dispatches_on_first_token() {
+        let records: Vec<_> = IterParseStream::<_, Either<Negated, Expr>>::new(lex("1 - 2 + 3 -4"))
+            .map(|r| r.expect("parse failed"))
+            .collect();
+        assert_eq!(
+            records,
+            [
+                Either::Right(Expr { value: 1 }),
+                Either::Left(Negated(-2)),
+                Either::Right(Expr { value: 3 }),
+                Either::Left(Negated(-4)),
+            ]
+        );
+
+        let mut stream = IterParseStream::<_, Either<Negated, Expr>>::new(lex("-"));
+        assert_eq!(stream.next(), Some(Err(StreamError::IncompleteInput)));
+    }
+
+    #[test]
+    fn test_iter_parse_stream_incomplete_input() {
+        let mut stream = IterParseStream::<_, Expr>::new(lex("1 +"));
+        assert_eq!(stream.next().expect("node").expect("ok").value, 1);
+        assert!(matches!(
+            stream.next(),
+            Some(Err(StreamError::IncompleteInput))
+        ));
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn test_iter_parse_stream_stops_after_error() {
+        let tokens = vec![MockToken::Number(1), MockToken::Eof, MockToken::Number(2)];
+        let mut stream = IterParseStream::<_, Expr>::new(tokens);
+        assert!(stream.next().expect("node").is_ok());
+        assert!(matches!(
+            stream.next(),
+            Some(Err(StreamError::ParseError(_)))
+        ));
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn test_iter_parse_stream_compacts_buffer() {
+        let tokens = (0..1000).map(MockToken::Number);
+        let mut stream = IterParseStream::<_, Expr>::with_capacity(tokens, 4);
+        let mut count = 0;
+        while let Some(node) = stream.next() {
+            assert_eq!(node.expect("parse failed").value, count);
+            assert_eq!(stream.buffered(), 0);
+            assert!(stream.checkpoint().cursor <= 1);
+            count += 1;
+        }
+        assert_eq!(count, 1000);
+    }
+}
+
 #[cfg(feature = "tokio")]
 mod tokio_tests {
     use super::*;
-    use synkit::async_stream::tokio_impl::AstStream;
+    use std::sync::{Arc, Mutex};
+    use synkit::ProgressGranularity;
+    use synkit::async_stream::Emitted;
+    use synkit::async_stream::tokio_impl::{AstStream, AsyncTokenStream};
     use tokio::sync::mpsc;
 
     #[tokio::test]
@@ -396,6 +799,144 @@ mod tokio_tests {
         assert_eq!(nodes[2].value, 3);
     }
 
+    #[tokio::test]
+    async fn test_ast_stream_memory_budget() {
+        use synkit::EstimateSize;
+        use synkit::async_stream::tokio_impl::MemoryBudget;
+
+        let (token_tx, token_rx) = mpsc::channel::<MockToken>(32);
+        let (ast_tx, mut ast_rx) = mpsc::channel::<Expr>(16);
+        let node_size = Expr { value: 0 }.estimated_size();
+        let budget = MemoryBudget::new(node_size * 2);
+
+        let parser_budget = budget.clone();
+        tokio::spawn(async move {
+            let mut parser = AstStream::<Expr, MockToken>::new(token_rx, ast_tx)
+                .with_memory_budget(parser_budget);
+            parser.run().await.unwrap();
+        });
+
+        for n in 1..=4 {
+            token_tx.send(MockToken::Number(n)).await.unwrap();
+        }
+        drop(token_tx);
+
+        let first = ast_rx.recv().await.unwrap();
+        let second = ast_rx.recv().await.unwrap();
+        assert_eq!(budget.in_use(), node_size * 2);
+        let blocked =
+            tokio::time::timeout(std::time::Duration::from_millis(50), ast_rx.recv()).await;
+        assert!(blocked.is_err(), "third node should wait for the budget");
+
+        budget.release_node(&first);
+        assert_eq!(ast_rx.recv().await.unwrap().value, 3);
+        budget.release_node(&second);
+        assert_eq!(ast_rx.recv().await.unwrap().value, 4);
+        assert!(ast_rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_ast_stream_diagnostics() {
+        let (token_tx, token_rx) = mpsc::channel::<MockToken>(32);
+        let (ast_tx, mut ast_rx) = mpsc::channel::<Expr>(16);
+        let (diag_tx, mut diag_rx) = mpsc::unbounded_channel();
+
+        tokio::spawn(async move {
+            let mut parser =
+                AstStream::<Expr, MockToken>::new(token_rx, ast_tx).with_diagnostics(diag_tx);
+            parser.run().await.unwrap();
+        });
+
+        for n in [1, 200, 3, 400] {
+            token_tx.send(MockToken::Number(n)).await.unwrap();
+        }
+        drop(token_tx);
+
+        let mut nodes = Vec::new();
+        while let Some(expr) = ast_rx.recv().await {
+            nodes.push(expr.value);
+        }
+        assert_eq!(nodes, [1, 200, 3, 400]);
+
+        let mut diagnostics = Vec::new();
+        while let Some(diag) = diag_rx.recv().await {
+            diagnostics.push((diag.node, diag.message));
+        }
+        assert_eq!(
+            diagnostics,
+            [
+                (1, "200 is large".to_string()),
+                (3, "400 is large".to_string())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ast_stream_emitted() {
+        let (token_tx, token_rx) = mpsc::channel::<SpannedMock>(32);
+        let (ast_tx, mut ast_rx) = mpsc::channel::<Emitted<Expr>>(16);
+
+        tokio::spawn(async move {
+            let mut parser = AstStream::<Expr, SpannedMock>::emitted(token_rx, ast_tx);
+            parser.run().await.unwrap();
+        });
+
+        for token in spanned_sum() {
+            token_tx.send(token).await.unwrap();
+        }
+        drop(token_tx);
+
+        let mut nodes = Vec::new();
+        while let Some(node) = ast_rx.recv().await {
+            nodes.push(node);
+        }
+        assert_eq!(
+            nodes,
+            [
+                Emitted {
+                    value: Expr { value: 10 },
+                    range: 0..2,
+                    index: 0,
+                },
+                Emitted {
+                    value: Expr { value: 20 },
+                    range: 3..7,
+                    index: 1,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_yield_budget_lets_other_tasks_run() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use synkit::async_stream::tokio_impl::YieldBudget;
+
+        let ran = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&ran);
+        tokio::spawn(async move { flag.store(true, Ordering::SeqCst) });
+
+        let mut budget = YieldBudget::new(3);
+        budget.tick(2).await;
+        assert!(!ran.load(Ordering::SeqCst));
+        budget.tick(1).await;
+        assert!(ran.load(Ordering::SeqCst));
+
+        let mut never = YieldBudget::new(0);
+        never.tick(usize::MAX).await;
+    }
+
+    #[tokio::test]
+    async fn test_memory_budget_admits_oversized_node() {
+        use synkit::async_stream::tokio_impl::MemoryBudget;
+
+        let budget = MemoryBudget::new(8);
+        budget.acquire(1000).await.unwrap();
+        assert_eq!(budget.in_use(), 8);
+        budget.release(1000);
+        assert_eq!(budget.in_use(), 0);
+    }
+
     #[tokio::test]
     async fn test_channel_closure_detected_by_parser() {
         let (token_tx, token_rx) = mpsc::channel::<MockToken>(32);
@@ -445,6 +986,233 @@ mod tokio_tests {
         send_handle.await.unwrap();
         assert_eq!(count, 5);
     }
+
+    #[tokio::test]
+    async fn test_token_stream_progress() {
+        let (token_tx, mut token_rx) = mpsc::channel::<MockToken>(32);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let mut lexer = AsyncTokenStream::<MockLexer>::new(token_tx).on_progress(
+            ProgressGranularity::every_bytes(8),
+            move |bytes, tokens| {
+                sink.lock().unwrap().push((bytes, tokens));
+            },
+        );
+        for chunk in ["1 + 2 ", "+ 3 ", "+ 44 ", "- 5"] {
+            lexer.feed(chunk).await.unwrap();
+        }
+        assert_eq!(lexer.bytes_consumed(), 18);
+        lexer.finish().await.unwrap();
+
+        let mut count = 0;
+        while token_rx.recv().await.is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 9);
+        assert_eq!(*seen.lock().unwrap(), [(10, 5), (18, 9)]);
+    }
+
+    #[tokio::test]
+    async fn test_async_token_stream_feed_frame() {
+        let (token_tx, mut token_rx) = mpsc::channel::<MockToken>(32);
+        let mut lexer = AsyncTokenStream::<MockLexer>::new(token_tx);
+
+        let mut framer = Framer::new(Framing::LengthU32);
+        framer.push(&[0, 0, 0, 3, b'1', b' ', b'+', 0, 0, 0, 1, b'2']);
+        while let Some(frame) = framer.next_frame().unwrap() {
+            lexer.feed_frame(&frame).await.unwrap();
+        }
+        assert_eq!(lexer.tokens_consumed(), 3);
+        lexer.finish().await.unwrap();
+
+        let mut tokens = Vec::new();
+        while let Some(token) = token_rx.recv().await {
+            tokens.push(token);
+        }
+        assert_eq!(
+            tokens,
+            [MockToken::Number(1), MockToken::Plus, MockToken::Number(2)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_token_stream_progress_async() {
+        let (token_tx, _token_rx) = mpsc::channel::<MockToken>(32);
+        let (progress_tx, mut progress_rx) = mpsc::channel(1);
+        let lexer_task = tokio::spawn(async move {
+            let mut lexer = AsyncTokenStream::<MockLexer>::new(token_tx).on_progress_async(
+                ProgressGranularity::every_tokens(2),
+                move |_, tokens| {
+                    let progress_tx = progress_tx.clone();
+                    async move {
+                        let _ = progress_tx.send(tokens).await;
+                    }
+                },
+            );
+            for chunk in ["1 + ", "2 ", "+ 3"] {
+                lexer.feed(chunk).await.unwrap();
+            }
+            lexer.finish().await.unwrap();
+        });
+
+        let mut reports = Vec::new();
+        while let Some(tokens) = progress_rx.recv().await {
+            reports.push(tokens);
+        }
+        lexer_task.await.unwrap();
+        assert_eq!(reports, [2, 5]);
+    }
+}
+
+#[cfg(feature = "tokio")]
+mod supervisor_tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use synkit::async_stream::supervisor::{RestartPolicy, Supervisor, SupervisorStatus, TaskKind};
+
+    async fn collect(
+        mut status: tokio::sync::mpsc::UnboundedReceiver<SupervisorStatus>,
+    ) -> Vec<SupervisorStatus> {
+        let mut events = Vec::new();
+        while let Some(event) = status.recv().await {
+            events.push(event);
+        }
+        events
+    }
+
+    #[test]
+    fn test_recoverable_errors() {
+        assert!(StreamError::ParseError("x".into()).is_recoverable());
+        assert!(StreamError::Timeout.is_recoverable());
+        assert!(!StreamError::ChannelClosed.is_recoverable());
+        assert!(!StreamError::IncompleteInput.is_recoverable());
+    }
+
+    #[tokio::test]
+    async fn test_restarts_until_success() {
+        let mut supervisor = Supervisor::new(RestartPolicy::on_error(3));
+        let status = supervisor.status();
+        supervisor.spawn_parser(|attempt| async move {
+            if attempt < 2 {
+                Err(StreamError::ParseError(format!("bad input {attempt}")))
+            } else {
+                Ok(())
+            }
+        });
+        supervisor.join().await.expect("parser should recover");
+
+        let events = collect(status).await;
+        let task = TaskKind::Parser;
+        assert_eq!(
+            events,
+            [
+                SupervisorStatus::Started { task, attempt: 0 },
+                SupervisorStatus::Restarting {
+                    task,
+                    attempt: 1,
+                    error: StreamError::ParseError("bad input 0".into()),
+                },
+                SupervisorStatus::Started { task, attempt: 1 },
+                SupervisorStatus::Restarting {
+                    task,
+                    attempt: 2,
+                    error: StreamError::ParseError("bad input 1".into()),
+                },
+                SupervisorStatus::Started { task, attempt: 2 },
+                SupervisorStatus::Finished { task },
+            ]
+        );
+        assert_eq!(events[2].to_string(), "parser started (restart 1)");
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_restarts() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let mut supervisor = Supervisor::new(RestartPolicy::on_error(2));
+        let status = supervisor.status();
+        let counter = Arc::clone(&runs);
+        supervisor.spawn_lexer(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async { Err(StreamError::LexError("bad byte".into())) }
+        });
+
+        let err = supervisor.join().await.unwrap_err();
+        assert_eq!(err, StreamError::LexError("bad byte".into()));
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        assert_eq!(
+            collect(status).await.last(),
+            Some(&SupervisorStatus::Failed {
+                task: TaskKind::Lexer,
+                error: StreamError::LexError("bad byte".into()),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unrecoverable_errors_are_not_restarted() {
+        let mut supervisor = Supervisor::new(RestartPolicy::on_error(5));
+        let status = supervisor.status();
+        supervisor.spawn_parser(|_| async { Err(StreamError::ChannelClosed) });
+        assert_eq!(supervisor.join().await, Err(StreamError::ChannelClosed));
+        assert_eq!(collect(status).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_with_restarted_parser() {
+        let (token_tx, token_rx) = tokio::sync::mpsc::channel::<MockToken>(32);
+        let token_rx = Arc::new(tokio::sync::Mutex::new(token_rx));
+        let parsed = Arc::new(AtomicUsize::new(0));
+
+        let mut supervisor = Supervisor::new(
+            RestartPolicy::on_error(1).with_backoff(std::time::Duration::from_millis(1)),
+        );
+        let status = supervisor.status();
+
+        supervisor.spawn_lexer(move |_| {
+            let token_tx = token_tx.clone();
+            async move {
+                let mut lexer = MockLexer::new();
+                let tokens = lexer
+                    .feed("1 2 3")
+                    .map_err(|e| StreamError::LexError(e.to_string()))?;
+                for token in tokens {
+                    token_tx
+                        .send(token)
+                        .await
+                        .map_err(|_| StreamError::ChannelClosed)?;
+                }
+                Ok(())
+            }
+        });
+
+        let count = Arc::clone(&parsed);
+        supervisor.spawn_parser(move |attempt| {
+            let token_rx = Arc::clone(&token_rx);
+            let count = Arc::clone(&count);
+            async move {
+                let mut rx = token_rx.lock().await;
+                while let Some(token) = rx.recv().await {
+                    if attempt == 0 && token == MockToken::Number(2) {
+                        return Err(StreamError::ParseError("unexpected 2".into()));
+                    }
+                    count.fetch_add(1, Ordering::SeqCst);
+                }
+                Ok(())
+            }
+        });
+
+        supervisor.join().await.expect("pipeline should finish");
+        assert_eq!(parsed.load(Ordering::SeqCst), 2);
+        let events = collect(status).await;
+        assert_eq!(
+            events
+                .iter()
+                .filter(|e| matches!(e, SupervisorStatus::Finished { .. }))
+                .count(),
+            2
+        );
+    }
 }
 
 #[cfg(feature = "futures")]
@@ -452,22 +1220,24 @@ mod futures_tests {
     use super::*;
     use futures_core::Stream;
     use std::pin::Pin;
-    use std::task::{Context, Poll};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::task::{Context, Poll, Wake, Waker};
     use synkit::async_stream::futures_impl::ParseStream;
 
-    struct TokenIter {
-        tokens: Vec<MockToken>,
+    struct TokenIter<Tok = MockToken> {
+        tokens: Vec<Tok>,
         index: usize,
     }
 
-    impl TokenIter {
-        fn new(tokens: Vec<MockToken>) -> Self {
+    impl<Tok> TokenIter<Tok> {
+        fn new(tokens: Vec<Tok>) -> Self {
             Self { tokens, index: 0 }
         }
     }
 
-    impl Stream for TokenIter {
-        type Item = MockToken;
+    impl<Tok: Clone + Unpin> Stream for TokenIter<Tok> {
+        type Item = Tok;
 
         fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
             if self.index < self.tokens.len() {
@@ -486,6 +1256,54 @@ mod futures_tests {
         let token_stream = TokenIter::new(tokens);
         let _parse_stream: ParseStream<_, Expr, _> = ParseStream::new(token_stream);
     }
+
+    #[test]
+    fn test_parse_stream_emitted() {
+        let token_stream = TokenIter::new(spanned_sum());
+        let mut stream = ParseStream::<_, Expr, _>::new(token_stream).emitted();
+        let waker = Waker::noop();
+        let mut cx = Context::from_waker(waker);
+
+        let mut nodes = Vec::new();
+        while let Poll::Ready(Some(node)) = Pin::new(&mut stream).poll_next(&mut cx) {
+            let node = node.unwrap();
+            nodes.push((node.value.value, node.range, node.index));
+        }
+        assert_eq!(nodes, [(10, 0..2, 0), (20, 3..7, 1)]);
+    }
+
+    struct CountingWaker(AtomicUsize);
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.wake_by_ref();
+        }
+
+        fn wake_by_ref(self: &Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_parse_stream_max_tokens_per_poll() {
+        let token_stream = TokenIter::new(spanned_sum());
+        let mut stream = ParseStream::<_, Expr, _>::new(token_stream).with_max_tokens_per_poll(1);
+        let wakes = Arc::new(CountingWaker(AtomicUsize::new(0)));
+        let waker = Waker::from(Arc::clone(&wakes));
+        let mut cx = Context::from_waker(&waker);
+
+        let mut polls = Vec::new();
+        loop {
+            match Pin::new(&mut stream).poll_next(&mut cx) {
+                Poll::Ready(Some(node)) => polls.push(Some(node.unwrap().value)),
+                Poll::Ready(None) => break,
+                Poll::Pending => polls.push(None),
+            }
+        }
+        // `+ 20` spans two polls, the first ending after `+`
+        assert_eq!(polls, [Some(10), None, Some(20)]);
+        assert_eq!(wakes.0.load(Ordering::SeqCst), 1);
+    }
 }
 
 #[cfg(all(feature = "tokio", feature = "futures"))]
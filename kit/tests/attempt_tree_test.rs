@@ -0,0 +1,172 @@
+//! Tests for recording speculative parses with `TokenStream::attempt`.
+
+use synkit::{AttemptOutcome, TokenStream as _};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token("(")]
+        LParen,
+
+        #[token(")")]
+        RParen,
+
+        #[token(",")]
+        Comma,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+    },
+
+    delimiters: {},
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+/// `name` or `name(arg, arg)`.
+#[derive(Debug)]
+enum Expr {
+    Call(Call),
+    Name(Spanned<tokens::IdentToken>),
+}
+
+#[derive(Debug)]
+struct Call {
+    name: Spanned<tokens::IdentToken>,
+    args: Vec<Spanned<Expr>>,
+}
+
+impl Parse for Expr {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        if let Ok(call) = stream.attempt("call", Call::parse) {
+            return Ok(Self::Call(call));
+        }
+        Ok(Self::Name(stream.attempt_parse::<tokens::IdentToken>()?))
+    }
+}
+
+impl Parse for Call {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        let name = stream.parse()?;
+        stream.parse::<tokens::LParenToken>()?;
+        let mut args = Vec::new();
+        while !stream.peek::<tokens::RParenToken>() {
+            args.push(stream.parse()?);
+            if !stream.peek::<tokens::CommaToken>() {
+                break;
+            }
+            stream.parse::<tokens::CommaToken>()?;
+        }
+        stream.parse::<tokens::RParenToken>()?;
+        Ok(Self { name, args })
+    }
+}
+
+#[test]
+fn test_attempts_form_a_tree() {
+    let mut stream = TokenStream::lex("f(a)")
+        .expect("lex failed")
+        .with_attempt_tree();
+    let expr = stream.parse::<Expr>().expect("parse failed");
+    let Expr::Call(call) = expr.value else {
+        panic!("expected a call, got {:?}", expr.value);
+    };
+    assert_eq!(call.name.value.as_str(), "f");
+    assert_eq!(call.args.len(), 1);
+
+    let tree = stream.attempt_tree().expect("tree attached");
+    assert_eq!(
+        tree.to_string(),
+        "call @ 0: accepted after 4 tokens\n  \
+         call @ 2: rejected after 2 tokens: expected (, found ) after `a`\n  \
+         IdentToken @ 2: accepted after 1 tokens\n"
+    );
+    let attempts = tree.attempts();
+    assert_eq!(tree.roots().len(), 1);
+    assert_eq!(attempts[0].children.len(), 2);
+}
+
+#[test]
+fn test_conflicts() {
+    let mut stream = TokenStream::lex("f(a)")
+        .expect("lex failed")
+        .with_attempt_tree();
+    stream.parse::<Expr>().expect("parse failed");
+
+    let conflicts = stream.attempt_tree().expect("tree attached").conflicts();
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(
+        conflicts[0].to_string(),
+        "call @ 2 needs 2 tokens of lookahead (chose `IdentToken`)"
+    );
+}
+
+#[test]
+fn test_rejected_attempts_leave_stream_in_place() {
+    let mut stream = TokenStream::lex("f g")
+        .expect("lex failed")
+        .with_attempt_tree();
+    let err = stream.attempt("call", Call::parse).unwrap_err();
+    assert!(matches!(err, LexError::Expected { expect: "(", .. }));
+    assert_eq!(stream.cursor(), 0);
+
+    let name = stream.attempt_parse::<tokens::IdentToken>().expect("ident");
+    assert_eq!(name.value.as_str(), "f");
+    assert!(stream.peek::<tokens::IdentToken>());
+
+    let attempts = stream.attempt_tree().expect("tree attached").attempts();
+    assert!(matches!(
+        attempts[0].outcome,
+        AttemptOutcome::Rejected { tokens: 2, .. }
+    ));
+}
+
+#[test]
+fn test_without_tree() {
+    let mut stream = TokenStream::lex("f(a, b(c))").expect("lex failed");
+    let expr = stream.parse::<Expr>().expect("parse failed");
+    assert!(matches!(expr.value, Expr::Call(ref call) if call.args.len() == 2));
+    assert!(stream.is_empty());
+    assert!(stream.attempt_tree().is_none());
+}
+
+#[test]
+fn test_reports() {
+    let mut stream = TokenStream::lex("x")
+        .expect("lex failed")
+        .with_attempt_tree();
+    let expr = stream.parse::<Expr>().expect("parse failed");
+    assert!(matches!(expr.value, Expr::Name(ref name) if name.value.as_str() == "x"));
+    let tree = stream.attempt_tree().expect("tree attached");
+
+    let json = tree.to_json();
+    assert!(json.starts_with(r#"[{"label":"call","offset":0,"outcome":"rejected","tokens":1,"error":"expected (, found EOF""#), "{json}");
+    assert!(json.contains(
+        r#"{"label":"IdentToken","offset":0,"outcome":"accepted","tokens":1,"children":[]}"#
+    ));
+
+    let html = tree.to_html();
+    assert!(html.contains("<details class=\"rejected\"><summary>call @ 0"));
+}
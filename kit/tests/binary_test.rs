@@ -0,0 +1,188 @@
+//! Tests for binary AST encoding with `packed_spans`.
+#![cfg(feature = "binary")]
+
+use serde::{Deserialize, Serialize};
+use synkit::Delimited;
+use synkit::binary::{self, PackedSpan};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    packed_spans: true,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+    },
+
+    delimiters: {},
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug, serde::Serialize, serde::Deserialize],
+    // Dropped from `Span` and `Spanned`, which `packed_spans` implements.
+    custom_derives: [serde::Serialize, serde::Deserialize],
+}
+
+mod with_files {
+    use super::LexError;
+
+    synkit::parser_kit! {
+        error: LexError,
+
+        file_ids: true,
+        packed_spans: true,
+
+        tokens: {
+            #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+            Ident(String),
+        },
+
+        delimiters: {},
+
+        span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+        token_derives: [Clone, PartialEq, Debug, serde::Serialize, serde::Deserialize],
+    }
+}
+
+mod with_provenance {
+    use super::LexError;
+
+    synkit::parser_kit! {
+        error: LexError,
+
+        file_ids: true,
+        packed_spans: true,
+        provenance: true,
+
+        tokens: {
+            #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+            Ident(String),
+        },
+
+        delimiters: {},
+
+        span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+        token_derives: [Clone, PartialEq, Debug, serde::Serialize, serde::Deserialize],
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Words {
+    words: Vec<Spanned<tokens::IdentToken>>,
+}
+
+fn words(source: &str) -> Words {
+    let mut stream = TokenStream::lex(source).expect("lex failed");
+    let mut words = Vec::new();
+    while !stream.is_empty() {
+        words.push(stream.parse().expect("parse failed"));
+    }
+    Words { words }
+}
+
+#[test]
+fn test_round_trip() {
+    let ast = words("ab cd");
+    let bytes = binary::to_bytes(&ast).unwrap();
+    assert_eq!(binary::from_bytes::<Words>(&bytes).unwrap(), ast);
+
+    // Length, then per word: span tag, start, length, string length, text
+    assert_eq!(bytes, [2, 1, 0, 2, 2, b'a', b'b', 1, 3, 2, 2, b'c', b'd']);
+}
+
+#[test]
+fn test_call_site_spans() {
+    let ast: Delimited<Spanned<tokens::IdentToken>, Span> =
+        Delimited::call_site(Spanned::default());
+    let bytes = binary::to_bytes(&ast).unwrap();
+    let back: Delimited<Spanned<tokens::IdentToken>, Span> = binary::from_bytes(&bytes).unwrap();
+    assert_eq!(back.span, Span::CallSite);
+    assert_eq!(back.inner.span, Span::CallSite);
+}
+
+#[test]
+fn test_smaller_than_json() {
+    let source = "alpha beta gamma delta ".repeat(100);
+    let ast = words(&source);
+    let bytes = binary::to_bytes(&ast).unwrap();
+    let json = serde_json::to_string(&ast).unwrap();
+    assert!(
+        bytes.len() * 3 < json.len(),
+        "{} vs {}",
+        bytes.len(),
+        json.len()
+    );
+}
+
+#[test]
+fn test_json_spans() {
+    let json = serde_json::to_string(&words("ab")).unwrap();
+    assert_eq!(json, r#"{"words":[{"span":[0,2],"value":"ab"}]}"#);
+}
+
+#[test]
+fn test_file_ids() {
+    let file = synkit::FileId::from_index(3).unwrap();
+    let mut stream = with_files::TokenStream::lex_with_file("ab", file).expect("lex failed");
+    let word: with_files::Spanned<with_files::tokens::IdentToken> =
+        stream.parse().expect("parse failed");
+
+    let bytes = binary::to_bytes(&word).unwrap();
+    let back: with_files::Spanned<with_files::tokens::IdentToken> =
+        binary::from_bytes(&bytes).unwrap();
+    assert_eq!(back, word);
+    assert_eq!(back.span.file(), Some(file));
+}
+
+#[test]
+fn test_provenance() {
+    let tool = synkit::ToolId::try_from(7).unwrap();
+    let file = synkit::FileId::from_index(1).unwrap();
+    let span = with_provenance::Span::new(2, 6)
+        .with_file(file)
+        .generated_by(tool);
+
+    let bytes = binary::to_bytes(&span).unwrap();
+    // Packed span, then the file and provenance tags and ids
+    assert_eq!(bytes, [1, 2, 4, 1, 2, 1, 7]);
+    let back: with_provenance::Span = binary::from_bytes(&bytes).unwrap();
+    assert_eq!(back, span);
+}
+
+#[test]
+fn test_decode_errors() {
+    let bytes = binary::to_bytes(&words("ab")).unwrap();
+    assert!(matches!(
+        binary::from_bytes::<Words>(&bytes[..bytes.len() - 1]),
+        Err(binary::Error::Postcard(_))
+    ));
+
+    let mut longer = bytes.clone();
+    longer.push(0);
+    assert!(matches!(
+        binary::from_bytes::<Words>(&longer),
+        Err(binary::Error::TrailingBytes(1))
+    ));
+
+    assert_eq!(PackedSpan::pack(&Span::new(4, 9)), PackedSpan(Some((4, 5))));
+}
@@ -0,0 +1,106 @@
+//! Tests for `#[block_comment(open = "...", close = "...", nested)]`.
+
+use logos::Logos;
+use synkit::SpanLike;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    token_tests: true,
+
+    skip_tokens: [Space, Comment],
+
+    tokens: {
+        #[regex(r"[ \n]+", priority = 0)]
+        #[fmt("whitespace")]
+        Space,
+
+        #[block_comment(open = "/*", close = "*/", nested)]
+        #[fmt("comment")]
+        Comment,
+
+        #[block_comment(open = "{-", close = "-}")]
+        #[fmt("flat comment")]
+        FlatComment,
+
+        #[regex(r"[a-z]+")]
+        #[fmt("identifier")]
+        Ident,
+    },
+
+    delimiters: {},
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+fn lex(source: &str) -> Vec<(Result<Token, LexError>, &str)> {
+    let mut lexer = Token::lexer(source);
+    let mut out = Vec::new();
+    while let Some(token) = lexer.next() {
+        out.push((token, lexer.slice()));
+    }
+    out
+}
+
+#[test]
+fn test_nested_comment_spans_to_balancing_close() {
+    assert_eq!(
+        lex("/* a /* b */ c */d"),
+        vec![
+            (Ok(Token::Comment), "/* a /* b */ c */"),
+            (Ok(Token::Ident), "d"),
+        ]
+    );
+}
+
+#[test]
+fn test_unbalanced_nested_comment_is_an_error() {
+    assert_eq!(lex("/* /* */")[0], (Err(LexError::Unknown), "/*"));
+}
+
+#[test]
+fn test_flat_comment_stops_at_first_close() {
+    assert_eq!(
+        lex("{- a {- b -} c"),
+        vec![
+            (Ok(Token::FlatComment), "{- a {- b -}"),
+            (Ok(Token::Space), " "),
+            (Ok(Token::Ident), "c"),
+        ]
+    );
+}
+
+#[test]
+fn test_stream_spans_whole_comment() {
+    let stream = stream::TokenStream::lex("a /* x /* y */ */ b").unwrap();
+    stream.verify_coverage().unwrap();
+    let spans: Vec<_> = stream
+        .all()
+        .iter()
+        .filter(|t| t.value != Token::Space)
+        .map(|t| (t.value.clone(), t.span.start(), t.span.end()))
+        .collect();
+    assert_eq!(
+        spans,
+        vec![
+            (Token::Ident, 0, 1),
+            (Token::Comment, 2, 17),
+            (Token::Ident, 18, 19),
+        ]
+    );
+}
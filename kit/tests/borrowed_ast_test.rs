@@ -0,0 +1,185 @@
+//! Tests for AST nodes that borrow `&'src str` slices from the source, and
+//! the `BorrowedToken<'src>` lexer behind `borrowed: true`.
+
+use synkit::SpanLike;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    borrowed: true,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token("=")]
+        Eq,
+
+        #[token(",")]
+        Comma,
+
+        #[token("[")]
+        LBracket,
+
+        #[token("]")]
+        RBracket,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+
+        #[regex(r#""[^"]*""#, |lex| lex.slice().to_string())]
+        #[fmt("string")]
+        Str(String),
+    },
+
+    delimiters: {
+        Bracket => (LBracket, RBracket),
+    },
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+/// `key = "value"`, holding slices of the source.
+#[derive(Debug)]
+struct Pair<'src> {
+    key: Spanned<&'src str>,
+    eq: Spanned<tokens::EqToken>,
+    value: Spanned<&'src str>,
+}
+
+impl<'src> ParseBorrowed<'src> for Pair<'src> {
+    fn parse_borrowed(stream: &mut BorrowedStream<'src>) -> Result<Self, LexError> {
+        Ok(Self {
+            key: stream.text::<tokens::IdentToken>()?,
+            eq: stream.parse()?,
+            value: stream.text::<tokens::StrToken>()?,
+        })
+    }
+}
+
+/// `[pair, pair, ...]`
+struct Pairs<'src> {
+    items: Vec<Spanned<Pair<'src>>>,
+}
+
+impl<'src> ParseBorrowed<'src> for Pairs<'src> {
+    fn parse_borrowed(stream: &mut BorrowedStream<'src>) -> Result<Self, LexError> {
+        let (mut inner, _) =
+            stream.extract_inner::<tokens::LBracketToken, tokens::RBracketToken>()?;
+        let mut items = Vec::new();
+        while !inner.is_empty() {
+            items.push(inner.parse()?);
+            if inner.peek::<tokens::CommaToken>() {
+                inner.parse::<tokens::CommaToken>()?;
+            }
+        }
+        Ok(Self { items })
+    }
+}
+
+#[test]
+fn test_slices_point_into_source() {
+    let source = String::from(r#"  name = "synkit""#);
+    let pair = Pair::parse_borrowed_str(&source).expect("parse failed");
+    assert_eq!(pair.key.value, "name");
+    assert_eq!(pair.value.value, r#""synkit""#);
+    assert_eq!((pair.key.span.start(), pair.key.span.end()), (2, 6));
+    assert!(
+        source
+            .as_bytes()
+            .as_ptr_range()
+            .contains(&pair.key.value.as_ptr())
+    );
+    assert_eq!(pair.eq.span.start(), 7);
+}
+
+#[test]
+fn test_spanned_and_nested() {
+    let source = r#"[a = "1", bc = "2"]"#;
+    let mut stream = BorrowedStream::lex(source).expect("lex failed");
+    let pairs = stream.parse::<Pairs>().expect("parse failed");
+    assert_eq!((pairs.span.start(), pairs.span.end()), (0, source.len()));
+
+    let keys: Vec<&str> = pairs.items.iter().map(|p| p.value.key.value).collect();
+    assert_eq!(keys, ["a", "bc"]);
+    assert_eq!(stream.slice(&pairs.items[1].span), r#"bc = "2""#);
+}
+
+#[test]
+fn test_tokens_borrow_from_source() {
+    let source = String::from(r#"key = "value""#);
+    let stream = BorrowedStream::lex(&source).expect("lex failed");
+    assert_eq!(stream.source().as_ptr(), source.as_ptr());
+
+    let texts: Vec<&str> = stream.all().iter().filter_map(|t| t.value.text()).collect();
+    assert_eq!(texts, ["key", r#""value""#]);
+    assert_eq!(texts[0].as_ptr(), source.as_ptr());
+    assert_eq!(
+        stream.all()[2].value,
+        BorrowedToken::Eq,
+        "unit tokens lex as in `Token`"
+    );
+    assert_eq!(stream.all()[4].value.to_string(), r#""value""#);
+}
+
+#[test]
+fn test_payload_tokens_parse_owned() {
+    let mut stream = BorrowedStream::lex("abc").expect("lex failed");
+    let ident = stream.parse::<tokens::IdentToken>().expect("parse failed");
+    assert_eq!(ident.value.as_str(), "abc");
+    assert!(stream.is_empty());
+}
+
+#[test]
+fn test_fork_and_advance() {
+    let mut stream = BorrowedStream::lex(r#"a = "1" b"#).expect("lex failed");
+    let mut fork = stream.fork();
+    fork.parse::<Pair>().expect("parse failed");
+    assert!(stream.peek::<tokens::IdentToken>());
+    assert_eq!(
+        stream.fork().text::<tokens::IdentToken>().expect("a").value,
+        "a"
+    );
+
+    stream.advance_to(&fork);
+    assert_eq!(stream.text::<tokens::IdentToken>().expect("b").value, "b");
+}
+
+#[test]
+fn test_errors() {
+    let err = Pair::parse_borrowed_str(r#"a = b"#).unwrap_err();
+    assert_eq!(
+        err,
+        LexError::Expected {
+            expect: "string",
+            found: "b after `=`".to_string(),
+        }
+    );
+
+    let err = Pair::parse_borrowed_str(r#"a = "1" x"#).unwrap_err();
+    assert!(matches!(
+        err,
+        LexError::Expected {
+            expect: "end of input",
+            ..
+        }
+    ));
+}
@@ -0,0 +1,197 @@
+//! Tests for `BoundaryParsed`, the `IncrementalParse` adapter over blocking
+//! `Parse` types.
+
+use std::sync::Arc;
+
+use synkit::SpannedLike;
+use synkit::async_stream::{
+    BoundaryParsed, ChunkBoundary, ChunkStream, IncrementalParse as _, ParseCheckpoint, StreamError,
+};
+use synkit::iter_stream::IterParseStream;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ")]
+        Space,
+
+        #[token("\n")]
+        Newline,
+
+        #[token("+")]
+        Plus,
+
+        #[regex(r"[0-9]+", |lex| lex.slice().parse::<i64>().ok())]
+        Number(i64),
+    },
+
+    delimiters: {},
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+/// One line: numbers joined by `+`.
+#[derive(Debug, Clone, PartialEq)]
+struct Sum(i64);
+
+impl synkit::Parse for Sum {
+    type Token = Token;
+    type Error = LexError;
+
+    fn parse<S>(stream: &mut S) -> Result<Self, LexError>
+    where
+        S: synkit::TokenStream<Token = Token>,
+    {
+        let mut total = 0;
+        loop {
+            match stream.next().map(SpannedLike::value) {
+                Some(Token::Number(n)) => total += n,
+                Some(found) => {
+                    return Err(LexError::Expected {
+                        expect: "number",
+                        found: format!("{found:?}"),
+                    });
+                }
+                None => return Err(LexError::Empty { expect: "number" }),
+            }
+            match stream.next().map(SpannedLike::value) {
+                Some(Token::Plus) => {}
+                Some(found) => {
+                    return Err(LexError::Expected {
+                        expect: "`+`",
+                        found: format!("{found:?}"),
+                    });
+                }
+                None => return Ok(Sum(total)),
+            }
+        }
+    }
+}
+
+/// Splits lines at newlines.
+struct Lines;
+
+impl ChunkBoundary for Lines {
+    type Token = Token;
+
+    fn is_boundary_token(token: &Token) -> bool {
+        matches!(token, Token::Newline)
+    }
+
+    fn is_ignorable(token: &Token) -> bool {
+        matches!(token, Token::Space)
+    }
+}
+
+impl ChunkStream for Lines {
+    type Stream = stream::TokenStream;
+
+    fn chunk_stream<S: AsRef<Token>>(tokens: &[S]) -> stream::TokenStream {
+        let tokens = tokens.iter().map(|tok| span::Spanned {
+            span: span::Span::call_site(),
+            value: tok.as_ref().clone(),
+        });
+        stream::TokenStream::from_tokens(Arc::from(""), Arc::new(tokens.collect()))
+    }
+}
+
+type Line = BoundaryParsed<Sum, Lines>;
+
+fn lex(source: &str) -> Vec<tokens::SpannedToken> {
+    stream::TokenStream::lex(source)
+        .expect("lex failed")
+        .all()
+        .to_vec()
+}
+
+fn sums(source: &str) -> Vec<Result<i64, StreamError>> {
+    IterParseStream::<_, Line>::new(lex(source))
+        .map(|line| line.map(|line| line.into_inner().0))
+        .collect()
+}
+
+#[test]
+fn test_parses_each_line() {
+    assert_eq!(sums("1 + 2\n3\n40 + 2 + 0\n"), [Ok(3), Ok(3), Ok(42)]);
+}
+
+#[test]
+fn test_skips_blank_lines() {
+    assert_eq!(sums("\n  \n1\n\n2\n\n\n"), [Ok(1), Ok(2)]);
+}
+
+#[test]
+fn test_waits_for_boundary() {
+    let tokens = lex("1 + 2\n3 +");
+    let checkpoint = ParseCheckpoint::default();
+    assert!(Line::can_parse(&tokens, &checkpoint));
+
+    let (line, next) = Line::parse_incremental(&tokens, &checkpoint).unwrap();
+    assert_eq!(line.map(Line::into_inner), Some(Sum(3)));
+    assert_eq!(next.cursor, 6);
+    assert!(!Line::can_parse(&tokens, &next));
+
+    let (line, after) = Line::parse_incremental(&tokens, &next).unwrap();
+    assert_eq!(line, None);
+    assert_eq!(after.cursor, next.cursor);
+}
+
+#[test]
+fn test_reports_parse_errors() {
+    assert_eq!(
+        sums("1 +\n"),
+        [Err(StreamError::ParseError(
+            "expected number, found EOF".to_string()
+        ))]
+    );
+}
+
+#[test]
+fn test_incomplete_last_line() {
+    assert_eq!(sums("1\n2"), [Ok(1), Err(StreamError::IncompleteInput)]);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_ast_stream_consumes_trailing_blank_lines() {
+    use synkit::async_stream::tokio_impl::AstStream;
+    use tokio::sync::mpsc;
+
+    let (token_tx, token_rx) = mpsc::channel(32);
+    let (ast_tx, mut ast_rx) = mpsc::channel(16);
+    let parser = tokio::spawn(async move {
+        AstStream::<Line, tokens::SpannedToken>::new(token_rx, ast_tx)
+            .run()
+            .await
+    });
+
+    for token in lex("1\n2\n\n \n") {
+        token_tx.send(token).await.unwrap();
+    }
+    drop(token_tx);
+
+    let mut lines = Vec::new();
+    while let Some(line) = ast_rx.recv().await {
+        lines.push(line.into_inner().0);
+    }
+    assert_eq!(lines, [1, 2]);
+    assert_eq!(parser.await.unwrap(), Ok(()));
+}
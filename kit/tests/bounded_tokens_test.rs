@@ -0,0 +1,139 @@
+//! Tests for `until = "..."` and `longest` on `#[token]`/`#[regex]`, and the
+//! `synkit::scan` helpers behind them.
+
+use logos::Logos;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    token_tests: true,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[regex(r"[ \n]+", priority = 0)]
+        #[fmt("whitespace")]
+        Space,
+
+        #[token("/*", until = "*/")]
+        #[fmt("block comment")]
+        BlockComment,
+
+        #[token("/*!", until = "*/", longest)]
+        #[fmt("doc comment")]
+        DocComment,
+
+        #[regex("//", |lex| lex.slice()[2..].trim().to_string(), until = "\n")]
+        #[fmt("line comment")]
+        #[sample("// note\n")]
+        LineComment(String),
+
+        #[regex(r"\(\*", |lex| {
+            let len = synkit::scan::nested(lex.remainder(), "(*", "*)");
+            lex.bump(len.unwrap_or(lex.remainder().len()));
+            len.is_some()
+        })]
+        #[fmt("nested comment")]
+        #[sample("(* (* *) *)")]
+        NestedComment,
+
+        #[regex(r"[a-z]+")]
+        #[fmt("identifier")]
+        Ident,
+    },
+
+    delimiters: {},
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+fn lex(source: &str) -> Vec<(Result<Token, LexError>, &str)> {
+    let mut lexer = Token::lexer(source);
+    let mut out = Vec::new();
+    while let Some(token) = lexer.next() {
+        out.push((token, lexer.slice()));
+    }
+    out
+}
+
+#[test]
+fn test_until_stops_at_first_terminator() {
+    assert_eq!(
+        lex("/* a */ b */"),
+        vec![
+            (Ok(Token::BlockComment), "/* a */"),
+            (Ok(Token::Space), " "),
+            (Ok(Token::Ident), "b"),
+            (Ok(Token::Space), " "),
+            (Err(LexError::Unknown), "*"),
+            (Err(LexError::Unknown), "/"),
+        ]
+    );
+}
+
+#[test]
+fn test_longest_runs_to_last_terminator() {
+    assert_eq!(
+        lex("/*! a */ b */ c"),
+        vec![
+            (Ok(Token::DocComment), "/*! a */ b */"),
+            (Ok(Token::Space), " "),
+            (Ok(Token::Ident), "c"),
+        ]
+    );
+}
+
+#[test]
+fn test_unterminated_without_callback_is_an_error() {
+    let tokens = lex("/* a");
+    assert_eq!(tokens[0], (Err(LexError::Unknown), "/*"));
+}
+
+#[test]
+fn test_unterminated_with_callback_runs_to_end() {
+    assert_eq!(
+        lex("// note"),
+        vec![(Ok(Token::LineComment("note".to_string())), "// note")]
+    );
+    assert_eq!(
+        lex("// a\nb"),
+        vec![
+            (Ok(Token::LineComment("a".to_string())), "// a\n"),
+            (Ok(Token::Ident), "b"),
+        ]
+    );
+}
+
+#[test]
+fn test_nested_helper() {
+    assert_eq!(
+        lex("(* a (* b *) c *) d"),
+        vec![
+            (Ok(Token::NestedComment), "(* a (* b *) c *)"),
+            (Ok(Token::Space), " "),
+            (Ok(Token::Ident), "d"),
+        ]
+    );
+    assert_eq!(lex("(* (* *)")[0].0, Err(LexError::Unknown));
+}
+
+#[test]
+fn test_stream_spans_cover_bounded_tokens() {
+    let stream = stream::TokenStream::lex("/* a */ b").unwrap();
+    stream.verify_coverage().unwrap();
+}
@@ -0,0 +1,207 @@
+//! Tests for fuel, timeout and no-progress budgets, and progress callbacks.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use synkit::{BudgetLimit, ParseConfig, ProgressGranularity};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+
+    #[error(transparent)]
+    Budget(#[from] synkit::Error),
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token(";")]
+        Semi,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+    },
+
+    delimiters: {},
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+/// Words up to a `;`, found by retrying from the start after every word:
+/// quadratic in the number of words.
+#[derive(Debug)]
+struct Backtracking(usize);
+
+impl Parse for Backtracking {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        use synkit::TokenStream as _;
+        let start = stream.cursor();
+        let mut words = 0;
+        loop {
+            stream.rewind(start);
+            for _ in 0..words {
+                let _: Spanned<tokens::IdentToken> = stream.parse()?;
+            }
+            if stream.peek::<tokens::SemiToken>() {
+                let _: Spanned<tokens::SemiToken> = stream.parse()?;
+                return Ok(Self(words));
+            }
+            words += 1;
+        }
+    }
+}
+
+fn source(words: usize) -> String {
+    let mut source = "w ".repeat(words);
+    source.push(';');
+    source
+}
+
+#[test]
+fn test_within_budget() {
+    let config = ParseConfig::new().with_max_fuel(1_000);
+    let mut stream = TokenStream::lex(&source(10))
+        .expect("lex failed")
+        .with_budget(&config);
+    let parsed: Spanned<Backtracking> = stream.parse().expect("parse failed");
+    assert_eq!(parsed.value.0, 10);
+    assert_eq!(stream.check_budget(), Ok(()));
+    assert!(stream.budget().expect("budget attached").consumed() > 10);
+}
+
+#[test]
+fn test_fuel_stops_backtracking() {
+    let config = ParseConfig::new().with_max_fuel(1_000);
+    let mut stream = TokenStream::lex(&source(1_000))
+        .expect("lex failed")
+        .with_budget(&config);
+    let exceeded = synkit::Error::BudgetExceeded {
+        consumed: 1_000,
+        limit: BudgetLimit::Fuel(1_000),
+    };
+    assert_eq!(
+        stream.parse::<Backtracking>().map(|_| ()),
+        Err(LexError::Budget(exceeded))
+    );
+    assert_eq!(stream.check_budget(), Err(exceeded));
+    assert_eq!(stream.budget_error(), Some(LexError::Budget(exceeded)));
+    assert!(stream.is_empty());
+}
+
+#[test]
+fn test_timeout() {
+    let config = ParseConfig::new().with_timeout(Duration::ZERO);
+    let mut stream = TokenStream::lex(&source(3))
+        .expect("lex failed")
+        .with_budget(&config);
+    std::thread::sleep(Duration::from_millis(1));
+    assert!(matches!(
+        stream.parse_value::<Backtracking>(),
+        Err(LexError::Budget(synkit::Error::BudgetExceeded {
+            limit: BudgetLimit::Timeout(_),
+            ..
+        }))
+    ));
+    assert!(matches!(
+        stream.check_budget(),
+        Err(synkit::Error::BudgetExceeded {
+            limit: BudgetLimit::Timeout(_),
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_no_budget_by_default() {
+    let mut stream = TokenStream::lex(&source(3)).expect("lex failed");
+    let _: Spanned<Backtracking> = stream.parse().expect("parse failed");
+    assert!(stream.budget().is_none());
+    assert_eq!(stream.check_budget(), Ok(()));
+    assert_eq!(stream.budget_error(), None);
+}
+
+/// An optional word that never consumes the `;` it stops at.
+#[derive(Debug)]
+struct MaybeWord;
+
+impl Parse for MaybeWord {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        if stream.peek::<tokens::IdentToken>() {
+            let _: Spanned<tokens::IdentToken> = stream.parse()?;
+        }
+        Ok(Self)
+    }
+}
+
+/// Buggy: loops until end of input, but `MaybeWord` can't get past `;`.
+#[derive(Debug)]
+struct Words;
+
+impl Parse for Words {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        while !stream.is_empty() {
+            let _: Spanned<MaybeWord> = stream.parse()?;
+        }
+        Ok(Self)
+    }
+}
+
+#[test]
+fn test_no_progress() {
+    let config = ParseConfig::new().with_max_repeats(16);
+    let mut stream = TokenStream::lex("a b ;")
+        .expect("lex failed")
+        .with_budget(&config);
+    let result = stream.parse::<Words>();
+    let Err(LexError::Budget(synkit::Error::NoProgress {
+        type_name,
+        start,
+        end,
+        count,
+    })) = result
+    else {
+        panic!("expected NoProgress, got {result:?}");
+    };
+    assert!(type_name.ends_with("MaybeWord"), "{type_name}");
+    assert_eq!((start, end, count), (4, 5, 17));
+    assert!(matches!(
+        stream.check_budget(),
+        Err(synkit::Error::NoProgress { count: 17, .. })
+    ));
+}
+
+#[test]
+fn test_progress() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = seen.clone();
+    let mut stream = TokenStream::lex(&source(6))
+        .expect("lex failed")
+        .on_progress(ProgressGranularity::every_bytes(4), move |bytes, tokens| {
+            sink.lock().expect("lock").push((bytes, tokens));
+        });
+    let parsed: Spanned<Backtracking> = stream.parse().expect("parse failed");
+    assert_eq!(parsed.value.0, 6);
+    // Rewinds never report a smaller position; the last token always reports
+    assert_eq!(
+        *seen.lock().expect("lock"),
+        [(4, 4), (8, 8), (12, 12), (13, 13)]
+    );
+}
@@ -0,0 +1,108 @@
+//! Tests for `#[cfg(...)]` on tokens, delimiters and grammar sections.
+//!
+//! `cfg(all())` is always enabled and `cfg(any())` always disabled, standing
+//! in for crate features.
+
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space, Tab],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[cfg(any())]
+        #[token("\t", priority = 0)]
+        Tab,
+
+        #[token("(")]
+        LParen,
+
+        #[token(")")]
+        RParen,
+
+        #[cfg(all())]
+        #[token("<")]
+        LAngle,
+
+        #[cfg(all())]
+        #[token(">")]
+        RAngle,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+    },
+
+    #[cfg(any())]
+    tokens: {
+        #[token("[")]
+        LBracket,
+
+        #[token("]")]
+        RBracket,
+
+        #[token("async")]
+        KwAsync,
+    },
+
+    delimiters: {
+        Paren => (LParen, RParen),
+        Angle => (LAngle, RAngle),
+        // Inherits the `cfg(any())` of its tokens
+        Bracket => (LBracket, RBracket),
+    },
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+fn values(source: &str) -> Result<Vec<Token>, LexError> {
+    let stream = TokenStream::lex(source)?;
+    Ok(stream.all().iter().map(|t| t.value.clone()).collect())
+}
+
+#[test]
+fn test_enabled_tokens_lex() {
+    assert_eq!(
+        values("<a>").expect("lex failed"),
+        vec![Token::LAngle, Token::Ident("a".to_string()), Token::RAngle]
+    );
+    assert_eq!(tokens::LAngleToken::fmt(), "<");
+    let _ = delimiters::Angle::call_site();
+}
+
+#[test]
+fn test_disabled_tokens_are_absent() {
+    // `async` lexes as an identifier, `\t` and `[` don't lex at all
+    assert_eq!(
+        values("async").expect("lex failed"),
+        vec![Token::Ident("async".to_string())]
+    );
+    assert!(values("\t").is_err());
+    assert!(values("[").is_err());
+}
+
+#[test]
+fn test_disabled_skip_token_ignored() {
+    let mut stream = TokenStream::lex("( a )").expect("lex failed");
+    let _: Spanned<tokens::LParenToken> = stream.parse().expect("lparen");
+    let ident: Spanned<tokens::IdentToken> = stream.parse().expect("ident");
+    assert_eq!(*ident.value, "a");
+}
@@ -0,0 +1,118 @@
+//! Tests for `container_impls`: `Parse`/`Peek` for std containers.
+
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    container_impls: true,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token(",")]
+        Comma,
+
+        #[token(";")]
+        Semi,
+
+        #[token("pub")]
+        Pub,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string(), priority = 1)]
+        #[fmt("identifier")]
+        Ident(String),
+    },
+
+    delimiters: {},
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+/// `[pub] a, b, c;`
+#[derive(Debug)]
+struct Decl {
+    vis: Option<Spanned<tokens::PubToken>>,
+    names: Vec<(tokens::IdentToken, Option<tokens::CommaToken>)>,
+    semi: Spanned<tokens::SemiToken>,
+}
+
+impl Parse for Decl {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        Ok(Self {
+            vis: Parse::parse(stream)?,
+            names: Parse::parse(stream)?,
+            semi: stream.parse()?,
+        })
+    }
+}
+
+impl ToTokens for Decl {
+    fn write(&self, p: &mut Printer) {
+        use synkit::Printer as _;
+        if let Some(vis) = &self.vis {
+            vis.write(p);
+            p.space();
+        }
+        for (name, comma) in &self.names {
+            name.write(p);
+            if comma.is_some() {
+                p.word(", ");
+            }
+        }
+        self.semi.write(p);
+    }
+}
+
+#[test]
+fn test_vec_of_pairs() {
+    let decl = Decl::parse_str("a,b , c;").expect("parse failed");
+    let names: Vec<_> = decl
+        .names
+        .iter()
+        .map(|(name, comma)| (name.as_str(), comma.is_some()))
+        .collect();
+    assert_eq!(names, [("a", true), ("b", true), ("c", false)]);
+    assert_eq!(decl.to_string_formatted(), "a, b, c;");
+}
+
+#[test]
+fn test_optional_spanned() {
+    let decl = Decl::parse_str("pub x;").expect("parse failed");
+    let vis = decl.vis.expect("missing `pub`");
+    assert_eq!(synkit::SpanLike::start(&vis.span), 0);
+    assert_eq!(synkit::SpanLike::start(&decl.semi.span), 5);
+
+    let decl = Decl::parse_str("x;").expect("parse failed");
+    assert!(decl.vis.is_none());
+}
+
+#[test]
+fn test_empty_vec() {
+    let decl = Decl::parse_str(";").expect("parse failed");
+    assert!(decl.names.is_empty());
+}
+
+#[test]
+fn test_peek_option() {
+    let stream = TokenStream::lex("pub").expect("lex failed");
+    assert!(stream.peek::<Option<tokens::PubToken>>());
+    assert!(!stream.peek::<Option<tokens::IdentToken>>());
+}
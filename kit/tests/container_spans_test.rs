@@ -0,0 +1,101 @@
+//! Tests for the overall spans of `Punctuated` and `Repeated` sequences.
+
+use synkit::{Punctuated, Repeated, RepeatedItem, SpanLike, Terminated};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token(",")]
+        Comma,
+
+        #[token(";")]
+        Semi,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+    },
+
+    delimiters: {},
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+type Names = Punctuated<Spanned<tokens::IdentToken>, Spanned<tokens::CommaToken>>;
+
+fn parse_names(source: &str) -> Names {
+    let mut stream = TokenStream::lex(source).expect("lex failed");
+    let mut names = Names::new();
+    while stream.peek::<tokens::IdentToken>() {
+        names.push_value(stream.parse().expect("parse name"));
+        if !stream.peek::<tokens::CommaToken>() {
+            break;
+        }
+        names.push_punct(stream.parse().expect("parse comma"));
+    }
+    names
+}
+
+fn range(span: Span) -> (usize, usize) {
+    (span.start(), span.end())
+}
+
+#[test]
+fn test_punctuated_span() {
+    let names = parse_names("  a, bc , d");
+    assert_eq!(names.span().map(range), Some((2, 11)));
+}
+
+#[test]
+fn test_punctuated_span_includes_trailing_punct() {
+    let names = parse_names("a, b,  ");
+    assert!(names.trailing_punct());
+    assert_eq!(names.span().map(range), Some((0, 5)));
+}
+
+#[test]
+fn test_empty_sequences_have_no_span() {
+    assert!(parse_names("").span().is_none());
+    assert!(Repeated::<(), (), Spanned<Token>>::empty().span().is_none());
+}
+
+#[test]
+fn test_terminated_span() {
+    let mut stmts = Terminated::<Spanned<tokens::IdentToken>, Spanned<tokens::SemiToken>>::new();
+    stmts.push_value(Spanned::new(1, 4, tokens::IdentToken::new("use")));
+    stmts.push_punct(Spanned::new(4, 5, tokens::SemiToken::new()));
+    assert_eq!(stmts.span().map(range), Some((1, 5)));
+}
+
+#[test]
+fn test_repeated_span() {
+    let mut stream = TokenStream::lex("a , b;").expect("lex failed");
+    let mut items = Repeated::<tokens::IdentToken, tokens::CommaToken, Spanned<Token>>::empty();
+    while let Some(value) = stream.next() {
+        let sep = stream.next();
+        items.push(RepeatedItem::new(value, sep));
+    }
+    assert_eq!(items.len(), 2);
+    assert_eq!(items.span().map(range), Some((0, 6)));
+}
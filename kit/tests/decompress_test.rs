@@ -0,0 +1,184 @@
+//! Tests for the gzip and zstd decompressing sources.
+#![cfg(all(feature = "gzip", feature = "zstd"))]
+
+use std::io::Write;
+
+use synkit::async_stream::StreamError;
+use synkit::decompress::{Compression, DecompressedChunks, Decompressor};
+
+const SOURCE: &str = "GET /café 200\nGET /naïve 404\nPOST /日本 201\n";
+
+fn gzip(text: &str) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(text.as_bytes()).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn zstd(text: &str) -> Vec<u8> {
+    zstd::encode_all(text.as_bytes(), 0).unwrap()
+}
+
+fn read_all(bytes: &[u8], compression: Compression, chunk_size: usize) -> Vec<String> {
+    DecompressedChunks::new(bytes, compression)
+        .unwrap()
+        .with_chunk_size(chunk_size)
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+fn push_bytewise(bytes: &[u8], compression: Compression) -> Result<String, StreamError> {
+    let mut inflate = Decompressor::new(compression)?;
+    let mut text = String::new();
+    for byte in bytes {
+        text.push_str(&inflate.push(std::slice::from_ref(byte))?);
+    }
+    text.push_str(&inflate.finish()?);
+    Ok(text)
+}
+
+#[test]
+fn test_compression_from_path() {
+    assert_eq!(
+        Compression::from_path("logs/app.log.gz"),
+        Ok(Compression::Gzip)
+    );
+    assert_eq!(Compression::from_path("dump.zst"), Ok(Compression::Zstd));
+    assert_eq!(Compression::from_path("app.log"), Ok(Compression::Plain));
+    assert_eq!(Compression::from_path("README"), Ok(Compression::Plain));
+}
+
+#[test]
+fn test_chunks_split_on_character_boundaries() {
+    for (bytes, compression) in [
+        (gzip(SOURCE), Compression::Gzip),
+        (zstd(SOURCE), Compression::Zstd),
+        (SOURCE.as_bytes().to_vec(), Compression::Plain),
+    ] {
+        let chunks = read_all(&bytes, compression, 3);
+        assert!(chunks.len() > 1, "{compression:?}");
+        assert!(chunks.iter().all(|chunk| chunk.len() <= 3 + 2));
+        assert_eq!(chunks.concat(), SOURCE, "{compression:?}");
+    }
+}
+
+#[test]
+fn test_concatenated_members() {
+    let mut bytes = gzip("first\n");
+    bytes.extend(gzip("second\n"));
+    assert_eq!(
+        read_all(&bytes, Compression::Gzip, 1024).concat(),
+        "first\nsecond\n"
+    );
+
+    let mut bytes = zstd("first\n");
+    bytes.extend(zstd("second\n"));
+    assert_eq!(
+        read_all(&bytes, Compression::Zstd, 1024).concat(),
+        "first\nsecond\n"
+    );
+}
+
+#[test]
+fn test_truncated_input_fails() {
+    for (bytes, compression) in [
+        (gzip(SOURCE), Compression::Gzip),
+        (zstd(SOURCE), Compression::Zstd),
+    ] {
+        let truncated = &bytes[..bytes.len() - 4];
+        let mut chunks = DecompressedChunks::new(truncated, compression).unwrap();
+        let last = chunks.by_ref().last().unwrap();
+        assert!(
+            matches!(last, Err(StreamError::LexError(_))),
+            "{compression:?}: {last:?}"
+        );
+        assert_eq!(chunks.next(), None);
+    }
+}
+
+#[test]
+fn test_invalid_utf8_fails() {
+    let bytes = [b'o', b'k', 0xff, b'\n'];
+    let result: Result<Vec<_>, _> = DecompressedChunks::new(&bytes[..], Compression::Plain)
+        .unwrap()
+        .collect();
+    assert_eq!(
+        result,
+        Err(StreamError::LexError(
+            "decompressed input is not UTF-8 at byte 2".to_string()
+        ))
+    );
+
+    // A character cut off at the end of the input
+    let bytes = "é".as_bytes();
+    let result: Result<Vec<_>, _> = DecompressedChunks::new(&bytes[..1], Compression::Plain)
+        .unwrap()
+        .collect();
+    assert!(matches!(result, Err(StreamError::LexError(_))));
+}
+
+#[test]
+fn test_open_detects_compression() {
+    let path =
+        std::env::temp_dir().join(format!("synkit-decompress-{}.log.gz", std::process::id()));
+    std::fs::write(&path, gzip(SOURCE)).unwrap();
+    let chunks: Result<Vec<_>, _> = DecompressedChunks::open(&path).unwrap().collect();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(chunks.unwrap().concat(), SOURCE);
+}
+
+#[test]
+fn test_decompressor_pushed_bytewise() {
+    assert_eq!(
+        push_bytewise(&gzip(SOURCE), Compression::Gzip),
+        Ok(SOURCE.to_string())
+    );
+    assert_eq!(
+        push_bytewise(&zstd(SOURCE), Compression::Zstd),
+        Ok(SOURCE.to_string())
+    );
+    assert_eq!(
+        push_bytewise(SOURCE.as_bytes(), Compression::Plain),
+        Ok(SOURCE.to_string())
+    );
+}
+
+#[test]
+fn test_decompressor_position() {
+    let bytes = gzip(SOURCE);
+    let (head, tail) = bytes.split_at(bytes.len() / 2);
+    let mut inflate = Decompressor::new(Compression::Gzip).unwrap();
+    let mut text = inflate.push(head).unwrap();
+    assert_eq!(inflate.position(), text.len());
+    text.push_str(&inflate.push(tail).unwrap());
+    assert_eq!(inflate.position(), SOURCE.len());
+    assert_eq!(text + &inflate.finish().unwrap(), SOURCE);
+}
+
+#[test]
+fn test_decompressor_concatenated_members() {
+    for (encode, compression) in [
+        (gzip as fn(&str) -> Vec<u8>, Compression::Gzip),
+        (zstd, Compression::Zstd),
+    ] {
+        let mut bytes = encode("first\n");
+        bytes.extend(encode("second\n"));
+        assert_eq!(
+            push_bytewise(&bytes, compression),
+            Ok("first\nsecond\n".to_string())
+        );
+    }
+}
+
+#[test]
+fn test_decompressor_truncated_input_fails() {
+    for (bytes, compression) in [
+        (gzip(SOURCE), Compression::Gzip),
+        (zstd(SOURCE), Compression::Zstd),
+    ] {
+        let result = push_bytewise(&bytes[..bytes.len() - 4], compression);
+        assert!(
+            matches!(result, Err(StreamError::LexError(_))),
+            "{compression:?}: {result:?}"
+        );
+    }
+}
@@ -0,0 +1,114 @@
+//! Tests for `delimiter_sugar`: `Maybe<Delim>` and `<Delim>List` types.
+
+use synkit::SpanLike;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    delimiter_sugar: true,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token("(")]
+        LParen,
+
+        #[token(")")]
+        RParen,
+
+        #[token("[")]
+        LBracket,
+
+        #[token("]")]
+        RBracket,
+
+        #[token(",")]
+        Comma,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+    },
+
+    delimiters: {
+        Paren => (LParen, RParen),
+        Bracket => (LBracket, RBracket),
+    },
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+use delimiters::{BracketList, MaybeParen};
+
+type Names = BracketList<tokens::IdentToken, tokens::CommaToken>;
+
+#[test]
+fn test_maybe_paren() {
+    let bare = MaybeParen::<tokens::IdentToken>::parse_str("a").expect("bare");
+    assert!(bare.delimiter().is_none());
+    assert_eq!(bare.value().value.as_str(), "a");
+
+    let wrapped = MaybeParen::<tokens::IdentToken>::parse_str("( a )").expect("wrapped");
+    let delim = wrapped.delimiter().expect("parenthesized");
+    assert_eq!((delim.span().start(), delim.span().end()), (0, 5));
+    assert_eq!(wrapped.value().span.start(), 2);
+    assert_eq!(wrapped.to_string_formatted(), "(a)");
+}
+
+#[test]
+fn test_maybe_paren_rejects_leftovers() {
+    let err = MaybeParen::<tokens::IdentToken>::parse_str("(a b)").unwrap_err();
+    assert_eq!(err.to_string(), "expected ), found b after `a`");
+}
+
+#[test]
+fn test_maybe_paren_peek() {
+    let stream = TokenStream::lex("(a)").expect("lex failed");
+    assert!(stream.peek::<MaybeParen<tokens::IdentToken>>());
+    let stream = TokenStream::lex(",").expect("lex failed");
+    assert!(!stream.peek::<MaybeParen<tokens::IdentToken>>());
+}
+
+#[test]
+fn test_bracket_list() {
+    let list = Names::parse_str("[a,b , c]").expect("parse failed");
+    let names: Vec<_> = list.items.iter().map(|n| n.value.as_str()).collect();
+    assert_eq!(names, ["a", "b", "c"]);
+    assert!(!list.items.trailing_punct());
+    assert_eq!(list.to_string_formatted(), "[a, b, c]");
+
+    let list = Names::parse_str("[a,]").expect("trailing comma");
+    assert!(list.items.trailing_punct());
+    assert_eq!(list.to_string_formatted(), "[a,]");
+
+    assert!(Names::parse_str("[]").expect("empty").items.is_empty());
+    assert!(Names::parse_str("[a b]").is_err());
+}
+
+#[test]
+fn test_nested_sugar() {
+    let list =
+        BracketList::<MaybeParen<tokens::IdentToken>, tokens::CommaToken>::parse_str("[a, (b)]")
+            .expect("parse failed");
+    assert!(list.items.first().expect("a").value.delimiter().is_none());
+    assert!(list.items.last().expect("b").value.delimiter().is_some());
+    assert_eq!(list.to_string_formatted(), "[a, (b)]");
+}
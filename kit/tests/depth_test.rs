@@ -0,0 +1,102 @@
+//! Tests for nesting depth tracking on the stream.
+
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token("[")]
+        LBracket,
+
+        #[token("]")]
+        RBracket,
+
+        #[token("(")]
+        LParen,
+
+        #[token(")")]
+        RParen,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+    },
+
+    delimiters: {
+        Bracket => (LBracket, RBracket),
+        Paren => (LParen, RParen),
+    },
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+#[test]
+fn test_delimiters_increase_depth() {
+    let mut root = TokenStream::lex("[( [a] )] b").expect("lex failed");
+    assert_eq!(root.depth(), 0);
+    assert_eq!(root.depth_context(), None);
+
+    let (mut one, _) = root.extract_bracket().expect("extract failed");
+    assert_eq!(one.depth_context().as_deref(), Some("inside `[`"));
+    let (mut two, _) = one.extract_paren().expect("extract failed");
+    let (three, _) = two.extract_bracket().expect("extract failed");
+
+    assert_eq!((one.depth(), two.depth(), three.depth()), (1, 2, 3));
+    assert_eq!(
+        three.depth_context().as_deref(),
+        Some("inside 3 levels of `[`")
+    );
+    assert_eq!(root.depth(), 0);
+    assert_eq!(root.max_depth_seen(), 3);
+}
+
+#[test]
+fn test_enter_nested_respects_limit() {
+    let mut stream = TokenStream::lex("a").expect("lex failed");
+    stream.enter_nested(2).expect("within limit");
+    stream.enter_nested(2).expect("within limit");
+    assert_eq!(
+        stream.enter_nested(2),
+        Err(synkit::Error::RecursionLimitExceeded { depth: 3, limit: 2 })
+    );
+    assert_eq!(stream.depth(), 2);
+    assert_eq!(
+        stream.depth_context().as_deref(),
+        Some("inside 2 levels of nesting")
+    );
+
+    stream.exit_nested();
+    stream.exit_nested();
+    assert_eq!(stream.depth(), 0);
+    assert_eq!(stream.max_depth_seen(), 2);
+}
+
+#[test]
+fn test_forks_share_max_depth() {
+    let root = TokenStream::lex("[[a]]").expect("lex failed");
+    let mut fork = synkit::TokenStream::fork(&root);
+    let (mut inner, _) = fork.extract_bracket().expect("extract failed");
+    let _ = inner.extract_bracket().expect("extract failed");
+    assert_eq!(root.max_depth_seen(), 2);
+    assert_eq!(root.depth(), 0);
+}
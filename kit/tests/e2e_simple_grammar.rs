@@ -103,7 +103,9 @@ synkit::parser_kit! {
     },
 
     span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
-    token_derives: [Clone, PartialEq, Debug],
+    token_derives: [Clone, PartialEq, Debug, synkit::SpanInsensitive, synkit::EstimateSize],
+
+    from_str: [StructDef],
 }
 
 // Now we can use the generated types
@@ -141,7 +143,7 @@ impl synkit::SpannedError for LexError {
 // Parse implementations for token structs are now auto-generated by parser_kit!
 
 // AST node definitions
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, synkit::SpanInsensitive, synkit::EstimateSize)]
 pub struct StructField {
     pub name: Spanned<tokens::IdentToken>,
     pub colon: Spanned<tokens::ColonToken>,
@@ -174,7 +176,7 @@ impl ToTokens for StructField {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, synkit::SpanInsensitive, synkit::EstimateSize)]
 pub struct StructDef {
     pub kw_struct: Spanned<tokens::KwStructToken>,
     pub name: Spanned<tokens::IdentToken>,
@@ -333,8 +335,6 @@ mod tests {
 
     #[test]
     fn test_token_stream_skips_whitespace() {
-        use synkit::TokenStream as _;
-
         let source = "struct   Foo";
         let mut ts = stream::TokenStream::lex(source).expect("lexing failed");
 
@@ -553,4 +553,343 @@ mod tests {
         assert!(output.contains("x: i32"));
         assert!(output.contains("y: i32"));
     }
+
+    #[test]
+    fn test_parse_str() {
+        let parsed = StructDef::parse_str("  struct Point { x: i32 }\n").expect("parse_str failed");
+        assert_eq!(*parsed.name.value, "Point");
+        assert_eq!(parsed.fields.len(), 1);
+    }
+
+    #[test]
+    fn test_iterate_tokens() {
+        use synkit::TokenStream as _;
+
+        let mut ts = stream::TokenStream::lex("struct Foo { }").expect("lexing failed");
+        let _kw = ts.next();
+
+        // Borrowing iteration leaves the stream where it was
+        let remaining: Vec<_> = (&ts).into_iter().map(|t| t.value.to_string()).collect();
+        assert_eq!(remaining, ["Foo", "{", "}"]);
+
+        let raw: Vec<_> = ts.fork().iter_raw().map(|t| t.value.to_string()).collect();
+        assert_eq!(raw, ["<Space>", "Foo", "<Space>", "{", "<Space>", "}"]);
+
+        let names: Vec<_> = ts
+            .by_ref()
+            .filter_map(|t| match t.value {
+                Token::Ident(name) => Some(name),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(names, ["Foo"]);
+        assert!(ts.is_empty());
+    }
+
+    #[test]
+    fn test_lex_with_skip() {
+        use synkit::TokenStream as _;
+
+        // Keep spaces visible, e.g. for a formatter
+        let mut ts = stream::TokenStream::lex_with_skip("struct  Foo\n{ }", |tok| {
+            matches!(tok, Token::Newline)
+        })
+        .expect("lexing failed");
+        let _kw: Spanned<tokens::KwStructToken> = ts.parse().expect("parse struct kw");
+        assert!(ts.peek::<tokens::SpaceToken>());
+
+        let mut fork = ts.fork();
+        let visible: Vec<_> = fork.by_ref().map(|t| t.value.to_string()).collect();
+        assert_eq!(visible, ["<Space>", "<Space>", "Foo", "{", "<Space>", "}"]);
+        assert_eq!((&ts).into_iter().count(), visible.len());
+    }
+
+    #[test]
+    fn test_parse_delimited() {
+        use synkit::SpanLike;
+
+        let mut ts = stream::TokenStream::lex("( Foo ) ;").expect("lexing failed");
+        let (_open, name, _close) = ts
+            .parse_delimited::<tokens::LParenToken, tokens::RParenToken, tokens::IdentToken>()
+            .expect("parse_delimited failed");
+        assert_eq!(*name.value, "Foo");
+        assert_eq!((name.span.start(), name.span.end()), (0, 7));
+        assert!(ts.peek::<tokens::SemiToken>());
+    }
+
+    #[test]
+    fn test_parse_delimited_rejects_leftovers() {
+        let mut ts = stream::TokenStream::lex("(Foo Bar)").expect("lexing failed");
+        let err = ts
+            .parse_delimited::<tokens::LParenToken, tokens::RParenToken, tokens::IdentToken>()
+            .unwrap_err();
+        assert_eq!(
+            err,
+            LexError::Expected {
+                expect: ")",
+                found: "Bar after `Foo`".to_string(),
+            }
+        );
+
+        let mut ts = stream::TokenStream::lex("[Foo]").expect("lexing failed");
+        assert!(
+            ts.parse_delimited::<tokens::LParenToken, tokens::RParenToken, tokens::IdentToken>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_extract_any_inner() {
+        use synkit::SpanLike;
+
+        let pairs = [delimiters::Paren::PAIR, delimiters::Bracket::PAIR];
+        let mut ts = stream::TokenStream::lex("[a (b)] (c)").expect("lexing failed");
+
+        let (mut inner, span, which) = ts.extract_any_inner(&pairs).expect("first group");
+        assert_eq!(which, 1);
+        assert_eq!((span.start(), span.end()), (0, 7));
+        let _a: tokens::IdentToken = inner.parse_value().expect("parse a");
+        let (_, _, which) = inner.extract_any_inner(&pairs).expect("nested group");
+        assert_eq!(which, 0);
+
+        let (_, span, which) = ts.extract_any_inner(&pairs).expect("second group");
+        assert_eq!(which, 0);
+        assert_eq!((span.start(), span.end()), (8, 11));
+        assert!(ts.is_empty());
+    }
+
+    #[test]
+    fn test_extract_any_inner_errors() {
+        let pairs = [stream::DelimiterPair::of::<
+            tokens::LParenToken,
+            tokens::RParenToken,
+        >()];
+        assert_eq!((pairs[0].open(), pairs[0].close()), ("(", ")"));
+
+        let mut ts = stream::TokenStream::lex("struct [").expect("lexing failed");
+        let Err(err) = ts.extract_any_inner(&pairs) else {
+            panic!("`struct` is not a delimiter");
+        };
+        assert_eq!(err.to_string(), "expected opening delimiter, found struct");
+
+        let Err(err) = ts.extract_any_inner(&[delimiters::Bracket::PAIR]) else {
+            panic!("`[` is never closed");
+        };
+        assert_eq!(err, LexError::Empty { expect: "]" });
+        assert!(ts.extract_any_inner(&pairs).is_err());
+    }
+
+    #[test]
+    fn test_spanned_display_and_debug() {
+        let name = Spanned::new(12, 15, tokens::Token::Ident("Foo".into()));
+        assert_eq!(name.to_string(), "Foo");
+        assert_eq!(format!("{name:#?}"), "Ident(\n    \"Foo\",\n) @ 12..15");
+        assert!(format!("{name:?}").starts_with("Spanned { span: Known("));
+
+        let kw = Spanned::call_site(tokens::KwStructToken::new());
+        assert_eq!(format!("{kw:#?}"), "KwStructToken @ call site");
+    }
+
+    #[test]
+    fn test_spanned_eq_and_hash() {
+        use std::collections::HashSet;
+
+        let a = Spanned::new(0, 3, tokens::Token::Ident("Foo".into()));
+        assert_eq!(a, a.clone());
+        assert_ne!(a, Spanned::new(4, 7, tokens::Token::Ident("Foo".into())));
+
+        let set: HashSet<_> = [Spanned::new(0, 1, 1u8), Spanned::new(0, 1, 1u8)].into();
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn test_recent_tokens() {
+        let mut ts = stream::TokenStream::lex("struct Foo : 1").expect("lexing failed");
+        assert!(ts.recent(2).is_empty());
+
+        let _kw: Spanned<tokens::KwStructToken> = ts.parse().expect("parse struct kw");
+        let _name: Spanned<tokens::IdentToken> = ts.parse().expect("parse name");
+        let recent: Vec<_> = ts.recent(5).iter().map(|t| t.value.to_string()).collect();
+        assert_eq!(recent, ["struct", "Foo"]);
+        assert_eq!(ts.recent(1)[0].value, tokens::Token::Ident("Foo".into()));
+
+        let _colon: Spanned<tokens::ColonToken> = ts.parse().expect("parse colon");
+        let err = ts.parse::<tokens::IdentToken>().unwrap_err();
+        assert_eq!(
+            err,
+            LexError::Expected {
+                expect: "identifier",
+                found: "1 after `:`".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_first_token_error_has_no_context() {
+        let mut ts = stream::TokenStream::lex("1").expect("lexing failed");
+        let err = ts.parse::<tokens::IdentToken>().unwrap_err();
+        assert_eq!(err.to_string(), "expected identifier, found 1");
+    }
+
+    #[test]
+    fn test_raw_mode() {
+        let mut ts = stream::TokenStream::lex("struct \tFoo").expect("lexing failed");
+        let _kw: Spanned<tokens::KwStructToken> = ts.parse().expect("parse struct kw");
+
+        let trivia = ts.raw_mode(|s| {
+            let mut trivia = 0;
+            while s.peek::<tokens::SpaceToken>() || s.peek::<tokens::TabToken>() {
+                s.next();
+                trivia += 1;
+            }
+            trivia
+        });
+        assert_eq!(trivia, 2);
+
+        // Filtering is back on after the closure
+        assert!(!ts.raw_mode(|s| s.is_empty()));
+        let name: Spanned<tokens::IdentToken> = ts.parse().expect("parse name");
+        assert_eq!(*name.value, "Foo");
+    }
+
+    #[test]
+    fn test_peek_cache_follows_cursor() {
+        use synkit::TokenStream as _;
+
+        let mut ts = stream::TokenStream::lex("struct \tFoo :").expect("lexing failed");
+        let start = ts.cursor();
+        let _kw: Spanned<tokens::KwStructToken> = ts.parse().expect("parse struct kw");
+        assert!(!ts.peek::<tokens::KwStructToken>() && ts.peek::<tokens::IdentToken>());
+
+        // Changing the skip set or moving the cursor drops the cached index
+        assert!(ts.raw_mode(|s| s.peek::<tokens::SpaceToken>()));
+        assert!(ts.peek::<tokens::IdentToken>());
+        let mut fork = ts.fork();
+        assert!(fork.next().is_some());
+        assert!(fork.peek::<tokens::ColonToken>());
+        assert!(ts.peek::<tokens::IdentToken>());
+        ts.rewind(start);
+        assert!(ts.peek::<tokens::KwStructToken>());
+    }
+
+    /// A type name, a tuple, or an array, picked with one `token_match!`.
+    fn type_head(stream: &mut stream::TokenStream) -> Result<&'static str, LexError> {
+        Ok(token_match!(stream {
+            Ident | KwType => {
+                stream.next();
+                "name"
+            },
+            LParen => "tuple",
+            LBracket => "array",
+        }))
+    }
+
+    #[test]
+    fn test_token_match() {
+        let mut ts = stream::TokenStream::lex("  Foo (").expect("lexing failed");
+        assert_eq!(type_head(&mut ts), Ok("name"));
+        assert_eq!(type_head(&mut ts), Ok("tuple"));
+
+        let mut ts = stream::TokenStream::lex(": x").expect("lexing failed");
+        assert_eq!(
+            type_head(&mut ts),
+            Err(LexError::Expected {
+                expect: "one of identifier, `type`, `(`, `[`",
+                found: ":".to_string(),
+            })
+        );
+        let mut ts = stream::TokenStream::lex(" ").expect("lexing failed");
+        assert_eq!(
+            type_head(&mut ts),
+            Err(LexError::Empty {
+                expect: "one of identifier, `type`, `(`, `[`",
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_value_and_pair() {
+        use synkit::SpanLike;
+
+        let mut ts = stream::TokenStream::lex("struct Foo").expect("lexing failed");
+        let _kw: tokens::KwStructToken = ts.parse_value().expect("parse struct kw");
+        let (name, span): (tokens::IdentToken, _) = ts.parse_pair().expect("parse name");
+        assert_eq!(*name, "Foo");
+        assert_eq!((span.start(), span.end()), (7, 10));
+    }
+
+    #[test]
+    fn test_spanned_aliases_and_token_kinds() {
+        use tokens::kind;
+
+        let mut ts = stream::TokenStream::lex("struct Foo").expect("lexing failed");
+        let _kw: tokens::SpannedKwStruct = ts.parse().expect("parse struct kw");
+        let name = ts.token::<kind::Ident>().expect("parse name");
+        let name: &tokens::SpannedIdent = &name;
+        assert_eq!(*name.value, "Foo");
+
+        let mut ts = stream::TokenStream::lex("Foo").expect("lexing failed");
+        assert!(ts.token::<kind::KwStruct>().is_err());
+    }
+
+    #[test]
+    fn test_parse_str_trailing_tokens() {
+        let err = StructDef::parse_str("struct A { } struct B { }").unwrap_err();
+        assert_eq!(
+            err,
+            LexError::Expected {
+                expect: "end of input",
+                found: "struct".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        let parsed: StructDef = "struct Data { a: A, b: B }"
+            .parse()
+            .expect("from_str failed");
+        assert_eq!(*parsed.name.value, "Data");
+        assert_eq!(parsed.fields.len(), 2);
+
+        assert!("struct".parse::<StructDef>().is_err());
+    }
+
+    #[test]
+    fn test_ignore_spans_equality() {
+        use synkit::{IgnoreSpans, SpanInsensitive};
+
+        let a = StructDef::parse_str("struct P { x: i32, y: i32 }").expect("parse a");
+        let b = StructDef::parse_str("struct   P {\n\tx:i32,\n\ty:i32\n}").expect("parse b");
+        assert_ne!(a.fields[0].span, b.fields[0].span);
+        assert_ne!(a.rbrace.span, b.rbrace.span);
+        assert!(a.eq_ignore_spans(&b));
+
+        let c = StructDef::parse_str("struct P { x: i32, y: i64 }").expect("parse c");
+        assert!(!a.eq_ignore_spans(&c));
+
+        let mut set = std::collections::HashSet::new();
+        set.insert(IgnoreSpans(a));
+        assert!(set.contains(&IgnoreSpans(b)));
+        assert!(!set.contains(&IgnoreSpans(c)));
+    }
+
+    #[test]
+    fn test_estimate_size() {
+        use std::mem::size_of;
+        use synkit::EstimateSize;
+
+        let small = StructDef::parse_str("struct P { x: i32 }").expect("parse small");
+        let field = &small.fields[0].value;
+        assert_eq!(
+            field.heap_size(),
+            field.name.value.capacity() + field.ty.value.capacity()
+        );
+        assert!(small.estimated_size() >= size_of::<StructDef>() + field.estimated_size());
+
+        let long = format!("struct {} {{ x: i32 }}", "p".repeat(1000));
+        let large = StructDef::parse_str(&long).expect("parse large");
+        assert!(large.estimated_size() >= small.estimated_size() + 999);
+        assert_eq!(Token::Ident("abc".into()).heap_size(), 3);
+    }
 }
@@ -0,0 +1,76 @@
+//! Snapshot of the code `parser_kit!` generates.
+//!
+//! Review changes with `cargo insta review`. The expansion depends on the
+//! macro features, so the snapshot is only checked with the default set;
+//! what `tokio`, `futures` and `json` add is checked item by item.
+#![cfg(feature = "expand_to_string")]
+
+const EXPANSION: &str = synkit::expand_to_string! {
+    error: LexError,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        /// Assignment.
+        #[token("=")]
+        Eq,
+
+        #[token("(")]
+        LParen,
+
+        #[token(")")]
+        RParen,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+    },
+
+    delimiters: {
+        Paren => (LParen, RParen),
+    },
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash],
+    token_derives: [Clone, PartialEq, Debug],
+};
+
+#[cfg(all(
+    feature = "std",
+    not(feature = "tokio"),
+    not(feature = "futures"),
+    not(feature = "json")
+))]
+#[test]
+fn snapshot_parser_kit_expansion() {
+    insta::assert_snapshot!(EXPANSION);
+}
+
+#[test]
+fn test_feature_items() {
+    let items = [
+        (
+            cfg!(feature = "tokio"),
+            "pub type AstStream<T> = synkit::async_stream::tokio_impl::AstStream<T, SpannedToken>;",
+        ),
+        (
+            cfg!(feature = "tokio"),
+            "pub trait IncrementalParse: Sized {",
+        ),
+        (cfg!(feature = "tokio"), "pub use traits::IncrementalParse;"),
+        (cfg!(feature = "futures"), "pub type ParseStream<S, T> ="),
+        (
+            cfg!(feature = "json"),
+            "impl synkit::ToJsonValue for Token {",
+        ),
+        (
+            cfg!(feature = "json"),
+            "impl<T: synkit::ToJsonValue> synkit::ToJsonValue for Spanned<T> {",
+        ),
+    ];
+    for (enabled, item) in items {
+        assert_eq!(EXPANSION.contains(item), enabled, "{item}");
+    }
+}
@@ -0,0 +1,121 @@
+//! Tests for `export: none | prelude` keeping generated names out of the
+//! invocation site.
+
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+// Names `export: full` would re-export at the crate root
+#[allow(dead_code)]
+struct Span;
+#[allow(dead_code)]
+struct Token;
+#[allow(dead_code)]
+trait Parse {}
+#[allow(unused_macros)]
+macro_rules! paren {
+    () => {};
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    export: prelude,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token("(")]
+        LParen,
+
+        #[token(")")]
+        RParen,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+    },
+
+    delimiters: {
+        Paren => (LParen, RParen),
+    },
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+mod unexported {
+    use super::LexError;
+
+    synkit::parser_kit! {
+        error: LexError,
+
+        export: none,
+
+        skip_tokens: [],
+
+        tokens: {
+            #[token("x")]
+            X,
+        },
+
+        span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+        token_derives: [Clone, PartialEq, Debug],
+    }
+}
+
+fn parse_parens(
+    stream: &mut prelude::TokenStream,
+) -> Result<(delimiters::Paren, Vec<String>), LexError> {
+    let mut inner;
+    let paren = prelude::paren!(inner in stream);
+    let mut idents = Vec::new();
+    while !inner.is_empty() {
+        let ident: prelude::Spanned<tokens::IdentToken> = inner.parse()?;
+        idents.push(ident.value.0);
+    }
+    Ok((paren, idents))
+}
+
+#[test]
+fn test_prelude_exports() {
+    let mut stream = prelude::TokenStream::lex("(a b)").expect("lex failed");
+    let (paren, idents) = parse_parens(&mut stream).expect("parse failed");
+    assert_eq!(idents, ["a", "b"]);
+    assert_eq!(*paren.span(), prelude::Span::new(0, 5));
+}
+
+fn parse_empty_parens(stream: &mut prelude::TokenStream) -> Result<bool, LexError> {
+    let inner;
+    delimiters::paren!(inner in stream);
+    Ok(inner.is_empty())
+}
+
+#[test]
+fn test_macros_in_generated_modules() {
+    let stream = tokens::quote_tokens! { (a) }.expect("quote failed");
+    assert_eq!(stream.len(), 3);
+
+    let mut stream = prelude::TokenStream::lex("()").expect("lex failed");
+    assert_eq!(parse_empty_parens(&mut stream), Ok(true));
+}
+
+#[test]
+fn test_export_none() {
+    let stream = unexported::stream::TokenStream::lex("xx").expect("lex failed");
+    assert_eq!(stream.all().len(), 2);
+}
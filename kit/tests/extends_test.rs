@@ -0,0 +1,116 @@
+//! Tests for extending a kit through `export_as` and `extends`.
+//!
+//! The exported macro names the base kit's modules as `$crate::tokens` etc.
+//! unless `self_path` says otherwise, so the base kit is invoked at the crate
+//! root here. A base kit inside a module needs `self_path: crate::module`.
+
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    export: none,
+    export_as: base_kit,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token("(")]
+        LParen,
+
+        #[token(")")]
+        RParen,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+    },
+
+    delimiters: {
+        Paren => (LParen, RParen),
+    },
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+// `base_kit!` is in textual scope here; `crate::base_kit` is rejected for
+// macros exported by a macro expansion in the same crate.
+mod plugin {
+    use super::LexError;
+
+    synkit::parser_kit! {
+        error: LexError,
+
+        export: none,
+        extends: base_kit,
+        self_path: crate::plugin,
+
+        tokens: {
+            #[token("async", priority = 3)]
+            KwAsync,
+        },
+
+        span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+        token_derives: [Clone, PartialEq, Debug],
+    }
+}
+
+#[test]
+fn test_extended_kit_lexes_base_and_own_tokens() {
+    let stream = plugin::stream::TokenStream::lex("async (run)").expect("lex failed");
+    let kinds: Vec<_> = stream.all().iter().map(|t| t.value.clone()).collect();
+    assert_eq!(
+        kinds,
+        [
+            plugin::tokens::Token::KwAsync,
+            plugin::tokens::Token::Space,
+            plugin::tokens::Token::LParen,
+            plugin::tokens::Token::Ident("run".to_string()),
+            plugin::tokens::Token::RParen,
+        ]
+    );
+}
+
+#[test]
+fn test_extended_kit_keeps_base_delimiters() {
+    let mut stream = plugin::stream::TokenStream::lex("(async)").expect("lex failed");
+    let (mut inner, _) = stream.extract_paren().expect("extract failed");
+    let token: plugin::span::Spanned<plugin::tokens::KwAsyncToken> =
+        inner.parse().expect("parse failed");
+    assert_eq!(token.value, plugin::tokens::KwAsyncToken);
+    assert!(inner.is_empty());
+}
+
+#[test]
+fn test_token_conversions() {
+    let token: plugin::tokens::Token = tokens::Token::Ident("x".to_string()).into();
+    assert_eq!(token, plugin::tokens::Token::Ident("x".to_string()));
+    assert_eq!(
+        tokens::Token::try_from(token),
+        Ok(tokens::Token::Ident("x".to_string()))
+    );
+    assert_eq!(
+        tokens::Token::try_from(plugin::tokens::Token::KwAsync),
+        Err(plugin::tokens::Token::KwAsync)
+    );
+
+    let ident: plugin::tokens::IdentToken = tokens::IdentToken("y".to_string()).into();
+    assert_eq!(ident.0, "y");
+}
@@ -0,0 +1,122 @@
+//! Tests for `file_ids`: spans that remember which file they came from.
+
+use synkit::{FileId, SourceFiles, SpanLike};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    file_ids: true,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token("(")]
+        LParen,
+
+        #[token(")")]
+        RParen,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+    },
+
+    delimiters: {
+        Paren => (LParen, RParen),
+    },
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+fn parse_idents(stream: &mut TokenStream) -> Vec<Spanned<tokens::IdentToken>> {
+    let mut idents = Vec::new();
+    while !stream.is_empty() {
+        idents.push(stream.parse().expect("parse failed"));
+    }
+    idents
+}
+
+#[test]
+fn test_spans_from_merged_files() {
+    let mut files = SourceFiles::new();
+    let mut idents = Vec::new();
+    for (path, source) in [("a.txt", "alpha beta"), ("b.txt", "  gamma")] {
+        let mut stream = TokenStream::lex_in(&mut files, path, source).expect("lex failed");
+        assert_eq!(stream.source_path().map(|p| p.to_str()), Some(Some(path)));
+        idents.extend(parse_idents(&mut stream));
+    }
+
+    let located: Vec<_> = idents
+        .iter()
+        .map(|ident| {
+            let file = ident.span.file().expect("span has no file");
+            (
+                files.path(file).and_then(|p| p.to_str()),
+                files.slice(file, &ident.span),
+            )
+        })
+        .collect();
+    assert_eq!(
+        located,
+        [
+            (Some("a.txt"), Some("alpha")),
+            (Some("a.txt"), Some("beta")),
+            (Some("b.txt"), Some("gamma")),
+        ]
+    );
+}
+
+#[test]
+fn test_plain_lex_has_no_file() {
+    let mut stream = TokenStream::lex("a").expect("lex failed");
+    let ident: Spanned<tokens::IdentToken> = stream.parse().expect("parse failed");
+    assert_eq!(ident.span.file(), None);
+    assert_eq!(ident.span.with_file(file(0)).file(), Some(file(0)));
+}
+
+#[test]
+fn test_derived_spans_keep_file() {
+    let mut stream = TokenStream::lex_with_file("(a b)", file(2)).expect("lex failed");
+    let (_, paren) = stream.extract_paren().expect("extract failed");
+    assert_eq!(paren.span().file(), Some(file(2)));
+    assert_eq!(paren.span().byte_range(), Some(0..5));
+
+    let joined = Span::new(9, 10).join(paren.span());
+    assert_eq!(joined.file(), Some(file(2)));
+    assert_eq!(joined.byte_range(), Some(0..10));
+
+    let mut tokens = TokenStream::lex_with_file("a", file(2))
+        .expect("lex failed")
+        .to_mut_stream();
+    tokens.shift_spans(3);
+    let span = tokens.covering_span();
+    assert_eq!((span.start(), span.file()), (3, Some(file(2))));
+}
+
+#[test]
+fn test_call_site_has_no_file() {
+    assert_eq!(Span::call_site().with_file(file(1)), Span::CallSite);
+    assert_eq!(Span::CallSite.with_range(1, 2).file(), None);
+}
+
+fn file(index: usize) -> FileId {
+    FileId::from_index(index).expect("index fits in u32")
+}
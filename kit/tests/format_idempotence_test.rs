@@ -0,0 +1,224 @@
+//! Tests for the generated `testing` helpers.
+
+use synkit::testing::{IdempotenceError, RelexError};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space, Newline],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token("\n")]
+        Newline,
+
+        #[token("=")]
+        Eq,
+
+        #[regex(r"[a-z_]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+    },
+
+    delimiters: {},
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+/// `key = value` lines.
+#[derive(Debug, Clone)]
+struct Assigns(Vec<Spanned<Assign>>);
+
+#[derive(Debug, Clone)]
+struct Assign {
+    key: Spanned<tokens::IdentToken>,
+    value: Spanned<tokens::IdentToken>,
+}
+
+impl Parse for Assigns {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        let mut items = Vec::new();
+        while !stream.is_empty() {
+            items.push(stream.parse()?);
+        }
+        Ok(Self(items))
+    }
+}
+
+impl Parse for Assign {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        let key = stream.parse()?;
+        let _: Spanned<tokens::EqToken> = stream.parse()?;
+        Ok(Self {
+            key,
+            value: stream.parse()?,
+        })
+    }
+}
+
+impl ToTokens for Assigns {
+    fn write(&self, p: &mut Printer) {
+        use synkit::Printer as _;
+        for item in &self.0 {
+            item.write(p);
+            p.newline();
+        }
+    }
+}
+
+impl ToTokens for Assign {
+    fn write(&self, p: &mut Printer) {
+        use synkit::Printer as _;
+        self.key.write(p);
+        p.word(" = ");
+        self.value.write(p);
+    }
+}
+
+/// Buggy formatter: appends `_` to every value it prints.
+#[derive(Debug, Clone)]
+struct Suffixed(Assigns);
+
+impl Parse for Suffixed {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        Ok(Self(stream.parse::<Assigns>()?.value))
+    }
+}
+
+impl ToTokens for Suffixed {
+    fn write(&self, p: &mut Printer) {
+        use synkit::Printer as _;
+        for item in &self.0.0 {
+            p.mapped(&item.span, |p| {
+                item.value.key.write(p);
+                p.word(" = ");
+                p.mapped(&item.value.value.span, |p| {
+                    p.word(&item.value.value.value);
+                    p.word("_");
+                });
+            });
+            p.newline();
+        }
+    }
+}
+
+/// Buggy formatter: forgets the `=`.
+#[derive(Debug, Clone)]
+struct NoEq(Assigns);
+
+impl Parse for NoEq {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        Ok(Self(stream.parse::<Assigns>()?.value))
+    }
+}
+
+impl ToTokens for NoEq {
+    fn write(&self, p: &mut Printer) {
+        use synkit::Printer as _;
+        for item in &self.0.0 {
+            p.mapped(&item.span, |p| {
+                item.value.key.write(p);
+                p.space();
+                item.value.value.write(p);
+            });
+            p.newline();
+        }
+    }
+}
+
+#[test]
+fn test_idempotent_formatter() {
+    let output = testing::assert_format_idempotent::<Assigns>("a=b  c =d");
+    assert_eq!(output, "a = b\nc = d\n");
+}
+
+#[test]
+fn test_divergence_points_at_node() {
+    let err = testing::check_format_idempotent::<Suffixed>("a=b\nc=d").unwrap_err();
+    let IdempotenceError::Diverged {
+        first,
+        offset,
+        span,
+        ..
+    } = &err
+    else {
+        panic!("expected divergence, got {err:?}");
+    };
+    assert_eq!(first, "a = b_\nc = d_\n");
+    assert_eq!(*offset, 6);
+    assert_eq!(span.clone().map(|s| &first[s]), Some("b_"));
+    assert!(err.to_string().contains("at 1:7"));
+}
+
+#[test]
+fn test_unparseable_input() {
+    let err = testing::check_format_idempotent::<Assigns>("a = =").unwrap_err();
+    assert!(matches!(err, IdempotenceError::Input { .. }));
+}
+
+#[test]
+#[should_panic(expected = "formatting is not idempotent")]
+fn test_assert_panics_on_divergence() {
+    testing::assert_format_idempotent::<Suffixed>("a=b");
+}
+
+#[test]
+fn test_relex_matches_input_tokens() {
+    let output = testing::assert_relex::<Assigns>("a=b\n  c =d");
+    assert_eq!(output, "a = b\nc = d\n");
+}
+
+#[test]
+fn test_relex_pinpoints_first_mismatch() {
+    let source = "a=b\nc=d";
+    let err = testing::check_relex::<NoEq>(source).unwrap_err();
+    let RelexError::Mismatch(mismatch) = &err else {
+        panic!("expected mismatch, got {err:?}");
+    };
+    assert_eq!(mismatch.index, 1);
+    assert_eq!(mismatch.expected, Some(("=".to_string(), 1..2)));
+    assert_eq!(mismatch.found, Some(("b".to_string(), 2..3)));
+    assert_eq!(mismatch.node.clone().map(|s| &source[s]), Some("b"));
+    assert!(err.to_string().contains("at 1:3 in output"));
+}
+
+#[test]
+#[should_panic(expected = "re-lexes differently at token 1")]
+fn test_assert_relex_panics_on_mismatch() {
+    testing::assert_relex::<NoEq>("a=b");
+}
+
+#[test]
+fn test_shrink_to_smallest_mismatch() {
+    let source = "a=b\nc=d\n  e = f\ng=h\n";
+    let fails = |s: &str| {
+        matches!(
+            testing::check_relex::<NoEq>(s),
+            Err(RelexError::Mismatch(_))
+        )
+    };
+    let small = testing::shrink(source, fails);
+    assert!(fails(&small));
+    assert_eq!(Assigns::parse_str(&small).expect("parse").0.len(), 1);
+    assert_eq!(small, "g=h\n");
+
+    // Inputs that don't fail are returned as-is
+    assert_eq!(testing::shrink(source, |_| false), source);
+}
@@ -0,0 +1,71 @@
+//! Tests for loading token definitions with `from_file`.
+#![cfg(feature = "from_file")]
+
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    from_file: "tests/grammar/assign.toml",
+
+    // Extends the file's tokens
+    tokens: {
+        #[token("let")]
+        KwLet,
+    },
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+#[test]
+fn test_tokens_from_file() {
+    let mut stream = TokenStream::lex("let x = y;").expect("lex failed");
+    let _: Spanned<tokens::KwLetToken> = stream.parse().expect("let");
+    let name: Spanned<tokens::IdentToken> = stream.parse().expect("name");
+    assert_eq!(name.value.as_str(), "x");
+    let _: Spanned<tokens::EqToken> = stream.parse().expect("=");
+    let _: Spanned<tokens::IdentToken> = stream.parse().expect("value");
+    let _: Spanned<tokens::SemiToken> = stream.parse().expect(";");
+    assert!(stream.is_empty());
+}
+
+fn parse_block(stream: &mut TokenStream) -> Result<Vec<String>, LexError> {
+    let mut inner;
+    delimiters::brace!(inner in stream);
+    let mut idents = Vec::new();
+    while !inner.is_empty() {
+        let ident: Spanned<tokens::IdentToken> = inner.parse()?;
+        idents.push(ident.value.0);
+    }
+    Ok(idents)
+}
+
+#[test]
+fn test_delimiters_from_file() {
+    let mut stream = TokenStream::lex("{ a b }").expect("lex failed");
+    assert_eq!(parse_block(&mut stream).expect("parse failed"), ["a", "b"]);
+}
+
+#[test]
+fn test_formats_from_file() {
+    assert_eq!(tokens::IdentToken::fmt(), "identifier");
+    let err = LexError::Expected {
+        expect: tokens::IdentToken::fmt(),
+        found: "1".into(),
+    };
+    assert_eq!(err.to_string(), "expected identifier, found 1");
+}
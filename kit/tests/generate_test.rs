@@ -0,0 +1,253 @@
+//! Tests for generating random programs with `Generate`.
+
+use synkit::{Generate, Generator, Printer as _, Separated};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[regex(r"[ \n]+")]
+        Space,
+
+        #[token("let")]
+        KwLet,
+
+        #[token("=")]
+        Eq,
+
+        #[token(",")]
+        Comma,
+
+        #[token(";")]
+        Semi,
+
+        #[token("[")]
+        LBracket,
+
+        #[token("]")]
+        RBracket,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+
+        #[regex(r"[0-9]+", |lex| lex.slice().parse::<u64>().ok())]
+        #[fmt("number")]
+        Number(u64),
+
+        #[regex(r#""[^"]*""#, |lex| lex.slice().to_string())]
+        #[sample(r#""hello""#)]
+        #[sample(r#""""#)]
+        #[fmt("string")]
+        Str(String),
+    },
+
+    delimiters: {
+        Bracket => (LBracket, RBracket),
+    },
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+#[derive(Debug, Generate)]
+struct Program {
+    stmts: Vec<Stmt>,
+}
+
+/// `let name = value;`
+#[derive(Debug, Generate)]
+struct Stmt {
+    kw: Spanned<tokens::KwLetToken>,
+    name: Spanned<tokens::IdentToken>,
+    eq: Spanned<tokens::EqToken>,
+    value: Spanned<Value>,
+    semi: Spanned<tokens::SemiToken>,
+}
+
+#[derive(Debug, Generate)]
+enum Value {
+    List(List),
+    Number(Spanned<tokens::NumberToken>),
+    Str(Spanned<tokens::StrToken>),
+}
+
+#[derive(Debug, Generate)]
+struct List {
+    open: Spanned<tokens::LBracketToken>,
+    items: Separated<Spanned<Value>, Spanned<tokens::CommaToken>>,
+    close: Spanned<tokens::RBracketToken>,
+}
+
+impl Parse for Program {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        let mut stmts = Vec::new();
+        while !stream.is_empty() {
+            stmts.push(Stmt::parse(stream)?);
+        }
+        Ok(Self { stmts })
+    }
+}
+
+impl Parse for Stmt {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        Ok(Self {
+            kw: stream.parse()?,
+            name: stream.parse()?,
+            eq: stream.parse()?,
+            value: stream.parse()?,
+            semi: stream.parse()?,
+        })
+    }
+}
+
+impl Parse for Value {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        if stream.peek::<tokens::LBracketToken>() {
+            Ok(Self::List(List::parse(stream)?))
+        } else if stream.peek::<tokens::NumberToken>() {
+            Ok(Self::Number(stream.parse()?))
+        } else {
+            Ok(Self::Str(stream.parse()?))
+        }
+    }
+}
+
+impl Parse for List {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        let open = stream.parse()?;
+        let mut items = Separated::new();
+        while !stream.peek::<tokens::RBracketToken>() {
+            items.push_value(stream.parse()?);
+            if !stream.peek::<tokens::CommaToken>() {
+                break;
+            }
+            items.push_punct(stream.parse()?);
+        }
+        Ok(Self {
+            open,
+            items,
+            close: stream.parse()?,
+        })
+    }
+}
+
+impl ToTokens for Program {
+    fn write(&self, p: &mut Printer) {
+        self.stmts.write(p);
+    }
+}
+
+impl ToTokens for Stmt {
+    fn write(&self, p: &mut Printer) {
+        self.kw.write(p);
+        p.space();
+        self.name.write(p);
+        p.space();
+        self.eq.write(p);
+        p.space();
+        self.value.write(p);
+        self.semi.write(p);
+        p.newline();
+    }
+}
+
+impl ToTokens for Value {
+    fn write(&self, p: &mut Printer) {
+        match self {
+            Self::List(list) => list.write(p),
+            Self::Number(n) => n.write(p),
+            Self::Str(s) => s.write(p),
+        }
+    }
+}
+
+impl ToTokens for List {
+    fn write(&self, p: &mut Printer) {
+        self.open.write(p);
+        self.items.write(p);
+        self.close.write(p);
+    }
+}
+
+#[test]
+fn test_generated_programs_parse() {
+    for source in testing::corpus::<Program>(0, 64) {
+        let program = Program::parse_str(&source)
+            .unwrap_or_else(|e| panic!("{e} in generated program:\n{source}"));
+        assert_eq!(program.to_string_formatted(), source);
+        testing::assert_relex::<Program>(&source);
+    }
+}
+
+#[test]
+fn test_deterministic_per_seed() {
+    assert_eq!(
+        testing::generate::<Program>(7),
+        testing::generate::<Program>(7)
+    );
+    let corpus = testing::corpus::<Stmt>(0, 16);
+    assert!(corpus.iter().any(|s| s != &corpus[0]));
+}
+
+#[test]
+fn test_size_bound() {
+    for seed in 0..32 {
+        let mut g = Generator::new(seed).with_max_size(12);
+        let value = Value::generate(&mut g);
+        let source = value.to_string_formatted();
+        let mut stream = TokenStream::lex(&source).expect("lex failed");
+        let mut count = 0;
+        while stream.next().is_some() {
+            count += 1;
+        }
+        // Closing brackets are required, so they may run past the budget
+        assert!(count <= 2 * 12, "{source}");
+    }
+
+    // Exhausted enums fall back to a variant without recursion
+    let mut g = Generator::new(0).with_max_depth(0);
+    assert!(!matches!(Value::generate(&mut g), Value::List(_)));
+
+    #[derive(Generate)]
+    #[allow(dead_code)]
+    enum Marked {
+        Number(Spanned<tokens::NumberToken>),
+        #[generate(fallback)]
+        List(List),
+    }
+    let mut g = Generator::new(0).with_max_depth(0);
+    assert!(matches!(Marked::generate(&mut g), Marked::List(_)));
+}
+
+#[test]
+fn test_token_samples() {
+    let mut seen = std::collections::HashSet::new();
+    for seed in 0..32 {
+        let mut g = Generator::new(seed);
+        let s = tokens::StrToken::generate(&mut g);
+        assert!(s.0 == r#""hello""# || s.0 == r#""""#);
+        seen.insert(s.0);
+
+        let ident = tokens::IdentToken::generate(&mut g);
+        assert!(ident.chars().all(|c| c.is_ascii_lowercase()), "{}", ident.0);
+        assert_ne!(ident.0, "let");
+    }
+    assert_eq!(seen.len(), 2);
+}
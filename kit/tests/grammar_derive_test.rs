@@ -0,0 +1,150 @@
+//! Tests for `Peek`/`Parse` impls derived from FIRST sets with `Grammar`.
+
+use synkit::Grammar;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[regex(r"[ \n]+")]
+        Space,
+
+        #[token("let")]
+        KwLet,
+
+        #[token("pub")]
+        KwPub,
+
+        #[token("=")]
+        Eq,
+
+        #[token("-")]
+        Minus,
+
+        #[token(";")]
+        Semi,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+
+        #[regex(r"[0-9]+", |lex| lex.slice().parse::<u64>().ok())]
+        #[fmt("number")]
+        Number(u64),
+    },
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+    container_impls: true,
+}
+
+#[derive(Debug, Grammar)]
+#[grammar(error = LexError)]
+struct Program {
+    stmts: Vec<Stmt>,
+}
+
+/// `pub? let name = value;`
+#[derive(Debug, Grammar)]
+#[grammar(error = LexError, expect = "statement")]
+#[allow(dead_code)]
+struct Stmt {
+    vis: Option<Spanned<tokens::KwPubToken>>,
+    kw: Spanned<tokens::KwLetToken>,
+    name: Spanned<tokens::IdentToken>,
+    eq: Spanned<tokens::EqToken>,
+    value: Spanned<Value>,
+    semi: Spanned<tokens::SemiToken>,
+}
+
+#[derive(Debug, Grammar)]
+#[grammar(error = LexError, expect = "value")]
+#[allow(dead_code)]
+enum Value {
+    Number(Spanned<tokens::NumberToken>),
+    Neg(Spanned<tokens::MinusToken>, Box<Spanned<Value>>),
+    Var { name: Spanned<tokens::IdentToken> },
+}
+
+/// A value, or nothing before the `;`.
+#[derive(Debug, Grammar)]
+#[grammar(error = LexError)]
+enum Init {
+    Value(Value),
+    Empty,
+}
+
+#[test]
+fn test_struct_first_set_includes_optional_prefix() {
+    assert!(Stmt::is(&Token::KwPub));
+    assert!(Stmt::is(&Token::KwLet));
+    assert!(!Stmt::is(&Token::Ident("x".to_string())));
+}
+
+#[test]
+fn test_enum_first_set_is_union() {
+    assert!(Value::is(&Token::Number(1)));
+    assert!(Value::is(&Token::Minus));
+    assert!(Value::is(&Token::Ident("x".to_string())));
+    assert!(!Value::is(&Token::Semi));
+}
+
+#[test]
+fn test_parse_dispatches_on_first_token() {
+    let program = Program::parse_str("let a = 1; pub let b = --a;").expect("parse failed");
+    assert_eq!(program.stmts.len(), 2);
+    assert!(program.stmts[0].vis.is_none());
+    assert!(program.stmts[1].vis.is_some());
+    assert!(matches!(program.stmts[0].value.value, Value::Number(_)));
+
+    let Value::Neg(_, inner) = &program.stmts[1].value.value else {
+        panic!("expected a negation");
+    };
+    let Value::Neg(_, inner) = &inner.value else {
+        panic!("expected a double negation");
+    };
+    assert!(matches!(&inner.value, Value::Var { name } if *name.value == "a"));
+}
+
+#[test]
+fn test_nullable_variant_is_fallback() {
+    let mut stream = TokenStream::lex(";").expect("lex failed");
+    assert!(matches!(stream.parse_value::<Init>(), Ok(Init::Empty)));
+
+    let mut stream = TokenStream::lex("7").expect("lex failed");
+    assert!(matches!(
+        stream.parse_value::<Init>(),
+        Ok(Init::Value(Value::Number(_)))
+    ));
+}
+
+#[test]
+fn test_no_matching_variant() {
+    assert_eq!(
+        Value::parse_str(";").map(|_| ()),
+        Err(LexError::Expected {
+            expect: "value",
+            found: ";".to_string(),
+        })
+    );
+    assert_eq!(
+        Value::parse_str("").map(|_| ()),
+        Err(LexError::Empty { expect: "value" })
+    );
+}
@@ -0,0 +1,83 @@
+//! Tests for `testing::lex_print_lex`, which checks that printed tokens
+//! re-lex to themselves.
+
+use synkit::testing::{LexPrintLexError, PrintedTokenMismatch};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space, Newline],
+
+    tokens: {
+        #[token(" ")]
+        Space,
+
+        // Prints its placeholder instead of a line break
+        #[token("\n")]
+        #[fmt("newline")]
+        Newline,
+
+        #[token("=")]
+        Eq,
+
+        #[regex(r"[a-z_]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+
+        #[regex(r"[0-9]+", |lex| lex.slice().parse::<u64>().ok())]
+        #[fmt("number")]
+        Number(u64),
+    },
+
+    delimiters: {},
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+#[test]
+fn test_round_tripping_tokens_pass() {
+    assert_eq!(testing::lex_print_lex(["a = b", "x = 007", ""]), Ok(()));
+    testing::assert_lex_print_lex(["a = 1"]);
+}
+
+#[test]
+fn test_reports_first_placeholder_token() {
+    let err = testing::lex_print_lex(["a = b", "c = d\ne = f\n"]).unwrap_err();
+    assert_eq!(
+        err,
+        LexPrintLexError::Mismatch(Box::new(PrintedTokenMismatch {
+            entry: 1,
+            range: 5..6,
+            kind: "Newline".into(),
+            printed: "newline".into(),
+            found: "`Ident`".into(),
+        }))
+    );
+}
+
+#[test]
+fn test_unlexable_entry() {
+    let err = testing::lex_print_lex(["a = ?"]).unwrap_err();
+    assert!(matches!(err, LexPrintLexError::Input { entry: 0, .. }));
+}
+
+#[test]
+#[should_panic(expected = "token `Newline` at 1..2 in corpus entry 0 prints as \"newline\"")]
+fn test_assert_panics_on_mismatch() {
+    testing::assert_lex_print_lex(["a\n"]);
+}
@@ -0,0 +1,91 @@
+//! Tests for `line_index`: line/column lookups through the stream.
+
+use synkit::{LineCol, TokenStream as _};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    line_index: true,
+
+    skip_tokens: [Space, Newline],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token("\n")]
+        Newline,
+
+        #[token("[")]
+        LBracket,
+
+        #[token("]")]
+        RBracket,
+
+        #[token("=")]
+        Eq,
+
+        #[regex(r"[a-zé]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+    },
+
+    delimiters: {
+        Bracket => (LBracket, RBracket),
+    },
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+#[test]
+fn test_span_line_and_column() {
+    let source = "a = b\n  été = [c]\n";
+    let mut stream = TokenStream::lex(source).expect("lex failed");
+    assert_eq!(stream.line_index().line_count(), 3);
+
+    let mut positions = Vec::new();
+    while let Some(tok) = stream.next() {
+        if matches!(tok.value, Token::Ident(_)) {
+            positions.push((tok.span.line(&stream), tok.span.column(&stream)));
+        }
+    }
+    assert_eq!(positions, [(1, 1), (1, 5), (2, 3), (2, 10)]);
+}
+
+#[test]
+fn test_error_position_from_stream() {
+    let mut stream = TokenStream::lex("a\n\n  = b").expect("lex failed");
+    let _: Spanned<tokens::IdentToken> = stream.parse().expect("parse failed");
+    let _: Spanned<tokens::EqToken> = stream.parse().expect("parse failed");
+    let span = stream.last_span().expect("no token consumed");
+    assert_eq!(span.line_col(&stream), LineCol { line: 3, column: 3 });
+}
+
+#[test]
+fn test_nested_stream_shares_index() {
+    let mut stream = TokenStream::lex("x =\n[y]").expect("lex failed");
+    let _: Spanned<tokens::IdentToken> = stream.parse().expect("parse failed");
+    let _: Spanned<tokens::EqToken> = stream.parse().expect("parse failed");
+    let (mut inner, _) = stream.extract_bracket().expect("extract failed");
+    let y: Spanned<tokens::IdentToken> = inner.parse().expect("parse failed");
+    assert_eq!(
+        inner.line_col(synkit::SpanLike::start(&y.span)),
+        LineCol { line: 2, column: 2 }
+    );
+    assert_eq!(y.span.line(&inner), 2);
+}
@@ -0,0 +1,204 @@
+//! Tests for lowering ASTs to IR with `Lower`.
+
+use synkit::{Lower, LowerCx, Lowered, Separated, Severity};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[regex(r"[ \n]+")]
+        Space,
+
+        #[token("let")]
+        KwLet,
+
+        #[token("=")]
+        Eq,
+
+        #[token(",")]
+        Comma,
+
+        #[token(";")]
+        Semi,
+
+        #[token("[")]
+        LBracket,
+
+        #[token("]")]
+        RBracket,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+
+        #[regex(r"[0-9]+", |lex| lex.slice().parse::<u64>().ok())]
+        #[fmt("number")]
+        Number(u64),
+    },
+
+    delimiters: {
+        Bracket => (LBracket, RBracket),
+    },
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+mod ir {
+    use super::Span;
+    use synkit::Lowered;
+
+    #[derive(Debug, PartialEq)]
+    pub struct Stmt {
+        pub name: Lowered<String, Span>,
+        pub value: Lowered<Value, Span>,
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum Value {
+        Number(Lowered<u64, Span>),
+        Var(Lowered<String, Span>),
+        List(Vec<Lowered<Value, Span>>),
+    }
+}
+
+/// `let name = value;`
+#[derive(Debug, Lower)]
+#[lower(into = ir::Stmt, validate = check_stmt)]
+struct Stmt {
+    #[lower(skip)]
+    kw: Spanned<tokens::KwLetToken>,
+    name: Spanned<tokens::IdentToken>,
+    #[lower(skip)]
+    eq: Spanned<tokens::EqToken>,
+    value: Spanned<Value>,
+    #[lower(skip)]
+    semi: Spanned<tokens::SemiToken>,
+}
+
+#[derive(Debug, Lower)]
+#[lower(into = ir::Value)]
+enum Value {
+    Number(#[lower(with = lower_number)] Spanned<tokens::NumberToken>),
+    Var(Spanned<tokens::IdentToken>),
+    List(
+        #[lower(skip)] Spanned<tokens::LBracketToken>,
+        Separated<Spanned<Value>, Spanned<tokens::CommaToken>>,
+        #[lower(skip)] Spanned<tokens::RBracketToken>,
+    ),
+}
+
+fn check_stmt(stmt: &Stmt, cx: &mut LowerCx<Span>) {
+    if *stmt.name.value == "self" {
+        cx.error("`self` is reserved");
+    }
+}
+
+fn lower_number(
+    number: &Spanned<tokens::NumberToken>,
+    cx: &mut LowerCx<Span>,
+) -> Lowered<u64, Span> {
+    if *number.value > 255 {
+        cx.error_at(number.span, "number out of range");
+    }
+    number.lower(cx)
+}
+
+impl Parse for Stmt {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        Ok(Self {
+            kw: stream.parse()?,
+            name: stream.parse()?,
+            eq: stream.parse()?,
+            value: stream.parse()?,
+            semi: stream.parse()?,
+        })
+    }
+}
+
+impl Parse for Value {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        if stream.peek::<tokens::LBracketToken>() {
+            let open = stream.parse()?;
+            let mut items = Separated::new();
+            while !stream.peek::<tokens::RBracketToken>() {
+                items.push_value(stream.parse()?);
+                if stream.peek::<tokens::CommaToken>() {
+                    items.push_punct(stream.parse()?);
+                }
+            }
+            Ok(Self::List(open, items, stream.parse()?))
+        } else if stream.peek::<tokens::IdentToken>() {
+            Ok(Self::Var(stream.parse()?))
+        } else {
+            Ok(Self::Number(stream.parse()?))
+        }
+    }
+}
+
+fn lower(source: &str) -> (Lowered<ir::Stmt, Span>, LowerCx<Span>) {
+    let mut stream = TokenStream::lex(source).expect("lex failed");
+    let stmt: Spanned<Stmt> = stream.parse().expect("parse failed");
+    let mut cx = LowerCx::new();
+    let ir = stmt.lower(&mut cx);
+    (ir, cx)
+}
+
+#[test]
+fn test_lower_carries_spans_and_ids() {
+    let (stmt, cx) = lower("let x = [1, y];");
+    assert_eq!(stmt.span, Span::new(0, 15));
+    assert_eq!(*stmt.name, "x");
+    assert_eq!(stmt.name.span, Span::new(4, 5));
+
+    let ir::Value::List(items) = &stmt.value.node else {
+        panic!("expected a list, got {:?}", stmt.value.node);
+    };
+    assert_eq!(stmt.value.span, Span::new(8, 14));
+    let ir::Value::Number(one) = &items[0].node else {
+        panic!("expected a number");
+    };
+    assert_eq!((one.node, one.span), (1, Span::new(9, 10)));
+    assert!(matches!(&items[1].node, ir::Value::Var(y) if y.node == "y"));
+
+    // Ids are allocated in pre-order and index the span table
+    let ids: Vec<_> = [stmt.id, stmt.name.id, stmt.value.id, items[0].id, one.id]
+        .iter()
+        .map(|id| id.index())
+        .collect();
+    assert_eq!(ids, [0, 1, 2, 3, 4]);
+    assert_eq!(cx.span(items[1].id), Some(&Span::new(12, 13)));
+    assert!(cx.diagnostics().is_empty());
+}
+
+#[test]
+fn test_lower_diagnostics() {
+    let (stmt, cx) = lower("let self = 300;");
+    let diagnostics = cx.diagnostics();
+    assert_eq!(diagnostics.len(), 2);
+
+    assert_eq!(diagnostics[0].message, "`self` is reserved");
+    assert_eq!(diagnostics[0].node, Some(stmt.id));
+    assert_eq!(diagnostics[0].span, Some(stmt.span));
+
+    assert_eq!(diagnostics[1].severity, Severity::Error);
+    assert_eq!(diagnostics[1].node, Some(stmt.value.id));
+    assert_eq!(diagnostics[1].span, Some(Span::new(11, 14)));
+    assert!(cx.has_errors());
+}
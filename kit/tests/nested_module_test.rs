@@ -0,0 +1,169 @@
+//! Tests for `parser_kit!` invoked outside the crate root, and for several
+//! kits in one crate.
+
+mod errors {
+    use thiserror::Error;
+
+    #[derive(Error, Debug, Clone, Default, PartialEq)]
+    pub enum OtherError {
+        #[default]
+        #[error("unknown")]
+        Unknown,
+
+        #[error("expected {expect}, found {found}")]
+        Expected { expect: &'static str, found: String },
+
+        #[error("expected {expect}, found EOF")]
+        Empty { expect: &'static str },
+    }
+}
+
+/// synkit re-exported under another name, as a facade crate would.
+mod facade {
+    pub use synkit::*;
+}
+
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+mod grammar {
+    use super::LexError;
+
+    synkit::parser_kit! {
+        error: LexError,
+
+        self_path: crate::grammar,
+
+        skip_tokens: [Space],
+
+        tokens: {
+            #[token(" ", priority = 0)]
+            Space,
+
+            #[token("(")]
+            LParen,
+
+            #[token(")")]
+            RParen,
+
+            #[token("[")]
+            LBracket,
+
+            #[token("]")]
+            RBracket,
+
+            #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+            #[fmt("identifier")]
+            Ident(String),
+        },
+
+        delimiters: {
+            Paren => (LParen, RParen),
+            Bracket => (LBracket, RBracket),
+        },
+
+        span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+        token_derives: [Clone, PartialEq, Debug],
+    }
+}
+
+mod other {
+    synkit::parser_kit! {
+        error: crate::errors::OtherError,
+
+        crate_path: crate::facade,
+
+        skip_tokens: [],
+
+        tokens: {
+            #[token("<")]
+            LAngle,
+
+            #[token(">")]
+            RAngle,
+        },
+
+        delimiters: {
+            Angle => (LAngle, RAngle),
+        },
+
+        span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+        token_derives: [Clone, PartialEq, Debug],
+    }
+}
+
+use grammar::{Spanned, TokenStream, tokens::IdentToken};
+
+fn parse_list(stream: &mut TokenStream) -> Result<Vec<String>, LexError> {
+    let mut parens;
+    let _ = grammar::paren!(parens in stream);
+    let mut brackets;
+    let _ = grammar::delimiters::bracket!(brackets in parens);
+    let mut idents = Vec::new();
+    while !brackets.is_empty() {
+        let ident: Spanned<IdentToken> = brackets.parse()?;
+        idents.push(ident.value.0);
+    }
+    Ok(idents)
+}
+
+fn parse_empty_angle(stream: &mut other::TokenStream) -> Result<other::Span, errors::OtherError> {
+    let inner;
+    let angle = other::angle!(inner in stream);
+    assert!(inner.is_empty());
+    Ok(*angle.span())
+}
+
+#[test]
+fn test_delimiter_macros_in_nested_module() {
+    let mut stream = TokenStream::lex("([a b])").expect("lex failed");
+    assert_eq!(parse_list(&mut stream).expect("parse failed"), ["a", "b"]);
+}
+
+#[test]
+fn test_delimiter_macro_error() {
+    let mut stream = TokenStream::lex("[a]").expect("lex failed");
+    assert!(parse_list(&mut stream).is_err());
+}
+
+#[test]
+fn test_extract_method() {
+    let mut stream = TokenStream::lex("(a)").expect("lex failed");
+    let (inner, paren) = stream.extract_paren().expect("extract failed");
+    assert_eq!(inner.all().len(), 1);
+    assert_eq!(*paren.span(), grammar::Span::new(0, 3));
+}
+
+#[test]
+fn test_second_kit() {
+    let mut stream = other::TokenStream::lex("<>").expect("lex failed");
+    assert_eq!(parse_empty_angle(&mut stream), Ok(other::Span::new(0, 2)));
+}
+
+#[test]
+fn test_token_macros_with_self_path() {
+    use grammar::tokens::{SpannedTok, Tok};
+
+    let mut stream = TokenStream::lex("a b").expect("lex failed");
+    let a: SpannedTok![ident] = stream.parse().expect("ident");
+    let b: Spanned<Tok![ident]> = stream.parse().expect("ident");
+    assert_eq!((a.value.0, b.value.0), ("a".to_string(), "b".to_string()));
+}
+
+#[test]
+fn test_quote_tokens_with_self_path() {
+    let stream = grammar::quote_tokens! { (a) }.expect("quote failed");
+    assert_eq!(stream.len(), 3);
+}
@@ -0,0 +1,97 @@
+//! Tests for `normalize_newlines`: `\r\n` folded at lex time, with spans
+//! mapped back to the original text.
+
+use synkit::LineCol;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    normalize_newlines: true,
+    line_index: true,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token("\n")]
+        Newline,
+
+        #[token("=")]
+        Eq,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+    },
+
+    delimiters: {},
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+#[test]
+fn test_mixed_line_endings_lex_as_newlines() {
+    let source = "a = b\r\nc\nd\r\n";
+    let mut stream = TokenStream::lex(source).expect("lex failed");
+    assert_eq!(stream.source(), "a = b\nc\nd\n");
+    assert_eq!(stream.newline_map().len(), 2);
+
+    let mut original = Vec::new();
+    while let Some(tok) = stream.next() {
+        let range = stream.original_range(&tok.span);
+        original.push((tok.value.clone(), &source[range]));
+    }
+    assert_eq!(
+        original,
+        [
+            (Token::Ident("a".into()), "a"),
+            (Token::Eq, "="),
+            (Token::Ident("b".into()), "b"),
+            (Token::Newline, "\r\n"),
+            (Token::Ident("c".into()), "c"),
+            (Token::Newline, "\n"),
+            (Token::Ident("d".into()), "d"),
+            (Token::Newline, "\r\n"),
+        ]
+    );
+}
+
+#[test]
+fn test_line_index_counts_folded_lines() {
+    let mut stream = TokenStream::lex("a\r\n\r\n  b").expect("lex failed");
+    let _: Spanned<tokens::IdentToken> = stream.parse().expect("parse failed");
+    let _: Spanned<tokens::NewlineToken> = stream.parse().expect("parse failed");
+    let _: Spanned<tokens::NewlineToken> = stream.parse().expect("parse failed");
+    let b: Spanned<tokens::IdentToken> = stream.parse().expect("parse failed");
+    assert_eq!(b.span.line_col(&stream), LineCol { line: 3, column: 3 });
+    assert_eq!(stream.original_range(&b.span), 7..8);
+}
+
+#[test]
+fn test_from_tokens_keeps_offsets() {
+    let lexed = TokenStream::lex("a\r\nb").expect("lex failed");
+    let stream = TokenStream::from_tokens(
+        std::sync::Arc::from("a\nb"),
+        std::sync::Arc::new(lexed.all().to_vec()),
+    );
+    assert!(stream.newline_map().is_empty());
+    let b = &stream.all()[2];
+    assert_eq!(stream.original_range(&b.span), 2..3);
+}
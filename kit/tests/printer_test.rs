@@ -0,0 +1,393 @@
+//! Tests for the generated printer: source mapping and output helpers.
+
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token("=")]
+        Eq,
+
+        #[token(";")]
+        Semi,
+
+        #[token(",")]
+        Comma,
+
+        #[token("-")]
+        Minus,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+
+        #[regex(r"-?[0-9]+", |lex| lex.slice().parse().ok())]
+        #[fmt("number")]
+        Number(i64),
+    },
+
+    delimiters: {},
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+#[derive(Debug, Clone)]
+struct Assign {
+    name: Spanned<tokens::IdentToken>,
+    eq: Spanned<tokens::EqToken>,
+    value: Spanned<tokens::NumberToken>,
+    semi: Spanned<tokens::SemiToken>,
+}
+
+impl Parse for Assign {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        Ok(Self {
+            name: stream.parse()?,
+            eq: stream.parse()?,
+            value: stream.parse()?,
+            semi: stream.parse()?,
+        })
+    }
+}
+
+impl ToTokens for Assign {
+    fn write(&self, p: &mut Printer) {
+        use synkit::Printer as _;
+        self.name.write(p);
+        p.space();
+        self.eq.write(p);
+        p.space();
+        self.value.write(p);
+        self.semi.write(p);
+    }
+}
+
+fn parse_assigns(source: &str) -> Vec<Spanned<Assign>> {
+    let mut stream = TokenStream::lex(source).expect("lex failed");
+    let mut out = Vec::new();
+    while !stream.is_empty() {
+        out.push(stream.parse().expect("parse failed"));
+    }
+    out
+}
+
+#[test]
+fn test_source_map_disabled_by_default() {
+    let assigns = parse_assigns("x=1;");
+    let mut printer = Printer::new();
+    assigns.write(&mut printer);
+    assert!(printer.source_map().is_none());
+
+    let (output, map) = printer.finish_with_source_map();
+    assert_eq!(output, "x = 1;");
+    assert!(map.is_empty());
+}
+
+#[test]
+fn test_source_map_records_token_spans() {
+    let source = "x=1;  foo=42;";
+    let assigns = parse_assigns(source);
+    let (output, map) = assigns.to_string_with_source_map();
+    assert_eq!(output, "x = 1;foo = 42;");
+
+    // "42" printed at 12..14 came from 10..12 in the source
+    let entry = map.source_for_output(12).expect("mapping for `42`");
+    assert_eq!(entry.output, 12..14);
+    assert_eq!(&source[entry.source.clone()], "42");
+
+    // "foo" maps back to its source position
+    let entry = map.source_for_output(6).expect("mapping for `foo`");
+    assert_eq!(&source[entry.source.clone()], "foo");
+    assert_eq!(&output[entry.output.clone()], "foo");
+}
+
+#[test]
+fn test_source_map_parent_covers_children() {
+    let source = "x = 1;";
+    let assigns = parse_assigns(source);
+    let (output, map) = assigns.to_string_with_source_map();
+
+    // The space after `=` is only covered by the whole assignment
+    let entry = map.source_for_output(3).expect("mapping for whitespace");
+    assert_eq!(entry.output, 0..output.len());
+    assert_eq!(entry.source, 0..source.len());
+
+    let inner = map.output_for_source(4).expect("mapping for `1`");
+    assert_eq!(&output[inner.output.clone()], "1");
+}
+
+#[test]
+fn test_source_map_skips_call_site_spans() {
+    let assign = Spanned::call_site(Assign {
+        name: Spanned::call_site(tokens::IdentToken::new("y".to_string())),
+        eq: Spanned::call_site(tokens::EqToken::new()),
+        value: Spanned::new(7, 8, tokens::NumberToken::new(5)),
+        semi: Spanned::call_site(tokens::SemiToken::new()),
+    });
+
+    let (output, map) = assign.to_string_with_source_map();
+    assert_eq!(output, "y = 5;");
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.entries()[0].output, 4..5);
+    assert_eq!(map.entries()[0].source, 7..8);
+}
+
+mod comments {
+    use super::*;
+    use synkit::{CommentPlacement, CommentPolicy, Printer as _};
+
+    fn print_with(policy: CommentPolicy, comment: &str) -> String {
+        let assigns = parse_assigns("x=1;");
+        let mut p = Printer::new().with_comment_policy(policy);
+        p.word("[table]");
+        p.indent();
+        p.newline();
+        assigns.write(&mut p);
+        p.comment("#", comment);
+        p.newline();
+        p.comment("#", "done");
+        p.into_string()
+    }
+
+    #[test]
+    fn test_same_line_by_default() {
+        let output = print_with(CommentPolicy::new(), "  the answer ");
+        assert_eq!(output, "[table]\n    x = 1; # the answer\n    # done");
+    }
+
+    #[test]
+    fn test_move_above() {
+        let policy = CommentPolicy::new().with_placement(CommentPlacement::Above);
+        let output = print_with(policy, "the answer");
+        assert_eq!(output, "[table]\n    # the answer\n    x = 1;\n    # done");
+    }
+
+    #[test]
+    fn test_wrap_moves_long_comments_above() {
+        let policy = CommentPolicy::new().with_wrap_width(20);
+        let output = print_with(policy, "short");
+        assert_eq!(output, "[table]\n    x = 1; # short\n    # done");
+
+        let output = print_with(policy, "a comment  that is too long");
+        assert_eq!(
+            output,
+            "[table]\n    # a comment that\n    # is too long\n    x = 1;\n    # done"
+        );
+    }
+
+    #[test]
+    fn test_wrap_on_empty_line() {
+        let mut p = Printer::new().with_comment_policy(CommentPolicy::new().with_wrap_width(12));
+        p.comment("//", "one two three four");
+        assert_eq!(p.into_string(), "// one two\n// three\n// four");
+    }
+
+    #[test]
+    fn test_move_above_keeps_source_map() {
+        let source = "x=1;";
+        let assigns = parse_assigns(source);
+        let policy = CommentPolicy::new().with_placement(CommentPlacement::Above);
+        let mut p = Printer::new().with_source_map().with_comment_policy(policy);
+        assigns.write(&mut p);
+        p.comment("#", "c");
+        let (output, map) = p.finish_with_source_map();
+        assert_eq!(output, "# c\nx = 1;");
+
+        let entry = map.output_for_source(2).expect("mapping for `1`");
+        assert_eq!(&output[entry.output.clone()], "1");
+    }
+}
+
+mod whitespace {
+    use super::*;
+    use synkit::Printer as _;
+
+    #[test]
+    fn test_ensure_space() {
+        let mut p = Printer::new();
+        p.ensure_space();
+        p.word("a");
+        p.ensure_space();
+        p.ensure_space();
+        p.word("b");
+        p.space();
+        p.ensure_space();
+        p.word("c");
+        p.indent();
+        p.newline();
+        p.ensure_space();
+        p.word("d");
+        assert_eq!(p.last_char(), Some('d'));
+        assert_eq!(p.into_string(), "a b c\n    d");
+    }
+
+    #[test]
+    fn test_no_space() {
+        let mut p = Printer::new();
+        p.word("a");
+        p.spaces(2);
+        p.no_space();
+        p.word(",");
+        p.indent();
+        p.newline();
+        p.no_space();
+        p.word("b");
+        assert_eq!(p.into_string(), "a,\n    b");
+    }
+
+    #[test]
+    fn test_blank_lines() {
+        let mut p = Printer::new();
+        p.blank_lines(2);
+        p.word("a");
+        p.newline();
+        p.newline();
+        p.newline();
+        p.blank_lines(1);
+        p.word("b ");
+        p.indent();
+        p.blank_lines(0);
+        p.word("c");
+        assert_eq!(p.into_string(), "a\n\nb\n    c");
+    }
+}
+
+mod separation {
+    use super::*;
+    use synkit::Printer as _;
+
+    fn print(tokens: &[Token]) -> String {
+        let mut p = Printer::new();
+        for t in tokens {
+            p.token(t);
+        }
+        p.into_string()
+    }
+
+    #[test]
+    fn test_adjacent_idents_are_separated() {
+        let a = Token::Ident("a".into());
+        let b = Token::Ident("b".into());
+        assert_eq!(print(&[a.clone(), b, Token::Eq, a]), "a b=a");
+    }
+
+    #[test]
+    fn test_minus_before_number() {
+        assert_eq!(print(&[Token::Minus, Token::Number(1)]), "- 1");
+        assert_eq!(print(&[Token::Minus, Token::Number(-1)]), "--1");
+        assert_eq!(print(&[Token::Ident("x".into()), Token::Number(1)]), "x1");
+    }
+
+    #[test]
+    fn test_only_directly_adjacent_tokens() {
+        let mut p = Printer::new();
+        p.token(&Token::Ident("a".into()));
+        p.word(" ");
+        p.token(&Token::Ident("b".into()));
+        p.word("c");
+        p.token(&Token::Ident("d".into()));
+        assert_eq!(p.into_string(), "a bcd");
+    }
+
+    #[test]
+    fn test_separation_disabled() {
+        let mut p = Printer::new().with_token_separation(false);
+        p.token(&Token::Minus);
+        p.token(&Token::Number(2));
+        assert_eq!(p.into_string(), "-2");
+    }
+
+    #[test]
+    fn test_tokens_merge() {
+        assert!(Printer::tokens_merge("ab", "c"));
+        assert!(Printer::tokens_merge("-", "3"));
+        assert!(!Printer::tokens_merge("=", "-3"));
+        assert!(!Printer::tokens_merge("a b", "c"));
+    }
+}
+
+mod trailing_separators {
+    use super::*;
+    use synkit::{Printer as _, Punctuated, Separated, Terminated, TrailingPolicy};
+
+    type Names = Punctuated<Spanned<tokens::IdentToken>, Spanned<tokens::CommaToken>>;
+
+    fn names(source: &str) -> Names {
+        let mut stream = TokenStream::lex(source).expect("lex failed");
+        let mut names = Names::new();
+        while !stream.is_empty() {
+            names.push_value(stream.parse().expect("parse name"));
+            if stream.peek::<tokens::CommaToken>() {
+                names.push_punct(stream.parse().expect("parse comma"));
+            }
+        }
+        names
+    }
+
+    fn print_with<T: ToTokens>(value: &T, policy: TrailingPolicy) -> String {
+        let mut p = Printer::new().with_trailing_policy(policy);
+        value.write(&mut p);
+        p.into_string()
+    }
+
+    #[test]
+    fn test_preserved_by_default() {
+        assert_eq!(names("a,b,").to_string_formatted(), "a, b,");
+        assert_eq!(names("a,b").to_string_formatted(), "a, b");
+    }
+
+    #[test]
+    fn test_printer_policy() {
+        for source in ["a , b", "a, b,"] {
+            let names = names(source);
+            assert_eq!(print_with(&names, TrailingPolicy::Required), "a, b,");
+            assert_eq!(print_with(&names, TrailingPolicy::Forbidden), "a, b");
+        }
+        assert_eq!(print_with(&names("a"), TrailingPolicy::Required), "a,");
+        assert_eq!(print_with(&Names::new(), TrailingPolicy::Required), "");
+    }
+
+    #[test]
+    fn test_inserted_separator_is_unmapped() {
+        let names = names("a");
+        let mut p = Printer::new()
+            .with_trailing_policy(TrailingPolicy::Required)
+            .with_source_map();
+        names.write(&mut p);
+        let (output, map) = p.finish_with_source_map();
+        assert_eq!(output, "a,");
+        assert!(map.source_for_output(1).is_none());
+    }
+
+    #[test]
+    fn test_terminated_and_separated_keep_their_policy() {
+        let mut stmts = Terminated::<tokens::IdentToken, tokens::SemiToken>::new();
+        stmts.push_value(tokens::IdentToken::new("a"));
+        assert_eq!(print_with(&stmts, TrailingPolicy::Forbidden), "a;");
+
+        let mut args = Separated::<tokens::IdentToken, tokens::CommaToken>::new();
+        args.push_value(tokens::IdentToken::new("a"));
+        args.push_punct(tokens::CommaToken::new());
+        assert_eq!(print_with(&args, TrailingPolicy::Required), "a");
+    }
+}
@@ -0,0 +1,125 @@
+//! Tests for `provenance`: spans that record whether their code was parsed
+//! or generated.
+
+use std::num::NonZeroU32;
+
+use synkit::{Provenance, SpanLike, ToolId};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    provenance: true,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+    },
+
+    delimiters: {},
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+mod plain {
+    use super::LexError;
+
+    synkit::parser_kit! {
+        error: LexError,
+
+        tokens: {
+            #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+            Ident(String),
+        },
+
+        delimiters: {},
+
+        span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+        token_derives: [Clone, PartialEq, Debug],
+    }
+}
+
+const EXPANDER: ToolId = ToolId::new(NonZeroU32::MIN);
+
+fn idents(source: &str) -> Vec<Spanned<tokens::IdentToken>> {
+    let mut stream = TokenStream::lex(source).expect("lex failed");
+    let mut idents = Vec::new();
+    while !stream.is_empty() {
+        idents.push(stream.parse().expect("parse failed"));
+    }
+    idents
+}
+
+#[test]
+fn test_lexed_spans_are_user_source() {
+    for ident in idents("ab cd") {
+        assert_eq!(ident.span.provenance(), Some(Provenance::UserSource));
+        assert!(!ident.span.is_generated());
+    }
+}
+
+#[test]
+fn test_generated_spans() {
+    let span = Span::new(3, 5).generated_by(EXPANDER);
+    assert_eq!(span.provenance(), Some(Provenance::Generated(EXPANDER)));
+    assert!(span.is_generated());
+    assert_eq!((span.start(), span.end()), (3, 5));
+
+    // Re-ranged and joined spans keep it
+    assert!(SpanLike::with_range(&span, 0, 1).is_generated());
+    assert!(Span::new(0, 1).join(&span).is_generated());
+    assert!(!Span::new(0, 1).join(&Span::new(4, 6)).is_generated());
+
+    assert_eq!(
+        span.with_provenance(Provenance::UserSource),
+        Span::new(3, 5)
+    );
+}
+
+#[test]
+fn test_call_site_spans() {
+    assert_eq!(Span::CallSite.provenance(), None);
+    assert!(Span::CallSite.is_generated());
+    assert_eq!(Span::CallSite.generated_by(EXPANDER), Span::CallSite);
+}
+
+#[test]
+fn test_filter_generated_errors() {
+    let mut nodes = idents("ab cd ef");
+    nodes[1].span = nodes[1].span.generated_by(EXPANDER);
+    let user: Vec<&str> = nodes
+        .iter()
+        .filter(|node| !node.span.is_generated())
+        .map(|node| node.value.as_str())
+        .collect();
+    assert_eq!(user, ["ab", "ef"]);
+}
+
+#[test]
+fn test_default_provenance() {
+    // Without `provenance`, `SpanLike` reports known spans as user source
+    let span = plain::Span::new(0, 2);
+    assert_eq!(SpanLike::provenance(&span), Some(Provenance::UserSource));
+    assert!(!SpanLike::is_generated(&span));
+    assert!(SpanLike::is_generated(&plain::Span::CallSite));
+}
@@ -0,0 +1,155 @@
+//! Tests for the `pyo3` helpers and `python_module!`.
+#![cfg(feature = "pyo3")]
+
+use synkit::SpannedError as _;
+use synkit::python::pyo3::prelude::*;
+use synkit::python::pyo3::types::{PyDict, PyModule};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+
+    #[error("{source}")]
+    Spanned { source: Box<LexError>, span: Span },
+}
+
+impl synkit::SpannedError for LexError {
+    type Span = Span;
+
+    fn with_span(self, span: Span) -> Self {
+        Self::Spanned {
+            source: Box::new(self),
+            span,
+        }
+    }
+
+    fn span(&self) -> Option<&Span> {
+        match self {
+            Self::Spanned { span, .. } => Some(span),
+            _ => None,
+        }
+    }
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space, Newline],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token("\n", priority = 0)]
+        Newline,
+
+        #[token(",")]
+        Comma,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+
+        #[regex(r"[0-9]+", |lex| lex.slice().parse().ok())]
+        #[fmt("number")]
+        Number(i64),
+    },
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+/// Parse whitespace-separated numbers.
+fn parse_numbers(input: &str) -> Result<Vec<i64>, LexError> {
+    let mut stream = TokenStream::lex(input)?;
+    let mut numbers = Vec::new();
+    while let Some(token) = stream.next() {
+        match token.value {
+            Token::Number(n) => numbers.push(n),
+            other => {
+                return Err(LexError::Expected {
+                    expect: "number",
+                    found: other.to_string(),
+                }
+                .with_span(token.span));
+            }
+        }
+    }
+    Ok(numbers)
+}
+
+synkit::python_module! {
+    /// Test module.
+    numbers {
+        parse_numbers => parse_numbers,
+    }
+}
+
+fn with_module<F: FnOnce(&Bound<'_, PyModule>)>(f: F) {
+    Python::initialize();
+    Python::attach(|py| {
+        let module = PyModule::new(py, "numbers").unwrap();
+        numbers(&module).unwrap();
+        f(&module);
+    });
+}
+
+#[test]
+fn test_python_parse_ok() {
+    with_module(|module| {
+        let result = module.call_method1("parse_numbers", ("1 2 3",)).unwrap();
+        let values: Vec<i64> = result.extract().unwrap();
+        assert_eq!(values, vec![1, 2, 3]);
+    });
+}
+
+#[test]
+fn test_python_parse_error_diagnostic() {
+    with_module(|module| {
+        let err = module
+            .call_method1("parse_numbers", ("1\n2 x",))
+            .unwrap_err();
+        let py = module.py();
+        assert!(err.is_instance_of::<synkit::python::ParseError>(py));
+
+        let (message, diagnostic): (String, Bound<'_, PyDict>) =
+            err.value(py).getattr("args").unwrap().extract().unwrap();
+        assert_eq!(message, "2:3: expected number, found x");
+
+        let line: usize = diagnostic
+            .get_item("line")
+            .unwrap()
+            .unwrap()
+            .extract()
+            .unwrap();
+        let column: usize = diagnostic
+            .get_item("column")
+            .unwrap()
+            .unwrap()
+            .extract()
+            .unwrap();
+        let start: usize = diagnostic
+            .get_item("start")
+            .unwrap()
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert_eq!((line, column, start), (2, 3, 4));
+    });
+}
+
+#[test]
+fn test_python_module_exports_parse_error() {
+    with_module(|module| {
+        assert!(module.getattr("ParseError").is_ok());
+    });
+}
@@ -0,0 +1,203 @@
+//! Tests for `Query` selectors over derived `QueryNode` trees.
+
+use synkit::{Query, QueryNode};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token("=")]
+        Eq,
+
+        #[token("[")]
+        LBracket,
+
+        #[token("]")]
+        RBracket,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+
+        #[regex(r"[0-9]+", |lex| lex.slice().parse::<i64>().ok())]
+        #[fmt("number")]
+        Number(i64),
+    },
+
+    delimiters: {
+        Bracket => (LBracket, RBracket),
+    },
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+#[derive(Debug, synkit::QueryNode)]
+struct Document {
+    tables: Vec<Spanned<Table>>,
+}
+
+#[derive(Debug, synkit::QueryNode)]
+struct Table {
+    lbracket: Spanned<tokens::LBracketToken>,
+    name: Spanned<tokens::IdentToken>,
+    rbracket: Spanned<tokens::RBracketToken>,
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, synkit::QueryNode)]
+enum Entry {
+    Pair(Box<Spanned<KeyValue>>),
+}
+
+#[derive(Debug, synkit::QueryNode)]
+struct KeyValue {
+    key: Spanned<tokens::IdentToken>,
+    eq: Spanned<tokens::EqToken>,
+    value: Spanned<Value>,
+}
+
+#[derive(Debug, synkit::QueryNode)]
+enum Value {
+    Number(Spanned<tokens::NumberToken>),
+    Ident(Spanned<tokens::IdentToken>),
+}
+
+impl Parse for Value {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        if tokens::NumberToken::peek(stream) {
+            Ok(Value::Number(stream.parse()?))
+        } else {
+            Ok(Value::Ident(stream.parse()?))
+        }
+    }
+}
+
+impl Parse for KeyValue {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        Ok(Self {
+            key: stream.parse()?,
+            eq: stream.parse()?,
+            value: stream.parse()?,
+        })
+    }
+}
+
+impl Parse for Table {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        let lbracket = stream.parse()?;
+        let name = stream.parse()?;
+        let rbracket = stream.parse()?;
+        let mut entries = Vec::new();
+        while tokens::IdentToken::peek(stream) {
+            entries.push(Entry::Pair(Box::new(stream.parse()?)));
+        }
+        Ok(Self {
+            lbracket,
+            name,
+            rbracket,
+            entries,
+        })
+    }
+}
+
+impl Parse for Document {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        let mut tables = Vec::new();
+        while !stream.is_empty() {
+            tables.push(stream.parse()?);
+        }
+        Ok(Self { tables })
+    }
+}
+
+const SOURCE: &str = "[server] host = local port = 80 [client] port = 81";
+
+fn document() -> Document {
+    let mut stream = TokenStream::lex(SOURCE).expect("lex failed");
+    stream.parse_value().expect("parse failed")
+}
+
+#[test]
+fn test_child_with_predicate() {
+    let doc = document();
+    let found = synkit::query!("Table > KeyValue[key='port']").find_all(&doc);
+    let spans: Vec<_> = found.iter().map(|m| m.span().unwrap()).collect();
+    assert_eq!(spans, [22..31, 41..50]);
+    assert_eq!(&SOURCE[spans[0].clone()], "port = 80");
+
+    let kv = found[1].downcast_ref::<KeyValue>().unwrap();
+    assert_eq!(kv.value.text().as_deref(), Some("81"));
+}
+
+#[test]
+fn test_descendant_and_text() {
+    let doc = document();
+    let found = synkit::query!("Table[name='client'] NumberToken")
+        .find(&doc)
+        .unwrap();
+    assert_eq!(found.kind(), "NumberToken");
+    assert_eq!(found.text().as_deref(), Some("81"));
+    assert_eq!(found.span(), Some(48..50));
+
+    let hosts = synkit::query!("KeyValue[key='host'] > IdentToken[.!='host']").find_all(&doc);
+    assert_eq!(hosts.len(), 1);
+    assert_eq!(hosts[0].text().as_deref(), Some("local"));
+}
+
+#[test]
+fn test_enums_are_transparent() {
+    let doc = document();
+    let kinds: Vec<_> = synkit::query!("Table[name='server'] > KeyValue > *")
+        .find_all(&doc)
+        .iter()
+        .map(|m| m.kind())
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            "IdentToken",
+            "EqToken",
+            "IdentToken",
+            "IdentToken",
+            "EqToken",
+            "NumberToken"
+        ]
+    );
+    assert!(Query::parse("Entry").unwrap().find_all(&doc).is_empty());
+    assert_eq!(doc.kind(), Some("Document"));
+}
+
+#[test]
+fn test_runtime_queries() {
+    let doc = document();
+    let query: Query = "Document > Table[name='server'] > KeyValue[value='80']"
+        .parse()
+        .unwrap();
+    assert_eq!(query.find_all(&doc).len(), 1);
+
+    let err = Query::parse("Table >> KeyValue").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid query at 7: expected a node kind or `*`"
+    );
+}
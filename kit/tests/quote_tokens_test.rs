@@ -0,0 +1,282 @@
+//! Tests for the generated `quote_tokens!` macro and `MutTokenStream` editing.
+
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token("let")]
+        KwLet,
+
+        #[token("=")]
+        Eq,
+
+        #[token("==")]
+        EqEq,
+
+        #[token("->")]
+        Arrow,
+
+        #[token(";")]
+        Semi,
+
+        #[token("(")]
+        LParen,
+
+        #[token(")")]
+        RParen,
+
+        #[token(",")]
+        Comma,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+
+        #[regex(r"[0-9]+", |lex| lex.slice().parse().ok())]
+        #[fmt("number")]
+        Number(i64),
+    },
+
+    delimiters: {
+        Paren => (LParen, RParen),
+    },
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+fn values(stream: &MutTokenStream) -> Vec<Token> {
+    stream
+        .all_tokens()
+        .iter()
+        .map(|t| t.value.clone())
+        .collect()
+}
+
+#[test]
+fn test_quote_literal_tokens() {
+    let stream = quote_tokens! { let x = 1; }.expect("quote failed");
+    assert_eq!(
+        values(&stream),
+        vec![
+            Token::KwLet,
+            Token::Ident("x".to_string()),
+            Token::Eq,
+            Token::Number(1),
+            Token::Semi,
+        ]
+    );
+    assert!(stream.all_tokens().iter().all(|t| t.span == Span::CallSite));
+}
+
+#[test]
+fn test_quote_interpolation() {
+    let name = tokens::IdentToken::new("count".to_string());
+    let value = tokens::NumberToken::new(42);
+    let stream = quote_tokens! { let #name = #value; }.expect("quote failed");
+    assert_eq!(
+        values(&stream),
+        vec![
+            Token::KwLet,
+            Token::Ident("count".to_string()),
+            Token::Eq,
+            Token::Number(42),
+            Token::Semi,
+        ]
+    );
+}
+
+#[test]
+fn test_quote_nested_groups() {
+    let arg = tokens::IdentToken::new("a".to_string());
+    let stream = quote_tokens! { f(#arg, (2)) }.expect("quote failed");
+    assert_eq!(
+        values(&stream),
+        vec![
+            Token::Ident("f".to_string()),
+            Token::LParen,
+            Token::Ident("a".to_string()),
+            Token::Comma,
+            Token::LParen,
+            Token::Number(2),
+            Token::RParen,
+            Token::RParen,
+        ]
+    );
+}
+
+#[test]
+fn test_quote_multi_char_punct() {
+    let rhs = tokens::NumberToken::new(2);
+    let stream = quote_tokens! { f() -> x == #rhs = = }.expect("quote failed");
+    assert_eq!(
+        values(&stream),
+        vec![
+            Token::Ident("f".to_string()),
+            Token::LParen,
+            Token::RParen,
+            Token::Arrow,
+            Token::Ident("x".to_string()),
+            Token::EqEq,
+            Token::Number(2),
+            Token::Eq,
+            Token::Eq,
+        ]
+    );
+}
+
+#[test]
+fn test_quote_empty() {
+    let stream = quote_tokens! {}.expect("quote failed");
+    assert!(stream.all_tokens().is_empty());
+}
+
+#[test]
+fn test_quote_unknown_token_errors() {
+    assert!(quote_tokens! { let x = [1]; }.is_err());
+}
+
+#[test]
+fn test_mut_stream_insert_remove() {
+    let mut stream = quote_tokens! { let x = 1; }.expect("quote failed");
+    assert_eq!(stream.len(), 5);
+
+    let removed = stream.remove(1).expect("token at index 1");
+    assert_eq!(removed.value, Token::Ident("x".to_string()));
+    assert!(stream.remove(99).is_none());
+
+    stream.insert(1, Spanned::call_site(Token::Ident("y".to_string())));
+    assert_eq!(values(&stream)[1], Token::Ident("y".to_string()));
+
+    // Out-of-range inserts clamp to the end
+    stream.insert(99, Spanned::call_site(Token::Semi));
+    assert_eq!(values(&stream).last(), Some(&Token::Semi));
+    assert_eq!(stream.len(), 6);
+}
+
+#[test]
+fn test_mut_stream_replace_and_splice() {
+    let mut stream = quote_tokens! { let x = 1; }.expect("quote failed");
+    let value = quote_tokens! { f(2) }.expect("quote failed");
+
+    let removed = stream.replace_range(3..4, value);
+    assert_eq!(
+        values(&MutTokenStream::from(removed)),
+        vec![Token::Number(1)]
+    );
+    assert_eq!(
+        values(&stream),
+        vec![
+            Token::KwLet,
+            Token::Ident("x".to_string()),
+            Token::Eq,
+            Token::Ident("f".to_string()),
+            Token::LParen,
+            Token::Number(2),
+            Token::RParen,
+            Token::Semi,
+        ]
+    );
+
+    let prefix = quote_tokens! { let y = 0; }.expect("quote failed");
+    stream.splice(0, prefix);
+    assert_eq!(stream.len(), 13);
+
+    let drained = stream.remove_range(5..);
+    assert_eq!(drained.len(), 8);
+    assert_eq!(stream.len(), 5);
+    assert!(stream.remove_range(10..20).is_empty());
+}
+
+#[test]
+fn test_mut_stream_respan() {
+    let mut stream: MutTokenStream = vec![
+        Spanned::new(4, 5, Token::Ident("a".to_string())),
+        Spanned::new(6, 7, Token::Eq),
+        Spanned::call_site(Token::Number(1)),
+    ]
+    .into();
+
+    assert_eq!(stream.covering_span(), Span::new(4, 7));
+
+    stream.shift_spans(-4);
+    assert_eq!(stream.all_tokens()[0].span, Span::new(0, 1));
+    assert_eq!(stream.all_tokens()[2].span, Span::CallSite);
+    assert_eq!(stream.covering_span(), Span::new(0, 3));
+
+    stream.respan_call_site();
+    assert_eq!(stream.covering_span(), Span::CallSite);
+}
+
+#[test]
+fn test_mut_stream_reparse() {
+    use synkit::TokenStream as _;
+
+    let lexed = TokenStream::lex("let x = 1;").expect("lex failed");
+    let mut editable = lexed.to_mut_stream();
+    editable.retain(|t| t.value != Token::Space);
+    assert_eq!(editable.len(), 5);
+
+    editable.replace_range(3..4, quote_tokens! { 7 }.expect("quote failed"));
+
+    let mut stream: TokenStream = editable.into();
+    let _: Spanned<tokens::KwLetToken> = stream.parse().expect("let");
+    let name: Spanned<tokens::IdentToken> = stream.parse().expect("ident");
+    let _: Spanned<tokens::EqToken> = stream.parse().expect("eq");
+    let value: Spanned<tokens::NumberToken> = stream.parse().expect("number");
+    let _: Spanned<tokens::SemiToken> = stream.parse().expect("semi");
+
+    assert_eq!(*name.value, "x");
+    assert_eq!(*value.value, 7);
+    assert!(stream.ensure_consumed().is_ok());
+}
+
+#[test]
+fn test_synthetic_tokens() {
+    let mut editable = MutTokenStream::new();
+    editable.push_synthetic(Token::kw_let());
+    editable.push(Token::ident("x").spanned());
+    editable.push_synthetic(Token::eq());
+    editable.push(SpannedToken::synthetic(Token::number(7)));
+    editable.push_synthetic(Token::semi());
+    assert!(
+        editable
+            .all_tokens()
+            .iter()
+            .all(|t| t.span == Span::CallSite)
+    );
+
+    let mut stream: TokenStream = editable.into();
+    let _: Spanned<tokens::KwLetToken> = stream.parse().expect("let");
+    let name: Spanned<tokens::IdentToken> = stream.parse().expect("ident");
+    assert_eq!(name.value, tokens::IdentToken::new("x"));
+
+    let _: Spanned<tokens::EqToken> = stream.parse().expect("eq");
+    let value: Spanned<tokens::NumberToken> = stream.parse().expect("number");
+    assert_eq!(*value.value, 7);
+
+    // Token structs build call-site AST nodes directly
+    let node = tokens::NumberToken::new(7).spanned();
+    assert_eq!(node.span, Span::CallSite);
+    assert_eq!(node.value, value.value);
+}
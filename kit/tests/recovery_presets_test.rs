@@ -0,0 +1,107 @@
+//! Tests for `parse_or_recover` with the `synkit::recovery` strategies.
+
+use synkit::recovery::{SkipToClose, SkipToLineStart, SkipToSeparator};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[regex(r"[ \n]+")]
+        Space,
+
+        #[token(",")]
+        Comma,
+
+        #[token("(")]
+        LParen,
+
+        #[token(")")]
+        RParen,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+
+        #[regex(r"[0-9]+", |lex| lex.slice().parse().ok())]
+        #[fmt("number")]
+        Number(i64),
+    },
+
+    delimiters: {
+        Paren => (LParen, RParen),
+    },
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+use stream::Recovered;
+
+/// Each item as its number, or the text of its error node.
+fn summarize(items: &[Recovered<tokens::NumberToken>]) -> Vec<String> {
+    items
+        .iter()
+        .map(|item| match item {
+            Recovered::Ok(number) => number.value.to_string(),
+            Recovered::Error(node) => {
+                let text: Vec<String> = node.tokens.iter().map(|t| t.value.to_string()).collect();
+                format!("error: {}", text.join(" "))
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn test_skip_to_separator() {
+    let mut stream = TokenStream::lex("(1, x y, (2, 3) z, 4)").expect("lex failed");
+    let (mut args, _) = stream
+        .extract_inner::<tokens::LParenToken, tokens::RParenToken>()
+        .expect("extract failed");
+
+    let mut items = Vec::new();
+    while !args.is_empty() {
+        items.push(args.parse_or_recover(SkipToSeparator::<tokens::CommaToken>::new()));
+        let _ = args.parse::<tokens::CommaToken>();
+    }
+    assert_eq!(
+        summarize(&items),
+        ["1", "error: x y", "error: ( 2 , 3 ) z", "4"]
+    );
+}
+
+#[test]
+fn test_skip_to_close() {
+    let mut stream = TokenStream::lex("(a (b, c) d) 5").expect("lex failed");
+    let _: Spanned<tokens::LParenToken> = stream.parse().expect("open");
+
+    let item = stream.parse_or_recover::<tokens::NumberToken>(SkipToClose::new());
+    assert_eq!(summarize(&[item]), ["error: a ( b , c ) d"]);
+    let _: Spanned<tokens::RParenToken> = stream.parse().expect("close");
+    assert!(stream.peek::<tokens::NumberToken>());
+}
+
+#[test]
+fn test_skip_to_line_start() {
+    let mut stream = TokenStream::lex("1\nx y, z\n  2\n").expect("lex failed");
+    let mut items = Vec::new();
+    while !stream.is_empty() {
+        items.push(stream.parse_or_recover(SkipToLineStart));
+    }
+    assert_eq!(summarize(&items), ["1", "error: x y , z", "2"]);
+}
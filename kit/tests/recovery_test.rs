@@ -0,0 +1,156 @@
+//! Tests for `parse_or_error`: error nodes instead of failed parses.
+
+use synkit::SpanLike;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token("=")]
+        Eq,
+
+        #[token(";")]
+        Semi,
+
+        #[token("let")]
+        Let,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+
+        #[regex(r"[0-9]+", |lex| lex.slice().parse().ok())]
+        #[fmt("number")]
+        Number(i64),
+    },
+
+    delimiters: {},
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+use stream::Recovered;
+
+/// `let name = value;`
+#[derive(Debug, Clone)]
+struct Let {
+    name: Spanned<tokens::IdentToken>,
+    value: Spanned<tokens::NumberToken>,
+}
+
+impl Parse for Let {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        let _: Spanned<tokens::LetToken> = stream.parse()?;
+        let name = stream.parse()?;
+        let _: Spanned<tokens::EqToken> = stream.parse()?;
+        let value = stream.parse()?;
+        let _: Spanned<tokens::SemiToken> = stream.parse()?;
+        Ok(Self { name, value })
+    }
+}
+
+impl Peek for Let {
+    fn is(token: &Token) -> bool {
+        tokens::LetToken::is(token)
+    }
+}
+
+impl ToTokens for Let {
+    fn write(&self, p: &mut Printer) {
+        use synkit::Printer as _;
+        p.word("let ");
+        self.name.write(p);
+        p.word(" = ");
+        self.value.write(p);
+        p.word(";");
+    }
+}
+
+fn parse_all(source: &str) -> Vec<Recovered<Let>> {
+    let mut stream = TokenStream::lex(source).expect("lex failed");
+    let mut out = Vec::new();
+    while !stream.is_empty() {
+        out.push(stream.parse_or_error::<Let>());
+    }
+    out
+}
+
+#[test]
+fn test_valid_input_has_no_errors() {
+    let items = parse_all("let a = 1; let b = 2;");
+    assert_eq!(items.len(), 2);
+    assert!(items.iter().all(Recovered::is_ok));
+    assert_eq!(items[1].ok().expect("b").value.name.as_str(), "b");
+}
+
+#[test]
+fn test_skips_to_next_statement() {
+    let source = "let a = 1; let b = x y; let c = 3;";
+    let items = parse_all(source);
+    assert_eq!(items.len(), 3);
+    assert!(items[0].is_ok());
+    assert!(items[2].is_ok());
+
+    let node = items[1].error().expect("error node");
+    assert_eq!(node.tokens.len(), 6);
+    let span = items[1].span();
+    assert_eq!(&source[span.start()..span.end()], "let b = x y;");
+    assert_eq!(
+        node.diag,
+        LexError::Expected {
+            expect: "number",
+            found: "x after `=`".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_always_makes_progress() {
+    let items = parse_all("; ; let a = 1;");
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0].error().expect("error node").tokens.len(), 2);
+    assert!(items[1].is_ok());
+}
+
+#[test]
+fn test_error_at_end_of_input() {
+    let mut stream = TokenStream::lex("").expect("lex failed");
+    let item = stream.parse_or_error::<Let>();
+    let node = item.error().expect("error node");
+    assert!(node.tokens.is_empty());
+    assert_eq!(node.diag, LexError::Empty { expect: "let" });
+}
+
+#[test]
+fn test_custom_recovery_point() {
+    let mut stream = TokenStream::lex("let = 1; let a = 2;").expect("lex failed");
+    let item = stream.parse_or_error_with::<Let>(|tok| matches!(tok, Token::Semi));
+    assert_eq!(item.error().expect("error node").tokens.len(), 3);
+    assert!(stream.peek::<tokens::SemiToken>());
+}
+
+#[test]
+fn test_error_nodes_print_their_tokens() {
+    let items = parse_all("let a = 1;let b=;");
+    assert_eq!(items.to_string_formatted(), "let a = 1;let b=;");
+}
@@ -0,0 +1,139 @@
+//! Tests for applying span-based edits with `Rewriter`.
+
+use synkit::{RewriteError, Rewriter, SpanLike};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space, Newline],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token("\n", priority = 0)]
+        Newline,
+
+        #[token("=")]
+        Eq,
+
+        #[token(";")]
+        Semi,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+
+        #[regex(r"[0-9]+", |lex| lex.slice().parse::<i64>().ok())]
+        #[fmt("number")]
+        Number(i64),
+    },
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+#[derive(Debug)]
+struct Assignment {
+    name: Spanned<tokens::IdentToken>,
+    _eq: Spanned<tokens::EqToken>,
+    value: Spanned<tokens::NumberToken>,
+    _semi: Spanned<tokens::SemiToken>,
+}
+
+impl Parse for Assignment {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        Ok(Self {
+            name: stream.parse()?,
+            _eq: stream.parse()?,
+            value: stream.parse()?,
+            _semi: stream.parse()?,
+        })
+    }
+}
+
+fn parse(source: &str) -> Result<Vec<Assignment>, LexError> {
+    let mut stream = TokenStream::lex(source)?;
+    let mut assignments = Vec::new();
+    while !stream.is_empty() {
+        assignments.push(stream.parse_value()?);
+    }
+    Ok(assignments)
+}
+
+const SOURCE: &str = "port = 80; host = 1; port = 81;";
+
+#[test]
+fn test_rename_and_reparse() {
+    let assignments = parse(SOURCE).unwrap();
+    let mut rewriter = Rewriter::new(SOURCE);
+    for assignment in assignments.iter().filter(|a| a.name.value.0 == "port") {
+        rewriter.replace(&assignment.name.span, "listen").unwrap();
+        rewriter.insert_after(&assignment.value.span, "0").unwrap();
+    }
+    let (output, reparsed) = rewriter.apply_verified(parse).unwrap();
+    assert_eq!(output, "listen = 800; host = 1; listen = 810;");
+    assert_eq!(reparsed[2].value.value.0, 810);
+    assert_eq!(
+        &output[rewriter.map_offset(assignments[1].name.span.start())..][..4],
+        "host"
+    );
+}
+
+#[test]
+fn test_verification_catches_broken_output() {
+    let assignments = parse(SOURCE).unwrap();
+    let mut rewriter = Rewriter::new(SOURCE);
+    rewriter.delete(&assignments[0]._semi.span).unwrap();
+    assert!(rewriter.apply_verified(parse).is_err());
+}
+
+#[test]
+fn test_rejected_edits() {
+    let assignments = parse(SOURCE).unwrap();
+    let mut rewriter = Rewriter::new(SOURCE);
+    rewriter.replace(&assignments[0].name.span, "a").unwrap();
+    assert_eq!(
+        rewriter.replace(&assignments[0].name.span, "b").err(),
+        Some(RewriteError::Overlap {
+            range: 0..4,
+            existing: 0..4
+        })
+    );
+
+    let synthetic = tokens::IdentToken::new("x").spanned();
+    assert_eq!(
+        rewriter.delete(&synthetic.span).err(),
+        Some(RewriteError::CallSite)
+    );
+    assert_eq!(rewriter.apply(), "a = 80; host = 1; port = 81;");
+}
+
+#[test]
+fn test_unified_diff_names_source_path() {
+    let source = "port = 80;\nhost = 1;\n";
+    let mut stream = TokenStream::lex_with_path(source, Some("conf/app.conf")).unwrap();
+    let mut rewriter = Rewriter::new(source).with_path(stream.source_path());
+    let first: Assignment = stream.parse_value().unwrap();
+    rewriter.replace(&first.value.span, "8080").unwrap();
+    assert_eq!(
+        rewriter.unified_diff(),
+        "--- a/conf/app.conf\n+++ b/conf/app.conf\n@@ -1,2 +1,2 @@\n-port = 80;\n+port = 8080;\n host = 1;\n"
+    );
+    // The dry run leaves the source alone
+    assert_eq!(rewriter.source(), source);
+}
@@ -297,6 +297,140 @@ mod span_arithmetic_tests {
         assert_eq!(span.start(), 0);
         assert_eq!(span.end(), 0);
     }
+
+    #[test]
+    fn span_byte_range() {
+        let span = span::Span::from(3..7);
+        assert_eq!(span, span::Span::new(3, 7));
+        assert_eq!(span.byte_range(), Some(3..7));
+        assert_eq!(span::Span::call_site().byte_range(), None);
+    }
+
+    #[test]
+    fn span_try_new_rejects_inverted() {
+        assert_eq!(span::Span::try_new(2, 5), Ok(span::Span::new(2, 5)));
+        assert_eq!(span::Span::try_new(4, 4), Ok(span::Span::new(4, 4)));
+        assert_eq!(
+            span::Span::try_new(5, 2),
+            Err(synkit::SpanError::Inverted { start: 5, end: 2 })
+        );
+    }
+
+    #[test]
+    fn span_clamp_to() {
+        assert_eq!(span::Span::new(2, 5).clamp_to(10), span::Span::new(2, 5));
+        assert_eq!(span::Span::new(2, 50).clamp_to(10), span::Span::new(2, 10));
+        assert_eq!(
+            span::Span::new(20, 50).clamp_to(10),
+            span::Span::new(10, 10)
+        );
+        assert_eq!(span::Span::new(8, 3).clamp_to(10), span::Span::new(3, 3));
+        assert_eq!(span::Span::call_site().clamp_to(0), span::Span::call_site());
+    }
+
+    #[test]
+    fn stream_try_slice() {
+        let source = "fn \"é\"";
+        let ts = stream::TokenStream::lex(source).expect("lexing failed");
+        assert_eq!(ts.try_slice(&span::Span::new(4, 6)), Ok("é"));
+        assert_eq!(
+            ts.try_slice(&span::Span::new(3, 40)),
+            Err(synkit::SpanError::OutOfBounds {
+                start: 3,
+                end: 40,
+                len: source.len()
+            })
+        );
+        assert_eq!(
+            ts.try_slice(&span::Span::new(5, 6))
+                .map_err(|e| e.to_string()),
+            Err("span offset 5 is not on a character boundary".to_string())
+        );
+        let clamped = span::Span::new(3, 40).clamp_to(source.len());
+        assert_eq!(ts.slice(&clamped), "\"é\"");
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "TokenStream::slice: span 6..2 is inverted")]
+    fn stream_slice_asserts_in_debug() {
+        let ts = stream::TokenStream::lex("fn f()").expect("lexing failed");
+        let _ = ts.slice(&span::Span::new(6, 2));
+    }
+}
+
+mod coverage_tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use synkit::CoverageError;
+
+    fn restream(
+        source: &str,
+        edit: impl FnOnce(&mut Vec<tokens::SpannedToken>),
+    ) -> stream::TokenStream {
+        let ts = stream::TokenStream::lex(source).expect("lexing failed");
+        let mut tokens = ts.all().to_vec();
+        edit(&mut tokens);
+        stream::TokenStream::from_tokens(Arc::from(source), Arc::new(tokens))
+    }
+
+    #[test]
+    fn lexed_stream_is_covered() {
+        let ts = stream::TokenStream::lex("struct Foo { a: \"é\" }\n").expect("lexing failed");
+        assert_eq!(ts.verify_coverage(), Ok(()));
+    }
+
+    #[test]
+    fn reports_first_anomaly() {
+        // Tokens: struct, space, Foo
+        let ts = restream("struct Foo", |tokens| {
+            tokens.remove(1);
+        });
+        assert_eq!(
+            ts.verify_coverage(),
+            Err(CoverageError::Gap { start: 6, end: 7 })
+        );
+
+        let ts = restream("struct Foo", |tokens| {
+            tokens[2].span = span::Span::new(5, 10);
+        });
+        assert_eq!(
+            ts.verify_coverage(),
+            Err(CoverageError::Overlap {
+                index: 2,
+                start: 5,
+                previous_end: 7
+            })
+        );
+
+        let ts = restream("struct Foo", |tokens| {
+            tokens[2].span = span::Span::call_site();
+        });
+        assert_eq!(
+            ts.verify_coverage().map_err(|e| e.to_string()),
+            Err("token 2 has a call-site span".to_string())
+        );
+    }
+}
+
+#[cfg(feature = "proc-macro2")]
+mod macro_input_tests {
+    use super::*;
+
+    #[test]
+    fn lex_macro_input() {
+        let tokens: synkit::proc_macro2::TokenStream = "fn foo(x: i32) -> bar".parse().unwrap();
+        let input = synkit::MacroInput::new(tokens);
+        assert_eq!(input.text(), "fn foo (x : i32) -> bar");
+
+        let mut ts = stream::TokenStream::lex(input.text()).expect("lexing failed");
+        let kw = ts.next().expect("fn");
+        assert_eq!(kw.span.byte_range(), input.byte_range(0));
+
+        let err = input.error(&kw.span, "unexpected fn").to_string();
+        assert!(err.starts_with("compile_error"));
+    }
 }
 
 mod stream_span_tests {
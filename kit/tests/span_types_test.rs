@@ -0,0 +1,180 @@
+//! Tests for `span_types`: generating a kit against user-provided span types.
+
+use synkit::{SpanLike, SpannedLike};
+use thiserror::Error;
+
+/// Span type shared with another subsystem.
+pub mod codemap {
+    /// Byte span; `u32::MAX` marks synthetic spans.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct CodeSpan {
+        pub lo: u32,
+        pub hi: u32,
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct Node<T> {
+        pub span: CodeSpan,
+        pub value: T,
+    }
+}
+
+use codemap::{CodeSpan, Node};
+
+impl SpanLike for CodeSpan {
+    fn start(&self) -> usize {
+        if self.is_call_site() {
+            0
+        } else {
+            self.lo as usize
+        }
+    }
+
+    fn end(&self) -> usize {
+        if self.is_call_site() {
+            0
+        } else {
+            self.hi as usize
+        }
+    }
+
+    fn new(start: usize, end: usize) -> Self {
+        Self {
+            lo: start as u32,
+            hi: end as u32,
+        }
+    }
+
+    fn call_site() -> Self {
+        Self {
+            lo: u32::MAX,
+            hi: u32::MAX,
+        }
+    }
+
+    fn is_call_site(&self) -> bool {
+        self.lo == u32::MAX
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        match (self.is_call_site(), other.is_call_site()) {
+            (true, _) => *other,
+            (_, true) => *self,
+            _ => Self {
+                lo: self.lo.min(other.lo),
+                hi: self.hi.max(other.hi),
+            },
+        }
+    }
+}
+
+impl<T: Clone> SpannedLike<T> for Node<T> {
+    type Span = CodeSpan;
+
+    fn span(&self) -> &CodeSpan {
+        &self.span
+    }
+
+    fn value_ref(&self) -> &T {
+        &self.value
+    }
+
+    fn value(self) -> T {
+        self.value
+    }
+
+    fn new(start: usize, end: usize, value: T) -> Self {
+        Self {
+            span: CodeSpan::new(start, end),
+            value,
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    span_types: {
+        span: codemap::CodeSpan,
+        spanned: codemap::Node,
+    },
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token("(")]
+        LParen,
+
+        #[token(")")]
+        RParen,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+    },
+
+    delimiters: {
+        Paren => (LParen, RParen),
+    },
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+#[test]
+fn test_tokens_use_custom_spans() {
+    let stream = TokenStream::lex("(ab)").expect("lex failed");
+    let spans: Vec<CodeSpan> = stream.all().iter().map(|t| t.span).collect();
+    assert_eq!(
+        spans,
+        [
+            CodeSpan { lo: 0, hi: 1 },
+            CodeSpan { lo: 1, hi: 3 },
+            CodeSpan { lo: 3, hi: 4 },
+        ]
+    );
+}
+
+#[test]
+fn test_parse_spanned_custom() {
+    let mut stream = TokenStream::lex(" ab").expect("lex failed");
+    let ident: Node<tokens::IdentToken> = stream.parse().expect("parse failed");
+    assert_eq!(ident.span, CodeSpan { lo: 1, hi: 3 });
+    assert_eq!(ident.value.0, "ab");
+}
+
+#[test]
+fn test_delimiters_with_custom_spans() {
+    let mut stream = TokenStream::lex("(a)").expect("lex failed");
+    let (inner, paren) = stream.extract_paren().expect("extract failed");
+    assert_eq!(*paren.span(), CodeSpan { lo: 0, hi: 3 });
+    assert_eq!(inner.all().len(), 1);
+}
+
+#[test]
+fn test_call_site_spans() {
+    let mut stream = MutTokenStream::new();
+    stream.push_source("a").expect("lex failed");
+    assert!(stream.covering_span().is_call_site());
+    stream.shift_spans(4);
+    assert!(stream.covering_span().is_call_site());
+
+    let stream = TokenStream::lex("").expect("lex failed");
+    assert!(stream.current_span().is_call_site());
+}
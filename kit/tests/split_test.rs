@@ -0,0 +1,108 @@
+//! Tests for `TokenStream::split_top_level`.
+
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token(",")]
+        Comma,
+
+        #[token(";")]
+        Semi,
+
+        #[token("(")]
+        LParen,
+
+        #[token(")")]
+        RParen,
+
+        #[token("[")]
+        LBracket,
+
+        #[token("]")]
+        RBracket,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+    },
+
+    delimiters: {
+        Paren => (LParen, RParen),
+        Bracket => (LBracket, RBracket),
+    },
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+/// Each piece's tokens, printed.
+fn pieces(stream: &TokenStream) -> Vec<String> {
+    stream
+        .split_top_level::<tokens::CommaToken>()
+        .iter()
+        .map(|piece| {
+            piece
+                .into_iter()
+                .map(|tok| tok.value.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+#[test]
+fn test_split_skips_nested_separators() {
+    let stream = TokenStream::lex("a, f(b, c), [d, (e, g)] h").expect("lex failed");
+    assert_eq!(pieces(&stream), ["a", "f ( b , c )", "[ d , ( e , g ) ] h"]);
+}
+
+#[test]
+fn test_split_empty_pieces() {
+    let stream = TokenStream::lex("a,, b,").expect("lex failed");
+    assert_eq!(pieces(&stream), ["a", "", "b", ""]);
+
+    let blank = TokenStream::lex("  ").expect("lex failed");
+    assert!(blank.split_top_level::<tokens::CommaToken>().is_empty());
+}
+
+#[test]
+fn test_split_pieces_are_bounded() {
+    let mut stream = TokenStream::lex("(a, b); c").expect("lex failed");
+    let (inner, _) = stream
+        .extract_inner::<tokens::LParenToken, tokens::RParenToken>()
+        .expect("extract failed");
+
+    let mut args = inner.split_top_level::<tokens::CommaToken>();
+    assert_eq!(args.len(), 2);
+    for (arg, name) in args.iter_mut().zip(["a", "b"]) {
+        let ident: Spanned<tokens::IdentToken> = arg.parse().expect("ident");
+        assert_eq!(*ident.value, name);
+        // Neither the separator nor the closing paren is in range
+        assert!(arg.is_empty());
+    }
+
+    // Splitting doesn't advance the stream
+    assert_eq!(pieces(&inner), ["a", "b"]);
+    assert_eq!(stream.split_top_level::<tokens::SemiToken>().len(), 2);
+}
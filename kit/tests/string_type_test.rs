@@ -0,0 +1,127 @@
+//! Tests for `string_type:`, which changes the payload of `(String)` tokens.
+
+use std::borrow::Cow;
+
+use synkit::EstimateSize;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+fn unquote(lex: &mut logos::Lexer<'_, tokens::Token>) -> Option<String> {
+    let s = lex.slice();
+    s.get(1..s.len() - 1).map(str::to_string)
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    string_type: std::borrow::Cow<'static, str>,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+
+        #[regex(r#""[^"]*""#, crate::unquote)]
+        #[fmt("string")]
+        Str(String),
+
+        #[regex(r"#[a-z]+", callback = |lex| lex.slice()[1..].to_string(), priority = 3)]
+        #[fmt("tag")]
+        Tag(String),
+
+        #[regex(r"[0-9]+", |lex| lex.slice().parse::<u64>().ok())]
+        #[fmt("number")]
+        Number(u64),
+    },
+
+    delimiters: {},
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+#[test]
+fn test_payloads_use_string_type() {
+    let mut stream = TokenStream::lex(r#"key "value" #tag 42"#).expect("lex failed");
+    let ident: Spanned<tokens::IdentToken> = stream.parse().expect("ident");
+    let value: Cow<'static, str> = ident.value.into_inner();
+    assert_eq!(value, "key");
+
+    let s: Spanned<tokens::StrToken> = stream.parse().expect("string");
+    assert_eq!(&**s.value, "value");
+
+    let tag: Spanned<tokens::TagToken> = stream.parse().expect("tag");
+    assert_eq!(&**tag.value, "tag");
+
+    let number: Spanned<tokens::NumberToken> = stream.parse().expect("number");
+    assert_eq!(*number.value, 42);
+}
+
+#[test]
+fn test_static_payloads_are_borrowed() {
+    let ident = tokens::IdentToken::new(Cow::Borrowed("static"));
+    assert_eq!(ident.token().to_string(), "static");
+    assert_eq!(ident.0.heap_size(), 0);
+    assert_eq!(Token::Ident("static".into()), ident.token());
+}
+
+#[test]
+fn test_failed_callbacks_still_error() {
+    assert!(TokenStream::lex(r#"""#).is_err());
+}
+
+#[cfg(feature = "compact_str")]
+mod compact {
+    use super::LexError;
+    use synkit::EstimateSize;
+    use synkit::compact_str::CompactString;
+
+    synkit::parser_kit! {
+        error: LexError,
+
+        self_path: crate::compact,
+
+        string_type: synkit::compact_str::CompactString,
+
+        skip_tokens: [Space],
+
+        tokens: {
+            #[token(" ", priority = 0)]
+            Space,
+
+            #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+            #[fmt("identifier")]
+            Ident(String),
+        },
+
+        delimiters: {},
+
+        span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+        token_derives: [Clone, PartialEq, Debug],
+    }
+
+    #[test]
+    fn test_short_payloads_are_inline() {
+        let mut stream = TokenStream::lex("short").expect("lex failed");
+        let ident: Spanned<tokens::IdentToken> = stream.parse().expect("ident");
+        assert_eq!(ident.value.0, CompactString::from("short"));
+        assert_eq!(ident.value.heap_size(), 0);
+    }
+}
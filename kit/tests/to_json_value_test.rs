@@ -0,0 +1,154 @@
+//! Tests for `ToJsonValue` on generated types and derived AST nodes.
+#![cfg(feature = "json")]
+
+use serde_json::json;
+use synkit::{Punctuated, ToJsonValue};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token("=")]
+        Eq,
+
+        #[token(",")]
+        Comma,
+
+        #[token("[")]
+        LBracket,
+
+        #[token("]")]
+        RBracket,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+
+        #[regex(r"[0-9]+", |lex| lex.slice().parse::<i64>().ok())]
+        #[fmt("number")]
+        Number(i64),
+    },
+
+    delimiters: {
+        Bracket => (LBracket, RBracket),
+    },
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+#[derive(Debug, synkit::ToJsonValue)]
+enum Value {
+    Number(Spanned<tokens::NumberToken>),
+    List(Punctuated<Spanned<Value>, Spanned<tokens::CommaToken>>),
+    Empty,
+}
+
+#[derive(Debug, synkit::ToJsonValue)]
+struct Assignment {
+    name: Spanned<tokens::IdentToken>,
+    eq: Spanned<tokens::EqToken>,
+    value: Spanned<Value>,
+}
+
+impl Parse for Value {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        if tokens::NumberToken::peek(stream) {
+            return Ok(Value::Number(stream.parse()?));
+        }
+        let (mut inner, _) = stream.extract_bracket()?;
+        if inner.is_empty() {
+            return Ok(Value::Empty);
+        }
+        let mut list = Punctuated::new();
+        loop {
+            list.push_value(inner.parse()?);
+            if inner.is_empty() {
+                return Ok(Value::List(list));
+            }
+            list.push_punct(inner.parse()?);
+        }
+    }
+}
+
+impl Parse for Assignment {
+    fn parse(stream: &mut TokenStream) -> Result<Self, LexError> {
+        Ok(Self {
+            name: stream.parse()?,
+            eq: stream.parse()?,
+            value: stream.parse()?,
+        })
+    }
+}
+
+fn assignment(source: &str) -> Assignment {
+    let mut stream = TokenStream::lex(source).expect("lex failed");
+    stream.parse_value().expect("parse failed")
+}
+
+#[test]
+fn test_without_spans() {
+    assert_eq!(
+        assignment("x = [1, [], 2]").to_json_value(),
+        json!({
+            "name": "x",
+            "eq": "=",
+            "value": {"List": [{"Number": 1}, "Empty", {"Number": 2}]},
+        })
+    );
+}
+
+#[test]
+fn test_with_spans() {
+    assert_eq!(
+        assignment("a = 7").to_json_value_with_spans(),
+        json!({
+            "name": {"span": [0, 1], "value": "a"},
+            "eq": {"span": [2, 3], "value": "="},
+            "value": {"span": [4, 5], "value": {"Number": {"span": [4, 5], "value": 7}}},
+        })
+    );
+}
+
+#[test]
+fn test_tokens() {
+    let tokens = TokenStream::lex("a = 1").expect("lex failed");
+    let values: Vec<_> = tokens
+        .all()
+        .iter()
+        .map(|tok| tok.value.to_json_value())
+        .collect();
+    assert_eq!(
+        values,
+        [
+            json!({"Ident": "a"}),
+            json!("space"),
+            json!("="),
+            json!("space"),
+            json!({"Number": 1})
+        ]
+    );
+    assert_eq!(
+        tokens::EqToken::new().spanned().to_json_value_with_spans(),
+        json!({"span": null, "value": "="})
+    );
+}
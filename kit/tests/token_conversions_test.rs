@@ -0,0 +1,88 @@
+//! Tests for `token_conversions`: `From`/`TryFrom` between token structs,
+//! payloads and `Token`.
+
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    token_conversions: true,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[token("=")]
+        Eq,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+
+        #[regex(r"[0-9]+", |lex| lex.slice().parse::<i64>().ok())]
+        #[fmt("number")]
+        Number(i64),
+    },
+
+    delimiters: {},
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+#[test]
+fn test_payload_into_struct() {
+    assert_eq!(
+        tokens::IdentToken::from("x".to_string()),
+        tokens::IdentToken::new("x")
+    );
+    let number: tokens::NumberToken = 7.into();
+    assert_eq!(*number, 7);
+}
+
+#[test]
+fn test_struct_into_enum() {
+    assert_eq!(Token::from(tokens::EqToken::new()), Token::Eq);
+    let token: Token = tokens::NumberToken(3).into();
+    assert_eq!(token, Token::Number(3));
+}
+
+#[test]
+fn test_enum_try_into_struct() {
+    assert_eq!(
+        tokens::IdentToken::try_from(Token::Ident("a".into())),
+        Ok(tokens::IdentToken::new("a"))
+    );
+    assert_eq!(tokens::EqToken::try_from(Token::Eq), Ok(tokens::EqToken));
+    // The token comes back on a mismatch
+    assert_eq!(
+        tokens::EqToken::try_from(Token::Number(1)),
+        Err(Token::Number(1))
+    );
+}
+
+#[test]
+fn test_lexed_tokens_convert() {
+    let stream = TokenStream::lex("a = 1").expect("lex failed");
+    let idents: Vec<tokens::IdentToken> = stream
+        .all()
+        .iter()
+        .filter_map(|tok| tok.value.clone().try_into().ok())
+        .collect();
+    assert_eq!(idents, [tokens::IdentToken::new("a")]);
+}
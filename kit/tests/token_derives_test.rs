@@ -0,0 +1,91 @@
+//! Tests for separate derive lists on the `Token` enum and token structs.
+
+use std::collections::HashSet;
+
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+/// A user-defined payload implementing only what the derive lists need.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json", derive(synkit::ToJsonValue))]
+pub struct Ratio(pub u32, pub u32);
+
+impl std::fmt::Display for Ratio {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.0, self.1)
+    }
+}
+
+fn ratio(lex: &mut logos::Lexer<'_, tokens::Token>) -> Option<Ratio> {
+    let (a, b) = lex.slice().split_once(':')?;
+    Some(Ratio(a.parse().ok()?, b.parse().ok()?))
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        #[derive(PartialEq, Eq, Hash)]
+        #[enum_derive(Eq, Hash)]
+        Ident(String),
+
+        #[regex(r"[0-9]+:[0-9]+", crate::ratio)]
+        #[fmt("ratio")]
+        Ratio(crate::Ratio),
+
+        #[token("<")]
+        #[enum_derive(Eq)]
+        Lt,
+    },
+
+    delimiters: {},
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_enum_derives: [Clone, PartialEq, Debug],
+    token_struct_derives: [Clone, Debug],
+}
+
+#[test]
+fn test_struct_derives_apply_to_structs() {
+    let mut stream = TokenStream::lex("a 1:2 a").expect("lex failed");
+    let first: Spanned<tokens::IdentToken> = stream.parse().expect("ident");
+    let ratio: Spanned<tokens::RatioToken> = stream.parse().expect("ratio");
+    let second: Spanned<tokens::IdentToken> = stream.parse().expect("ident");
+    assert_eq!(ratio.value.0, Ratio(1, 2));
+
+    // Per-token `#[derive]` still adds to the shared struct derives
+    let idents: HashSet<_> = [first.value, second.value].into_iter().collect();
+    assert_eq!(idents.len(), 1);
+}
+
+#[test]
+fn test_enum_derives_apply_to_enum() {
+    // `Eq` is requested by two tokens but derived once
+    let tokens: HashSet<Token> = [
+        Token::Ratio(Ratio(1, 2)),
+        Token::Ratio(Ratio(1, 2)),
+        Token::Lt,
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(tokens.len(), 2);
+}
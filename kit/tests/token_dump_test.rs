@@ -0,0 +1,128 @@
+//! Tests for `TokenStream::dump` and `TokenStream::load`.
+
+use synkit::{DumpError, SpanLike, TokenDump};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[regex(r"[ \n]+")]
+        Space,
+
+        #[token("let")]
+        KwLet,
+
+        #[token("=")]
+        Eq,
+
+        #[token(";")]
+        Semi,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+
+        #[regex(r"[0-9]+", |lex| lex.slice().parse().ok())]
+        #[fmt("number")]
+        Number(u64),
+    },
+
+    delimiters: {},
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+const FIXTURE: &str = r#"# synkit tokens
+0..3 KwLet "let"
+3..4 Space " "
+4..5 Ident "x"
+5..6 Space " "
+6..7 Eq "="
+7..8 Space " "
+8..10 Number "42"
+10..11 Semi ";"
+11..12 Space "\n"
+"#;
+
+#[test]
+fn test_dump_text() {
+    let stream = TokenStream::lex("let x = 42;\n").expect("lex failed");
+    assert_eq!(stream.dump().to_text(), FIXTURE);
+}
+
+#[test]
+fn test_load_and_parse() {
+    let dump = TokenDump::from_text(FIXTURE).expect("invalid dump");
+    let mut stream = TokenStream::load(&dump).expect("load failed");
+    assert_eq!(stream.source(), "let x = 42;\n");
+
+    let _: Spanned<tokens::KwLetToken> = stream.parse().expect("let");
+    let name: Spanned<tokens::IdentToken> = stream.parse().expect("name");
+    assert_eq!(*name.value, "x");
+    assert_eq!((name.span.start(), name.span.end()), (4, 5));
+    let _: Spanned<tokens::EqToken> = stream.parse().expect("=");
+    let value: Spanned<tokens::NumberToken> = stream.parse().expect("value");
+    assert_eq!(*value.value, 42);
+    assert_eq!((value.span.start(), value.span.end()), (8, 10));
+}
+
+#[test]
+fn test_binary_round_trip() {
+    let stream = TokenStream::lex("let answer = 42;").expect("lex failed");
+    let bytes = stream.dump().to_bytes();
+    let loaded = TokenStream::load(&TokenDump::parse(&bytes).unwrap()).expect("load failed");
+    assert_eq!(loaded.all(), stream.all());
+    assert_eq!(loaded.source(), stream.source());
+}
+
+#[test]
+fn test_load_without_skipped_tokens() {
+    // A hand-written fixture: the gaps become spaces in the rebuilt source
+    let dump = TokenDump::from_text("2..3 Ident \"a\"\n\n5..7 Number \"10\"").unwrap();
+    let stream = TokenStream::load(&dump).expect("load failed");
+    assert_eq!(stream.source(), "  a  10");
+    assert_eq!(stream.all().len(), 2);
+}
+
+#[test]
+fn test_load_errors() {
+    let relex = |text: &str| match TokenStream::load(&TokenDump::from_text(text).unwrap()) {
+        Ok(_) => panic!("`{text}` loaded"),
+        Err(err) => err,
+    };
+
+    assert_eq!(
+        relex("0..1 Ident \"x\"\n1..3 Ident \"42\""),
+        DumpError::Relex {
+            index: 1,
+            expected: "Ident".to_string(),
+            found: "`Number`".to_string(),
+        }
+    );
+    assert!(matches!(
+        relex("0..3 Ident \"a b\""),
+        DumpError::Relex { found, .. } if found == "several tokens"
+    ));
+    assert!(matches!(
+        relex("0..1 Ident \"?\""),
+        DumpError::Relex { found, .. } if found == "a lex error"
+    ));
+    assert_eq!(relex("0..2 Ident \"x\""), DumpError::Range { index: 0 });
+}
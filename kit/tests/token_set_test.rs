@@ -0,0 +1,101 @@
+//! Tests for sharing token definitions with `token_set!` / `include_tokens`.
+
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::token_set! {
+    expr_tokens {
+        #[token("+")]
+        Plus,
+
+        #[token("*")]
+        Star,
+
+        #[regex(r"[0-9]+", |lex| lex.slice().parse().ok())]
+        #[fmt("number")]
+        Number(i64),
+    }
+}
+
+synkit::token_set! {
+    trivia_tokens {
+        #[token(" ", priority = 0)]
+        Space,
+    }
+}
+
+synkit::parser_kit! {
+    error: LexError,
+    include_tokens: [expr_tokens, trivia_tokens],
+    skip_tokens: [Space],
+    tokens: {
+        #[token("let")]
+        KwLet,
+
+        #[token("=")]
+        Eq,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+    },
+}
+
+fn values(source: &str) -> Vec<Token> {
+    let stream = TokenStream::lex(source).expect("lex failed");
+    stream
+        .all()
+        .iter()
+        .map(|t| t.value.clone())
+        .filter(|t| *t != Token::Space)
+        .collect()
+}
+
+#[test]
+fn test_included_tokens_lex() {
+    assert_eq!(
+        values("1 + 2 * 3"),
+        vec![
+            Token::Number(1),
+            Token::Plus,
+            Token::Number(2),
+            Token::Star,
+            Token::Number(3),
+        ]
+    );
+}
+
+#[test]
+fn test_included_tokens_mix_with_local() {
+    use synkit::TokenStream as _;
+
+    let mut stream = TokenStream::lex("let x = 1 + 2").expect("lex failed");
+    let _: Spanned<tokens::KwLetToken> = stream.parse().expect("let");
+    let name: Spanned<tokens::IdentToken> = stream.parse().expect("ident");
+    let _: Spanned<tokens::EqToken> = stream.parse().expect("eq");
+    let lhs: Spanned<tokens::NumberToken> = stream.parse().expect("number");
+    let _: Spanned<tokens::PlusToken> = stream.parse().expect("plus");
+    let rhs: Spanned<tokens::NumberToken> = stream.parse().expect("number");
+
+    assert_eq!(*name.value, "x");
+    assert_eq!((*lhs.value, *rhs.value), (1, 2));
+    assert!(stream.ensure_consumed().is_ok());
+}
+
+#[test]
+fn test_included_token_display() {
+    assert_eq!(Token::Star.to_string(), "*");
+    assert_eq!(Token::Number(7).to_string(), "7");
+}
@@ -0,0 +1,65 @@
+//! Tests for `token_tests`: the generated `token_tests` module runs as part
+//! of this test binary, one `lexes_*` test per token.
+
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    token_tests: true,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        #[fmt("space")]
+        Space,
+
+        #[token("fn")]
+        KwFn,
+
+        #[token("if")]
+        KwIf,
+
+        #[token("->")]
+        Arrow,
+
+        // Regex samples skip the keywords above
+        #[regex(r"[a-z]{1,2}", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+
+        #[regex(r"[0-9]+", |lex| lex.slice().parse::<u64>().ok())]
+        #[fmt("number")]
+        Number(u64),
+
+        #[regex(r#""[^"]*""#, |lex| lex.slice().to_string())]
+        #[sample(r#""hello""#)]
+        #[fmt("string")]
+        Str(String),
+    },
+
+    delimiters: {},
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug],
+}
+
+#[test]
+fn test_kit_lexes() {
+    let stream = TokenStream::lex("fn f -> 1").expect("lex failed");
+    assert_eq!(stream.all().len(), 7);
+}
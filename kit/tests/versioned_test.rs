@@ -0,0 +1,165 @@
+//! Tests for schema-versioned and span-stripped serialization.
+#![cfg(feature = "serde")]
+
+use serde::{Deserialize, Deserializer, Serialize};
+use synkit::Delimited;
+use synkit::versioned::{self, Migrate, Tagged, Versioned, WithoutSpans};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, Default, PartialEq)]
+pub enum LexError {
+    #[default]
+    #[error("unknown")]
+    Unknown,
+
+    #[error("expected {expect}, found {found}")]
+    Expected { expect: &'static str, found: String },
+
+    #[error("expected {expect}, found EOF")]
+    Empty { expect: &'static str },
+}
+
+synkit::parser_kit! {
+    error: LexError,
+
+    skip_tokens: [Space],
+
+    tokens: {
+        #[token(" ", priority = 0)]
+        Space,
+
+        #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+        #[fmt("identifier")]
+        Ident(String),
+    },
+
+    delimiters: {},
+
+    span_derives: [Debug, Clone, PartialEq, Eq, Hash, Copy],
+    token_derives: [Clone, PartialEq, Debug, serde::Serialize, serde::Deserialize],
+    custom_derives: [serde::Serialize, serde::Deserialize],
+}
+
+/// Version 1: a single word, without a span.
+#[derive(Debug, Serialize, Deserialize)]
+struct WordV1 {
+    word: String,
+}
+
+impl Versioned for WordV1 {
+    const VERSION: u32 = 1;
+}
+
+impl Migrate for WordV1 {}
+
+/// Version 2: spanned words.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Words {
+    words: Vec<Spanned<tokens::IdentToken>>,
+}
+
+impl Versioned for Words {
+    const VERSION: u32 = 2;
+}
+
+impl Migrate for Words {
+    fn migrate<'de, D: Deserializer<'de>>(version: u32, deserializer: D) -> Result<Self, D::Error> {
+        let old: WordV1 = versioned::read(version, deserializer)?;
+        Ok(Words {
+            words: vec![Spanned {
+                span: Span::call_site(),
+                value: tokens::IdentToken::new(old.word),
+            }],
+        })
+    }
+}
+
+fn words(source: &str) -> Words {
+    let mut stream = TokenStream::lex(source).expect("lex failed");
+    let mut words = Vec::new();
+    while !stream.is_empty() {
+        words.push(stream.parse().expect("parse failed"));
+    }
+    Words { words }
+}
+
+#[test]
+fn test_tagged_round_trip() {
+    let ast = words("ab cd");
+    let json = serde_json::to_string(&Tagged(&ast)).unwrap();
+    assert!(json.starts_with(r#"{"version":2,"ast":"#), "{json}");
+
+    let Tagged(back) = serde_json::from_str::<Tagged<Words>>(&json).unwrap();
+    assert_eq!(back, ast);
+}
+
+#[test]
+fn test_tagged_migrates_older_version() {
+    let json = r#"{"version":1,"ast":{"word":"old"}}"#;
+    let Tagged(ast) = serde_json::from_str::<Tagged<Words>>(json).unwrap();
+    assert_eq!(ast.words.len(), 1);
+    assert_eq!(*ast.words[0].value, "old");
+    assert_eq!(ast.words[0].span, Span::CallSite);
+}
+
+#[test]
+fn test_tagged_rejects_unknown_versions() {
+    let newer = serde_json::from_str::<Tagged<Words>>(r#"{"version":3,"ast":{}}"#).unwrap_err();
+    assert!(
+        newer
+            .to_string()
+            .contains("schema version 3 is newer than 2"),
+        "{newer}"
+    );
+
+    let older = serde_json::from_str::<Tagged<Words>>(r#"{"version":0,"ast":{}}"#).unwrap_err();
+    assert!(
+        older
+            .to_string()
+            .contains("no migration from schema version 0 to 1"),
+        "{older}"
+    );
+}
+
+#[test]
+fn test_tagged_requires_version_first() {
+    let err =
+        serde_json::from_str::<Tagged<Words>>(r#"{"ast":{"words":[]},"version":2}"#).unwrap_err();
+    assert!(
+        err.to_string().contains("`version` must precede `ast`"),
+        "{err}"
+    );
+
+    let err = serde_json::from_str::<Tagged<Words>>(r#"{"ast":null}"#).unwrap_err();
+    assert!(
+        err.to_string().contains("`version` must precede `ast`"),
+        "{err}"
+    );
+
+    let err = serde_json::from_str::<Tagged<Words>>(r#"{"version":2}"#).unwrap_err();
+    assert!(err.to_string().contains("missing field `ast`"), "{err}");
+}
+
+#[test]
+fn test_without_spans() {
+    let ast = words("ab cd");
+    let json = serde_json::to_string(&WithoutSpans(&ast)).unwrap();
+    assert_eq!(json, r#"{"words":["ab","cd"]}"#);
+
+    // Reformatting changes spans but not the stripped output.
+    assert_eq!(
+        serde_json::to_string(&WithoutSpans(&words("  ab    cd"))).unwrap(),
+        json
+    );
+
+    assert_eq!(
+        serde_json::to_string(&Tagged(WithoutSpans(&ast))).unwrap(),
+        r#"{"version":2,"ast":{"words":["ab","cd"]}}"#
+    );
+
+    let delimited = Delimited::new(Span::new(0, 4), ast);
+    assert_eq!(
+        serde_json::to_string(&WithoutSpans(&delimited)).unwrap(),
+        r#"{"inner":{"words":["ab","cd"]}}"#
+    );
+}
@@ -1,7 +1,8 @@
 use proc_macro2::TokenStream;
-use quote::{format_ident, quote};
+use quote::{ToTokens, format_ident, quote};
 use syn::{
-    Attribute, Ident, LitStr, Path, Token, Type, braced, bracketed, parenthesized,
+    Attribute, Expr, Ident, LitStr, Path, ReturnType, Token, Type, braced, bracketed,
+    parenthesized,
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
 };
@@ -22,19 +23,69 @@ fn to_snake_case(s: &str) -> String {
     result
 }
 
+/// Attach `cfg` attributes to every item in `items`.
+pub(crate) fn with_cfg(cfg_attrs: &[Attribute], items: TokenStream) -> syn::Result<TokenStream> {
+    if cfg_attrs.is_empty() {
+        return Ok(items);
+    }
+    let mut file: syn::File = syn::parse2(items)?;
+    for item in &mut file.items {
+        if let Some(attrs) = item_attrs_mut(item) {
+            attrs.splice(0..0, cfg_attrs.iter().cloned());
+        }
+    }
+    Ok(quote! { #file })
+}
+
+fn item_attrs_mut(item: &mut syn::Item) -> Option<&mut Vec<Attribute>> {
+    use syn::Item;
+    match item {
+        Item::Const(i) => Some(&mut i.attrs),
+        Item::Enum(i) => Some(&mut i.attrs),
+        Item::Fn(i) => Some(&mut i.attrs),
+        Item::Impl(i) => Some(&mut i.attrs),
+        Item::Macro(i) => Some(&mut i.attrs),
+        Item::Mod(i) => Some(&mut i.attrs),
+        Item::Static(i) => Some(&mut i.attrs),
+        Item::Struct(i) => Some(&mut i.attrs),
+        Item::Trait(i) => Some(&mut i.attrs),
+        Item::Type(i) => Some(&mut i.attrs),
+        Item::Use(i) => Some(&mut i.attrs),
+        _ => None,
+    }
+}
+
 pub struct DeclareTokensInput {
     pub span_mod: Option<Path>,
-    pub error_type: Ident,
+    pub error_type: Path,
+    /// Path to the module containing `tokens` and `span`; `$crate` if unset.
+    pub self_path: Option<Path>,
     pub derives: Vec<Path>,
     pub struct_derives: Vec<Path>,
     pub logos_attrs: Vec<Attribute>,
+    /// Payload type replacing `String` in `(String)` tokens.
+    pub string_type: Option<Type>,
+    /// Generate `From`/`TryFrom` between token structs, payloads and `Token`.
+    pub token_conversions: bool,
+    /// Generate a `#[cfg(test)]` module lexing a sample of each token.
+    pub token_tests: bool,
+    /// Generate `BorrowedToken<'src>`, lexing payloads as source slices.
+    pub borrowed: bool,
     pub tokens: Vec<TokenDef>,
 }
 
 pub struct TokenDef {
     pub attrs: Vec<Attribute>,
+    /// `#[cfg(...)]` attributes, applied to every item generated for the token.
+    pub cfg_attrs: Vec<Attribute>,
+    /// `///` doc comments, copied onto the variant and token struct.
+    pub docs: Vec<Attribute>,
     pub fmt_str: Option<LitStr>,
     pub extra_derives: Vec<Path>,
+    /// `#[enum_derive(...)]`: derives this token needs on the `Token` enum.
+    pub enum_derives: Vec<Path>,
+    /// `#[sample("...")]`: example source text for `Generate`.
+    pub samples: Vec<LitStr>,
     pub no_to_tokens: bool,
     pub name: Ident,
     pub inner_type: Option<Type>,
@@ -44,8 +95,12 @@ impl Clone for TokenDef {
     fn clone(&self) -> Self {
         Self {
             attrs: self.attrs.clone(),
+            cfg_attrs: self.cfg_attrs.clone(),
+            docs: self.docs.clone(),
             fmt_str: self.fmt_str.clone(),
             extra_derives: self.extra_derives.clone(),
+            enum_derives: self.enum_derives.clone(),
+            samples: self.samples.clone(),
             no_to_tokens: self.no_to_tokens,
             name: self.name.clone(),
             inner_type: self.inner_type.clone(),
@@ -53,6 +108,41 @@ impl Clone for TokenDef {
     }
 }
 
+/// Re-emit the definition in `tokens: { ... }` syntax (used by `export_as`).
+impl quote::ToTokens for TokenDef {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let Self {
+            attrs,
+            cfg_attrs,
+            docs,
+            fmt_str,
+            extra_derives,
+            enum_derives,
+            samples,
+            no_to_tokens,
+            name,
+            inner_type,
+        } = self;
+        let fmt = fmt_str.as_ref().map(|s| quote! { #[fmt(#s)] });
+        let derive = (!extra_derives.is_empty()).then(|| quote! { #[derive(#(#extra_derives),*)] });
+        let enum_derive =
+            (!enum_derives.is_empty()).then(|| quote! { #[enum_derive(#(#enum_derives),*)] });
+        let no_to_tokens = no_to_tokens.then(|| quote! { #[no_to_tokens] });
+        let inner = inner_type.as_ref().map(|ty| quote! { (#ty) });
+        tokens.extend(quote! {
+            #(#cfg_attrs)*
+            #(#docs)*
+            #(#attrs)*
+            #fmt
+            #derive
+            #enum_derive
+            #(#[sample(#samples)])*
+            #no_to_tokens
+            #name #inner
+        });
+    }
+}
+
 impl Parse for DeclareTokensInput {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let mut span_mod = None;
@@ -60,6 +150,9 @@ impl Parse for DeclareTokensInput {
         let mut derives = Vec::new();
         let mut struct_derives = Vec::new();
         let mut logos_attrs = Vec::new();
+        let mut string_type = None;
+        let mut token_conversions = false;
+        let mut token_tests = false;
         let mut tokens = Vec::new();
 
         while !input.is_empty() {
@@ -105,6 +198,24 @@ impl Parse for DeclareTokensInput {
                         input.parse::<Token![,]>()?;
                     }
                 }
+                "string_type" => {
+                    string_type = Some(input.parse()?);
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "token_conversions" => {
+                    token_conversions = input.parse::<syn::LitBool>()?.value;
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "token_tests" => {
+                    token_tests = input.parse::<syn::LitBool>()?.value;
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
                 "tokens" => {
                     let content;
                     braced!(content in input);
@@ -133,9 +244,14 @@ impl Parse for DeclareTokensInput {
         Ok(Self {
             span_mod,
             error_type,
+            self_path: None,
             derives,
             struct_derives,
             logos_attrs,
+            string_type,
+            token_conversions,
+            token_tests,
+            borrowed: false,
             tokens,
         })
     }
@@ -144,22 +260,39 @@ impl Parse for DeclareTokensInput {
 impl Parse for TokenDef {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let mut attrs = Vec::new();
+        let mut cfg_attrs = Vec::new();
+        let mut docs = Vec::new();
         let mut fmt_str = None;
         let mut extra_derives = Vec::new();
+        let mut enum_derives = Vec::new();
+        let mut samples = Vec::new();
         let mut no_to_tokens = false;
 
         while input.peek(Token![#]) {
             let attr_list = input.call(Attribute::parse_outer)?;
             for attr in attr_list {
-                if attr.path().is_ident("fmt") {
+                if attr.path().is_ident("cfg") {
+                    cfg_attrs.push(attr);
+                } else if attr.path().is_ident("doc") {
+                    docs.push(attr);
+                } else if attr.path().is_ident("fmt") {
                     fmt_str = Some(attr.parse_args()?);
                 } else if attr.path().is_ident("derive") {
                     attr.parse_nested_meta(|meta| {
                         extra_derives.push(meta.path);
                         Ok(())
                     })?;
+                } else if attr.path().is_ident("enum_derive") {
+                    attr.parse_nested_meta(|meta| {
+                        enum_derives.push(meta.path);
+                        Ok(())
+                    })?;
+                } else if attr.path().is_ident("sample") {
+                    samples.push(attr.parse_args()?);
                 } else if attr.path().is_ident("no_to_tokens") {
                     no_to_tokens = true;
+                } else if attr.path().is_ident("block_comment") {
+                    attrs.push(block_comment(&attr)?);
                 } else {
                     attrs.push(attr);
                 }
@@ -178,8 +311,12 @@ impl Parse for TokenDef {
 
         Ok(Self {
             attrs,
+            cfg_attrs,
+            docs,
             fmt_str,
             extra_derives,
+            enum_derives,
+            samples,
             no_to_tokens,
             name,
             inner_type,
@@ -187,29 +324,483 @@ impl Parse for TokenDef {
     }
 }
 
+impl TokenDef {
+    /// How the token is listed in an expected set: its `#[fmt]`, else its
+    /// `#[token]` literal in backticks, else its name in lowercase.
+    pub fn expect_name(&self) -> String {
+        if let Some(fmt) = &self.fmt_str {
+            return fmt.value();
+        }
+        let literal = self
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("token"))
+            .find_map(|attr| {
+                attr.parse_args_with(|input: ParseStream| {
+                    let lit: LitStr = input.parse()?;
+                    input.parse::<TokenStream>()?;
+                    Ok(lit.value())
+                })
+                .ok()
+            });
+        match literal {
+            Some(literal) => format!("`{literal}`"),
+            None => self.name.to_string().to_lowercase(),
+        }
+    }
+
+    /// Store a `(String)` payload as `ty`, converting the lexer callback's
+    /// result with `synkit::IntoStringPayload`.
+    fn with_string_type(mut self, ty: &Type) -> Self {
+        let is_string = matches!(
+            &self.inner_type,
+            Some(Type::Path(p)) if p.qself.is_none() && p.path.is_ident("String")
+        );
+        if !is_string {
+            return self;
+        }
+        for attr in &mut self.attrs {
+            if attr.path().is_ident("token") || attr.path().is_ident("regex") {
+                convert_callback(attr, ty);
+            }
+        }
+        self.inner_type = Some(ty.clone());
+        self
+    }
+
+    /// Source text `Generate` tries for this token: `#[sample]`s if given,
+    /// else its `#[token]` literals, else strings matching its `#[regex]`es.
+    fn samples(&self) -> Vec<String> {
+        if !self.samples.is_empty() {
+            return self.samples.iter().map(LitStr::value).collect();
+        }
+        let literals = self.literal_samples();
+        if !literals.is_empty() {
+            return literals;
+        }
+        self.regex_samples().collect()
+    }
+
+    /// `#[token]` literals, each followed by its `until` text.
+    fn literal_samples(&self) -> Vec<String> {
+        self.pattern_attrs("token")
+            .map(|(literal, until)| literal + &until)
+            .collect()
+    }
+
+    /// Strings matching `#[regex]`es, each followed by its `until` text.
+    fn regex_samples(&self) -> impl Iterator<Item = String> + '_ {
+        self.pattern_attrs("regex").flat_map(|(re, until)| {
+            crate::regex_sample::samples(&re, 8)
+                .into_iter()
+                .map(move |sample| sample + &until)
+        })
+    }
+
+    /// The patterns of this token's `#[token]` or `#[regex]` attributes, by
+    /// `kind`, with their `until` text or `""`.
+    fn pattern_attrs(&self, kind: &str) -> impl Iterator<Item = (String, String)> + '_ {
+        let kind = kind.to_string();
+        self.attrs
+            .iter()
+            .filter(move |attr| attr.path().is_ident(&kind))
+            .filter_map(|attr| {
+                let pattern = attr
+                    .parse_args_with(|input: ParseStream| {
+                        let lit: LitStr = input.parse()?;
+                        input.parse::<TokenStream>()?;
+                        Ok(lit.value())
+                    })
+                    .ok()?;
+                Some((pattern, attr_until(attr).unwrap_or_default()))
+            })
+    }
+
+    /// The string arguments of this token's `#[token]` or `#[regex]`
+    /// attributes, by `kind`.
+    fn patterns(&self, kind: &str) -> Vec<String> {
+        self.pattern_attrs(kind)
+            .map(|(pattern, _)| pattern)
+            .collect()
+    }
+
+    /// Source text for this token's generated test: its first `#[sample]`
+    /// or `#[token]` literal, else the first string matching a `#[regex]`
+    /// that isn't a `literals` entry of another token.
+    fn primary_sample(&self, literals: &[String]) -> Option<String> {
+        if let Some(sample) = self.samples.first() {
+            return Some(sample.value());
+        }
+        if let Some(literal) = self.literal_samples().into_iter().next() {
+            return Some(literal);
+        }
+        self.regex_samples()
+            .find(|sample| !literals.contains(sample))
+    }
+
+    /// Doc attributes for the generated variant and struct: the user's doc
+    /// comments followed by the token's `#[token]`/`#[regex]` patterns.
+    fn doc_attrs(&self) -> TokenStream {
+        let docs = &self.docs;
+        let patterns: Vec<String> = self
+            .attrs
+            .iter()
+            .filter_map(|attr| {
+                let kind = ["token", "regex"]
+                    .into_iter()
+                    .find(|k| attr.path().is_ident(k))?;
+                let lit = attr
+                    .parse_args_with(|input: ParseStream| {
+                        let lit: LitStr = input.parse()?;
+                        input.parse::<TokenStream>()?;
+                        Ok(lit)
+                    })
+                    .ok()?;
+                Some(format!("{}: {}", kind, code_span(&lit.value())))
+            })
+            .collect();
+        if patterns.is_empty() {
+            return quote! { #(#docs)* };
+        }
+        let separator = (!docs.is_empty()).then(|| quote! { #[doc = ""] });
+        let lines = patterns.iter().map(|p| format!(" - {p}"));
+        quote! {
+            #(#docs)*
+            #separator
+            #[doc = " Matches:"]
+            #[doc = ""]
+            #(#[doc = #lines])*
+        }
+    }
+}
+
+/// Rewrite the callback of a `#[token]`/`#[regex]` attribute so its
+/// `String` result is converted to `ty`. Closure bodies are wrapped in place
+/// so logos still types the closure's argument; other callbacks are called
+/// from a new closure.
+fn convert_callback(attr: &mut Attribute, ty: &Type) {
+    let Ok(mut args) = attr.parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated) else {
+        return;
+    };
+    // Braced, since logos splits attribute arguments on top-level commas
+    let convert = |value: TokenStream| {
+        quote! {{ <_ as synkit::IntoStringPayload<#ty>>::into_payload(#value) }}
+    };
+    let wrap = |callback: &mut Expr| match callback {
+        Expr::Closure(closure) if matches!(closure.output, ReturnType::Default) => {
+            let body = convert(closure.body.to_token_stream());
+            *closure.body = Expr::Verbatim(body);
+        }
+        other => {
+            let call = convert(quote! { (#other)(lex) });
+            *other = Expr::Verbatim(quote! { |lex| #call });
+        }
+    };
+
+    for (i, arg) in args.iter_mut().enumerate() {
+        match arg {
+            Expr::Assign(assign) if matches!(&*assign.left, Expr::Path(p) if p.path.is_ident("callback")) =>
+            {
+                wrap(&mut assign.right);
+            }
+            Expr::Assign(_) => {}
+            Expr::Call(call) if matches!(&*call.func, Expr::Path(p) if p.path.is_ident("ignore")) =>
+                {}
+            Expr::Path(p) if p.path.is_ident("longest") || p.path.is_ident("nested") => {}
+            callback if i == 1 => wrap(callback),
+            _ => {}
+        }
+    }
+
+    let path = attr.path().clone();
+    *attr = syn::parse_quote! { #[#path(#args)] };
+}
+
+/// Replace the callback of a payload token's `#[token]`/`#[regex]` with one
+/// returning the matched slice, for `BorrowedToken`. `until` and the other
+/// options are kept.
+fn slice_callback(attr: &Attribute) -> Attribute {
+    if !(attr.path().is_ident("token") || attr.path().is_ident("regex")) {
+        return attr.clone();
+    }
+    let Ok(args) = attr.parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated) else {
+        return attr.clone();
+    };
+    let is_callback = |i: usize, arg: &Expr| match arg {
+        Expr::Assign(assign) => is_ident(&assign.left, "callback"),
+        Expr::Call(call) if is_ident(&call.func, "ignore") => false,
+        Expr::Path(p) if p.path.is_ident("longest") || p.path.is_ident("nested") => false,
+        _ => i == 1,
+    };
+    let mut args: Vec<Expr> = args
+        .into_iter()
+        .enumerate()
+        .filter(|(i, arg)| !is_callback(*i, arg))
+        .map(|(_, arg)| arg)
+        .collect();
+    args.insert(args.len().min(1), syn::parse_quote!(|lex| lex.slice()));
+    let path = attr.path();
+    syn::parse_quote! { #[#path(#(#args),*)] }
+}
+
+/// Rewrite `until = "..."` on a `#[token]`/`#[regex]` into a callback that
+/// bumps the match past the first `until` text (the last, with `longest`,
+/// or the one balancing the `#[token]` literal, with `nested`) before
+/// running the token's own callback. Logos ignores lazy repetition, so
+/// bounded matches need the callback.
+///
+/// Without a callback of its own, an unterminated match is a lex error;
+/// otherwise it runs to the end of the input and the callback decides.
+fn bounded_attr(attr: &Attribute) -> syn::Result<Attribute> {
+    if !(attr.path().is_ident("token") || attr.path().is_ident("regex")) {
+        return Ok(attr.clone());
+    }
+    let Ok(args) = attr.parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated) else {
+        return Ok(attr.clone());
+    };
+    let mut until = None;
+    let mut longest = None;
+    let mut nested = None;
+    let mut callback = None;
+    let mut rest: Vec<Expr> = Vec::new();
+    for (i, arg) in args.into_iter().enumerate() {
+        match arg {
+            Expr::Assign(assign) if is_ident(&assign.left, "until") => match *assign.right {
+                Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(close),
+                    ..
+                }) if !close.value().is_empty() => until = Some(close),
+                other => {
+                    return Err(syn::Error::new_spanned(
+                        other,
+                        "`until` takes a non-empty string literal",
+                    ));
+                }
+            },
+            Expr::Assign(assign) if is_ident(&assign.left, "callback") => {
+                callback = Some(*assign.right);
+            }
+            Expr::Path(path) if path.path.is_ident("longest") => longest = Some(path),
+            Expr::Path(path) if path.path.is_ident("nested") => nested = Some(path),
+            arg @ (Expr::Assign(_) | Expr::Call(_)) => rest.push(arg),
+            arg if i == 1 => callback = Some(arg),
+            arg => rest.push(arg),
+        }
+    }
+    let Some(close) = until else {
+        return match longest.or(nested) {
+            Some(option) => Err(syn::Error::new_spanned(
+                &option,
+                format!("`{}` needs `until = \"...\"`", quote!(#option)),
+            )),
+            None => Ok(attr.clone()),
+        };
+    };
+
+    let path = attr.path();
+    let mut rest = rest.into_iter();
+    let pattern = rest.next();
+    let scan = |remainder: TokenStream| match (&nested, &longest) {
+        (Some(nested), _) if !path.is_ident("token") => Err(syn::Error::new_spanned(
+            nested,
+            "`nested` needs a `#[token]` opener",
+        )),
+        (Some(_), Some(longest)) => Err(syn::Error::new_spanned(
+            longest,
+            "`longest` and `nested` can't be combined",
+        )),
+        (Some(_), None) => Ok(quote! { synkit::scan::nested(#remainder, #pattern, #close) }),
+        (None, longest) => {
+            let longest = longest.is_some();
+            Ok(quote! { synkit::scan::until(#remainder, #close, #longest) })
+        }
+    };
+    // Braced, since logos splits attribute arguments on top-level commas
+    let callback = match callback {
+        Some(callback) => {
+            // Inlined as logos does, since a closure called in place can't
+            // infer its argument type
+            let call = match callback {
+                Expr::Closure(closure) if closure.inputs.len() == 1 => {
+                    let arg = &closure.inputs[0];
+                    let body = &closure.body;
+                    quote! {{
+                        let #arg = lex;
+                        #body
+                    }}
+                }
+                callback => quote! { #callback(lex) },
+            };
+            let scan = scan(quote! { rest })?;
+            quote! {
+                |lex| {
+                    let rest = lex.remainder();
+                    lex.bump(#scan.unwrap_or(rest.len()));
+                    #call
+                }
+            }
+        }
+        None => {
+            let scan = scan(quote! { lex.remainder() })?;
+            quote! {
+                |lex| {
+                    match #scan {
+                        Some(len) => {
+                            lex.bump(len);
+                            true
+                        }
+                        None => false,
+                    }
+                }
+            }
+        }
+    };
+    Ok(syn::parse_quote! { #[#path(#pattern, #callback #(, #rest)*)] })
+}
+
+/// `#[block_comment(open = "/*", close = "*/", nested)]` as the
+/// `#[token("/*", until = "*/", nested)]` it stands for.
+fn block_comment(attr: &Attribute) -> syn::Result<Attribute> {
+    let mut open: Option<LitStr> = None;
+    let mut close: Option<LitStr> = None;
+    let mut nested = false;
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("open") {
+            open = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("close") {
+            close = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("nested") {
+            nested = true;
+        } else {
+            return Err(meta.error("expected `open`, `close` or `nested`"));
+        }
+        Ok(())
+    })?;
+    let (Some(open), Some(close)) = (open, close) else {
+        return Err(syn::Error::new_spanned(
+            attr,
+            "`#[block_comment]` needs `open = \"...\"` and `close = \"...\"`",
+        ));
+    };
+    if open.value().is_empty() {
+        return Err(syn::Error::new_spanned(open, "`open` can't be empty"));
+    }
+    let nested = nested.then(|| quote! { , nested });
+    Ok(syn::parse_quote! { #[token(#open, until = #close #nested)] })
+}
+
+/// The `until = "..."` text of a `#[token]`/`#[regex]` attribute, if any.
+fn attr_until(attr: &Attribute) -> Option<String> {
+    let args = attr
+        .parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)
+        .ok()?;
+    args.into_iter().find_map(|arg| match arg {
+        Expr::Assign(assign) if is_ident(&assign.left, "until") => match *assign.right {
+            Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(close),
+                ..
+            }) => Some(close.value()),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+pub(crate) fn is_ident(expr: &Expr, name: &str) -> bool {
+    matches!(expr, Expr::Path(path) if path.path.is_ident(name))
+}
+
+/// `path` as seen from a module generated inside the invocation's module:
+/// relative paths get a `super::` prefix, absolute ones are kept.
+pub(crate) fn child_path(path: &Path) -> TokenStream {
+    let absolute = path.leading_colon.is_some()
+        || path
+            .segments
+            .first()
+            .is_some_and(|s| s.ident == "crate" || s.ident == "$crate");
+    if absolute {
+        quote! { #path }
+    } else {
+        quote! { super::#path }
+    }
+}
+
+/// Render `text` as a markdown code span, padding the backtick fence so
+/// patterns containing backticks stay intact.
+fn code_span(text: &str) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in text.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    let fence = "`".repeat(longest + 1);
+    if longest == 0 {
+        format!("{fence}{text}{fence}")
+    } else {
+        format!("{fence} {text} {fence}")
+    }
+}
+
 pub fn expand(input: DeclareTokensInput) -> syn::Result<TokenStream> {
     let DeclareTokensInput {
         span_mod,
         error_type,
+        self_path,
         derives,
         struct_derives,
         logos_attrs,
+        string_type,
+        token_conversions,
+        token_tests,
+        borrowed,
         tokens,
     } = input;
 
+    // Payloads name the type through an alias: logos would otherwise tie
+    // lifetimes like `Cow<'static, str>`'s to the source lifetime
+    let string_payload = string_type.as_ref().map(|ty| {
+        quote! {
+            /// Payload type of tokens declared as `(String)`.
+            pub type StringPayload = #ty;
+        }
+    });
+    let tokens = match &string_type {
+        Some(_) => {
+            let alias: Type = syn::parse_quote!(StringPayload);
+            tokens
+                .into_iter()
+                .map(|t| t.with_string_type(&alias))
+                .collect()
+        }
+        None => tokens,
+    };
+
     let span_import = if let Some(ref path) = span_mod {
         quote! { use #path::{Span, Spanned}; }
     } else {
         quote! { use super::span::{Span, Spanned}; }
     };
 
-    let error_ref = quote! { super::#error_type };
+    let error_ref = child_path(&error_type);
+    let kit_path = self_path.map_or_else(|| quote! { $crate }, |path| quote! { #path });
 
-    let derives_tokens = if derives.is_empty() {
-        quote! { Clone, PartialEq, Debug }
+    let mut derives = if derives.is_empty() {
+        vec![
+            syn::parse_quote!(Clone),
+            syn::parse_quote!(PartialEq),
+            syn::parse_quote!(Debug),
+        ]
     } else {
-        quote! { #(#derives),* }
+        derives
     };
+    // `#[enum_derive(...)]` adds to the enum's derives, once per derive
+    for path in tokens.iter().flat_map(|t| &t.enum_derives) {
+        if !derives.contains(path) {
+            derives.push(path.clone());
+        }
+    }
+    let derives_tokens = quote! { #(#derives),* };
 
     let struct_derives_tokens = if struct_derives.is_empty() {
         quote! { Clone, PartialEq, Debug }
@@ -222,30 +813,41 @@ pub fn expand(input: DeclareTokensInput) -> syn::Result<TokenStream> {
         .map(|t| {
             let TokenDef {
                 attrs,
+                cfg_attrs,
                 name,
                 inner_type,
                 ..
             } = t;
-            if let Some(ty) = inner_type {
+            let docs = t.doc_attrs();
+            let attrs = attrs
+                .iter()
+                .map(bounded_attr)
+                .collect::<syn::Result<Vec<_>>>()?;
+            Ok(if let Some(ty) = inner_type {
                 quote! {
+                    #(#cfg_attrs)*
+                    #docs
                     #(#attrs)*
                     #name(#ty)
                 }
             } else {
                 quote! {
+                    #(#cfg_attrs)*
+                    #docs
                     #(#attrs)*
                     #name
                 }
-            }
+            })
         })
-        .collect();
+        .collect::<syn::Result<_>>()?;
 
     let display_arms: Vec<_> = tokens
         .iter()
         .map(|t| {
             let name = &t.name;
+            let cfg_attrs = &t.cfg_attrs;
             let fmt = t.fmt_str.as_ref().map(|s| s.value());
-            if t.inner_type.is_some() {
+            let arm = if t.inner_type.is_some() {
                 quote! {
                     Token::#name(v) => write!(f, "{}", v)
                 }
@@ -274,14 +876,16 @@ pub fn expand(input: DeclareTokensInput) -> syn::Result<TokenStream> {
                         Token::#name => write!(f, "<{}>", #name_str)
                     }
                 }
-            }
+            };
+            quote! { #(#cfg_attrs)* #arm }
         })
         .collect();
 
-    let token_structs: Vec<_> = tokens
+    let token_structs = tokens
         .iter()
         .map(|t| {
             let TokenDef {
+                cfg_attrs,
                 name,
                 inner_type,
                 fmt_str,
@@ -291,6 +895,9 @@ pub fn expand(input: DeclareTokensInput) -> syn::Result<TokenStream> {
                 ..
             } = t;
             let struct_name = format_ident!("{}Token", name);
+            let name_str = name.to_string();
+            let samples = t.samples();
+            let docs = t.doc_attrs();
 
             let all_derives = if extra_derives.is_empty() {
                 struct_derives_tokens.clone()
@@ -334,8 +941,51 @@ pub fn expand(input: DeclareTokensInput) -> syn::Result<TokenStream> {
                 }
             };
 
-            if let Some(ty) = inner_type {
+            // Payload tokens are their payload; others are their text
+            #[cfg(feature = "json")]
+            let json_impl = {
+                let value = if inner_type.is_some() {
+                    quote! { synkit::ToJsonValue::to_json(&self.0, spans) }
+                } else {
+                    quote! { synkit::serde_json::Value::String(Self::fmt().to_string()) }
+                };
                 quote! {
+                    impl synkit::ToJsonValue for #struct_name {
+                        #[allow(unused_variables)]
+                        fn to_json(&self, spans: bool) -> synkit::serde_json::Value {
+                            #value
+                        }
+                    }
+                }
+            };
+            #[cfg(not(feature = "json"))]
+            let json_impl = quote! {};
+
+            let kind = struct_name.to_string();
+            let query_impl = quote! {
+                impl synkit::QueryNode for #struct_name {
+                    #[inline]
+                    fn kind(&self) -> Option<&'static str> {
+                        Some(#kind)
+                    }
+
+                    fn text(&self) -> Option<std::borrow::Cow<'_, str>> {
+                        Some(std::borrow::Cow::Owned(self.token().to_string()))
+                    }
+
+                    #[inline]
+                    fn children<'a>(&'a self, _visit: &mut dyn FnMut(&'a dyn synkit::QueryNode)) {}
+
+                    #[inline]
+                    fn as_any(&self) -> Option<&dyn std::any::Any> {
+                        Some(self)
+                    }
+                }
+            };
+
+            let items = if let Some(ty) = inner_type {
+                let items = quote! {
+                    #docs
                     #[derive(#all_derives)]
                     pub struct #struct_name(pub #ty);
 
@@ -355,6 +1005,14 @@ pub fn expand(input: DeclareTokensInput) -> syn::Result<TokenStream> {
                         pub fn into_inner(self) -> #ty {
                             self.0
                         }
+
+                        /// This token with a call-site span.
+                        pub fn spanned(self) -> Spanned<Self> {
+                            Spanned {
+                                span: <Span as synkit::SpanLike>::call_site(),
+                                value: self,
+                            }
+                        }
                     }
 
                     impl Default for #struct_name {
@@ -383,10 +1041,72 @@ pub fn expand(input: DeclareTokensInput) -> syn::Result<TokenStream> {
                         }
                     }
 
+                    impl synkit::Generate for #struct_name {
+                        fn generate(g: &mut synkit::Generator) -> Self {
+                            const SAMPLES: &[&str] = &[#(#samples),*];
+                            g.sample_token(#name_str, SAMPLES, |sample| {
+                                let mut lexer = <Token as logos::Logos>::lexer(sample);
+                                match (lexer.next(), lexer.span()) {
+                                    (Some(Ok(Token::#name(value))), span) if span == (0..sample.len()) => {
+                                        Some(Self(value))
+                                    }
+                                    _ => None,
+                                }
+                            })
+                        }
+                    }
+
+                    impl synkit::Lower<Span> for #struct_name {
+                        type Output = #ty;
+
+                        fn lower(&self, _cx: &mut synkit::LowerCx<Span>) -> #ty {
+                            self.0.clone()
+                        }
+                    }
+
                     #to_tokens_impl
+
+                    #json_impl
+
+                    #query_impl
+                };
+                let conversions = token_conversions.then(|| {
+                    quote! {
+                        impl From<#ty> for #struct_name {
+                            #[inline]
+                            fn from(value: #ty) -> Self {
+                                Self(value)
+                            }
+                        }
+
+                        impl From<#struct_name> for Token {
+                            #[inline]
+                            fn from(token: #struct_name) -> Self {
+                                Token::#name(token.0)
+                            }
+                        }
+
+                        /// Gives the token back if it is another kind.
+                        impl TryFrom<Token> for #struct_name {
+                            type Error = Token;
+
+                            #[allow(unreachable_patterns)]
+                            fn try_from(token: Token) -> Result<Self, Token> {
+                                match token {
+                                    Token::#name(value) => Ok(Self(value)),
+                                    other => Err(other),
+                                }
+                            }
+                        }
+                    }
+                });
+                quote! {
+                    #items
+                    #conversions
                 }
             } else {
-                quote! {
+                let items = quote! {
+                    #docs
                     #[derive(#all_derives)]
                     pub struct #struct_name;
 
@@ -402,6 +1122,14 @@ pub fn expand(input: DeclareTokensInput) -> syn::Result<TokenStream> {
                         pub fn fmt() -> &'static str {
                             #fmt_impl
                         }
+
+                        /// This token with a call-site span.
+                        pub fn spanned(self) -> Spanned<Self> {
+                            Spanned {
+                                span: <Span as synkit::SpanLike>::call_site(),
+                                value: self,
+                            }
+                        }
                     }
 
                     impl Default for #struct_name {
@@ -423,11 +1151,84 @@ pub fn expand(input: DeclareTokensInput) -> syn::Result<TokenStream> {
                         }
                     }
 
+                    impl synkit::Generate for #struct_name {
+                        fn generate(g: &mut synkit::Generator) -> Self {
+                            g.take_token();
+                            Self::new()
+                        }
+                    }
+
+                    impl synkit::Lower<Span> for #struct_name {
+                        type Output = ();
+
+                        fn lower(&self, _cx: &mut synkit::LowerCx<Span>) {}
+                    }
+
                     #to_tokens_impl
+
+                    #json_impl
+
+                    #query_impl
+                };
+                let conversions = token_conversions.then(|| {
+                    quote! {
+                        impl From<#struct_name> for Token {
+                            #[inline]
+                            fn from(_: #struct_name) -> Self {
+                                Token::#name
+                            }
+                        }
+
+                        /// Gives the token back if it is another kind.
+                        impl TryFrom<Token> for #struct_name {
+                            type Error = Token;
+
+                            #[allow(unreachable_patterns)]
+                            fn try_from(token: Token) -> Result<Self, Token> {
+                                match token {
+                                    Token::#name => Ok(Self),
+                                    other => Err(other),
+                                }
+                            }
+                        }
+                    }
+                });
+                quote! {
+                    #items
+                    #conversions
                 }
-            }
+            };
+
+            // `SpannedToken` already names `Spanned<Token>`
+            let alias = (name != "Token").then(|| {
+                let alias = format_ident!("Spanned{}", name);
+                let doc = format!(" [`{struct_name}`] with its span.");
+                quote! {
+                    #[doc = #doc]
+                    pub type #alias = Spanned<#struct_name>;
+                }
+            });
+            with_cfg(cfg_attrs, quote! { #items #alias })
         })
-        .collect();
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let kind_markers = tokens
+        .iter()
+        .map(|t| {
+            let name = &t.name;
+            let struct_name = format_ident!("{}Token", name);
+            let doc = format!(" Names [`{struct_name}`](super::{struct_name}).");
+            let items = quote! {
+                #[doc = #doc]
+                pub enum #name {}
+
+                impl TokenKind for #name {
+                    type Token = super::#struct_name;
+                }
+            };
+            with_cfg(&t.cfg_attrs, items)
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
 
     let token_macro_arms: Vec<_> = tokens
         .iter()
@@ -450,13 +1251,13 @@ pub fn expand(input: DeclareTokensInput) -> syn::Result<TokenStream> {
                 // This handles punctuation like "=", "->", "::", etc.
                 if let Ok(token_trees) = s.parse::<proc_macro2::TokenStream>() {
                     Some(quote! {
-                        [#token_trees] => { $crate::tokens::#struct_name }
+                        [#token_trees] => { #kit_path::tokens::#struct_name }
                     })
                 } else if s.chars().all(|c| c.is_alphanumeric() || c == '_') {
                     // Keywords like "struct", "enum", etc.
                     let ident = format_ident!("{}", s);
                     Some(quote! {
-                        [#ident] => { $crate::tokens::#struct_name }
+                        [#ident] => { #kit_path::tokens::#struct_name }
                     })
                 } else {
                     // Can't create a macro arm for this token
@@ -467,7 +1268,7 @@ pub fn expand(input: DeclareTokensInput) -> syn::Result<TokenStream> {
                 let name_snake = to_snake_case(&name.to_string());
                 let name_ident = format_ident!("{}", name_snake);
                 Some(quote! {
-                    [#name_ident] => { $crate::tokens::#struct_name }
+                    [#name_ident] => { #kit_path::tokens::#struct_name }
                 })
             }
         })
@@ -503,20 +1304,84 @@ pub fn expand(input: DeclareTokensInput) -> syn::Result<TokenStream> {
             /// ```
             #[allow(non_snake_case)]
             macro_rules! SpannedTok {
-                ($tt:tt) => { $crate::span::Spanned<$crate::tokens::Tok![$tt]> };
+                ($tt:tt) => { #kit_path::span::Spanned<#kit_path::tokens::Tok![$tt]> };
             }
             pub(crate) use SpannedTok;
         }
     };
 
+    // Dense variant indices in declaration order, for per-kind tables
+    let kind_index_arms: Vec<_> = tokens
+        .iter()
+        .enumerate()
+        .map(|(i, t)| {
+            let name = &t.name;
+            let cfg_attrs = &t.cfg_attrs;
+            let pattern = if t.inner_type.is_some() {
+                quote! { Token::#name(_) }
+            } else {
+                quote! { Token::#name }
+            };
+            quote! { #(#cfg_attrs)* #pattern => #i, }
+        })
+        .collect();
+    let kind_count = tokens.len();
+
+    // Constructors named after the variants, e.g. `Token::kw_let()`. Names
+    // taken by `Logos`, `spanned` or `kind_index`, or unusable as raw
+    // identifiers, are left out; the variant itself still works.
+    let token_constructors: Vec<_> = tokens
+        .iter()
+        .filter_map(|t| {
+            let name = &t.name;
+            let cfg_attrs = &t.cfg_attrs;
+            let snake = to_snake_case(&name.to_string());
+            if matches!(
+                snake.as_str(),
+                "lexer"
+                    | "lexer_with_extras"
+                    | "spanned"
+                    | "kind_index"
+                    | "self"
+                    | "super"
+                    | "crate"
+            ) {
+                return None;
+            }
+            let ctor = syn::parse_str::<Ident>(&snake)
+                .unwrap_or_else(|_| Ident::new_raw(&snake, name.span()));
+            let doc = format!(" [`Token::{name}`].");
+            Some(if let Some(ty) = &t.inner_type {
+                quote! {
+                    #(#cfg_attrs)*
+                    #[doc = #doc]
+                    #[inline]
+                    pub fn #ctor(value: impl Into<#ty>) -> Self {
+                        Token::#name(value.into())
+                    }
+                }
+            } else {
+                quote! {
+                    #(#cfg_attrs)*
+                    #[doc = #doc]
+                    #[inline]
+                    pub fn #ctor() -> Self {
+                        Token::#name
+                    }
+                }
+            })
+        })
+        .collect();
+
     // Generate ToTokens arms for the Token enum
     // For tokens with no_to_tokens, we skip output (they handle their own serialization)
     let token_to_tokens_arms: Vec<_> = tokens
         .iter()
         .map(|t| {
             let name = &t.name;
+            let cfg_attrs = &t.cfg_attrs;
             let struct_name = format_ident!("{}Token", name);
-            if t.no_to_tokens {
+            let arm = if t.no_to_tokens {
                 // Token marked with #[no_to_tokens] - user handles this case
                 if t.inner_type.is_some() {
                     quote! {
@@ -535,13 +1400,237 @@ pub fn expand(input: DeclareTokensInput) -> syn::Result<TokenStream> {
                 quote! {
                     Token::#name => #struct_name::new().write(p)
                 }
-            }
+            };
+            quote! { #(#cfg_attrs)* #arm }
         })
         .collect();
 
+    // Payload variants are tagged like serde's default, `{"Ident": "x"}`
+    #[cfg(feature = "json")]
+    let token_json_impl = {
+        let arms = tokens.iter().map(|t| {
+            let name = &t.name;
+            let cfg_attrs = &t.cfg_attrs;
+            let struct_name = format_ident!("{}Token", name);
+            let tag = name.to_string();
+            let arm = if t.inner_type.is_some() {
+                quote! {
+                    Token::#name(v) => synkit::serde_json::Value::Object(
+                        synkit::serde_json::Map::from_iter([(
+                            #tag.to_string(),
+                            synkit::ToJsonValue::to_json(v, spans),
+                        )]),
+                    )
+                }
+            } else {
+                quote! {
+                    Token::#name => synkit::ToJsonValue::to_json(&#struct_name::new(), spans)
+                }
+            };
+            quote! { #(#cfg_attrs)* #arm }
+        });
+        quote! {
+            impl synkit::ToJsonValue for Token {
+                fn to_json(&self, spans: bool) -> synkit::serde_json::Value {
+                    match self {
+                        #(#arms),*
+                    }
+                }
+            }
+        }
+    };
+    #[cfg(not(feature = "json"))]
+    let token_json_impl = quote! {};
+
+    let shadowed_warnings = crate::token_conflicts::shadowed_literal_warnings(&tokens);
+
+    // `borrowed`: a second lexer whose payload tokens hold their text
+    let borrowed_token = if borrowed {
+        let variants = tokens
+            .iter()
+            .map(|t| {
+                let TokenDef {
+                    attrs,
+                    cfg_attrs,
+                    name,
+                    inner_type,
+                    ..
+                } = t;
+                let attrs = attrs
+                    .iter()
+                    .map(|attr| match inner_type {
+                        Some(_) => bounded_attr(&slice_callback(attr)),
+                        None => bounded_attr(attr),
+                    })
+                    .collect::<syn::Result<Vec<_>>>()?;
+                let payload = inner_type.as_ref().map(|_| quote! { (&'src str) });
+                Ok(quote! {
+                    #(#cfg_attrs)*
+                    #(#attrs)*
+                    #name #payload
+                })
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+        // Keeps `'src` used when every payload token is gated off
+        let phantom = tokens
+            .iter()
+            .all(|t| t.inner_type.is_none() || !t.cfg_attrs.is_empty())
+            .then(|| {
+                (
+                    quote! {
+                        #[doc(hidden)]
+                        __Source(std::marker::PhantomData<&'src str>),
+                    },
+                    quote! { BorrowedToken::__Source(_) => unreachable!(), },
+                )
+            });
+        let (phantom_variant, phantom_arm) = phantom.unzip();
+        let kind_arms = tokens.iter().enumerate().map(|(i, t)| {
+            let name = &t.name;
+            let cfg_attrs = &t.cfg_attrs;
+            let pattern = if t.inner_type.is_some() {
+                quote! { BorrowedToken::#name(_) }
+            } else {
+                quote! { BorrowedToken::#name }
+            };
+            quote! { #(#cfg_attrs)* #pattern => #i, }
+        });
+        let text_arms = tokens.iter().filter(|t| t.inner_type.is_some()).map(|t| {
+            let name = &t.name;
+            let cfg_attrs = &t.cfg_attrs;
+            quote! { #(#cfg_attrs)* BorrowedToken::#name(text) => Some(text), }
+        });
+        let display_arms = tokens.iter().map(|t| {
+            let name = &t.name;
+            let cfg_attrs = &t.cfg_attrs;
+            if t.inner_type.is_some() {
+                quote! { #(#cfg_attrs)* BorrowedToken::#name(text) => f.write_str(text), }
+            } else {
+                quote! { #(#cfg_attrs)* BorrowedToken::#name => std::fmt::Display::fmt(&Token::#name, f), }
+            }
+        });
+        quote! {
+            /// [`Token`] as lexed by `BorrowedStream`: each payload token
+            /// holds the `&'src str` it matched instead of running its
+            /// callback. Generated by `borrowed: true`.
+            #[derive(logos::Logos, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            #(#logos_attrs)*
+            #[logos(error = #error_ref)]
+            pub enum BorrowedToken<'src> {
+                #(#variants,)*
+                #phantom_variant
+            }
+
+            impl<'src> BorrowedToken<'src> {
+                /// Same as [`Token::kind_index`] for the same variant.
+                #[inline]
+                pub const fn kind_index(&self) -> usize {
+                    match *self {
+                        #(#kind_arms)*
+                        #phantom_arm
+                    }
+                }
+
+                /// The text a payload token matched; `None` for other tokens.
+                #[inline]
+                pub fn text(&self) -> Option<&'src str> {
+                    match *self {
+                        #(#text_arms)*
+                        _ => None,
+                    }
+                }
+            }
+
+            impl std::fmt::Display for BorrowedToken<'_> {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    match *self {
+                        #(#display_arms)*
+                        #phantom_arm
+                    }
+                }
+            }
+
+            pub type SpannedBorrowedToken<'src> = Spanned<BorrowedToken<'src>>;
+        }
+    } else {
+        quote! {}
+    };
+
+    // One test per token with a sample; regex samples skip other tokens'
+    // literals, which would rightly lex as those tokens instead
+    let token_tests_module = if token_tests {
+        let literals: Vec<String> = tokens.iter().flat_map(|t| t.patterns("token")).collect();
+        let tests = tokens
+            .iter()
+            .filter_map(|t| {
+                let sample = t.primary_sample(&literals)?;
+                let name = &t.name;
+                let cfg_attrs = &t.cfg_attrs;
+                let name_str = name.to_string();
+                let placeholder = format!("<{name_str}>");
+                let test_name = format_ident!("lexes_{}", to_snake_case(&name_str));
+                let pattern = if t.inner_type.is_some() {
+                    quote! { Token::#name(_) }
+                } else {
+                    quote! { Token::#name }
+                };
+                Some(quote! {
+                    #(#cfg_attrs)*
+                    #[test]
+                    fn #test_name() {
+                        let sample: &str = #sample;
+                        let mut lexer = <Token as logos::Logos>::lexer(sample);
+                        let token = match lexer.next() {
+                            Some(Ok(token)) => token,
+                            Some(Err(_)) => panic!("{:?} failed to lex, expected `{}`", sample, #name_str),
+                            None => panic!("{:?} lexed to nothing, expected `{}`", sample, #name_str),
+                        };
+                        assert!(
+                            matches!(token, #pattern),
+                            "{:?} lexed as {:?}, expected `{}`",
+                            sample,
+                            token.to_string(),
+                            #name_str,
+                        );
+                        assert_eq!(
+                            lexer.span(),
+                            0..sample.len(),
+                            "`{}` matched only part of {:?}",
+                            #name_str,
+                            sample,
+                        );
+                        assert!(lexer.next().is_none(), "{:?} lexed to several tokens", sample);
+
+                        let shown = token.to_string();
+                        assert!(!shown.is_empty(), "`{}` displays as an empty string", #name_str);
+                        assert_ne!(
+                            shown,
+                            #placeholder,
+                            "`{}` displays as a placeholder; give it a #[fmt(\"...\")]",
+                            #name_str,
+                        );
+                    }
+                })
+            });
+        quote! {
+            /// Lexes a sample of each token and checks its variant and
+            /// `Display`. Generated by `token_tests: true`.
+            #[cfg(test)]
+            mod token_tests {
+                use super::Token;
+
+                #(#tests)*
+            }
+        }
+    } else {
+        quote! {}
+    };
+
     let output = quote! {
         #span_import
 
+        #string_payload
+
         #[derive(logos::Logos, #derives_tokens)]
         #(#logos_attrs)*
         #[logos(error = #error_ref)]
@@ -549,6 +1638,33 @@ pub fn expand(input: DeclareTokensInput) -> syn::Result<TokenStream> {
             #(#token_variants),*
         }
 
+        impl Token {
+            /// The number of token kinds; [`kind_index`](Self::kind_index)
+            /// is always below it.
+            pub const KIND_COUNT: usize = #kind_count;
+
+            /// The variant's position in the declaration, for tables indexed
+            /// by token kind.
+            #[inline]
+            pub const fn kind_index(&self) -> usize {
+                match *self {
+                    #(#kind_index_arms)*
+                }
+            }
+
+            #(#token_constructors)*
+
+            /// This token with a call-site span, for tokens built in code:
+            /// `Token::ident("x").spanned()`.
+            #[inline]
+            pub fn spanned(self) -> SpannedToken {
+                Spanned {
+                    span: <Span as synkit::SpanLike>::call_site(),
+                    value: self,
+                }
+            }
+        }
+
         impl std::fmt::Display for Token {
             fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                 match self {
@@ -565,12 +1681,83 @@ pub fn expand(input: DeclareTokensInput) -> syn::Result<TokenStream> {
             }
         }
 
+        #token_json_impl
+
+        #borrowed_token
+
+        #token_tests_module
+
+        #shadowed_warnings
+
         #(#token_structs)*
 
+        /// Uninhabited markers naming each token, for `TokenStream::token`:
+        /// `stream.token::<kind::Ident>()` parses a `Spanned<IdentToken>`.
+        pub mod kind {
+            /// Maps a marker to its token struct.
+            pub trait TokenKind {
+                type Token: super::super::traits::Parse;
+            }
+
+            #(#kind_markers)*
+        }
+
         pub type SpannedToken = Spanned<Token>;
 
+        // Lets `Token` and `SpannedToken` feed `IncrementalParse` and the
+        // streaming adapters, which take any `AsRef<Token>` item.
+        impl AsRef<Token> for Token {
+            #[inline]
+            fn as_ref(&self) -> &Token {
+                self
+            }
+        }
+
+        impl AsRef<Token> for SpannedToken {
+            #[inline]
+            fn as_ref(&self) -> &Token {
+                &self.value
+            }
+        }
+
+        impl From<SpannedToken> for Token {
+            #[inline]
+            fn from(token: SpannedToken) -> Self {
+                token.value
+            }
+        }
+
         #token_macro
     };
 
     Ok(output)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_doc_attrs_include_patterns() {
+        let token: TokenDef = syn::parse_str(
+            r#"
+            /// A name.
+            #[regex(r"[a-z]+", |lex| lex.slice().to_string(), priority = 2)]
+            #[token("self")]
+            Ident(String)
+            "#,
+        )
+        .unwrap();
+        let attrs = token.doc_attrs().to_string();
+        assert!(attrs.contains(r#"" A name.""#), "{attrs}");
+        assert!(attrs.contains(r#"" - regex: `[a-z]+`""#), "{attrs}");
+        assert!(attrs.contains(r#"" - token: `self`""#), "{attrs}");
+    }
+
+    #[test]
+    fn test_code_span_escapes_backticks() {
+        assert_eq!(code_span("("), "`(`");
+        assert_eq!(code_span("`"), "`` ` ``");
+        assert_eq!(code_span("a``b"), "``` a``b ```");
+    }
+}
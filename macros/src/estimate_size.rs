@@ -0,0 +1,73 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Fields, parse_quote};
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(synkit::EstimateSize));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let (bindings, pattern) = bind_fields(&data.fields);
+            let sum = sum_expr(&bindings);
+            quote! {
+                let Self #pattern = self;
+                #sum
+            }
+        }
+        Data::Enum(data) if data.variants.is_empty() => quote! { match *self {} },
+        Data::Enum(data) => {
+            let arms = data.variants.iter().map(|variant| {
+                let vname = &variant.ident;
+                let (bindings, pattern) = bind_fields(&variant.fields);
+                let sum = sum_expr(&bindings);
+                quote! { Self::#vname #pattern => #sum, }
+            });
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "EstimateSize cannot be derived for unions",
+            ));
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics synkit::EstimateSize for #name #ty_generics #where_clause {
+            fn heap_size(&self) -> usize {
+                #body
+            }
+        }
+    })
+}
+
+/// Bind each field to `f_<n>`, returning the bindings and the destructuring
+/// pattern (`{ x: f_0, .. }`, `(f_0, ..)` or nothing).
+fn bind_fields(fields: &Fields) -> (Vec<syn::Ident>, TokenStream) {
+    let bindings: Vec<_> = (0..fields.len())
+        .map(|i| format_ident!("f_{}", i))
+        .collect();
+    let pattern = match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|f| &f.ident);
+            quote! { { #(#names: #bindings),* } }
+        }
+        Fields::Unnamed(_) => quote! { ( #(#bindings),* ) },
+        Fields::Unit => quote! {},
+    };
+    (bindings, pattern)
+}
+
+fn sum_expr(bindings: &[syn::Ident]) -> TokenStream {
+    quote! { 0 #(+ synkit::EstimateSize::heap_size(#bindings))* }
+}
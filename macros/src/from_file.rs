@@ -0,0 +1,124 @@
+use std::path::PathBuf;
+
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::quote;
+use syn::LitStr;
+
+/// Replace `from_file: "path"` in a `parser_kit!` input with the fields
+/// of that grammar file. Tokens and delimiters written in the invocation
+/// add to the file's and are reported as duplicates if they redefine one;
+/// other fields written after it override the file's.
+///
+/// The path is relative to the invoking crate's `CARGO_MANIFEST_DIR`.
+/// Returns the rewritten input and the absolute paths read, for
+/// [`track`].
+pub fn resolve(input: TokenStream) -> syn::Result<(TokenStream, Vec<PathBuf>)> {
+    let trees: Vec<TokenTree> = input.into_iter().collect();
+    let mut out = Vec::with_capacity(trees.len());
+    let mut files = Vec::new();
+
+    let mut i = 0;
+    while i < trees.len() {
+        let is_field = matches!(
+            (&trees[i], trees.get(i + 1)),
+            (TokenTree::Ident(id), Some(TokenTree::Punct(p))) if id == "from_file" && p.as_char() == ':'
+        );
+        if !is_field {
+            out.push(trees[i].clone());
+            i += 1;
+            continue;
+        }
+        let field_span = trees[i].span();
+        let lit: LitStr = match trees.get(i + 2) {
+            Some(tree) => syn::parse2(tree.clone().into())?,
+            None => {
+                return Err(syn::Error::new(
+                    field_span,
+                    "expected `from_file: \"path/to/grammar.toml\"`",
+                ));
+            }
+        };
+        i += 3;
+        if matches!(trees.get(i), Some(TokenTree::Punct(p)) if p.as_char() == ',') {
+            i += 1;
+        }
+
+        let path = manifest_dir(lit.span())?.join(lit.value());
+        out.extend(load(&path, &lit)?);
+        files.push(path);
+    }
+
+    Ok((out.into_iter().collect(), files))
+}
+
+/// The `parser_kit!` fields of the grammar file at `path`.
+#[cfg(feature = "from_file")]
+fn load(path: &std::path::Path, lit: &LitStr) -> syn::Result<TokenStream> {
+    let grammar =
+        synkit_build::Grammar::from_path(path).map_err(|err| syn::Error::new(lit.span(), err))?;
+    let fields = grammar
+        .to_fields()
+        .map_err(|err| syn::Error::new(lit.span(), format!("{}: {err}", lit.value())))?;
+    Ok(fields.parse()?)
+}
+
+#[cfg(not(feature = "from_file"))]
+fn load(_path: &std::path::Path, lit: &LitStr) -> syn::Result<TokenStream> {
+    Err(syn::Error::new(
+        lit.span(),
+        "`from_file` needs the `from_file` feature of synkit",
+    ))
+}
+
+/// Items that make the compiler rebuild when any of `files` changes.
+pub fn track(files: &[PathBuf]) -> TokenStream {
+    let paths = files.iter().map(|path| path.to_string_lossy().into_owned());
+    quote! {
+        #(const _: &[u8] = include_bytes!(#paths);)*
+    }
+}
+
+fn manifest_dir(span: Span) -> syn::Result<PathBuf> {
+    std::env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .ok_or_else(|| {
+            syn::Error::new(
+                span,
+                "`from_file` needs CARGO_MANIFEST_DIR; build with Cargo",
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "from_file")]
+    #[test]
+    fn test_splices_fields() {
+        let input = quote! { error: E, from_file: "../kit/tests/grammar/assign.toml", tokens: {} };
+        let (resolved, files) = resolve(input).unwrap();
+        let resolved = resolved.to_string();
+        assert!(
+            resolved.starts_with("error : E , skip_tokens : [Space] ,"),
+            "{resolved}"
+        );
+        assert!(resolved.ends_with("} , tokens : { }"), "{resolved}");
+        assert!(files[0].ends_with("kit/tests/grammar/assign.toml"));
+    }
+
+    #[test]
+    fn test_without_from_file() {
+        let input = quote! { error: E, tokens: {} };
+        let (resolved, files) = resolve(input.clone()).unwrap();
+        assert_eq!(resolved.to_string(), input.to_string());
+        assert!(files.is_empty());
+    }
+
+    #[cfg(feature = "from_file")]
+    #[test]
+    fn test_missing_file() {
+        let err = resolve(quote! { from_file: "missing.toml" }).unwrap_err();
+        assert!(err.to_string().contains("missing.toml"), "{err}");
+    }
+}
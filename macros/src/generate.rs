@@ -0,0 +1,140 @@
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+use syn::{Data, DeriveInput, Fields, Ident, Variant, parse_quote};
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(synkit::Generate));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let construct = construct(quote!(Self), &data.fields);
+            quote! { #construct }
+        }
+        Data::Enum(data) if data.variants.is_empty() => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "Generate cannot be derived for enums without variants",
+            ));
+        }
+        Data::Enum(data) => {
+            let last = data.variants.len() - 1;
+            let arms = data.variants.iter().enumerate().map(|(i, variant)| {
+                let vname = &variant.ident;
+                let construct = construct(quote!(Self::#vname), &variant.fields);
+                if i == last {
+                    quote! { _ => #construct, }
+                } else {
+                    quote! { #i => #construct, }
+                }
+            });
+            let count = data.variants.len();
+            let variants: Vec<_> = data.variants.iter().collect();
+            let fallback = fallback_variant(name, &variants)?;
+            quote! {
+                let variant = if g.exhausted() { #fallback } else { g.below(#count) };
+                match variant {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "Generate cannot be derived for unions",
+            ));
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics synkit::Generate for #name #ty_generics #where_clause {
+            fn generate(g: &mut synkit::Generator) -> Self {
+                g.nested(|g| { #body })
+            }
+        }
+    })
+}
+
+/// `path { x: generate(g), .. }`, `path(generate(g), ..)` or `path`, with
+/// fields generated in declaration order.
+fn construct(path: TokenStream, fields: &Fields) -> TokenStream {
+    let generate = quote! { synkit::Generate::generate(g) };
+    match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|f| &f.ident);
+            quote! { #path { #(#names: #generate),* } }
+        }
+        Fields::Unnamed(unnamed) => {
+            let values = unnamed.unnamed.iter().map(|_| &generate);
+            quote! { #path ( #(#values),* ) }
+        }
+        Fields::Unit => path,
+    }
+}
+
+/// The variant used once the generator is exhausted: the one marked
+/// `#[generate(fallback)]`, else the one needing the fewest non-token
+/// fields among those that don't mention the enum itself, so recursive
+/// grammars bottom out.
+fn fallback_variant(name: &Ident, variants: &[&Variant]) -> syn::Result<usize> {
+    let mut marked = None;
+    for (i, variant) in variants.iter().enumerate() {
+        for attr in variant
+            .attrs
+            .iter()
+            .filter(|a| a.path().is_ident("generate"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("fallback") {
+                    marked = Some(i);
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `fallback`"))
+                }
+            })?;
+        }
+    }
+    if let Some(i) = marked {
+        return Ok(i);
+    }
+
+    let name = name.to_string();
+    let fallback = variants
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| {
+            !v.fields
+                .iter()
+                .any(|f| mentions(f.ty.to_token_stream(), &name))
+        })
+        .min_by_key(|(_, v)| {
+            let nodes = v.fields.iter().filter(|f| !is_token(&f.ty)).count();
+            (nodes, v.fields.len())
+        })
+        .map_or(0, |(i, _)| i);
+    Ok(fallback)
+}
+
+fn mentions(tokens: TokenStream, name: &str) -> bool {
+    tokens.into_iter().any(|tt| match tt {
+        proc_macro2::TokenTree::Ident(ident) => ident == name || ident == "Self",
+        proc_macro2::TokenTree::Group(group) => mentions(group.stream(), name),
+        _ => false,
+    })
+}
+
+/// Whether `ty` looks like a token struct, e.g. `Spanned<tokens::IdentToken>`.
+fn is_token(ty: &syn::Type) -> bool {
+    let mut last = None;
+    for tt in ty.to_token_stream() {
+        if let proc_macro2::TokenTree::Ident(ident) = tt {
+            last = Some(ident.to_string());
+        }
+    }
+    last.is_some_and(|ident| ident.ends_with("Token"))
+}
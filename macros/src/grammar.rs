@@ -0,0 +1,390 @@
+//! `#[derive(Grammar)]`: `Peek` and `Parse` impls from FIRST sets.
+//!
+//! The FIRST set of a node is computed from its field types: fields are
+//! parsed in order, and `Option<T>` and `Vec<T>` may be empty, so a node can
+//! start with any of its leading fields up to and including the first one
+//! that can't be. `Spanned<T>` and `Box<T>` are looked through. What's left
+//! are the node's FIRST atoms, whose own `Peek` impls decide which tokens
+//! they start with.
+//!
+//! Enums dispatch on the next token: the first variant whose FIRST set
+//! accepts it is parsed, else the first variant that may be empty. Atoms
+//! shared by two variants are reported as warnings, since the later variant
+//! is never chosen for them.
+
+use std::collections::HashMap;
+
+use proc_macro2::TokenStream;
+use quote::{ToTokens, format_ident, quote};
+use syn::{
+    Attribute, Data, DeriveInput, Fields, GenericArgument, Ident, LitStr, Path, PathArguments,
+    Type, parse_quote,
+};
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let container = ContainerAttrs::parse(name, &input.attrs)?;
+    let error = &container.error;
+    let synkit_path = &container.crate_path;
+    let expect = container
+        .expect
+        .unwrap_or_else(|| LitStr::new(&name.to_string(), name.span()));
+
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(Parse));
+        param.bounds.push(parse_quote!(Peek));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let unexpected = quote! {
+        Err(match stream.peek_token() {
+            Some(tok) => #error::Expected {
+                expect: #expect,
+                found: tok.value.to_string(),
+            },
+            None => #error::Empty { expect: #expect },
+        })
+    };
+
+    let (first, body, warnings) = match &input.data {
+        Data::Struct(data) => {
+            let first = First::of(&data.fields);
+            let construct = construct(quote!(Self), &data.fields);
+            (first.atoms, quote! { Ok(#construct) }, TokenStream::new())
+        }
+        Data::Enum(data) => {
+            let alts: Vec<_> = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let vname = &variant.ident;
+                    let first = First::of(&variant.fields);
+                    let construct = construct(quote!(Self::#vname), &variant.fields);
+                    (vname, first, construct)
+                })
+                .collect();
+            let warnings = ambiguity_warnings(name, &alts);
+
+            let mut arms = Vec::new();
+            for (i, (_, first, _)) in alts.iter().enumerate() {
+                if let Some(is) = is_any(&first.atoms) {
+                    arms.push(quote! { if #is { return #i; } });
+                }
+            }
+            let fallback = alts
+                .iter()
+                .position(|(_, first, _)| first.nullable)
+                .map_or_else(|| quote! { usize::MAX }, |i| quote! { #i });
+            let constructs: Vec<_> = alts
+                .iter()
+                .enumerate()
+                .map(|(i, (_, _, construct))| quote! { #i => Ok(#construct), })
+                .collect();
+
+            let first = alts
+                .into_iter()
+                .flat_map(|(_, first, _)| first.atoms)
+                .collect();
+            let body = quote! {
+                use #synkit_path::TokenStream as _;
+                let alt = stream.peek_token().map_or(#fallback, |tok| {
+                    let token = &tok.value;
+                    #(#arms)*
+                    #fallback
+                });
+                match alt {
+                    #(#constructs)*
+                    _ => #unexpected,
+                }
+            };
+            (first, body, warnings)
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "Grammar cannot be derived for unions",
+            ));
+        }
+    };
+
+    let is = is_any(&first).unwrap_or_else(|| quote! { false });
+
+    Ok(quote! {
+        #warnings
+
+        impl #impl_generics Peek for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn is(token: &Token) -> bool {
+                #is
+            }
+        }
+
+        impl #impl_generics Parse for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn parse(stream: &mut TokenStream) -> Result<Self, #error> {
+                #body
+            }
+        }
+    })
+}
+
+/// `#[grammar(...)]` on the struct or enum.
+struct ContainerAttrs {
+    error: Path,
+    expect: Option<LitStr>,
+    /// The kit's `crate_path`; `synkit` by default.
+    crate_path: Path,
+}
+
+impl ContainerAttrs {
+    fn parse(name: &Ident, attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut error = None;
+        let mut expect = None;
+        let mut crate_path = None;
+        for attr in attrs.iter().filter(|a| a.path().is_ident("grammar")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("error") {
+                    error = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("expect") {
+                    expect = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("crate_path") {
+                    crate_path = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("expected `error`, `expect` or `crate_path`"));
+                }
+                Ok(())
+            })?;
+        }
+        let error = error.ok_or_else(|| {
+            syn::Error::new_spanned(
+                name,
+                "Grammar needs the parser's error type: add `#[grammar(error = MyError)]`",
+            )
+        })?;
+        Ok(Self {
+            error,
+            expect,
+            crate_path: crate_path.unwrap_or_else(|| parse_quote!(synkit)),
+        })
+    }
+}
+
+/// The FIRST set of a sequence of fields.
+struct First<'a> {
+    /// Types whose `Peek` impls make up the set, in field order.
+    atoms: Vec<&'a Type>,
+    /// Whether every field may be empty.
+    nullable: bool,
+}
+
+impl<'a> First<'a> {
+    fn of(fields: &'a Fields) -> Self {
+        let mut atoms = Vec::new();
+        for field in fields {
+            let (atom, nullable) = atom(&field.ty);
+            atoms.push(atom);
+            if !nullable {
+                return Self {
+                    atoms,
+                    nullable: false,
+                };
+            }
+        }
+        Self {
+            atoms,
+            nullable: true,
+        }
+    }
+}
+
+/// The type `ty` starts with once `Option`, `Vec`, `Box` and `Spanned` are
+/// looked through, and whether `ty` may be empty.
+fn atom(ty: &Type) -> (&Type, bool) {
+    let Some((wrapper, inner)) = single_arg(ty) else {
+        return (ty, false);
+    };
+    let (atom, nullable) = atom(inner);
+    match wrapper.as_str() {
+        "Option" | "Vec" => (atom, true),
+        "Box" | "Spanned" => (atom, nullable),
+        _ => (ty, false),
+    }
+}
+
+/// `Name<T>` as `("Name", T)`.
+fn single_arg(ty: &Type) -> Option<(String, &Type)> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.iter().collect::<Vec<_>>().as_slice() {
+        [GenericArgument::Type(inner)] => Some((segment.ident.to_string(), inner)),
+        _ => None,
+    }
+}
+
+/// `<A as Peek>::is(token) || ...` over the distinct atoms, or `None` for
+/// an empty set.
+fn is_any(atoms: &[&Type]) -> Option<TokenStream> {
+    let mut seen = Vec::new();
+    let mut checks = Vec::new();
+    for atom in atoms {
+        let key = key(atom);
+        if !seen.contains(&key) {
+            seen.push(key);
+            checks.push(quote! { <#atom as Peek>::is(token) });
+        }
+    }
+    (!checks.is_empty()).then(|| quote! { #(#checks)||* })
+}
+
+/// `ty` as written, without the spaces `to_string` puts between tokens.
+fn key(ty: &Type) -> String {
+    ty.to_token_stream().to_string().replace(' ', "")
+}
+
+/// `path { x: stream.parse()?, .. }`, `path(.., ..)` or `path`, parsing
+/// fields in declaration order.
+fn construct(path: TokenStream, fields: &Fields) -> TokenStream {
+    let values: Vec<_> = fields.iter().map(|field| parse_value(&field.ty)).collect();
+    match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|f| &f.ident);
+            quote! { #path { #(#names: #values),* } }
+        }
+        Fields::Unnamed(_) => quote! { #path ( #(#values),* ) },
+        Fields::Unit => path,
+    }
+}
+
+/// An expression parsing a `ty` from `stream`.
+fn parse_value(ty: &Type) -> TokenStream {
+    match single_arg(ty) {
+        Some((wrapper, _)) if wrapper == "Spanned" => quote! { stream.parse()? },
+        Some((wrapper, inner)) if wrapper == "Box" => {
+            let value = parse_value(inner);
+            quote! { Box::new(#value) }
+        }
+        _ => quote! { <#ty as Parse>::parse(stream)? },
+    }
+}
+
+/// A warning for every FIRST atom a variant shares with an earlier one, and
+/// for every empty-able variant after the first.
+fn ambiguity_warnings(name: &Ident, alts: &[(&Ident, First, TokenStream)]) -> TokenStream {
+    let mut taken: HashMap<String, &Ident> = HashMap::new();
+    let mut nullable: Option<&Ident> = None;
+    let mut notes = Vec::new();
+    for (vname, first, _) in alts {
+        let mut reported = Vec::new();
+        for atom in &first.atoms {
+            let key = key(atom);
+            match taken.get(&key) {
+                Some(earlier) if earlier != vname && !reported.contains(&key) => {
+                    notes.push((
+                        *vname,
+                        format!(
+                            "`{name}::{vname}` can start with `{key}`, like `{name}::{earlier}`; \
+                             `{earlier}` is tried first, so `{vname}` is never parsed there"
+                        ),
+                    ));
+                    reported.push(key);
+                }
+                Some(_) => {}
+                None => {
+                    taken.insert(key, vname);
+                }
+            }
+        }
+        if first.nullable {
+            match nullable {
+                Some(earlier) => notes.push((
+                    *vname,
+                    format!(
+                        "`{name}::{vname}` and `{name}::{earlier}` can both be empty; \
+                         `{earlier}` is parsed when no variant matches, so `{vname}` never is"
+                    ),
+                )),
+                None => nullable = Some(vname),
+            }
+        }
+    }
+    notes
+        .into_iter()
+        .enumerate()
+        .map(|(index, (vname, note))| {
+            crate::token_conflicts::warning(
+                format_ident!("ambiguous_first_set_{}", index, span = vname.span()),
+                &note,
+                std::iter::empty(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn derive(src: &str) -> String {
+        expand(syn::parse_str(src).unwrap()).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_first_set_stops_at_required_field() {
+        let fields: syn::FieldsUnnamed =
+            syn::parse_str("(Option<Spanned<A>>, Vec<B>, Box<C>, D)").unwrap();
+        let fields = Fields::Unnamed(fields);
+        let first = First::of(&fields);
+        let atoms: Vec<_> = first
+            .atoms
+            .iter()
+            .map(|t| t.to_token_stream().to_string())
+            .collect();
+        assert_eq!(atoms, ["A", "B", "C"]);
+        assert!(!first.nullable);
+    }
+
+    #[test]
+    fn test_nullable_fields() {
+        let fields: syn::FieldsUnnamed = syn::parse_str("(Option<A>, Vec<Spanned<B>>)").unwrap();
+        let fields = Fields::Unnamed(fields);
+        let first = First::of(&fields);
+        assert_eq!(first.atoms.len(), 2);
+        assert!(first.nullable);
+    }
+
+    #[test]
+    fn test_ambiguous_variants_warn() {
+        let output = derive(
+            "#[grammar(error = E)] enum Value { A(Spanned<XToken>), B(Spanned<XToken>, Y) }",
+        );
+        assert!(output.contains("deprecated"));
+        assert!(output.contains("ambiguous_first_set_0"));
+
+        let output =
+            derive("#[grammar(error = E)] enum Value { A(Spanned<XToken>), B(Spanned<YToken>) }");
+        assert!(!output.contains("deprecated"));
+    }
+
+    #[test]
+    fn test_crate_path() {
+        let output = derive(
+            "#[grammar(error = E, crate_path = ::my::synkit)] enum Value { A(Spanned<XToken>) }",
+        );
+        assert!(
+            output.contains("use :: my :: synkit :: TokenStream as _"),
+            "{output}"
+        );
+    }
+
+    #[test]
+    fn test_requires_error_type() {
+        let input = syn::parse_str("struct Node(A);").unwrap();
+        assert!(expand(input).is_err());
+    }
+}
@@ -15,6 +15,10 @@
 //! - [`declare_tokens!`]: Generates a token enum compatible with Logos
 //! - [`parser_kit!`]: Generates a complete parser infrastructure
 //!
+//! `expand_to_string!`, with the `expand_to_string` feature, renders what
+//! [`parser_kit!`] generates, for reviewing and snapshot testing the
+//! expansion.
+//!
 //! # Quick Start
 //!
 //! ```ignore
@@ -31,10 +35,23 @@
 //! }
 //! ```
 use proc_macro::TokenStream;
-use syn::parse_macro_input;
+use syn::{DeriveInput, parse_macro_input};
 
 mod declare_tokens;
+mod estimate_size;
+mod from_file;
+mod generate;
+mod grammar;
+mod lower;
 mod parser_kit;
+mod query;
+mod query_node;
+mod quote_tokens;
+mod regex_sample;
+mod span_insensitive;
+mod to_json_value;
+mod token_conflicts;
+mod token_set;
 
 /// Generates a token enum with Logos lexer integration.
 ///
@@ -238,8 +255,357 @@ pub fn declare_tokens(input: TokenStream) -> TokenStream {
 /// ```
 #[proc_macro]
 pub fn parser_kit(input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as parser_kit::ParserKitInput);
-    parser_kit::expand(input)
+    let (input, files) = match from_file::resolve(input.into()) {
+        Ok(resolved) => resolved,
+        Err(err) => return err.into_compile_error().into(),
+    };
+    let track = from_file::track(&files);
+    match token_set::expand_includes(input.clone()) {
+        Ok(Some(expanded)) => return quote::quote! { #track #expanded }.into(),
+        Ok(None) => {}
+        Err(err) => return err.into_compile_error().into(),
+    }
+    let expanded = syn::parse2::<parser_kit::ParserKitInput>(input)
+        .and_then(parser_kit::expand)
+        .unwrap_or_else(syn::Error::into_compile_error);
+    quote::quote! { #track #expanded }.into()
+}
+
+/// Expands a [`parser_kit!`] input to a string literal of the generated code.
+///
+/// Takes the same input as [`parser_kit!`] and evaluates to a `&'static str`
+/// holding the pretty-printed expansion, for reviewing or snapshot testing
+/// what a grammar generates:
+///
+/// ```ignore
+/// #[test]
+/// fn parser_kit_expansion() {
+///     insta::assert_snapshot!(synkit::expand_to_string! {
+///         error: MyError,
+///         skip_tokens: [Space],
+///         tokens: {
+///             #[token(" ")]
+///             Space,
+///             #[token("=")]
+///             Eq,
+///         },
+///     });
+/// }
+/// ```
+///
+/// Proc-macro crates can only export macros, so this is a macro rather than
+/// a function. `include_tokens` and `extends` are resolved by invoking other
+/// macros and are not supported here. The output depends on the enabled
+/// `std`/`tokio`/`futures` features.
+///
+/// Requires the `expand_to_string` feature.
+#[cfg(feature = "expand_to_string")]
+#[proc_macro]
+pub fn expand_to_string(input: TokenStream) -> TokenStream {
+    let (tokens, files) = match from_file::resolve(input.into()) {
+        Ok(resolved) => resolved,
+        Err(err) => return err.into_compile_error().into(),
+    };
+    let track = from_file::track(&files);
+    match token_set::expand_includes(tokens.clone()) {
+        Ok(Some(_)) => {
+            return syn::Error::new_spanned(
+                tokens,
+                "`include_tokens` and `extends` are not supported by `expand_to_string!`",
+            )
+            .into_compile_error()
+            .into();
+        }
+        Ok(None) => {}
+        Err(err) => return err.into_compile_error().into(),
+    }
+    let code = syn::parse2::<parser_kit::ParserKitInput>(tokens)
+        .and_then(parser_kit::expand_to_string)
+        .map(|code| syn::LitStr::new(&code, proc_macro2::Span::call_site()));
+    match code {
+        Ok(code) if files.is_empty() => quote::ToTokens::into_token_stream(code).into(),
+        Ok(code) => quote::quote! {{ #track #code }}.into(),
+        Err(err) => err.into_compile_error().into(),
+    }
+}
+
+/// Defines a reusable set of token definitions.
+///
+/// Generates a `macro_rules!` named after the set that other
+/// [`parser_kit!`] invocations pull in with `include_tokens`. Attributes
+/// (e.g. `#[macro_export]`) are applied to the generated macro.
+///
+/// ```ignore
+/// synkit::token_set! {
+///     #[macro_export]
+///     expr_tokens {
+///         #[token("+")]
+///         Plus,
+///         #[regex(r"[0-9]+", |lex| lex.slice().parse().ok())]
+///         Number(i64),
+///     }
+/// }
+///
+/// synkit::parser_kit! {
+///     error: MyError,
+///     include_tokens: [expr_tokens],
+///     tokens: {
+///         #[token("let")]
+///         KwLet,
+///     },
+/// }
+/// ```
+#[proc_macro]
+pub fn token_set(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as token_set::TokenSetInput);
+    token_set::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `synkit::SpanInsensitive` for AST nodes.
+///
+/// Every field is compared and hashed with `SpanInsensitive`, so spans
+/// (and `Spanned<T>` offsets) anywhere in the tree are ignored. Type
+/// parameters get a `SpanInsensitive` bound.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(SpanInsensitive)]
+/// enum Value {
+///     Number(Spanned<NumberToken>),
+///     List(Vec<Spanned<Value>>),
+/// }
+///
+/// assert_eq!(IgnoreSpans(parse("[1, 2]")?), IgnoreSpans(parse("[1,2]")?));
+/// ```
+#[proc_macro_derive(SpanInsensitive)]
+pub fn derive_span_insensitive(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    span_insensitive::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `synkit::EstimateSize` for AST nodes.
+///
+/// The heap size is the sum of every field's heap size; the inline size of
+/// the node itself comes from `size_of_val`. Type parameters get an
+/// `EstimateSize` bound.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(EstimateSize)]
+/// struct Table {
+///     name: Spanned<IdentToken>,
+///     rows: Vec<Spanned<Row>>,
+/// }
+///
+/// let bytes = table.estimated_size();
+/// ```
+#[proc_macro_derive(EstimateSize)]
+pub fn derive_estimate_size(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    estimate_size::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `synkit::Generate` for AST nodes.
+///
+/// Structs generate their fields in order. Enums pick a variant at random;
+/// once the generator's size or depth limit is reached they pick the
+/// variant marked `#[generate(fallback)]`, or else the one with the fewest
+/// non-token fields that doesn't mention the enum itself, so recursive
+/// grammars terminate. Type parameters get a `Generate` bound.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(Generate)]
+/// enum Expr {
+///     Number(Spanned<NumberToken>),
+///     Neg(Spanned<MinusToken>, Box<Spanned<Expr>>),
+///     #[generate(fallback)]
+///     Zero(Spanned<ZeroToken>),
+/// }
+///
+/// let corpus = testing::corpus::<Expr>(42, 100);
+/// ```
+#[proc_macro_derive(Generate, attributes(generate))]
+pub fn derive_generate(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    generate::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives the `Peek` and `Parse` impls generated by [`parser_kit!`] for
+/// AST nodes, from their FIRST sets.
+///
+/// Fields are parsed in order, `Spanned<T>` fields with `stream.parse()`
+/// and others with their `Parse` impl. A node starts with its leading
+/// fields up to the first one that can't be empty (`Option<T>` and
+/// `Vec<T>` can), so its `Peek` impl accepts any token theirs do. Enums
+/// parse the first variant whose FIRST set accepts the next token, else the
+/// first variant that may be empty, else fail with `Expected`/`Empty`.
+///
+/// FIRST sets are compared by type at expansion time: a variant starting
+/// with the same type as an earlier one is never parsed for it, and is
+/// reported as a warning. `Parse`, `Peek`, `Token` and `TokenStream` must
+/// be in scope, as the kit's prelude provides; type parameters get
+/// `Parse + Peek` bounds.
+///
+/// - `#[grammar(error = MyError)]` names the kit's error type (required).
+/// - `#[grammar(expect = "value")]` is what errors say was expected;
+///   defaults to the type name.
+/// - `#[grammar(crate_path = path)]` is the kit's `crate_path`; defaults to
+///   `synkit`.
+///
+/// `Option<T>` and `Vec<T>` fields need the kit's `container_impls: true`.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(Grammar)]
+/// #[grammar(error = MyError, expect = "value")]
+/// enum Value {
+///     Number(Spanned<NumberToken>),
+///     Neg(Spanned<MinusToken>, Box<Spanned<Value>>),
+/// }
+///
+/// if stream.peek::<Value>() {
+///     let value: Spanned<Value> = stream.parse()?;
+/// }
+/// ```
+#[proc_macro_derive(Grammar, attributes(grammar))]
+pub fn derive_grammar(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    grammar::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `synkit::Lower` for AST nodes.
+///
+/// `#[lower(into = path::to::Ir)]` names the IR type, which must have the
+/// same shape: fields of the same names, or variants of the same names.
+/// Each field is lowered with `Lower`, so `Spanned<T>` fields become
+/// `Lowered<T::Output, Span>` with a node id and span. The span type
+/// defaults to `Span`; set it with `#[lower(span = MySpan)]`. Type
+/// parameters get a `Lower<Span>` bound.
+///
+/// - `#[lower(validate = check)]` calls `check(&self, cx)` first, to report
+///   diagnostics about the node as a whole.
+/// - `#[lower(skip)]` on a field leaves it out of the IR.
+/// - `#[lower(with = f)]` on a field lowers it with `f(&field, cx)`.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(Lower)]
+/// #[lower(into = ir::Expr)]
+/// enum Expr {
+///     Number(Spanned<NumberToken>),
+///     Neg(#[lower(skip)] Spanned<MinusToken>, Box<Spanned<Expr>>),
+/// }
+///
+/// let mut cx = LowerCx::new();
+/// let ir: Lowered<ir::Expr, Span> = expr.lower(&mut cx);
+/// ```
+#[proc_macro_derive(Lower, attributes(lower))]
+pub fn derive_lower(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    lower::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `synkit::ToJsonValue` for AST nodes. Requires synkit's `json`
+/// feature.
+///
+/// Structs with named fields become objects, newtypes their field, other
+/// tuple structs arrays and unit structs `null`. Enum variants are tagged
+/// like serde's default: unit variants become their name, others
+/// `{"Variant": ...}`. Type parameters get a `ToJsonValue` bound.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(ToJsonValue)]
+/// enum Value {
+///     Number(Spanned<NumberToken>),
+///     List(Vec<Spanned<Value>>),
+/// }
+///
+/// assert_eq!(parse("[1, 2]")?.to_json_value(), json!({"List": [{"Number": 1}, {"Number": 2}]}));
+/// ```
+#[proc_macro_derive(ToJsonValue)]
+pub fn derive_to_json_value(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    to_json_value::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `synkit::QueryNode` for AST nodes, so `Query` selectors can
+/// run over them.
+///
+/// Structs are nodes named after the type. Their fields are visited in
+/// order and can be tested by name in predicates (tuple fields by index),
+/// and a newtype takes its text from its field. Enums are transparent:
+/// the active variant's fields are visited in place of the enum, and a
+/// newtype variant gives its text. Type parameters get a
+/// `QueryNode + 'static` bound.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(QueryNode)]
+/// struct Table {
+///     name: Spanned<IdentToken>,
+///     items: Vec<Spanned<KeyValue>>,
+/// }
+///
+/// let ports = query!("Table[name='server'] > KeyValue[key='port']").find_all(&document);
+/// ```
+#[proc_macro_derive(QueryNode)]
+pub fn derive_query_node(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    query_node::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Checks a `synkit::Query` selector at compile time and evaluates to a
+/// `&'static Query`, parsed once on first use.
+///
+/// # Example
+///
+/// ```ignore
+/// for found in synkit::query!("Table > KeyValue[key='port']").find_all(&document) {
+///     println!("{:?} at {:?}", found.text(), found.span());
+/// }
+/// ```
+#[proc_macro]
+pub fn query(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::LitStr);
+    query::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implementation detail of the `quote_tokens!` macro generated by [`parser_kit!`].
+///
+/// Takes the crate path of the generated parser followed by `;` and the
+/// token syntax to quote.
+#[doc(hidden)]
+#[proc_macro]
+pub fn __quote_tokens(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as quote_tokens::QuoteTokensInput);
+    quote_tokens::expand(input)
         .unwrap_or_else(syn::Error::into_compile_error)
         .into()
 }
@@ -0,0 +1,174 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Attribute, Data, DeriveInput, Fields, Ident, Path, PathArguments, Type, parse_quote};
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let container = ContainerAttrs::parse(name, &input.attrs)?;
+    let span = &container.span;
+    let output = &container.into;
+    let ctor = expr_path(output);
+
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(synkit::Lower<#span>));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let (pattern, construct) = lower_fields(quote!(#ctor), &data.fields)?;
+            quote! {
+                let Self #pattern = self;
+                #construct
+            }
+        }
+        Data::Enum(data) if data.variants.is_empty() => quote! { match *self {} },
+        Data::Enum(data) => {
+            let arms = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let vname = &variant.ident;
+                    let (pattern, construct) =
+                        lower_fields(quote!(#ctor::#vname), &variant.fields)?;
+                    Ok(quote! { Self::#vname #pattern => #construct, })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "Lower cannot be derived for unions",
+            ));
+        }
+    };
+
+    let validate = container.validate.map(|path| quote! { #path(self, cx); });
+
+    Ok(quote! {
+        impl #impl_generics synkit::Lower<#span> for #name #ty_generics #where_clause {
+            type Output = #output;
+
+            #[allow(unused_variables)]
+            fn lower(&self, cx: &mut synkit::LowerCx<#span>) -> Self::Output {
+                #validate
+                #body
+            }
+        }
+    })
+}
+
+/// `#[lower(...)]` on the struct or enum.
+struct ContainerAttrs {
+    into: Path,
+    span: Type,
+    validate: Option<Path>,
+}
+
+impl ContainerAttrs {
+    fn parse(name: &Ident, attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut into = None;
+        let mut span = None;
+        let mut validate = None;
+        for attr in attrs.iter().filter(|a| a.path().is_ident("lower")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("into") {
+                    into = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("span") {
+                    span = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("validate") {
+                    validate = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("expected `into`, `span` or `validate`"));
+                }
+                Ok(())
+            })?;
+        }
+        let into = into.ok_or_else(|| {
+            syn::Error::new_spanned(
+                name,
+                "Lower needs the IR type: add `#[lower(into = path::to::Type)]`",
+            )
+        })?;
+        Ok(Self {
+            into,
+            span: span.unwrap_or_else(|| parse_quote!(Span)),
+            validate,
+        })
+    }
+}
+
+/// How a field is lowered.
+enum FieldLowering {
+    Lower,
+    Skip,
+    With(Path),
+}
+
+fn field_lowering(attrs: &[Attribute]) -> syn::Result<FieldLowering> {
+    let mut lowering = FieldLowering::Lower;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("lower")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                lowering = FieldLowering::Skip;
+            } else if meta.path.is_ident("with") {
+                lowering = FieldLowering::With(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error("expected `skip` or `with`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(lowering)
+}
+
+/// The pattern binding each field to `f_<n>`, and the expression building
+/// `path` from the lowered fields, leaving out skipped ones.
+fn lower_fields(path: TokenStream, fields: &Fields) -> syn::Result<(TokenStream, TokenStream)> {
+    let bindings: Vec<_> = (0..fields.len())
+        .map(|i| format_ident!("f_{}", i))
+        .collect();
+    let mut values = Vec::new();
+    for (field, binding) in fields.iter().zip(&bindings) {
+        let value = match field_lowering(&field.attrs)? {
+            FieldLowering::Skip => continue,
+            FieldLowering::Lower => quote! { synkit::Lower::lower(#binding, cx) },
+            FieldLowering::With(with) => quote! { #with(#binding, cx) },
+        };
+        values.push(match &field.ident {
+            Some(ident) => quote! { #ident: #value },
+            None => value,
+        });
+    }
+    Ok(match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|f| &f.ident);
+            (
+                quote! { { #(#names: #bindings),* } },
+                quote! { #path { #(#values),* } },
+            )
+        }
+        Fields::Unnamed(_) => (
+            quote! { ( #(#bindings),* ) },
+            quote! { #path ( #(#values),* ) },
+        ),
+        Fields::Unit => (quote! {}, path),
+    })
+}
+
+/// `into` as an expression path: `ir::List<T>` becomes `ir::List::<T>`.
+fn expr_path(path: &Path) -> Path {
+    let mut path = path.clone();
+    for segment in &mut path.segments {
+        if let PathArguments::AngleBracketed(args) = &mut segment.arguments {
+            args.colon2_token = Some(Default::default());
+        }
+    }
+    path
+}
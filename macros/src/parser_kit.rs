@@ -1,25 +1,257 @@
+use std::collections::HashMap;
+
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 use syn::{
-    Attribute, Ident, Path, Token, braced, bracketed,
+    Attribute, Ident, LitStr, Path, Token, Type, braced, bracketed,
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
 };
 
-use crate::declare_tokens::{DeclareTokensInput, TokenDef};
+use crate::declare_tokens::{DeclareTokensInput, TokenDef, child_path, with_cfg};
 
 pub struct ParserKitInput {
-    pub error_type: Ident,
+    pub error_type: Path,
     pub skip_tokens: Vec<Ident>,
     pub logos_attrs: Vec<Attribute>,
     pub tokens: Vec<TokenDef>,
     pub delimiters: Vec<DelimiterDef>,
     pub span_derives: Vec<Path>,
     pub token_derives: Vec<Path>,
+    /// Derives for the `Token` enum only; `token_derives` if empty.
+    pub token_enum_derives: Vec<Path>,
+    /// Derives for the token structs only; `token_derives` if empty.
+    pub token_struct_derives: Vec<Path>,
     pub custom_derives: Vec<Path>,
+    pub from_str: Vec<Path>,
+    pub export_as: Option<Ident>,
+    pub export: Export,
+    /// Path to the synkit crate, for when it is re-exported or renamed.
+    pub crate_path: Option<Path>,
+    /// Absolute path to the module the kit is invoked in.
+    pub self_path: Option<Path>,
+    pub span_types: Option<SpanTypes>,
+    /// Carry a `synkit::FileId` in every generated span.
+    pub file_ids: bool,
+    /// Serialize generated spans as `synkit::binary::PackedSpan`.
+    pub packed_spans: bool,
+    /// Carry a `synkit::Provenance` in every generated span.
+    pub provenance: bool,
+    /// Build a line-start table when lexing, for `Span::line`/`column`.
+    pub line_index: bool,
+    /// Fold `\r\n` to `\n` when lexing, keeping a map to the original offsets.
+    pub normalize_newlines: bool,
+    /// Generate `Parse`/`Peek` impls for `Vec`, `Option<Spanned<_>>` and pairs.
+    pub container_impls: bool,
+    /// Generate `BorrowedToken<'src>`, `BorrowedStream` and `ParseBorrowed`.
+    pub borrowed: bool,
+    /// Generate `Maybe<Delim><T>` and `<Delim>List<T, Sep>` for each delimiter.
+    pub delimiter_sugar: bool,
+    /// Generate `From`/`TryFrom` between token structs, payloads and `Token`.
+    pub token_conversions: bool,
+    /// Generate a `#[cfg(test)]` module lexing a sample of each token.
+    pub token_tests: bool,
+    /// Emit compile-time layout and `Send`/`Sync` assertions.
+    pub assertions: bool,
+    /// Expected source bytes per lexed token, for pre-sizing the token vector.
+    pub bytes_per_token: usize,
+    /// Cache the index of the next non-skip token between peeks.
+    pub peek_cache: bool,
+    /// Payload type for `(String)` tokens.
+    pub string_type: Option<Type>,
+    pub base: Option<BaseKit>,
+}
+
+/// Default for `bytes_per_token`. Skip tokens are lexed too, so the average
+/// is low; overestimating only costs a reallocation or two.
+const DEFAULT_BYTES_PER_TOKEN: usize = 4;
+
+/// User-provided span types replacing the generated `span` module.
+pub struct SpanTypes {
+    pub span: Path,
+    pub spanned: Path,
+}
+
+impl Parse for SpanTypes {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        let brace = braced!(content in input);
+        let mut span = None;
+        let mut spanned = None;
+        while !content.is_empty() {
+            let ident: Ident = content.parse()?;
+            content.parse::<Token![:]>()?;
+            match ident.to_string().as_str() {
+                "span" => span = Some(content.parse()?),
+                "spanned" => spanned = Some(content.parse()?),
+                other => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        format!("unknown span type: {other}; expected `span` or `spanned`"),
+                    ));
+                }
+            }
+            if content.peek(Token![,]) {
+                content.parse::<Token![,]>()?;
+            }
+        }
+        match (span, spanned) {
+            (Some(span), Some(spanned)) => Ok(Self { span, spanned }),
+            _ => Err(syn::Error::new(
+                brace.span.join(),
+                "`span_types` needs both `span` and `spanned`",
+            )),
+        }
+    }
+}
+
+/// What the kit makes available outside its generated modules.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum Export {
+    /// Nothing: no `prelude`, and the delimiter and `quote_tokens!` macros
+    /// are only reachable through `delimiters::` and `tokens::`.
+    None,
+    /// A `prelude` module with the common types and macros, without
+    /// re-exporting it at the invocation site.
+    Prelude,
+    /// The `prelude`, glob re-exported at the invocation site.
+    #[default]
+    Full,
+}
+
+impl Parse for Export {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ident: Ident = input.parse()?;
+        match ident.to_string().as_str() {
+            "none" => Ok(Self::None),
+            "prelude" => Ok(Self::Prelude),
+            "full" => Ok(Self::Full),
+            _ => Err(syn::Error::new(
+                ident.span(),
+                "expected `none`, `prelude` or `full`",
+            )),
+        }
+    }
+}
+
+/// Definitions inherited from another kit via `extends`.
+///
+/// Produced by the `export_as` macro of the base kit; `path` is the base
+/// kit's module (`$crate` when exported from another crate).
+pub struct BaseKit {
+    pub path: Path,
+    pub logos_attrs: Vec<Attribute>,
+    pub skip_tokens: Vec<Ident>,
+    pub delimiters: Vec<DelimiterDef>,
+    pub tokens: Vec<TokenDef>,
+    pub string_type: Option<Type>,
+}
+
+impl Parse for BaseKit {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut path = None;
+        let mut logos_attrs = Vec::new();
+        let mut skip_tokens = Vec::new();
+        let mut delimiters = Vec::new();
+        let mut tokens = Vec::new();
+        let mut string_type = None;
+
+        while !input.is_empty() {
+            let ident: Ident = input.parse()?;
+            input.parse::<Token![:]>()?;
+            match ident.to_string().as_str() {
+                "path" => path = Some(input.parse()?),
+                "string_type" => string_type = Some(input.parse()?),
+                "attrs" => {
+                    let content;
+                    braced!(content in input);
+                    logos_attrs = content.call(Attribute::parse_outer)?;
+                }
+                "skip_tokens" => {
+                    let content;
+                    bracketed!(content in input);
+                    skip_tokens = Punctuated::<Ident, Token![,]>::parse_terminated(&content)?
+                        .into_iter()
+                        .collect();
+                }
+                "delimiters" => delimiters = parse_delimiter_defs(input)?,
+                "tokens" => tokens = parse_token_defs(input)?,
+                other => {
+                    return Err(syn::Error::new(
+                        ident.span(),
+                        format!("unknown base kit field: {}", other),
+                    ));
+                }
+            }
+            if input.peek(Token![,]) {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        let path = path.ok_or_else(|| input.error("missing base kit `path`"))?;
+        Ok(Self {
+            path,
+            logos_attrs,
+            skip_tokens,
+            delimiters,
+            tokens,
+            string_type,
+        })
+    }
+}
+
+/// Parse `{ Name => (Open, Close), ... }`.
+fn parse_delimiter_defs(input: ParseStream) -> syn::Result<Vec<DelimiterDef>> {
+    let content;
+    braced!(content in input);
+    let mut delimiters = Vec::new();
+    while !content.is_empty() {
+        let cfg_attrs = content.call(Attribute::parse_outer)?;
+        if let Some(attr) = cfg_attrs.iter().find(|a| !a.path().is_ident("cfg")) {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "only `#[cfg(...)]` is supported on delimiters",
+            ));
+        }
+        let name: Ident = content.parse()?;
+        content.parse::<Token![=>]>()?;
+        let inner;
+        syn::parenthesized!(inner in content);
+        let open: Ident = inner.parse()?;
+        inner.parse::<Token![,]>()?;
+        let close: Ident = inner.parse()?;
+        delimiters.push(DelimiterDef {
+            cfg_attrs,
+            name,
+            open,
+            close,
+        });
+        if content.peek(Token![,]) {
+            content.parse::<Token![,]>()?;
+        }
+    }
+    Ok(delimiters)
+}
+
+/// Parse `{ #[token(..)] Name, ... }`.
+fn parse_token_defs(input: ParseStream) -> syn::Result<Vec<TokenDef>> {
+    let content;
+    braced!(content in input);
+    let mut tokens = Vec::new();
+    while !content.is_empty() {
+        tokens.push(content.parse()?);
+        if content.peek(Token![,]) {
+            content.parse::<Token![,]>()?;
+        }
+    }
+    Ok(tokens)
 }
 
+#[derive(Clone)]
 pub struct DelimiterDef {
+    /// `#[cfg(...)]` attributes, including those inherited from the
+    /// open/close tokens.
+    pub cfg_attrs: Vec<Attribute>,
     pub name: Ident,
     pub open: Ident,
     pub close: Ident,
@@ -34,18 +266,57 @@ impl Parse for ParserKitInput {
         let mut delimiters = Vec::new();
         let mut span_derives = Vec::new();
         let mut token_derives = Vec::new();
+        let mut token_enum_derives = Vec::new();
+        let mut token_struct_derives = Vec::new();
         let mut custom_derives = Vec::new();
+        let mut from_str = Vec::new();
+        let mut export_as = None;
+        let mut export = Export::default();
+        let mut crate_path = None;
+        let mut self_path = None;
+        let mut span_types = None;
+        let mut file_ids: Option<syn::LitBool> = None;
+        let mut packed_spans: Option<syn::LitBool> = None;
+        let mut provenance: Option<syn::LitBool> = None;
+        let mut line_index = false;
+        let mut normalize_newlines = false;
+        let mut container_impls = false;
+        let mut borrowed = false;
+        let mut delimiter_sugar = false;
+        let mut token_conversions = false;
+        let mut token_tests = false;
+        let mut assertions = true;
+        let mut bytes_per_token = DEFAULT_BYTES_PER_TOKEN;
+        let mut peek_cache = true;
+        let mut string_type: Option<Type> = None;
+        let mut base: Option<BaseKit> = None;
+        // `#[cfg(...)]` on a `tokens`/`delimiters` section applies to each entry
+        let mut section_cfg: Vec<Attribute> = Vec::new();
 
         while !input.is_empty() {
             if input.peek(Token![#]) {
-                let attr = input.call(Attribute::parse_outer)?;
-                logos_attrs.extend(attr);
+                let attrs = input.call(Attribute::parse_outer)?;
+                let (cfg, other): (Vec<_>, Vec<_>) =
+                    attrs.into_iter().partition(|a| a.path().is_ident("cfg"));
+                section_cfg.extend(cfg);
+                logos_attrs.extend(other);
                 continue;
             }
 
             let ident: Ident = input.parse()?;
             input.parse::<Token![:]>()?;
 
+            let cfg = std::mem::take(&mut section_cfg);
+            if let Some(attr) = cfg
+                .first()
+                .filter(|_| !matches!(ident.to_string().as_str(), "tokens" | "delimiters"))
+            {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "`#[cfg(...)]` is only supported on `tokens` and `delimiters` sections",
+                ));
+            }
+
             match ident.to_string().as_str() {
                 "error" => {
                     error_type = Some(input.parse()?);
@@ -63,35 +334,161 @@ impl Parse for ParserKitInput {
                         input.parse::<Token![,]>()?;
                     }
                 }
-                "tokens" => {
-                    let content;
-                    braced!(content in input);
-                    while !content.is_empty() {
-                        tokens.push(content.parse()?);
-                        if content.peek(Token![,]) {
-                            content.parse::<Token![,]>()?;
-                        }
-                    }
+                "tokens" | "__included_tokens" => {
+                    tokens.extend(parse_token_defs(input)?.into_iter().map(|mut t| {
+                        t.cfg_attrs.splice(0..0, cfg.iter().cloned());
+                        t
+                    }));
                     if input.peek(Token![,]) {
                         input.parse::<Token![,]>()?;
                     }
                 }
                 "delimiters" => {
+                    delimiters.extend(parse_delimiter_defs(input)?.into_iter().map(|mut d| {
+                        d.cfg_attrs.splice(0..0, cfg.iter().cloned());
+                        d
+                    }));
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "crate_path" => {
+                    crate_path = Some(input.parse()?);
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "self_path" => {
+                    let path: Path = input.parse()?;
+                    let absolute = path.leading_colon.is_some()
+                        || path
+                            .segments
+                            .first()
+                            .is_some_and(|s| s.ident == "crate" || s.ident == "$crate");
+                    if !absolute {
+                        return Err(syn::Error::new_spanned(
+                            path,
+                            "`self_path` must be absolute, e.g. `crate::grammar`",
+                        ));
+                    }
+                    self_path = Some(path);
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "span_types" => {
+                    span_types = Some(input.parse()?);
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "file_ids" => {
+                    file_ids = Some(input.parse()?);
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "packed_spans" => {
+                    packed_spans = Some(input.parse()?);
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "provenance" => {
+                    provenance = Some(input.parse()?);
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "line_index" => {
+                    line_index = input.parse::<syn::LitBool>()?.value;
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "normalize_newlines" => {
+                    normalize_newlines = input.parse::<syn::LitBool>()?.value;
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "container_impls" => {
+                    container_impls = input.parse::<syn::LitBool>()?.value;
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "borrowed" => {
+                    borrowed = input.parse::<syn::LitBool>()?.value;
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "delimiter_sugar" => {
+                    delimiter_sugar = input.parse::<syn::LitBool>()?.value;
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "token_conversions" => {
+                    token_conversions = input.parse::<syn::LitBool>()?.value;
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "token_tests" => {
+                    token_tests = input.parse::<syn::LitBool>()?.value;
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "assertions" => {
+                    assertions = input.parse::<syn::LitBool>()?.value;
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "bytes_per_token" => {
+                    let lit: syn::LitInt = input.parse()?;
+                    bytes_per_token = lit.base10_parse()?;
+                    if bytes_per_token == 0 {
+                        return Err(syn::Error::new(
+                            lit.span(),
+                            "`bytes_per_token` must be at least 1",
+                        ));
+                    }
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "peek_cache" => {
+                    peek_cache = input.parse::<syn::LitBool>()?.value;
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "export" => {
+                    export = input.parse()?;
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "string_type" => {
+                    string_type = Some(input.parse()?);
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "export_as" => {
+                    export_as = Some(input.parse()?);
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "__extends" => {
                     let content;
                     braced!(content in input);
-                    while !content.is_empty() {
-                        let name: Ident = content.parse()?;
-                        content.parse::<Token![=>]>()?;
-                        let inner;
-                        syn::parenthesized!(inner in content);
-                        let open: Ident = inner.parse()?;
-                        inner.parse::<Token![,]>()?;
-                        let close: Ident = inner.parse()?;
-                        delimiters.push(DelimiterDef { name, open, close });
-                        if content.peek(Token![,]) {
-                            content.parse::<Token![,]>()?;
-                        }
-                    }
+                    base = Some(content.parse()?);
                     if input.peek(Token![,]) {
                         input.parse::<Token![,]>()?;
                     }
@@ -116,6 +513,27 @@ impl Parse for ParserKitInput {
                         input.parse::<Token![,]>()?;
                     }
                 }
+                "token_enum_derives" => {
+                    let content;
+                    bracketed!(content in input);
+                    token_enum_derives = Punctuated::<Path, Token![,]>::parse_terminated(&content)?
+                        .into_iter()
+                        .collect();
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "token_struct_derives" => {
+                    let content;
+                    bracketed!(content in input);
+                    token_struct_derives =
+                        Punctuated::<Path, Token![,]>::parse_terminated(&content)?
+                            .into_iter()
+                            .collect();
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
                 "custom_derives" => {
                     let content;
                     bracketed!(content in input);
@@ -126,6 +544,27 @@ impl Parse for ParserKitInput {
                         input.parse::<Token![,]>()?;
                     }
                 }
+                "from_str" => {
+                    let content;
+                    bracketed!(content in input);
+                    from_str = Punctuated::<Path, Token![,]>::parse_terminated(&content)?
+                        .into_iter()
+                        .collect();
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
+                "include_tokens" => {
+                    // Non-empty lists are resolved before parsing; see `token_set`.
+                    let content;
+                    bracketed!(content in input);
+                    if !content.is_empty() {
+                        return Err(content.error("unresolved `include_tokens` entry"));
+                    }
+                    if input.peek(Token![,]) {
+                        input.parse::<Token![,]>()?;
+                    }
+                }
                 other => {
                     return Err(syn::Error::new(
                         ident.span(),
@@ -138,6 +577,91 @@ impl Parse for ParserKitInput {
         let error_type =
             error_type.ok_or_else(|| syn::Error::new(input.span(), "missing `error` field"))?;
 
+        if let Some(lit) = file_ids
+            .as_ref()
+            .filter(|lit| lit.value && span_types.is_some())
+        {
+            return Err(syn::Error::new(
+                lit.span,
+                "`file_ids` only applies to the generated span types; \
+                 store the file in the `span_types` span instead",
+            ));
+        }
+        let file_ids = file_ids.is_some_and(|lit| lit.value);
+
+        if let Some(lit) = packed_spans
+            .as_ref()
+            .filter(|lit| lit.value && span_types.is_some())
+        {
+            return Err(syn::Error::new(
+                lit.span,
+                "`packed_spans` only applies to the generated span types; \
+                 use `synkit::binary::span` in the `span_types` impls instead",
+            ));
+        }
+        let packed_spans = packed_spans.is_some_and(|lit| lit.value);
+
+        if let Some(lit) = provenance
+            .as_ref()
+            .filter(|lit| lit.value && span_types.is_some())
+        {
+            return Err(syn::Error::new(
+                lit.span,
+                "`provenance` only applies to the generated span types; \
+                 override `SpanLike::provenance` in the `span_types` span instead",
+            ));
+        }
+        let provenance = provenance.is_some_and(|lit| lit.value);
+
+        // Base definitions come first; the extension adds to them.
+        if let Some(base) = &base {
+            let mut base_logos_attrs = base.logos_attrs.clone();
+            base_logos_attrs.append(&mut logos_attrs);
+            logos_attrs = base_logos_attrs;
+
+            let mut base_tokens = base.tokens.clone();
+            base_tokens.append(&mut tokens);
+            tokens = base_tokens;
+
+            let mut base_delimiters = base.delimiters.clone();
+            base_delimiters.append(&mut delimiters);
+            delimiters = base_delimiters;
+
+            let mut base_skip = base.skip_tokens.clone();
+            base_skip.extend(
+                skip_tokens
+                    .into_iter()
+                    .filter(|s| !base.skip_tokens.contains(s)),
+            );
+            skip_tokens = base_skip;
+
+            // Token conversions to and from the base kit move payloads as-is
+            let type_str = |ty: &Option<Type>| ty.as_ref().map(|t| quote!(#t).to_string());
+            match (&string_type, &base.string_type) {
+                (None, inherited) => string_type = inherited.clone(),
+                (Some(ty), _) if type_str(&string_type) != type_str(&base.string_type) => {
+                    return Err(syn::Error::new_spanned(
+                        ty,
+                        "`string_type` must match the base kit's; it is inherited when omitted",
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        // Delimiters only exist when both of their tokens do
+        for delim in &mut delimiters {
+            for side in [&delim.open, &delim.close] {
+                if let Some(token) = tokens.iter().find(|t| &t.name == side) {
+                    for attr in &token.cfg_attrs {
+                        if !delim.cfg_attrs.contains(attr) {
+                            delim.cfg_attrs.push(attr.clone());
+                        }
+                    }
+                }
+            }
+        }
+
         Ok(Self {
             error_type,
             skip_tokens,
@@ -146,12 +670,220 @@ impl Parse for ParserKitInput {
             delimiters,
             span_derives,
             token_derives,
+            token_enum_derives,
+            token_struct_derives,
             custom_derives,
+            from_str,
+            export_as,
+            export,
+            crate_path,
+            self_path,
+            span_types,
+            file_ids,
+            packed_spans,
+            provenance,
+            line_index,
+            normalize_newlines,
+            container_impls,
+            borrowed,
+            delimiter_sugar,
+            token_conversions,
+            token_tests,
+            assertions,
+            bytes_per_token,
+            peek_cache,
+            string_type,
+            base,
         })
     }
 }
 
+impl ParserKitInput {
+    /// Check the definitions for mistakes that would otherwise surface as
+    /// errors inside the generated code. All problems are reported at once.
+    fn validate(&self) -> syn::Result<()> {
+        let mut errors: Vec<syn::Error> = Vec::new();
+
+        let mut by_name: HashMap<String, &TokenDef> = HashMap::new();
+        for token in &self.tokens {
+            let key = token.name.to_string();
+            if let Some(first) = by_name
+                .get(&key)
+                .filter(|first| !cfg_exclusive(first, token))
+            {
+                let mut err = syn::Error::new(
+                    token.name.span(),
+                    format!("duplicate token `{}`", token.name),
+                );
+                err.combine(syn::Error::new(
+                    first.name.span(),
+                    format!("`{}` first defined here", first.name),
+                ));
+                errors.push(err);
+            } else {
+                by_name.insert(key, token);
+            }
+        }
+
+        for skip in &self.skip_tokens {
+            if !by_name.contains_key(&skip.to_string()) {
+                errors.push(syn::Error::new(
+                    skip.span(),
+                    format!("skip token `{}` is not defined in `tokens`", skip),
+                ));
+            }
+        }
+
+        let mut delimiter_names: HashMap<String, &Ident> = HashMap::new();
+        for delim in &self.delimiters {
+            if let Some(first) = delimiter_names.insert(delim.name.to_string(), &delim.name) {
+                let mut err = syn::Error::new(
+                    delim.name.span(),
+                    format!("duplicate delimiter `{}`", delim.name),
+                );
+                err.combine(syn::Error::new(
+                    first.span(),
+                    format!("`{}` first defined here", first),
+                ));
+                errors.push(err);
+            }
+            for side in [&delim.open, &delim.close] {
+                match by_name.get(&side.to_string()) {
+                    None => errors.push(syn::Error::new(
+                        side.span(),
+                        format!("delimiter token `{}` is not defined in `tokens`", side),
+                    )),
+                    Some(token) if token.inner_type.is_some() => {
+                        let mut err = syn::Error::new(
+                            side.span(),
+                            format!(
+                                "delimiter token `{}` carries a value; delimiters must be unit tokens",
+                                side
+                            ),
+                        );
+                        err.combine(syn::Error::new(
+                            token.name.span(),
+                            format!("`{}` defined here", token.name),
+                        ));
+                        errors.push(err);
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        // Identical patterns with the same priority make logos reject the
+        // enum with an error pointing into generated code.
+        let mut patterns: HashMap<(String, String, Option<String>), &TokenDef> = HashMap::new();
+        for token in &self.tokens {
+            for (kind, lit, priority) in token_patterns(token) {
+                let key = (kind.clone(), lit.value(), priority);
+                match patterns.get(&key) {
+                    Some(first) if first.name != token.name && !cfg_exclusive(first, token) => {
+                        let mut err = syn::Error::new(
+                            lit.span(),
+                            format!(
+                                "{} pattern {:?} conflicts with token `{}`",
+                                kind,
+                                lit.value(),
+                                first.name
+                            ),
+                        );
+                        err.combine(syn::Error::new(
+                            first.name.span(),
+                            format!(
+                                "`{}` uses the same pattern; give one a distinct `priority`",
+                                first.name
+                            ),
+                        ));
+                        errors.push(err);
+                    }
+                    Some(_) => {}
+                    None => {
+                        patterns.insert(key, token);
+                    }
+                }
+            }
+        }
+
+        let mut errors = errors.into_iter();
+        match errors.next() {
+            Some(mut first) => {
+                first.extend(errors);
+                Err(first)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// Whether two tokens can never be enabled together: one is gated on
+/// `cfg(x)` and the other on `cfg(not(x))`. Other differing gates, such as
+/// two features, may both hold, so those tokens are still checked.
+fn cfg_exclusive(a: &TokenDef, b: &TokenDef) -> bool {
+    let predicates = |token: &TokenDef| -> Vec<String> {
+        token
+            .cfg_attrs
+            .iter()
+            .filter_map(|attr| attr.parse_args::<syn::Meta>().ok())
+            .map(|meta| quote!(#meta).to_string())
+            .collect()
+    };
+    let (a, b) = (predicates(a), predicates(b));
+    let negates = |x: &[String], y: &[String]| {
+        x.iter()
+            .any(|p| y.iter().any(|q| *q == format!("not ({p})")))
+    };
+    negates(&a, &b) || negates(&b, &a)
+}
+
+/// `#[token(..)]` / `#[regex(..)]` patterns of a token as
+/// `(kind, pattern, priority)`.
+fn token_patterns(token: &TokenDef) -> Vec<(String, LitStr, Option<String>)> {
+    token
+        .attrs
+        .iter()
+        .filter_map(|attr| {
+            let kind = ["token", "regex"]
+                .into_iter()
+                .find(|k| attr.path().is_ident(k))?;
+            attr.parse_args_with(|input: ParseStream| {
+                let lit: LitStr = input.parse()?;
+                let mut priority = None;
+                while !input.is_empty() {
+                    input.parse::<Token![,]>()?;
+                    if input.is_empty() {
+                        break;
+                    }
+                    if input.peek(Ident) && input.peek2(Token![=]) {
+                        let key: Ident = input.parse()?;
+                        input.parse::<Token![=]>()?;
+                        let value: syn::Expr = input.parse()?;
+                        if key == "priority" {
+                            priority = Some(quote!(#value).to_string());
+                        }
+                    } else {
+                        // Callback or other argument
+                        input.parse::<syn::Expr>()?;
+                    }
+                }
+                Ok((kind.to_string(), lit, priority))
+            })
+            .ok()
+        })
+        .collect()
+}
+
+/// Expand `input` and pretty-print the generated code.
+#[cfg(feature = "expand_to_string")]
+pub fn expand_to_string(input: ParserKitInput) -> syn::Result<String> {
+    let file: syn::File = syn::parse2(expand(input)?)?;
+    Ok(prettyplease::unparse(&file))
+}
+
 pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
+    input.validate()?;
+
     let ParserKitInput {
         error_type,
         skip_tokens,
@@ -160,9 +892,44 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
         delimiters,
         span_derives,
         token_derives,
+        token_enum_derives,
+        token_struct_derives,
         custom_derives,
+        from_str,
+        export_as,
+        export,
+        crate_path,
+        self_path,
+        span_types,
+        file_ids,
+        packed_spans,
+        provenance,
+        line_index,
+        normalize_newlines,
+        container_impls,
+        borrowed,
+        delimiter_sugar,
+        token_conversions,
+        token_tests,
+        assertions,
+        bytes_per_token,
+        peek_cache,
+        string_type,
+        base,
     } = input;
 
+    let error_ref = child_path(&error_type);
+    // Modules generated below refer to synkit as `synkit::`
+    let crate_alias = crate_path
+        .as_ref()
+        .map(|path| quote! { use #path as synkit; });
+    let synkit_path = crate_path
+        .as_ref()
+        .map_or_else(|| quote! { synkit }, |path| quote! { #path });
+    let kit_path = self_path
+        .as_ref()
+        .map_or_else(|| quote! { $crate }, |path| quote! { #path });
+
     let span_derives_tokens = if span_derives.is_empty() {
         quote! { Debug, Clone, PartialEq, Eq, Hash, Copy }
     } else {
@@ -175,263 +942,1302 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
         quote! { #[derive(#(#custom_derives),*)] }
     };
 
-    let span_module = quote! {
-        pub mod span {
-            /// Raw byte span with start and end offsets.
-            ///
-            /// Layout: 16 bytes on 64-bit (2 × usize), 8-byte aligned.
-            #[derive(#span_derives_tokens)]
-            #custom_derives_attr
-            #[repr(C)]
-            pub struct RawSpan {
-                pub start: usize,
-                pub end: usize,
-            }
-
-            /// Source location span, either known or synthetic (call-site).
-            ///
-            /// Layout: 24 bytes on 64-bit (8-byte discriminant region + 16 bytes data).
-            /// Uses `usize::MAX` sentinel in start position for CallSite to enable
-            /// future niche optimization if needed.
-            #[derive(#span_derives_tokens)]
-            #custom_derives_attr
-            pub enum Span {
-                CallSite,
-                Known(RawSpan),
-            }
-
-            impl Span {
+    // `Spanned<T>` compares and hashes like `Span` does. Its `Debug` is
+    // written by hand (for the compact `{:#?}` form), so only the comparison
+    // traits are picked out of `span_derives`, skipping any that
+    // `custom_derives` already adds.
+    let derive_name = |path: &Path| path.segments.last().map(|s| s.ident.to_string());
+    let default_span_derives: Vec<Path> = vec![
+        syn::parse_quote!(PartialEq),
+        syn::parse_quote!(Eq),
+        syn::parse_quote!(Hash),
+    ];
+    let spanned_derives: Vec<&Path> = if span_derives.is_empty() {
+        &default_span_derives
+    } else {
+        &span_derives
+    }
+    .iter()
+    .filter(|path| {
+        derive_name(path).is_some_and(|name| {
+            matches!(name.as_str(), "PartialEq" | "Eq" | "Hash")
+                && !custom_derives
+                    .iter()
+                    .any(|custom| derive_name(custom).as_ref() == Some(&name))
+        })
+    })
+    .collect();
+
+    // With `packed_spans`, `Span` serializes as a `synkit::binary::PackedSpan`
+    // and `Spanned` derives serde through synkit's re-export, so serde
+    // derives requested for either are dropped instead of conflicting.
+    let keep_derive = |path: &&Path| {
+        !packed_spans
+            || !derive_name(path)
+                .is_some_and(|name| matches!(name.as_str(), "Serialize" | "Deserialize"))
+    };
+    let span_enum_derives_tokens = if span_derives.is_empty() {
+        span_derives_tokens.clone()
+    } else {
+        let derives = span_derives.iter().filter(keep_derive);
+        quote! { #(#derives),* }
+    };
+    let span_custom_derives: Vec<&Path> = custom_derives.iter().filter(keep_derive).collect();
+    let span_custom_derives_attr = if span_custom_derives.is_empty() {
+        quote! {}
+    } else {
+        quote! { #[derive(#(#span_custom_derives),*)] }
+    };
+    // Fields `RawSpan` carries besides its offsets: the file with `file_ids`,
+    // the provenance with `provenance`. Spans built from another span
+    // (joins, re-ranges) keep them.
+    let mut raw_extras: Vec<(TokenStream, TokenStream, TokenStream)> = Vec::new();
+    if file_ids {
+        raw_extras.push((
+            quote! {
+                /// File the offsets refer to, if known.
+                pub file: Option<synkit::FileId>,
+            },
+            quote! { file: None, },
+            quote! { file: a.file.or(b.file), },
+        ));
+    }
+    if provenance {
+        raw_extras.push((
+            quote! {
+                /// Where the code under the span came from.
+                pub provenance: synkit::Provenance,
+            },
+            quote! { provenance: synkit::Provenance::UserSource, },
+            quote! { provenance: a.provenance.join(b.provenance), },
+        ));
+    }
+    let raw_extra_fields = raw_extras.iter().map(|(field, _, _)| field);
+    let raw_extra_fields = quote! { #(#raw_extra_fields)* };
+    let raw_defaults = raw_extras.iter().map(|(_, default, _)| default);
+    let raw_defaults = quote! { #(#raw_defaults)* };
+    let raw_joined = raw_extras.iter().map(|(_, _, joined)| joined);
+    let (join_known, with_range) = if raw_extras.is_empty() {
+        (
+            quote! { Self::new(a.start.min(b.start), a.end.max(b.end)) },
+            quote! {},
+        )
+    } else {
+        (
+            quote! {
+                Self::Known(RawSpan {
+                    start: a.start.min(b.start),
+                    end: a.end.max(b.end),
+                    #(#raw_joined)*
+                })
+            },
+            quote! {
                 #[inline]
-                pub fn new(start: usize, end: usize) -> Self {
-                    Self::Known(RawSpan { start, end })
+                fn with_range(&self, start: usize, end: usize) -> Self {
+                    Self::Known(RawSpan {
+                        start,
+                        end,
+                        ..self.raw()
+                    })
                 }
+            },
+        )
+    };
+    let raw_span_doc = match (file_ids, provenance) {
+        (false, false) => quote! {
+            /// Raw byte span with start and end offsets.
+            ///
+            /// Layout: 16 bytes on 64-bit (2 × usize), 8-byte aligned.
+        },
+        (true, false) => quote! {
+            /// Raw byte span with start and end offsets and the file they refer to.
+            ///
+            /// Layout: 24 bytes on 64-bit (2 × usize + `Option<FileId>`), 8-byte aligned.
+        },
+        (false, true) => quote! {
+            /// Raw byte span with start and end offsets and where the code came from.
+            ///
+            /// Layout: 24 bytes on 64-bit (2 × usize + `Provenance`), 8-byte aligned.
+        },
+        (true, true) => quote! {
+            /// Raw byte span with start and end offsets, the file they refer to and
+            /// where the code came from.
+            ///
+            /// Layout: 24 bytes on 64-bit (2 × usize + `Option<FileId>` +
+            /// `Provenance`), 8-byte aligned.
+        },
+    };
 
+    let span_file_impl = file_ids.then(|| {
+        quote! {
+            impl Span {
+                /// File this span refers to; `None` for call-site spans
+                /// and spans lexed without a file.
                 #[inline]
-                pub fn call_site() -> Self {
-                    Self::CallSite
+                pub fn file(&self) -> Option<synkit::FileId> {
+                    match self {
+                        Self::Known(s) => s.file,
+                        Self::CallSite => None,
+                    }
                 }
 
+                /// This span, attributed to `file`. Call-site spans are
+                /// returned unchanged.
                 #[inline]
-                pub fn len(&self) -> usize {
+                pub fn with_file(self, file: synkit::FileId) -> Self {
                     match self {
-                        Self::Known(s) => s.end.saturating_sub(s.start),
-                        Self::CallSite => 0,
+                        Self::Known(s) => Self::Known(RawSpan {
+                            file: Some(file),
+                            ..s
+                        }),
+                        Self::CallSite => Self::CallSite,
                     }
                 }
+            }
+        }
+    });
+
+    let (span_provenance_impl, provenance_fn) = if provenance {
+        (
+            quote! {
+                impl Span {
+                    /// Where the code under this span came from; `None` for
+                    /// call-site spans.
+                    #[inline]
+                    pub fn provenance(&self) -> Option<synkit::Provenance> {
+                        match self {
+                            Self::Known(s) => Some(s.provenance),
+                            Self::CallSite => None,
+                        }
+                    }
 
+                    /// Returns `true` for call-site spans and spans generated by
+                    /// a tool.
+                    #[inline]
+                    pub fn is_generated(&self) -> bool {
+                        self.provenance()
+                            .is_none_or(synkit::Provenance::is_generated)
+                    }
+
+                    /// This span, marked as coming from `provenance`. Call-site
+                    /// spans are returned unchanged.
+                    #[inline]
+                    pub fn with_provenance(self, provenance: synkit::Provenance) -> Self {
+                        match self {
+                            Self::Known(s) => Self::Known(RawSpan { provenance, ..s }),
+                            Self::CallSite => Self::CallSite,
+                        }
+                    }
+
+                    /// This span, marked as generated by `tool`.
+                    #[inline]
+                    pub fn generated_by(self, tool: synkit::ToolId) -> Self {
+                        self.with_provenance(synkit::Provenance::Generated(tool))
+                    }
+                }
+            },
+            quote! {
                 #[inline]
-                pub fn is_empty(&self) -> bool {
-                    self.len() == 0
+                fn provenance(&self) -> Option<synkit::Provenance> {
+                    Span::provenance(self)
                 }
+            },
+        )
+    } else {
+        (quote! {}, quote! {})
+    };
 
-                #[inline]
-                pub fn raw(&self) -> RawSpan {
-                    match self {
-                        Self::Known(s) => *s,
-                        Self::CallSite => RawSpan { start: 0, end: 0 },
+    let (packed_span_impl, spanned_serde_attrs) = if packed_spans {
+        let (serialize, deserialize) = if raw_extras.is_empty() {
+            (
+                quote! { synkit::binary::span::serialize(self, serializer) },
+                quote! { synkit::binary::span::deserialize(deserializer) },
+            )
+        } else {
+            // The packed span, then the extra fields in declaration order
+            let mut values = vec![quote! { synkit::binary::PackedSpan::pack(self) }];
+            let mut names = vec![quote! { packed }];
+            let mut types = vec![quote! { synkit::binary::PackedSpan }];
+            let mut apply = Vec::new();
+            if file_ids {
+                values.push(quote! { self.file() });
+                names.push(quote! { file });
+                types.push(quote! { Option<synkit::FileId> });
+                apply.push(quote! {
+                    let span = match file {
+                        Some(file) => span.with_file(file),
+                        None => span,
+                    };
+                });
+            }
+            if provenance {
+                values.push(quote! { self.raw().provenance });
+                names.push(quote! { provenance });
+                types.push(quote! { synkit::Provenance });
+                apply.push(quote! { let span = span.with_provenance(provenance); });
+            }
+            (
+                quote! {
+                    synkit::serde::Serialize::serialize(&(#(#values),*), serializer)
+                },
+                quote! {
+                    let (#(#names),*): (#(#types),*) =
+                        synkit::serde::Deserialize::deserialize(deserializer)?;
+                    let span: Self = packed.unpack();
+                    #(#apply)*
+                    Ok(span)
+                },
+            )
+        };
+        (
+            quote! {
+                /// Serialized as a `synkit::binary::PackedSpan`.
+                impl synkit::serde::Serialize for Span {
+                    fn serialize<S: synkit::serde::Serializer>(
+                        &self,
+                        serializer: S,
+                    ) -> Result<S::Ok, S::Error> {
+                        #serialize
                     }
                 }
 
-                #[inline]
-                pub fn join(&self, other: &Self) -> Self {
-                    match (self, other) {
-                        (Self::Known(a), Self::Known(b)) => {
-                            Self::new(a.start.min(b.start), a.end.max(b.end))
-                        }
-                        (Self::Known(s), _) | (_, Self::Known(s)) => Self::Known(*s),
-                        _ => Self::CallSite,
+                impl<'de> synkit::serde::Deserialize<'de> for Span {
+                    fn deserialize<D: synkit::serde::Deserializer<'de>>(
+                        deserializer: D,
+                    ) -> Result<Self, D::Error> {
+                        #deserialize
                     }
                 }
-            }
+            },
+            quote! {
+                #[derive(synkit::serde::Serialize, synkit::serde::Deserialize)]
+                #[serde(crate = "synkit::serde")]
+            },
+        )
+    } else {
+        (quote! {}, quote! {})
+    };
+
+    let (raw_span_size, span_size, spanned_size) = if raw_extras.is_empty() {
+        (16, 24, 32)
+    } else {
+        (24, 32, 40)
+    };
+    let [raw_span_size, span_size, spanned_size] =
+        [raw_span_size, span_size, spanned_size].map(proc_macro2::Literal::usize_unsuffixed);
 
-            impl synkit::SpanLike for Span {
+    let span_line_impl = line_index.then(|| {
+        quote! {
+            impl Span {
+                /// 1-based line of the span start in `stream`'s source.
                 #[inline]
-                fn start(&self) -> usize {
-                    self.raw().start
+                pub fn line(&self, stream: &super::stream::TokenStream) -> usize {
+                    stream.line_index().line(synkit::SpanLike::start(self))
                 }
 
+                /// 1-based column (in characters) of the span start in
+                /// `stream`'s source.
                 #[inline]
-                fn end(&self) -> usize {
-                    self.raw().end
+                pub fn column(&self, stream: &super::stream::TokenStream) -> usize {
+                    self.line_col(stream).column
                 }
 
+                /// Line and column of the span start in `stream`'s source.
                 #[inline]
-                fn new(start: usize, end: usize) -> Self {
-                    Self::new(start, end)
+                pub fn line_col(&self, stream: &super::stream::TokenStream) -> synkit::LineCol {
+                    stream.line_col(synkit::SpanLike::start(self))
                 }
+            }
+        }
+    });
 
-                #[inline]
-                fn call_site() -> Self {
-                    Self::CallSite
+    let span_layout_asserts = assertions.then(|| {
+        quote! {
+            // Compile-time layout assertions for 64-bit platforms
+            #[cfg(target_pointer_width = "64")]
+            const _: () = {
+                use core::mem::{size_of, align_of};
+
+                // RawSpan: 16 bytes (2 × usize), 24 with `file_ids` or `provenance`; 8-byte aligned
+                const _RAW_SPAN_SIZE: () = assert!(size_of::<RawSpan>() == #raw_span_size);
+                const _RAW_SPAN_ALIGN: () = assert!(align_of::<RawSpan>() == 8);
+
+                // Span: 8 discriminant + RawSpan, 8-byte aligned
+                const _SPAN_SIZE: () = assert!(size_of::<Span>() == #span_size);
+                const _SPAN_ALIGN: () = assert!(align_of::<Span>() == 8);
+
+                // Spanned<u8>: span + 1 value + 7 padding
+                const _SPANNED_U8_SIZE: () = assert!(size_of::<Spanned<u8>>() == #spanned_size);
+
+                // Spanned<usize>: span + 8 value
+                const _SPANNED_USIZE_SIZE: () = assert!(size_of::<Spanned<usize>>() == #spanned_size);
+            };
+        }
+    });
+
+    // Only with the `json` feature, since the impls need `synkit::ToJsonValue`
+    #[cfg(feature = "json")]
+    let span_json_impls = quote! {
+        impl synkit::ToJsonValue for RawSpan {
+            fn to_json(&self, spans: bool) -> synkit::serde_json::Value {
+                if spans {
+                    synkit::serde_json::Value::from(vec![self.start, self.end])
+                } else {
+                    synkit::serde_json::Value::Null
                 }
             }
+        }
 
-            /// A value with associated source span.
-            ///
-            /// Field order optimized: span first (8-byte aligned) ensures T
-            /// starts at optimal offset regardless of T's alignment.
-            #[derive(Debug, Clone)]
-            #custom_derives_attr
-            #[repr(C)]
-            pub struct Spanned<T> {
-                pub span: Span,
-                pub value: T,
+        impl synkit::ToJsonValue for Span {
+            fn to_json(&self, spans: bool) -> synkit::serde_json::Value {
+                if spans {
+                    synkit::span_to_json(self)
+                } else {
+                    synkit::serde_json::Value::Null
+                }
             }
+        }
 
-            impl<T> Spanned<T> {
-                #[inline]
-                pub fn new(start: usize, end: usize, value: T) -> Self {
-                    Self {
-                        span: Span::new(start, end),
-                        value,
+        impl<T: synkit::ToJsonValue> synkit::ToJsonValue for Spanned<T> {
+            #[inline]
+            fn to_json(&self, spans: bool) -> synkit::serde_json::Value {
+                synkit::spanned_to_json(&self.span, &self.value, spans)
+            }
+        }
+    };
+    #[cfg(not(feature = "json"))]
+    let span_json_impls = quote! {};
+
+    let span_module = if let Some(SpanTypes { span, spanned }) = &span_types {
+        let span = child_path(span);
+        let spanned = child_path(spanned);
+        quote! {
+            pub mod span {
+                #crate_alias
+                pub use #span as Span;
+                pub use #spanned as Spanned;
+
+                /// Shared call-site span, for APIs returning `&Span`.
+                pub(crate) fn call_site_ref() -> &'static Span {
+                    static CALL_SITE: std::sync::OnceLock<Span> = std::sync::OnceLock::new();
+                    CALL_SITE.get_or_init(<Span as synkit::SpanLike>::call_site)
+                }
+            }
+        }
+    } else {
+        quote! {
+            pub mod span {
+                #crate_alias
+                #raw_span_doc
+                #[derive(#span_derives_tokens)]
+                #custom_derives_attr
+                #[repr(C)]
+                pub struct RawSpan {
+                    pub start: usize,
+                    pub end: usize,
+                    #raw_extra_fields
+                }
+
+                /// Source location span, either known or synthetic (call-site).
+                ///
+                /// Layout: 8-byte discriminant region + `RawSpan` data on 64-bit.
+                #[derive(#span_enum_derives_tokens)]
+                #span_custom_derives_attr
+                pub enum Span {
+                    CallSite,
+                    Known(RawSpan),
+                }
+
+                impl Span {
+                    #[inline]
+                    pub fn new(start: usize, end: usize) -> Self {
+                        Self::Known(RawSpan { start, end, #raw_defaults })
+                    }
+
+                    /// A span over `start..end`, rejecting an inverted range.
+                    #[inline]
+                    pub fn try_new(start: usize, end: usize) -> Result<Self, synkit::SpanError> {
+                        if start > end {
+                            return Err(synkit::SpanError::Inverted { start, end });
+                        }
+                        Ok(Self::new(start, end))
+                    }
+
+                    #[inline]
+                    pub fn call_site() -> Self {
+                        Self::CallSite
+                    }
+
+                    /// This span cut to fit a source of `source_len` bytes: the
+                    /// end is clamped to the source and the start to the end.
+                    /// Call-site spans are unchanged.
+                    #[inline]
+                    pub fn clamp_to(&self, source_len: usize) -> Self {
+                        match self {
+                            Self::Known(s) => {
+                                let mut raw = *s;
+                                raw.end = raw.end.min(source_len);
+                                raw.start = raw.start.min(raw.end);
+                                Self::Known(raw)
+                            }
+                            Self::CallSite => Self::CallSite,
+                        }
+                    }
+
+                    #[inline]
+                    pub fn len(&self) -> usize {
+                        match self {
+                            Self::Known(s) => s.end.saturating_sub(s.start),
+                            Self::CallSite => 0,
+                        }
+                    }
+
+                    #[inline]
+                    pub fn is_empty(&self) -> bool {
+                        self.len() == 0
+                    }
+
+                    #[inline]
+                    pub fn raw(&self) -> RawSpan {
+                        match self {
+                            Self::Known(s) => *s,
+                            Self::CallSite => RawSpan { start: 0, end: 0, #raw_defaults },
+                        }
+                    }
+
+                    #[inline]
+                    pub fn join(&self, other: &Self) -> Self {
+                        match (self, other) {
+                            (Self::Known(a), Self::Known(b)) => {
+                                #join_known
+                            }
+                            (Self::Known(s), _) | (_, Self::Known(s)) => Self::Known(*s),
+                            _ => Self::CallSite,
+                        }
+                    }
+
+                    /// Byte range of a known span; `None` for call-site spans.
+                    #[inline]
+                    pub fn byte_range(&self) -> Option<std::ops::Range<usize>> {
+                        match self {
+                            Self::Known(s) => Some(s.start..s.end),
+                            Self::CallSite => None,
+                        }
                     }
                 }
 
-                #[inline]
-                pub fn call_site(value: T) -> Self {
-                    Self {
-                        span: Span::CallSite,
-                        value,
+                #span_file_impl
+
+                #span_provenance_impl
+
+                #span_line_impl
+
+                #packed_span_impl
+
+                impl From<std::ops::Range<usize>> for Span {
+                    #[inline]
+                    fn from(range: std::ops::Range<usize>) -> Self {
+                        Self::new(range.start, range.end)
                     }
                 }
 
-                #[inline]
-                pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Spanned<U> {
-                    Spanned {
-                        span: self.span,
-                        value: f(self.value),
+                impl synkit::SpanLike for Span {
+                    #[inline]
+                    fn start(&self) -> usize {
+                        self.raw().start
+                    }
+
+                    #[inline]
+                    fn end(&self) -> usize {
+                        self.raw().end
+                    }
+
+                    #[inline]
+                    fn new(start: usize, end: usize) -> Self {
+                        Self::new(start, end)
+                    }
+
+                    #[inline]
+                    fn call_site() -> Self {
+                        Self::CallSite
+                    }
+
+                    #with_range
+
+                    #[inline]
+                    fn is_call_site(&self) -> bool {
+                        matches!(self, Self::CallSite)
                     }
+
+                    #provenance_fn
                 }
 
+                /// Shared call-site span, for APIs returning `&Span`.
                 #[inline]
-                pub fn as_ref(&self) -> Spanned<&T> {
-                    Spanned {
-                        span: self.span.clone(),
-                        value: &self.value,
+                pub(crate) fn call_site_ref() -> &'static Span {
+                    &Span::CallSite
+                }
+
+                /// A value with associated source span.
+                ///
+                /// Field order optimized: span first (8-byte aligned) ensures T
+                /// starts at optimal offset regardless of T's alignment.
+                ///
+                /// `Display` shows only the value; `{:#?}` shows the compact
+                /// `value @ 12..19` form.
+                #[derive(Clone, #(#spanned_derives),*)]
+                #span_custom_derives_attr
+                #spanned_serde_attrs
+                #[repr(C)]
+                pub struct Spanned<T> {
+                    pub span: Span,
+                    pub value: T,
+                }
+
+                /// A default value at the call site, e.g. a separator the
+                /// printer inserts.
+                impl<T: Default> Default for Spanned<T> {
+                    #[inline]
+                    fn default() -> Self {
+                        Self::call_site(T::default())
                     }
                 }
-            }
 
-            impl<T> std::ops::Deref for Spanned<T> {
-                type Target = T;
-                fn deref(&self) -> &Self::Target {
-                    &self.value
+                impl<T: synkit::Generate> synkit::Generate for Spanned<T> {
+                    fn generate(g: &mut synkit::Generator) -> Self {
+                        Self::call_site(T::generate(g))
+                    }
                 }
-            }
 
-            impl<T: Clone> synkit::SpannedLike<T> for Spanned<T> {
-                type Span = Span;
+                /// Lowering allocates a node for the value and keeps its span.
+                impl<T: synkit::Lower<Span>> synkit::Lower<Span> for Spanned<T> {
+                    type Output = synkit::Lowered<T::Output, Span>;
+
+                    fn lower(&self, cx: &mut synkit::LowerCx<Span>) -> Self::Output {
+                        let (id, node) = cx.node(self.span.clone(), |cx| self.value.lower(cx));
+                        synkit::Lowered {
+                            id,
+                            span: self.span.clone(),
+                            node,
+                        }
+                    }
+                }
 
-                fn span(&self) -> &Span {
-                    &self.span
+                impl<T> Spanned<T> {
+                    #[inline]
+                    pub fn new(start: usize, end: usize, value: T) -> Self {
+                        Self {
+                            span: Span::new(start, end),
+                            value,
+                        }
+                    }
+
+                    #[inline]
+                    pub fn call_site(value: T) -> Self {
+                        Self {
+                            span: Span::CallSite,
+                            value,
+                        }
+                    }
+
+                    #[inline]
+                    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Spanned<U> {
+                        Spanned {
+                            span: self.span,
+                            value: f(self.value),
+                        }
+                    }
+
+                    #[inline]
+                    pub fn as_ref(&self) -> Spanned<&T> {
+                        Spanned {
+                            span: self.span.clone(),
+                            value: &self.value,
+                        }
+                    }
                 }
 
-                fn value_ref(&self) -> &T {
-                    &self.value
+                impl<T: std::fmt::Debug> std::fmt::Debug for Spanned<T> {
+                    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        if !f.alternate() {
+                            return f
+                                .debug_struct("Spanned")
+                                .field("span", &self.span)
+                                .field("value", &self.value)
+                                .finish();
+                        }
+                        match self.span.byte_range() {
+                            Some(range) => write!(f, "{:#?} @ {:?}", self.value, range),
+                            None => write!(f, "{:#?} @ call site", self.value),
+                        }
+                    }
                 }
 
-                fn value(self) -> T {
-                    self.value
+                impl<T: std::fmt::Display> std::fmt::Display for Spanned<T> {
+                    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        self.value.fmt(f)
+                    }
                 }
 
-                fn new(start: usize, end: usize, value: T) -> Self {
-                    Self::new(start, end, value)
+                impl<T> std::ops::Deref for Spanned<T> {
+                    type Target = T;
+                    fn deref(&self) -> &Self::Target {
+                        &self.value
+                    }
                 }
-            }
 
-            // Compile-time layout assertions for 64-bit platforms
-            #[cfg(target_pointer_width = "64")]
-            const _: () = {
-                use core::mem::{size_of, align_of};
+                impl synkit::SpanInsensitive for RawSpan {
+                    #[inline]
+                    fn eq_ignore_spans(&self, _other: &Self) -> bool {
+                        true
+                    }
 
-                // RawSpan: 16 bytes, 8-byte aligned (2 × usize)
-                const _RAW_SPAN_SIZE: () = assert!(size_of::<RawSpan>() == 16);
-                const _RAW_SPAN_ALIGN: () = assert!(align_of::<RawSpan>() == 8);
+                    #[inline]
+                    fn hash_ignore_spans<H: std::hash::Hasher>(&self, _state: &mut H) {}
+                }
 
-                // Span: 24 bytes (8 discriminant + 16 data), 8-byte aligned
-                const _SPAN_SIZE: () = assert!(size_of::<Span>() == 24);
-                const _SPAN_ALIGN: () = assert!(align_of::<Span>() == 8);
+                impl synkit::SpanInsensitive for Span {
+                    #[inline]
+                    fn eq_ignore_spans(&self, _other: &Self) -> bool {
+                        true
+                    }
 
-                // Spanned<u8>: 32 bytes (24 span + 1 value + 7 padding)
-                const _SPANNED_U8_SIZE: () = assert!(size_of::<Spanned<u8>>() == 32);
+                    #[inline]
+                    fn hash_ignore_spans<H: std::hash::Hasher>(&self, _state: &mut H) {}
+                }
 
-                // Spanned<usize>: 32 bytes (24 span + 8 value)
-                const _SPANNED_USIZE_SIZE: () = assert!(size_of::<Spanned<usize>>() == 32);
-            };
+                impl<T: synkit::SpanInsensitive> synkit::SpanInsensitive for Spanned<T> {
+                    #[inline]
+                    fn eq_ignore_spans(&self, other: &Self) -> bool {
+                        self.value.eq_ignore_spans(&other.value)
+                    }
+
+                    #[inline]
+                    fn hash_ignore_spans<H: std::hash::Hasher>(&self, state: &mut H) {
+                        self.value.hash_ignore_spans(state);
+                    }
+                }
+
+                impl<T: synkit::QueryNode> synkit::QueryNode for Spanned<T> {
+                    #[inline]
+                    fn kind(&self) -> Option<&'static str> {
+                        synkit::QueryNode::kind(&self.value)
+                    }
+
+                    #[inline]
+                    fn span(&self) -> Option<std::ops::Range<usize>> {
+                        if synkit::SpanLike::is_call_site(&self.span) {
+                            None
+                        } else {
+                            Some(synkit::SpanLike::start(&self.span)..synkit::SpanLike::end(&self.span))
+                        }
+                    }
+
+                    #[inline]
+                    fn text(&self) -> Option<std::borrow::Cow<'_, str>> {
+                        synkit::QueryNode::text(&self.value)
+                    }
+
+                    #[inline]
+                    fn field(&self, name: &str) -> Option<&dyn synkit::QueryNode> {
+                        synkit::QueryNode::field(&self.value, name)
+                    }
+
+                    #[inline]
+                    fn children<'a>(&'a self, visit: &mut dyn FnMut(&'a dyn synkit::QueryNode)) {
+                        synkit::QueryNode::children(&self.value, visit);
+                    }
+
+                    #[inline]
+                    fn as_any(&self) -> Option<&dyn std::any::Any> {
+                        synkit::QueryNode::as_any(&self.value)
+                    }
+                }
+
+                impl synkit::EstimateSize for RawSpan {
+                    #[inline]
+                    fn heap_size(&self) -> usize {
+                        0
+                    }
+                }
+
+                impl synkit::EstimateSize for Span {
+                    #[inline]
+                    fn heap_size(&self) -> usize {
+                        0
+                    }
+                }
+
+                impl<T: synkit::EstimateSize> synkit::EstimateSize for Spanned<T> {
+                    #[inline]
+                    fn heap_size(&self) -> usize {
+                        self.value.heap_size()
+                    }
+                }
+
+                impl<T: Clone> synkit::SpannedLike<T> for Spanned<T> {
+                    type Span = Span;
+
+                    fn span(&self) -> &Span {
+                        &self.span
+                    }
+
+                    fn value_ref(&self) -> &T {
+                        &self.value
+                    }
+
+                    fn value(self) -> T {
+                        self.value
+                    }
+
+                    fn new(start: usize, end: usize, value: T) -> Self {
+                        Self::new(start, end, value)
+                    }
+                }
+
+                #span_json_impls
+
+                #span_layout_asserts
+            }
         }
     };
 
     let declare_tokens_input = DeclareTokensInput {
         span_mod: None,
         error_type: error_type.clone(),
-        derives: token_derives.clone(),
-        struct_derives: token_derives.clone(),
-        logos_attrs,
+        self_path: self_path.clone(),
+        derives: if token_enum_derives.is_empty() {
+            token_derives.clone()
+        } else {
+            token_enum_derives
+        },
+        struct_derives: if token_struct_derives.is_empty() {
+            token_derives.clone()
+        } else {
+            token_struct_derives
+        },
+        logos_attrs: logos_attrs.clone(),
+        string_type: string_type.clone(),
+        token_conversions,
+        token_tests,
+        borrowed,
         tokens: tokens.clone(),
     };
 
     let tokens_expanded = crate::declare_tokens::expand(declare_tokens_input)?;
 
-    let tokens_module = quote! {
-        pub mod tokens {
-            #tokens_expanded
+    let tokens_macro = quote! {
+        /// Build a `MutTokenStream` from literal token syntax.
+        ///
+        /// Each Rust token tree is lexed on its own with the grammar's lexer;
+        /// `#var` interpolates any `ToTokens` value by lexing its printed form.
+        /// Skip tokens are dropped and all tokens get call-site spans.
+        ///
+        /// # Usage
+        /// ```ignore
+        /// let name = tokens::IdentToken::new("x".into());
+        /// let stream = quote_tokens! { let #name = 1; }?;
+        /// ```
+        macro_rules! quote_tokens {
+            ($($tt:tt)*) => {
+                #synkit_path::__quote_tokens!(#kit_path; $($tt)*)
+            };
         }
     };
 
-    let skip_patterns: Vec<_> = skip_tokens
+    let expect_arms: Vec<_> = tokens
         .iter()
-        .map(|name| {
-            quote! { super::tokens::Token::#name }
+        .map(|t| {
+            let name = &t.name;
+            let expect = t.expect_name();
+            quote! { (@expect #name) => { #expect }; }
         })
         .collect();
-
-    let skip_match = if skip_patterns.is_empty() {
-        quote! { false }
-    } else {
-        quote! { matches!(tok.value, #(#skip_patterns)|*) }
+    let match_macro = quote! {
+        /// Branch on the kind of the next non-skip token with one `match`,
+        /// instead of a chain of `peek`s.
+        ///
+        /// Arms list token kinds, separated by `|`. If no arm matches, the
+        /// enclosing function returns an `Expected` error naming every kind
+        /// listed, or `Empty` at the end of input. Nothing is consumed.
+        ///
+        /// # Usage
+        /// ```ignore
+        /// let expr = token_match!(stream {
+        ///     LParen | LBracket => Expr::Group(stream.parse()?),
+        ///     Ident => Expr::Var(stream.parse()?),
+        ///     Number => Expr::Lit(stream.parse()?),
+        /// });
+        /// // On `;`: "expected one of `(`, `[`, identifier, number, found ;"
+        /// ```
+        macro_rules! token_match {
+            #(#expect_arms)*
+            ($stream:ident { $($($kind:ident)|+ => $body:expr),+ $(,)? }) => {{
+                let next = {
+                    use #synkit_path::TokenStream as _;
+                    $stream.peek_token().map(|tok| &tok.value)
+                };
+                match next {
+                    $(Some($(#kit_path::tokens::Token::$kind { .. })|+) => $body,)+
+                    found => {
+                        let expect: &'static str = concat!(
+                            "one of ",
+                            $($(#kit_path::tokens::token_match!(@expect $kind), ", ",)+)+
+                        );
+                        return Err(#kit_path::tokens::expected(
+                            expect.strip_suffix(", ").unwrap_or(expect),
+                            found,
+                        ));
+                    }
+                }
+            }};
+        }
     };
 
-    let stream_module = quote! {
-        pub mod stream {
-            use std::sync::Arc;
-            use std::path::Path;
-            use super::span::{Span, Spanned};
-            use super::tokens::{Token, SpannedToken};
-
-            pub struct TokenStream {
-                source: Arc<str>,
-                source_path: Option<Arc<Path>>,
-                tokens: Arc<Vec<SpannedToken>>,
-                cursor: usize,
-                range_start: usize,
-                range_end: usize,
-                last_cursor: usize,
+    // User-provided `Spanned` may live in another crate, so the inherent
+    // constructor is only generated for our own
+    let synthetic_impl = span_types.is_none().then(|| {
+        quote! {
+            impl SpannedToken {
+                /// `token` with a call-site span, for tokens built in code.
+                #[inline]
+                pub fn synthetic(token: Token) -> Self {
+                    Self::call_site(token)
+                }
             }
+        }
+    });
 
-            impl TokenStream {
-                pub fn lex(source: &str) -> Result<Self, super::#error_type> {
-                    Self::lex_with_path(source, None::<&Path>)
+    let tokens_module = quote! {
+        pub mod tokens {
+            #crate_alias
+            #tokens_expanded
+            #synthetic_impl
+
+            #tokens_macro
+            pub(crate) use quote_tokens;
+
+            #match_macro
+            pub(crate) use token_match;
+
+            /// The error for wanting `expect` and finding `found`, or the end
+            /// of input if `found` is `None`, as `token_match!` reports it.
+            pub fn expected(expect: &'static str, found: Option<&Token>) -> #error_ref {
+                match found {
+                    Some(found) => #error_ref::Expected {
+                        expect,
+                        found: found.to_string(),
+                    },
+                    None => #error_ref::Empty { expect },
                 }
+            }
+        }
+    };
+
+    // Skip tokens as a bitset over `Token::kind_index`, so the hot skip
+    // check is a table lookup however many kinds there are
+    let mut skip_words = vec![0u64; tokens.len().div_ceil(64).max(1)];
+    for name in skip_tokens.iter() {
+        if let Some(i) = tokens.iter().position(|t| t.name == *name) {
+            skip_words[i / 64] |= 1 << (i % 64);
+        }
+    }
+    let skip_word_count = skip_words.len();
+
+    // Variant names for `TokenStream::dump`/`load`
+    let kind_arms: Vec<_> = tokens
+        .iter()
+        .map(|t| {
+            let name = &t.name;
+            let cfg_attrs = &t.cfg_attrs;
+            let label = name.to_string();
+            let pattern = if t.inner_type.is_some() {
+                quote! { Token::#name(_) }
+            } else {
+                quote! { Token::#name }
+            };
+            quote! { #(#cfg_attrs)* #pattern => #label, }
+        })
+        .collect();
+
+    let skip_match = if skip_tokens.is_empty() {
+        quote! { false }
+    } else {
+        quote! {
+            const SKIP_MASK: [u64; #skip_word_count] = [#(#skip_words),*];
+            let i = tok.value.kind_index();
+            SKIP_MASK.get(i / 64).is_some_and(|word| (word >> (i % 64)) & 1 != 0)
+        }
+    };
 
+    // Source paths use `std::path`, so they're only generated with the `std` feature.
+    #[cfg(feature = "std")]
+    let path_support = true;
+    #[cfg(not(feature = "std"))]
+    let path_support = false;
+
+    let (
+        path_import,
+        path_field,
+        path_init,
+        path_clone,
+        lex_with_path,
+        source_path_fn,
+        stream_size,
+    ) = if path_support {
+        (
+            quote! { use std::path::Path; },
+            quote! { source_path: Option<Arc<Path>>, },
+            quote! { source_path: None, },
+            quote! { source_path: self.source_path.as_ref().map(Arc::clone), },
+            quote! {
                 pub fn lex_with_path(
                     source: &str,
                     path: Option<impl AsRef<Path>>,
-                ) -> Result<Self, super::#error_type> {
+                ) -> Result<Self, #error_ref> {
+                    let mut stream = Self::lex(source)?;
+                    stream.source_path = path.map(|p| Arc::from(p.as_ref()));
+                    Ok(stream)
+                }
+            },
+            quote! {
+                pub fn source_path(&self) -> Option<&Path> {
+                    self.source_path.as_deref()
+                }
+            },
+            144usize,
+        )
+    } else {
+        (
+            quote! {},
+            quote! {},
+            quote! {},
+            quote! {},
+            quote! {},
+            quote! {},
+            128usize,
+        )
+    };
+
+    let (line_field, line_let, line_init, line_clone, line_fns) = if line_index {
+        (
+            quote! { line_index: Arc<synkit::LineIndex>, },
+            quote! { let line_index = Arc::new(synkit::LineIndex::new(&source)); },
+            quote! { line_index, },
+            quote! { line_index: Arc::clone(&self.line_index), },
+            quote! {
+                /// Line-start table of the source, built when the stream is created.
+                pub fn line_index(&self) -> &synkit::LineIndex {
+                    &self.line_index
+                }
+
+                /// 1-based line and column of byte `offset` in the source.
+                pub fn line_col(&self, offset: usize) -> synkit::LineCol {
+                    self.line_index.line_col(&self.source, offset)
+                }
+            },
+        )
+    } else {
+        (quote! {}, quote! {}, quote! {}, quote! {}, quote! {})
+    };
+    let stream_size = stream_size + if line_index { 8 } else { 0 };
+
+    // Streams not built by `lex` share an empty map, as their source is
+    // taken as given.
+    let (newline_field, newline_init, newline_clone, newline_fns) = if normalize_newlines {
+        (
+            quote! { newline_map: Arc<synkit::NewlineMap>, },
+            quote! { newline_map: Arc::default(), },
+            quote! { newline_map: Arc::clone(&self.newline_map), },
+            quote! {
+                /// Where `lex` folded `\r\n` to `\n`; `source()` and spans
+                /// are in the normalized text.
+                pub fn newline_map(&self) -> &synkit::NewlineMap {
+                    &self.newline_map
+                }
+
+                /// Byte range of `span` in the source as passed to `lex`.
+                pub fn original_range(&self, span: &Span) -> std::ops::Range<usize> {
+                    use synkit::SpanLike;
+                    self.newline_map.original_range(span.start()..span.end())
+                }
+            },
+        )
+    } else {
+        (quote! {}, quote! {}, quote! {}, quote! {})
+    };
+    let stream_size = stream_size + if normalize_newlines { 8 } else { 0 };
+    let (lex_source, lex_finish) = if normalize_newlines {
+        (
+            quote! {
+                let (source, newline_map) = synkit::NewlineMap::normalize(source);
+                let source: Arc<str> = Arc::from(source);
+            },
+            quote! {
+                let mut stream = Self::from_tokens(source, Arc::new(tokens));
+                stream.newline_map = Arc::new(newline_map);
+                Ok(stream)
+            },
+        )
+    } else {
+        (
+            quote! { let source: Arc<str> = Arc::from(source); },
+            quote! { Ok(Self::from_tokens(source, Arc::new(tokens))) },
+        )
+    };
+
+    // `peeked` holds the index `peek_token` last resolved, or `usize::MAX`
+    // once the cursor or skip set changes. It's atomic to keep the stream
+    // `Sync`; relaxed ordering is enough since it's only a hint.
+    let (peek_field, peek_init, peek_clone, peek_lookup, peek_store, peek_invalidate) =
+        if peek_cache {
+            (
+                quote! {
+                    /// Index of the next non-skip token, cached by `peek_token`.
+                    peeked: core::sync::atomic::AtomicUsize,
+                },
+                quote! { peeked: core::sync::atomic::AtomicUsize::new(usize::MAX), },
+                quote! {
+                    peeked: core::sync::atomic::AtomicUsize::new(
+                        self.peeked.load(core::sync::atomic::Ordering::Relaxed),
+                    ),
+                },
+                quote! {
+                    let cached = self.peeked.load(core::sync::atomic::Ordering::Relaxed);
+                    if cached != usize::MAX {
+                        return cached;
+                    }
+                },
+                quote! {
+                    self.peeked.store(index, core::sync::atomic::Ordering::Relaxed);
+                },
+                quote! { *self.peeked.get_mut() = usize::MAX; },
+            )
+        } else {
+            (
+                quote! {},
+                quote! {},
+                quote! {},
+                quote! {},
+                quote! {},
+                quote! {},
+            )
+        };
+    let stream_size = stream_size + if peek_cache { 8 } else { 0 };
+
+    // `TokenStream::nesting` arms. A token shared by several delimiters
+    // gets one arm, so the match has no unreachable patterns.
+    let mut nesting_seen: Vec<&Ident> = Vec::new();
+    let mut nesting_arms = Vec::new();
+    for (tokens_of, nesting) in [
+        (
+            delimiters.iter().map(|d| (&d.open, d)).collect::<Vec<_>>(),
+            quote! { Open },
+        ),
+        (
+            delimiters.iter().map(|d| (&d.close, d)).collect(),
+            quote! { Close },
+        ),
+    ] {
+        for (token, d) in tokens_of {
+            if !nesting_seen.contains(&token) {
+                nesting_seen.push(token);
+                let cfg_attrs = &d.cfg_attrs;
+                nesting_arms.push(quote! {
+                    #(#cfg_attrs)*
+                    Token::#token { .. } => synkit::recovery::Nesting::#nesting,
+                });
+            }
+        }
+    }
+
+    let lexed_provenance =
+        provenance.then(|| quote! { provenance: synkit::Provenance::UserSource, });
+    let lex_fn = if file_ids {
+        let lex_in = path_support.then(|| {
+            quote! {
+                /// Register `source` under `path` in `files` and lex it, so
+                /// every span refers to the new file.
+                pub fn lex_in(
+                    files: &mut synkit::SourceFiles,
+                    path: impl AsRef<Path>,
+                    source: &str,
+                ) -> Result<Self, #error_ref> {
+                    let path = path.as_ref();
+                    let file = files.add(path, source);
+                    let mut stream = Self::lex_with_file(source, file)?;
+                    stream.source_path = Some(Arc::from(path));
+                    Ok(stream)
+                }
+            }
+        });
+        quote! {
+            pub fn lex(source: &str) -> Result<Self, #error_ref> {
+                Self::lex_file(source, None)
+            }
+
+            /// Lex `source` with every span attributed to `file`.
+            pub fn lex_with_file(source: &str, file: synkit::FileId) -> Result<Self, #error_ref> {
+                Self::lex_file(source, Some(file))
+            }
+
+            #lex_in
+
+            fn lex_file(source: &str, file: Option<synkit::FileId>) -> Result<Self, #error_ref> {
+                use logos::Logos;
+                #lex_source
+                let mut lex = Token::lexer(&source);
+                let mut tokens = Vec::with_capacity(Self::token_capacity(&source));
+
+                while let Some(tok) = lex.next() {
+                    let span = lex.span();
+                    let tok = tok?;
+                    tokens.push(Spanned {
+                        span: Span::Known(super::span::RawSpan {
+                            start: span.start,
+                            end: span.end,
+                            file,
+                            #lexed_provenance
+                        }),
+                        value: tok,
+                    });
+                }
+
+                #lex_finish
+            }
+        }
+    } else {
+        quote! {
+            pub fn lex(source: &str) -> Result<Self, #error_ref> {
+                use logos::Logos;
+                #lex_source
+                let mut lex = Token::lexer(&source);
+                let mut tokens = Vec::with_capacity(Self::token_capacity(&source));
+
+                while let Some(tok) = lex.next() {
+                    let span = lex.span();
+                    let tok = tok?;
+                    tokens.push(Spanned {
+                        span: <Span as synkit::SpanLike>::new(span.start, span.end),
+                        value: tok,
+                    });
+                }
+
+                #lex_finish
+            }
+        }
+    };
+
+    let stream_asserts = assertions.then(|| {
+        quote! {
+            // Compile-time assertions for TokenStream
+            const _: () = {
+                const fn assert_send<T: Send>() {}
+                const fn assert_sync<T: Sync>() {}
+                assert_send::<TokenStream>();
+                assert_sync::<TokenStream>();
+            };
+
+            #[cfg(target_pointer_width = "64")]
+            const _: () = {
+                use core::mem::{size_of, align_of};
+
+                // TokenStream layout on 64-bit:
+                // - source: Arc<str> = 16 bytes (DST: ptr + len)
+                // - source_path: Option<Arc<Path>> = 16 bytes (DST: ptr + len, `std` only)
+                // - line_index: Arc<LineIndex> = 8 bytes (`line_index` only)
+                // - newline_map: Arc<NewlineMap> = 8 bytes (`normalize_newlines` only)
+                // - tokens: Arc<Vec<SpannedToken>> = 8 bytes (thin ptr)
+                // - cursor: usize = 8 bytes
+                // - range_start: usize = 8 bytes
+                // - range_end: usize = 8 bytes
+                // - last_cursor: usize = 8 bytes
+                // - depth: RecursionGuard = 8 bytes
+                // - max_depth: Arc<AtomicUsize> = 8 bytes
+                // - delimiter: Option<&'static str> = 16 bytes
+                // - budget: Option<Arc<ParseBudget>> = 8 bytes
+                // - attempts: Option<Arc<AttemptTree>> = 8 bytes
+                // - progress: Option<Arc<ProgressReporter>> = 8 bytes
+                // - skip: Option<Arc<dyn Fn>> = 16 bytes
+                // - peeked: AtomicUsize = 8 bytes (`peek_cache` only)
+                // Total: 144 bytes (128 without `std`, +8 with `line_index`,
+                // +8 with `normalize_newlines`, +8 with `peek_cache`), 8-byte aligned
+                const _STREAM_SIZE: () = assert!(size_of::<TokenStream>() == #stream_size);
+                const _STREAM_ALIGN: () = assert!(align_of::<TokenStream>() == 8);
+            };
+        }
+    });
+
+    #[cfg(feature = "tokio")]
+    let tokio_aliases = quote! {
+        /// [`synkit::async_stream::tokio_impl::AstStream`] over a channel of
+        /// [`SpannedToken`]s.
+        pub type AstStream<T> = synkit::async_stream::tokio_impl::AstStream<T, SpannedToken>;
+    };
+    #[cfg(not(feature = "tokio"))]
+    let tokio_aliases = quote! {};
+
+    #[cfg(feature = "futures")]
+    let futures_aliases = quote! {
+        /// [`synkit::async_stream::futures_impl::ParseStream`] over a stream
+        /// of [`SpannedToken`]s.
+        pub type ParseStream<S, T> =
+            synkit::async_stream::futures_impl::ParseStream<S, T, SpannedToken>;
+    };
+    #[cfg(not(feature = "futures"))]
+    let futures_aliases = quote! {};
+
+    // `borrowed`: a stream over `BorrowedToken`s lexed from a `&'src str`
+    let borrowed_stream = borrowed.then(|| {
+        quote! {
+            /// Tokens lexed from a `&'src str` without copying it, for AST
+            /// nodes implementing [`ParseBorrowed`](super::traits::ParseBorrowed).
+            ///
+            /// Payload tokens are [`BorrowedToken`](super::tokens::BorrowedToken)s
+            /// holding the text they matched, so neither the stream nor its
+            /// tokens allocate per token, and nodes keep `&'src str` slices
+            /// of the source. Generated by `borrowed: true`.
+            ///
+            /// # Example
+            /// ```ignore
+            /// struct Field<'src> {
+            ///     name: Spanned<&'src str>,
+            /// }
+            ///
+            /// impl<'src> ParseBorrowed<'src> for Field<'src> {
+            ///     fn parse_borrowed(stream: &mut BorrowedStream<'src>) -> Result<Self, Error> {
+            ///         Ok(Self { name: stream.text::<tokens::IdentToken>()? })
+            ///     }
+            /// }
+            /// ```
+            pub struct BorrowedStream<'src> {
+                source: &'src str,
+                tokens: Arc<Vec<super::tokens::SpannedBorrowedToken<'src>>>,
+                cursor: usize,
+                range_start: usize,
+                range_end: usize,
+                last_cursor: usize,
+            }
+
+            impl<'src> BorrowedStream<'src> {
+                /// Lex `source` with the [`BorrowedToken`](super::tokens::BorrowedToken)
+                /// lexer. The source is used as given, without `normalize_newlines`.
+                pub fn lex(source: &'src str) -> Result<Self, #error_ref> {
                     use logos::Logos;
-                    let source: Arc<str> = Arc::from(source);
-                    let mut lex = Token::lexer(&source);
-                    let mut tokens = Vec::new();
+                    let mut lex = super::tokens::BorrowedToken::lexer(source);
+                    let mut tokens = Vec::with_capacity(TokenStream::token_capacity(source));
 
                     while let Some(tok) = lex.next() {
                         let span = lex.span();
-                        let tok = tok?;
-                        tokens.push(Spanned::new(span.start, span.end, tok));
+                        tokens.push(Spanned {
+                            span: <Span as synkit::SpanLike>::new(span.start, span.end),
+                            value: tok?,
+                        });
                     }
 
                     let len = tokens.len();
                     Ok(Self {
                         source,
-                        source_path: path.map(|p| Arc::from(p.as_ref())),
                         tokens: Arc::new(tokens),
                         cursor: 0,
                         range_start: 0,
@@ -440,79 +2246,891 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
                     })
                 }
 
-                /// Create a TokenStream from pre-lexed tokens.
+                /// The borrowed source text.
+                pub fn source(&self) -> &'src str {
+                    self.source
+                }
+
+                /// The source text under `span`; checked like
+                /// `TokenStream::slice`.
+                pub fn slice(&self, span: &Span) -> &'src str {
+                    match synkit::SpanLike::try_slice(span, self.source) {
+                        Ok(text) => text,
+                        Err(err) => {
+                            debug_assert!(false, "BorrowedStream::slice: {}", err);
+                            ""
+                        }
+                    }
+                }
+
+                pub fn all(&self) -> &[super::tokens::SpannedBorrowedToken<'src>] {
+                    &self.tokens[self.range_start..self.range_end]
+                }
+
+                /// Whether this stream skips `tok`.
+                pub fn skips(&self, tok: &super::tokens::SpannedBorrowedToken<'_>) -> bool {
+                    #skip_match
+                }
+
+                /// Check if the stream has reached EOF (no more non-skip tokens).
+                pub fn is_empty(&self) -> bool {
+                    use synkit::TokenStream as _;
+                    self.peek_token().is_none()
+                }
+
+                /// Peek without consuming to check if the next token matches `T`.
+                pub fn peek<T: super::traits::PeekBorrowed>(&self) -> bool {
+                    T::peek_borrowed(self)
+                }
+
+                /// Parse a borrowing node and wrap it with span information.
+                pub fn parse<T: super::traits::ParseBorrowed<'src>>(
+                    &mut self,
+                ) -> Result<Spanned<T>, #error_ref> {
+                    T::parse_borrowed_spanned(self)
+                }
+
+                /// Consume the next non-skip token, which must be a `T`.
+                pub fn expect<T: super::traits::PeekBorrowed + super::traits::Diagnostic>(
+                    &mut self,
+                ) -> Result<super::tokens::SpannedBorrowedToken<'src>, #error_ref> {
+                    use synkit::TokenStream as _;
+                    match self.next() {
+                        Some(tok) if T::is_borrowed(&tok.value) => Ok(tok),
+                        Some(tok) => Err(#error_ref::Expected {
+                            expect: T::fmt(),
+                            found: self.describe_found(&tok.value),
+                        }),
+                        None => Err(#error_ref::Empty { expect: T::fmt() }),
+                    }
+                }
+
+                /// Consume a `T` token, returning the source text it covers.
+                pub fn text<T: super::traits::PeekBorrowed + super::traits::Diagnostic>(
+                    &mut self,
+                ) -> Result<Spanned<&'src str>, #error_ref> {
+                    let Spanned { span, .. } = self.expect::<T>()?;
+                    Ok(Spanned {
+                        value: self.slice(&span),
+                        span,
+                    })
+                }
+
+                /// Describe `found`, the token just consumed, for an
+                /// `Expected` error, naming the token before it when there
+                /// is one: ``1 after `=` ``.
+                pub fn describe_found(&self, found: &super::tokens::BorrowedToken<'_>) -> String {
+                    let consumed = self
+                        .tokens
+                        .get(self.range_start..self.last_cursor)
+                        .unwrap_or_default();
+                    match consumed.iter().rev().find(|tok| !self.skips(tok)) {
+                        Some(prev) => format!("{} after `{}`", found, prev.value),
+                        None => found.to_string(),
+                    }
+                }
+
+                /// Extract the tokens between matching delimiters as a new
+                /// stream; see [`TokenStream::extract_inner`].
+                pub fn extract_inner<Open, Close>(&mut self) -> Result<(Self, Span), #error_ref>
+                where
+                    Open: super::traits::PeekBorrowed + super::traits::Diagnostic,
+                    Close: super::traits::PeekBorrowed + super::traits::Diagnostic,
+                {
+                    use synkit::TokenStream as _;
+                    use synkit::SpanLike;
+
+                    let open = self.expect::<Open>()?;
+                    let open_index = self.last_cursor;
+                    let mut depth = 1usize;
+                    while let Some(tok) = self.next_raw() {
+                        if Open::is_borrowed(&tok.value) {
+                            depth += 1;
+                        } else if Close::is_borrowed(&tok.value) {
+                            depth -= 1;
+                            if depth == 0 {
+                                let span = open.span.with_range(open.span.start(), tok.span.end());
+                                let inner = Self {
+                                    source: self.source,
+                                    tokens: Arc::clone(&self.tokens),
+                                    cursor: open_index + 1,
+                                    range_start: open_index + 1,
+                                    range_end: self.last_cursor,
+                                    last_cursor: open_index + 1,
+                                };
+                                return Ok((inner, span));
+                            }
+                        }
+                    }
+                    Err(#error_ref::Empty {
+                        expect: Close::fmt(),
+                    })
+                }
+
+                /// Fork for lookahead; see [`synkit::TokenStream::fork`].
+                pub fn fork(&self) -> Self {
+                    synkit::TokenStream::fork(self)
+                }
+
+                /// Move to `fork`'s position after a successful lookahead.
+                pub fn advance_to(&mut self, fork: &Self) {
+                    self.cursor = fork.cursor;
+                    self.last_cursor = fork.last_cursor;
+                }
+            }
+
+            impl<'src> synkit::TokenStream for BorrowedStream<'src> {
+                type Token = super::tokens::BorrowedToken<'src>;
+                type Span = Span;
+                type Spanned<T: Clone> = Spanned<T>;
+
+                fn peek_token_raw(&self) -> Option<&super::tokens::SpannedBorrowedToken<'src>> {
+                    self.tokens
+                        .get(self.cursor)
+                        .filter(|_| self.cursor < self.range_end)
+                }
+
+                fn next_raw(&mut self) -> Option<super::tokens::SpannedBorrowedToken<'src>> {
+                    let tok = self.peek_token_raw().cloned()?;
+                    self.last_cursor = self.cursor;
+                    self.cursor += 1;
+                    Some(tok)
+                }
+
+                fn next(&mut self) -> Option<super::tokens::SpannedBorrowedToken<'src>> {
+                    loop {
+                        let tok = self.next_raw()?;
+                        if !self.skips(&tok) {
+                            return Some(tok);
+                        }
+                    }
+                }
+
+                fn peek_token(&self) -> Option<&super::tokens::SpannedBorrowedToken<'src>> {
+                    let end = self.range_end.min(self.tokens.len());
+                    self.tokens
+                        .get(self.cursor..end)?
+                        .iter()
+                        .find(|tok| !self.skips(tok))
+                }
+
+                fn cursor(&self) -> usize {
+                    self.cursor
+                }
+
+                fn rewind(&mut self, pos: usize) {
+                    self.cursor = pos.clamp(self.range_start, self.range_end);
+                }
+
+                fn fork(&self) -> Self {
+                    Self {
+                        source: self.source,
+                        tokens: Arc::clone(&self.tokens),
+                        cursor: self.cursor,
+                        range_start: self.range_start,
+                        range_end: self.range_end,
+                        last_cursor: self.last_cursor,
+                    }
+                }
+
+                fn cursor_span(&self) -> Option<Span> {
+                    self.tokens.get(self.cursor).map(|t| t.span.clone())
+                }
+
+                fn last_span(&self) -> Option<Span> {
+                    self.tokens.get(self.last_cursor).map(|t| t.span.clone())
+                }
+
+                fn span_at(&self, pos: usize) -> Option<Span> {
+                    self.tokens.get(pos).map(|t| t.span.clone())
+                }
+            }
+        }
+    });
+
+    let stream_module = quote! {
+        pub mod stream {
+            #crate_alias
+            use std::ops::{Bound, RangeBounds};
+            use std::sync::Arc;
+            #path_import
+            use super::span::{Span, Spanned};
+            use super::tokens::{Token, SpannedToken};
+
+            #tokio_aliases
+            #futures_aliases
+
+            /// Tokens skipped by [`TokenStream::parse_or_error`] after a
+            /// failed parse, standing in for the node that failed.
+            #[derive(Debug, Clone)]
+            pub struct ErrorNode {
+                /// Span of the skipped tokens; empty at the end of input.
+                pub span: Span,
+                /// The skipped non-skip tokens, in order.
+                pub tokens: Vec<SpannedToken>,
+                /// The error the parse failed with.
+                pub diag: #error_ref,
+            }
+
+            /// The result of [`TokenStream::parse_or_error`]: the parsed node,
+            /// or an error node covering the tokens skipped in its place.
+            #[derive(Debug, Clone)]
+            pub enum Recovered<T> {
+                Ok(Spanned<T>),
+                Error(ErrorNode),
+            }
+
+            impl<T> Recovered<T> {
+                pub fn is_ok(&self) -> bool {
+                    matches!(self, Self::Ok(_))
+                }
+
+                pub fn is_error(&self) -> bool {
+                    matches!(self, Self::Error(_))
+                }
+
+                /// Span of the node or of the skipped tokens.
+                pub fn span(&self) -> &Span {
+                    match self {
+                        Self::Ok(value) => &value.span,
+                        Self::Error(node) => &node.span,
+                    }
+                }
+
+                pub fn ok(&self) -> Option<&Spanned<T>> {
+                    match self {
+                        Self::Ok(value) => Some(value),
+                        Self::Error(_) => None,
+                    }
+                }
+
+                pub fn error(&self) -> Option<&ErrorNode> {
+                    match self {
+                        Self::Ok(_) => None,
+                        Self::Error(node) => Some(node),
+                    }
+                }
+
+                pub fn into_result(self) -> Result<Spanned<T>, ErrorNode> {
+                    match self {
+                        Self::Ok(value) => Ok(value),
+                        Self::Error(node) => Err(node),
+                    }
+                }
+            }
+
+            /// Writes the skipped tokens back out unchanged.
+            impl super::traits::ToTokens for ErrorNode {
+                fn write(&self, p: &mut super::printer::Printer) {
+                    for tok in &self.tokens {
+                        tok.value.write(p);
+                    }
+                }
+            }
+
+            impl<T: super::traits::ToTokens> super::traits::ToTokens for Recovered<T> {
+                fn write(&self, p: &mut super::printer::Printer) {
+                    match self {
+                        Self::Ok(value) => value.write(p),
+                        Self::Error(node) => node.write(p),
+                    }
+                }
+            }
+
+            /// An open/close delimiter pair, for
+            /// [`TokenStream::extract_any_inner`].
+            #[derive(Debug, Clone, Copy)]
+            pub struct DelimiterPair {
+                is_open: fn(&Token) -> bool,
+                is_close: fn(&Token) -> bool,
+                open_fmt: fn() -> &'static str,
+                close_fmt: fn() -> &'static str,
+            }
+
+            impl DelimiterPair {
+                /// The pair delimited by `Open` and `Close` tokens.
+                pub const fn of<Open, Close>() -> Self
+                where
+                    Open: super::traits::Peek + super::traits::Diagnostic,
+                    Close: super::traits::Peek + super::traits::Diagnostic,
+                {
+                    Self {
+                        is_open: <Open as super::traits::Peek>::is,
+                        is_close: <Close as super::traits::Peek>::is,
+                        open_fmt: <Open as super::traits::Diagnostic>::fmt,
+                        close_fmt: <Close as super::traits::Diagnostic>::fmt,
+                    }
+                }
+
+                /// Display form of the opening delimiter.
+                pub fn open(&self) -> &'static str {
+                    (self.open_fmt)()
+                }
+
+                /// Display form of the closing delimiter.
+                pub fn close(&self) -> &'static str {
+                    (self.close_fmt)()
+                }
+            }
+
+            pub struct TokenStream {
+                source: Arc<str>,
+                #path_field
+                #line_field
+                #newline_field
+                tokens: Arc<Vec<SpannedToken>>,
+                cursor: usize,
+                range_start: usize,
+                range_end: usize,
+                last_cursor: usize,
+                depth: synkit::RecursionGuard,
+                /// Deepest `depth` reached by this stream or any stream
+                /// forked or extracted from it.
+                max_depth: Arc<core::sync::atomic::AtomicUsize>,
+                /// Opening delimiter of the group this stream was extracted from.
+                delimiter: Option<&'static str>,
+                /// Budget from `with_budget`, shared with forks and nested
+                /// streams, and the conversion of its errors.
+                budget: Option<Arc<(synkit::ParseBudget, fn(synkit::Error) -> #error_ref)>>,
+                /// Recorder for `attempt`s, shared with forks and nested streams.
+                attempts: Option<Arc<synkit::AttemptTree>>,
+                /// Progress callback from `on_progress`, shared with forks and
+                /// nested streams.
+                progress: Option<Arc<synkit::ProgressReporter>>,
+                /// Runtime replacement for the compiled-in skip set.
+                skip: Option<Arc<dyn Fn(&Token) -> bool + Send + Sync>>,
+                #peek_field
+            }
+
+            impl TokenStream {
+                #lex_fn
+
+                #lex_with_path
+
+                /// Create a TokenStream from pre-lexed tokens.
+                ///
+                /// This is the zero-copy path for incremental parsing: tokens are
+                /// borrowed via `Arc` without re-lexing.
+                ///
+                /// # Arguments
+                /// * `source` - The original source text (for span slicing)
+                /// * `tokens` - Pre-lexed tokens to parse
+                ///
+                /// # Example
+                /// ```ignore
+                /// let tokens = Arc::new(lexed_tokens);
+                /// let source: Arc<str> = Arc::from(source_text);
+                /// let stream = TokenStream::from_tokens(source, tokens);
+                /// let value: MyAst = stream.parse()?;
+                /// ```
+                pub fn from_tokens(source: Arc<str>, tokens: Arc<Vec<SpannedToken>>) -> Self {
+                    let len = tokens.len();
+                    #line_let
+                    Self {
+                        source,
+                        #path_init
+                        #line_init
+                        #newline_init
+                        tokens,
+                        cursor: 0,
+                        range_start: 0,
+                        range_end: len,
+                        last_cursor: 0,
+                        depth: synkit::RecursionGuard::new(),
+                        max_depth: Arc::default(),
+                        delimiter: None,
+                        budget: None,
+                        attempts: None,
+                        progress: None,
+                        skip: None,
+                        #peek_init
+                    }
+                }
+
+                /// Create a TokenStream from a range of pre-lexed tokens.
+                ///
+                /// This allows parsing a subset of tokens without copying.
+                pub fn from_tokens_range(
+                    source: Arc<str>,
+                    tokens: Arc<Vec<SpannedToken>>,
+                    range: std::ops::Range<usize>,
+                ) -> Self {
+                    #line_let
+                    Self {
+                        source,
+                        #path_init
+                        #line_init
+                        #newline_init
+                        tokens,
+                        cursor: range.start,
+                        range_start: range.start,
+                        range_end: range.end,
+                        last_cursor: range.start,
+                        depth: synkit::RecursionGuard::new(),
+                        max_depth: Arc::default(),
+                        delimiter: None,
+                        budget: None,
+                        attempts: None,
+                        progress: None,
+                        skip: None,
+                        #peek_init
+                    }
+                }
+
+                pub fn source(&self) -> &str {
+                    &self.source
+                }
+
+                #source_path_fn
+
+                #line_fns
+
+                #newline_fns
+
+                /// The source text under `span`.
+                ///
+                /// A span that doesn't fit the source fails a debug assertion
+                /// naming the problem, and slices to `""` in release builds;
+                /// use [`try_slice`](Self::try_slice) to handle it.
+                pub fn slice(&self, span: &Span) -> &str {
+                    match self.try_slice(span) {
+                        Ok(text) => text,
+                        Err(err) => {
+                            debug_assert!(false, "TokenStream::slice: {}", err);
+                            ""
+                        }
+                    }
+                }
+
+                /// The source text under `span`, or why it doesn't fit.
+                pub fn try_slice(&self, span: &Span) -> Result<&str, synkit::SpanError> {
+                    synkit::SpanLike::try_slice(span, &self.source)
+                }
+
+                /// Check that the lexed tokens tile the source: every span fits
+                /// it, none overlap, and the only bytes between them are ones
+                /// the lexer skips. Reports the first anomaly.
+                ///
+                /// A test and debugging aid for catching logos callbacks that
+                /// bump past or report the wrong slice. Checks every token of
+                /// the source, not just this stream's range.
+                pub fn verify_coverage(&self) -> Result<(), synkit::CoverageError> {
+                    use logos::Logos;
+                    synkit::CoverageError::check(
+                        &self.source,
+                        self.tokens.iter().map(|tok| {
+                            (!synkit::SpanLike::is_call_site(&tok.span))
+                                .then(|| synkit::SpanLike::start(&tok.span)..synkit::SpanLike::end(&tok.span))
+                        }),
+                        |gap| Token::lexer(gap).next().is_none(),
+                    )
+                }
+
+                pub fn all(&self) -> &[SpannedToken] {
+                    &self.tokens[self.range_start..self.range_end]
+                }
+
+                /// Copy this stream's tokens into a `MutTokenStream` for editing.
+                pub fn to_mut_stream(&self) -> MutTokenStream {
+                    MutTokenStream::from(self.all().to_vec())
+                }
+
+                /// Snapshot this stream's tokens, skipped ones included, with
+                /// their spans and source text, for token-level fixtures.
+                pub fn dump(&self) -> synkit::TokenDump {
+                    use synkit::SpanLike;
+                    let mut dump = synkit::TokenDump::new();
+                    for tok in self.all() {
+                        let text = self.source.get(tok.span.start()..tok.span.end()).unwrap_or("");
+                        dump.push(tok.span.start(), tok.span.end(), Self::kind_name(&tok.value), text);
+                    }
+                    dump
+                }
+
+                /// Rebuild a stream from a dump, without the original source.
+                ///
+                /// Spans are kept; the source is the tokens' text at their
+                /// offsets with spaces in between. Each token's text is lexed
+                /// again and must produce exactly one token of the dumped kind.
+                pub fn load(dump: &synkit::TokenDump) -> Result<Self, synkit::DumpError> {
+                    use logos::Logos;
+                    let source: Arc<str> = Arc::from(dump.source()?);
+                    let mut tokens = Vec::with_capacity(dump.tokens.len());
+                    for (index, dumped) in dump.tokens.iter().enumerate() {
+                        let mut lex = Token::lexer(&dumped.text);
+                        let first = lex.next();
+                        let whole = lex.span() == (0..dumped.text.len());
+                        let found = match (first, lex.next()) {
+                            (Some(Ok(token)), None)
+                                if whole && Self::kind_name(&token) == dumped.kind =>
+                            {
+                                tokens.push(Spanned {
+                                    span: <Span as synkit::SpanLike>::new(dumped.start, dumped.end),
+                                    value: token,
+                                });
+                                continue;
+                            }
+                            (Some(Ok(token)), None) if whole => format!("`{}`", Self::kind_name(&token)),
+                            (Some(Ok(token)), None) => {
+                                format!("`{}` for part of the text", Self::kind_name(&token))
+                            }
+                            (Some(Err(_)), _) => "a lex error".to_string(),
+                            (None, _) => "no tokens".to_string(),
+                            _ => "several tokens".to_string(),
+                        };
+                        return Err(synkit::DumpError::Relex {
+                            index,
+                            expected: dumped.kind.clone(),
+                            found,
+                        });
+                    }
+                    Ok(Self::from_tokens(source, Arc::new(tokens)))
+                }
+
+                pub(super) fn kind_name(token: &Token) -> &'static str {
+                    match *token {
+                        #(#kind_arms)*
+                    }
+                }
+
+                /// Current nesting depth: delimited groups this stream was
+                /// extracted through, plus open `enter_nested` calls.
+                pub fn depth(&self) -> usize {
+                    self.depth.depth()
+                }
+
+                /// Deepest nesting reached by this stream and every stream
+                /// forked or extracted from it.
+                pub fn max_depth_seen(&self) -> usize {
+                    self.max_depth.load(core::sync::atomic::Ordering::Relaxed)
+                }
+
+                /// Enter a nested parse, failing if the depth would exceed `limit`
+                /// (usually `ParseConfig::max_recursion_depth`). Pair with
+                /// `exit_nested`.
+                pub fn enter_nested(&mut self, limit: usize) -> Result<(), synkit::Error> {
+                    if let Err(err) = self.depth.enter(limit) {
+                        self.depth.exit();
+                        return Err(err);
+                    }
+                    self.record_depth(self.depth.depth());
+                    Ok(())
+                }
+
+                /// Leave a nested parse entered with `enter_nested`.
+                pub fn exit_nested(&mut self) {
+                    self.depth.exit();
+                }
+
+                /// Describe the current nesting for error messages, naming the
+                /// innermost delimiter, e.g. "inside 12 levels of `[`".
+                ///
+                /// Returns `None` at depth 0.
+                pub fn depth_context(&self) -> Option<String> {
+                    match (self.depth.depth(), self.delimiter) {
+                        (0, _) => None,
+                        (1, Some(open)) => Some(format!("inside `{open}`")),
+                        (depth, Some(open)) => Some(format!("inside {depth} levels of `{open}`")),
+                        (depth, None) => Some(format!("inside {depth} levels of nesting")),
+                    }
+                }
+
+                /// Limit this stream, its forks and nested streams to
+                /// `config.max_fuel` token reads and `config.timeout`.
+                ///
+                /// Once the budget runs out the stream reports end of input, and
+                /// `parse` and `parse_value` return the budget's `synkit::Error`
+                /// converted with `From`.
+                pub fn with_budget(mut self, config: &synkit::ParseConfig) -> Self
+                where
+                    for<'e> #error_ref: From<synkit::Error>,
+                {
+                    let into_error: fn(synkit::Error) -> #error_ref = From::from;
+                    self.budget = Some(Arc::new((synkit::ParseBudget::new(config), into_error)));
+                    self
+                }
+
+                /// The budget attached by `with_budget`.
+                pub fn budget(&self) -> Option<&synkit::ParseBudget> {
+                    self.budget.as_deref().map(|(budget, _)| budget)
+                }
+
+                /// `Err(synkit::Error::BudgetExceeded)` if the budget ran out, or
+                /// `Err(synkit::Error::NoProgress)` if a parse got stuck.
+                pub fn check_budget(&self) -> Result<(), synkit::Error> {
+                    self.budget().map_or(Ok(()), synkit::ParseBudget::check)
+                }
+
+                /// `check_budget`'s error as the parser's error type.
+                pub fn budget_error(&self) -> Option<#error_ref> {
+                    let (budget, into_error) = self.budget.as_deref()?;
+                    budget.check().err().map(*into_error)
+                }
+
+                /// `result`, unless the budget ran out: then its error.
+                pub(crate) fn within_budget<T>(
+                    &self,
+                    result: Result<T, #error_ref>,
+                ) -> Result<T, #error_ref> {
+                    match self.budget_error() {
+                        Some(err) => Err(err),
+                        None => result,
+                    }
+                }
+
+                /// Note a parse of `type_name` starting at the cursor, for the
+                /// budget's no-progress guard; `start..end` is its first token.
+                pub(crate) fn note_parse(
+                    &self,
+                    type_name: &'static str,
+                    start: usize,
+                    end: usize,
+                ) -> Result<(), #error_ref> {
+                    let Some((budget, into_error)) = self.budget.as_deref() else {
+                        return Ok(());
+                    };
+                    budget
+                        .note_parse(self.cursor, type_name, start, end)
+                        .map_err(*into_error)
+                }
+
+                /// Call `f(bytes_consumed, tokens_consumed)` as this stream, its
+                /// forks and nested streams read tokens, once per `granularity`
+                /// step and again after the last token.
+                ///
+                /// Counts are the furthest position reached, including skip
+                /// tokens, so backtracking never moves them backwards.
+                pub fn on_progress(
+                    mut self,
+                    granularity: synkit::ProgressGranularity,
+                    f: impl FnMut(usize, usize) + Send + 'static,
+                ) -> Self {
+                    self.progress = Some(Arc::new(synkit::ProgressReporter::new(granularity, f)));
+                    self
+                }
+
+                /// Record every `attempt` made on this stream, its forks and
+                /// nested streams into a new [`synkit::AttemptTree`].
+                pub fn with_attempt_tree(mut self) -> Self {
+                    self.attempts = Some(Arc::new(synkit::AttemptTree::new()));
+                    self
+                }
+
+                /// The tree attached by `with_attempt_tree`.
+                pub fn attempt_tree(&self) -> Option<&synkit::AttemptTree> {
+                    self.attempts.as_deref()
+                }
+
+                /// Run `f` speculatively on a fork: on success the stream
+                /// advances past what `f` consumed, on failure it stays put.
+                ///
+                /// With `with_attempt_tree`, the attempt is recorded under
+                /// `label` with its outcome and the tokens it consumed.
+                ///
+                /// # Example
+                /// ```ignore
+                /// let expr = match stream.attempt("call", Call::parse) {
+                ///     Ok(call) => Expr::Call(call),
+                ///     Err(_) => Expr::Ident(stream.parse()?),
+                /// };
+                /// ```
+                pub fn attempt<T>(
+                    &mut self,
+                    label: &str,
+                    f: impl FnOnce(&mut Self) -> Result<T, #error_ref>,
+                ) -> Result<T, #error_ref>
+                where
+                    for<'e> &'e #error_ref: std::fmt::Display,
+                {
+                    use synkit::TokenStream as _;
+
+                    let mut fork = self.fork();
+                    let Some(tree) = self.attempts.clone() else {
+                        let value = f(&mut fork)?;
+                        self.rewind(fork.cursor);
+                        self.last_cursor = fork.last_cursor;
+                        return Ok(value);
+                    };
+
+                    let offset = self
+                        .peek_token()
+                        .map_or(self.source.len(), |tok| synkit::SpanLike::start(&tok.span));
+                    let id = tree.enter(label, offset);
+                    let result = f(&mut fork);
+                    let tokens = self
+                        .tokens
+                        .get(self.cursor..fork.cursor)
+                        .map_or(0, |consumed| consumed.iter().filter(|tok| !self.skips(tok)).count());
+                    let outcome = match &result {
+                        Ok(_) => synkit::AttemptOutcome::Accepted { tokens },
+                        Err(err) => synkit::AttemptOutcome::Rejected {
+                            tokens,
+                            error: err.to_string(),
+                        },
+                    };
+                    tree.finish(id, outcome);
+                    if result.is_ok() {
+                        self.rewind(fork.cursor);
+                        self.last_cursor = fork.last_cursor;
+                    }
+                    result
+                }
+
+                /// [`attempt`](Self::attempt) to parse a `T`, labelled with its
+                /// type name.
+                pub fn attempt_parse<T: super::traits::Parse>(&mut self) -> Result<Spanned<T>, #error_ref>
+                where
+                    for<'e> &'e #error_ref: std::fmt::Display,
+                {
+                    // `a::b::Call` as `Call`; generic names are kept whole
+                    let name = std::any::type_name::<T>();
+                    let label = if name.contains('<') {
+                        name
+                    } else {
+                        name.rsplit("::").next().unwrap_or(name)
+                    };
+                    self.attempt(label, |stream| stream.parse::<T>())
+                }
+
+                fn budget_exhausted(&self) -> bool {
+                    self.budget().is_some_and(synkit::ParseBudget::is_exhausted)
+                }
+
+                fn record_depth(&self, depth: usize) {
+                    self.max_depth
+                        .fetch_max(depth, core::sync::atomic::Ordering::Relaxed);
+                }
+
+                /// Consume the next non-skip token.
+                ///
+                /// Same as `synkit::TokenStream::next`; defined here so calls
+                /// aren't ambiguous with `Iterator::next`.
+                #[allow(clippy::should_implement_trait)]
+                pub fn next(&mut self) -> Option<SpannedToken> {
+                    synkit::TokenStream::next(self)
+                }
+
+                /// Iterate over the remaining tokens, including skip tokens,
+                /// consuming them from the stream.
+                pub fn iter_raw(&mut self) -> impl Iterator<Item = SpannedToken> + '_ {
+                    std::iter::from_fn(move || synkit::TokenStream::next_raw(self))
+                }
+
+                /// Expected source bytes per token, skip tokens included. Set
+                /// with the kit's `bytes_per_token` option.
+                pub const BYTES_PER_TOKEN: usize = #bytes_per_token;
+
+                /// The token vector capacity `lex` starts with for `source`.
+                #[inline]
+                pub fn token_capacity(source: &str) -> usize {
+                    source.len() / Self::BYTES_PER_TOKEN + 1
+                }
+
+                fn is_skip_token(tok: &SpannedToken) -> bool {
+                    #skip_match
+                }
+
+                /// Lex `source`, letting `skip` decide which tokens parsers
+                /// don't see instead of the kit's `skip_tokens`.
                 ///
-                /// This is the zero-copy path for incremental parsing: tokens are
-                /// borrowed via `Arc` without re-lexing.
+                /// Forks and nested streams inherit the predicate; `next_raw`
+                /// still yields every token.
+                pub fn lex_with_skip(
+                    source: &str,
+                    skip: impl Fn(&Token) -> bool + Send + Sync + 'static,
+                ) -> Result<Self, #error_ref> {
+                    Ok(Self::lex(source)?.with_skip(skip))
+                }
+
+                /// Replace this stream's skip set with `skip`.
+                pub fn with_skip(mut self, skip: impl Fn(&Token) -> bool + Send + Sync + 'static) -> Self {
+                    self.skip = Some(Arc::new(skip));
+                    self.invalidate_peek();
+                    self
+                }
+
+                /// Run `f` with skip-token filtering disabled, so `next`,
+                /// `peek` and `parse` see trivia too. The previous skip set is
+                /// restored afterwards.
                 ///
-                /// # Arguments
-                /// * `source` - The original source text (for span slicing)
-                /// * `tokens` - Pre-lexed tokens to parse
+                /// Streams forked or extracted inside `f` stay in raw mode.
                 ///
-                /// # Example
                 /// ```ignore
-                /// let tokens = Arc::new(lexed_tokens);
-                /// let source: Arc<str> = Arc::from(source_text);
-                /// let stream = TokenStream::from_tokens(source, tokens);
-                /// let value: MyAst = stream.parse()?;
+                /// let newline = stream.raw_mode(|s| s.peek::<tokens::NewlineToken>());
                 /// ```
-                pub fn from_tokens(source: Arc<str>, tokens: Arc<Vec<SpannedToken>>) -> Self {
-                    let len = tokens.len();
-                    Self {
-                        source,
-                        source_path: None,
-                        tokens,
-                        cursor: 0,
-                        range_start: 0,
-                        range_end: len,
-                        last_cursor: 0,
-                    }
+                pub fn raw_mode<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+                    let saved = self.skip.replace(Arc::new(|_: &Token| false));
+                    self.invalidate_peek();
+                    let result = f(self);
+                    self.skip = saved;
+                    self.invalidate_peek();
+                    result
                 }
 
-                /// Create a TokenStream from a range of pre-lexed tokens.
-                ///
-                /// This allows parsing a subset of tokens without copying.
-                pub fn from_tokens_range(
-                    source: Arc<str>,
-                    tokens: Arc<Vec<SpannedToken>>,
-                    range: std::ops::Range<usize>,
-                ) -> Self {
-                    Self {
-                        source,
-                        source_path: None,
-                        tokens,
-                        cursor: range.start,
-                        range_start: range.start,
-                        range_end: range.end,
-                        last_cursor: range.start,
+                /// Whether this stream skips `tok`.
+                pub fn skips(&self, tok: &SpannedToken) -> bool {
+                    match &self.skip {
+                        Some(skip) => skip(&tok.value),
+                        None => Self::is_skip_token(tok),
                     }
                 }
 
-                pub fn source(&self) -> &str {
-                    &self.source
+                /// The last `n` non-skip tokens consumed from this stream,
+                /// oldest first.
+                ///
+                /// Only tokens inside the stream's range count, so a stream
+                /// returned by `extract_inner` does not see its open delimiter.
+                pub fn recent(&self, n: usize) -> Vec<&SpannedToken> {
+                    let consumed = self
+                        .tokens
+                        .get(self.range_start..self.cursor)
+                        .unwrap_or_default();
+                    let mut out: Vec<_> = consumed
+                        .iter()
+                        .rev()
+                        .filter(|tok| !self.skips(tok))
+                        .take(n)
+                        .collect();
+                    out.reverse();
+                    out
                 }
 
-                pub fn source_path(&self) -> Option<&Path> {
-                    self.source_path.as_deref()
+                /// Describe `found`, the token `next` just returned, for an
+                /// `Expected` error, naming the token before it when there is
+                /// one: ``1 after `=` ``.
+                pub fn describe_found(&self, found: &Token) -> String {
+                    match self.recent(2).as_slice() {
+                        [prev, _] => format!("{} after `{}`", found, prev.value),
+                        _ => found.to_string(),
+                    }
                 }
 
-                pub fn slice(&self, span: &Span) -> &str {
-                    use synkit::SpanLike;
-                    &self.source[span.start()..span.end()]
+                /// Parse a value from the stream and wrap it with span information.
+                /// This is the primary parsing method users should use.
+                pub fn parse<T: super::traits::Parse>(&mut self) -> Result<Spanned<T>, #error_ref> {
+                    T::parse_spanned(self)
                 }
 
-                pub fn all(&self) -> &[SpannedToken] {
-                    &self.tokens[self.range_start..self.range_end]
+                /// Parse the token named by a `tokens::kind` marker:
+                /// `stream.token::<kind::Ident>()` is
+                /// `stream.parse::<IdentToken>()`.
+                pub fn token<K: super::tokens::kind::TokenKind>(
+                    &mut self,
+                ) -> Result<Spanned<K::Token>, #error_ref> {
+                    self.parse()
                 }
 
-                fn is_skip_token(tok: &SpannedToken) -> bool {
-                    #skip_match
+                /// Parse a value from the stream, discarding its span.
+                pub fn parse_value<T: super::traits::Parse>(&mut self) -> Result<T, #error_ref> {
+                    let result = T::parse(self);
+                    self.within_budget(result)
                 }
 
-                /// Parse a value from the stream and wrap it with span information.
-                /// This is the primary parsing method users should use.
-                pub fn parse<T: super::traits::Parse>(&mut self) -> Result<Spanned<T>, super::#error_type> {
-                    T::parse_spanned(self)
+                /// Parse a value from the stream, returning it and its span
+                /// as separate components.
+                pub fn parse_pair<T: super::traits::Parse>(&mut self) -> Result<(T, Span), #error_ref> {
+                    let Spanned { span, value } = T::parse_spanned(self)?;
+                    Ok((value, span))
                 }
 
                 /// Peek without consuming to check if the next token matches type T.
@@ -530,7 +3148,7 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
                 pub fn current_span(&self) -> &Span {
                     self.tokens.get(self.cursor)
                         .map(|t| &t.span)
-                        .unwrap_or(&Span::CallSite)
+                        .unwrap_or_else(|| super::span::call_site_ref())
                 }
 
                 /// Extract tokens between matching delimiters (e.g., brackets, braces, parens).
@@ -551,22 +3169,73 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
                 pub fn extract_inner<
                     Open: super::traits::Parse + super::traits::Peek + super::traits::Diagnostic,
                     Close: super::traits::Parse + super::traits::Peek + super::traits::Diagnostic,
-                >(&mut self) -> Result<(TokenStream, Span), super::#error_type> {
+                >(&mut self) -> Result<(TokenStream, Span), #error_ref> {
+                    self.extract_pair(&DelimiterPair::of::<Open, Close>())
+                }
+
+                /// Extract tokens between whichever of `pairs` opens at the
+                /// cursor, for constructs that accept more than one bracket
+                /// style.
+                ///
+                /// Returns the inner stream, the span of the whole group, and
+                /// the index in `pairs` of the pair that matched.
+                ///
+                /// # Example
+                /// ```ignore
+                /// // For input: [1, 2] or (1, 2)
+                /// let (inner, span, which) = stream.extract_any_inner(&[
+                ///     DelimiterPair::of::<LParenToken, RParenToken>(),
+                ///     DelimiterPair::of::<LBracketToken, RBracketToken>(),
+                /// ])?;
+                /// ```
+                pub fn extract_any_inner(
+                    &mut self,
+                    pairs: &[DelimiterPair],
+                ) -> Result<(TokenStream, Span, usize), #error_ref> {
+                    use synkit::TokenStream as _;
+
+                    let found = self.peek_token().map(|tok| {
+                        pairs.iter().position(|pair| (pair.is_open)(&tok.value))
+                    });
+                    match found {
+                        Some(Some(index)) => {
+                            let (inner, span) = self.extract_pair(&pairs[index])?;
+                            Ok((inner, span, index))
+                        }
+                        Some(None) => {
+                            let tok = self.next();
+                            Err(#error_ref::Expected {
+                                expect: "opening delimiter",
+                                found: tok
+                                    .map(|tok| self.describe_found(&tok.value))
+                                    .unwrap_or_default(),
+                            })
+                        }
+                        None => Err(#error_ref::Empty {
+                            expect: "opening delimiter",
+                        }),
+                    }
+                }
+
+                fn extract_pair(
+                    &mut self,
+                    pair: &DelimiterPair,
+                ) -> Result<(TokenStream, Span), #error_ref> {
                     use synkit::TokenStream as _;
                     use synkit::SpanLike;
 
                     // Consume and validate opening delimiter
                     let first_span = match self.next() {
-                        Some(tok) if Open::is(&tok.value) => tok.span.clone(),
+                        Some(tok) if (pair.is_open)(&tok.value) => tok.span.clone(),
                         Some(tok) => {
-                            return Err(super::#error_type::Expected {
-                                expect: Open::fmt(),
-                                found: format!("{}", tok.value),
+                            return Err(#error_ref::Expected {
+                                expect: pair.open(),
+                                found: self.describe_found(&tok.value),
                             });
                         }
                         None => {
-                            return Err(super::#error_type::Empty {
-                                expect: Open::fmt(),
+                            return Err(#error_ref::Empty {
+                                expect: pair.open(),
                             });
                         }
                     };
@@ -577,9 +3246,9 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
 
                     // Find matching close delimiter, tracking nesting
                     while let Some(tok) = self.next_raw() {
-                        if Open::is(&tok.value) {
+                        if (pair.is_open)(&tok.value) {
                             depth += 1;
-                        } else if Close::is(&tok.value) {
+                        } else if (pair.is_close)(&tok.value) {
                             depth -= 1;
                             if depth == 0 {
                                 end_pos = Some(self.cursor);
@@ -595,28 +3264,295 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
 
                         let close_span = self.tokens.get(close_index)
                             .map(|t| &t.span)
-                            .unwrap_or(&Span::CallSite);
+                            .unwrap_or_else(|| super::span::call_site_ref());
 
-                        let combined_span = Span::new(first_span.start(), close_span.end());
+                        let combined_span =
+                            first_span.with_range(first_span.start(), close_span.end());
+
+                        let inner_depth = self.depth.nested();
+                        self.record_depth(inner_depth.depth());
 
                         Ok((
                             TokenStream {
                                 source: Arc::clone(&self.source),
-                                source_path: self.source_path.as_ref().map(Arc::clone),
+                                #path_clone
+                                #line_clone
+                                #newline_clone
                                 tokens: Arc::clone(&self.tokens),
                                 cursor: inner_start,
                                 range_start: inner_start,
                                 range_end: inner_end,
                                 last_cursor: inner_start,
+                                depth: inner_depth,
+                                max_depth: Arc::clone(&self.max_depth),
+                                delimiter: Some(pair.open()),
+                                budget: self.budget.clone(),
+                                attempts: self.attempts.clone(),
+                                progress: self.progress.clone(),
+                                skip: self.skip.clone(),
+                                #peek_init
                             },
                             combined_span,
                         ))
                     } else {
-                        Err(super::#error_type::Empty {
-                            expect: Close::fmt(),
+                        Err(#error_ref::Empty {
+                            expect: pair.close(),
                         })
                     }
                 }
+
+                /// Parse a `T`, or on failure skip to the next token that
+                /// could start one and return an [`ErrorNode`] instead.
+                ///
+                /// Never fails, so a tree is always produced; see
+                /// [`parse_or_error_with`](Self::parse_or_error_with).
+                pub fn parse_or_error<T>(&mut self) -> Recovered<T>
+                where
+                    T: super::traits::Parse + super::traits::Peek,
+                {
+                    self.parse_or_error_with::<T>(|tok| T::is(tok))
+                }
+
+                /// Parse a `T`, or on failure rewind and skip tokens up to the
+                /// next one `recover` accepts, returning them as an
+                /// [`ErrorNode`] with the parse error.
+                ///
+                /// At least one token is always skipped, so a loop over
+                /// `parse_or_error_with` makes progress. The recovery token
+                /// itself is left in the stream.
+                ///
+                /// # Example
+                /// ```ignore
+                /// // Skip a broken statement up to the next `;`
+                /// let stmt = stream.parse_or_error_with::<Stmt>(|tok| matches!(tok, Token::Semi));
+                /// ```
+                pub fn parse_or_error_with<T: super::traits::Parse>(
+                    &mut self,
+                    recover: impl Fn(&Token) -> bool,
+                ) -> Recovered<T> {
+                    self.parse_or_recover::<T>(synkit::recovery::SkipUntil(recover))
+                }
+
+                /// Parse a `T`, or on failure rewind and skip tokens until
+                /// `strategy` stops, returning them as an [`ErrorNode`] with
+                /// the parse error.
+                ///
+                /// `synkit::recovery` has strategies for the common cases:
+                /// the next separator at the same depth, the enclosing
+                /// closing delimiter, or the next line. As with
+                /// [`parse_or_error_with`](Self::parse_or_error_with), at
+                /// least one token is skipped.
+                ///
+                /// # Example
+                /// ```ignore
+                /// use synkit::recovery::SkipToSeparator;
+                ///
+                /// let arg = args.parse_or_recover::<Arg>(SkipToSeparator::<CommaToken>::new());
+                /// ```
+                pub fn parse_or_recover<T: super::traits::Parse>(
+                    &mut self,
+                    mut strategy: impl synkit::recovery::Recover<Token>,
+                ) -> Recovered<T> {
+                    use synkit::TokenStream as _;
+
+                    let start = self.cursor;
+                    let diag = match self.parse::<T>() {
+                        Ok(value) => return Recovered::Ok(value),
+                        Err(e) => e,
+                    };
+                    self.rewind(start);
+
+                    let mut tokens: Vec<SpannedToken> = Vec::new();
+                    while let Some(tok) = self.peek_token() {
+                        let starts_line = !synkit::SpanLike::is_call_site(&tok.span)
+                            && synkit::recovery::starts_line(
+                                &self.source,
+                                synkit::SpanLike::start(&tok.span),
+                            );
+                        let stop = strategy.stop(synkit::recovery::RecoveryToken {
+                            token: &tok.value,
+                            nesting: Self::nesting(&tok.value),
+                            starts_line,
+                        });
+                        if stop && !tokens.is_empty() {
+                            break;
+                        }
+                        tokens.extend(self.next());
+                    }
+
+                    let span = match (tokens.first(), tokens.last()) {
+                        (Some(first), Some(last)) => synkit::SpanLike::join(&first.span, &last.span),
+                        _ => {
+                            let end = self.source.len();
+                            <Span as synkit::SpanLike>::new(end, end)
+                        }
+                    };
+                    Recovered::Error(ErrorNode { span, tokens, diag })
+                }
+
+                /// Parse a `T` that fills the whole of a delimited group.
+                ///
+                /// Like [`extract_inner`](Self::extract_inner), but parses `T`
+                /// from the inner stream and fails if any non-skip tokens are
+                /// left over, instead of leaving that check to the caller.
+                /// Returns the delimiter tokens with the parsed value, whose
+                /// span covers the group including the delimiters.
+                ///
+                /// # Example
+                /// ```ignore
+                /// // For input: (a, b)
+                /// let (open, args, close) =
+                ///     stream.parse_delimited::<LParenToken, RParenToken, Args>()?;
+                /// ```
+                pub fn parse_delimited<Open, Close, T>(
+                    &mut self,
+                ) -> Result<(Open, Spanned<T>, Close), #error_ref>
+                where
+                    Open: super::traits::Parse + super::traits::Peek + super::traits::Diagnostic,
+                    Close: super::traits::Parse + super::traits::Peek + super::traits::Diagnostic,
+                    T: super::traits::Parse,
+                {
+                    use synkit::TokenStream as _;
+
+                    let open = Open::parse(&mut self.fork())?;
+                    let (mut inner, span) = self.extract_inner::<Open, Close>()?;
+                    let value = T::parse(&mut inner)?;
+                    if let Some(tok) = inner.next() {
+                        return Err(#error_ref::Expected {
+                            expect: Close::fmt(),
+                            found: inner.describe_found(&tok.value),
+                        });
+                    }
+
+                    let mut close_stream = self.fork();
+                    close_stream.rewind(self.last_cursor);
+                    let close = Close::parse(&mut close_stream)?;
+                    Ok((open, Spanned { span, value }, close))
+                }
+
+                /// Split the rest of the stream at `Sep` tokens outside any
+                /// delimiter group, e.g. `a, f(b, c)` at its first comma only.
+                ///
+                /// Each piece is a stream over a subrange of this stream's
+                /// tokens, without the separators; this stream isn't advanced.
+                /// `n` separators give `n + 1` pieces, so a trailing separator
+                /// leaves an empty last piece. A stream with no tokens left
+                /// gives no pieces.
+                ///
+                /// # Example
+                /// ```ignore
+                /// let (args, _) = stream.extract_inner::<LParenToken, RParenToken>()?;
+                /// for mut arg in args.split_top_level::<CommaToken>() {
+                ///     exprs.push(arg.parse::<Expr>()?);
+                /// }
+                /// ```
+                pub fn split_top_level<Sep: super::traits::Peek>(&self) -> Vec<TokenStream> {
+                    if self.is_empty() {
+                        return Vec::new();
+                    }
+
+                    let mut pieces = Vec::new();
+                    let mut depth = 0usize;
+                    let mut start = self.cursor;
+                    let end = self.range_end.min(self.tokens.len());
+                    let remaining = self.tokens.get(self.cursor..end).unwrap_or_default();
+                    for (index, tok) in remaining.iter().enumerate() {
+                        if self.skips(tok) {
+                            continue;
+                        }
+                        match Self::nesting(&tok.value) {
+                            synkit::recovery::Nesting::Open => depth += 1,
+                            synkit::recovery::Nesting::Close => depth = depth.saturating_sub(1),
+                            synkit::recovery::Nesting::Other if depth == 0 && Sep::is(&tok.value) => {
+                                let index = self.cursor + index;
+                                pieces.push(self.subrange(start, index));
+                                start = index + 1;
+                            }
+                            synkit::recovery::Nesting::Other => {}
+                        }
+                    }
+                    pieces.push(self.subrange(start, end));
+                    pieces
+                }
+
+                /// Whether `token` opens or closes one of the kit's delimiters.
+                fn nesting(token: &Token) -> synkit::recovery::Nesting {
+                    match token {
+                        #(#nesting_arms)*
+                        _ => synkit::recovery::Nesting::Other,
+                    }
+                }
+
+                /// A stream over `start..end` of this stream's tokens.
+                fn subrange(&self, start: usize, end: usize) -> TokenStream {
+                    let mut stream = synkit::TokenStream::fork(self);
+                    stream.cursor = start;
+                    stream.range_start = start;
+                    stream.range_end = end;
+                    stream.last_cursor = start;
+                    stream.invalidate_peek();
+                    stream
+                }
+
+                /// Index of the first non-skip token at or after the cursor,
+                /// or `range_end` if there is none.
+                fn next_significant(&self) -> usize {
+                    #peek_lookup
+                    let end = self.range_end.min(self.tokens.len());
+                    let remaining = self.tokens.get(self.cursor..end).unwrap_or_default();
+                    let index = remaining
+                        .iter()
+                        .position(|tok| !self.skips(tok))
+                        .map_or(self.range_end, |offset| self.cursor + offset);
+                    #peek_store
+                    index
+                }
+
+                /// Forget the cached `next_significant` index, after the
+                /// cursor, range or skip set changes.
+                #[inline]
+                fn invalidate_peek(&mut self) {
+                    #peek_invalidate
+                }
+            }
+
+            /// Consumes the remaining non-skip tokens.
+            impl Iterator for TokenStream {
+                type Item = SpannedToken;
+
+                fn next(&mut self) -> Option<SpannedToken> {
+                    synkit::TokenStream::next(self)
+                }
+            }
+
+            /// Borrows the remaining non-skip tokens without consuming them.
+            impl<'a> IntoIterator for &'a TokenStream {
+                type Item = &'a SpannedToken;
+                type IntoIter = Iter<'a>;
+
+                fn into_iter(self) -> Iter<'a> {
+                    let remaining = self.tokens.get(self.cursor..self.range_end).unwrap_or(&[]);
+                    Iter {
+                        stream: self,
+                        tokens: remaining.iter(),
+                    }
+                }
+            }
+
+            /// Iterator over a stream's remaining non-skip tokens, from
+            /// `&TokenStream`'s `IntoIterator`.
+            pub struct Iter<'a> {
+                stream: &'a TokenStream,
+                tokens: std::slice::Iter<'a, SpannedToken>,
+            }
+
+            impl<'a> Iterator for Iter<'a> {
+                type Item = &'a SpannedToken;
+
+                fn next(&mut self) -> Option<&'a SpannedToken> {
+                    let stream = self.stream;
+                    self.tokens.find(|tok| !stream.skips(tok))
+                }
             }
 
             impl synkit::TokenStream for TokenStream {
@@ -627,17 +3563,29 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
                 fn peek_token_raw(&self) -> Option<&SpannedToken> {
                     self.tokens
                         .get(self.cursor)
-                        .filter(|_| self.cursor < self.range_end)
+                        .filter(|_| self.cursor < self.range_end && !self.budget_exhausted())
                 }
 
                 fn next_raw(&mut self) -> Option<SpannedToken> {
                     if self.cursor >= self.range_end {
                         return None;
                     }
+                    if let Some(budget) = self.budget() {
+                        if budget.charge().is_err() {
+                            return None;
+                        }
+                    }
                     let tok = self.tokens.get(self.cursor).cloned();
-                    if tok.is_some() {
+                    if let Some(tok) = &tok {
                         self.last_cursor = self.cursor;
                         self.cursor += 1;
+                        self.invalidate_peek();
+                        if let Some(progress) = &self.progress {
+                            progress.update(synkit::SpanLike::end(&tok.span), self.cursor);
+                            if self.cursor == self.tokens.len() {
+                                progress.finish();
+                            }
+                        }
                     }
                     tok
                 }
@@ -645,109 +3593,277 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
                 fn next(&mut self) -> Option<SpannedToken> {
                     loop {
                         let tok = self.next_raw()?;
-                        if !Self::is_skip_token(&tok) {
+                        if !self.skips(&tok) {
                             return Some(tok);
                         }
                     }
                 }
 
                 fn peek_token(&self) -> Option<&SpannedToken> {
-                    let mut cursor = self.cursor;
-                    while cursor < self.range_end {
-                        if let Some(tok) = self.tokens.get(cursor) {
-                            if !Self::is_skip_token(tok) {
-                                return Some(tok);
-                            }
-                            cursor += 1;
-                        } else {
-                            break;
-                        }
+                    if self.budget_exhausted() {
+                        return None;
+                    }
+                    let index = self.next_significant();
+                    self.tokens.get(index).filter(|_| index < self.range_end)
+                }
+
+                fn cursor(&self) -> usize {
+                    self.cursor
+                }
+
+                fn rewind(&mut self, pos: usize) {
+                    self.cursor = pos.clamp(self.range_start, self.range_end);
+                    self.invalidate_peek();
+                }
+
+                fn fork(&self) -> Self {
+                    Self {
+                        source: Arc::clone(&self.source),
+                        #path_clone
+                        #line_clone
+                        #newline_clone
+                        tokens: Arc::clone(&self.tokens),
+                        cursor: self.cursor,
+                        range_start: self.range_start,
+                        range_end: self.range_end,
+                        last_cursor: self.last_cursor,
+                        depth: self.depth,
+                        max_depth: Arc::clone(&self.max_depth),
+                        delimiter: self.delimiter,
+                        budget: self.budget.clone(),
+                        attempts: self.attempts.clone(),
+                        progress: self.progress.clone(),
+                        skip: self.skip.clone(),
+                        #peek_clone
+                    }
+                }
+
+                fn cursor_span(&self) -> Option<Span> {
+                    self.tokens.get(self.cursor).map(|t| t.span.clone())
+                }
+
+                fn last_span(&self) -> Option<Span> {
+                    self.tokens.get(self.last_cursor).map(|t| t.span.clone())
+                }
+
+                fn span_at(&self, pos: usize) -> Option<Span> {
+                    self.tokens.get(pos).map(|t| t.span.clone())
+                }
+            }
+
+            #borrowed_stream
+
+            #stream_asserts
+
+            #[derive(Default, Debug, Clone)]
+            pub struct MutTokenStream {
+                tokens: Vec<SpannedToken>,
+            }
+
+            impl From<Vec<SpannedToken>> for MutTokenStream {
+                fn from(tokens: Vec<SpannedToken>) -> Self {
+                    Self { tokens }
+                }
+            }
+
+            impl From<MutTokenStream> for TokenStream {
+                fn from(stream: MutTokenStream) -> Self {
+                    stream.into_token_stream("")
+                }
+            }
+
+            impl FromIterator<SpannedToken> for MutTokenStream {
+                fn from_iter<I: IntoIterator<Item = SpannedToken>>(iter: I) -> Self {
+                    Self {
+                        tokens: iter.into_iter().collect(),
                     }
-                    None
+                }
+            }
+
+            impl IntoIterator for MutTokenStream {
+                type Item = SpannedToken;
+                type IntoIter = std::vec::IntoIter<SpannedToken>;
+
+                fn into_iter(self) -> Self::IntoIter {
+                    self.tokens.into_iter()
+                }
+            }
+
+            impl MutTokenStream {
+                pub fn new() -> Self {
+                    Self::default()
+                }
+
+                pub fn push(&mut self, token: SpannedToken) {
+                    self.tokens.push(token);
+                }
+
+                /// Push a token with a call-site span.
+                pub fn push_synthetic(&mut self, token: Token) {
+                    self.tokens.push(token.spanned());
+                }
+
+                pub fn extend<I: IntoIterator<Item = SpannedToken>>(&mut self, iter: I) {
+                    self.tokens.extend(iter);
+                }
+
+                pub fn all_tokens(&self) -> &[SpannedToken] {
+                    &self.tokens
+                }
+
+                pub fn into_vec(self) -> Vec<SpannedToken> {
+                    self.tokens
+                }
+
+                pub fn len(&self) -> usize {
+                    self.tokens.len()
+                }
+
+                pub fn is_empty(&self) -> bool {
+                    self.tokens.is_empty()
                 }
 
-                fn cursor(&self) -> usize {
-                    self.cursor
+                /// Mutable access to the underlying tokens.
+                pub fn tokens_mut(&mut self) -> &mut [SpannedToken] {
+                    &mut self.tokens
                 }
 
-                fn rewind(&mut self, pos: usize) {
-                    self.cursor = pos.clamp(self.range_start, self.range_end);
+                /// Insert a token at `index` (clamped to the stream length).
+                pub fn insert(&mut self, index: usize, token: SpannedToken) {
+                    let index = index.min(self.tokens.len());
+                    self.tokens.insert(index, token);
                 }
 
-                fn fork(&self) -> Self {
-                    Self {
-                        source: Arc::clone(&self.source),
-                        source_path: self.source_path.as_ref().map(Arc::clone),
-                        tokens: Arc::clone(&self.tokens),
-                        cursor: self.cursor,
-                        range_start: self.range_start,
-                        range_end: self.range_end,
-                        last_cursor: self.last_cursor,
-                    }
+                /// Remove and return the token at `index`, if any.
+                pub fn remove(&mut self, index: usize) -> Option<SpannedToken> {
+                    (index < self.tokens.len()).then(|| self.tokens.remove(index))
                 }
 
-                fn cursor_span(&self) -> Option<Span> {
-                    self.tokens.get(self.cursor).map(|t| t.span.clone())
+                /// Remove and return the tokens in `range` (clamped to the stream length).
+                pub fn remove_range<R: RangeBounds<usize>>(&mut self, range: R) -> Vec<SpannedToken> {
+                    let range = self.clamp_range(range);
+                    self.tokens.drain(range).collect()
                 }
 
-                fn last_span(&self) -> Option<Span> {
-                    self.tokens.get(self.last_cursor).map(|t| t.span.clone())
+                /// Replace the tokens in `range` with `replacement`, returning the removed tokens.
+                ///
+                /// The range is clamped to the stream length.
+                pub fn replace_range<R, I>(&mut self, range: R, replacement: I) -> Vec<SpannedToken>
+                where
+                    R: RangeBounds<usize>,
+                    I: IntoIterator<Item = SpannedToken>,
+                {
+                    let range = self.clamp_range(range);
+                    self.tokens.splice(range, replacement).collect()
                 }
 
-                fn span_at(&self, pos: usize) -> Option<Span> {
-                    self.tokens.get(pos).map(|t| t.span.clone())
+                /// Insert all tokens of `other` at `index` (clamped to the stream length).
+                pub fn splice(&mut self, index: usize, other: MutTokenStream) {
+                    let index = index.min(self.tokens.len());
+                    self.tokens.splice(index..index, other.tokens);
                 }
-            }
 
-            // Compile-time assertions for TokenStream
-            const _: () = {
-                const fn assert_send<T: Send>() {}
-                const fn assert_sync<T: Sync>() {}
-                assert_send::<TokenStream>();
-                assert_sync::<TokenStream>();
-            };
+                /// Append all tokens of `other`.
+                pub fn append(&mut self, mut other: MutTokenStream) {
+                    self.tokens.append(&mut other.tokens);
+                }
 
-            #[cfg(target_pointer_width = "64")]
-            const _: () = {
-                use core::mem::{size_of, align_of};
+                /// Keep only the tokens for which `f` returns `true`.
+                pub fn retain<F: FnMut(&SpannedToken) -> bool>(&mut self, f: F) {
+                    self.tokens.retain(f);
+                }
 
-                // TokenStream layout on 64-bit:
-                // - source: Arc<str> = 16 bytes (DST: ptr + len)
-                // - source_path: Option<Arc<Path>> = 16 bytes (DST: ptr + len)
-                // - tokens: Arc<Vec<SpannedToken>> = 8 bytes (thin ptr)
-                // - cursor: usize = 8 bytes
-                // - range_start: usize = 8 bytes
-                // - range_end: usize = 8 bytes
-                // - last_cursor: usize = 8 bytes
-                // Total: 72 bytes, 8-byte aligned
-                const _STREAM_SIZE: () = assert!(size_of::<TokenStream>() == 72);
-                const _STREAM_ALIGN: () = assert!(align_of::<TokenStream>() == 8);
-            };
+                /// Set the span of every token to `span`.
+                pub fn respan(&mut self, span: Span) {
+                    for tok in &mut self.tokens {
+                        tok.span = span.clone();
+                    }
+                }
 
-            #[derive(Default, Debug, Clone)]
-            pub struct MutTokenStream {
-                tokens: Vec<SpannedToken>,
-            }
+                /// Set the span of every token to the call-site span.
+                pub fn respan_call_site(&mut self) {
+                    self.respan(<Span as synkit::SpanLike>::call_site());
+                }
 
-            impl MutTokenStream {
-                pub fn new() -> Self {
-                    Self::default()
+                /// Shift every known span by `offset` bytes (saturating).
+                ///
+                /// Useful after splicing tokens lexed from a different buffer.
+                pub fn shift_spans(&mut self, offset: isize) {
+                    use synkit::SpanLike;
+                    for tok in &mut self.tokens {
+                        if !tok.span.is_call_site() {
+                            tok.span = tok.span.with_range(
+                                tok.span.start().saturating_add_signed(offset),
+                                tok.span.end().saturating_add_signed(offset),
+                            );
+                        }
+                    }
                 }
 
-                pub fn push(&mut self, token: SpannedToken) {
-                    self.tokens.push(token);
+                /// The span joining all known token spans, or the call-site span if none.
+                pub fn covering_span(&self) -> Span {
+                    use synkit::SpanLike;
+                    self.tokens
+                        .iter()
+                        .fold(<Span as SpanLike>::call_site(), |acc, tok| acc.join(&tok.span))
                 }
 
-                pub fn extend<I: IntoIterator<Item = SpannedToken>>(&mut self, iter: I) {
-                    self.tokens.extend(iter);
+                /// Convert into an immutable `TokenStream` for reparsing.
+                ///
+                /// `source` is used for `TokenStream::slice`; pass the buffer the
+                /// known spans refer to, or `""` if all spans are call-site.
+                pub fn into_token_stream(self, source: impl Into<Arc<str>>) -> TokenStream {
+                    TokenStream::from_tokens(source.into(), Arc::new(self.tokens))
                 }
 
-                pub fn all_tokens(&self) -> &[SpannedToken] {
-                    &self.tokens
+                fn clamp_range<R: RangeBounds<usize>>(&self, range: R) -> std::ops::Range<usize> {
+                    let len = self.tokens.len();
+                    let start = match range.start_bound() {
+                        Bound::Included(&n) => n,
+                        Bound::Excluded(&n) => n.saturating_add(1),
+                        Bound::Unbounded => 0,
+                    };
+                    let end = match range.end_bound() {
+                        Bound::Included(&n) => n.saturating_add(1),
+                        Bound::Excluded(&n) => n,
+                        Bound::Unbounded => len,
+                    };
+                    let end = end.min(len);
+                    start.min(end)..end
                 }
 
-                pub fn into_vec(self) -> Vec<SpannedToken> {
-                    self.tokens
+                /// Lex `source` and append its tokens, dropping skip tokens.
+                ///
+                /// Appended tokens get call-site spans since they don't point
+                /// into any parsed input.
+                pub fn push_source(&mut self, source: &str) -> Result<(), #error_ref> {
+                    use logos::Logos;
+                    let mut lex = Token::lexer(source);
+                    while let Some(tok) = lex.next() {
+                        let tok = Spanned {
+                            span: <Span as synkit::SpanLike>::call_site(),
+                            value: tok?,
+                        };
+                        if !TokenStream::is_skip_token(&tok) {
+                            self.tokens.push(tok);
+                        }
+                    }
+                    Ok(())
+                }
+
+                /// Build a stream by lexing each piece independently.
+                ///
+                /// This is the runtime half of the `quote_tokens!` macro.
+                pub fn from_pieces<I, S>(pieces: I) -> Result<Self, #error_ref>
+                where
+                    I: IntoIterator<Item = S>,
+                    S: AsRef<str>,
+                {
+                    let mut stream = Self::new();
+                    for piece in pieces {
+                        stream.push_source(piece.as_ref())?;
+                    }
+                    Ok(stream)
                 }
             }
         }
@@ -755,6 +3871,7 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
 
     let printer_module = quote! {
         pub mod printer {
+            #crate_alias
             use super::tokens::Token;
 
             pub struct Printer {
@@ -762,6 +3879,12 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
                 pub indent_level: usize,
                 indent_width: usize,
                 use_tabs: bool,
+                source_map: Option<synkit::SourceMapping>,
+                comment_policy: synkit::CommentPolicy,
+                trailing_policy: synkit::TrailingPolicy,
+                separate_tokens: bool,
+                /// Output range of the last token written by `token`.
+                last_token: Option<std::ops::Range<usize>>,
             }
 
             impl Default for Printer {
@@ -777,6 +3900,11 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
                         indent_level: 0,
                         indent_width: 4,
                         use_tabs: false,
+                        source_map: None,
+                        comment_policy: synkit::CommentPolicy::new(),
+                        trailing_policy: synkit::TrailingPolicy::Optional,
+                        separate_tokens: true,
+                        last_token: None,
                     }
                 }
 
@@ -796,6 +3924,73 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
                     self.use_tabs = true;
                     self
                 }
+
+                /// Set how `comment` places and wraps comments.
+                pub fn with_comment_policy(mut self, policy: synkit::CommentPolicy) -> Self {
+                    self.comment_policy = policy;
+                    self
+                }
+
+                /// Add (`Required`), drop (`Forbidden`) or keep (`Optional`,
+                /// the default) the separator after the last item of printed
+                /// `Punctuated` sequences.
+                pub fn with_trailing_policy(mut self, policy: synkit::TrailingPolicy) -> Self {
+                    self.trailing_policy = policy;
+                    self
+                }
+
+                /// Whether `token` inserts a space between two adjacent tokens
+                /// that would lex differently when joined (default: `true`).
+                pub fn with_token_separation(mut self, enabled: bool) -> Self {
+                    self.separate_tokens = enabled;
+                    self
+                }
+
+                /// Whether `prev` directly followed by `next` lexes as
+                /// something other than those two tokens, e.g. two identifiers
+                /// merging into one. Texts that don't lex as a single token
+                /// on their own are never considered merged.
+                pub fn tokens_merge(prev: &str, next: &str) -> bool {
+                    use logos::Logos;
+                    fn single(text: &str) -> bool {
+                        let mut lex = Token::lexer(text);
+                        matches!(lex.next(), Some(Ok(_)))
+                            && lex.span() == (0..text.len())
+                            && lex.next().is_none()
+                    }
+                    if !single(prev) || !single(next) {
+                        return false;
+                    }
+                    let joined = format!("{prev}{next}");
+                    let mut lex = Token::lexer(&joined);
+                    let split = matches!(lex.next(), Some(Ok(_)))
+                        && lex.span() == (0..prev.len())
+                        && matches!(lex.next(), Some(Ok(_)))
+                        && lex.span() == (prev.len()..joined.len())
+                        && lex.next().is_none();
+                    !split
+                }
+
+                /// Enable recording of output→source mappings.
+                ///
+                /// Every `Spanned<T>` written with a known span records the
+                /// output range it produced.
+                pub fn with_source_map(mut self) -> Self {
+                    self.source_map = Some(synkit::SourceMapping::new());
+                    self
+                }
+
+                /// The mappings recorded so far, if source mapping is enabled.
+                pub fn source_map(&self) -> Option<&synkit::SourceMapping> {
+                    self.source_map.as_ref()
+                }
+
+                /// Consume the printer, returning the output and its source mapping.
+                ///
+                /// The mapping is empty if `with_source_map` was not called.
+                pub fn finish_with_source_map(self) -> (String, synkit::SourceMapping) {
+                    (self.buf, self.source_map.unwrap_or_default())
+                }
             }
 
             impl synkit::Printer for Printer {
@@ -829,34 +4024,195 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
                     self.use_tabs
                 }
 
+                fn source_mapping_mut(&mut self) -> Option<&mut synkit::SourceMapping> {
+                    self.source_map.as_mut()
+                }
+
+                fn comment_policy(&self) -> synkit::CommentPolicy {
+                    self.comment_policy
+                }
+
+                fn trailing_policy(&self) -> synkit::TrailingPolicy {
+                    self.trailing_policy
+                }
+
+                /// Write `t`'s text, preceded by a space if it would otherwise
+                /// merge with the token written just before it.
                 fn token(&mut self, t: &Token) {
-                    use std::fmt::Write;
-                    let _ = write!(self.buf, "{}", t);
+                    use core::fmt::Write as _;
+                    let mut start = self.buf.len();
+                    let _ = write!(self.buf, "{t}");
+                    if self.separate_tokens {
+                        let adjacent = self
+                            .last_token
+                            .clone()
+                            .filter(|prev| prev.end == start)
+                            .and_then(|prev| self.buf.get(prev));
+                        if let Some(prev) = adjacent {
+                            if Self::tokens_merge(prev, &self.buf[start..]) {
+                                self.buf.insert(start, ' ');
+                                start += 1;
+                            }
+                        }
+                    }
+                    self.last_token = Some(start..self.buf.len());
                 }
             }
         }
     };
 
     // Generate delimiter structs (inside delimiters module)
-    let delimiter_structs: Vec<_> = delimiters
+    let delimiter_structs = delimiters
         .iter()
         .map(|d| {
-            let DelimiterDef { name, open, close } = d;
+            let DelimiterDef {
+                cfg_attrs,
+                name,
+                open,
+                close,
+            } = d;
+            let open_token = format_ident!("{}Token", open);
+            let close_token = format_ident!("{}Token", close);
+            let extract_fn = format_ident!("extract_{}", name.to_string().to_lowercase());
+            let extract_doc = format!(
+                " Extract the tokens between a matching `{open}`/`{close}` pair as a [`{name}`]."
+            );
+
+            let maybe_name = format_ident!("Maybe{}", name);
+            let list_name = format_ident!("{}List", name);
+            let maybe_doc = format!(" A `T`, optionally wrapped in `{open}`/`{close}`.");
+            let list_doc = format!(
+                " `Sep`-separated `T`s between `{open}`/`{close}`, with an optional trailing `Sep`."
+            );
+            let sugar = delimiter_sugar.then(|| {
+                quote! {
+                    #[doc = #maybe_doc]
+                    #[derive(Debug, Clone)]
+                    pub enum #maybe_name<T> {
+                        Wrapped(#name, super::span::Spanned<T>),
+                        Bare(super::span::Spanned<T>),
+                    }
 
-            quote! {
+                    impl<T> #maybe_name<T> {
+                        /// The value, with its own span.
+                        pub fn value(&self) -> &super::span::Spanned<T> {
+                            match self {
+                                Self::Wrapped(_, value) | Self::Bare(value) => value,
+                            }
+                        }
+
+                        pub fn into_value(self) -> super::span::Spanned<T> {
+                            match self {
+                                Self::Wrapped(_, value) | Self::Bare(value) => value,
+                            }
+                        }
+
+                        /// The delimiters, if the value was wrapped.
+                        pub fn delimiter(&self) -> Option<&#name> {
+                            match self {
+                                Self::Wrapped(delim, _) => Some(delim),
+                                Self::Bare(_) => None,
+                            }
+                        }
+                    }
+
+                    impl<T: super::traits::Parse> super::traits::Parse for #maybe_name<T> {
+                        fn parse(
+                            stream: &mut super::stream::TokenStream,
+                        ) -> Result<Self, #error_ref> {
+                            if !stream.peek::<super::tokens::#open_token>() {
+                                return Ok(Self::Bare(stream.parse()?));
+                            }
+                            let (mut inner, delim) = stream.#extract_fn()?;
+                            let value = inner.parse()?;
+                            if let Some(tok) = inner.next() {
+                                return Err(#error_ref::Expected {
+                                    expect: super::tokens::#close_token::fmt(),
+                                    found: inner.describe_found(&tok.value),
+                                });
+                            }
+                            Ok(Self::Wrapped(delim, value))
+                        }
+                    }
+
+                    impl<T: super::traits::Peek> super::traits::Peek for #maybe_name<T> {
+                        fn is(token: &super::tokens::Token) -> bool {
+                            <super::tokens::#open_token as super::traits::Peek>::is(token)
+                                || T::is(token)
+                        }
+                    }
+
+                    impl<T: super::traits::ToTokens> super::traits::ToTokens for #maybe_name<T> {
+                        fn write(&self, p: &mut super::printer::Printer) {
+                            match self {
+                                Self::Wrapped(delim, value) => delim.write_with(p, |p| value.write(p)),
+                                Self::Bare(value) => value.write(p),
+                            }
+                        }
+                    }
+
+                    #[doc = #list_doc]
+                    #[derive(Debug, Clone)]
+                    pub struct #list_name<T, Sep> {
+                        pub delim: #name,
+                        pub items: synkit::Punctuated<super::span::Spanned<T>, super::span::Spanned<Sep>>,
+                    }
+
+                    impl<T: super::traits::Parse, Sep: super::traits::Parse> super::traits::Parse
+                        for #list_name<T, Sep>
+                    {
+                        fn parse(
+                            stream: &mut super::stream::TokenStream,
+                        ) -> Result<Self, #error_ref> {
+                            let (mut inner, delim) = stream.#extract_fn()?;
+                            let mut items = synkit::Punctuated::new();
+                            while !inner.is_empty() {
+                                items.push_value(inner.parse()?);
+                                if inner.is_empty() {
+                                    break;
+                                }
+                                items.push_punct(inner.parse()?);
+                            }
+                            Ok(Self { delim, items })
+                        }
+                    }
+
+                    impl<T, Sep> super::traits::Peek for #list_name<T, Sep> {
+                        fn is(token: &super::tokens::Token) -> bool {
+                            <super::tokens::#open_token as super::traits::Peek>::is(token)
+                        }
+                    }
+
+                    impl<T: super::traits::ToTokens, Sep: super::traits::ToTokens + Default>
+                        super::traits::ToTokens for #list_name<T, Sep>
+                    {
+                        fn write(&self, p: &mut super::printer::Printer) {
+                            self.delim.write_with(p, |p| self.items.write(p));
+                        }
+                    }
+                }
+            });
+
+            let items = quote! {
                 #[derive(Debug, Clone)]
                 pub struct #name {
                     span: super::span::Span,
                 }
 
                 impl #name {
+                    /// This delimiter's token pair, for `extract_any_inner`.
+                    pub const PAIR: super::stream::DelimiterPair = super::stream::DelimiterPair::of::<
+                        super::tokens::#open_token,
+                        super::tokens::#close_token,
+                    >();
+
                     pub fn new(span: super::span::Span) -> Self {
                         Self { span }
                     }
 
                     pub fn call_site() -> Self {
                         Self {
-                            span: super::span::Span::CallSite,
+                            span: <super::span::Span as synkit::SpanLike>::call_site(),
                         }
                     }
 
@@ -874,18 +4230,36 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
                         printer.token(&super::tokens::Token::#close);
                     }
                 }
-            }
+
+                impl super::stream::TokenStream {
+                    #[doc = #extract_doc]
+                    pub fn #extract_fn(
+                        &mut self,
+                    ) -> Result<(super::stream::TokenStream, #name), #error_ref> {
+                        let (inner, span) = self.extract_inner::<
+                            super::tokens::#open_token,
+                            super::tokens::#close_token,
+                        >()?;
+                        Ok((inner, #name::new(span)))
+                    }
+                }
+
+                #sugar
+            };
+            with_cfg(cfg_attrs, items)
         })
-        .collect();
+        .collect::<syn::Result<Vec<_>>>()?;
 
-    // Generate delimiter macros at crate level (not inside module, for proper re-export)
+    // Delimiter macros, scoped to `delimiters` and re-exported from `prelude`.
+    // They only call methods on the stream, so they work wherever the kit is.
     let delimiter_macros: Vec<_> = delimiters
         .iter()
         .map(|d| {
-            let DelimiterDef { name, open, close } = d;
-            let open_token = format_ident!("{}Token", open);
-            let close_token = format_ident!("{}Token", close);
+            let DelimiterDef {
+                cfg_attrs, name, ..
+            } = d;
             let macro_name = format_ident!("{}", name.to_string().to_lowercase());
+            let extract_fn = format_ident!("extract_{}", macro_name);
 
             quote! {
                 /// Extract tokens within matching delimiters.
@@ -896,46 +4270,61 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
                 /// // `inner` is now a TokenStream of the contents
                 /// // `delim` holds the span information
                 /// ```
+                #(#cfg_attrs)*
                 #[allow(non_snake_case)]
-                #[macro_export]
                 macro_rules! #macro_name {
                     ($tokens:ident in $input:ident) => {
-                        match $input.extract_inner::<
-                            $crate::tokens::#open_token,
-                            $crate::tokens::#close_token
-                        >() {
-                            Ok((tokens, span)) => {
+                        match $input.#extract_fn() {
+                            Ok((tokens, delim)) => {
                                 $tokens = tokens;
-                                $crate::delimiters::#name::new(span)
+                                delim
                             }
                             Err(e) => return Err(e),
                         }
                     };
                     ($tokens:ident in $input:ident; $err:expr) => {
-                        match $input.extract_inner::<
-                            $crate::tokens::#open_token,
-                            $crate::tokens::#close_token
-                        >() {
-                            Ok((tokens, span)) => {
+                        match $input.#extract_fn() {
+                            Ok((tokens, delim)) => {
                                 $tokens = tokens;
-                                $crate::delimiters::#name::new(span)
+                                delim
                             }
                             Err(..) => return $err,
                         }
                     };
                 }
+                #(#cfg_attrs)*
+                pub(crate) use #macro_name;
+            }
+        })
+        .collect();
+
+    // Opt-in FromStr impls for AST roots listed in `from_str: [...]`
+    let from_str_impls: Vec<_> = from_str
+        .iter()
+        .map(|ty| {
+            quote! {
+                impl std::str::FromStr for #ty {
+                    type Err = #error_type;
+
+                    fn from_str(s: &str) -> Result<Self, Self::Err> {
+                        <Self as traits::Parse>::parse_str(s)
+                    }
+                }
             }
         })
         .collect();
 
     let delimiters_module = quote! {
         pub mod delimiters {
+            #crate_alias
             #(#delimiter_structs)*
+
+            #(#delimiter_macros)*
         }
     };
 
     // Generate Diagnostic, Peek, and Parse impls for token structs
-    let token_trait_impls: Vec<_> = tokens
+    let token_trait_impls = tokens
         .iter()
         .map(|t| {
             let name = &t.name;
@@ -945,17 +4334,17 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
             let parse_impl = if has_inner {
                 quote! {
                     impl Parse for super::tokens::#struct_name {
-                        fn parse(stream: &mut TokenStream) -> Result<Self, super::#error_type> {
+                        fn parse(stream: &mut TokenStream) -> Result<Self, #error_ref> {
                             use synkit::TokenStream as _;
                             match stream.next() {
                                 Some(tok) => match tok.value {
                                     super::tokens::Token::#name(v) => Ok(super::tokens::#struct_name::new(v)),
-                                    ref other => Err(super::#error_type::Expected {
+                                    ref other => Err(#error_ref::Expected {
                                         expect: super::tokens::#struct_name::fmt(),
-                                        found: format!("{}", other),
+                                        found: stream.describe_found(other),
                                     }),
                                 },
-                                None => Err(super::#error_type::Empty {
+                                None => Err(#error_ref::Empty {
                                     expect: super::tokens::#struct_name::fmt(),
                                 }),
                             }
@@ -965,17 +4354,17 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
             } else {
                 quote! {
                     impl Parse for super::tokens::#struct_name {
-                        fn parse(stream: &mut TokenStream) -> Result<Self, super::#error_type> {
+                        fn parse(stream: &mut TokenStream) -> Result<Self, #error_ref> {
                             use synkit::TokenStream as _;
                             match stream.next() {
                                 Some(tok) => match &tok.value {
                                     super::tokens::Token::#name => Ok(super::tokens::#struct_name::new()),
-                                    other => Err(super::#error_type::Expected {
+                                    other => Err(#error_ref::Expected {
                                         expect: super::tokens::#struct_name::fmt(),
-                                        found: format!("{}", other),
+                                        found: stream.describe_found(other),
                                     }),
                                 },
-                                None => Err(super::#error_type::Empty {
+                                None => Err(#error_ref::Empty {
                                     expect: super::tokens::#struct_name::fmt(),
                                 }),
                             }
@@ -984,7 +4373,50 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
                 }
             };
 
-            quote! {
+            // Payload tokens re-lex their text with `Token`'s lexer to
+            // build the owned value; borrowing nodes use `stream.text` instead.
+            let borrowed_impl = borrowed.then(|| {
+                let (pattern, parse_body) = if has_inner {
+                    (
+                        quote! { super::tokens::BorrowedToken::#name(_) },
+                        quote! {
+                            let text = stream.text::<Self>()?.value;
+                            match <super::tokens::Token as logos::Logos>::lexer(text).next() {
+                                Some(Ok(super::tokens::Token::#name(v))) => Ok(super::tokens::#struct_name::new(v)),
+                                _ => Err(#error_ref::Expected {
+                                    expect: super::tokens::#struct_name::fmt(),
+                                    found: text.to_string(),
+                                }),
+                            }
+                        },
+                    )
+                } else {
+                    (
+                        quote! { super::tokens::BorrowedToken::#name },
+                        quote! {
+                            stream.expect::<Self>()?;
+                            Ok(super::tokens::#struct_name::new())
+                        },
+                    )
+                };
+                quote! {
+                    impl PeekBorrowed for super::tokens::#struct_name {
+                        fn is_borrowed(token: &super::tokens::BorrowedToken<'_>) -> bool {
+                            matches!(token, #pattern)
+                        }
+                    }
+
+                    impl<'src> ParseBorrowed<'src> for super::tokens::#struct_name {
+                        fn parse_borrowed(
+                            stream: &mut super::stream::BorrowedStream<'src>,
+                        ) -> Result<Self, #error_ref> {
+                            #parse_body
+                        }
+                    }
+                }
+            });
+
+            let items = quote! {
                 impl Diagnostic for super::tokens::#struct_name {
                     fn fmt() -> &'static str {
                         super::tokens::#struct_name::fmt()
@@ -997,60 +4429,192 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
                     }
                 }
                 #parse_impl
-            }
+                #borrowed_impl
+            };
+            with_cfg(&t.cfg_attrs, items)
         })
-        .collect();
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    #[cfg(any(feature = "tokio", feature = "futures"))]
+    let async_traits = quote! {
+
+            /// Simplified IncrementalParse trait for streaming/chunked parsing.
+            ///
+            /// Implement this trait to enable incremental parsing of AST nodes
+            /// from a token buffer with checkpoint-based state management.
+            ///
+            /// ```ignore
+            /// impl IncrementalParse for MyNode {
+            ///     fn parse_incremental(
+            ///         tokens: &[Token],
+            ///         checkpoint: &synkit::async_stream::ParseCheckpoint,
+            ///     ) -> Result<(Option<Self>, synkit::async_stream::ParseCheckpoint), LexError> {
+            ///         // ...
+            ///     }
+            ///
+            ///     fn can_parse(tokens: &[Token], checkpoint: &synkit::async_stream::ParseCheckpoint) -> bool {
+            ///         checkpoint.cursor < tokens.len()
+            ///     }
+            /// }
+            /// ```
+            pub trait IncrementalParse: Sized {
+                /// Attempt to parse from the given tokens starting at the checkpoint.
+                ///
+                /// Returns:
+                /// - `Ok((Some(node), new_checkpoint))` if a complete node was parsed
+                /// - `Ok((None, checkpoint))` if more tokens are needed
+                /// - `Err(error)` if an unrecoverable error occurred
+                fn parse_incremental(
+                    tokens: &[Token],
+                    checkpoint: &synkit::async_stream::ParseCheckpoint,
+                ) -> Result<(Option<Self>, synkit::async_stream::ParseCheckpoint), #error_ref>;
+
+                /// Check if parsing can produce a result with the current tokens.
+                ///
+                /// This is used for early return when more input is clearly needed.
+                fn can_parse(tokens: &[Token], checkpoint: &synkit::async_stream::ParseCheckpoint) -> bool;
+            }
+    };
+    #[cfg(not(any(feature = "tokio", feature = "futures")))]
+    let async_traits = quote! {};
+
+    // Generate user-friendly local trait aliases
+    let container_traits = container_impls.then(|| {
+        quote! {
+            /// Parses `T`s for as long as the next token peeks as one.
+            impl<T: Parse + Peek> Parse for Vec<T> {
+                fn parse(stream: &mut TokenStream) -> Result<Self, #error_ref> {
+                    let mut items = Vec::new();
+                    while T::peek(stream) {
+                        items.push(T::parse(stream)?);
+                    }
+                    Ok(items)
+                }
+            }
+
+            impl<T: Parse + Peek> Parse for Option<Spanned<T>> {
+                fn parse(stream: &mut TokenStream) -> Result<Self, #error_ref> {
+                    if T::peek(stream) {
+                        Ok(Some(stream.parse()?))
+                    } else {
+                        Ok(None)
+                    }
+                }
+            }
+
+            /// An item followed by an optional `P`, e.g. a trailing separator.
+            impl<T: Parse, P: Parse + Peek> Parse for (T, Option<P>) {
+                fn parse(stream: &mut TokenStream) -> Result<Self, #error_ref> {
+                    let item = T::parse(stream)?;
+                    Ok((item, Option::<P>::parse(stream)?))
+                }
+            }
+
+            impl<T: Peek> Peek for Option<T> {
+                fn is(token: &Token) -> bool {
+                    T::is(token)
+                }
+            }
+
+            impl<T: Peek, P> Peek for (T, Option<P>) {
+                fn is(token: &Token) -> bool {
+                    T::is(token)
+                }
+            }
 
-    #[cfg(any(feature = "tokio", feature = "futures"))]
-    let async_traits = quote! {
+            impl<T: ToTokens, P: ToTokens> ToTokens for (T, Option<P>) {
+                fn write(&self, p: &mut Printer) {
+                    self.0.write(p);
+                    self.1.write(p);
+                }
+            }
+        }
+    });
 
-            /// Simplified IncrementalParse trait for streaming/chunked parsing.
+    // `borrowed`: parse and peek traits over `BorrowedStream`
+    let borrowed_traits = borrowed.then(|| {
+        quote! {
+            /// `Parse` for AST nodes that borrow from the source text.
             ///
-            /// Implement this trait to enable incremental parsing of AST nodes
-            /// from a token buffer with checkpoint-based state management.
+            /// Nodes take `&'src str` slices from a
+            /// [`BorrowedStream`](super::stream::BorrowedStream), whose
+            /// tokens borrow from the source too, instead of allocating a
+            /// `String` per node. Token structs implement it.
             ///
             /// ```ignore
-            /// impl IncrementalParse for MyNode {
-            ///     fn parse_incremental(
-            ///         tokens: &[Token],
-            ///         checkpoint: &synkit::async_stream::ParseCheckpoint,
-            ///     ) -> Result<(Option<Self>, synkit::async_stream::ParseCheckpoint), LexError> {
-            ///         // ...
-            ///     }
-            ///
-            ///     fn can_parse(tokens: &[Token], checkpoint: &synkit::async_stream::ParseCheckpoint) -> bool {
-            ///         checkpoint.cursor < tokens.len()
+            /// impl<'src> ParseBorrowed<'src> for Pair<'src> {
+            ///     fn parse_borrowed(stream: &mut BorrowedStream<'src>) -> Result<Self, LexError> {
+            ///         let key = stream.text::<tokens::IdentToken>()?;
+            ///         stream.parse::<tokens::EqToken>()?;
+            ///         let value = stream.text::<tokens::StringToken>()?;
+            ///         Ok(Self { key, value })
             ///     }
             /// }
             /// ```
-            pub trait IncrementalParse: Sized {
-                /// Attempt to parse from the given tokens starting at the checkpoint.
-                ///
-                /// Returns:
-                /// - `Ok((Some(node), new_checkpoint))` if a complete node was parsed
-                /// - `Ok((None, checkpoint))` if more tokens are needed
-                /// - `Err(error)` if an unrecoverable error occurred
-                fn parse_incremental(
-                    tokens: &[Token],
-                    checkpoint: &synkit::async_stream::ParseCheckpoint,
-                ) -> Result<(Option<Self>, synkit::async_stream::ParseCheckpoint), super::#error_type>;
+            pub trait ParseBorrowed<'src>: Sized {
+                fn parse_borrowed(
+                    stream: &mut super::stream::BorrowedStream<'src>,
+                ) -> Result<Self, #error_ref>;
+
+                /// Parse and wrap the result with span information, like
+                /// [`Parse::parse_spanned`].
+                fn parse_borrowed_spanned(
+                    stream: &mut super::stream::BorrowedStream<'src>,
+                ) -> Result<Spanned<Self>, #error_ref> {
+                    use synkit::TokenStream as _;
+                    let first = stream.peek_token().map(|t| t.span.clone());
+                    let start = first.as_ref().map_or(0, synkit::SpanLike::start);
 
-                /// Check if parsing can produce a result with the current tokens.
-                ///
-                /// This is used for early return when more input is clearly needed.
-                fn can_parse(tokens: &[Token], checkpoint: &synkit::async_stream::ParseCheckpoint) -> bool;
+                    let value = Self::parse_borrowed(stream)?;
+
+                    let end = stream.last_span()
+                        .map(|s| synkit::SpanLike::end(&s))
+                        .unwrap_or(start);
+
+                    let span = match &first {
+                        Some(first) => synkit::SpanLike::with_range(first, start, end),
+                        None => <Span as synkit::SpanLike>::new(start, end),
+                    };
+                    Ok(Spanned { span, value })
+                }
+
+                /// Lex `input`, parse a `Self` borrowing from it, and require
+                /// that nothing but skip tokens remains.
+                fn parse_borrowed_str(input: &'src str) -> Result<Self, #error_ref> {
+                    use synkit::TokenStream as _;
+                    let mut stream = super::stream::BorrowedStream::lex(input)?;
+                    let value = Self::parse_borrowed(&mut stream)?;
+                    match stream.peek_token() {
+                        Some(tok) => Err(#error_ref::Expected {
+                            expect: "end of input",
+                            found: format!("{}", tok.value),
+                        }),
+                        None => Ok(value),
+                    }
+                }
             }
-    };
-    #[cfg(not(any(feature = "tokio", feature = "futures")))]
-    let async_traits = quote! {};
 
-    // Generate user-friendly local trait aliases
+            /// `Peek` over [`BorrowedToken`](super::tokens::BorrowedToken)s.
+            pub trait PeekBorrowed {
+                /// Whether `token` is a `Self`.
+                fn is_borrowed(token: &super::tokens::BorrowedToken<'_>) -> bool;
+
+                /// Whether the next non-skip token in `stream` is a `Self`.
+                fn peek_borrowed(stream: &super::stream::BorrowedStream<'_>) -> bool {
+                    use synkit::TokenStream as _;
+                    stream.peek_token().is_some_and(|tok| Self::is_borrowed(&tok.value))
+                }
+            }
+        }
+    });
+
     let traits_module = quote! {
         /// User-friendly traits using concrete types.
         ///
         /// These traits use concrete types (TokenStream, Token, Error) so users don't need
         /// to specify associated types when implementing them.
         pub mod traits {
+            #crate_alias
             use super::span::{Span, Spanned};
             use super::tokens::Token;
             use super::stream::TokenStream;
@@ -1067,28 +4631,57 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
             /// }
             /// ```
             pub trait Parse: Sized {
-                fn parse(stream: &mut TokenStream) -> Result<Self, super::#error_type>;
+                fn parse(stream: &mut TokenStream) -> Result<Self, #error_ref>;
 
                 /// Parse and wrap the result with span information.
                 ///
                 /// The span starts from the first non-skip token (not from whitespace).
-                fn parse_spanned(stream: &mut TokenStream) -> Result<Spanned<Self>, super::#error_type> {
+                ///
+                /// With a budget attached, parsing the same type at the same
+                /// cursor too often fails here with `synkit::Error::NoProgress`,
+                /// converted to the parser's error.
+                fn parse_spanned(stream: &mut TokenStream) -> Result<Spanned<Self>, #error_ref> {
                     use synkit::TokenStream as _;
                     // Get span of first non-skip token (peek_token skips whitespace)
-                    let start = stream.peek_token()
-                        .map(|t| synkit::SpanLike::start(&t.span))
-                        .unwrap_or(0);
+                    let first = stream.peek_token().map(|t| t.span.clone());
+                    let start = first.as_ref().map_or(0, synkit::SpanLike::start);
 
-                    let value = Self::parse(stream)?;
+                    let first_end = first.as_ref().map_or(start, synkit::SpanLike::end);
+                    let type_name = std::any::type_name::<Self>();
+                    stream.note_parse(type_name, start, first_end)?;
+
+                    let value = Self::parse(stream);
+                    let value = stream.within_budget(value)?;
 
                     let end = stream.last_span()
                         .map(|s| synkit::SpanLike::end(&s))
                         .unwrap_or(start);
 
-                    Ok(Spanned::new(start, end, value))
+                    let span = match &first {
+                        Some(first) => synkit::SpanLike::with_range(first, start, end),
+                        None => <Span as synkit::SpanLike>::new(start, end),
+                    };
+                    Ok(Spanned { span, value })
+                }
+
+                /// Lex `input`, parse a `Self`, and require that nothing but
+                /// skip tokens remains.
+                fn parse_str(input: &str) -> Result<Self, #error_ref> {
+                    use synkit::TokenStream as _;
+                    let mut stream = TokenStream::lex(input)?;
+                    let value = Self::parse(&mut stream)?;
+                    match stream.peek_token() {
+                        Some(tok) => Err(#error_ref::Expected {
+                            expect: "end of input",
+                            found: format!("{}", tok.value),
+                        }),
+                        None => Ok(value),
+                    }
                 }
             }
 
+            #borrowed_traits
+
             /// Simplified Peek trait using concrete Token type.
             ///
             /// Implement this trait to enable lookahead for your AST nodes:
@@ -1132,6 +4725,13 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
                     self.write(&mut printer);
                     synkit::Printer::into_string(printer)
                 }
+
+                /// Print to a string, recording output→source mappings.
+                fn to_string_with_source_map(&self) -> (String, synkit::SourceMapping) {
+                    let mut printer = Printer::new().with_source_map();
+                    self.write(&mut printer);
+                    printer.finish_with_source_map()
+                }
             }
 
             /// Simplified Diagnostic trait for error messages.
@@ -1142,7 +4742,7 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
 
             // Blanket impls for Option, Box, etc. using local traits
             impl<T: Parse + Peek> Parse for Option<T> {
-                fn parse(stream: &mut TokenStream) -> Result<Self, super::#error_type> {
+                fn parse(stream: &mut TokenStream) -> Result<Self, #error_ref> {
                     if T::peek(stream) {
                         Ok(Some(T::parse(stream)?))
                     } else {
@@ -1152,7 +4752,7 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
             }
 
             impl<T: Parse> Parse for Box<T> {
-                fn parse(stream: &mut TokenStream) -> Result<Self, super::#error_type> {
+                fn parse(stream: &mut TokenStream) -> Result<Self, #error_ref> {
                     Ok(Box::new(T::parse(stream)?))
                 }
             }
@@ -1191,6 +4791,62 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
                 }
             }
 
+            impl<T: ToTokens> ToTokens for Spanned<T> {
+                fn write(&self, p: &mut Printer) {
+                    if synkit::SpanLike::is_call_site(&self.span) {
+                        self.value.write(p);
+                    } else {
+                        synkit::Printer::mapped(p, &self.span, |p| self.value.write(p));
+                    }
+                }
+            }
+
+            /// Write `a, b, c` with a space after each separator. The last
+            /// separator follows `policy`; missing ones are `P::default()`.
+            fn write_punctuated<T: ToTokens, P: ToTokens + Default>(
+                items: &synkit::PunctuatedInner<T, P>,
+                policy: synkit::TrailingPolicy,
+                p: &mut Printer,
+            ) {
+                use synkit::Printer as _;
+                let len = items.len();
+                for (i, (value, sep)) in items.pairs().enumerate() {
+                    value.write(p);
+                    if i + 1 < len {
+                        sep.write(p);
+                        p.space();
+                        continue;
+                    }
+                    match (policy, sep) {
+                        (synkit::TrailingPolicy::Forbidden, _) => {}
+                        (synkit::TrailingPolicy::Required, None) => P::default().write(p),
+                        (_, sep) => sep.write(p),
+                    }
+                }
+            }
+
+            /// Honors the printer's `trailing_policy`.
+            impl<T: ToTokens, P: ToTokens + Default> ToTokens for synkit::Punctuated<T, P> {
+                fn write(&self, p: &mut Printer) {
+                    let policy = synkit::Printer::trailing_policy(p);
+                    write_punctuated(self.as_ref(), policy, p);
+                }
+            }
+
+            impl<T: ToTokens, P: ToTokens + Default> ToTokens for synkit::Terminated<T, P> {
+                fn write(&self, p: &mut Printer) {
+                    write_punctuated(self.as_ref(), Self::POLICY, p);
+                }
+            }
+
+            impl<T: ToTokens, P: ToTokens + Default> ToTokens for synkit::Separated<T, P> {
+                fn write(&self, p: &mut Printer) {
+                    write_punctuated(self.as_ref(), Self::POLICY, p);
+                }
+            }
+
+            #container_traits
+
             #async_traits
 
             // Implement local traits for generated token structs
@@ -1206,23 +4862,386 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
     #[cfg(not(any(feature = "tokio", feature = "futures")))]
     let async_exports = quote! {};
 
+    let testing_module = quote! {
+        /// Test helpers for `ToTokens`-based formatters and generated
+        /// corpora.
+        pub mod testing {
+            #crate_alias
+            use super::traits::{Parse, ToTokens};
+
+            /// Parse `input`, print it, then reparse and reprint the output;
+            /// `Err` if the two outputs differ. Returns the formatted output.
+            pub fn check_format_idempotent<T>(
+                input: &str,
+            ) -> Result<String, synkit::testing::IdempotenceError>
+            where
+                T: Parse + ToTokens,
+                // Higher-ranked so the bound is checked at the call site and
+                // error types without `Display` don't break the kit.
+                for<'e> &'e #error_ref: std::fmt::Display,
+            {
+                synkit::testing::check_format_idempotent(
+                    input,
+                    |source: &str| T::parse_str(source).map_err(|e| (&e).to_string()),
+                    ToTokens::to_string_with_source_map,
+                )
+            }
+
+            /// Like `check_format_idempotent`, but panics with a report of
+            /// the first divergent line and node.
+            #[track_caller]
+            pub fn assert_format_idempotent<T>(input: &str) -> String
+            where
+                T: Parse + ToTokens,
+                for<'e> &'e #error_ref: std::fmt::Display,
+            {
+                synkit::testing::assert_format_idempotent_with(
+                    input,
+                    |source: &str| T::parse_str(source).map_err(|e| (&e).to_string()),
+                    ToTokens::to_string_with_source_map,
+                )
+            }
+
+            /// Parse and print `input`, then check that the output lexes to
+            /// the same significant tokens as `input`. Returns the output.
+            pub fn check_relex<T>(input: &str) -> Result<String, synkit::testing::RelexError>
+            where
+                T: Parse + ToTokens,
+                for<'e> &'e #error_ref: std::fmt::Display,
+            {
+                synkit::testing::check_relex(
+                    input,
+                    significant_tokens,
+                    |source: &str| T::parse_str(source).map_err(|e| (&e).to_string()),
+                    ToTokens::to_string_with_source_map,
+                )
+            }
+
+            /// Like `check_relex`, but panics with a report of the first
+            /// mismatched token and the node that printed it.
+            #[track_caller]
+            pub fn assert_relex<T>(input: &str) -> String
+            where
+                T: Parse + ToTokens,
+                for<'e> &'e #error_ref: std::fmt::Display,
+            {
+                synkit::testing::assert_relex_with(
+                    input,
+                    significant_tokens,
+                    |source: &str| T::parse_str(source).map_err(|e| (&e).to_string()),
+                    ToTokens::to_string_with_source_map,
+                )
+            }
+
+            /// Lex each corpus entry and check that every token, printed on
+            /// its own with `ToTokens`, lexes back to itself. `Err` names the
+            /// first token that doesn't, such as a `#[fmt("newline")]`
+            /// placeholder printed instead of the newline.
+            pub fn lex_print_lex<'a>(
+                corpus: impl IntoIterator<Item = &'a str>,
+            ) -> Result<(), synkit::testing::LexPrintLexError>
+            where
+                for<'e> &'e #error_ref: std::fmt::Display,
+            {
+                synkit::testing::lex_print_lex(
+                    corpus,
+                    all_tokens,
+                    super::stream::TokenStream::kind_name,
+                    ToTokens::to_string_formatted,
+                )
+            }
+
+            /// Like `lex_print_lex`, but panics with a report of the first
+            /// token that doesn't round-trip.
+            #[track_caller]
+            pub fn assert_lex_print_lex<'a>(corpus: impl IntoIterator<Item = &'a str>)
+            where
+                for<'e> &'e #error_ref: std::fmt::Display,
+            {
+                synkit::testing::assert_lex_print_lex_with(
+                    corpus,
+                    all_tokens,
+                    super::stream::TokenStream::kind_name,
+                    ToTokens::to_string_formatted,
+                )
+            }
+
+            /// Shrink `input` along token boundaries to a smaller input for
+            /// which `fails` still returns true.
+            pub fn shrink(input: &str, fails: impl FnMut(&str) -> bool) -> String
+            where
+                for<'e> &'e #error_ref: std::fmt::Display,
+            {
+                synkit::testing::shrink(input, significant_tokens, fails)
+            }
+
+            /// Print a random `T` generated from `seed`, with the default
+            /// size and depth limits.
+            pub fn generate<T>(seed: u64) -> String
+            where
+                T: synkit::Generate + ToTokens,
+            {
+                T::generate(&mut synkit::Generator::new(seed)).to_string_formatted()
+            }
+
+            /// `count` generated programs, from seeds `seed..seed + count`.
+            pub fn corpus<T>(seed: u64, count: usize) -> Vec<String>
+            where
+                T: synkit::Generate + ToTokens,
+            {
+                (seed..).take(count).map(generate::<T>).collect()
+            }
+
+            fn all_tokens(
+                source: &str,
+            ) -> Result<Vec<(super::tokens::Token, std::ops::Range<usize>)>, String>
+            where
+                for<'e> &'e #error_ref: std::fmt::Display,
+            {
+                use synkit::TokenStream as _;
+                let stream = super::stream::TokenStream::lex(source).map_err(|e| (&e).to_string())?;
+                Ok(stream
+                    .all()
+                    .iter()
+                    .map(|tok| {
+                        let range = synkit::SpanLike::start(&tok.span)..synkit::SpanLike::end(&tok.span);
+                        (tok.value.clone(), range)
+                    })
+                    .collect())
+            }
+
+            fn significant_tokens(source: &str) -> Result<Vec<synkit::testing::LexedToken>, String>
+            where
+                for<'e> &'e #error_ref: std::fmt::Display,
+            {
+                use synkit::TokenStream as _;
+                let mut stream = super::stream::TokenStream::lex(source).map_err(|e| (&e).to_string())?;
+                let mut tokens = Vec::new();
+                while let Some(tok) = stream.next() {
+                    let range = synkit::SpanLike::start(&tok.span)..synkit::SpanLike::end(&tok.span);
+                    tokens.push((tok.value.to_string(), range));
+                }
+                Ok(tokens)
+            }
+        }
+    };
+
+    let raw_span_export = span_types
+        .is_none()
+        .then(|| quote! { pub use span::RawSpan; });
+
+    let borrowed_exports = borrowed.then(|| {
+        quote! {
+            pub use tokens::{BorrowedToken, SpannedBorrowedToken};
+            pub use stream::BorrowedStream;
+            pub use traits::{ParseBorrowed, PeekBorrowed};
+        }
+    });
+
     let reexports = quote! {
-        pub use span::{Span, RawSpan, Spanned};
+        pub use span::{Span, Spanned};
+        #raw_span_export
         pub use tokens::{Token, SpannedToken};
         pub use stream::{TokenStream, MutTokenStream};
         pub use printer::Printer;
         pub use traits::{Parse, Peek, ToTokens, Diagnostic};
+        #borrowed_exports
 
         #async_exports
     };
 
-    let delimiter_reexports: Vec<_> = delimiters.iter().map(|d| &d.name).collect();
-    let delimiter_reexport = if delimiter_reexports.is_empty() {
+    let delimiter_reexport: Vec<_> = delimiters
+        .iter()
+        .map(|d| {
+            let DelimiterDef {
+                cfg_attrs, name, ..
+            } = d;
+            quote! {
+                #(#cfg_attrs)*
+                pub use delimiters::#name;
+            }
+        })
+        .collect();
+
+    let macro_reexport: Vec<_> = delimiters
+        .iter()
+        .map(|d| {
+            let cfg_attrs = &d.cfg_attrs;
+            let macro_name = format_ident!("{}", d.name.to_string().to_lowercase());
+            quote! {
+                #(#cfg_attrs)*
+                pub(crate) use super::delimiters::#macro_name;
+            }
+        })
+        .collect();
+
+    let prelude_module = quote! {
+        #[allow(unused)]
+        pub mod prelude {
+            use super::*;
+            #reexports
+            #(#delimiter_reexport)*
+            #(#macro_reexport)*
+            pub(crate) use super::tokens::{quote_tokens, token_match};
+        }
+    };
+
+    let exports = match export {
+        Export::None => quote! {},
+        Export::Prelude => prelude_module,
+        Export::Full => quote! {
+            #prelude_module
+            pub use prelude::*;
+        },
+    };
+
+    // `extends`: conversions between the base kit's tokens and ours
+    let extends_impls = if let Some(BaseKit {
+        path: base_path,
+        tokens: base_tokens,
+        ..
+    }) = &base
+    {
+        let to_ext: Vec<_> = base_tokens
+            .iter()
+            .map(|t| {
+                let name = &t.name;
+                let cfg_attrs = &t.cfg_attrs;
+                if t.inner_type.is_some() {
+                    quote! { #(#cfg_attrs)* #base_path::tokens::Token::#name(v) => Self::#name(v) }
+                } else {
+                    quote! { #(#cfg_attrs)* #base_path::tokens::Token::#name => Self::#name }
+                }
+            })
+            .collect();
+        let to_base: Vec<_> = base_tokens
+            .iter()
+            .map(|t| {
+                let name = &t.name;
+                let cfg_attrs = &t.cfg_attrs;
+                if t.inner_type.is_some() {
+                    quote! { #(#cfg_attrs)* tokens::Token::#name(v) => Ok(Self::#name(v)) }
+                } else {
+                    quote! { #(#cfg_attrs)* tokens::Token::#name => Ok(Self::#name) }
+                }
+            })
+            .collect();
+        let struct_impls = base_tokens
+            .iter()
+            .map(|t| {
+                let struct_name = format_ident!("{}Token", t.name);
+                let body = if t.inner_type.is_some() {
+                    quote! { Self(token.0) }
+                } else {
+                    quote! { Self }
+                };
+                let items = quote! {
+                    impl From<#base_path::tokens::#struct_name> for tokens::#struct_name {
+                        fn from(token: #base_path::tokens::#struct_name) -> Self {
+                            #body
+                        }
+                    }
+                };
+                with_cfg(&t.cfg_attrs, items)
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        quote! {
+            impl From<#base_path::tokens::Token> for tokens::Token {
+                fn from(token: #base_path::tokens::Token) -> Self {
+                    match token {
+                        #(#to_ext,)*
+                    }
+                }
+            }
+
+            impl TryFrom<tokens::Token> for #base_path::tokens::Token {
+                /// Tokens added by the extension are returned unchanged.
+                type Error = tokens::Token;
+
+                #[allow(unreachable_patterns)]
+                fn try_from(token: tokens::Token) -> Result<Self, tokens::Token> {
+                    match token {
+                        #(#to_base,)*
+                        other => Err(other),
+                    }
+                }
+            }
+
+            #(#struct_impls)*
+        }
+    } else {
         quote! {}
+    };
+
+    // `export_as`: a macro other kits use to `extends` this one
+    let export_macro = if let Some(name) = &export_as {
+        // `self_path` as seen from the crate expanding the macro
+        let base_path = match &self_path {
+            Some(path) if path.segments.first().is_some_and(|s| s.ident == "crate") => {
+                let rest = path.segments.iter().skip(1);
+                quote! { $crate #(::#rest)* }
+            }
+            Some(path) => quote! { #path },
+            None => quote! { $crate },
+        };
+        let skip = &skip_tokens;
+        let string_type = string_type.as_ref().map(|ty| quote! { string_type: #ty, });
+        let delim_defs = delimiters.iter().map(|d| {
+            let DelimiterDef {
+                cfg_attrs,
+                name,
+                open,
+                close,
+            } = d;
+            quote! { #(#cfg_attrs)* #name => (#open, #close) }
+        });
+        quote! {
+            /// Definitions of this parser kit, for `parser_kit! { extends: ... }`.
+            #[macro_export]
+            macro_rules! #name {
+                (@__synkit_parser_kit { $($rest:tt)* }) => {
+                    synkit::parser_kit! {
+                        __extends: {
+                            path: #base_path,
+                            attrs: { #(#logos_attrs)* },
+                            skip_tokens: [#(#skip),*],
+                            delimiters: { #(#delim_defs),* },
+                            tokens: { #(#tokens),* },
+                            #string_type
+                        },
+                        $($rest)*
+                    }
+                };
+            }
+        }
     } else {
-        quote! { pub use delimiters::{#(#delimiter_reexports),*}; }
+        quote! {}
     };
 
+    let send_asserts = assertions.then(|| {
+        let delimiter_asserts = delimiters.iter().map(|d| {
+            let DelimiterDef {
+                cfg_attrs, name, ..
+            } = d;
+            quote! {
+                #(#cfg_attrs)*
+                assert_send::<delimiters::#name>();
+            }
+        });
+        quote! {
+            // Compile-time assertions that generated helper types can cross threads
+            const _: () = {
+                const fn assert_send<T: Send>() {}
+                assert_send::<span::Span>();
+                assert_send::<span::Spanned<tokens::Token>>();
+                assert_send::<stream::MutTokenStream>();
+                assert_send::<printer::Printer>();
+                #(#delimiter_asserts)*
+            };
+        }
+    });
+
     let output = quote! {
         #[allow(unused)]
         #span_module
@@ -1236,19 +5255,732 @@ pub fn expand(input: ParserKitInput) -> syn::Result<TokenStream> {
         #delimiters_module
         #[allow(unused)]
         #traits_module
-
         #[allow(unused)]
-        pub mod prelude {
-            use super::*;
-            #reexports
-        }
-        pub use prelude::*;
+        #testing_module
 
-        #delimiter_reexport
+        #send_asserts
 
-        // Delimiter extraction macros
-        #(#delimiter_macros)*
+        #exports
+
+        #(#from_str_impls)*
+
+        #extends_impls
+
+        #export_macro
     };
 
     Ok(output)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(src: &str) -> Result<(), Vec<String>> {
+        let input: ParserKitInput = syn::parse_str(src).map_err(|e| vec![e.to_string()])?;
+        input
+            .validate()
+            .map_err(|e| e.into_iter().map(|e| e.to_string()).collect())
+    }
+
+    #[test]
+    fn test_valid_input() {
+        let src = r#"
+            error: E,
+            skip_tokens: [Space],
+            tokens: {
+                #[token(" ", priority = 0)]
+                Space,
+                #[token("(")]
+                LParen,
+                #[token(")")]
+                RParen,
+                #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+                Ident(String),
+            },
+            delimiters: { Paren => (LParen, RParen) },
+        "#;
+        assert_eq!(validate(src), Ok(()));
+    }
+
+    #[cfg(feature = "expand_to_string")]
+    #[test]
+    fn test_expand_to_string() {
+        let input: ParserKitInput = syn::parse_str(
+            r#"
+            error: E,
+            tokens: { #[token("=")] Eq },
+            "#,
+        )
+        .unwrap();
+        let code = expand_to_string(input).unwrap();
+        assert!(code.contains("pub enum Token {"), "{code}");
+        assert!(code.contains("pub struct EqToken;"), "{code}");
+    }
+
+    #[test]
+    fn test_export_values() {
+        let parse = |value: &str| {
+            syn::parse_str::<ParserKitInput>(&format!("error: E, export: {value}, tokens: {{}}"))
+                .map(|input| input.export)
+                .map_err(|e| e.to_string())
+        };
+        assert!(parse("none") == Ok(Export::None));
+        assert!(parse("prelude") == Ok(Export::Prelude));
+        assert!(parse("full") == Ok(Export::Full));
+        assert_eq!(
+            parse("all").err().as_deref(),
+            Some("expected `none`, `prelude` or `full`")
+        );
+    }
+
+    #[test]
+    fn test_duplicate_token() {
+        let src = r#"
+            error: E,
+            tokens: {
+                #[token("a")] A,
+                #[token("b")] A,
+            },
+        "#;
+        let errors = validate(src).unwrap_err();
+        assert_eq!(errors[0], "duplicate token `A`");
+        assert_eq!(errors[1], "`A` first defined here");
+    }
+
+    #[test]
+    fn test_cfg_exclusive_tokens() {
+        let src = r#"
+            error: E,
+            tokens: {
+                #[cfg(feature = "a")]
+                #[token("x")]
+                X,
+                #[cfg(not(feature = "a"))]
+                #[token("x")]
+                X,
+            },
+        "#;
+        assert_eq!(validate(src), Ok(()));
+    }
+
+    #[test]
+    fn test_cfg_different_features_still_checked() {
+        // Both features may be enabled at once
+        let src = r#"
+            error: E,
+            tokens: {
+                #[cfg(feature = "a")]
+                #[token("x")]
+                X,
+                #[cfg(feature = "b")]
+                #[token("y")]
+                X,
+            },
+        "#;
+        assert_eq!(validate(src).unwrap_err()[0], "duplicate token `X`");
+
+        let src = r#"
+            error: E,
+            tokens: {
+                #[cfg(feature = "a")]
+                #[token("x")]
+                X,
+                #[cfg(feature = "b")]
+                #[token("x")]
+                Y,
+            },
+        "#;
+        assert_eq!(
+            validate(src).unwrap_err()[0],
+            "token pattern \"x\" conflicts with token `X`"
+        );
+    }
+
+    #[test]
+    fn test_cfg_on_other_section() {
+        let src = r#"
+            error: E,
+            #[cfg(feature = "a")]
+            skip_tokens: [],
+            tokens: {},
+        "#;
+        assert_eq!(
+            validate(src).unwrap_err(),
+            vec!["`#[cfg(...)]` is only supported on `tokens` and `delimiters` sections"]
+        );
+    }
+
+    #[test]
+    fn test_unknown_skip_token() {
+        let src = r#"
+            error: E,
+            skip_tokens: [Whitespace],
+            tokens: { #[token(" ")] Space },
+        "#;
+        assert_eq!(
+            validate(src).unwrap_err(),
+            vec!["skip token `Whitespace` is not defined in `tokens`"]
+        );
+    }
+
+    #[test]
+    fn test_delimiter_with_payload() {
+        let src = r#"
+            error: E,
+            tokens: {
+                #[regex(r"<[a-z]+>", |lex| lex.slice().to_string())]
+                Open(String),
+                #[token(")")]
+                Close,
+            },
+            delimiters: { Tag => (Open, Close), Bad => (Close, Missing) },
+        "#;
+        let errors = validate(src).unwrap_err();
+        assert_eq!(
+            errors[0],
+            "delimiter token `Open` carries a value; delimiters must be unit tokens"
+        );
+        assert!(
+            errors.contains(&"delimiter token `Missing` is not defined in `tokens`".to_string())
+        );
+    }
+
+    #[test]
+    fn test_conflicting_patterns() {
+        let src = r#"
+            error: E,
+            tokens: {
+                #[token("let")]
+                Let,
+                #[token("let")]
+                LetAgain,
+                #[regex(r"[a-z]+", priority = 1)]
+                Word,
+                #[regex(r"[a-z]+", priority = 2)]
+                Name,
+            },
+        "#;
+        let errors = validate(src).unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors[0],
+            "token pattern \"let\" conflicts with token `Let`"
+        );
+    }
+
+    const BASE: &str = r#"
+        __extends: {
+            path: crate::base,
+            attrs: {},
+            skip_tokens: [Space],
+            delimiters: { Paren => (LParen, RParen) },
+            tokens: {
+                #[token(" ", priority = 0)]
+                Space,
+                #[token("(")]
+                LParen,
+                #[token(")")]
+                RParen,
+                #[regex(r"[0-9]+", |lex| lex.slice().parse().ok())]
+                #[fmt("number")]
+                Number(i64),
+            },
+        },
+    "#;
+
+    #[test]
+    fn test_extends_merges_base() {
+        let src = format!(
+            "{BASE} error: E, skip_tokens: [Space, Tab], tokens: {{ #[token(\"\\t\")] Tab, #[token(\"let\")] KwLet }},"
+        );
+        let input: ParserKitInput = syn::parse_str(&src).unwrap();
+        let names: Vec<_> = input.tokens.iter().map(|t| t.name.to_string()).collect();
+        assert_eq!(
+            names,
+            ["Space", "LParen", "RParen", "Number", "Tab", "KwLet"]
+        );
+        let skip: Vec<_> = input.skip_tokens.iter().map(ToString::to_string).collect();
+        assert_eq!(skip, ["Space", "Tab"]);
+        assert_eq!(input.delimiters.len(), 1);
+        assert_eq!(validate(&src), Ok(()));
+
+        let output = expand(input).unwrap().to_string();
+        assert!(
+            output.contains("impl From < crate :: base :: tokens :: Token > for tokens :: Token")
+        );
+        assert!(
+            output.contains("crate :: base :: tokens :: Token :: Number (v) => Self :: Number (v)")
+        );
+        assert!(output.contains(
+            "impl From < crate :: base :: tokens :: LParenToken > for tokens :: LParenToken"
+        ));
+    }
+
+    #[test]
+    fn test_extends_reports_duplicates() {
+        let src = format!("{BASE} error: E, tokens: {{ #[token(\"(\")] LParen }},");
+        let errors = validate(&src).unwrap_err();
+        assert_eq!(errors[0], "duplicate token `LParen`");
+    }
+
+    #[test]
+    fn test_export_as_round_trips() {
+        let src = r#"
+            error: E,
+            export_as: base_kit,
+            skip_tokens: [Space],
+            tokens: {
+                #[token(" ", priority = 0)]
+                Space,
+                #[regex(r"[a-z]+", |lex| lex.slice().to_string())]
+                #[fmt("identifier")]
+                #[derive(Eq)]
+                Ident(String),
+            },
+        "#;
+        let input: ParserKitInput = syn::parse_str(src).unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains("macro_rules ! base_kit"));
+        assert!(output.contains("path : $ crate"));
+        assert!(output.contains("# [fmt (\"identifier\")] # [derive (Eq)] Ident (String)"));
+    }
+
+    #[test]
+    fn test_self_path() {
+        let src = r#"
+            error: crate::errors::E,
+            self_path: crate::grammar,
+            export_as: base_kit,
+            tokens: { #[regex(r"[a-z]+")] Ident },
+        "#;
+        let input: ParserKitInput = syn::parse_str(src).unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains("path : $ crate :: grammar ,"));
+        assert!(output.contains("[ident] => { crate :: grammar :: tokens :: IdentToken }"));
+        assert!(output.contains("Result < Self , crate :: errors :: E >"));
+
+        let relative = syn::parse_str::<ParserKitInput>("error: E, self_path: grammar, tokens: {}");
+        assert_eq!(
+            relative.err().map(|e| e.to_string()).as_deref(),
+            Some("`self_path` must be absolute, e.g. `crate::grammar`")
+        );
+    }
+
+    #[test]
+    fn test_span_types() {
+        let src = r#"
+            error: E,
+            span_types: { spanned: my::Node, span: my::CodeSpan },
+            tokens: {},
+        "#;
+        let input: ParserKitInput = syn::parse_str(src).unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains("pub use super :: my :: CodeSpan as Span ;"));
+        assert!(output.contains("pub use super :: my :: Node as Spanned ;"));
+        assert!(!output.contains("RawSpan"));
+
+        let missing = syn::parse_str::<ParserKitInput>("error: E, span_types: { span: S }");
+        assert_eq!(
+            missing.err().map(|e| e.to_string()).as_deref(),
+            Some("`span_types` needs both `span` and `spanned`")
+        );
+    }
+
+    #[test]
+    fn test_file_ids() {
+        let input: ParserKitInput = syn::parse_str("error: E, file_ids: true, tokens: {}").unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains("pub file : Option < synkit :: FileId > ,"));
+        assert!(output.contains("pub fn lex_with_file"));
+
+        let input: ParserKitInput =
+            syn::parse_str("error: E, file_ids: false, tokens: {}").unwrap();
+        assert!(!expand(input).unwrap().to_string().contains("FileId"));
+
+        let conflict = syn::parse_str::<ParserKitInput>(
+            "error: E, file_ids: true, span_types: { span: S, spanned: N }",
+        );
+        assert!(conflict.is_err());
+    }
+
+    #[test]
+    fn test_provenance() {
+        let input: ParserKitInput =
+            syn::parse_str("error: E, provenance: true, tokens: {}").unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains("pub provenance : synkit :: Provenance ,"));
+        assert!(output.contains("pub fn generated_by"));
+
+        let input: ParserKitInput = syn::parse_str("error: E, tokens: {}").unwrap();
+        assert!(
+            !expand(input)
+                .unwrap()
+                .to_string()
+                .contains("pub provenance")
+        );
+
+        let conflict = syn::parse_str::<ParserKitInput>(
+            "error: E, provenance: true, span_types: { span: S, spanned: N }",
+        );
+        assert!(conflict.is_err());
+    }
+
+    #[test]
+    fn test_packed_spans() {
+        let input: ParserKitInput = syn::parse_str(
+            "error: E, packed_spans: true, custom_derives: [serde::Serialize, Default], tokens: {}",
+        )
+        .unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains("impl synkit :: serde :: Serialize for Span"));
+        assert!(
+            output.contains("# [derive (Default)] pub enum Span"),
+            "{output}"
+        );
+        assert!(output.contains(
+            "# [derive (synkit :: serde :: Serialize , synkit :: serde :: Deserialize)] # [serde (crate = \"synkit::serde\")] # [repr (C)] pub struct Spanned"
+        ));
+        // `RawSpan` keeps the requested derives
+        assert!(
+            output.contains(
+                "# [derive (serde :: Serialize , Default)] # [repr (C)] pub struct RawSpan"
+            )
+        );
+
+        let input: ParserKitInput = syn::parse_str("error: E, tokens: {}").unwrap();
+        assert!(!expand(input).unwrap().to_string().contains("PackedSpan"));
+
+        let conflict = syn::parse_str::<ParserKitInput>(
+            "error: E, packed_spans: true, span_types: { span: S, spanned: N }",
+        );
+        assert!(conflict.is_err());
+    }
+
+    #[test]
+    fn test_skip_mask() {
+        let names: Vec<_> = (0..70)
+            .map(|i| format!("#[token(\"t{i}\")] T{i},"))
+            .collect();
+        let src = format!(
+            "error: E, skip_tokens: [T1, T66], tokens: {{ {} }}",
+            names.join(" ")
+        );
+        let input: ParserKitInput = syn::parse_str(&src).unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains("pub const KIND_COUNT : usize = 70usize"));
+        assert!(output.contains("Token :: T66 => 66usize"));
+        assert!(output.contains("const SKIP_MASK : [u64 ; 2usize] = [2u64 , 4u64]"));
+
+        let input: ParserKitInput =
+            syn::parse_str("error: E, tokens: { #[token(\"a\")] A }").unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(!output.contains("SKIP_MASK"));
+    }
+
+    #[test]
+    fn test_token_constructors() {
+        let input: ParserKitInput = syn::parse_str(
+            r#"error: E, tokens: {
+                #[token("let")] KwLet,
+                #[token("fn")] Fn,
+                #[token("self")] SelfValue,
+                #[token("spanned")] Spanned,
+                #[regex("[a-z]+", |lex| lex.slice().to_string())] Ident(String),
+            }"#,
+        )
+        .unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains("pub fn kw_let () -> Self { Token :: KwLet }"));
+        assert!(output.contains("pub fn r#fn () -> Self"));
+        assert!(output.contains("pub fn self_value () -> Self"));
+        assert!(output.contains(
+            "pub fn ident (value : impl Into < String >) -> Self { Token :: Ident (value . into ()) }"
+        ));
+        // `Token::spanned` is the builder method, not a constructor
+        assert_eq!(output.matches("pub fn spanned").count(), 6);
+        assert!(output.contains("pub fn synthetic (token : Token) -> Self"));
+
+        let input: ParserKitInput =
+            syn::parse_str("error: E, span_types: { span: S, spanned: N }, tokens: {}").unwrap();
+        assert!(!expand(input).unwrap().to_string().contains("fn synthetic"));
+    }
+
+    #[test]
+    fn test_container_impls() {
+        let input: ParserKitInput =
+            syn::parse_str("error: E, container_impls: true, tokens: {}").unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains("impl < T : Parse + Peek > Parse for Vec < T >"));
+        assert!(output.contains("impl < T : Peek > Peek for Option < T >"));
+
+        let input: ParserKitInput = syn::parse_str("error: E, tokens: {}").unwrap();
+        assert!(!expand(input).unwrap().to_string().contains("Parse for Vec"));
+    }
+
+    #[test]
+    fn test_borrowed() {
+        let input: ParserKitInput = syn::parse_str(
+            r#"error: E, borrowed: true, tokens: {
+                #[regex(r"[a-z]+", crate::ident)]
+                Ident(String),
+            }"#,
+        )
+        .unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains("pub enum BorrowedToken < 'src >"));
+        assert!(
+            output.contains("# [regex (r\"[a-z]+\" , | lex | lex . slice ())] Ident (& 'src str)")
+        );
+        assert!(output.contains("pub struct BorrowedStream < 'src >"));
+        assert!(
+            output
+                .contains("impl < 'src > ParseBorrowed < 'src > for super :: tokens :: IdentToken")
+        );
+
+        let input: ParserKitInput = syn::parse_str("error: E, tokens: {}").unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(!output.contains("BorrowedToken"));
+        assert!(!output.contains("BorrowedStream"));
+    }
+
+    #[test]
+    fn test_delimiter_sugar() {
+        let src =
+            "error: E, delimiter_sugar: true, tokens: { L, R }, delimiters: { Paren => (L, R) }";
+        let input: ParserKitInput = syn::parse_str(src).unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains("pub enum MaybeParen < T >"));
+        assert!(output.contains("pub struct ParenList < T , Sep >"));
+
+        let input: ParserKitInput =
+            syn::parse_str("error: E, tokens: { L, R }, delimiters: { Paren => (L, R) }").unwrap();
+        assert!(!expand(input).unwrap().to_string().contains("MaybeParen"));
+    }
+
+    #[test]
+    fn test_token_conversions() {
+        let src = "error: E, token_conversions: true, tokens: { Eq, Ident(String) }";
+        let input: ParserKitInput = syn::parse_str(src).unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains("impl From < String > for IdentToken"));
+        assert!(output.contains("impl From < EqToken > for Token"));
+        assert!(output.contains("impl TryFrom < Token > for IdentToken"));
+
+        let input: ParserKitInput =
+            syn::parse_str("error: E, tokens: { Eq, Ident(String) }").unwrap();
+        assert!(!expand(input).unwrap().to_string().contains("TryFrom"));
+    }
+
+    #[test]
+    fn test_token_tests() {
+        let src = r#"error: E, token_tests: true, tokens: {
+            #[token("=")] Eq,
+            #[regex("[a-z]+")] Ident,
+            Opaque,
+        }"#;
+        let input: ParserKitInput = syn::parse_str(src).unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains("mod token_tests"));
+        assert!(output.contains("fn lexes_eq ()"));
+        assert!(output.contains("fn lexes_ident ()"));
+        // No pattern to sample
+        assert!(!output.contains("fn lexes_opaque"));
+
+        let input: ParserKitInput = syn::parse_str("error: E, tokens: { Eq }").unwrap();
+        assert!(!expand(input).unwrap().to_string().contains("token_tests"));
+    }
+
+    #[test]
+    fn test_bounded_tokens() {
+        let src = r#"error: E, token_tests: true, tokens: {
+            #[token("/*", until = "*/")] Block,
+            #[regex("//", |lex| lex.slice().to_string(), until = "\n", longest)] Line(String),
+        }"#;
+        let input: ParserKitInput = syn::parse_str(src).unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains("synkit :: scan :: until (lex . remainder () , \"*/\" , false)"));
+        assert!(output.contains("synkit :: scan :: until (rest , \"\\n\" , true)"));
+        assert!(!output.contains("until ="));
+        // Samples run through the terminator
+        assert!(output.contains("\"/**/\""));
+
+        let input: ParserKitInput =
+            syn::parse_str(r#"error: E, tokens: { #[regex("a", longest)] A }"#).unwrap();
+        let err = expand(input).unwrap_err().to_string();
+        assert!(err.contains("`longest` needs `until"));
+    }
+
+    #[test]
+    fn test_block_comment() {
+        let src = r#"error: E, token_tests: true, tokens: {
+            #[block_comment(open = "/*", close = "*/", nested)] Nested,
+            #[block_comment(open = "(*", close = "*)")] Flat,
+        }"#;
+        let input: ParserKitInput = syn::parse_str(src).unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains(
+            "# [token (\"/*\" , | lex | { match synkit :: scan :: nested (lex . remainder () , \"/*\" , \"*/\")"
+        ));
+        assert!(output.contains("synkit :: scan :: until (lex . remainder () , \"*)\" , false)"));
+        assert!(!output.contains("block_comment"));
+
+        // Rejected while parsing the token
+        for (src, message) in [
+            (r#"#[block_comment(open = "/*")] C"#, "needs `open"),
+            (
+                r#"#[block_comment(open = "/*", close = "*/", deep)] C"#,
+                "expected `open`",
+            ),
+        ] {
+            let src = format!("error: E, tokens: {{ {src} }}");
+            let err = syn::parse_str::<ParserKitInput>(&src).err().unwrap();
+            assert!(err.to_string().contains(message), "{src}: {err}");
+        }
+        // Rejected while expanding
+        for (src, message) in [
+            (r#"#[token("/*", nested)] C"#, "`nested` needs `until"),
+            (
+                r#"#[regex("/[*]", until = "*/", nested)] C"#,
+                "needs a `#[token]` opener",
+            ),
+            (
+                r#"#[token("/*", until = "*/", nested, longest)] C"#,
+                "can't be combined",
+            ),
+        ] {
+            let src = format!("error: E, tokens: {{ {src} }}");
+            let input: ParserKitInput = syn::parse_str(&src).unwrap();
+            let err = expand(input).unwrap_err().to_string();
+            assert!(err.contains(message), "{src}: {err}");
+        }
+    }
+
+    #[test]
+    fn test_assertions_flag() {
+        let input: ParserKitInput = syn::parse_str("error: E, tokens: {}").unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains("assert_send :: < printer :: Printer > ()"));
+        assert!(output.contains("const _STREAM_SIZE"));
+
+        let input: ParserKitInput =
+            syn::parse_str("error: E, assertions: false, tokens: {}").unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(!output.contains("assert_send"));
+        assert!(!output.contains("size_of"));
+    }
+
+    #[test]
+    fn test_bytes_per_token() {
+        let input: ParserKitInput = syn::parse_str("error: E, tokens: {}").unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains("pub const BYTES_PER_TOKEN : usize = 4usize"));
+        assert!(output.contains("Vec :: with_capacity (Self :: token_capacity (& source))"));
+
+        let input: ParserKitInput =
+            syn::parse_str("error: E, bytes_per_token: 6, tokens: {}").unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains("pub const BYTES_PER_TOKEN : usize = 6usize"));
+
+        let err = syn::parse_str::<ParserKitInput>("error: E, bytes_per_token: 0, tokens: {}")
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("at least 1"));
+    }
+
+    #[test]
+    fn test_token_match() {
+        let input: ParserKitInput = syn::parse_str(
+            r#"error: E, tokens: {
+                #[token("(")] LParen,
+                #[regex("[a-z]+", |lex| lex.slice().to_string())] #[fmt("identifier")] Ident(String),
+                #[regex("[0-9]+")] Number,
+            }"#,
+        )
+        .unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains("macro_rules ! token_match"));
+        assert!(output.contains(r#"(@ expect LParen) => { "`(`" } ;"#));
+        assert!(output.contains(r#"(@ expect Ident) => { "identifier" } ;"#));
+        assert!(output.contains(r#"(@ expect Number) => { "number" } ;"#));
+        assert!(
+            output.contains("pub (crate) use super :: tokens :: { quote_tokens , token_match } ;")
+        );
+    }
+
+    #[test]
+    fn test_peek_cache() {
+        let input: ParserKitInput = syn::parse_str("error: E, tokens: {}").unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains("peeked : core :: sync :: atomic :: AtomicUsize ,"));
+        assert!(output.contains("self . peeked . store (index"));
+
+        let input: ParserKitInput =
+            syn::parse_str("error: E, peek_cache: false, tokens: {}").unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(!output.contains("peeked"));
+        assert!(output.contains("fn next_significant (& self) -> usize"));
+    }
+
+    #[test]
+    fn test_normalize_newlines() {
+        let input: ParserKitInput = syn::parse_str("error: E, tokens: {}").unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(!output.contains("newline_map"));
+
+        let input: ParserKitInput =
+            syn::parse_str("error: E, normalize_newlines: true, tokens: {}").unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains("newline_map : Arc < synkit :: NewlineMap > ,"));
+        assert!(output.contains("synkit :: NewlineMap :: normalize (source)"));
+        assert!(output.contains("pub fn original_range (& self , span : & Span)"));
+    }
+
+    #[test]
+    fn test_spanned_derives() {
+        let input: ParserKitInput = syn::parse_str("error: E, tokens: {}").unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains(
+            "# [derive (Clone , PartialEq , Eq , Hash)] # [repr (C)] pub struct Spanned"
+        ));
+
+        let input: ParserKitInput = syn::parse_str(
+            "error: E, span_derives: [Debug, Clone, Copy, PartialEq], custom_derives: [Hash], tokens: {}",
+        )
+        .unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains(
+            "# [derive (Clone , PartialEq)] # [derive (Hash)] # [repr (C)] pub struct Spanned"
+        ));
+    }
+
+    #[test]
+    fn test_line_index() {
+        let input: ParserKitInput =
+            syn::parse_str("error: E, line_index: true, tokens: {}").unwrap();
+        let output = expand(input).unwrap().to_string();
+        assert!(output.contains("line_index : Arc < synkit :: LineIndex > ,"));
+        assert!(
+            output.contains("pub fn column (& self , stream : & super :: stream :: TokenStream)")
+        );
+
+        let input: ParserKitInput = syn::parse_str("error: E, tokens: {}").unwrap();
+        assert!(!expand(input).unwrap().to_string().contains("LineIndex"));
+    }
+
+    #[test]
+    fn test_extends_rewrites_to_base_macro() {
+        let src: TokenStream =
+            "error: E, extends: other::base_kit, include_tokens: [shared], tokens: {}"
+                .parse()
+                .unwrap();
+        let output = crate::token_set::expand_includes(src)
+            .unwrap()
+            .unwrap()
+            .to_string();
+        assert_eq!(
+            output,
+            "other :: base_kit ! { @ __synkit_parser_kit { include_tokens : [shared] , error : E , tokens : { } } }"
+        );
+    }
+}
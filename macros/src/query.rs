@@ -0,0 +1,38 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::LitStr;
+
+/// `Query`'s selector grammar, shared with `synkit-core` so both agree on
+/// what is valid. `query_syntax.rs` is a copy of `core/src/query/syntax.rs`
+/// so the published crate carries its own; a test keeps the two identical.
+/// Only the parser is used here.
+#[allow(dead_code)]
+#[path = "query_syntax.rs"]
+mod syntax;
+
+pub fn expand(input: LitStr) -> syn::Result<TokenStream> {
+    let source = input.value();
+    if let Err(err) = syntax::parse(&source) {
+        return Err(syn::Error::new(input.span(), err));
+    }
+    Ok(quote! {{
+        static QUERY: std::sync::LazyLock<synkit::Query> = std::sync::LazyLock::new(|| {
+            match synkit::Query::parse(#input) {
+                Ok(query) => query,
+                Err(err) => unreachable!("checked by `query!`: {}", err),
+            }
+        });
+        &*QUERY
+    }})
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_syntax_matches_core() {
+        assert!(
+            include_str!("query_syntax.rs") == include_str!("../../core/src/query/syntax.rs"),
+            "macros/src/query_syntax.rs is out of date; copy core/src/query/syntax.rs over it"
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{Data, DeriveInput, Fields, parse_quote};
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let kind = name.unraw().to_string();
+
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(synkit::QueryNode));
+        param.bounds.push(parse_quote!('static));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // `Any` needs `'static`, so borrowing nodes can't be downcast
+    let as_any = if input.generics.lifetimes().next().is_none() {
+        quote! {
+            #[inline]
+            fn as_any(&self) -> Option<&dyn std::any::Any> {
+                Some(self)
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let (pattern, children, fields) = fields_query(&data.fields);
+            let text = forward_single(&data.fields, quote! { text });
+            quote! {
+                #[inline]
+                fn kind(&self) -> Option<&'static str> {
+                    Some(#kind)
+                }
+
+                fn text(&self) -> Option<std::borrow::Cow<'_, str>> {
+                    let Self #pattern = self;
+                    #text
+                }
+
+                fn field(&self, name: &str) -> Option<&dyn synkit::QueryNode> {
+                    let Self #pattern = self;
+                    match name {
+                        #fields
+                        _ => None,
+                    }
+                }
+
+                fn children<'a>(&'a self, visit: &mut dyn FnMut(&'a dyn synkit::QueryNode)) {
+                    let Self #pattern = self;
+                    #children
+                }
+
+                #as_any
+            }
+        }
+        Data::Enum(data) if data.variants.is_empty() => quote! {
+            fn children<'a>(&'a self, visit: &mut dyn FnMut(&'a dyn synkit::QueryNode)) {
+                match *self {}
+            }
+        },
+        Data::Enum(data) => {
+            let mut text_arms = Vec::new();
+            let mut children_arms = Vec::new();
+            for variant in &data.variants {
+                let vname = &variant.ident;
+                let (pattern, children, _) = fields_query(&variant.fields);
+                let text = forward_single(&variant.fields, quote! { text });
+                text_arms.push(quote! { Self::#vname #pattern => #text, });
+                children_arms.push(quote! { Self::#vname #pattern => { #children } });
+            }
+            quote! {
+                fn text(&self) -> Option<std::borrow::Cow<'_, str>> {
+                    match self {
+                        #(#text_arms)*
+                    }
+                }
+
+                fn children<'a>(&'a self, visit: &mut dyn FnMut(&'a dyn synkit::QueryNode)) {
+                    match self {
+                        #(#children_arms)*
+                    }
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "QueryNode cannot be derived for unions",
+            ));
+        }
+    };
+
+    Ok(quote! {
+        #[allow(unused_variables)]
+        impl #impl_generics synkit::QueryNode for #name #ty_generics #where_clause {
+            #body
+        }
+    })
+}
+
+/// The destructuring pattern for `fields`, the statements visiting each,
+/// and the `field()` match arms naming them (by index for tuple fields).
+fn fields_query(fields: &Fields) -> (TokenStream, TokenStream, TokenStream) {
+    let bindings: Vec<_> = (0..fields.len())
+        .map(|i| format_ident!("field_{}", i))
+        .collect();
+    let children = quote! { #(visit(#bindings);)* };
+    match fields {
+        Fields::Named(named) => {
+            let names: Vec<_> = named.named.iter().map(|f| &f.ident).collect();
+            let keys = names
+                .iter()
+                .filter_map(|name| name.as_ref())
+                .map(|name| name.unraw().to_string());
+            (
+                quote! { { #(#names: #bindings),* } },
+                children,
+                quote! { #(#keys => Some(#bindings),)* },
+            )
+        }
+        Fields::Unnamed(_) => {
+            let keys = (0..bindings.len()).map(|i| i.to_string());
+            (
+                quote! { ( #(#bindings),* ) },
+                children,
+                quote! { #(#keys => Some(#bindings),)* },
+            )
+        }
+        Fields::Unit => (quote! {}, quote! {}, quote! {}),
+    }
+}
+
+/// `method` on the only field of a newtype, otherwise `None`.
+fn forward_single(fields: &Fields, method: TokenStream) -> TokenStream {
+    if fields.len() == 1 {
+        quote! { synkit::QueryNode::#method(field_0) }
+    } else {
+        quote! { None }
+    }
+}
@@ -0,0 +1,198 @@
+//! The selector grammar of `Query`, free of the rest of the crate so that
+//! `synkit-macros` can carry a copy of this file to check `query!` selectors
+//! at expansion time without depending on `synkit-core`. Keep it std-only,
+//! and copy it to `macros/src/query_syntax.rs` after editing.
+
+use std::fmt;
+
+/// Parse a selector into its steps.
+pub(crate) fn parse(source: &str) -> Result<Vec<Step>, QueryError> {
+    QueryParser {
+        source,
+        chars: source.char_indices().peekable(),
+    }
+    .parse()
+}
+
+/// How a step relates to the one before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Combinator {
+    /// `A B`: anywhere below.
+    Descendant,
+    /// `A > B`: the nearest node below, through transparent wrappers.
+    Child,
+}
+
+/// `[field='value']`, `[field!='value']` or `[.='value']`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Predicate {
+    /// `None` for `.`, the node's own text.
+    pub(crate) field: Option<String>,
+    pub(crate) negated: bool,
+    pub(crate) value: String,
+}
+
+/// One selector step: a node kind and its predicates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Step {
+    pub(crate) combinator: Combinator,
+    /// `None` for `*`.
+    pub(crate) kind: Option<String>,
+    pub(crate) predicates: Vec<Predicate>,
+}
+
+/// A malformed `Query` selector.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    /// Byte offset in the selector.
+    pub offset: usize,
+    /// What was expected there.
+    pub message: String,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid query at {}: {}", self.offset, self.message)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+struct QueryParser<'s> {
+    source: &'s str,
+    chars: std::iter::Peekable<std::str::CharIndices<'s>>,
+}
+
+impl QueryParser<'_> {
+    fn parse(mut self) -> Result<Vec<Step>, QueryError> {
+        let mut steps = Vec::new();
+        self.skip_whitespace();
+        steps.push(self.step(Combinator::Descendant)?);
+        loop {
+            let spaced = self.skip_whitespace();
+            let combinator = match self.peek() {
+                None => break,
+                Some('>') => {
+                    self.chars.next();
+                    self.skip_whitespace();
+                    Combinator::Child
+                }
+                Some(_) if spaced => Combinator::Descendant,
+                Some(c) => return Err(self.error(format!("expected `>` or a space, found `{c}`"))),
+            };
+            steps.push(self.step(combinator)?);
+        }
+        Ok(steps)
+    }
+
+    fn step(&mut self, combinator: Combinator) -> Result<Step, QueryError> {
+        let kind = if self.eat('*') {
+            None
+        } else {
+            Some(self.name("a node kind or `*`")?)
+        };
+        let mut predicates = Vec::new();
+        while self.eat('[') {
+            predicates.push(self.predicate()?);
+        }
+        Ok(Step {
+            combinator,
+            kind,
+            predicates,
+        })
+    }
+
+    fn predicate(&mut self) -> Result<Predicate, QueryError> {
+        self.skip_whitespace();
+        let field = if self.eat('.') {
+            None
+        } else {
+            Some(self.name("a field name or `.`")?)
+        };
+        self.skip_whitespace();
+        let negated = self.eat('!');
+        if !self.eat('=') {
+            return Err(self.error(if negated {
+                "expected `=`"
+            } else {
+                "expected `=` or `!=`"
+            }));
+        }
+        self.skip_whitespace();
+        let value = self.string()?;
+        self.skip_whitespace();
+        if !self.eat(']') {
+            return Err(self.error("expected `]`"));
+        }
+        Ok(Predicate {
+            field,
+            negated,
+            value,
+        })
+    }
+
+    /// An identifier or tuple index.
+    fn name(&mut self, expected: &str) -> Result<String, QueryError> {
+        let mut name = String::new();
+        while let Some(c) = self.peek().filter(|&c| c == '_' || c.is_alphanumeric()) {
+            name.push(c);
+            self.chars.next();
+        }
+        if name.is_empty() {
+            return Err(self.error(format!("expected {expected}")));
+        }
+        Ok(name)
+    }
+
+    fn string(&mut self) -> Result<String, QueryError> {
+        let Some(quote) = self.peek().filter(|&c| c == '\'' || c == '"') else {
+            return Err(self.error("expected a quoted string"));
+        };
+        self.chars.next();
+        let mut value = String::new();
+        while let Some((_, c)) = self.chars.next() {
+            match c {
+                '\\' => match self.chars.next() {
+                    Some((_, escaped)) => value.push(escaped),
+                    None => break,
+                },
+                c if c == quote => return Ok(value),
+                c => value.push(c),
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|&(_, c)| c)
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        let matched = self.peek() == Some(expected);
+        if matched {
+            self.chars.next();
+        }
+        matched
+    }
+
+    /// Skip whitespace, returning whether there was any.
+    fn skip_whitespace(&mut self) -> bool {
+        let mut skipped = false;
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.chars.next();
+            skipped = true;
+        }
+        skipped
+    }
+
+    fn error(&mut self, message: impl Into<String>) -> QueryError {
+        let offset = self
+            .chars
+            .peek()
+            .map_or(self.source.len(), |&(offset, _)| offset);
+        QueryError {
+            offset,
+            message: message.into(),
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use proc_macro2::{Delimiter, Spacing, TokenStream, TokenTree};
+use quote::quote;
+use syn::{
+    Token,
+    parse::{Parse, ParseStream},
+};
+
+/// Input to `__quote_tokens!`: `$crate; <tokens>`.
+///
+/// The crate path is captured as raw tokens since `$crate` is not a valid
+/// `syn::Path` segment.
+pub struct QuoteTokensInput {
+    pub krate: TokenStream,
+    pub body: TokenStream,
+}
+
+impl Parse for QuoteTokensInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut krate = TokenStream::new();
+        while !input.peek(Token![;]) {
+            let tt: TokenTree = input.parse()?;
+            krate.extend(std::iter::once(tt));
+        }
+        input.parse::<Token![;]>()?;
+        Ok(Self {
+            krate,
+            body: input.parse()?,
+        })
+    }
+}
+
+pub fn expand(input: QuoteTokensInput) -> syn::Result<TokenStream> {
+    let QuoteTokensInput { krate, body } = input;
+
+    let mut pieces = Vec::new();
+    collect_pieces(body, &krate, &mut pieces);
+    let len = pieces.len();
+
+    Ok(quote! {
+        {
+            let __pieces: [::std::borrow::Cow<'static, str>; #len] = [#(#pieces),*];
+            #krate::stream::MutTokenStream::from_pieces(__pieces)
+        }
+    })
+}
+
+/// Flatten token trees into lexable pieces, expanding `#var` interpolations.
+///
+/// Punctuation marked `Spacing::Joint` is kept in one piece with the
+/// punctuation after it, so `==` or `->` lex as multi-char tokens.
+fn collect_pieces(body: TokenStream, krate: &TokenStream, out: &mut Vec<TokenStream>) {
+    let mut joint = String::new();
+    let mut iter = body.into_iter().peekable();
+    while let Some(tt) = iter.next() {
+        match tt {
+            TokenTree::Punct(p)
+                if p.as_char() == '#' && matches!(iter.peek(), Some(TokenTree::Ident(_))) =>
+            {
+                flush(&mut joint, out);
+                if let Some(TokenTree::Ident(var)) = iter.next() {
+                    out.push(quote! {
+                        ::std::borrow::Cow::Owned(
+                            #krate::traits::ToTokens::to_string_formatted(&#var)
+                        )
+                    });
+                }
+            }
+            TokenTree::Punct(p) => {
+                joint.push(p.as_char());
+                if p.spacing() == Spacing::Alone {
+                    flush(&mut joint, out);
+                }
+            }
+            TokenTree::Group(group) => {
+                flush(&mut joint, out);
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::Brace => ("{", "}"),
+                    Delimiter::None => ("", ""),
+                };
+                if !open.is_empty() {
+                    out.push(borrowed(open));
+                }
+                collect_pieces(group.stream(), krate, out);
+                if !close.is_empty() {
+                    out.push(borrowed(close));
+                }
+            }
+            other => {
+                flush(&mut joint, out);
+                out.push(borrowed(&other.to_string()));
+            }
+        }
+    }
+    flush(&mut joint, out);
+}
+
+/// Push the accumulated joint punctuation, if any, as one piece.
+fn flush(joint: &mut String, out: &mut Vec<TokenStream>) {
+    if !joint.is_empty() {
+        out.push(borrowed(&std::mem::take(joint)));
+    }
+}
+
+fn borrowed(s: &str) -> TokenStream {
+    quote! { ::std::borrow::Cow::Borrowed(#s) }
+}
@@ -0,0 +1,160 @@
+//! Example strings matching a `#[regex]` pattern, for `Generate` impls on
+//! token structs.
+
+use regex_syntax::hir::{Class, Hir, HirKind};
+
+/// Extra repetitions beyond a repetition's minimum.
+const MAX_EXTRA_REPEATS: u32 = 3;
+
+/// Up to `count` distinct strings matching `pattern`, deterministic for a
+/// given pattern. Empty if the pattern doesn't parse (e.g. it uses logos
+/// subpatterns) or can't be sampled as UTF-8.
+pub fn samples(pattern: &str, count: usize) -> Vec<String> {
+    let Ok(hir) = regex_syntax::parse(pattern) else {
+        return Vec::new();
+    };
+    let mut out: Vec<String> = Vec::new();
+    for seed in 0..count as u64 * 4 {
+        if out.len() == count {
+            break;
+        }
+        let mut sampler = Sampler { state: seed };
+        let mut sample = String::new();
+        if sampler.write(&hir, &mut sample) && !sample.is_empty() && !out.contains(&sample) {
+            out.push(sample);
+        }
+    }
+    out
+}
+
+struct Sampler {
+    state: u64,
+}
+
+impl Sampler {
+    /// A value in `0..n` (SplitMix64).
+    fn below(&mut self, n: u64) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        (z ^ (z >> 31)) % n.max(1)
+    }
+
+    /// Append a string matching `hir`; `false` if there is none to pick.
+    fn write(&mut self, hir: &Hir, out: &mut String) -> bool {
+        match hir.kind() {
+            HirKind::Empty | HirKind::Look(_) => true,
+            HirKind::Literal(lit) => match std::str::from_utf8(&lit.0) {
+                Ok(s) => {
+                    out.push_str(s);
+                    true
+                }
+                Err(_) => false,
+            },
+            HirKind::Class(class) => match self.pick_char(class) {
+                Some(c) => {
+                    out.push(c);
+                    true
+                }
+                None => false,
+            },
+            HirKind::Repetition(rep) => {
+                let extra = rep.max.map_or(MAX_EXTRA_REPEATS, |max| {
+                    max.saturating_sub(rep.min).min(MAX_EXTRA_REPEATS)
+                });
+                let times = rep.min + self.below(u64::from(extra) + 1) as u32;
+                (0..times).all(|_| self.write(&rep.sub, out))
+            }
+            HirKind::Capture(capture) => self.write(&capture.sub, out),
+            HirKind::Concat(parts) => parts.iter().all(|part| self.write(part, out)),
+            HirKind::Alternation(alts) => {
+                let alt = &alts[self.below(alts.len() as u64) as usize];
+                self.write(alt, out)
+            }
+        }
+    }
+
+    /// A character from `class`, preferring visible ASCII, then a space,
+    /// then anything.
+    fn pick_char(&mut self, class: &Class) -> Option<char> {
+        let ranges: Vec<(u32, u32)> = match class {
+            Class::Unicode(class) => class
+                .ranges()
+                .iter()
+                .map(|r| (r.start() as u32, r.end() as u32))
+                .collect(),
+            Class::Bytes(class) => class
+                .ranges()
+                .iter()
+                .filter(|r| r.start() < 0x80)
+                .map(|r| (u32::from(r.start()), u32::from(r.end()).min(0x7F)))
+                .collect(),
+        };
+        for (lo, hi) in [(0x21, 0x7E), (0x20, 0x20), (0, u32::MAX)] {
+            let clipped: Vec<(u32, u32)> = ranges
+                .iter()
+                .filter_map(|&(start, end)| {
+                    let (start, end) = (start.max(lo), end.min(hi));
+                    (start <= end).then_some((start, end))
+                })
+                .collect();
+            let total: u64 = clipped
+                .iter()
+                .map(|&(start, end)| u64::from(end - start) + 1)
+                .sum();
+            if total == 0 {
+                continue;
+            }
+            let mut index = self.below(total);
+            for (start, end) in clipped {
+                let len = u64::from(end - start) + 1;
+                if index < len {
+                    return char::from_u32(start + index as u32);
+                }
+                index -= len;
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_samples_are_distinct_and_deterministic() {
+        let a = samples("[a-z]+", 8);
+        assert_eq!(a, samples("[a-z]+", 8));
+        assert_eq!(a.len(), 8);
+        assert!(a.iter().all(|s| s.chars().all(|c| c.is_ascii_lowercase())));
+    }
+
+    #[test]
+    fn test_structure() {
+        for s in samples(r"-?[0-9]+(\.[0-9]+)?", 8) {
+            let digits = s.trim_start_matches('-');
+            assert!(
+                digits
+                    .split('.')
+                    .all(|d| !d.is_empty() && d.bytes().all(|b| b.is_ascii_digit())),
+                "{s}"
+            );
+        }
+        assert_eq!(samples("let|fn", 8).len(), 2);
+        assert_eq!(
+            samples(r#""[^"]*""#, 1)[0]
+                .chars()
+                .filter(|&c| c == '"')
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_unsupported() {
+        assert!(samples("(?&ident)", 4).is_empty());
+        assert!(samples("^$", 4).is_empty());
+    }
+}
@@ -0,0 +1,123 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Fields, parse_quote};
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(synkit::SpanInsensitive));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let (eq_body, hash_body) = match &input.data {
+        Data::Struct(data) => {
+            let (bindings_a, pattern_a) = bind_fields(&data.fields, "a");
+            let (bindings_b, pattern_b) = bind_fields(&data.fields, "b");
+            let eq = eq_expr(&bindings_a, &bindings_b);
+            let hash = hash_stmts(&bindings_a);
+            (
+                quote! {
+                    let Self #pattern_a = self;
+                    let Self #pattern_b = other;
+                    #eq
+                },
+                quote! {
+                    let Self #pattern_a = self;
+                    #(#hash)*
+                },
+            )
+        }
+        Data::Enum(data) if data.variants.is_empty() => {
+            (quote! { match *self {} }, quote! { match *self {} })
+        }
+        Data::Enum(data) => {
+            let mut eq_arms = Vec::new();
+            let mut hash_arms = Vec::new();
+            for variant in &data.variants {
+                let vname = &variant.ident;
+                let (bindings_a, pattern_a) = bind_fields(&variant.fields, "a");
+                let (bindings_b, pattern_b) = bind_fields(&variant.fields, "b");
+                let eq = eq_expr(&bindings_a, &bindings_b);
+                let hash = hash_stmts(&bindings_a);
+                eq_arms.push(quote! {
+                    (Self::#vname #pattern_a, Self::#vname #pattern_b) => { #eq }
+                });
+                hash_arms.push(quote! {
+                    Self::#vname #pattern_a => { #(#hash)* }
+                });
+            }
+            let fallback = if data.variants.len() > 1 {
+                quote! { _ => false, }
+            } else {
+                quote! {}
+            };
+            (
+                quote! {
+                    match (self, other) {
+                        #(#eq_arms)*
+                        #fallback
+                    }
+                },
+                quote! {
+                    std::hash::Hash::hash(&std::mem::discriminant(self), state);
+                    match self {
+                        #(#hash_arms)*
+                    }
+                },
+            )
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "SpanInsensitive cannot be derived for unions",
+            ));
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics synkit::SpanInsensitive for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn eq_ignore_spans(&self, other: &Self) -> bool {
+                #eq_body
+            }
+
+            #[allow(unused_variables)]
+            fn hash_ignore_spans<__H: std::hash::Hasher>(&self, state: &mut __H) {
+                #hash_body
+            }
+        }
+    })
+}
+
+/// Bind each field to `<prefix>_<n>`, returning the bindings and the
+/// destructuring pattern (`{ x: a_0, .. }`, `(a_0, ..)` or nothing).
+fn bind_fields(fields: &Fields, prefix: &str) -> (Vec<syn::Ident>, TokenStream) {
+    let bindings: Vec<_> = (0..fields.len())
+        .map(|i| format_ident!("{}_{}", prefix, i))
+        .collect();
+    let pattern = match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|f| &f.ident);
+            quote! { { #(#names: #bindings),* } }
+        }
+        Fields::Unnamed(_) => quote! { ( #(#bindings),* ) },
+        Fields::Unit => quote! {},
+    };
+    (bindings, pattern)
+}
+
+fn eq_expr(a: &[syn::Ident], b: &[syn::Ident]) -> TokenStream {
+    if a.is_empty() {
+        return quote! { true };
+    }
+    quote! { #(synkit::SpanInsensitive::eq_ignore_spans(#a, #b))&&* }
+}
+
+fn hash_stmts(bindings: &[syn::Ident]) -> Vec<TokenStream> {
+    bindings
+        .iter()
+        .map(|b| quote! { synkit::SpanInsensitive::hash_ignore_spans(#b, state); })
+        .collect()
+}
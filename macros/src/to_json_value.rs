@@ -0,0 +1,101 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{Data, DeriveInput, Fields, parse_quote};
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(synkit::ToJsonValue));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let (pattern, value) = fields_json(&data.fields);
+            let value = value.unwrap_or_else(|| quote! { synkit::serde_json::Value::Null });
+            quote! {
+                let Self #pattern = self;
+                #value
+            }
+        }
+        Data::Enum(data) if data.variants.is_empty() => quote! { match *self {} },
+        Data::Enum(data) => {
+            let arms = data.variants.iter().map(|variant| {
+                let vname = &variant.ident;
+                let tag = vname.unraw().to_string();
+                let (pattern, value) = fields_json(&variant.fields);
+                let value = match value {
+                    Some(value) => quote! {
+                        synkit::serde_json::Value::Object(
+                            synkit::serde_json::Map::from_iter([(#tag.to_string(), #value)]),
+                        )
+                    },
+                    None => quote! { synkit::serde_json::Value::String(#tag.to_string()) },
+                };
+                quote! { Self::#vname #pattern => #value, }
+            });
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "ToJsonValue cannot be derived for unions",
+            ));
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics synkit::ToJsonValue for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn to_json(&self, spans: bool) -> synkit::serde_json::Value {
+                #body
+            }
+        }
+    })
+}
+
+/// The destructuring pattern for `fields` and the JSON it becomes: an
+/// object for named fields, the field itself for one unnamed field, an
+/// array for several, and `None` for a unit.
+fn fields_json(fields: &Fields) -> (TokenStream, Option<TokenStream>) {
+    let bindings: Vec<_> = (0..fields.len())
+        .map(|i| format_ident!("field_{}", i))
+        .collect();
+    match fields {
+        Fields::Named(named) => {
+            let names: Vec<_> = named.named.iter().map(|f| &f.ident).collect();
+            let keys = names
+                .iter()
+                .filter_map(|name| name.as_ref())
+                .map(|name| name.unraw().to_string());
+            (
+                quote! { { #(#names: #bindings),* } },
+                Some(quote! {{
+                    let mut object = synkit::serde_json::Map::new();
+                    #(object.insert(#keys.to_string(), synkit::ToJsonValue::to_json(#bindings, spans));)*
+                    synkit::serde_json::Value::Object(object)
+                }}),
+            )
+        }
+        Fields::Unnamed(_) if bindings.len() == 1 => (
+            quote! { ( #(#bindings),* ) },
+            Some(quote! { #(synkit::ToJsonValue::to_json(#bindings, spans))* }),
+        ),
+        Fields::Unnamed(_) => (
+            quote! { ( #(#bindings),* ) },
+            Some(quote! {
+                synkit::serde_json::Value::Array(vec![
+                    #(synkit::ToJsonValue::to_json(#bindings, spans)),*
+                ])
+            }),
+        ),
+        Fields::Unit => (quote! {}, None),
+    }
+}
@@ -0,0 +1,332 @@
+//! Expansion-time warnings for literal tokens that a regex token always
+//! beats, like `#[token("true")]` next to a bare-key regex with a raised
+//! priority.
+//!
+//! Logos prefers the longest match and breaks ties by priority: an explicit
+//! `priority = N`, else a score from the pattern (2 per literal character
+//! or class, repetitions counted at their minimum). A literal whose whole
+//! text a higher-priority regex matches can never be lexed.
+
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote_spanned};
+use regex_syntax::hir::{Class, Hir, HirKind};
+use syn::parse::ParseStream;
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Expr, ExprLit, Lit, LitStr, Token};
+
+use crate::declare_tokens::{TokenDef, is_ident};
+
+/// A `#[token]` or `#[regex]` attribute's pattern and options.
+struct PatternAttr {
+    pattern: LitStr,
+    priority: Option<usize>,
+    ignore_case: bool,
+}
+
+impl PatternAttr {
+    fn parse(attr: &Attribute) -> Option<Self> {
+        attr.parse_args_with(|input: ParseStream| {
+            let pattern: LitStr = input.parse()?;
+            let mut priority = None;
+            let mut ignore_case = false;
+            if input.parse::<Option<Token![,]>>()?.is_some() {
+                for option in Punctuated::<Expr, Token![,]>::parse_terminated(input)? {
+                    match option {
+                        Expr::Assign(assign) if is_ident(&assign.left, "priority") => {
+                            if let Expr::Lit(ExprLit {
+                                lit: Lit::Int(int), ..
+                            }) = &*assign.right
+                            {
+                                priority = int.base10_parse().ok();
+                            }
+                        }
+                        Expr::Call(call) if is_ident(&call.func, "ignore") => {
+                            ignore_case = true;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Ok(Self {
+                pattern,
+                priority,
+                ignore_case,
+            })
+        })
+        .ok()
+    }
+}
+
+/// A regex token pattern, parsed.
+struct RegexPattern<'a> {
+    token: &'a TokenDef,
+    hir: Hir,
+    priority: usize,
+}
+
+/// A deprecation warning, spanned to the literal, for every `#[token]`
+/// literal that a higher-priority `#[regex]` of another token matches in
+/// full. Patterns the analysis can't parse, such as logos subpatterns, are
+/// skipped.
+pub fn shadowed_literal_warnings(tokens: &[TokenDef]) -> TokenStream {
+    let regexes: Vec<RegexPattern> = tokens
+        .iter()
+        .flat_map(|token| {
+            token
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("regex"))
+                .filter_map(PatternAttr::parse)
+                .filter_map(move |attr| {
+                    let hir = regex_syntax::ParserBuilder::new()
+                        .case_insensitive(attr.ignore_case)
+                        .build()
+                        .parse(&attr.pattern.value())
+                        .ok()?;
+                    let priority = attr.priority.unwrap_or_else(|| complexity(&hir));
+                    Some(RegexPattern {
+                        token,
+                        hir,
+                        priority,
+                    })
+                })
+        })
+        .collect();
+
+    let mut warnings = TokenStream::new();
+    let literals = tokens.iter().flat_map(|token| {
+        token
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("token"))
+            .filter_map(PatternAttr::parse)
+            .map(move |attr| (token, attr))
+    });
+    for (index, (token, attr)) in literals.enumerate() {
+        let text = attr.pattern.value();
+        let priority = attr.priority.unwrap_or_else(|| 2 * text.chars().count());
+        let Some(regex) = regexes.iter().find(|regex| {
+            regex.token.name != token.name
+                && regex.priority > priority
+                && matches_whole(&regex.hir, &text)
+        }) else {
+            continue;
+        };
+        let note = format!(
+            "`{}` ({:?}, priority {}) always lexes as `{}`, whose regex matches it with priority {}; \
+             give `{}` a priority above {} or lower `{}`'s",
+            token.name,
+            text,
+            priority,
+            regex.token.name,
+            regex.priority,
+            token.name,
+            regex.priority,
+            regex.token.name,
+        );
+        warnings.extend(warning(
+            format_ident!("shadowed_token_{}", index, span = attr.pattern.span()),
+            &note,
+            token.cfg_attrs.iter().chain(&regex.token.cfg_attrs),
+        ));
+    }
+    warnings
+}
+
+/// Stable proc macros can't emit warnings, so use a deprecated constant:
+/// rustc reports `note` at `name`'s span.
+pub(crate) fn warning<'a>(
+    name: Ident,
+    note: &str,
+    cfg_attrs: impl Iterator<Item = &'a Attribute>,
+) -> TokenStream {
+    let span = name.span();
+    quote_spanned! {span=>
+        #(#cfg_attrs)*
+        const _: () = {
+            #[deprecated(note = #note)]
+            #[allow(non_upper_case_globals)]
+            const #name: () = ();
+            #name
+        };
+    }
+}
+
+/// Logos' default priority for a pattern.
+fn complexity(hir: &Hir) -> usize {
+    match hir.kind() {
+        HirKind::Empty | HirKind::Look(_) => 0,
+        HirKind::Literal(lit) => match std::str::from_utf8(&lit.0) {
+            Ok(s) => 2 * s.chars().count(),
+            Err(_) => 2 * lit.0.len(),
+        },
+        HirKind::Class(_) => 2,
+        HirKind::Repetition(rep) => rep.min as usize * complexity(&rep.sub),
+        HirKind::Capture(capture) => complexity(&capture.sub),
+        HirKind::Concat(parts) => parts.iter().map(complexity).sum(),
+        HirKind::Alternation(alts) => alts.iter().map(complexity).min().unwrap_or(0),
+    }
+}
+
+/// Whether `hir` matches all of `text`. Look-arounds are assumed to hold.
+fn matches_whole(hir: &Hir, text: &str) -> bool {
+    ends(hir, text.as_bytes(), 0).contains(&text.len())
+}
+
+/// Every offset a match of `hir` starting at `start` can end at.
+fn ends(hir: &Hir, text: &[u8], start: usize) -> Vec<usize> {
+    match hir.kind() {
+        HirKind::Empty | HirKind::Look(_) => vec![start],
+        HirKind::Literal(lit) => {
+            let end = start + lit.0.len();
+            match text.get(start..end) {
+                Some(bytes) if bytes == &*lit.0 => vec![end],
+                _ => Vec::new(),
+            }
+        }
+        HirKind::Class(Class::Unicode(class)) => {
+            let rest = text.get(start..).unwrap_or_default();
+            let c = std::str::from_utf8(rest)
+                .ok()
+                .and_then(|rest| rest.chars().next());
+            match c {
+                Some(c)
+                    if class
+                        .ranges()
+                        .iter()
+                        .any(|r| r.start() <= c && c <= r.end()) =>
+                {
+                    vec![start + c.len_utf8()]
+                }
+                _ => Vec::new(),
+            }
+        }
+        HirKind::Class(Class::Bytes(class)) => match text.get(start) {
+            Some(&b)
+                if class
+                    .ranges()
+                    .iter()
+                    .any(|r| r.start() <= b && b <= r.end()) =>
+            {
+                vec![start + 1]
+            }
+            _ => Vec::new(),
+        },
+        HirKind::Repetition(rep) => {
+            let mut found = Vec::new();
+            let mut frontier = vec![start];
+            let mut count = 0u32;
+            // Each round consumes at least a byte or stops growing
+            while !frontier.is_empty() && count <= text.len() as u32 + rep.min {
+                if count >= rep.min {
+                    merge(&mut found, &frontier);
+                }
+                if rep.max.is_some_and(|max| count >= max) {
+                    break;
+                }
+                let mut next = Vec::new();
+                for &pos in &frontier {
+                    merge(&mut next, &ends(&rep.sub, text, pos));
+                }
+                next.retain(|pos| count < rep.min || !found.contains(pos));
+                frontier = next;
+                count += 1;
+            }
+            found
+        }
+        HirKind::Capture(capture) => ends(&capture.sub, text, start),
+        HirKind::Concat(parts) => parts.iter().fold(vec![start], |positions, part| {
+            let mut next = Vec::new();
+            for pos in positions {
+                merge(&mut next, &ends(part, text, pos));
+            }
+            next
+        }),
+        HirKind::Alternation(alts) => {
+            let mut found = Vec::new();
+            for alt in alts {
+                merge(&mut found, &ends(alt, text, start));
+            }
+            found
+        }
+    }
+}
+
+/// Add the offsets of `new` missing from `into`.
+fn merge(into: &mut Vec<usize>, new: &[usize]) {
+    for &pos in new {
+        if !into.contains(&pos) {
+            into.push(pos);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hir(pattern: &str) -> Hir {
+        regex_syntax::parse(pattern).unwrap()
+    }
+
+    #[test]
+    fn test_matches_whole() {
+        assert!(matches_whole(&hir("[a-z]+"), "true"));
+        assert!(!matches_whole(&hir("[a-z]"), "true"));
+        assert!(matches_whole(&hir("[a-z]{2,4}"), "true"));
+        assert!(!matches_whole(&hir("[a-z]{5,}"), "true"));
+        assert!(matches_whole(&hir("(a|ab)c"), "abc"));
+        assert!(matches_whole(&hir("x*y?"), ""));
+        assert!(matches_whole(&hir("é+"), "éé"));
+        assert!(!matches_whole(&hir("[0-9]+"), "1a"));
+    }
+
+    #[test]
+    fn test_complexity_matches_logos() {
+        assert_eq!(complexity(&hir("[a-z]+")), 2);
+        assert_eq!(complexity(&hir("true")), 8);
+        assert_eq!(complexity(&hir("[a-z][a-z0-9]*")), 2);
+        assert_eq!(complexity(&hir("a|bc")), 2);
+        assert_eq!(complexity(&hir("x{3}")), 6);
+    }
+
+    fn tokens(src: &str) -> Vec<TokenDef> {
+        syn::parse::Parser::parse_str(Punctuated::<TokenDef, Token![,]>::parse_terminated, src)
+            .unwrap()
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_warns_on_shadowed_literal() {
+        let output = shadowed_literal_warnings(&tokens(
+            r#"#[token("true")] True, #[regex("[a-z]+", priority = 10)] Key(String)"#,
+        ))
+        .to_string();
+        assert!(output.contains("deprecated"));
+        assert!(output.contains("`True` (\\\"true\\\", priority 8) always lexes as `Key`"));
+    }
+
+    #[test]
+    fn test_default_priorities_dont_warn() {
+        let quiet = [
+            r#"#[token("true")] True, #[regex("[a-z]+")] Key(String)"#,
+            // Same priority: logos reports the ambiguity itself
+            r#"#[token("ab")] Ab, #[regex("[a-z]+", priority = 4)] Key"#,
+            r#"#[token("true", priority = 20)] True, #[regex("[a-z]+", priority = 10)] Key"#,
+            r#"#[token("1")] One, #[regex("[a-z]+", priority = 10)] Key"#,
+            r#"#[regex("(?&name)", priority = 10)] Key, #[token("a")] A"#,
+        ];
+        for src in quiet {
+            assert!(shadowed_literal_warnings(&tokens(src)).is_empty(), "{src}");
+        }
+    }
+
+    #[test]
+    fn test_ignore_case_regex() {
+        let output = shadowed_literal_warnings(&tokens(
+            r#"#[token("TRUE")] True, #[regex("[a-z]+", ignore(case), priority = 9)] Key"#,
+        ));
+        assert!(!output.is_empty());
+    }
+}
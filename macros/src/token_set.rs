@@ -0,0 +1,163 @@
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use quote::quote;
+use syn::{
+    Attribute, Ident, Path, Token, braced,
+    parse::{Parse, ParseStream, Parser},
+    punctuated::Punctuated,
+};
+
+use crate::declare_tokens::TokenDef;
+
+/// Marker passed to token set macros so they forward to `parser_kit!`.
+const CALLBACK: &str = "__synkit_parser_kit";
+
+pub struct TokenSetInput {
+    pub attrs: Vec<Attribute>,
+    pub name: Ident,
+    pub tokens: TokenStream,
+}
+
+impl Parse for TokenSetInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let name: Ident = input.parse()?;
+        let content;
+        braced!(content in input);
+        let tokens: TokenStream = content.parse()?;
+
+        // Parse eagerly so mistakes are reported at the definition site
+        // rather than inside every grammar that includes the set.
+        let check = |stream: ParseStream| {
+            while !stream.is_empty() {
+                stream.parse::<TokenDef>()?;
+                if stream.peek(Token![,]) {
+                    stream.parse::<Token![,]>()?;
+                }
+            }
+            Ok(())
+        };
+        check.parse2(tokens.clone())?;
+
+        Ok(Self {
+            attrs,
+            name,
+            tokens,
+        })
+    }
+}
+
+/// Generate a `macro_rules!` that splices the token definitions into a
+/// `parser_kit!` invocation via `include_tokens`.
+pub fn expand(input: TokenSetInput) -> syn::Result<TokenStream> {
+    let TokenSetInput {
+        attrs,
+        name,
+        tokens,
+    } = input;
+    let callback = Ident::new(CALLBACK, proc_macro2::Span::call_site());
+
+    Ok(quote! {
+        #(#attrs)*
+        macro_rules! #name {
+            (@#callback { $($rest:tt)* }) => {
+                synkit::parser_kit! {
+                    __included_tokens: { #tokens },
+                    $($rest)*
+                }
+            };
+        }
+    })
+}
+
+/// Resolve `extends: path::to::base_kit` and `include_tokens: [a, b, ..]`
+/// in a `parser_kit!` input by rewriting it as a call to the base kit or
+/// first token set macro:
+///
+/// `a! { @__synkit_parser_kit { include_tokens: [b, ..], <rest> } }`
+///
+/// Each such macro prepends its definitions and re-invokes `parser_kit!`,
+/// so imports are resolved one at a time (`extends` first). Returns `None`
+/// when there is nothing left to resolve.
+pub fn expand_includes(input: TokenStream) -> syn::Result<Option<TokenStream>> {
+    let trees: Vec<TokenTree> = input.into_iter().collect();
+    let mut rest = Vec::with_capacity(trees.len());
+    let mut includes: Option<Vec<Path>> = None;
+    let mut extends: Option<Path> = None;
+
+    let mut i = 0;
+    while i < trees.len() {
+        let field = match (&trees[i], trees.get(i + 1)) {
+            (TokenTree::Ident(id), Some(TokenTree::Punct(p)))
+                if p.as_char() == ':' && (id == "include_tokens" || id == "extends") =>
+            {
+                id.to_string()
+            }
+            _ => {
+                rest.push(trees[i].clone());
+                i += 1;
+                continue;
+            }
+        };
+        let field_span = trees[i].span();
+        i += 2;
+
+        if field == "extends" {
+            // Path tokens up to the next top-level `,`
+            let end = trees[i..]
+                .iter()
+                .position(|t| matches!(t, TokenTree::Punct(p) if p.as_char() == ','))
+                .map_or(trees.len(), |n| i + n);
+            let path_tokens: TokenStream = trees[i..end].iter().cloned().collect();
+            if extends.is_some() {
+                return Err(syn::Error::new(field_span, "duplicate `extends` field"));
+            }
+            extends = Some(syn::parse2(path_tokens)?);
+            i = end;
+        } else {
+            let group = match trees.get(i) {
+                Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Bracket => g,
+                _ => {
+                    return Err(syn::Error::new(
+                        field_span,
+                        "expected `include_tokens: [path::to::token_set, ..]`",
+                    ));
+                }
+            };
+            if includes.is_some() {
+                return Err(syn::Error::new(
+                    field_span,
+                    "duplicate `include_tokens` field",
+                ));
+            }
+            includes = Some(
+                Punctuated::<Path, Token![,]>::parse_terminated
+                    .parse2(group.stream())?
+                    .into_iter()
+                    .collect(),
+            );
+            i += 1;
+        }
+
+        if matches!(trees.get(i), Some(TokenTree::Punct(p)) if p.as_char() == ',') {
+            i += 1;
+        }
+    }
+
+    let mut includes = includes.unwrap_or_default();
+    let target = match extends {
+        Some(base) => base,
+        None if !includes.is_empty() => includes.remove(0),
+        None => return Ok(None),
+    };
+    let callback = Ident::new(CALLBACK, proc_macro2::Span::call_site());
+    let rest: TokenStream = rest.into_iter().collect();
+
+    Ok(Some(quote! {
+        #target! {
+            @#callback {
+                include_tokens: [#(#includes),*],
+                #rest
+            }
+        }
+    }))
+}